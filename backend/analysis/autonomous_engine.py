@@ -22,6 +22,8 @@ from datetime import datetime
 from typing import Any
 from uuid import uuid4
 
+import packs  # type: ignore[import-not-found]
+import review  # type: ignore[import-not-found]
 from database import async_session_factory  # type: ignore[import-not-found]
 from models.deep_insight import DeepInsight, InsightType, InsightAction  # type: ignore[import-not-found]
 
@@ -332,6 +334,7 @@ class AutonomousDeepEngine:
     async def _get_portfolio_holdings(self) -> dict[str, dict[str, float]]:
         """Fetch portfolio holdings from the database.
 
+        Holdings of every account except paper ones are combined by symbol.
         Returns a dict mapping symbol to holding info, e.g.:
         {"AAPL": {"shares": 50, "cost_basis": 150.0, "total_cost": 7500.0}}
 
@@ -343,19 +346,26 @@ class AutonomousDeepEngine:
             from models.portfolio import Portfolio  # type: ignore[import-not-found]
 
             async with async_session_factory() as session:
-                result = await session.execute(select(Portfolio).limit(1))
-                portfolio = result.scalar_one_or_none()
+                result = await session.execute(
+                    select(Portfolio).where(Portfolio.account_type != "paper")
+                )
+                portfolios = result.scalars().all()
 
-                if not portfolio or not portfolio.holdings:
+                holdings: dict[str, dict[str, float]] = {}
+                for portfolio in portfolios:
+                    for h in portfolio.holdings:
+                        held = holdings.setdefault(
+                            h.symbol.upper(), {"shares": 0.0, "total_cost": 0.0}
+                        )
+                        held["shares"] += h.shares
+                        held["total_cost"] += h.shares * h.cost_basis
+                if not holdings:
                     return {}
 
-                holdings: dict[str, dict[str, float]] = {}
-                for h in portfolio.holdings:
-                    holdings[h.symbol.upper()] = {
-                        "shares": h.shares,
-                        "cost_basis": h.cost_basis,
-                        "total_cost": h.shares * h.cost_basis,
-                    }
+                for held in holdings.values():
+                    held["cost_basis"] = (
+                        held["total_cost"] / held["shares"] if held["shares"] else 0.0
+                    )
 
                 logger.info(
                     f"Loaded {len(holdings)} portfolio holdings: "
@@ -976,7 +986,7 @@ class AutonomousDeepEngine:
 
         # Query LLM
         response = await self._query_llm(
-            COVERAGE_EVALUATOR_PROMPT,
+            packs.get_prompt("coverage_evaluator", COVERAGE_EVALUATOR_PROMPT),
             formatted_context,
             "coverage_evaluator",
         )
@@ -1400,6 +1410,11 @@ class AutonomousDeepEngine:
                             continue
                         if insight.action not in actionable_actions:
                             continue
+                        if not review.is_approved(insight.id):
+                            logger.info(
+                                f"[AUTO] {insight.primary_symbol} awaits review; not tracking it yet"
+                            )
+                            continue
                         predicted_direction = action_to_direction[insight.action]
                         await outcome_tracker.start_tracking(
                             insight_id=insight.id,
@@ -1785,7 +1800,7 @@ class AutonomousDeepEngine:
 
         # Query LLM
         response = await self._query_llm(
-            SECTOR_ROTATOR_PROMPT,
+            packs.get_prompt("sector_rotator", SECTOR_ROTATOR_PROMPT),
             formatted_context,
             "sector_rotator",
         )
@@ -1851,7 +1866,7 @@ class AutonomousDeepEngine:
 
         # Query LLM
         response = await self._query_llm(
-            OPPORTUNITY_HUNTER_PROMPT,
+            packs.get_prompt("opportunity_hunter", OPPORTUNITY_HUNTER_PROMPT),
             formatted_context,
             "opportunity_hunter",
         )
@@ -2048,7 +2063,7 @@ class AutonomousDeepEngine:
         Returns:
             Parsed analyst report.
         """
-        prompt = config["prompt"]
+        prompt = packs.get_prompt(analyst_name, config["prompt"])
         format_func = config["format_context"]
         parse_func = config["parse_response"]
 
@@ -2387,6 +2402,11 @@ class AutonomousDeepEngine:
                             continue
                         if insight.action not in actionable_actions:
                             continue
+                        if not review.is_approved(insight.id):
+                            logger.info(
+                                f"[AUTO] {insight.primary_symbol} awaits review; not tracking it yet"
+                            )
+                            continue
                         predicted_direction = action_to_direction[insight.action]
                         await outcome_tracker.start_tracking(
                             insight_id=insight.id,
@@ -19,6 +19,7 @@ from typing import Any
 from sqlalchemy import and_, select
 from sqlalchemy.ext.asyncio import AsyncSession
 
+import packs
 from models.deep_insight import DeepInsight
 from models.insight_outcome import InsightOutcome, TrackingStatus
 from models.knowledge_pattern import KnowledgePattern
@@ -68,6 +69,17 @@ class ConfidenceAdjuster:
     MIN_CONFIDENCE = 0.1  # Never allow confidence below 10%
     MAX_CONFIDENCE = 0.95  # Never allow confidence above 95%
 
+    # Constants a strategy pack can override in its "confidence" section, by
+    # their lowercase names. Each must be a number between 0 and 1.
+    STRATEGY_SETTINGS = (
+        "BASE_WEIGHT",
+        "HISTORICAL_WEIGHT",
+        "PATTERN_SUCCESS_THRESHOLD",
+        "MAX_PATTERN_BOOST",
+        "MIN_CONFIDENCE",
+        "MAX_CONFIDENCE",
+    )
+
     def __init__(
         self,
         db_session: AsyncSession,
@@ -81,6 +93,23 @@ class ConfidenceAdjuster:
         """
         self.db = db_session
         self.memory_service = memory_service
+        self._apply_strategy(packs.get_strategy("confidence"))
+
+    def _apply_strategy(self, settings: dict[str, Any]) -> None:
+        """Override the weights and bounds with those of a strategy pack."""
+        for name in self.STRATEGY_SETTINGS:
+            value = settings.get(name.lower())
+            if value is None:
+                continue
+            number = isinstance(value, (int, float)) and not isinstance(value, bool)
+            if not number or not 0 <= value <= 1:
+                logger.warning(f"Ignoring strategy setting confidence.{name.lower()}={value!r}")
+                continue
+            setattr(self, name, float(value))
+        if self.MIN_CONFIDENCE > self.MAX_CONFIDENCE:
+            logger.warning("Ignoring strategy confidence bounds: minimum above maximum")
+            self.MIN_CONFIDENCE = ConfidenceAdjuster.MIN_CONFIDENCE
+            self.MAX_CONFIDENCE = ConfidenceAdjuster.MAX_CONFIDENCE
 
     async def adjust_confidence(
         self,
@@ -16,6 +16,8 @@ import logging
 from datetime import datetime
 from typing import Any
 
+import packs
+import review
 from llm.client_pool import pool_query_llm
 
 from database import async_session_factory
@@ -342,7 +344,7 @@ class DeepAnalysisEngine:
             Exception: If analyst fails after all retries.
         """
         analyst_start = datetime.utcnow()
-        prompt = config["prompt"]
+        prompt = packs.get_prompt(analyst_name, config["prompt"])
         format_func = config["format_context"]
         parse_func = config["parse_response"]
         context_type = config["context_type"]
@@ -413,7 +415,9 @@ class DeepAnalysisEngine:
         logger.info(f"[DEEP] Synthesis context length: {len(synthesis_context)} chars")
 
         # Query LLM
-        response_text = await self._query_llm(SYNTHESIS_LEAD_PROMPT, synthesis_context, "synthesis")
+        response_text = await self._query_llm(
+            packs.get_prompt("synthesis", SYNTHESIS_LEAD_PROMPT), synthesis_context, "synthesis"
+        )
         logger.info(f"[DEEP] Synthesis response length: {len(response_text)} chars")
         logger.info(f"[DEEP] Synthesis response preview: {response_text[:500]}")
 
@@ -864,6 +868,11 @@ class DeepAnalysisEngine:
                 if predicted_direction == "neutral":
                     continue
 
+                # The shell starts tracking once the reviewer approves it
+                if not review.is_approved(insight.id):
+                    logger.info(f"Insight {insight.id} awaits review; not tracking it yet")
+                    continue
+
                 # Start tracking
                 await outcome_tracker.start_tracking(
                     insight_id=insight.id,
@@ -42,7 +42,6 @@ class AnalysisEngine:
         from database import async_session_factory
         from models.stock import Stock
         from models.price import PriceHistory
-        from models.insight import Insight
         from sqlalchemy import select
 
         self._last_run = datetime.utcnow()
@@ -97,79 +96,15 @@ class AnalysisEngine:
 
                         results["symbols_analyzed"] += 1
 
-                        # 2. Run technical indicators
-                        indicator_results = await self.indicator_analyzer.analyze_stock(
-                            price_data
-                        )
-                        signals = await self.indicator_analyzer.get_signals(
-                            indicator_results
-                        )
-
-                        # Generate insight for strong signals
-                        if signals.get("confidence", 0) >= 0.7:
-                            insight = Insight(
-                                stock_id=stock.id,
-                                insight_type="technical",
-                                title=f"Strong {signals['overall_signal'].title()} Signal",
-                                description=(
-                                    f"{stock.symbol}: Technical indicators show "
-                                    f"{signals['overall_signal']} bias with "
-                                    f"{signals['confidence']:.0%} confidence. "
-                                    f"Bullish: {signals['bullish_count']}, "
-                                    f"Bearish: {signals['bearish_count']}"
-                                ),
-                                severity="info",
-                                confidence=signals.get("confidence", 0),
-                                data_json=json.dumps(signals),
-                                is_active=True,
-                            )
-                            db.add(insight)
-                            results["insights_generated"] += 1
-
-                        # 3. Detect patterns
-                        patterns = await self.pattern_detector.detect_all_patterns(
-                            stock.symbol, price_data
-                        )
-
-                        for pattern in patterns:
-                            results["patterns_detected"] += 1
-
-                            insight = Insight(
-                                stock_id=stock.id,
-                                insight_type="pattern",
-                                title=f"{pattern.pattern_type.value.replace('_', ' ').title()} Pattern",
-                                description=pattern.description,
-                                severity="warning" if pattern.confidence >= 0.8 else "info",
-                                confidence=pattern.confidence,
-                                data_json=json.dumps(pattern.to_dict()),
-                                is_active=True,
-                            )
-                            db.add(insight)
-                            results["insights_generated"] += 1
-
-                        # 4. Detect anomalies
-                        anomalies = await self.anomaly_detector.detect_all_anomalies(
-                            stock.symbol, price_data
+                        # 2-4. Technical signals, patterns and anomalies
+                        _, insights = await self.analyze_prices(
+                            stock.id, stock.symbol, price_data
                         )
-
-                        for anomaly in anomalies:
-                            results["anomalies_detected"] += 1
-
-                            insight = Insight(
-                                stock_id=stock.id,
-                                insight_type="anomaly",
-                                title=f"{anomaly.anomaly_type.value.replace('_', ' ').title()}",
-                                description=anomaly.description,
-                                severity=anomaly.severity,
-                                confidence=min(abs(anomaly.z_score) / 5, 1.0),
-                                data_json=json.dumps({
-                                    "anomaly_type": anomaly.anomaly_type.value,
-                                    "value": anomaly.value,
-                                    "z_score": anomaly.z_score,
-                                    "expected_range": anomaly.expected_range,
-                                }),
-                                is_active=True,
-                            )
+                        for insight in insights:
+                            if insight.insight_type == "pattern":
+                                results["patterns_detected"] += 1
+                            elif insight.insight_type == "anomaly":
+                                results["anomalies_detected"] += 1
                             db.add(insight)
                             results["insights_generated"] += 1
 
@@ -194,6 +129,86 @@ class AnalysisEngine:
         results["completed_at"] = datetime.utcnow().isoformat()
         return results
 
+    async def analyze_prices(
+        self,
+        stock_id: int | None,
+        symbol: str,
+        price_data: list[dict[str, Any]],
+    ) -> tuple[dict[str, Any], list[Any]]:
+        """
+        Run the technical indicators, pattern and anomaly detection on one stock.
+
+        Used by the full analysis and by the historical replay, so both raise
+        the same signals and alerts from the same bars.
+
+        Args:
+            stock_id: ID of the stock the insights are about.
+            symbol: Stock ticker symbol.
+            price_data: OHLCV dicts, oldest first.
+
+        Returns:
+            The aggregated indicator signals, and unsaved Insight rows for a
+            strong signal and each pattern and anomaly found.
+        """
+        from models.insight import Insight
+
+        insights: list[Insight] = []
+
+        indicator_results = await self.indicator_analyzer.analyze_stock(price_data)
+        signals = await self.indicator_analyzer.get_signals(indicator_results)
+
+        # Generate insight for strong signals
+        if signals.get("confidence", 0) >= 0.7:
+            insights.append(Insight(
+                stock_id=stock_id,
+                insight_type="technical",
+                title=f"Strong {signals['overall_signal'].title()} Signal",
+                description=(
+                    f"{symbol}: Technical indicators show "
+                    f"{signals['overall_signal']} bias with "
+                    f"{signals['confidence']:.0%} confidence. "
+                    f"Bullish: {signals['bullish_count']}, "
+                    f"Bearish: {signals['bearish_count']}"
+                ),
+                severity="info",
+                confidence=signals.get("confidence", 0),
+                data_json=json.dumps(signals),
+                is_active=True,
+            ))
+
+        patterns = await self.pattern_detector.detect_all_patterns(symbol, price_data)
+        for pattern in patterns:
+            insights.append(Insight(
+                stock_id=stock_id,
+                insight_type="pattern",
+                title=f"{pattern.pattern_type.value.replace('_', ' ').title()} Pattern",
+                description=pattern.description,
+                severity="warning" if pattern.confidence >= 0.8 else "info",
+                confidence=pattern.confidence,
+                data_json=json.dumps(pattern.to_dict()),
+                is_active=True,
+            ))
+
+        anomalies = await self.anomaly_detector.detect_all_anomalies(symbol, price_data)
+        for anomaly in anomalies:
+            insights.append(Insight(
+                stock_id=stock_id,
+                insight_type="anomaly",
+                title=f"{anomaly.anomaly_type.value.replace('_', ' ').title()}",
+                description=anomaly.description,
+                severity=anomaly.severity,
+                confidence=min(abs(anomaly.z_score) / 5, 1.0),
+                data_json=json.dumps({
+                    "anomaly_type": anomaly.anomaly_type.value,
+                    "value": anomaly.value,
+                    "z_score": anomaly.z_score,
+                    "expected_range": anomaly.expected_range,
+                }),
+                is_active=True,
+            ))
+
+        return signals, insights
+
     async def _run_sector_analysis(
         self,
         db: Any,
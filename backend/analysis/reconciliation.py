@@ -0,0 +1,279 @@
+"""End-of-day reconciliation of stored data against its sources.
+
+Runs nightly after the price refresh and analysis, and records a
+``ReconciliationReport`` so silent drift between what the app stored and what
+its sources say is caught within a day:
+
+- portfolio: holdings against the broker's positions. No broker integration
+  exists yet, so only the stored holdings themselves are checked.
+- prices: cached closes of the last sessions against the provider's closes.
+- alerts: insights the analysis raised, recomputed from the stored bars. An
+  alert the bars no longer produce was raised on data that has since changed.
+"""
+
+import logging
+from collections import defaultdict
+from datetime import date, datetime, time, timedelta
+from typing import Any
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from analysis.engine import analysis_engine
+from analysis.replay import LOOKBACK_BARS
+from data.adapters.yahoo import YahooFinanceAdapter, YahooFinanceError
+from models.insight import Insight
+from models.portfolio import PortfolioHolding
+from models.price import PriceHistory
+from models.reconciliation import ReconciliationReport
+from models.stock import Stock
+
+logger = logging.getLogger(__name__)
+
+# A cached close further than this from the provider's, in percent, is reported
+PRICE_TOLERANCE_PCT = 0.5
+
+# Calendar days of cached bars compared, enough to span a long weekend
+PRICE_WINDOW_DAYS = 7
+
+# Insight types raised from a stock's bars by AnalysisEngine.analyze_prices
+ALERT_TYPES = ("technical", "pattern", "anomaly")
+
+
+class EndOfDayReconciler:
+    """Compares stored portfolio, price and alert data with their sources."""
+
+    def __init__(self, db: AsyncSession) -> None:
+        """Initialize the reconciler.
+
+        Args:
+            db: Async database session for reading data and storing the report
+        """
+        self.db = db
+        self._yahoo_adapter = YahooFinanceAdapter()
+
+    async def run(self, as_of: date | None = None) -> ReconciliationReport:
+        """Run every check for ``as_of`` (today by default) and store the report.
+
+        Returns:
+            The stored report
+        """
+        as_of = as_of or date.today()
+        logger.info(f"Running end-of-day reconciliation for {as_of}")
+
+        sections = {
+            "portfolio": await self.check_portfolio(),
+            "prices": await self.check_prices(as_of),
+            "alerts": await self.check_alerts(as_of),
+        }
+        discrepancy_count = sum(len(s["discrepancies"]) for s in sections.values())
+
+        report = ReconciliationReport(
+            as_of=as_of,
+            discrepancy_count=discrepancy_count,
+            report=sections,
+        )
+        self.db.add(report)
+        await self.db.commit()
+        await self.db.refresh(report)
+
+        if discrepancy_count:
+            logger.warning(
+                f"Reconciliation for {as_of} found {discrepancy_count} discrepancies: "
+                + ", ".join(f"{name} {len(s['discrepancies'])}" for name, s in sections.items())
+            )
+        else:
+            logger.info(f"Reconciliation for {as_of} found no discrepancies")
+        return report
+
+    async def check_portfolio(self) -> dict[str, Any]:
+        """Check the stored holdings.
+
+        Without a broker integration there are no broker positions to compare
+        with, which the section says; holdings with impossible share counts or
+        cost bases are still reported.
+        """
+        result = await self.db.execute(select(PortfolioHolding))
+        holdings = result.scalars().all()
+
+        discrepancies: list[dict[str, Any]] = []
+        for holding in holdings:
+            problems = []
+            if holding.shares <= 0:
+                problems.append(f"{holding.shares} shares")
+            if holding.cost_basis < 0:
+                problems.append(f"cost basis {holding.cost_basis}")
+            if problems:
+                discrepancies.append({
+                    "kind": "invalid_holding",
+                    "account_id": holding.portfolio_id,
+                    "symbol": holding.symbol,
+                    "detail": ", ".join(problems),
+                })
+
+        return {
+            "broker": "not_configured",
+            "note": "No broker integration is configured, so holdings were not "
+                    "compared with broker positions.",
+            "holdings_checked": len(holdings),
+            "discrepancies": discrepancies,
+        }
+
+    async def check_prices(self, as_of: date) -> dict[str, Any]:
+        """Compare the cached bars of the week up to ``as_of`` with the provider's.
+
+        Reports closes that differ by more than ``PRICE_TOLERANCE_PCT``,
+        provider bars missing from the cache, and cached bars the provider
+        does not have.
+        """
+        start = as_of - timedelta(days=PRICE_WINDOW_DAYS)
+        stocks = (
+            await self.db.execute(select(Stock).where(Stock.is_active == True))  # noqa: E712
+        ).scalars().all()
+
+        discrepancies: list[dict[str, Any]] = []
+        errors: list[dict[str, str]] = []
+        symbols_checked = 0
+        bars_checked = 0
+
+        for stock in stocks:
+            cached_query = select(PriceHistory).where(
+                PriceHistory.stock_id == stock.id,
+                PriceHistory.date >= start,
+                PriceHistory.date <= as_of,
+            )
+            cached = {p.date: p.close for p in (await self.db.execute(cached_query)).scalars()}
+
+            try:
+                # The provider's end date is exclusive
+                bars = await self._yahoo_adapter.get_price_history(
+                    stock.symbol, start_date=start, end_date=as_of + timedelta(days=1)
+                )
+            except YahooFinanceError as e:
+                errors.append({"symbol": stock.symbol, "error": str(e)})
+                continue
+            provider = {
+                bar["date"]: bar["close"]
+                for bar in bars
+                if bar["close"] is not None and start <= bar["date"] <= as_of
+            }
+            if not cached and not provider:
+                continue
+            symbols_checked += 1
+
+            for day, close in sorted(provider.items()):
+                if day not in cached:
+                    discrepancies.append({
+                        "kind": "missing_bar",
+                        "symbol": stock.symbol,
+                        "date": day.isoformat(),
+                        "provider_close": close,
+                    })
+                    continue
+                bars_checked += 1
+                diff_pct = abs(cached[day] - close) / close * 100 if close else 0.0
+                if diff_pct > PRICE_TOLERANCE_PCT:
+                    discrepancies.append({
+                        "kind": "close_mismatch",
+                        "symbol": stock.symbol,
+                        "date": day.isoformat(),
+                        "cached_close": cached[day],
+                        "provider_close": close,
+                        "diff_pct": round(diff_pct, 3),
+                    })
+            for day in sorted(cached.keys() - provider.keys()):
+                discrepancies.append({
+                    "kind": "unconfirmed_bar",
+                    "symbol": stock.symbol,
+                    "date": day.isoformat(),
+                    "cached_close": cached[day],
+                })
+
+        return {
+            "start_date": start.isoformat(),
+            "end_date": as_of.isoformat(),
+            "tolerance_pct": PRICE_TOLERANCE_PCT,
+            "symbols_checked": symbols_checked,
+            "bars_checked": bars_checked,
+            "discrepancies": discrepancies,
+            "errors": errors,
+        }
+
+    async def check_alerts(self, as_of: date) -> dict[str, Any]:
+        """Recompute the insights raised on ``as_of`` or the day before.
+
+        The previous day is included as the evening analysis can finish after
+        midnight UTC. Each stock's analysis is rerun on its stored bars up to
+        the day the insight was raised; an insight the rerun does not raise
+        again, by type and title, is reported.
+        """
+        since = datetime.combine(as_of - timedelta(days=1), time.min)
+        until = datetime.combine(as_of + timedelta(days=1), time.min)
+        rows = (
+            await self.db.execute(
+                select(Insight, Stock.symbol)
+                .join(Stock, Insight.stock_id == Stock.id)
+                .where(
+                    Insight.insight_type.in_(ALERT_TYPES),
+                    Insight.created_at >= since,
+                    Insight.created_at < until,
+                )
+                .order_by(Insight.id)
+            )
+        ).all()
+
+        # One rerun per stock and day
+        raised: dict[tuple[int, str, date], list[Insight]] = defaultdict(list)
+        for insight, symbol in rows:
+            raised[(insight.stock_id, symbol, insight.created_at.date())].append(insight)
+
+        discrepancies: list[dict[str, Any]] = []
+        errors: list[dict[str, str]] = []
+        for (stock_id, symbol, day), insights in raised.items():
+            price_query = (
+                select(PriceHistory)
+                .where(PriceHistory.stock_id == stock_id, PriceHistory.date <= day)
+                .order_by(PriceHistory.date.desc())
+                .limit(LOOKBACK_BARS)
+            )
+            prices = list(reversed((await self.db.execute(price_query)).scalars().all()))
+            price_data = [
+                {
+                    "date": p.date,
+                    "open": p.open,
+                    "high": p.high,
+                    "low": p.low,
+                    "close": p.close,
+                    "volume": p.volume,
+                }
+                for p in prices
+            ]
+
+            reproduced: set[tuple[str, str]] = set()
+            if price_data:
+                try:
+                    _, rerun = await analysis_engine.analyze_prices(stock_id, symbol, price_data)
+                except Exception as e:
+                    logger.error(f"Failed to rerun the analysis of {symbol} for {day}: {e}")
+                    errors.append({"symbol": symbol, "date": day.isoformat(), "error": str(e)})
+                    continue
+                reproduced = {(i.insight_type, i.title) for i in rerun}
+
+            for insight in insights:
+                if (insight.insight_type, insight.title) in reproduced:
+                    continue
+                discrepancies.append({
+                    "kind": "alert_not_reproduced" if price_data else "alert_without_data",
+                    "insight_id": insight.id,
+                    "symbol": symbol,
+                    "insight_type": insight.insight_type,
+                    "title": insight.title,
+                    "severity": insight.severity,
+                    "raised_at": insight.created_at.isoformat(),
+                })
+
+        return {
+            "alerts_checked": len(rows),
+            "discrepancies": discrepancies,
+            "errors": errors,
+        }
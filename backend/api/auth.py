@@ -0,0 +1,79 @@
+"""Shared-secret check on every request when the desktop shell spawned the backend,
+and a second one on the privileged endpoints only the shell may call."""
+
+import hmac
+import logging
+import os
+
+from fastapi import HTTPException
+from starlette.datastructures import Headers, QueryParams
+from starlette.responses import JSONResponse
+from starlette.types import ASGIApp, Receive, Scope, Send
+
+logger = logging.getLogger(__name__)
+
+# Set by the desktop shell to a new random value for each launch of the app;
+# the check is off when the backend runs standalone.
+AUTH_TOKEN_ENV = "TELETRAAN_AUTH_TOKEN"
+
+AUTH_HEADER = "X-Teletraan-Token"
+
+# For WebSockets and links opened by the webview, which cannot set headers
+AUTH_QUERY_PARAM = "token"
+
+# Set by the desktop shell to a second random value for each launch. Unlike
+# the auth token it is never handed to the webview or put in a URL.
+SHELL_TOKEN_ENV = "TELETRAAN_SHELL_TOKEN"
+
+SHELL_HEADER = "X-Teletraan-Shell-Token"
+
+
+def require_shell_token(token: str | None) -> None:
+    """Only the desktop shell that spawned this backend may call a privileged
+    endpoint (shutdown, log level, maintenance, packs), with the token from
+    ``TELETRAAN_SHELL_TOKEN`` in the ``X-Teletraan-Shell-Token`` header, on top
+    of the auth token every request needs. The endpoints do not exist when the
+    backend runs without a shell token."""
+    expected = os.environ.get(SHELL_TOKEN_ENV)
+    if not expected:
+        raise HTTPException(status_code=404, detail="Not Found")
+    if not token or not hmac.compare_digest(token.encode(), expected.encode()):
+        raise HTTPException(status_code=403, detail="Invalid shell token")
+
+
+class ShellAuthMiddleware:
+    """Refuse requests without the token from ``TELETRAAN_AUTH_TOKEN``.
+
+    Keeps other local processes, and web pages that reach localhost, from
+    driving a backend the desktop shell started (and spending its LLM
+    credits). CORS preflights pass, since browsers send them without the
+    header; the request that follows still needs it.
+    """
+
+    def __init__(self, app: ASGIApp) -> None:
+        self.app = app
+
+    async def __call__(self, scope: Scope, receive: Receive, send: Send) -> None:
+        expected = os.environ.get(AUTH_TOKEN_ENV)
+        if (
+            not expected
+            or scope["type"] not in ("http", "websocket")
+            or (scope["type"] == "http" and scope["method"] == "OPTIONS")
+        ):
+            await self.app(scope, receive, send)
+            return
+
+        token = Headers(scope=scope).get(AUTH_HEADER) or QueryParams(
+            scope.get("query_string", b"")
+        ).get(AUTH_QUERY_PARAM)
+        if token and hmac.compare_digest(token.encode(), expected.encode()):
+            await self.app(scope, receive, send)
+            return
+
+        logger.warning(f"Refused {scope['type']} request to {scope['path']} without a valid token")
+        if scope["type"] == "websocket":
+            # Closing before the handshake is accepted answers it with 403.
+            await send({"type": "websocket.close", "code": 1008})
+            return
+        response = JSONResponse({"detail": "Invalid or missing token"}, status_code=401)
+        await response(scope, receive, send)
@@ -12,6 +12,8 @@ from api.routes.insight_conversations import router as insight_conversations_rou
 from api.routes.insight_modifications import router as insight_modifications_router
 from api.routes.insights import router as insights_router
 from api.routes.knowledge import router as knowledge_router
+from api.routes.log_level import router as log_level_router
+from api.routes.maintenance import router as maintenance_router
 from api.routes.outcomes import router as outcomes_router
 from api.routes.portfolio import router as portfolio_router
 from api.routes.reports import router as reports_router
@@ -19,6 +21,7 @@ from api.routes.research import router as research_router
 from api.routes.runs import router as runs_router
 from api.routes.search import router as search_router
 from api.routes.settings import router as settings_router
+from api.routes.shutdown import router as shutdown_router
 from api.routes.statistical_features import router as statistical_features_router
 from api.routes.stocks import router as stocks_router
 
@@ -36,6 +39,9 @@ router.include_router(insight_modifications_router, tags=["insight-modifications
 router.include_router(insights_router)
 router.include_router(search_router)
 router.include_router(settings_router, tags=["settings"])
+router.include_router(shutdown_router, tags=["health"])
+router.include_router(log_level_router, tags=["health"])
+router.include_router(maintenance_router, tags=["health"])
 router.include_router(statistical_features_router, tags=["features"])
 router.include_router(stocks_router, tags=["stocks"])
 router.include_router(outcomes_router, tags=["outcomes"])
@@ -1,9 +1,12 @@
 """Analysis API endpoints for technical indicators, patterns, and anomalies."""
 
-from datetime import datetime
+import json
+from collections.abc import AsyncIterator
+from datetime import datetime, timedelta
 from typing import Any
 
 from fastapi import APIRouter, BackgroundTasks, Depends, HTTPException, Query
+from fastapi.responses import StreamingResponse
 from sqlalchemy import func, select
 from sqlalchemy.ext.asyncio import AsyncSession
 
@@ -13,6 +16,7 @@ from analysis.patterns import pattern_detector
 from analysis.anomalies import anomaly_detector
 from analysis.sectors import sector_analyzer, SECTOR_ETFS
 from analysis.engine import analysis_engine
+from analysis.replay import LOOKBACK_BARS, ReplaySeries, replay
 from models.stock import Stock
 from models.price import PriceHistory
 from models.insight import Insight
@@ -25,6 +29,7 @@ from schemas.analysis import (
     IndicatorDetail,
     PatternDetail,
     PatternResponse,
+    ReplayRequest,
     SectorAnalysisResponse,
     TechnicalAnalysisResponse,
 )
@@ -395,6 +400,86 @@ async def trigger_analysis(
     )
 
 
+async def _stream_replay(
+    series: list[ReplaySeries],
+    request: ReplayRequest,
+    missing_symbols: list[str],
+) -> AsyncIterator[str]:
+    """The replay's events as NDJSON lines."""
+    async for event in replay(
+        series, request.start_date, request.end_date, request.interval_seconds
+    ):
+        if event["type"] == "summary":
+            event["missing_symbols"] = missing_symbols
+        yield json.dumps(event) + "\n"
+
+
+@router.post("/replay")
+async def replay_analysis(
+    request: ReplayRequest,
+    db: AsyncSession = Depends(get_db),
+) -> StreamingResponse:
+    """
+    Replay a past period through the analysis, one trading day at a time.
+
+    Each day only sees the bars up to it, so the stream shows the signals and
+    alerts the live analysis would have raised then. Events are sent as
+    NDJSON, a ``bar`` event per symbol and day and a ``summary`` at the end,
+    ``interval_seconds`` apart per day. Nothing is stored.
+    """
+    # Enough calendar days before the period for the analysis' lookback
+    warmup_start = request.start_date - timedelta(days=LOOKBACK_BARS * 7 // 5 + 14)
+    series: list[ReplaySeries] = []
+    missing_symbols: list[str] = []
+
+    # Loaded up front: the session is closed before the body is streamed
+    for symbol in request.symbols:
+        stock = (
+            await db.execute(select(Stock).where(Stock.symbol == symbol))
+        ).scalar_one_or_none()
+        prices = []
+        if stock:
+            price_query = (
+                select(PriceHistory)
+                .where(
+                    PriceHistory.stock_id == stock.id,
+                    PriceHistory.date >= warmup_start,
+                    PriceHistory.date <= request.end_date,
+                )
+                .order_by(PriceHistory.date.asc())
+            )
+            prices = (await db.execute(price_query)).scalars().all()
+        bars = [
+            {
+                "date": p.date,
+                "open": p.open,
+                "high": p.high,
+                "low": p.low,
+                "close": p.close,
+                "volume": p.volume,
+            }
+            for p in prices
+        ]
+        if not any(bar["date"] >= request.start_date for bar in bars):
+            missing_symbols.append(symbol)
+            continue
+        series.append(ReplaySeries(stock_id=stock.id, symbol=symbol, bars=bars))
+
+    if not series:
+        raise HTTPException(
+            status_code=404,
+            detail=(
+                f"No price data between {request.start_date} and {request.end_date} "
+                f"for {', '.join(request.symbols)}"
+            ),
+        )
+
+    return StreamingResponse(
+        _stream_replay(series, request, missing_symbols),
+        media_type="application/x-ndjson",
+    )
+
+
 @router.get("/summary", response_model=AnalysisSummaryResponse)
 async def get_analysis_summary(
     db: AsyncSession = Depends(get_db),
@@ -2,20 +2,23 @@
 
 import asyncio
 import logging
+from datetime import date, datetime
 from typing import Any
 
-from fastapi import APIRouter, Depends, HTTPException
-from pydantic import BaseModel, Field
+from fastapi import APIRouter, Depends, HTTPException, Query
+from pydantic import BaseModel, ConfigDict, Field
 from sqlalchemy import select
 from sqlalchemy.dialects.sqlite import insert as sqlite_insert
 from sqlalchemy.ext.asyncio import AsyncSession
 
 from analysis.engine import AnalysisEngine
 from analysis.deep_engine import deep_analysis_engine
+from analysis.reconciliation import EndOfDayReconciler
 from api.deps import get_db
 from data.adapters.yahoo import YahooFinanceAdapter, YahooFinanceError
 from models.stock import Stock
 from models.price import PriceHistory
+from models.reconciliation import ReconciliationReport
 
 logger = logging.getLogger(__name__)
 
@@ -55,6 +58,28 @@ class RefreshResponse(BaseModel):
     )
 
 
+class ReconciliationRequest(BaseModel):
+    """Request body for running the reconciliation on demand."""
+
+    as_of: date | None = Field(
+        default=None, description="Day to reconcile. Defaults to today."
+    )
+
+
+class ReconciliationReportResponse(BaseModel):
+    """A stored end-of-day reconciliation report."""
+
+    model_config = ConfigDict(from_attributes=True)
+
+    id: int
+    as_of: date
+    created_at: datetime
+    discrepancy_count: int = Field(description="Discrepancies found across all checks")
+    report: dict[str, Any] = Field(
+        description="Portfolio, prices and alerts sections, each with its discrepancies"
+    )
+
+
 async def refresh_single_symbol(
     adapter: YahooFinanceAdapter,
     symbol: str,
@@ -306,3 +331,34 @@ async def run_deep_analysis(
     except Exception as e:
         logger.error(f"Deep analysis failed: {e}")
         raise HTTPException(status_code=500, detail=str(e))
+
+
+@router.get("/reconciliation", response_model=ReconciliationReportResponse)
+async def get_reconciliation_report(
+    as_of: date | None = Query(None, description="Day of the report; the latest if omitted"),
+    db: AsyncSession = Depends(get_db),
+) -> ReconciliationReport:
+    """Get the latest end-of-day reconciliation report.
+
+    The report is produced nightly at 8 PM ET on weekdays, comparing
+    holdings, cached prices and the day's alerts with their sources.
+    """
+    query = select(ReconciliationReport)
+    if as_of is not None:
+        query = query.where(ReconciliationReport.as_of == as_of)
+    query = query.order_by(ReconciliationReport.id.desc()).limit(1)
+    report = (await db.execute(query)).scalar_one_or_none()
+
+    if not report:
+        raise HTTPException(status_code=404, detail="No reconciliation report found")
+    return report
+
+
+@router.post("/reconciliation", response_model=ReconciliationReportResponse)
+async def run_reconciliation(
+    request: ReconciliationRequest | None = None,
+    db: AsyncSession = Depends(get_db),
+) -> ReconciliationReport:
+    """Run the end-of-day reconciliation now and return its report."""
+    as_of = request.as_of if request else None
+    return await EndOfDayReconciler(db).run(as_of)
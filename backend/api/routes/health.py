@@ -1,29 +1,108 @@
 """Health check endpoint."""
 
+import hashlib
+import platform
+import time
 from datetime import datetime, timezone
 
 from fastapi import APIRouter
-from sqlalchemy import text
+from sqlalchemy import func, select, text
 
 from api.deps import DbSession
-from schemas.health import HealthResponse
+from config import get_settings
+from database import Base, sync_pending_schema_changes
+from llm.client_pool import get_client_pool
+from models.analysis_task import TERMINAL_STATUSES, AnalysisTask
+from schemas.health import DeepHealthResponse, HealthResponse, VersionResponse
 
 router = APIRouter()
 
+# Routes are imported once at startup, so this approximates the server start.
+_STARTED_AT = time.monotonic()
+
+VERSION = "1.0.0"
+
+
+def _schema_version() -> str:
+    """Fingerprint of the model schema; changes whenever a table or column does."""
+    columns = sorted(
+        f"{table_name}.{column.name}"
+        for table_name, table in Base.metadata.tables.items()
+        for column in table.columns
+    )
+    return hashlib.sha256("\n".join(columns).encode()).hexdigest()[:12]
+
 
 @router.get("/health", response_model=HealthResponse)
 async def health_check(db: DbSession) -> HealthResponse:
     """Return health status of the API including database connectivity."""
     # Check database connectivity
     db_status = "connected"
+    pending_migrations = 0
+    queue_depth = 0
     try:
         await db.execute(text("SELECT 1"))
+        connection = await db.connection()
+        missing_tables, missing_columns = await connection.run_sync(
+            sync_pending_schema_changes
+        )
+        pending_migrations = len(missing_tables) + len(missing_columns)
+        queue_depth = await db.scalar(
+            select(func.count())
+            .select_from(AnalysisTask)
+            .where(AnalysisTask.status.notin_([s.value for s in TERMINAL_STATUSES]))
+        ) or 0
     except Exception:
         db_status = "disconnected"
 
+    pool = get_client_pool().stats
     return HealthResponse(
         status="healthy",
-        version="1.0.0",
+        service="teletraan",
+        version=VERSION,
         database=db_status,
         timestamp=datetime.now(timezone.utc),
+        pending_migrations=pending_migrations,
+        llm_provider=get_settings().get_llm_provider(),
+        llm_clients_available=pool["available"],
+        llm_pool_size=pool["pool_size"],
+        queue_depth=queue_depth,
+        uptime_seconds=round(time.monotonic() - _STARTED_AT, 1),
+    )
+
+
+@router.get("/health/deep", response_model=DeepHealthResponse)
+async def deep_health_check(db: DbSession) -> DeepHealthResponse:
+    """Check that the database is reachable and its schema matches the models."""
+    missing_tables: list[str] = []
+    missing_columns: list[str] = []
+    try:
+        connection = await db.connection()
+        missing_tables, missing_columns = await connection.run_sync(
+            sync_pending_schema_changes
+        )
+        db_status = "connected"
+    except Exception:
+        db_status = "disconnected"
+
+    schema_up_to_date = db_status == "connected" and not (missing_tables or missing_columns)
+    return DeepHealthResponse(
+        status="healthy" if schema_up_to_date else "degraded",
+        database=db_status,
+        schema_up_to_date=schema_up_to_date,
+        missing_tables=missing_tables,
+        missing_columns=missing_columns,
+        timestamp=datetime.now(timezone.utc),
+    )
+
+
+@router.get("/version", response_model=VersionResponse)
+async def version() -> VersionResponse:
+    """Return the backend's version and runtime, for the desktop About screen."""
+    return VersionResponse(
+        service="teletraan",
+        version=VERSION,
+        python_version=platform.python_version(),
+        platform=platform.platform(),
+        schema_version=_schema_version(),
     )
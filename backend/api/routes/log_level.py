@@ -0,0 +1,38 @@
+"""Runtime log-level control used by the desktop shell."""
+
+import logging
+
+from fastapi import APIRouter, Header, HTTPException
+
+from api.auth import require_shell_token
+from schemas.health import LogLevelRequest, LogLevelResponse
+
+logger = logging.getLogger(__name__)
+
+router = APIRouter()
+
+LEVELS = ("DEBUG", "INFO", "WARNING", "ERROR", "CRITICAL")
+
+
+@router.post("/log-level", response_model=LogLevelResponse)
+async def set_log_level(
+    request: LogLevelRequest,
+    x_teletraan_shell_token: str | None = Header(default=None),
+) -> LogLevelResponse:
+    """Change a logger's level without restarting, e.g. to reproduce an issue at DEBUG.
+
+    Requires the shell-only token the desktop shell passed in ``TELETRAAN_SHELL_TOKEN``.
+    """
+    require_shell_token(x_teletraan_shell_token)
+
+    level = request.level.upper()
+    if level not in LEVELS:
+        raise HTTPException(status_code=422, detail=f"Unknown log level: {request.level}")
+
+    target = logging.getLogger(request.logger) if request.logger else logging.getLogger()
+    target.setLevel(level)
+    # basicConfig's handler filters too; let it pass whatever the loggers allow.
+    for handler in logging.getLogger().handlers:
+        handler.setLevel(logging.NOTSET)
+    logger.info(f"Log level of {request.logger or 'root'} set to {level} by desktop shell")
+    return LogLevelResponse(logger=request.logger or "root", level=level)
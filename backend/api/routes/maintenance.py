@@ -0,0 +1,401 @@
+"""SQLite maintenance (integrity check, VACUUM, ANALYZE, pruning, export, import) and pack reloads used by the desktop shell."""
+
+import asyncio
+import csv
+import io
+import logging
+import os
+import sqlite3
+import time
+from collections.abc import Iterator
+from datetime import date, timedelta
+from pathlib import Path
+
+from fastapi import APIRouter, Header, HTTPException
+from fastapi.responses import StreamingResponse
+from sqlalchemy import delete, func, select
+
+import database
+import db_encryption
+import packs
+from api.auth import require_shell_token
+from api.deps import DbSession
+from models.analysis_task import TERMINAL_STATUSES, AnalysisTask
+from models.deep_insight import DeepInsight
+from models.insight_outcome import InsightOutcome, TrackingStatus
+from models.price import PriceHistory
+from schemas.health import (
+    DatabaseImportRequest,
+    DatabaseImportResponse,
+    DatabaseMaintenanceRequest,
+    DatabaseMaintenanceResponse,
+    MarketDataPruneRequest,
+    MarketDataPruneResponse,
+    PacksReloadResponse,
+)
+
+logger = logging.getLogger(__name__)
+
+router = APIRouter()
+
+# integrity_check stops after this many problems
+MAX_INTEGRITY_ERRORS = 100
+
+# Tables the desktop shell can export, by the names it uses for them
+EXPORT_TABLES = {
+    "insights": DeepInsight.__tablename__,
+    "outcomes": InsightOutcome.__tablename__,
+    "candles": PriceHistory.__tablename__,
+}
+
+# Rows read and written per chunk of an export
+EXPORT_BATCH_ROWS = 5000
+
+# How long an import waits for the app's own writes to finish
+IMPORT_LOCK_TIMEOUT_SECONDS = 30
+
+
+def _database_size() -> int | None:
+    """Size of the database file and its write-ahead log, or None if in memory."""
+    path = database.engine.url.database
+    if not path or path == ":memory:":
+        return None
+    return sum(
+        os.path.getsize(p) for p in (path, f"{path}-wal") if os.path.exists(p)
+    )
+
+
+@router.post("/maintenance/database", response_model=DatabaseMaintenanceResponse)
+async def run_database_maintenance(
+    request: DatabaseMaintenanceRequest,
+    db: DbSession,
+    x_teletraan_shell_token: str | None = Header(default=None),
+) -> DatabaseMaintenanceResponse:
+    """Check the database for corruption, then compact it and refresh its statistics.
+
+    VACUUM and ANALYZE are skipped when the integrity check fails, since
+    rewriting a damaged file can lose more data. VACUUM is refused while an
+    analysis is running because it needs the database to itself.
+
+    Requires the shell-only token the desktop shell passed in ``TELETRAAN_SHELL_TOKEN``.
+    """
+    require_shell_token(x_teletraan_shell_token)
+
+    if request.vacuum:
+        active = await db.scalar(
+            select(func.count())
+            .select_from(AnalysisTask)
+            .where(AnalysisTask.status.notin_([s.value for s in TERMINAL_STATUSES]))
+        )
+        if active:
+            raise HTTPException(
+                status_code=409,
+                detail=f"{active} analysis task(s) running; try again when they finish",
+            )
+    # VACUUM cannot run inside a transaction, including this session's.
+    await db.rollback()
+
+    started = time.monotonic()
+    size_before = _database_size()
+    async with database.engine.connect() as conn:
+        conn = await conn.execution_options(isolation_level="AUTOCOMMIT")
+        result = await conn.exec_driver_sql(f"PRAGMA integrity_check({MAX_INTEGRITY_ERRORS})")
+        findings = [row[0] for row in result.fetchall()]
+        integrity_ok = findings == ["ok"]
+        vacuumed = analyzed = False
+        if integrity_ok:
+            if request.vacuum:
+                await conn.exec_driver_sql("VACUUM")
+                # Fold the rewritten pages back so the file size reflects them.
+                await conn.exec_driver_sql("PRAGMA wal_checkpoint(TRUNCATE)")
+                vacuumed = True
+            if request.analyze:
+                await conn.exec_driver_sql("ANALYZE")
+                analyzed = True
+        else:
+            logger.error(f"Database integrity check failed: {findings}")
+    size_after = _database_size()
+
+    reclaimed = None
+    if size_before is not None and size_after is not None:
+        reclaimed = max(size_before - size_after, 0)
+    logger.info(
+        f"Database maintenance: integrity_ok={integrity_ok} vacuumed={vacuumed} "
+        f"analyzed={analyzed} reclaimed_bytes={reclaimed}"
+    )
+    return DatabaseMaintenanceResponse(
+        integrity_ok=integrity_ok,
+        integrity_errors=[] if integrity_ok else findings,
+        vacuumed=vacuumed,
+        analyzed=analyzed,
+        size_before_bytes=size_before,
+        size_after_bytes=size_after,
+        reclaimed_bytes=reclaimed,
+        duration_ms=int((time.monotonic() - started) * 1000),
+    )
+
+
+@router.post("/maintenance/prune", response_model=MarketDataPruneResponse)
+async def prune_market_data(
+    request: MarketDataPruneRequest,
+    db: DbSession,
+    x_teletraan_shell_token: str | None = Header(default=None),
+) -> MarketDataPruneResponse:
+    """Delete price history older than ``candle_days`` and finished outcomes
+    whose evaluation window closed more than ``outcome_days`` ago.
+
+    Outcomes still pending or being tracked are never deleted. The freed pages
+    are reused by new rows; run maintenance with VACUUM to shrink the file.
+
+    Requires the shell-only token the desktop shell passed in ``TELETRAAN_SHELL_TOKEN``.
+    """
+    require_shell_token(x_teletraan_shell_token)
+
+    today = date.today()
+    candles_deleted = outcomes_deleted = 0
+    if request.candle_days:
+        result = await db.execute(
+            delete(PriceHistory).where(
+                PriceHistory.date < today - timedelta(days=request.candle_days)
+            )
+        )
+        candles_deleted = result.rowcount or 0
+    if request.outcome_days:
+        result = await db.execute(
+            delete(InsightOutcome).where(
+                InsightOutcome.tracking_status.in_(
+                    [TrackingStatus.COMPLETED.value, TrackingStatus.INVALIDATED.value]
+                ),
+                InsightOutcome.tracking_end_date
+                < today - timedelta(days=request.outcome_days),
+            )
+        )
+        outcomes_deleted = result.rowcount or 0
+    await db.commit()
+
+    logger.info(
+        f"Pruned market data: candles_deleted={candles_deleted} "
+        f"outcomes_deleted={outcomes_deleted}"
+    )
+    return MarketDataPruneResponse(
+        candles_deleted=candles_deleted, outcomes_deleted=outcomes_deleted
+    )
+
+
+def _stream_csv(conn: sqlite3.Connection, table: str) -> Iterator[str]:
+    """Yield ``table`` as CSV a batch of rows at a time, then close ``conn``."""
+    try:
+        cursor = conn.execute(f'SELECT * FROM "{table}"')
+        buffer = io.StringIO()
+        writer = csv.writer(buffer)
+        writer.writerow(column[0] for column in cursor.description)
+        while rows := cursor.fetchmany(EXPORT_BATCH_ROWS):
+            writer.writerows(rows)
+            yield buffer.getvalue()
+            buffer.seek(0)
+            buffer.truncate()
+        if buffer.tell():
+            yield buffer.getvalue()
+    finally:
+        conn.close()
+
+
+@router.get("/maintenance/export/{name}")
+async def export_table(
+    name: str,
+    x_teletraan_shell_token: str | None = Header(default=None),
+) -> StreamingResponse:
+    """Stream every row of an exportable table as CSV, with a header row.
+
+    The database is opened read-only on a separate connection, so the export
+    never blocks the app's writes and holds at most one batch in memory. The
+    row count at the start is sent in ``X-Total-Rows`` for progress reporting.
+
+    Requires the shell-only token the desktop shell passed in ``TELETRAAN_SHELL_TOKEN``.
+    """
+    require_shell_token(x_teletraan_shell_token)
+
+    table = EXPORT_TABLES.get(name)
+    if table is None:
+        raise HTTPException(
+            status_code=404,
+            detail=f"Unknown table {name!r}; expected one of {', '.join(EXPORT_TABLES)}",
+        )
+    path = database.engine.url.database
+    if not path or path == ":memory:":
+        raise HTTPException(status_code=409, detail="The database is not stored in a file")
+
+    # Served from a worker thread while the body is streamed.
+    conn = db_encryption.connect(
+        f"{Path(path).resolve().as_uri()}?mode=ro", uri=True, check_same_thread=False
+    )
+    try:
+        total = conn.execute(f'SELECT COUNT(*) FROM "{table}"').fetchone()[0]
+    except db_encryption.database_errors() as e:
+        conn.close()
+        raise HTTPException(status_code=500, detail=f"Failed to read {table}: {e}") from e
+
+    logger.info(f"Exporting {total} rows of {table}")
+    return StreamingResponse(
+        _stream_csv(conn, table),
+        media_type="text/csv",
+        headers={"X-Total-Rows": str(total)},
+    )
+
+
+def _common_columns(conn: sqlite3.Connection, table: str) -> list[str]:
+    """Columns of ``table`` in both the app's database and the one attached as ``source``.
+
+    Databases from other versions may have columns this one lacks, or lack
+    newer ones, which then take their defaults.
+    """
+    ours = {row[1] for row in conn.execute(f'PRAGMA main.table_info("{table}")')}
+    return [
+        row[1]
+        for row in conn.execute(f'PRAGMA source.table_info("{table}")')
+        if row[1] in ours
+    ]
+
+
+def _insert(conn: sqlite3.Connection, table: str, record: dict) -> int:
+    """Insert ``record`` into ``table`` of the app's database; returns the new rowid."""
+    columns = ", ".join(f'"{column}"' for column in record)
+    placeholders = ", ".join("?" for _ in record)
+    cursor = conn.execute(
+        f'INSERT INTO main."{table}" ({columns}) VALUES ({placeholders})',
+        list(record.values()),
+    )
+    return cursor.lastrowid
+
+
+def _merge_database(target: str, source: str) -> DatabaseImportResponse:
+    """Copy the insights and outcomes of ``source`` missing from ``target``.
+
+    An insight is already there when one has its ``created_at`` and either its
+    ID or its title; an outcome when one has its ID, which is a UUID. Insights
+    whose ID is taken by another insight get a new one, and their outcomes and
+    follow-ups are pointed at it. Links to conversations are dropped, since
+    those are not imported. Everything is merged in one transaction, so a
+    failed import changes nothing.
+    """
+    insights = DeepInsight.__tablename__
+    outcomes = InsightOutcome.__tablename__
+    conn = db_encryption.connect(
+        Path(target).resolve().as_uri(),
+        uri=True,
+        timeout=IMPORT_LOCK_TIMEOUT_SECONDS,
+        isolation_level=None,
+    )
+    try:
+        conn.execute(
+            "ATTACH DATABASE ? AS source" + db_encryption.attach_plaintext_clause(),
+            (f"{Path(source).resolve().as_uri()}?mode=ro",),
+        )
+        insight_columns = _common_columns(conn, insights)
+        outcome_columns = _common_columns(conn, outcomes)
+        if not {"id", "created_at"} <= set(insight_columns) or "id" not in outcome_columns:
+            raise ValueError(f"{source} is not a Teletraan database")
+
+        conn.execute("BEGIN IMMEDIATE")
+        ids: dict[int, int] = {}
+        response = DatabaseImportResponse()
+        selected = ", ".join(f'"{column}"' for column in insight_columns)
+        rows = conn.execute(f'SELECT {selected} FROM source."{insights}" ORDER BY id').fetchall()
+        for row in rows:
+            record = dict(zip(insight_columns, row))
+            source_id = record["id"]
+            existing = conn.execute(
+                f'SELECT id FROM main."{insights}" WHERE created_at = ? AND (id = ? OR title = ?)',
+                (record["created_at"], source_id, record.get("title")),
+            ).fetchone()
+            if existing:
+                ids[source_id] = existing[0]
+                response.insights_skipped += 1
+                continue
+            if conn.execute(
+                f'SELECT 1 FROM main."{insights}" WHERE id = ?', (source_id,)
+            ).fetchone():
+                del record["id"]
+            if "parent_insight_id" in record:
+                record["parent_insight_id"] = ids.get(record["parent_insight_id"])
+            if "source_conversation_id" in record:
+                record["source_conversation_id"] = None
+            ids[source_id] = _insert(conn, insights, record)
+            response.insights_imported += 1
+
+        selected = ", ".join(f'"{column}"' for column in outcome_columns)
+        rows = conn.execute(f'SELECT {selected} FROM source."{outcomes}"').fetchall()
+        for row in rows:
+            record = dict(zip(outcome_columns, row))
+            insight_id = ids.get(record.get("insight_id"))
+            if insight_id is None or conn.execute(
+                f'SELECT 1 FROM main."{outcomes}" WHERE id = ?', (record["id"],)
+            ).fetchone():
+                response.outcomes_skipped += 1
+                continue
+            record["insight_id"] = insight_id
+            _insert(conn, outcomes, record)
+            response.outcomes_imported += 1
+        conn.execute("COMMIT")
+        return response
+    except BaseException:
+        if conn.in_transaction:
+            conn.execute("ROLLBACK")
+        raise
+    finally:
+        conn.close()
+
+
+@router.post("/maintenance/import", response_model=DatabaseImportResponse)
+async def import_database(
+    request: DatabaseImportRequest,
+    x_teletraan_shell_token: str | None = Header(default=None),
+) -> DatabaseImportResponse:
+    """Merge the insights and outcomes of another Teletraan database into this one.
+
+    Rows already present are skipped, so importing the same file twice adds
+    nothing. The other database is only read. Other tables, such as prices,
+    conversations and settings, are left as they are.
+
+    Requires the shell-only token the desktop shell passed in ``TELETRAAN_SHELL_TOKEN``.
+    """
+    require_shell_token(x_teletraan_shell_token)
+
+    path = database.engine.url.database
+    if not path or path == ":memory:":
+        raise HTTPException(status_code=409, detail="The database is not stored in a file")
+    if not os.path.isfile(request.path):
+        raise HTTPException(status_code=404, detail=f"{request.path} does not exist")
+
+    try:
+        response = await asyncio.get_event_loop().run_in_executor(
+            None, _merge_database, path, request.path
+        )
+    except (*db_encryption.database_errors(), ValueError) as e:
+        raise HTTPException(
+            status_code=400, detail=f"Failed to import {request.path}: {e}"
+        ) from e
+
+    logger.info(
+        f"Imported {request.path}: insights_imported={response.insights_imported} "
+        f"insights_skipped={response.insights_skipped} "
+        f"outcomes_imported={response.outcomes_imported} "
+        f"outcomes_skipped={response.outcomes_skipped}"
+    )
+    return response
+
+
+@router.post("/maintenance/reload-packs", response_model=PacksReloadResponse)
+async def reload_packs(
+    x_teletraan_shell_token: str | None = Header(default=None),
+) -> PacksReloadResponse:
+    """Read the prompt and strategy packs the shell lists in ``TELETRAAN_PACKS_FILE`` again.
+
+    Analyses started after the reload use the new packs; running ones keep
+    the prompts they were given.
+
+    Requires the shell-only token the desktop shell passed in ``TELETRAAN_SHELL_TOKEN``.
+    """
+    require_shell_token(x_teletraan_shell_token)
+
+    return PacksReloadResponse(**packs.reload())
@@ -8,6 +8,7 @@ from fastapi import APIRouter, Depends, HTTPException, Query
 from sqlalchemy import func, select
 from sqlalchemy.ext.asyncio import AsyncSession
 
+import review
 from api.deps import get_db
 from analysis.outcome_tracker import InsightOutcomeTracker
 from models.insight_outcome import InsightOutcome, TrackingStatus
@@ -246,8 +247,13 @@ async def start_tracking(
         The created InsightOutcome
 
     Raises:
-        HTTPException: If insight not found or tracking fails
+        HTTPException: If insight not found, awaits review, or tracking fails
     """
+    if not review.is_approved(request.insight_id):
+        raise HTTPException(
+            status_code=409,
+            detail=f"Insight {request.insight_id} has not been approved in review",
+        )
     tracker = InsightOutcomeTracker(db)
 
     try:
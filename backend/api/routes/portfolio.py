@@ -1,9 +1,14 @@
-"""Portfolio API routes for managing investment holdings."""
+"""Portfolio API routes for managing investment holdings.
+
+Holdings are kept per account (taxable, IRA, paper), each a ``Portfolio`` row
+with its own risk limit. Routes that take no account use the default account,
+the first one, so single-account clients keep working.
+"""
 
 import asyncio
 import logging
 
-from fastapi import APIRouter, Depends, HTTPException
+from fastapi import APIRouter, Depends, HTTPException, Query
 from sqlalchemy import select
 from sqlalchemy.ext.asyncio import AsyncSession
 
@@ -13,13 +18,19 @@ from api.deps import get_db
 from models.portfolio import Portfolio, PortfolioHolding
 from models.deep_insight import DeepInsight
 from schemas.portfolio import (
+    AccountPosition,
+    AccountSummary,
     AffectedHolding,
+    ConsolidatedHolding,
+    ConsolidatedPortfolioResponse,
     HoldingCreate,
     HoldingResponse,
     HoldingUpdate,
     PortfolioCreate,
     PortfolioImpactResponse,
     PortfolioResponse,
+    PortfolioUpdate,
+    RiskLimitBreach,
 )
 
 logger = logging.getLogger(__name__)
@@ -42,9 +53,16 @@ async def _fetch_current_price(symbol: str) -> float | None:
     return await asyncio.get_event_loop().run_in_executor(None, _get)
 
 
+async def _fetch_prices(symbols: set[str]) -> dict[str, float | None]:
+    """Fetch the current price of each symbol in parallel."""
+    ordered = sorted(symbols)
+    prices = await asyncio.gather(*(_fetch_current_price(s) for s in ordered))
+    return dict(zip(ordered, prices))
+
+
 async def _get_or_create_portfolio(db: AsyncSession) -> Portfolio:
-    """Get the single portfolio, creating it if it doesn't exist."""
-    result = await db.execute(select(Portfolio).limit(1))
+    """Get the default account, creating it if no account exists."""
+    result = await db.execute(select(Portfolio).order_by(Portfolio.id).limit(1))
     portfolio = result.scalar_one_or_none()
 
     if not portfolio:
@@ -56,25 +74,48 @@ async def _get_or_create_portfolio(db: AsyncSession) -> Portfolio:
     return portfolio
 
 
+async def _get_account(db: AsyncSession, account_id: int | None) -> Portfolio:
+    """Get the account ``account_id``, or the default account when it is None."""
+    if account_id is None:
+        return await _get_or_create_portfolio(db)
+
+    portfolio = await db.get(Portfolio, account_id)
+    if not portfolio:
+        raise HTTPException(status_code=404, detail=f"Account {account_id} not found")
+    return portfolio
+
+
+async def _list_accounts(db: AsyncSession) -> list[Portfolio]:
+    """All accounts, the default one first, creating it if none exists."""
+    await _get_or_create_portfolio(db)
+    result = await db.execute(select(Portfolio).order_by(Portfolio.id))
+    return list(result.scalars().all())
+
+
 async def _enrich_holdings(
     holdings: list[PortfolioHolding],
+    prices: dict[str, float | None] | None = None,
 ) -> tuple[list[HoldingResponse], float]:
     """Enrich holdings with current prices and compute totals.
 
+    Args:
+        holdings: Holdings of one account.
+        prices: Prices already fetched by symbol; fetched when omitted.
+
     Returns:
         Tuple of (enriched holding responses, total market value).
     """
     if not holdings:
         return [], 0.0
 
-    # Fetch all prices in parallel
-    price_tasks = [_fetch_current_price(h.symbol) for h in holdings]
-    prices = await asyncio.gather(*price_tasks)
+    if prices is None:
+        prices = await _fetch_prices({h.symbol for h in holdings})
 
     total_value = 0.0
     enriched: list[dict] = []
 
-    for holding, price in zip(holdings, prices):
+    for holding in holdings:
+        price = prices.get(holding.symbol)
         market_value = (price * holding.shares) if price else None
         cost_total = holding.cost_basis * holding.shares
         gain_loss = (market_value - cost_total) if market_value is not None else None
@@ -114,32 +155,78 @@ async def _enrich_holdings(
     return responses, total_value
 
 
+def _apply_totals(
+    resp: PortfolioResponse | AccountSummary | ConsolidatedPortfolioResponse,
+    total_value: float,
+    total_cost: float,
+) -> None:
+    """Set the value, cost and gain/loss totals on a response."""
+    total_gain_loss = (total_value - total_cost) if total_value > 0 else None
+    resp.total_value = total_value if total_value > 0 else None
+    resp.total_cost = total_cost if total_cost > 0 else None
+    resp.total_gain_loss = total_gain_loss
+    resp.total_gain_loss_pct = (
+        ((total_gain_loss / total_cost) * 100)
+        if total_gain_loss is not None and total_cost > 0
+        else None
+    )
+
+
+def _limit_breaches(
+    portfolio: Portfolio, holdings: list[HoldingResponse]
+) -> list[RiskLimitBreach]:
+    """Holdings taking a larger share of the account than its position limit."""
+    if portfolio.max_position_pct is None:
+        return []
+    return [
+        RiskLimitBreach(
+            account_id=portfolio.id,
+            symbol=h.symbol,
+            allocation_pct=h.allocation_pct,
+            max_position_pct=portfolio.max_position_pct,
+        )
+        for h in holdings
+        if h.allocation_pct is not None and h.allocation_pct > portfolio.max_position_pct
+    ]
+
+
+async def _summarize_account(
+    portfolio: Portfolio,
+    prices: dict[str, float | None] | None = None,
+) -> tuple[AccountSummary, list[HoldingResponse]]:
+    """Summarize an account, returning its enriched holdings as well."""
+    enriched_holdings, total_value = await _enrich_holdings(portfolio.holdings, prices)
+    total_cost = sum(h.shares * h.cost_basis for h in portfolio.holdings)
+
+    summary = AccountSummary.model_validate(portfolio)
+    summary.holdings_count = len(portfolio.holdings)
+    summary.limit_breaches = _limit_breaches(portfolio, enriched_holdings)
+    _apply_totals(summary, total_value, total_cost)
+
+    return summary, enriched_holdings
+
+
 @router.get("", response_model=PortfolioResponse)
-async def get_portfolio(db: AsyncSession = Depends(get_db)):
-    """Get the portfolio with enriched holdings.
+async def get_portfolio(
+    account_id: int | None = Query(None, description="Account to get; the default account if omitted"),
+    db: AsyncSession = Depends(get_db),
+):
+    """Get an account with enriched holdings.
 
-    If no portfolio exists, auto-creates one named 'My Portfolio'.
+    If no account exists, auto-creates one named 'My Portfolio'.
     Each holding is enriched with current price, market value,
-    gain/loss, and allocation percentage.
+    gain/loss, and allocation percentage, and holdings over the
+    account's position limit are listed in ``limit_breaches``.
     """
-    portfolio = await _get_or_create_portfolio(db)
+    portfolio = await _get_account(db, account_id)
 
     enriched_holdings, total_value = await _enrich_holdings(portfolio.holdings)
-
     total_cost = sum(h.shares * h.cost_basis for h in portfolio.holdings)
-    total_gain_loss = (total_value - total_cost) if total_value > 0 else None
-    total_gain_loss_pct = (
-        ((total_gain_loss / total_cost) * 100)
-        if total_gain_loss is not None and total_cost > 0
-        else None
-    )
 
     resp = PortfolioResponse.model_validate(portfolio)
     resp.holdings = enriched_holdings
-    resp.total_value = total_value if total_value > 0 else None
-    resp.total_cost = total_cost if total_cost > 0 else None
-    resp.total_gain_loss = total_gain_loss
-    resp.total_gain_loss_pct = total_gain_loss_pct
+    resp.limit_breaches = _limit_breaches(portfolio, enriched_holdings)
+    _apply_totals(resp, total_value, total_cost)
 
     return resp
 
@@ -165,10 +252,7 @@ async def create_portfolio(
     if existing:
         return PortfolioResponse.model_validate(existing)
 
-    portfolio = Portfolio(
-        name=request.name if request else "My Portfolio",
-        description=request.description if request else None,
-    )
+    portfolio = Portfolio(**(request or PortfolioCreate()).model_dump())
     db.add(portfolio)
     await db.commit()
     await db.refresh(portfolio)
@@ -176,24 +260,197 @@ async def create_portfolio(
     return PortfolioResponse.model_validate(portfolio)
 
 
+@router.get("/accounts", response_model=list[AccountSummary])
+async def list_accounts(db: AsyncSession = Depends(get_db)):
+    """List every account with its totals and risk-limit breaches.
+
+    The default account comes first; it is created if no account exists.
+    """
+    accounts = await _list_accounts(db)
+    prices = await _fetch_prices({h.symbol for a in accounts for h in a.holdings})
+
+    return [(await _summarize_account(a, prices))[0] for a in accounts]
+
+
+@router.post("/accounts", response_model=AccountSummary)
+async def create_account(
+    request: PortfolioCreate,
+    db: AsyncSession = Depends(get_db),
+):
+    """Create another account.
+
+    Args:
+        request: Account name, description, type and position limit.
+        db: Database session.
+
+    Returns:
+        The created account.
+    """
+    # Create the default account first, so the new one does not become it
+    await _get_or_create_portfolio(db)
+
+    portfolio = Portfolio(**request.model_dump())
+    db.add(portfolio)
+    await db.commit()
+    await db.refresh(portfolio)
+
+    return (await _summarize_account(portfolio))[0]
+
+
+@router.put("/accounts/{account_id}", response_model=AccountSummary)
+async def update_account(
+    account_id: int,
+    request: PortfolioUpdate,
+    db: AsyncSession = Depends(get_db),
+):
+    """Update an account.
+
+    Only the fields sent are changed; ``null`` clears the description or
+    the position limit.
+
+    Args:
+        account_id: The ID of the account to update.
+        request: Fields to update.
+        db: Database session.
+
+    Returns:
+        The updated account.
+    """
+    portfolio = await _get_account(db, account_id)
+
+    for field, value in request.model_dump(exclude_unset=True).items():
+        if value is None and field in ("name", "account_type"):
+            continue
+        setattr(portfolio, field, value)
+
+    await db.commit()
+    await db.refresh(portfolio)
+
+    return (await _summarize_account(portfolio))[0]
+
+
+@router.delete("/accounts/{account_id}")
+async def delete_account(
+    account_id: int,
+    db: AsyncSession = Depends(get_db),
+):
+    """Delete an account and its holdings.
+
+    Args:
+        account_id: The ID of the account to delete.
+        db: Database session.
+
+    Returns:
+        Confirmation message.
+    """
+    portfolio = await _get_account(db, account_id)
+
+    await db.delete(portfolio)
+    await db.commit()
+
+    return {"message": "Account deleted"}
+
+
+@router.get("/consolidated", response_model=ConsolidatedPortfolioResponse)
+async def get_consolidated_portfolio(
+    include_paper: bool = Query(False, description="Include paper accounts"),
+    db: AsyncSession = Depends(get_db),
+):
+    """Combine the holdings of every account into one view.
+
+    Positions in the same symbol are merged, with their shares per account
+    and the average cost across accounts; allocation is a share of the
+    combined value. Paper accounts are left out unless ``include_paper``
+    is set, as their positions are simulated.
+    """
+    accounts = [
+        a for a in await _list_accounts(db)
+        if include_paper or a.account_type != "paper"
+    ]
+    prices = await _fetch_prices({h.symbol for a in accounts for h in a.holdings})
+
+    summaries: list[AccountSummary] = []
+    positions: dict[str, dict] = {}
+    for account in accounts:
+        summary, enriched_holdings = await _summarize_account(account, prices)
+        summaries.append(summary)
+        for h in enriched_holdings:
+            position = positions.setdefault(
+                h.symbol, {"shares": 0.0, "cost": 0.0, "accounts": []}
+            )
+            position["shares"] += h.shares
+            position["cost"] += h.shares * h.cost_basis
+            position["accounts"].append(AccountPosition(
+                account_id=account.id,
+                account_name=account.name,
+                account_type=account.account_type,
+                shares=h.shares,
+                market_value=h.market_value,
+            ))
+
+    total_value = sum(s.total_value or 0.0 for s in summaries)
+    total_cost = sum(p["cost"] for p in positions.values())
+
+    holdings: list[ConsolidatedHolding] = []
+    for symbol, position in sorted(positions.items()):
+        price = prices.get(symbol)
+        market_value = (price * position["shares"]) if price else None
+        gain_loss = (market_value - position["cost"]) if market_value is not None else None
+        holdings.append(ConsolidatedHolding(
+            symbol=symbol,
+            shares=position["shares"],
+            cost_basis=position["cost"] / position["shares"] if position["shares"] else 0.0,
+            current_price=price,
+            market_value=market_value,
+            gain_loss=gain_loss,
+            gain_loss_pct=(
+                ((gain_loss / position["cost"]) * 100)
+                if gain_loss is not None and position["cost"] > 0
+                else None
+            ),
+            allocation_pct=(
+                ((market_value / total_value) * 100)
+                if market_value is not None and total_value > 0
+                else None
+            ),
+            accounts=position["accounts"],
+        ))
+
+    value_by_account_type: dict[str, float] = {}
+    for summary in summaries:
+        value_by_account_type[summary.account_type] = (
+            value_by_account_type.get(summary.account_type, 0.0) + (summary.total_value or 0.0)
+        )
+
+    resp = ConsolidatedPortfolioResponse(
+        accounts=summaries,
+        holdings=holdings,
+        value_by_account_type=value_by_account_type,
+        limit_breaches=[b for s in summaries for b in s.limit_breaches],
+    )
+    _apply_totals(resp, total_value, total_cost)
+
+    return resp
+
+
 @router.post("/holdings", response_model=HoldingResponse)
 async def add_holding(
     request: HoldingCreate,
     db: AsyncSession = Depends(get_db),
 ):
-    """Add a new holding to the portfolio.
+    """Add a new holding to an account.
 
-    Auto-creates the portfolio if it doesn't exist.
-    Symbol is uppercased automatically.
+    Goes to the default account, auto-created if needed, when no
+    ``account_id`` is given. Symbol is uppercased automatically.
 
     Args:
-        request: Holding details (symbol, shares, cost_basis, notes).
+        request: Holding details (symbol, shares, cost_basis, notes, account_id).
         db: Database session.
 
     Returns:
         The created holding.
     """
-    portfolio = await _get_or_create_portfolio(db)
+    portfolio = await _get_account(db, request.account_id)
 
     # Check if holding with same symbol already exists
     existing_query = select(PortfolioHolding).where(
@@ -287,8 +544,11 @@ async def delete_holding(
 
 
 @router.get("/impact", response_model=PortfolioImpactResponse)
-async def get_portfolio_impact(db: AsyncSession = Depends(get_db)):
-    """Analyze how active deep insights affect portfolio holdings.
+async def get_portfolio_impact(
+    account_id: int | None = Query(None, description="Account to analyze; the default account if omitted"),
+    db: AsyncSession = Depends(get_db),
+):
+    """Analyze how active deep insights affect an account's holdings.
 
     For each active insight, checks if primary_symbol or related_symbols
     match any holding. Computes allocation exposure and impact direction
@@ -297,10 +557,11 @@ async def get_portfolio_impact(db: AsyncSession = Depends(get_db)):
     Returns:
         PortfolioImpactResponse with affected holdings and exposure breakdown.
     """
-    portfolio = await _get_or_create_portfolio(db)
+    portfolio = await _get_account(db, account_id)
 
     if not portfolio.holdings:
         return PortfolioImpactResponse(
+            account_id=portfolio.id,
             portfolio_value=0.0,
             affected_holdings=[],
             overall_bullish_exposure=0.0,
@@ -382,6 +643,7 @@ async def get_portfolio_impact(db: AsyncSession = Depends(get_db)):
     )
 
     return PortfolioImpactResponse(
+        account_id=portfolio.id,
         portfolio_value=total_value,
         affected_holdings=affected_holdings,
         overall_bullish_exposure=overall_bullish,
@@ -0,0 +1,50 @@
+"""Cooperative shutdown endpoint used by the desktop shell."""
+
+import asyncio
+import logging
+import signal
+
+from fastapi import APIRouter, Header, status
+from sqlalchemy import func, select
+
+from api.auth import require_shell_token
+from api.deps import DbSession
+from models.analysis_task import TERMINAL_STATUSES, AnalysisTask
+from schemas.health import ShutdownResponse
+
+logger = logging.getLogger(__name__)
+
+router = APIRouter()
+
+def _request_graceful_exit() -> None:
+    # Goes through uvicorn's own signal handler, which stops accepting
+    # connections and waits for in-flight requests and background tasks
+    # (analysis runs) before running the lifespan shutdown.
+    signal.raise_signal(signal.SIGINT)
+
+
+@router.post(
+    "/shutdown",
+    response_model=ShutdownResponse,
+    status_code=status.HTTP_202_ACCEPTED,
+)
+async def shutdown(
+    db: DbSession,
+    x_teletraan_shell_token: str | None = Header(default=None),
+) -> ShutdownResponse:
+    """Begin a graceful shutdown so running analyses can finish writing results.
+
+    Requires the shell-only token the desktop shell passed in ``TELETRAAN_SHELL_TOKEN``.
+    """
+    require_shell_token(x_teletraan_shell_token)
+
+    active = await db.scalar(
+        select(func.count())
+        .select_from(AnalysisTask)
+        .where(AnalysisTask.status.notin_([s.value for s in TERMINAL_STATUSES]))
+    )
+    logger.info(f"Shutdown requested by desktop shell ({active} analysis task(s) running)")
+
+    # Respond before the server starts winding down.
+    asyncio.get_running_loop().call_later(0.1, _request_graceful_exit)
+    return ShutdownResponse(status="shutting_down", active_analysis_tasks=active or 0)
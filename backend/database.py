@@ -3,11 +3,15 @@
 import logging
 import os
 from collections.abc import AsyncGenerator
+from functools import partial
+
+import aiosqlite
 
 from sqlalchemy import inspect as sa_inspect, text
 from sqlalchemy.ext.asyncio import AsyncSession, async_sessionmaker, create_async_engine
 from sqlalchemy.orm import DeclarativeBase
 
+import db_encryption
 from config import get_settings
 
 logger = logging.getLogger(__name__)
@@ -15,27 +19,31 @@ logger = logging.getLogger(__name__)
 settings = get_settings()
 
 
-def _ensure_sqlite_dir(database_url: str) -> None:
-    """Create the parent directory for a SQLite database file if it doesn't exist.
-
-    Parses the DATABASE_URL to extract the file path and calls os.makedirs()
-    on its parent directory.  This prevents ``sqlite3.OperationalError: unable
-    to open database file`` when the data directory hasn't been pre-created
-    (e.g., when the backend runs as a Tauri sidecar for the first time).
-    """
+def _sqlite_file(database_url: str) -> str | None:
+    """The file path of a SQLite DATABASE_URL, or ``None`` for another database."""
     # SQLAlchemy SQLite URLs look like:
     #   sqlite+aiosqlite:///./data/db.sqlite   (relative)
     #   sqlite+aiosqlite:////abs/path/db.sqlite (absolute)
     if not database_url.startswith("sqlite"):
-        return
+        return None
 
     # Strip the scheme (everything up to and including "///")
     prefix = ":///"
     idx = database_url.find(prefix)
     if idx == -1:
-        return
-    file_path = database_url[idx + len(prefix):]
+        return None
+    return database_url[idx + len(prefix):] or None
+
+
+def _ensure_sqlite_dir(database_url: str) -> None:
+    """Create the parent directory for a SQLite database file if it doesn't exist.
 
+    Parses the DATABASE_URL to extract the file path and calls os.makedirs()
+    on its parent directory.  This prevents ``sqlite3.OperationalError: unable
+    to open database file`` when the data directory hasn't been pre-created
+    (e.g., when the backend runs as a Tauri sidecar for the first time).
+    """
+    file_path = _sqlite_file(database_url)
     if not file_path:
         return
 
@@ -47,10 +55,26 @@ def _ensure_sqlite_dir(database_url: str) -> None:
 
 _ensure_sqlite_dir(settings.DATABASE_URL)
 
+_database_file = _sqlite_file(settings.DATABASE_URL)
+_engine_options = {}
+if _database_file:
+    # Encrypt or decrypt the file as the desktop shell asks, before it is opened.
+    db_encryption.prepare(_database_file)
+    if db_encryption.active():
+
+        async def _connect_encrypted() -> aiosqlite.Connection:
+            return await aiosqlite.Connection(
+                partial(db_encryption.connect, _database_file), iter_chunk_size=64
+            )
+
+        _engine_options["async_creator"] = _connect_encrypted
+        logger.info("Opening the database with SQLCipher")
+
 # Create async engine
 engine = create_async_engine(
     settings.DATABASE_URL,
     echo=settings.DEBUG,
+    **_engine_options,
 )
 
 # Create async session factory
@@ -133,6 +157,29 @@ def _sync_migrate_missing_columns(connection) -> None:
                 connection.execute(text(ddl))
 
 
+def sync_pending_schema_changes(connection) -> tuple[list[str], list[str]]:
+    """Return the model tables and ``table.column`` names missing from the DB.
+
+    Both lists are empty once ``init_db`` has brought the schema up to date.
+    """
+    inspector = sa_inspect(connection)
+    db_tables = set(inspector.get_table_names())
+
+    missing_tables: list[str] = []
+    missing_columns: list[str] = []
+    for table_name, table in Base.metadata.tables.items():
+        if table_name not in db_tables:
+            missing_tables.append(table_name)
+            continue
+        existing_cols = {col["name"] for col in inspector.get_columns(table_name)}
+        missing_columns.extend(
+            f"{table_name}.{column.name}"
+            for column in table.columns
+            if column.name not in existing_cols
+        )
+    return missing_tables, missing_columns
+
+
 async def init_db() -> None:
     """Initialize database tables and migrate missing columns."""
     async with engine.begin() as conn:
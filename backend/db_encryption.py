@@ -0,0 +1,143 @@
+"""SQLCipher encryption of the database file, keyed by the desktop shell.
+
+The shell keeps the key in the OS keychain and passes it in
+``TELETRAAN_DB_KEY``. At startup, before anything opens the database, a plain
+SQLite file is encrypted with it, or with ``TELETRAAN_DB_DECRYPT`` set an
+encrypted one is decrypted, so turning encryption on or off only takes a
+restart. Needs the ``sqlcipher3`` package (``sqlcipher3-binary``) whenever a
+key is set; without one, the stdlib ``sqlite3`` is used as before.
+"""
+
+import logging
+import os
+import re
+import sqlite3
+from types import ModuleType
+
+logger = logging.getLogger(__name__)
+
+DB_KEY_ENV = "TELETRAAN_DB_KEY"
+DB_DECRYPT_ENV = "TELETRAAN_DB_DECRYPT"
+
+SQLITE_HEADER = b"SQLite format 3\x00"
+
+# Written next to the database, then renamed over it.
+MIGRATING_SUFFIX = ".migrating"
+
+
+def database_key() -> str | None:
+    """The raw key from the environment: 64 hex digits, or ``None``."""
+    key = os.environ.get(DB_KEY_ENV, "").strip().lower()
+    if not key:
+        return None
+    if not re.fullmatch(r"[0-9a-f]{64}", key):
+        raise ValueError(f"{DB_KEY_ENV} must be 64 hexadecimal digits")
+    return key
+
+
+def decrypting() -> bool:
+    return os.environ.get(DB_DECRYPT_ENV, "").strip().lower() in ("1", "true", "yes")
+
+
+def active() -> bool:
+    """Whether the database is opened with SQLCipher and the key."""
+    return database_key() is not None and not decrypting()
+
+
+def is_plaintext(path: str) -> bool:
+    """Whether ``path`` is plain SQLite, or empty or missing."""
+    try:
+        with open(path, "rb") as f:
+            header = f.read(len(SQLITE_HEADER))
+    except FileNotFoundError:
+        return True
+    return not header or header == SQLITE_HEADER
+
+
+def _sqlcipher() -> ModuleType:
+    try:
+        import sqlcipher3  # type: ignore[import-not-found]
+    except ImportError as e:
+        raise RuntimeError(
+            f"{DB_KEY_ENV} is set but SQLCipher is not available; install sqlcipher3-binary"
+        ) from e
+    return sqlcipher3
+
+
+def _key_literal(key: str) -> str:
+    # A raw key, so SQLCipher skips its own key derivation. Safe to inline:
+    # ``database_key`` only lets hex digits through.
+    return f"\"x'{key}'\""
+
+
+def connect(database: str, **kwargs):
+    """``sqlite3.connect``, or with encryption active a SQLCipher connection
+    unlocked with the key."""
+    if not active():
+        return sqlite3.connect(database, **kwargs)
+    conn = _sqlcipher().connect(database, **kwargs)
+    conn.execute(f"PRAGMA key = {_key_literal(database_key())}")
+    return conn
+
+
+def attach_plaintext_clause() -> str:
+    """Appended to ``ATTACH DATABASE``, so a plain file is attached to an
+    encrypted connection without the main database's key."""
+    return " KEY ''" if active() else ""
+
+
+def database_errors() -> tuple[type[Exception], ...]:
+    """The ``DatabaseError`` classes ``connect`` may raise."""
+    if active():
+        return (sqlite3.DatabaseError, _sqlcipher().DatabaseError)
+    return (sqlite3.DatabaseError,)
+
+
+def _export(path: str, key: str, encrypt: bool) -> None:
+    """Rewrite ``path`` with ``sqlcipher_export``, encrypted or decrypted."""
+    sqlcipher = _sqlcipher()
+    migrated = path + MIGRATING_SUFFIX
+    if os.path.exists(migrated):
+        os.remove(migrated)
+    conn = sqlcipher.connect(path, isolation_level=None)
+    try:
+        if not encrypt:
+            conn.execute(f"PRAGMA key = {_key_literal(key)}")
+        target_key = _key_literal(key) if encrypt else "''"
+        conn.execute(f"ATTACH DATABASE ? AS migrated KEY {target_key}", (migrated,))
+        conn.execute("SELECT sqlcipher_export('migrated')")
+        # Not copied by sqlcipher_export.
+        version = int(conn.execute("PRAGMA main.user_version").fetchone()[0])
+        conn.execute(f"PRAGMA migrated.user_version = {version}")
+        conn.execute("DETACH DATABASE migrated")
+    except BaseException:
+        conn.close()
+        if os.path.exists(migrated):
+            os.remove(migrated)
+        raise
+    conn.close()
+    # Closing the last connection checkpoints the write-ahead log; one left
+    # behind would be applied to the wrong file.
+    for suffix in ("-wal", "-shm"):
+        if os.path.exists(path + suffix):
+            os.remove(path + suffix)
+    os.replace(migrated, path)
+
+
+def prepare(path: str) -> None:
+    """Encrypt or decrypt the database at ``path`` if it is not as the
+    environment asks. Does nothing without a key."""
+    key = database_key()
+    if key is None or path == ":memory:" or not os.path.exists(path):
+        return
+    if os.path.getsize(path) == 0:
+        return
+    plaintext = is_plaintext(path)
+    if decrypting() and not plaintext:
+        logger.warning("Decrypting the database %s", path)
+        _export(path, key, encrypt=False)
+        logger.info("Decrypted the database")
+    elif not decrypting() and plaintext:
+        logger.warning("Encrypting the database %s with SQLCipher", path)
+        _export(path, key, encrypt=True)
+        logger.info("Encrypted the database")
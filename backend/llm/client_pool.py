@@ -266,7 +266,8 @@ class ClientPool:
     def stats(self) -> dict:
         return {
             "pool_size": self._size,
-            "available": self._available.qsize(),
+            # Slots are only queued on first checkout; until then all are free.
+            "available": self._available.qsize() if self._initialized else self._size,
             "total_created": self._total_created,
             "total_queries": self._total_queries,
         }
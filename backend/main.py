@@ -1,6 +1,7 @@
 """FastAPI application entry point."""
 
 import logging
+import os
 import sys
 from contextlib import asynccontextmanager
 from collections.abc import AsyncIterator
@@ -23,8 +24,9 @@ from fastapi.responses import JSONResponse  # noqa: E402
 
 # Configure logging before importing other modules
 # This ensures all loggers created with getLogger(__name__) use this configuration
+# TELETRAAN_LOG_LEVEL is set by the desktop shell after `set_log_level`.
 logging.basicConfig(
-    level=logging.INFO,
+    level=os.environ.get("TELETRAAN_LOG_LEVEL", "INFO").upper(),
     format="%(asctime)s - %(name)s - %(levelname)s - %(message)s",
     handlers=[
         logging.StreamHandler(sys.stdout)
@@ -40,6 +42,7 @@ logging.getLogger("httpx").setLevel(logging.WARNING)  # Reduce HTTP client noise
 logger = logging.getLogger(__name__)
 
 from api import api_router  # noqa: E402
+from api.auth import ShellAuthMiddleware  # noqa: E402
 from api.exceptions import (  # noqa: E402
     NotFoundError,
     ValidationError,
@@ -197,15 +200,19 @@ async def lifespan(app: FastAPI) -> AsyncIterator[None]:
         )
     # Initialize database and create tables
     await init_db()
+    # Startup milestones below are matched by the desktop shell's splash screen
+    print("\033[38;5;39m[DB]\033[0m Migrations applied", flush=True)  # noqa: T201
     # Load saved LLM settings from database into os.environ
     # (must happen after init_db so tables exist, but before LLM provider detection)
     from services.llm_settings import load_llm_settings_on_startup
     async with async_session_factory() as session:
         await load_llm_settings_on_startup(session)
+    print("\033[38;5;39m[LLM]\033[0m Providers initialized", flush=True)  # noqa: T201
     # Mark any leftover in-progress analysis tasks as failed
     await _cleanup_stale_analysis_tasks()
     # Start ETL scheduler for background data fetching
     etl_orchestrator.start()
+    print("\033[38;5;39m[ETL]\033[0m Scheduler started", flush=True)  # noqa: T201
     yield
     # Shutdown: Cleanup resources
     etl_orchestrator.stop()
@@ -219,6 +226,10 @@ app = FastAPI(
     lifespan=lifespan,
 )
 
+# Added before CORS so that CORS wraps it: preflights are answered and
+# refusals still carry the CORS headers the webview needs to read them.
+app.add_middleware(ShellAuthMiddleware)
+
 # Configure CORS for frontend (allow any localhost port + Tauri desktop origins)
 # Tauri v2 custom-protocol uses "tauri://localhost" on macOS;
 # the localhost plugin uses "http://tauri.localhost".
@@ -259,8 +270,22 @@ if __name__ == "__main__":
     parser = argparse.ArgumentParser()
     parser.add_argument("--host", default="127.0.0.1")
     parser.add_argument("--port", type=int, default=8000)
+    parser.add_argument(
+        "--uds", help="Serve on this Unix domain socket instead of --host/--port"
+    )
+    parser.add_argument("--ssl-certfile", help="Serve HTTPS with this PEM certificate")
+    parser.add_argument("--ssl-keyfile", help="PEM private key of --ssl-certfile")
     args = parser.parse_args()
 
     import uvicorn
 
-    uvicorn.run(app, host=args.host, port=args.port)
+    if args.uds:
+        uvicorn.run(app, uds=args.uds)
+    else:
+        uvicorn.run(
+            app,
+            host=args.host,
+            port=args.port,
+            ssl_certfile=args.ssl_certfile,
+            ssl_keyfile=args.ssl_keyfile,
+        )
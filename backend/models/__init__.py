@@ -24,6 +24,7 @@ from .insight_research_context import InsightResearchContext
 from .knowledge_pattern import KnowledgePattern, PatternType
 from .portfolio import Portfolio, PortfolioHolding
 from .price import PriceHistory
+from .reconciliation import ReconciliationReport
 from .settings import UserSettings
 from .analysis_task import AnalysisTask, AnalysisTaskStatus, PHASE_PROGRESS, PHASE_NAMES
 from .statistical_feature import StatisticalFeature, StatisticalFeatureType
@@ -75,4 +76,6 @@ __all__ = [
     # Portfolio models
     "Portfolio",
     "PortfolioHolding",
+    # Reconciliation models
+    "ReconciliationReport",
 ]
@@ -46,6 +46,13 @@ PHASE_PROGRESS = {
     AnalysisTaskStatus.CANCELLED: -1,
 }
 
+# Statuses after which a task no longer runs
+TERMINAL_STATUSES = (
+    AnalysisTaskStatus.COMPLETED,
+    AnalysisTaskStatus.FAILED,
+    AnalysisTaskStatus.CANCELLED,
+)
+
 # Human-readable phase names
 PHASE_NAMES = {
     AnalysisTaskStatus.PENDING: "Initializing...",
@@ -13,14 +13,26 @@ if TYPE_CHECKING:
     pass
 
 
+# Kinds of account a portfolio can be. Paper accounts are simulated and are
+# left out of consolidated views unless asked for.
+ACCOUNT_TYPES = ("taxable", "ira", "paper")
+
+
 class Portfolio(TimestampMixin, Base):
-    """Model representing an investment portfolio."""
+    """Model representing an investment portfolio, one per account.
+
+    The first portfolio is the default account, used where no account is
+    given.
+    """
 
     __tablename__ = "portfolios"
 
     id: Mapped[int] = mapped_column(primary_key=True)
     name: Mapped[str] = mapped_column(String(100), default="My Portfolio")
     description: Mapped[str | None] = mapped_column(Text, nullable=True)
+    account_type: Mapped[str] = mapped_column(String(20), default="taxable")
+    # Risk limit: the largest share of the account one holding may take, in percent
+    max_position_pct: Mapped[float | None] = mapped_column(nullable=True)
 
     # Relationships
     holdings: Mapped[list["PortfolioHolding"]] = relationship(
@@ -30,7 +42,7 @@ class Portfolio(TimestampMixin, Base):
     )
 
     def __repr__(self) -> str:
-        return f"<Portfolio(id={self.id}, name={self.name!r})>"
+        return f"<Portfolio(id={self.id}, name={self.name!r}, account_type={self.account_type!r})>"
 
 
 class PortfolioHolding(TimestampMixin, Base):
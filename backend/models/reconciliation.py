@@ -0,0 +1,32 @@
+"""Reconciliation report model for the end-of-day data checks."""
+
+from datetime import date as date_type
+from typing import Any
+
+from sqlalchemy import JSON
+from sqlalchemy.orm import Mapped, mapped_column
+
+from database import Base
+
+from .base import TimestampMixin
+
+
+class ReconciliationReport(TimestampMixin, Base):
+    """Discrepancies found by one end-of-day reconciliation run.
+
+    ``report`` holds a section per check (portfolio, prices, alerts), each
+    with its own list of discrepancies.
+    """
+
+    __tablename__ = "reconciliation_reports"
+
+    id: Mapped[int] = mapped_column(primary_key=True)
+    as_of: Mapped[date_type] = mapped_column(index=True)
+    discrepancy_count: Mapped[int] = mapped_column(default=0)
+    report: Mapped[dict[str, Any]] = mapped_column(JSON)
+
+    def __repr__(self) -> str:
+        return (
+            f"<ReconciliationReport(id={self.id}, as_of={self.as_of}, "
+            f"discrepancy_count={self.discrepancy_count})>"
+        )
@@ -0,0 +1,140 @@
+"""Prompt and strategy packs installed by the desktop shell.
+
+The shell downloads signed packs into the data directory and lists the ones
+in use in the JSON file named by ``TELETRAAN_PACKS_FILE``::
+
+    {"packs": [{"name": "core-prompts", "kind": "prompts", "version": "1.4.0",
+                "path": "/.../packs/core-prompts/1.4.0.json"}]}
+
+A ``prompts`` pack maps prompt names (those in ``PROMPT_NAMES``) to system
+prompts that replace the built-in ones. A ``strategy`` pack maps sections,
+such as ``confidence``, to the settings they override. Packs are read on first
+use and again by ``reload()``, which the shell calls through
+``POST /api/v1/maintenance/reload-packs`` after a change. Without the variable
+or the file, the built-in prompts and settings apply.
+"""
+
+import json
+import logging
+import os
+import threading
+from pathlib import Path
+from typing import Any
+
+logger = logging.getLogger(__name__)
+
+PACKS_FILE_ENV = "TELETRAAN_PACKS_FILE"
+
+KINDS = ("prompts", "strategy")
+
+# Prompts a pack can replace
+PROMPT_NAMES = (
+    "technical",
+    "sector",
+    "macro",
+    "correlation",
+    "risk",
+    "synthesis",
+    "coverage_evaluator",
+    "sector_rotator",
+    "opportunity_hunter",
+)
+
+_lock = threading.Lock()
+_loaded = False
+_prompts: dict[str, str] = {}
+_strategy: dict[str, dict[str, Any]] = {}
+
+
+def _read_pack(entry: dict[str, Any]) -> dict[str, Any]:
+    """The pack ``entry`` of the packs file points at, checked against it."""
+    path = Path(entry["path"])
+    pack = json.loads(path.read_text(encoding="utf-8"))
+    if not isinstance(pack, dict):
+        raise ValueError("not a JSON object")
+    for key in ("name", "kind", "version"):
+        if pack.get(key) != entry.get(key):
+            raise ValueError(f"{key} is {pack.get(key)!r}, expected {entry.get(key)!r}")
+    if pack["kind"] not in KINDS:
+        raise ValueError(f"unknown kind {pack['kind']!r}")
+    return pack
+
+
+def reload() -> dict[str, Any]:
+    """Read the packs file again. Returns the packs in use and why others
+    were skipped; a pack that cannot be read leaves the built-ins in place."""
+    global _loaded
+    prompts: dict[str, str] = {}
+    strategy: dict[str, dict[str, Any]] = {}
+    packs: list[dict[str, str]] = []
+    errors: list[str] = []
+
+    packs_file = os.environ.get(PACKS_FILE_ENV)
+    entries: list[dict[str, Any]] = []
+    if packs_file:
+        try:
+            entries = json.loads(Path(packs_file).read_text(encoding="utf-8"))["packs"]
+        except FileNotFoundError:
+            pass
+        except (OSError, ValueError, KeyError, TypeError) as e:
+            errors.append(f"{packs_file}: {e}")
+
+    for entry in entries:
+        name = entry.get("name", "?") if isinstance(entry, dict) else "?"
+        try:
+            pack = _read_pack(entry)
+            if pack["kind"] == "prompts":
+                templates = pack.get("prompts", {})
+                unknown = sorted(set(templates) - set(PROMPT_NAMES))
+                if unknown:
+                    errors.append(f"{name}: unknown prompts {unknown} ignored")
+                for prompt, template in templates.items():
+                    if prompt not in PROMPT_NAMES:
+                        continue
+                    if not isinstance(template, str) or not template.strip():
+                        errors.append(f"{name}: prompt {prompt!r} is empty")
+                        continue
+                    prompts[prompt] = template
+            else:
+                for section, values in pack.get("strategy", {}).items():
+                    if isinstance(values, dict):
+                        strategy.setdefault(section, {}).update(values)
+            packs.append(
+                {"name": pack["name"], "kind": pack["kind"], "version": pack["version"]}
+            )
+        except (OSError, ValueError, KeyError, TypeError) as e:
+            errors.append(f"{name}: {e}")
+
+    with _lock:
+        _prompts.clear()
+        _prompts.update(prompts)
+        _strategy.clear()
+        _strategy.update(strategy)
+        _loaded = True
+    for error in errors:
+        logger.warning(f"Packs: {error}")
+    if packs:
+        logger.info(
+            "Packs in use: "
+            + ", ".join(f"{p['name']} {p['version']} ({p['kind']})" for p in packs)
+        )
+    return {"packs": packs, "errors": errors}
+
+
+def _ensure_loaded() -> None:
+    if not _loaded:
+        reload()
+
+
+def get_prompt(name: str, default: str) -> str:
+    """The system prompt ``name`` from a prompts pack, or the built-in ``default``."""
+    _ensure_loaded()
+    with _lock:
+        return _prompts.get(name, default)
+
+
+def get_strategy(section: str) -> dict[str, Any]:
+    """The settings strategy packs override in ``section``, e.g. ``confidence``."""
+    _ensure_loaded()
+    with _lock:
+        return dict(_strategy.get(section, {}))
@@ -0,0 +1,47 @@
+"""Human review of insights, kept by the desktop shell.
+
+When the user turns on ``review.required``, the shell passes the path of its
+review queue in ``TELETRAAN_REVIEW_QUEUE_FILE``. The queue maps insight ids to
+the reviewer's decision::
+
+    {"42": {"insight_id": 42, "status": "approved", "insight": {...},
+            "edits": {"action": "HOLD"}, ...}}
+
+While the variable is set, only approved insights count toward the track
+record: outcome tracking is not started for the others when they are
+generated, and the shell starts it once one is approved. The file is read on
+every check, since decisions change while the backend runs. Without the
+variable every insight is treated as approved.
+"""
+
+import json
+import logging
+import os
+from pathlib import Path
+
+logger = logging.getLogger(__name__)
+
+REVIEW_FILE_ENV = "TELETRAAN_REVIEW_QUEUE_FILE"
+
+
+def required() -> bool:
+    """Whether insights need the reviewer's approval."""
+    return bool(os.environ.get(REVIEW_FILE_ENV))
+
+
+def is_approved(insight_id: int) -> bool:
+    """Whether the insight ``insight_id`` may be used: approved in the queue,
+    or any insight when review is off. A queue that cannot be read approves
+    nothing."""
+    queue_file = os.environ.get(REVIEW_FILE_ENV)
+    if not queue_file:
+        return True
+    try:
+        queue = json.loads(Path(queue_file).read_text(encoding="utf-8"))
+    except FileNotFoundError:
+        return False
+    except (OSError, ValueError) as e:
+        logger.warning(f"Failed to read the review queue {queue_file}: {e}")
+        return False
+    item = queue.get(str(insight_id)) if isinstance(queue, dict) else None
+    return isinstance(item, dict) and item.get("status") == "approved"
@@ -22,6 +22,7 @@ from models.price import PriceHistory
 from models.economic import EconomicIndicator
 from analysis.engine import AnalysisEngine
 from analysis.outcome_tracker import InsightOutcomeTracker
+from analysis.reconciliation import EndOfDayReconciler
 from analysis.memory_service import InstitutionalMemoryService
 from analysis.statistical_calculator import StatisticalFeatureCalculator
 
@@ -36,6 +37,7 @@ class ETLOrchestrator:
     - Fetching economic indicators from FRED
     - Running analysis pipelines
     - Backfilling historical data
+    - Reconciling stored data against its sources each night
 
     Example:
         ```python
@@ -507,6 +509,25 @@ class ETLOrchestrator:
                 "symbols": len(all_symbols),
             }
 
+    async def reconcile_end_of_day(self) -> dict[str, Any]:
+        """Nightly job to reconcile stored data against its sources.
+
+        Compares holdings, cached prices and the day's alerts with their
+        sources and stores the discrepancy report.
+
+        Returns:
+            Dict containing the report_id and discrepancy count.
+        """
+        logger.info("Running end-of-day reconciliation job")
+
+        async with async_session_factory() as session:
+            report = await EndOfDayReconciler(session).run()
+
+            return {
+                "report_id": report.id,
+                "discrepancies": report.discrepancy_count,
+            }
+
     def start(self) -> None:
         """Start the scheduler with configured jobs."""
         if self._is_running:
@@ -537,6 +558,15 @@ class ETLOrchestrator:
             replace_existing=True,
         )
 
+        # End-of-day reconciliation at 8 PM ET, after the price refresh and analysis
+        self.scheduler.add_job(
+            self.reconcile_end_of_day,
+            CronTrigger(hour=20, minute=0, day_of_week="mon-fri",
+                        timezone="America/New_York"),
+            id="daily_reconciliation",
+            replace_existing=True,
+        )
+
         # Weekly stock info refresh (Sundays at 12 PM)
         self.scheduler.add_job(
             self.refresh_stock_info,
@@ -584,7 +614,8 @@ class ETLOrchestrator:
         self._is_running = True
         logger.info(
             "ETL scheduler started with jobs: daily_price_refresh, "
-            "weekly_economic_refresh, daily_analysis, weekly_stock_info_refresh, "
+            "weekly_economic_refresh, daily_analysis, daily_reconciliation, "
+            "weekly_stock_info_refresh, "
             "intraday_outcome_check, daily_outcome_check, daily_theme_decay, "
             "daily_feature_computation"
         )
@@ -8,7 +8,7 @@ from schemas.deep_insight import (
     InsightAction,
     InsightType,
 )
-from schemas.health import HealthResponse
+from schemas.health import DeepHealthResponse, HealthResponse, ShutdownResponse
 from schemas.insight import (
     AnnotationCreate,
     AnnotationResponse,
@@ -86,7 +86,11 @@ from schemas.outcome import (
     StartTrackingRequest,
 )
 from schemas.portfolio import (
+    AccountPosition,
+    AccountSummary,
     AffectedHolding,
+    ConsolidatedHolding,
+    ConsolidatedPortfolioResponse,
     HoldingBase,
     HoldingCreate,
     HoldingResponse,
@@ -96,6 +100,8 @@ from schemas.portfolio import (
     PortfolioImpactResponse,
     PortfolioResponse,
     PortfolioSummaryResponse,
+    PortfolioUpdate,
+    RiskLimitBreach,
 )
 from schemas.stock import (
     PriceHistoryResponse,
@@ -119,7 +125,9 @@ __all__ = [
     "DeepInsightCreate",
     "DeepInsightListResponse",
     "DeepInsightResponse",
+    "DeepHealthResponse",
     "HealthResponse",
+    "ShutdownResponse",
     "InsightAction",
     "InsightBase",
     "InsightListResponse",
@@ -188,7 +196,11 @@ __all__ = [
     "TrackRecordResponse",
     "TypeBreakdown",
     # Portfolio schemas
+    "AccountPosition",
+    "AccountSummary",
     "AffectedHolding",
+    "ConsolidatedHolding",
+    "ConsolidatedPortfolioResponse",
     "HoldingBase",
     "HoldingCreate",
     "HoldingResponse",
@@ -198,4 +210,6 @@ __all__ = [
     "PortfolioImpactResponse",
     "PortfolioResponse",
     "PortfolioSummaryResponse",
+    "PortfolioUpdate",
+    "RiskLimitBreach",
 ]
@@ -3,7 +3,7 @@
 from datetime import date, datetime
 from typing import Any
 
-from pydantic import BaseModel, ConfigDict, Field
+from pydantic import BaseModel, ConfigDict, Field, model_validator
 
 
 # Technical Analysis Schemas
@@ -147,6 +147,29 @@ class AnalysisRunResponse(BaseModel):
     started_at: datetime
 
 
+# Replay Schemas
+class ReplayRequest(BaseModel):
+    """Request to replay stored price history through the analysis."""
+    symbols: list[str] = Field(..., min_length=1, max_length=50)
+    start_date: date
+    end_date: date | None = None  # None means start_date only
+    interval_seconds: float = Field(default=0.0, ge=0, le=60)
+
+    @model_validator(mode="after")
+    def validate_period(self) -> "ReplayRequest":
+        """Normalize symbols and check the period."""
+        self.symbols = list(dict.fromkeys(s.strip().upper() for s in self.symbols if s.strip()))
+        if not self.symbols:
+            raise ValueError("symbols list cannot be empty")
+        if self.end_date is None:
+            self.end_date = self.start_date
+        if self.end_date < self.start_date:
+            raise ValueError("end_date must not be before start_date")
+        if (self.end_date - self.start_date).days > 366:
+            raise ValueError("a replay covers at most a year")
+        return self
+
+
 # Analysis Summary Schemas
 class AnalysisSummaryResponse(BaseModel):
     """Summary of latest analysis results."""
@@ -1,9 +1,109 @@
 from datetime import datetime
-from pydantic import BaseModel
+from pydantic import BaseModel, Field
 
 
 class HealthResponse(BaseModel):
     status: str
+    # Identifies this API to clients probing a port, e.g. the desktop shell
+    service: str = "teletraan"
     version: str
     database: str
     timestamp: datetime
+    # Model tables and columns not yet created in the database
+    pending_migrations: int = 0
+    llm_provider: str
+    llm_clients_available: int
+    llm_pool_size: int
+    # Analysis tasks queued or running
+    queue_depth: int = 0
+    uptime_seconds: float = 0.0
+
+
+class VersionResponse(BaseModel):
+    service: str
+    version: str
+    python_version: str
+    platform: str
+    # Fingerprint of the model tables and columns
+    schema_version: str
+
+
+class DeepHealthResponse(BaseModel):
+    status: str
+    database: str
+    schema_up_to_date: bool
+    missing_tables: list[str]
+    missing_columns: list[str]
+    timestamp: datetime
+
+
+class ShutdownResponse(BaseModel):
+    status: str
+    active_analysis_tasks: int
+
+
+class LogLevelRequest(BaseModel):
+    level: str
+    # Logger name, e.g. "uvicorn"; the root logger when omitted
+    logger: str | None = None
+
+
+class LogLevelResponse(BaseModel):
+    logger: str
+    level: str
+
+
+class DatabaseMaintenanceRequest(BaseModel):
+    vacuum: bool = True
+    analyze: bool = True
+
+
+class DatabaseMaintenanceResponse(BaseModel):
+    integrity_ok: bool
+    # Problems reported by PRAGMA integrity_check, empty when it passed
+    integrity_errors: list[str]
+    vacuumed: bool
+    analyzed: bool
+    # Database plus write-ahead log; None for an in-memory database
+    size_before_bytes: int | None = None
+    size_after_bytes: int | None = None
+    reclaimed_bytes: int | None = None
+    duration_ms: int
+
+
+class MarketDataPruneRequest(BaseModel):
+    # Age limits in days; 0 keeps that kind of row regardless of age
+    candle_days: int = Field(default=0, ge=0)
+    outcome_days: int = Field(default=0, ge=0)
+
+
+class MarketDataPruneResponse(BaseModel):
+    candles_deleted: int
+    outcomes_deleted: int
+
+
+class DatabaseImportRequest(BaseModel):
+    # Another Teletraan database file, readable by the backend
+    path: str
+
+
+class DatabaseImportResponse(BaseModel):
+    insights_imported: int = 0
+    # Already present, by ID or title and creation time
+    insights_skipped: int = 0
+    outcomes_imported: int = 0
+    # Already present, or for an insight that was not imported
+    outcomes_skipped: int = 0
+
+
+class PackInfo(BaseModel):
+    name: str
+    # "prompts" or "strategy"
+    kind: str
+    version: str
+
+
+class PacksReloadResponse(BaseModel):
+    packs: list[PackInfo]
+    # Packs, or parts of them, that were skipped and why
+    errors: list[str]
@@ -1,5 +1,9 @@
 from datetime import datetime
-from pydantic import BaseModel, ConfigDict
+from typing import Literal
+
+from pydantic import BaseModel, ConfigDict, Field
+
+AccountType = Literal["taxable", "ira", "paper"]
 
 
 class HoldingBase(BaseModel):
@@ -10,7 +14,7 @@ class HoldingBase(BaseModel):
 
 
 class HoldingCreate(HoldingBase):
-    pass
+    account_id: int | None = None  # None means the default account
 
 
 class HoldingUpdate(BaseModel):
@@ -35,12 +39,28 @@ class HoldingResponse(HoldingBase):
 class PortfolioBase(BaseModel):
     name: str = "My Portfolio"
     description: str | None = None
+    account_type: AccountType = "taxable"
+    max_position_pct: float | None = Field(default=None, gt=0, le=100)
 
 
 class PortfolioCreate(PortfolioBase):
     pass
 
 
+class PortfolioUpdate(BaseModel):
+    name: str | None = None
+    description: str | None = None
+    account_type: AccountType | None = None
+    max_position_pct: float | None = Field(default=None, gt=0, le=100)
+
+
+class RiskLimitBreach(BaseModel):
+    account_id: int
+    symbol: str
+    allocation_pct: float
+    max_position_pct: float
+
+
 class PortfolioResponse(PortfolioBase):
     model_config = ConfigDict(from_attributes=True)
     id: int
@@ -49,10 +69,53 @@ class PortfolioResponse(PortfolioBase):
     total_cost: float | None = None
     total_gain_loss: float | None = None
     total_gain_loss_pct: float | None = None
+    limit_breaches: list[RiskLimitBreach] = []
     created_at: datetime
     updated_at: datetime | None = None
 
 
+class AccountSummary(PortfolioBase):
+    model_config = ConfigDict(from_attributes=True)
+    id: int
+    holdings_count: int = 0
+    total_value: float | None = None
+    total_cost: float | None = None
+    total_gain_loss: float | None = None
+    total_gain_loss_pct: float | None = None
+    limit_breaches: list[RiskLimitBreach] = []
+
+
+class AccountPosition(BaseModel):
+    account_id: int
+    account_name: str
+    account_type: str
+    shares: float
+    market_value: float | None = None
+
+
+class ConsolidatedHolding(BaseModel):
+    symbol: str
+    shares: float
+    cost_basis: float  # average price per share across accounts
+    current_price: float | None = None
+    market_value: float | None = None
+    gain_loss: float | None = None
+    gain_loss_pct: float | None = None
+    allocation_pct: float | None = None
+    accounts: list[AccountPosition]
+
+
+class ConsolidatedPortfolioResponse(BaseModel):
+    accounts: list[AccountSummary]
+    holdings: list[ConsolidatedHolding]
+    total_value: float | None = None
+    total_cost: float | None = None
+    total_gain_loss: float | None = None
+    total_gain_loss_pct: float | None = None
+    value_by_account_type: dict[str, float]
+    limit_breaches: list[RiskLimitBreach]
+
+
 class PortfolioSummaryResponse(BaseModel):
     total_value: float
     total_cost: float
@@ -70,6 +133,7 @@ class AffectedHolding(BaseModel):
 
 
 class PortfolioImpactResponse(BaseModel):
+    account_id: int
     portfolio_value: float
     affected_holdings: list[AffectedHolding]
     overall_bullish_exposure: float
@@ -92,6 +92,15 @@ async def client(db_session: AsyncSession) -> AsyncGenerator[AsyncClient, None]:
     app.dependency_overrides.clear()
 
 
+@pytest.fixture()
+def shell_headers(monkeypatch) -> dict[str, str]:
+    """Start the backend with the tokens the desktop shell passes, and return
+    the headers the shell sends to a privileged endpoint."""
+    monkeypatch.setenv("TELETRAAN_AUTH_TOKEN", "secret")
+    monkeypatch.setenv("TELETRAAN_SHELL_TOKEN", "shell-secret")
+    return {"X-Teletraan-Token": "secret", "X-Teletraan-Shell-Token": "shell-secret"}
+
+
 # ---------------------------------------------------------------------------
 # Sample data fixtures
 # ---------------------------------------------------------------------------
@@ -0,0 +1,50 @@
+"""Tests for the shared-secret check between the desktop shell and the backend."""
+
+from httpx import AsyncClient
+
+
+async def test_auth_off_without_token(client: AsyncClient, monkeypatch):
+    """A standalone backend answers without a token."""
+    monkeypatch.delenv("TELETRAAN_AUTH_TOKEN", raising=False)
+
+    response = await client.get("/api/v1/health")
+
+    assert response.status_code == 200
+
+
+async def test_auth_rejects_missing_or_wrong_token(client: AsyncClient, monkeypatch):
+    """With a token configured, requests without it are refused."""
+    monkeypatch.setenv("TELETRAAN_AUTH_TOKEN", "secret")
+
+    missing = await client.get("/api/v1/health")
+    wrong = await client.get("/api/v1/health", headers={"X-Teletraan-Token": "nope"})
+
+    assert missing.status_code == 401
+    assert wrong.status_code == 401
+
+
+async def test_auth_accepts_header_or_query_token(client: AsyncClient, monkeypatch):
+    """The token is taken from the header, or the query for links and WebSockets."""
+    monkeypatch.setenv("TELETRAAN_AUTH_TOKEN", "secret")
+
+    header = await client.get("/api/v1/health", headers={"X-Teletraan-Token": "secret"})
+    query = await client.get("/api/v1/health", params={"token": "secret"})
+
+    assert header.status_code == 200
+    assert query.status_code == 200
+
+
+async def test_auth_lets_cors_preflight_through(client: AsyncClient, monkeypatch):
+    """Browsers send preflights without custom headers."""
+    monkeypatch.setenv("TELETRAAN_AUTH_TOKEN", "secret")
+
+    response = await client.options(
+        "/api/v1/health",
+        headers={
+            "Origin": "tauri://localhost",
+            "Access-Control-Request-Method": "GET",
+            "Access-Control-Request-Headers": "x-teletraan-token",
+        },
+    )
+
+    assert response.status_code == 200
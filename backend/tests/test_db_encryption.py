@@ -0,0 +1,59 @@
+"""Tests for the SQLCipher key handling and migration checks."""
+
+import sqlite3
+import sys
+
+import pytest
+
+import db_encryption
+
+KEY = "ab" * 32
+
+
+def test_no_key_leaves_database_alone(tmp_path, monkeypatch):
+    """Without a key nothing is migrated and the stdlib driver is used."""
+    monkeypatch.delenv("TELETRAAN_DB_KEY", raising=False)
+    path = tmp_path / "plain.db"
+    sqlite3.connect(path).execute("CREATE TABLE t (x)").connection.close()
+
+    db_encryption.prepare(str(path))
+
+    assert db_encryption.is_plaintext(str(path))
+    assert not db_encryption.active()
+    assert db_encryption.attach_plaintext_clause() == ""
+
+
+def test_invalid_key_is_rejected(monkeypatch):
+    """Only a raw 32-byte hex key is accepted."""
+    monkeypatch.setenv("TELETRAAN_DB_KEY", "not-a-key")
+
+    with pytest.raises(ValueError):
+        db_encryption.database_key()
+
+
+def test_key_without_sqlcipher_fails_clearly(tmp_path, monkeypatch):
+    """A key with no SQLCipher driver installed is an error, not a silent plain database."""
+    monkeypatch.setenv("TELETRAAN_DB_KEY", KEY)
+    monkeypatch.delenv("TELETRAAN_DB_DECRYPT", raising=False)
+    monkeypatch.setitem(sys.modules, "sqlcipher3", None)
+    path = tmp_path / "plain.db"
+    sqlite3.connect(path).execute("CREATE TABLE t (x)").connection.close()
+
+    with pytest.raises(RuntimeError, match="SQLCipher"):
+        db_encryption.prepare(str(path))
+
+    assert db_encryption.is_plaintext(str(path))
+
+
+def test_decrypt_flag_turns_encryption_off(tmp_path, monkeypatch):
+    """With the decrypt flag a plain database stays plain and is opened without SQLCipher."""
+    monkeypatch.setenv("TELETRAAN_DB_KEY", KEY)
+    monkeypatch.setenv("TELETRAAN_DB_DECRYPT", "1")
+    path = tmp_path / "plain.db"
+    sqlite3.connect(path).execute("CREATE TABLE t (x)").connection.close()
+
+    db_encryption.prepare(str(path))
+
+    assert not db_encryption.active()
+    assert db_encryption.is_plaintext(str(path))
+    assert db_encryption.is_plaintext(str(tmp_path / "missing.db"))
@@ -10,9 +10,14 @@ async def test_health_returns_200(client: AsyncClient):
     assert response.status_code == 200
     data = response.json()
     assert data["status"] == "healthy"
+    assert data["service"] == "teletraan"
     assert data["database"] == "connected"
     assert "timestamp" in data
     assert "version" in data
+    assert data["pending_migrations"] == 0
+    assert data["queue_depth"] == 0
+    assert isinstance(data["llm_provider"], str)
+    assert data["uptime_seconds"] >= 0
 
 
 async def test_health_includes_version_info(client: AsyncClient):
@@ -25,3 +30,29 @@ async def test_health_includes_version_info(client: AsyncClient):
     assert len(data["version"]) > 0
     # Version should follow semver-ish pattern (e.g. "1.0.0")
     assert data["version"] == "1.0.0"
+
+
+async def test_version_reports_backend_and_runtime(client: AsyncClient):
+    """Version endpoint matches the health version and names the Python runtime."""
+    response = await client.get("/api/v1/version")
+
+    assert response.status_code == 200
+    data = response.json()
+    assert data["service"] == "teletraan"
+    assert data["version"] == "1.0.0"
+    assert data["python_version"].count(".") == 2
+    assert data["platform"]
+    assert len(data["schema_version"]) == 12
+
+
+async def test_deep_health_reports_current_schema(client: AsyncClient):
+    """Deep health passes when every model table and column exists."""
+    response = await client.get("/api/v1/health/deep")
+
+    assert response.status_code == 200
+    data = response.json()
+    assert data["status"] == "healthy"
+    assert data["database"] == "connected"
+    assert data["schema_up_to_date"] is True
+    assert data["missing_tables"] == []
+    assert data["missing_columns"] == []
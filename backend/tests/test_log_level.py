@@ -0,0 +1,48 @@
+"""Tests for the runtime log-level endpoint."""
+
+import logging
+
+from httpx import AsyncClient
+
+
+async def test_log_level_disabled_without_token(client: AsyncClient, monkeypatch):
+    """Without a configured token the endpoint does not exist."""
+    monkeypatch.delenv("TELETRAAN_AUTH_TOKEN", raising=False)
+    monkeypatch.delenv("TELETRAAN_SHELL_TOKEN", raising=False)
+
+    response = await client.post("/api/v1/log-level", json={"level": "DEBUG"})
+
+    assert response.status_code == 404
+
+
+async def test_log_level_rejects_wrong_token(client: AsyncClient, monkeypatch):
+    """A mismatched token is refused."""
+    monkeypatch.setenv("TELETRAAN_AUTH_TOKEN", "secret")
+
+    response = await client.post(
+        "/api/v1/log-level",
+        json={"level": "DEBUG"},
+        headers={"X-Teletraan-Token": "nope"},
+    )
+
+    assert response.status_code == 401
+
+
+async def test_log_level_sets_named_logger(client: AsyncClient, shell_headers, monkeypatch):
+    """The named logger's level changes; unknown levels are rejected."""
+    target = logging.getLogger("teletraan.test")
+    monkeypatch.setattr(target, "level", logging.NOTSET)
+
+    response = await client.post(
+        "/api/v1/log-level",
+        json={"level": "debug", "logger": "teletraan.test"},
+        headers=shell_headers,
+    )
+    invalid = await client.post(
+        "/api/v1/log-level", json={"level": "LOUD"}, headers=shell_headers
+    )
+
+    assert response.status_code == 200
+    assert response.json() == {"logger": "teletraan.test", "level": "DEBUG"}
+    assert target.level == logging.DEBUG
+    assert invalid.status_code == 422
@@ -0,0 +1,303 @@
+"""Tests for the database maintenance, pruning, export and import endpoints."""
+
+import sqlite3
+from datetime import date, timedelta
+
+from httpx import AsyncClient
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import create_async_engine
+
+from models.insight_outcome import InsightOutcome
+from models.price import PriceHistory
+
+
+async def test_maintenance_disabled_without_token(client: AsyncClient, monkeypatch):
+    """Without a configured token the endpoint does not exist."""
+    monkeypatch.delenv("TELETRAAN_AUTH_TOKEN", raising=False)
+    monkeypatch.delenv("TELETRAAN_SHELL_TOKEN", raising=False)
+
+    response = await client.post("/api/v1/maintenance/database", json={})
+
+    assert response.status_code == 404
+
+
+async def test_maintenance_rejects_wrong_token(client: AsyncClient, monkeypatch):
+    """A mismatched token is refused."""
+    monkeypatch.setenv("TELETRAAN_AUTH_TOKEN", "secret")
+
+    response = await client.post(
+        "/api/v1/maintenance/database",
+        json={},
+        headers={"X-Teletraan-Token": "nope"},
+    )
+
+    assert response.status_code == 401
+
+
+async def test_maintenance_needs_the_shell_token(client: AsyncClient, shell_headers):
+    """The token the webview holds, in a header or a link, is not enough."""
+    by_link = await client.post("/api/v1/maintenance/database?token=secret", json={})
+    by_header = await client.post(
+        "/api/v1/maintenance/database",
+        json={},
+        headers={"X-Teletraan-Token": "secret"},
+    )
+    wrong = await client.post(
+        "/api/v1/maintenance/database",
+        json={},
+        headers={**shell_headers, "X-Teletraan-Shell-Token": "wrong"},
+    )
+
+    assert by_link.status_code == 403
+    assert by_header.status_code == 403
+    assert wrong.status_code == 403
+
+
+async def test_maintenance_checks_vacuums_and_analyzes(
+    client: AsyncClient, db_session, shell_headers, monkeypatch
+):
+    """A healthy database passes the check and is vacuumed and analyzed."""
+    monkeypatch.setattr("database.engine", db_session.bind)
+
+    response = await client.post(
+        "/api/v1/maintenance/database",
+        json={},
+        headers=shell_headers,
+    )
+
+    assert response.status_code == 200
+    data = response.json()
+    assert data["integrity_ok"] is True
+    assert data["integrity_errors"] == []
+    assert data["vacuumed"] is True
+    assert data["analyzed"] is True
+    # In-memory databases have no file to measure.
+    assert data["size_before_bytes"] is None
+
+
+async def test_maintenance_refuses_vacuum_during_analysis(
+    client: AsyncClient, db_session, shell_headers, monkeypatch, sample_analysis_task
+):
+    """VACUUM waits for running analyses; a check-only run is still allowed."""
+    monkeypatch.setattr("database.engine", db_session.bind)
+    headers = shell_headers
+
+    refused = await client.post(
+        "/api/v1/maintenance/database", json={}, headers=headers
+    )
+    check_only = await client.post(
+        "/api/v1/maintenance/database",
+        json={"vacuum": False, "analyze": False},
+        headers=headers,
+    )
+
+    assert refused.status_code == 409
+    assert check_only.status_code == 200
+    assert check_only.json()["vacuumed"] is False
+
+
+async def test_prune_rejects_wrong_token(client: AsyncClient, monkeypatch):
+    """Pruning needs the same token as maintenance."""
+    monkeypatch.setenv("TELETRAAN_AUTH_TOKEN", "secret")
+
+    response = await client.post(
+        "/api/v1/maintenance/prune",
+        json={"candle_days": 30},
+        headers={"X-Teletraan-Token": "nope"},
+    )
+
+    assert response.status_code == 401
+
+
+async def test_prune_deletes_old_candles_and_finished_outcomes(
+    client: AsyncClient, db_session, shell_headers, sample_stock, sample_deep_insight
+):
+    """Rows past the age limits go; recent candles and open outcomes stay."""
+    today = date.today()
+    for age in (400, 10):
+        db_session.add(
+            PriceHistory(
+                stock_id=sample_stock.id,
+                date=today - timedelta(days=age),
+                open=1.0,
+                high=1.0,
+                low=1.0,
+                close=1.0,
+                volume=1,
+            )
+        )
+    for status in ("COMPLETED", "TRACKING"):
+        db_session.add(
+            InsightOutcome(
+                insight_id=sample_deep_insight.id,
+                tracking_status=status,
+                tracking_start_date=today - timedelta(days=430),
+                tracking_end_date=today - timedelta(days=400),
+                initial_price=100.0,
+                predicted_direction="bullish",
+            )
+        )
+    await db_session.commit()
+
+    response = await client.post(
+        "/api/v1/maintenance/prune",
+        json={"candle_days": 365, "outcome_days": 365},
+        headers=shell_headers,
+    )
+
+    assert response.status_code == 200
+    assert response.json() == {"candles_deleted": 1, "outcomes_deleted": 1}
+    candles = (await db_session.scalars(select(PriceHistory))).all()
+    assert [c.date for c in candles] == [today - timedelta(days=10)]
+    outcomes = (await db_session.scalars(select(InsightOutcome))).all()
+    assert [o.tracking_status for o in outcomes] == ["TRACKING"]
+
+
+async def test_prune_keeps_everything_by_default(
+    client: AsyncClient, db_session, shell_headers, sample_stock_with_prices
+):
+    """A limit of 0 keeps that kind of row regardless of age."""
+
+    response = await client.post(
+        "/api/v1/maintenance/prune",
+        json={},
+        headers=shell_headers,
+    )
+
+    assert response.status_code == 200
+    assert response.json() == {"candles_deleted": 0, "outcomes_deleted": 0}
+
+
+async def test_export_rejects_unknown_table(client: AsyncClient, shell_headers):
+    """Only the insight, outcome and candle tables can be exported."""
+    response = await client.get(
+        "/api/v1/maintenance/export/user_settings",
+        headers=shell_headers,
+    )
+
+    assert response.status_code == 404
+
+
+async def test_export_streams_table_as_csv(
+    client: AsyncClient, shell_headers, monkeypatch, tmp_path
+):
+    """Every row is exported from the database file, after a header row."""
+    path = tmp_path / "market-analyzer.db"
+    conn = sqlite3.connect(path)
+    conn.execute("CREATE TABLE price_history (id INTEGER, date TEXT, close REAL)")
+    conn.executemany(
+        "INSERT INTO price_history VALUES (?, ?, ?)",
+        [(i, f"2024-01-{i:02d}", None if i == 2 else 1.5) for i in range(1, 4)],
+    )
+    conn.commit()
+    conn.close()
+    monkeypatch.setattr("database.engine", create_async_engine(f"sqlite+aiosqlite:///{path}"))
+    monkeypatch.setattr("api.routes.maintenance.EXPORT_BATCH_ROWS", 2)
+
+    response = await client.get(
+        "/api/v1/maintenance/export/candles",
+        headers=shell_headers,
+    )
+
+    assert response.status_code == 200
+    assert response.headers["X-Total-Rows"] == "3"
+    assert response.text.splitlines() == [
+        "id,date,close",
+        "1,2024-01-01,1.5",
+        "2,2024-01-02,",
+        "3,2024-01-03,1.5",
+    ]
+
+
+def _insight_database(path, insights, outcomes):
+    """A database file with just the insight and outcome columns the merge looks at."""
+    conn = sqlite3.connect(path)
+    conn.execute(
+        "CREATE TABLE deep_insights (id INTEGER PRIMARY KEY, title TEXT, "
+        "created_at TEXT, parent_insight_id INTEGER, source_conversation_id INTEGER)"
+    )
+    conn.execute(
+        "CREATE TABLE insight_outcomes (id TEXT PRIMARY KEY, insight_id INTEGER, created_at TEXT)"
+    )
+    conn.executemany("INSERT INTO deep_insights VALUES (?, ?, ?, ?, ?)", insights)
+    conn.executemany("INSERT INTO insight_outcomes VALUES (?, ?, ?)", outcomes)
+    conn.commit()
+    conn.close()
+
+
+async def test_import_merges_new_insights_and_outcomes(
+    client: AsyncClient, shell_headers, monkeypatch, tmp_path
+):
+    """Rows already present are skipped and clashing IDs are renumbered."""
+    target = tmp_path / "market-analyzer.db"
+    source = tmp_path / "other.db"
+    _insight_database(
+        target,
+        [(1, "A", "2024-01-01", None, None), (2, "B", "2024-01-02", None, None)],
+        [("u1", 1, "2024-01-01")],
+    )
+    _insight_database(
+        source,
+        [
+            (1, "A", "2024-01-01", None, 7),
+            (2, "C", "2024-02-01", None, 7),
+            (3, "D", "2024-02-02", 2, None),
+        ],
+        [("u1", 1, "2024-01-01"), ("u2", 2, "2024-02-01"), ("u3", 9, "2024-02-03")],
+    )
+    monkeypatch.setattr(
+        "database.engine", create_async_engine(f"sqlite+aiosqlite:///{target}")
+    )
+    headers = shell_headers
+
+    first = await client.post(
+        "/api/v1/maintenance/import", json={"path": str(source)}, headers=headers
+    )
+    second = await client.post(
+        "/api/v1/maintenance/import", json={"path": str(source)}, headers=headers
+    )
+
+    assert first.status_code == 200
+    assert first.json() == {
+        "insights_imported": 2,
+        "insights_skipped": 1,
+        "outcomes_imported": 1,
+        "outcomes_skipped": 2,
+    }
+    assert second.json()["insights_imported"] == 0
+    assert second.json()["outcomes_imported"] == 0
+    conn = sqlite3.connect(target)
+    assert conn.execute("SELECT * FROM deep_insights ORDER BY id").fetchall() == [
+        (1, "A", "2024-01-01", None, None),
+        (2, "B", "2024-01-02", None, None),
+        (3, "C", "2024-02-01", None, None),
+        (4, "D", "2024-02-02", 3, None),
+    ]
+    assert conn.execute("SELECT id, insight_id FROM insight_outcomes ORDER BY id").fetchall() == [
+        ("u1", 1),
+        ("u2", 3),
+    ]
+    conn.close()
+
+
+async def test_import_rejects_other_files(
+    client: AsyncClient, shell_headers, monkeypatch, tmp_path
+):
+    """A file without the insight tables is refused and nothing changes."""
+    target = tmp_path / "market-analyzer.db"
+    _insight_database(target, [], [])
+    other = tmp_path / "notes.db"
+    conn = sqlite3.connect(other)
+    conn.execute("CREATE TABLE notes (id INTEGER)")
+    conn.close()
+    monkeypatch.setattr(
+        "database.engine", create_async_engine(f"sqlite+aiosqlite:///{target}")
+    )
+
+    response = await client.post(
+        "/api/v1/maintenance/import",
+        json={"path": str(other)},
+        headers=shell_headers,
+    )
+
+    assert response.status_code == 400
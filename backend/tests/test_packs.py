@@ -0,0 +1,115 @@
+"""Tests for prompt and strategy packs and the reload endpoint."""
+
+import json
+
+from httpx import AsyncClient
+
+import packs
+from analysis.confidence_adjuster import ConfidenceAdjuster
+
+
+def _install(tmp_path, monkeypatch, *entries: dict) -> None:
+    """Write each pack and a packs file listing them, as the shell does."""
+    listed = []
+    for pack in entries:
+        path = tmp_path / f"{pack['name']}-{pack['version']}.json"
+        path.write_text(json.dumps(pack))
+        entry = {key: pack[key] for key in ("name", "kind", "version")}
+        listed.append({**entry, "path": str(path)})
+    packs_file = tmp_path / "active.json"
+    packs_file.write_text(json.dumps({"packs": listed}))
+    monkeypatch.setenv(packs.PACKS_FILE_ENV, str(packs_file))
+
+
+async def test_reload_packs_disabled_without_token(client: AsyncClient, monkeypatch):
+    """Without a configured token the endpoint does not exist."""
+    monkeypatch.delenv("TELETRAAN_AUTH_TOKEN", raising=False)
+    monkeypatch.delenv("TELETRAAN_SHELL_TOKEN", raising=False)
+
+    response = await client.post("/api/v1/maintenance/reload-packs")
+
+    assert response.status_code == 404
+
+
+async def test_reload_packs_rejects_wrong_token(client: AsyncClient, monkeypatch):
+    """A mismatched token is refused."""
+    monkeypatch.setenv("TELETRAAN_AUTH_TOKEN", "secret")
+
+    response = await client.post(
+        "/api/v1/maintenance/reload-packs", headers={"X-Teletraan-Token": "nope"}
+    )
+
+    assert response.status_code == 401
+
+
+async def test_reload_packs_uses_new_prompts(
+    client: AsyncClient, tmp_path, shell_headers, monkeypatch
+):
+    """Reloaded prompts replace the built-ins; unknown ones are reported."""
+    _install(
+        tmp_path,
+        monkeypatch,
+        {
+            "name": "core-prompts",
+            "kind": "prompts",
+            "version": "1.1.0",
+            "prompts": {"technical": "Read the charts.", "poetry": "Write a sonnet."},
+        },
+    )
+
+    response = await client.post(
+        "/api/v1/maintenance/reload-packs", headers=shell_headers
+    )
+
+    assert response.status_code == 200
+    data = response.json()
+    assert data["packs"] == [{"name": "core-prompts", "kind": "prompts", "version": "1.1.0"}]
+    assert len(data["errors"]) == 1 and "poetry" in data["errors"][0]
+    assert packs.get_prompt("technical", "built-in") == "Read the charts."
+    assert packs.get_prompt("macro", "built-in") == "built-in"
+
+    monkeypatch.delenv(packs.PACKS_FILE_ENV)
+    packs.reload()
+    assert packs.get_prompt("technical", "built-in") == "built-in"
+
+
+def test_pack_not_matching_its_entry_is_skipped(tmp_path, monkeypatch):
+    """A pack file whose version differs from the packs file is not used."""
+    _install(
+        tmp_path,
+        monkeypatch,
+        {"name": "core-prompts", "kind": "prompts", "version": "1.1.0", "prompts": {"risk": "x"}},
+    )
+    listed = json.loads((tmp_path / "active.json").read_text())
+    listed["packs"][0]["version"] = "1.2.0"
+    (tmp_path / "active.json").write_text(json.dumps(listed))
+
+    report = packs.reload()
+
+    assert report["packs"] == []
+    assert "version" in report["errors"][0]
+    assert packs.get_prompt("risk", "built-in") == "built-in"
+    monkeypatch.delenv(packs.PACKS_FILE_ENV)
+    packs.reload()
+
+
+def test_strategy_pack_overrides_confidence_settings(tmp_path, monkeypatch):
+    """Valid confidence settings apply to new adjusters; invalid ones are ignored."""
+    _install(
+        tmp_path,
+        monkeypatch,
+        {
+            "name": "conservative",
+            "kind": "strategy",
+            "version": "2.0.0",
+            "strategy": {"confidence": {"max_confidence": 0.8, "base_weight": "high"}},
+        },
+    )
+    packs.reload()
+
+    adjuster = ConfidenceAdjuster(None, None)
+
+    assert adjuster.MAX_CONFIDENCE == 0.8
+    assert adjuster.BASE_WEIGHT == ConfidenceAdjuster.BASE_WEIGHT
+    monkeypatch.delenv(packs.PACKS_FILE_ENV)
+    packs.reload()
@@ -368,3 +368,132 @@ async def test_portfolio_impact_with_matching_insights(
 
     # Since both are bearish, overall_bearish_exposure should be > 0
     assert body["overall_bearish_exposure"] > 0  # noqa: S101
+
+
+# ---------------------------------------------------------------------------
+# Accounts
+# ---------------------------------------------------------------------------
+
+
+async def _seed_account(
+    db: AsyncSession,
+    name: str,
+    account_type: str = "taxable",
+    max_position_pct: float | None = None,
+    **holdings: tuple[float, float],
+) -> Portfolio:
+    """Insert an account with ``symbol=(shares, cost_basis)`` holdings."""
+    account = Portfolio(name=name, account_type=account_type, max_position_pct=max_position_pct)
+    db.add(account)
+    await db.commit()
+    await db.refresh(account)
+
+    for symbol, (shares, cost_basis) in holdings.items():
+        db.add(PortfolioHolding(
+            portfolio_id=account.id, symbol=symbol, shares=shares, cost_basis=cost_basis,
+        ))
+    await db.commit()
+    # Reloaded by the route with its holdings, see the impact test above
+    db.expire(account)
+    return account
+
+
+async def test_holdings_are_kept_per_account(client: AsyncClient, db_session: AsyncSession):
+    """A holding added to one account does not show in the default account."""
+    default = await _seed_account(db_session, "My Portfolio")
+
+    resp = await client.post(
+        "/api/v1/portfolio/accounts", json={"name": "Retirement", "account_type": "ira"}
+    )
+    assert resp.status_code == 200  # noqa: S101
+    ira = resp.json()
+    assert ira["account_type"] == "ira"  # noqa: S101
+
+    resp = await client.post(
+        "/api/v1/portfolio/holdings",
+        json={"symbol": "VTI", "shares": 4, "cost_basis": 200.0, "account_id": ira["id"]},
+    )
+    assert resp.status_code == 200  # noqa: S101
+    assert resp.json()["portfolio_id"] == ira["id"]  # noqa: S101
+    db_session.expire_all()
+
+    with _patch_yfinance_price(250.0):
+        default_body = (await client.get("/api/v1/portfolio")).json()
+        ira_body = (await client.get(f"/api/v1/portfolio?account_id={ira['id']}")).json()
+        accounts = (await client.get("/api/v1/portfolio/accounts")).json()
+
+    assert default_body["id"] == default.id  # noqa: S101
+    assert default_body["holdings"] == []  # noqa: S101
+    assert [h["symbol"] for h in ira_body["holdings"]] == ["VTI"]  # noqa: S101
+    assert [a["id"] for a in accounts] == [default.id, ira["id"]]  # noqa: S101
+    assert accounts[1]["total_value"] == pytest.approx(1000.0)  # noqa: S101
+
+
+async def test_unknown_account_is_not_found(client: AsyncClient, db_session: AsyncSession):
+    """Holdings cannot be added to an account that does not exist."""
+    resp = await client.post(
+        "/api/v1/portfolio/holdings",
+        json={"symbol": "AAPL", "shares": 1, "cost_basis": 100.0, "account_id": 999},
+    )
+    assert resp.status_code == 404  # noqa: S101
+
+
+async def test_consolidated_merges_accounts_without_paper(
+    client: AsyncClient, db_session: AsyncSession
+):
+    """Positions are merged by symbol; paper accounts only count when asked for."""
+    taxable = await _seed_account(db_session, "Brokerage", AAPL=(10.0, 150.0))
+    ira = await _seed_account(db_session, "IRA", "ira", AAPL=(10.0, 170.0), MSFT=(5.0, 300.0))
+    await _seed_account(db_session, "Paper", "paper", AAPL=(100.0, 100.0))
+
+    with _patch_yfinance_price(200.0):
+        body = (await client.get("/api/v1/portfolio/consolidated")).json()
+        with_paper = (
+            await client.get("/api/v1/portfolio/consolidated?include_paper=true")
+        ).json()
+
+    aapl = next(h for h in body["holdings"] if h["symbol"] == "AAPL")
+    assert aapl["shares"] == pytest.approx(20.0)  # noqa: S101
+    assert aapl["cost_basis"] == pytest.approx(160.0)  # noqa: S101
+    assert [a["account_id"] for a in aapl["accounts"]] == [taxable.id, ira.id]  # noqa: S101
+    assert body["total_value"] == pytest.approx(5000.0)  # noqa: S101
+    assert aapl["allocation_pct"] == pytest.approx(80.0)  # noqa: S101
+    assert body["value_by_account_type"] == {  # noqa: S101
+        "taxable": pytest.approx(2000.0),
+        "ira": pytest.approx(3000.0),
+    }
+
+    aapl_with_paper = next(h for h in with_paper["holdings"] if h["symbol"] == "AAPL")
+    assert aapl_with_paper["shares"] == pytest.approx(120.0)  # noqa: S101
+    assert len(with_paper["accounts"]) == 3  # noqa: S101
+
+
+async def test_position_limit_breaches_are_reported(
+    client: AsyncClient, db_session: AsyncSession
+):
+    """Holdings over the account's position limit are listed until the limit is cleared."""
+    account = await _seed_account(
+        db_session, "Concentrated", max_position_pct=50.0, AAPL=(3.0, 100.0), MSFT=(1.0, 100.0),
+    )
+
+    with _patch_yfinance_price(100.0):
+        body = (await client.get("/api/v1/portfolio")).json()
+        consolidated = (await client.get("/api/v1/portfolio/consolidated")).json()
+
+    assert body["limit_breaches"] == [{  # noqa: S101
+        "account_id": account.id,
+        "symbol": "AAPL",
+        "allocation_pct": pytest.approx(75.0),
+        "max_position_pct": 50.0,
+    }]
+    assert len(consolidated["limit_breaches"]) == 1  # noqa: S101
+
+    with _patch_yfinance_price(100.0):
+        resp = await client.put(
+            f"/api/v1/portfolio/accounts/{account.id}", json={"max_position_pct": None}
+        )
+
+    assert resp.status_code == 200  # noqa: S101
+    assert resp.json()["max_position_pct"] is None  # noqa: S101
+    assert resp.json()["limit_breaches"] == []  # noqa: S101
+    assert resp.json()["name"] == "Concentrated"  # noqa: S101
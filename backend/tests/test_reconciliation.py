@@ -0,0 +1,131 @@
+"""Tests for the end-of-day reconciliation."""
+
+from datetime import date, datetime
+
+from httpx import AsyncClient
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from analysis.engine import analysis_engine
+from analysis.reconciliation import EndOfDayReconciler
+from data.adapters.yahoo import YahooFinanceAdapter
+from models.insight import Insight
+from models.portfolio import Portfolio, PortfolioHolding
+
+AS_OF = date(2026, 1, 9)
+
+
+def _patch_provider(monkeypatch, closes: dict[date, float]) -> None:
+    """Make the provider return a bar with each of ``closes``."""
+
+    async def get_price_history(self, symbol, start_date=None, end_date=None, period="1y"):
+        return [{"date": day, "close": close} for day, close in sorted(closes.items())]
+
+    monkeypatch.setattr(YahooFinanceAdapter, "get_price_history", get_price_history)
+
+
+def _patch_rerun(monkeypatch, *titles: str) -> None:
+    """Make the analysis rerun raise anomalies with ``titles``."""
+
+    async def analyze_prices(stock_id, symbol, price_data):
+        return {}, [
+            Insight(stock_id=stock_id, insight_type="anomaly", title=title,
+                    description="", severity="alert", confidence=0.9)
+            for title in titles
+        ]
+
+    monkeypatch.setattr(analysis_engine, "analyze_prices", analyze_prices)
+
+
+def _kinds(section: dict) -> list[tuple[str, str | None]]:
+    return [(d["kind"], d.get("date")) for d in section["discrepancies"]]
+
+
+async def test_cached_closes_are_compared_with_the_provider(
+    db_session: AsyncSession, sample_stock_with_prices, monkeypatch
+):
+    """Mismatched, missing and unconfirmed bars are all reported."""
+    _patch_provider(monkeypatch, {
+        date(2026, 1, 2): 183.0,  # not cached
+        date(2026, 1, 5): 184.0,
+        date(2026, 1, 6): 190.0,  # cached 186.0
+        # 2026-01-07 cached but not at the provider
+        date(2026, 1, 8): 187.5,  # within the tolerance of 187.0
+        date(2026, 1, 9): 189.0,
+    })
+    _patch_rerun(monkeypatch)
+
+    report = await EndOfDayReconciler(db_session).run(AS_OF)
+
+    prices = report.report["prices"]
+    assert prices["symbols_checked"] == 1
+    assert prices["bars_checked"] == 4
+    assert _kinds(prices) == [
+        ("missing_bar", "2026-01-02"),
+        ("close_mismatch", "2026-01-06"),
+        ("unconfirmed_bar", "2026-01-07"),
+    ]
+    assert prices["discrepancies"][1]["diff_pct"] == 2.105
+    assert report.discrepancy_count == 3
+
+
+async def test_alerts_the_bars_no_longer_raise_are_reported(
+    db_session: AsyncSession, sample_stock_with_prices, monkeypatch
+):
+    """Only the alert the rerun does not raise again is a discrepancy."""
+    raised_at = datetime(2026, 1, 9, 23, 0)
+    for title in ("Volume Spike", "Price Gap"):
+        db_session.add(Insight(
+            stock_id=sample_stock_with_prices.id, insight_type="anomaly", title=title,
+            description="", severity="alert", confidence=0.9, created_at=raised_at,
+        ))
+    await db_session.commit()
+    _patch_provider(monkeypatch, {})
+    _patch_rerun(monkeypatch, "Volume Spike")
+
+    report = await EndOfDayReconciler(db_session).run(AS_OF)
+
+    alerts = report.report["alerts"]
+    assert alerts["alerts_checked"] == 2
+    assert [(d["kind"], d["title"]) for d in alerts["discrepancies"]] == [
+        ("alert_not_reproduced", "Price Gap"),
+    ]
+
+
+async def test_holdings_are_checked_without_a_broker(db_session: AsyncSession, monkeypatch):
+    """The broker comparison is skipped, but impossible holdings are reported."""
+    portfolio = Portfolio(name="My Portfolio")
+    db_session.add(portfolio)
+    await db_session.commit()
+    db_session.add_all([
+        PortfolioHolding(portfolio_id=portfolio.id, symbol="AAPL", shares=10.0, cost_basis=150.0),
+        PortfolioHolding(portfolio_id=portfolio.id, symbol="MSFT", shares=0.0, cost_basis=300.0),
+    ])
+    await db_session.commit()
+
+    section = await EndOfDayReconciler(db_session).check_portfolio()
+
+    assert section["broker"] == "not_configured"
+    assert section["holdings_checked"] == 2
+    assert [d["symbol"] for d in section["discrepancies"]] == ["MSFT"]
+
+
+async def test_reports_are_served_after_a_run(
+    client: AsyncClient, sample_stock_with_prices, monkeypatch
+):
+    """A report produced on demand is the latest one served."""
+    missing = await client.get("/api/v1/data/reconciliation")
+    assert missing.status_code == 404
+
+    _patch_provider(monkeypatch, {date(2026, 1, 9): 189.0})
+    _patch_rerun(monkeypatch)
+
+    run = await client.post("/api/v1/data/reconciliation", json={"as_of": "2026-01-09"})
+    assert run.status_code == 200
+    assert run.json()["as_of"] == "2026-01-09"
+
+    latest = await client.get("/api/v1/data/reconciliation")
+    assert latest.json()["id"] == run.json()["id"]
+    assert set(latest.json()["report"]) == {"portfolio", "prices", "alerts"}
+
+    other_day = await client.get("/api/v1/data/reconciliation?as_of=2026-01-08")
+    assert other_day.status_code == 404
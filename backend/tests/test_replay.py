@@ -0,0 +1,87 @@
+"""Tests for the historical replay of the analysis."""
+
+import json
+from datetime import date
+
+from httpx import AsyncClient
+
+from analysis import replay as replay_module
+from analysis.engine import analysis_engine
+from models.insight import Insight
+
+
+async def _collect(events) -> list[dict]:
+    return [event async for event in events]
+
+
+async def test_each_day_only_sees_the_bars_up_to_it(sample_price_data, monkeypatch):
+    """No bar after the replayed day reaches the analysis."""
+    seen: list[tuple[date, int]] = []
+
+    async def analyze_prices(stock_id, symbol, price_data):
+        seen.append((price_data[-1]["date"], len(price_data)))
+        alert = Insight(
+            stock_id=stock_id,
+            insight_type="anomaly",
+            title="Volume Spike",
+            description=f"{symbol} traded heavily",
+            severity="alert",
+            confidence=0.9,
+        )
+        return {"overall_signal": "bullish", "confidence": 0.8}, [alert]
+
+    monkeypatch.setattr(analysis_engine, "analyze_prices", analyze_prices)
+    series = [replay_module.ReplaySeries(stock_id=1, symbol="AAPL", bars=sample_price_data)]
+
+    events = await _collect(
+        replay_module.replay(series, date(2026, 1, 7), date(2026, 1, 8))
+    )
+
+    assert seen == [(date(2026, 1, 7), 3), (date(2026, 1, 8), 4)]
+    assert [e["type"] for e in events] == ["bar", "bar", "summary"]
+    assert events[0]["close"] == 185.0
+    assert events[0]["alerts"][0]["severity"] == "alert"
+    assert events[-1]["days"] == 2
+    assert events[-1]["alerts_by_severity"] == {"alert": 2}
+
+
+async def test_replay_streams_ndjson(client: AsyncClient, sample_stock_with_prices):
+    """The route streams a bar per stored day and a summary, without storing insights."""
+    response = await client.post(
+        "/api/v1/analysis/replay",
+        json={
+            "symbols": ["aapl", "MSFT"],
+            "start_date": "2026-01-05",
+            "end_date": "2026-01-09",
+        },
+    )
+
+    assert response.status_code == 200
+    assert response.headers["content-type"].startswith("application/x-ndjson")
+    events = [json.loads(line) for line in response.text.splitlines()]
+    summary = events[-1]
+    assert summary["type"] == "summary"
+    assert summary["days"] == 5
+    assert summary["missing_symbols"] == ["MSFT"]
+    assert len(events) - 1 + len(summary["errors"]) == 5
+
+    insights = await client.get("/api/v1/insights")
+    assert insights.json()["total"] == 0
+
+
+async def test_replay_without_data_is_not_found(client: AsyncClient, sample_stock_with_prices):
+    response = await client.post(
+        "/api/v1/analysis/replay",
+        json={"symbols": ["AAPL"], "start_date": "2025-01-02", "end_date": "2025-01-31"},
+    )
+
+    assert response.status_code == 404
+
+
+async def test_replay_rejects_a_reversed_period(client: AsyncClient):
+    response = await client.post(
+        "/api/v1/analysis/replay",
+        json={"symbols": ["AAPL"], "start_date": "2026-01-09", "end_date": "2026-01-05"},
+    )
+
+    assert response.status_code == 422
@@ -0,0 +1,66 @@
+"""Tests for the review gate on outcome tracking."""
+
+import json
+
+from httpx import AsyncClient
+
+import review
+
+
+def _queue(tmp_path, monkeypatch, **statuses: str) -> None:
+    """Write a review queue with the given status per insight id, as the shell does."""
+    queue = {
+        insight_id: {"insight_id": int(insight_id), "status": status, "insight": {}}
+        for insight_id, status in statuses.items()
+    }
+    queue_file = tmp_path / "review_queue.json"
+    queue_file.write_text(json.dumps(queue))
+    monkeypatch.setenv(review.REVIEW_FILE_ENV, str(queue_file))
+
+
+def test_everything_is_approved_without_review(monkeypatch):
+    """Without the variable the gate is open."""
+    monkeypatch.delenv(review.REVIEW_FILE_ENV, raising=False)
+
+    assert not review.required()
+    assert review.is_approved(1)
+
+
+def test_only_approved_insights_pass(tmp_path, monkeypatch):
+    """Pending, rejected and unknown insights are held back."""
+    _queue(tmp_path, monkeypatch, **{"1": "approved", "2": "pending", "3": "rejected"})
+
+    assert review.required()
+    assert review.is_approved(1)
+    assert not review.is_approved(2)
+    assert not review.is_approved(3)
+    assert not review.is_approved(4)
+
+
+def test_unreadable_queue_approves_nothing(tmp_path, monkeypatch):
+    """A missing or corrupt queue fails closed."""
+    monkeypatch.setenv(review.REVIEW_FILE_ENV, str(tmp_path / "missing.json"))
+    assert not review.is_approved(1)
+
+    queue_file = tmp_path / "review_queue.json"
+    queue_file.write_text("{not json")
+    monkeypatch.setenv(review.REVIEW_FILE_ENV, str(queue_file))
+    assert not review.is_approved(1)
+
+
+async def test_start_tracking_refuses_unapproved_insight(
+    client: AsyncClient, sample_deep_insight, tmp_path, monkeypatch
+):
+    """The track record only takes insights the reviewer approved."""
+    _queue(tmp_path, monkeypatch, **{str(sample_deep_insight.id): "pending"})
+
+    response = await client.post(
+        "/api/v1/outcomes/start",
+        json={
+            "insight_id": sample_deep_insight.id,
+            "symbol": "NVDA",
+            "predicted_direction": "bullish",
+        },
+    )
+
+    assert response.status_code == 409
@@ -0,0 +1,42 @@
+"""Tests for the cooperative shutdown endpoint."""
+
+from unittest.mock import patch
+
+from httpx import AsyncClient
+
+
+async def test_shutdown_disabled_without_token(client: AsyncClient, monkeypatch):
+    """Without a configured token the endpoint does not exist."""
+    monkeypatch.delenv("TELETRAAN_AUTH_TOKEN", raising=False)
+    monkeypatch.delenv("TELETRAAN_SHELL_TOKEN", raising=False)
+
+    response = await client.post("/api/v1/shutdown")
+
+    assert response.status_code == 404
+
+
+async def test_shutdown_rejects_wrong_token(client: AsyncClient, monkeypatch):
+    """A missing or mismatched token is refused and no shutdown is scheduled."""
+    monkeypatch.setenv("TELETRAAN_AUTH_TOKEN", "secret")
+
+    with patch("api.routes.shutdown._request_graceful_exit") as exit_mock:
+        missing = await client.post("/api/v1/shutdown")
+        wrong = await client.post(
+            "/api/v1/shutdown", headers={"X-Teletraan-Token": "nope"}
+        )
+
+    assert missing.status_code == 401
+    assert wrong.status_code == 401
+    exit_mock.assert_not_called()
+
+
+async def test_shutdown_accepts_token(client: AsyncClient, shell_headers):
+    """The correct token is acknowledged with the number of running analyses."""
+    with patch("api.routes.shutdown.asyncio.get_running_loop") as loop_mock:
+        response = await client.post("/api/v1/shutdown", headers=shell_headers)
+
+    assert response.status_code == 202
+    data = response.json()
+    assert data["status"] == "shutting_down"
+    assert data["active_analysis_tasks"] == 0
+    loop_mock.return_value.call_later.assert_called_once()
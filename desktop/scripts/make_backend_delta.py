@@ -0,0 +1,157 @@
+#!/usr/bin/env python3
+"""Build a delta between two PyInstaller backend folders for the updater.
+
+    make_backend_delta.py OLD_DIR NEW_DIR OUT.tar.gz --from 1.2.0 --to 1.3.0
+
+OLD_DIR and NEW_DIR are `teletraan-backend` folders as bundled with the app.
+Unchanged files are listed to be copied from the installed backend, changed
+files are shipped as bsdiff patches when `bsdiff4` is installed and the patch
+is smaller, and everything else is shipped whole. The shell applies the delta
+and checks every file against the SHA-256 recorded here (see
+`desktop/src-tauri/src/delta.rs`).
+
+Sign the result like a full backend bundle, with `tauri signer sign`.
+"""
+
+import argparse
+import bz2
+import gzip
+import hashlib
+import io
+import json
+import os
+import struct
+import sys
+import tarfile
+from pathlib import Path
+
+# Skipped by the shell's integrity check too.
+IGNORED_NAMES = {"__pycache__", ".DS_Store"}
+
+# A patch is only used when it is at most this fraction of the gzipped file.
+MAX_PATCH_RATIO = 0.8
+
+try:
+    import bsdiff4
+except ImportError:  # Changed files are then shipped whole.
+    bsdiff4 = None
+
+
+def list_files(root: Path) -> dict[str, Path]:
+    files = {}
+    for dirpath, dirnames, filenames in os.walk(root):
+        dirnames[:] = sorted(d for d in dirnames if d not in IGNORED_NAMES)
+        for name in sorted(filenames):
+            if name in IGNORED_NAMES:
+                continue
+            path = Path(dirpath) / name
+            files[path.relative_to(root).as_posix()] = path
+    return files
+
+
+def sha256(path: Path) -> str:
+    digest = hashlib.sha256()
+    with path.open("rb") as f:
+        for chunk in iter(lambda: f.read(1024 * 1024), b""):
+            digest.update(chunk)
+    return digest.hexdigest()
+
+
+def offtout(value: int) -> bytes:
+    """bsdiff's sign-and-magnitude little-endian integer."""
+    data = bytearray(struct.pack("<q", abs(value)))
+    if value < 0:
+        data[7] |= 0x80
+    return bytes(data)
+
+
+def offtin(data: bytes) -> int:
+    value = struct.unpack("<q", bytes(data[:7]) + bytes([data[7] & 0x7F]))[0]
+    return -value if data[7] & 0x80 else value
+
+
+def to_gzip_patch(patch: bytes) -> bytes:
+    """Turn a `BSDIFF40` patch into the `BSDIFFGZ` one the shell reads: the
+    same blocks, recompressed with gzip."""
+    if patch[:8] != b"BSDIFF40":
+        raise ValueError("not a BSDIFF40 patch")
+    ctrl_len, diff_len, new_size = (offtin(patch[i : i + 8]) for i in (8, 16, 24))
+    body = patch[32:]
+    blocks = [
+        body[:ctrl_len],
+        body[ctrl_len : ctrl_len + diff_len],
+        body[ctrl_len + diff_len :],
+    ]
+    ctrl, diff, extra = (gzip.compress(bz2.decompress(block), mtime=0) for block in blocks)
+    header = b"BSDIFFGZ" + offtout(len(ctrl)) + offtout(len(diff)) + offtout(new_size)
+    return header + ctrl + diff + extra
+
+
+def add_bytes(tar: tarfile.TarFile, name: str, data: bytes, mode: int = 0o644) -> None:
+    info = tarfile.TarInfo(name)
+    info.size = len(data)
+    info.mode = mode
+    tar.addfile(info, io.BytesIO(data))
+
+
+def main() -> int:
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[0])
+    parser.add_argument("old_dir", type=Path)
+    parser.add_argument("new_dir", type=Path)
+    parser.add_argument("output", type=Path)
+    parser.add_argument("--from", dest="from_version", required=True)
+    parser.add_argument("--to", dest="to_version", required=True)
+    args = parser.parse_args()
+
+    old_files = list_files(args.old_dir)
+    new_files = list_files(args.new_dir)
+    old_hashes = {name: sha256(path) for name, path in old_files.items()}
+    by_hash = {digest: name for name, digest in old_hashes.items()}
+    if bsdiff4 is None:
+        print("bsdiff4 is not installed; changed files are shipped whole", file=sys.stderr)
+
+    entries = []
+    counts = {"copy": 0, "patch": 0, "add": 0}
+    shipped = 0
+    with tarfile.open(args.output, "w:gz", format=tarfile.PAX_FORMAT) as tar:
+        for name, path in new_files.items():
+            digest = sha256(path)
+            entry = {"path": name, "sha256": digest}
+            if old_hashes.get(name) == digest:
+                entry["source"] = "copy"
+            elif digest in by_hash:
+                entry.update(source="copy", **{"from": by_hash[digest]})
+            else:
+                data = path.read_bytes()
+                patch = None
+                if bsdiff4 is not None and name in old_files:
+                    patch = to_gzip_patch(bsdiff4.diff(old_files[name].read_bytes(), data))
+                    if len(patch) > MAX_PATCH_RATIO * len(gzip.compress(data, mtime=0)):
+                        patch = None
+                if patch is not None:
+                    entry["source"] = "patch"
+                    add_bytes(tar, f"patches/{name}.bsdiff", patch)
+                    shipped += len(patch)
+                else:
+                    entry["source"] = "add"
+                    add_bytes(tar, f"files/{name}", data, path.stat().st_mode & 0o777)
+                    shipped += len(data)
+            counts[entry["source"]] += 1
+            entries.append(entry)
+
+        delta = {
+            "from_version": args.from_version,
+            "to_version": args.to_version,
+            "files": entries,
+        }
+        add_bytes(tar, "delta.json", json.dumps(delta, indent=2).encode())
+
+    print(
+        f"{args.output}: {counts['copy']} copied, {counts['patch']} patched, "
+        f"{counts['add']} added ({shipped / (1024 * 1024):.1f} MiB before compression)"
+    )
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
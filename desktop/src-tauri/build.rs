@@ -1,3 +1,77 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use std::process::Command;
+
+use sha2::{Digest, Sha256};
+
 fn main() {
+    // Commit the shell was built from, reported by `get_versions`. CI can set
+    // TELETRAAN_BUILD_HASH; local builds ask git.
+    println!("cargo:rerun-if-env-changed=TELETRAAN_BUILD_HASH");
+    let hash = std::env::var("TELETRAAN_BUILD_HASH").ok().or_else(|| {
+        let output = Command::new("git")
+            .args(["rev-parse", "--short=12", "HEAD"])
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    });
+    if let Some(hash) = hash.filter(|h| !h.is_empty()) {
+        println!("cargo:rustc-env=TELETRAAN_BUILD_HASH={hash}");
+    }
+
+    write_backend_manifest();
+
     tauri_build::build()
 }
+
+/// The SHA-256 of every file of the bundled backend, written to
+/// `backend_manifest.rs` for `integrity` to check before each spawn. Empty
+/// when the backend has not been built, as in development.
+fn write_backend_manifest() {
+    let dir = Path::new("resources").join("teletraan-backend");
+    println!("cargo:rerun-if-changed={}", dir.display());
+    let mut files = Vec::new();
+    collect_files(&dir, &dir, &mut files);
+    files.sort();
+
+    let mut source = String::from("&[\n");
+    for (name, path) in files {
+        let bytes = std::fs::read(&path).unwrap_or_else(|e| panic!("read {}: {e}", path.display()));
+        let hash = hex::encode(Sha256::digest(&bytes));
+        writeln!(source, "    ({name:?}, {hash:?}),").unwrap();
+    }
+    source.push_str("]\n");
+    let out = Path::new(&std::env::var("OUT_DIR").unwrap()).join("backend_manifest.rs");
+    std::fs::write(out, source).unwrap();
+}
+
+/// Files under `dir` as (`/`-separated path relative to `root`, path).
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<(String, std::path::PathBuf)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        // Left by Finder or a Python run from the folder; `integrity`
+        // ignores them too.
+        if matches!(
+            entry.file_name().to_str(),
+            Some("__pycache__" | ".DS_Store")
+        ) {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(root, &path, files);
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let name = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push((name, path));
+        }
+    }
+}
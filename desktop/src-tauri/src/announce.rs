@@ -0,0 +1,340 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::review;
+use crate::settings;
+
+/// A tag that was notified within this window is not notified again.
+const DEDUP_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// At most `RATE_LIMIT` notifications are posted per `RATE_WINDOW`.
+const RATE_LIMIT: usize = 5;
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Recently posted `notify` notifications.
+static RECENT: Mutex<Recent> = Mutex::new(Recent {
+    tags: None,
+    posted: VecDeque::new(),
+});
+
+struct Recent {
+    /// When each tag was last notified.
+    tags: Option<HashMap<String, Instant>>,
+    posted: VecDeque<Instant>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// User preferences for how announcements are delivered, the
+/// `[announcements]` section of `settings.toml`.
+///
+/// Each `*_sound` is the platform sound name to play with the native
+/// notification for that severity (`"default"` uses the system sound);
+/// `None` keeps that severity silent.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnnouncementSettings {
+    /// Also post a native OS notification (which VoiceOver, Narrator, and
+    /// Orca read aloud) rather than only the in-app toast.
+    pub native: bool,
+    pub info_sound: Option<String>,
+    pub warning_sound: Option<String>,
+    pub critical_sound: Option<String>,
+}
+
+impl Default for AnnouncementSettings {
+    fn default() -> Self {
+        Self {
+            native: true,
+            info_sound: None,
+            warning_sound: None,
+            critical_sound: Some("default".into()),
+        }
+    }
+}
+
+impl AnnouncementSettings {
+    fn sound_for(&self, severity: Severity) -> Option<&str> {
+        match severity {
+            Severity::Info => self.info_sound.as_deref(),
+            Severity::Warning => self.warning_sound.as_deref(),
+            Severity::Critical => self.critical_sound.as_deref(),
+        }
+    }
+}
+
+/// Payload of the `accessibility-announcement` event. The frontend mirrors it
+/// into an ARIA live region (`assertive` for critical) next to the toast.
+#[derive(Clone, Serialize)]
+struct Announcement<'a> {
+    severity: Severity,
+    title: &'a str,
+    message: &'a str,
+}
+
+fn load_settings(app: &AppHandle) -> AnnouncementSettings {
+    settings::current(app).announcements
+}
+
+/// Announce an event through every channel a user might be relying on: the
+/// in-app live region, the platform's screen reader announcement and, unless
+/// disabled, a native notification with the severity's sound cue.
+pub fn announce(app: &AppHandle, severity: Severity, title: &str, message: &str) {
+    let _ = app.emit(
+        "accessibility-announcement",
+        Announcement {
+            severity,
+            title,
+            message,
+        },
+    );
+    if let Some(window) = app.get_webview_window("main") {
+        let text = format!("{title}: {message}");
+        let speaker = window.clone();
+        if let Err(e) = window.run_on_main_thread(move || speak(&speaker, severity, &text)) {
+            log::warn!("Failed to post accessibility announcement: {e}");
+        }
+    }
+
+    let settings = load_settings(app);
+    if !settings.native {
+        return;
+    }
+
+    let mut notification = app.notification().builder().title(title).body(message);
+    if let Some(sound) = settings.sound_for(severity) {
+        notification = notification.sound(sound);
+    }
+    if let Err(e) = notification.show() {
+        log::warn!("Failed to post native announcement: {e}");
+    }
+}
+
+/// Have VoiceOver read `text` out, with a priority by severity, through an
+/// `NSAccessibilityAnnouncementRequestedNotification` from the app.
+#[cfg(target_os = "macos")]
+fn speak(_window: &tauri::WebviewWindow, severity: Severity, text: &str) {
+    use objc2::rc::Retained;
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+    use objc2_foundation::NSString;
+
+    #[link(name = "AppKit", kind = "framework")]
+    extern "C" {
+        static NSAccessibilityAnnouncementRequestedNotification: &'static NSString;
+        static NSAccessibilityAnnouncementKey: &'static NSString;
+        static NSAccessibilityPriorityKey: &'static NSString;
+        fn NSAccessibilityPostNotificationWithUserInfo(
+            element: *mut AnyObject,
+            notification: &NSString,
+            user_info: *mut AnyObject,
+        );
+    }
+
+    // NSAccessibilityPriorityLevel
+    let priority: isize = match severity {
+        Severity::Info => 10,
+        Severity::Warning => 50,
+        Severity::Critical => 90,
+    };
+    // SAFETY: on the main thread, as AppKit requires; the keys are AppKit's
+    // own constants and every object outlives the call.
+    unsafe {
+        let app: *mut AnyObject = msg_send![class!(NSApplication), sharedApplication];
+        let announcement = NSString::from_str(text);
+        let priority: Retained<AnyObject> =
+            msg_send![class!(NSNumber), numberWithInteger: priority];
+        let objects: [*const AnyObject; 2] = [
+            Retained::as_ptr(&announcement).cast(),
+            Retained::as_ptr(&priority),
+        ];
+        let keys: [*const AnyObject; 2] = [
+            (NSAccessibilityAnnouncementKey as *const NSString).cast(),
+            (NSAccessibilityPriorityKey as *const NSString).cast(),
+        ];
+        let user_info: *mut AnyObject = msg_send![
+            class!(NSDictionary),
+            dictionaryWithObjects: objects.as_ptr(),
+            forKeys: keys.as_ptr(),
+            count: objects.len()
+        ];
+        NSAccessibilityPostNotificationWithUserInfo(
+            app,
+            NSAccessibilityAnnouncementRequestedNotification,
+            user_info,
+        );
+    }
+}
+
+/// Have Narrator read `text` out through a UI Automation notification event
+/// from the window; a critical one interrupts what it is reading.
+#[cfg(windows)]
+fn speak(window: &tauri::WebviewWindow, severity: Severity, text: &str) {
+    use windows::core::BSTR;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Accessibility::{
+        NotificationKind_Other, NotificationProcessing_All, NotificationProcessing_ImportantAll,
+        UiaHostProviderFromHwnd, UiaRaiseNotificationEvent,
+    };
+
+    let Ok(hwnd) = window.hwnd() else {
+        return;
+    };
+    let processing = match severity {
+        Severity::Critical => NotificationProcessing_ImportantAll,
+        Severity::Info | Severity::Warning => NotificationProcessing_All,
+    };
+    // SAFETY: `hwnd` is the live main window, and the provider is released
+    // when it is dropped.
+    let result = unsafe {
+        UiaHostProviderFromHwnd(HWND(hwnd.0)).and_then(|provider| {
+            UiaRaiseNotificationEvent(
+                &provider,
+                NotificationKind_Other,
+                processing,
+                &BSTR::from(text),
+                &BSTR::from("teletraan-announcement"),
+            )
+        })
+    };
+    if let Err(e) = result {
+        log::warn!("Failed to raise UI Automation notification: {e}");
+    }
+}
+
+/// Have Orca read `text` out through the ATK `announcement` signal of the
+/// window, which the AT-SPI bridge forwards as `Object:Announcement`. ATK
+/// before 2.46 has no such signal; the notification still reaches Orca.
+#[cfg(target_os = "linux")]
+fn speak(window: &tauri::WebviewWindow, _severity: Severity, text: &str) {
+    use gtk::glib::subclass::SignalId;
+    use gtk::prelude::*;
+
+    let Some(accessible) = window.gtk_window().ok().and_then(|w| w.accessible()) else {
+        return;
+    };
+    if SignalId::lookup("announcement", accessible.type_()).is_some() {
+        accessible.emit_by_name::<()>("announcement", &[&text]);
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows, target_os = "linux")))]
+fn speak(_window: &tauri::WebviewWindow, _severity: Severity, _text: &str) {}
+
+/// Tauri command: announce a frontend-originated event (e.g. a high-severity alert).
+#[tauri::command]
+pub async fn announce_event(
+    app: AppHandle,
+    severity: Severity,
+    title: String,
+    message: String,
+) -> Result<(), String> {
+    announce(&app, severity, &title, &message);
+    Ok(())
+}
+
+/// Tauri command: current announcement preferences.
+#[tauri::command]
+pub async fn get_announcement_settings(app: AppHandle) -> Result<AnnouncementSettings, String> {
+    Ok(load_settings(&app))
+}
+
+/// Tauri command: replace announcement preferences, which saves them to
+/// `settings.toml` like `set_setting`.
+#[tauri::command]
+pub async fn set_announcement_settings(
+    app: AppHandle,
+    settings: AnnouncementSettings,
+) -> Result<(), String> {
+    settings::update(&app, |current| current.announcements = settings).map(|_| ())
+}
+
+/// Result of `post_notification`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyOutcome {
+    Shown,
+    /// The same tag was notified in the last 15 minutes.
+    Duplicate,
+    /// Five notifications were already posted in the last minute.
+    RateLimited,
+    /// Native notifications are turned off in the announcement settings.
+    Disabled,
+    /// The insight it is about has not been approved in review yet.
+    AwaitingReview,
+}
+
+/// Post a native notification unless the same `tag` was notified recently or
+/// too many notifications went out in the last minute, so a burst of similar
+/// alerts (e.g. one per new high-confidence insight) shows only once.
+pub fn post_notification(
+    app: &AppHandle,
+    title: &str,
+    body: &str,
+    tag: Option<&str>,
+) -> NotifyOutcome {
+    if !load_settings(app).native {
+        return NotifyOutcome::Disabled;
+    }
+    {
+        let now = Instant::now();
+        let mut recent = RECENT.lock().unwrap();
+        let Recent { tags, posted } = &mut *recent;
+        let tags = tags.get_or_insert_with(HashMap::new);
+        tags.retain(|_, at| now.duration_since(*at) < DEDUP_WINDOW);
+        while posted
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= RATE_WINDOW)
+        {
+            posted.pop_front();
+        }
+        if tag.is_some_and(|tag| tags.contains_key(tag)) {
+            log::debug!("Suppressed duplicate notification {tag:?}: {title}");
+            return NotifyOutcome::Duplicate;
+        }
+        if posted.len() >= RATE_LIMIT {
+            log::info!("Suppressed notification over the rate limit: {title}");
+            return NotifyOutcome::RateLimited;
+        }
+        if let Some(tag) = tag {
+            tags.insert(tag.to_string(), now);
+        }
+        posted.push_back(now);
+    }
+
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        log::warn!("Failed to post notification: {e}");
+    }
+    NotifyOutcome::Shown
+}
+
+/// Tauri command: post a native notification, deduplicated by `tag` and rate
+/// limited; see [`post_notification`]. A notification about an `insight` is
+/// held back while review is required and it is not approved, and the
+/// insight joins the review queue.
+#[tauri::command]
+pub async fn notify(
+    app: AppHandle,
+    title: String,
+    body: String,
+    tag: Option<String>,
+    insight: Option<serde_json::Value>,
+) -> Result<NotifyOutcome, String> {
+    if let Some(insight) = insight {
+        if review::held(&app, insight)? {
+            return Ok(NotifyOutcome::AwaitingReview);
+        }
+    }
+    Ok(post_notification(&app, &title, &body, tag.as_deref()))
+}
@@ -0,0 +1,389 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{Datelike, Timelike};
+use flate2::read::GzDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+
+/// Minimal zip writer: deflated entries, no zip64, no encryption. Enough for
+/// support bundles, which stay far below 4 GiB.
+pub struct ZipWriter {
+    out: BufWriter<File>,
+    entries: Vec<CentralEntry>,
+}
+
+struct CentralEntry {
+    name: String,
+    crc: u32,
+    compressed_size: u32,
+    size: u32,
+    offset: u32,
+    dos_time: u16,
+    dos_date: u16,
+}
+
+impl ZipWriter {
+    pub fn create(path: &Path) -> Result<Self, String> {
+        let file =
+            File::create(path).map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+        Ok(Self {
+            out: BufWriter::new(file),
+            entries: Vec::new(),
+        })
+    }
+
+    /// Add `data` as the entry `name` (forward slashes).
+    pub fn add(&mut self, name: &str, data: &[u8]) -> Result<(), String> {
+        self.add_entry(name, data)
+            .map_err(|e| format!("Failed to add {name} to archive: {e}"))
+    }
+
+    /// Add the file at `path` as the entry `name`.
+    pub fn add_file(&mut self, name: &str, path: &Path) -> Result<(), String> {
+        let data =
+            std::fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        self.add(name, &data)
+    }
+
+    /// Write the central directory and flush.
+    pub fn finish(mut self) -> Result<(), String> {
+        self.write_central_directory()
+            .map_err(|e| format!("Failed to finish archive: {e}"))
+    }
+
+    fn add_entry(&mut self, name: &str, data: &[u8]) -> std::io::Result<()> {
+        let mut crc = Crc::new();
+        crc.update(data);
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data)?;
+        let compressed = encoder.finish()?;
+        let (dos_time, dos_date) = dos_now();
+
+        let entry = CentralEntry {
+            name: name.to_string(),
+            crc: crc.sum(),
+            compressed_size: to_u32(compressed.len())?,
+            size: to_u32(data.len())?,
+            offset: to_u32(self.out.stream_position()? as usize)?,
+            dos_time,
+            dos_date,
+        };
+
+        let out = &mut self.out;
+        out.write_all(&0x0403_4b50u32.to_le_bytes())?;
+        out.write_all(&20u16.to_le_bytes())?; // version needed
+        out.write_all(&0x0800u16.to_le_bytes())?; // UTF-8 names
+        out.write_all(&8u16.to_le_bytes())?; // deflate
+        out.write_all(&entry.dos_time.to_le_bytes())?;
+        out.write_all(&entry.dos_date.to_le_bytes())?;
+        out.write_all(&entry.crc.to_le_bytes())?;
+        out.write_all(&entry.compressed_size.to_le_bytes())?;
+        out.write_all(&entry.size.to_le_bytes())?;
+        out.write_all(&to_u16(entry.name.len())?.to_le_bytes())?;
+        out.write_all(&0u16.to_le_bytes())?; // extra field length
+        out.write_all(entry.name.as_bytes())?;
+        out.write_all(&compressed)?;
+
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    fn write_central_directory(&mut self) -> std::io::Result<()> {
+        let start = to_u32(self.out.stream_position()? as usize)?;
+        let out = &mut self.out;
+        for entry in &self.entries {
+            out.write_all(&0x0201_4b50u32.to_le_bytes())?;
+            out.write_all(&20u16.to_le_bytes())?; // version made by
+            out.write_all(&20u16.to_le_bytes())?; // version needed
+            out.write_all(&0x0800u16.to_le_bytes())?;
+            out.write_all(&8u16.to_le_bytes())?;
+            out.write_all(&entry.dos_time.to_le_bytes())?;
+            out.write_all(&entry.dos_date.to_le_bytes())?;
+            out.write_all(&entry.crc.to_le_bytes())?;
+            out.write_all(&entry.compressed_size.to_le_bytes())?;
+            out.write_all(&entry.size.to_le_bytes())?;
+            out.write_all(&to_u16(entry.name.len())?.to_le_bytes())?;
+            out.write_all(&[0; 12])?; // extra, comment, disk, internal and external attributes
+            out.write_all(&entry.offset.to_le_bytes())?;
+            out.write_all(entry.name.as_bytes())?;
+        }
+        let end = to_u32(out.stream_position()? as usize)?;
+        let count = to_u16(self.entries.len())?;
+
+        out.write_all(&0x0605_4b50u32.to_le_bytes())?;
+        out.write_all(&[0; 4])?; // disk numbers
+        out.write_all(&count.to_le_bytes())?;
+        out.write_all(&count.to_le_bytes())?;
+        out.write_all(&(end - start).to_le_bytes())?;
+        out.write_all(&start.to_le_bytes())?;
+        out.write_all(&0u16.to_le_bytes())?; // comment length
+        out.flush()
+    }
+}
+
+/// Current local time in MS-DOS format, as zip headers store it.
+fn dos_now() -> (u16, u16) {
+    let now = chrono::Local::now();
+    let time = (now.hour() << 11) | (now.minute() << 5) | (now.second() / 2);
+    let date = ((now.year().clamp(1980, 2107) as u32 - 1980) << 9) | (now.month() << 5) | now.day();
+    (time as u16, date as u16)
+}
+
+fn to_u32(value: usize) -> std::io::Result<u32> {
+    u32::try_from(value).map_err(|_| std::io::Error::other("archive exceeds 4 GiB"))
+}
+
+fn to_u16(value: usize) -> std::io::Result<u16> {
+    u16::try_from(value).map_err(|_| std::io::Error::other("too many entries or name too long"))
+}
+
+/// Unpack the `.tar.gz` at `archive` into `dest`, which must not exist yet.
+/// Minimal reader: files, folders, symlinks and hard links, with ustar,
+/// GNU long and pax names. Entries that would land outside `dest` are
+/// refused. Returns the number of files unpacked.
+pub fn unpack_tar_gz(archive: &Path, dest: &Path) -> Result<usize, String> {
+    let file =
+        File::open(archive).map_err(|e| format!("Failed to open {}: {e}", archive.display()))?;
+    std::fs::create_dir_all(dest)
+        .map_err(|e| format!("Failed to create {}: {e}", dest.display()))?;
+    let mut reader = GzDecoder::new(BufReader::new(file));
+    unpack_tar(&mut reader, dest)
+        .map_err(|e| format!("Failed to unpack {}: {e}", archive.display()))
+}
+
+fn unpack_tar(reader: &mut impl Read, dest: &Path) -> std::io::Result<usize> {
+    let invalid = |message: String| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+    let mut files = 0;
+    // Set by a GNU long name or pax header for the entry that follows.
+    let mut long_name: Option<String> = None;
+    let mut long_link: Option<String> = None;
+    let mut header = [0u8; 512];
+    loop {
+        if !read_block(reader, &mut header)? || header.iter().all(|&b| b == 0) {
+            return Ok(files);
+        }
+        let size = tar_number(&header[124..136])
+            .ok_or_else(|| invalid("invalid entry size".to_string()))?;
+        let kind = header[156];
+        let mut name = long_name.take().unwrap_or_else(|| {
+            let name = tar_string(&header[..100]);
+            let prefix = tar_string(&header[345..500]);
+            if &header[257..262] == b"ustar" && !prefix.is_empty() {
+                format!("{prefix}/{name}")
+            } else {
+                name
+            }
+        });
+        let link = long_link
+            .take()
+            .unwrap_or_else(|| tar_string(&header[157..257]));
+
+        match kind {
+            b'L' | b'K' | b'x' => {
+                let data = read_data(reader, size)?;
+                if kind == b'x' {
+                    for (key, value) in pax_records(&data) {
+                        match key.as_str() {
+                            "path" => long_name = Some(value),
+                            "linkpath" => long_link = Some(value),
+                            _ => {}
+                        }
+                    }
+                } else {
+                    let text = tar_string(&data);
+                    if kind == b'L' {
+                        long_name = Some(text);
+                    } else {
+                        long_link = Some(text);
+                    }
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        name = name.trim_start_matches("./").to_string();
+        if name.is_empty() {
+            skip_data(reader, size)?;
+            continue;
+        }
+        let path = safe_join(dest, &name)
+            .filter(|path| !through_symlink(dest, path))
+            .ok_or_else(|| invalid(format!("unsafe path {name:?}")))?;
+        match kind {
+            b'0' | 0 | b'7' => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let mut out = BufWriter::new(File::create(&path)?);
+                let copied = std::io::copy(&mut reader.take(size), &mut out)?;
+                if copied != size {
+                    return Err(invalid(format!("{name} is cut off")));
+                }
+                skip_padding(reader, size)?;
+                out.flush()?;
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    let mode = tar_number(&header[100..108]).unwrap_or(0o644) as u32 & 0o777;
+                    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode))?;
+                }
+                files += 1;
+            }
+            b'5' => {
+                std::fs::create_dir_all(&path)?;
+                skip_data(reader, size)?;
+            }
+            b'1' => {
+                let target = safe_join(dest, link.trim_start_matches("./"))
+                    .filter(|target| !through_symlink(dest, target))
+                    .ok_or_else(|| invalid(format!("unsafe hard link {name:?} -> {link:?}")))?;
+                std::fs::copy(&target, &path)?;
+                skip_data(reader, size)?;
+                files += 1;
+            }
+            b'2' => {
+                // Relative, and resolving inside `dest`.
+                let base = Path::new(&name).parent().unwrap_or(Path::new(""));
+                if !stays_inside(&base.join(&link)) {
+                    return Err(invalid(format!("unsafe symlink {name:?} -> {link:?}")));
+                }
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                #[cfg(unix)]
+                std::os::unix::fs::symlink(&link, &path)?;
+                #[cfg(not(unix))]
+                log::warn!("Skipping symlink {name} -> {link}");
+                skip_data(reader, size)?;
+            }
+            _ => {
+                log::debug!("Skipping {name}, of tar entry type {kind}");
+                skip_data(reader, size)?;
+            }
+        }
+    }
+}
+
+/// Read a whole 512-byte block; `false` at the end of the stream.
+fn read_block(reader: &mut impl Read, block: &mut [u8; 512]) -> std::io::Result<bool> {
+    let mut read = 0;
+    while read < block.len() {
+        match reader.read(&mut block[read..])? {
+            0 if read == 0 => return Ok(false),
+            0 => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+            n => read += n,
+        }
+    }
+    Ok(true)
+}
+
+fn read_data(reader: &mut impl Read, size: u64) -> std::io::Result<Vec<u8>> {
+    if size > 1024 * 1024 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "oversized tar header",
+        ));
+    }
+    let mut data = vec![0u8; size as usize];
+    reader.read_exact(&mut data)?;
+    skip_padding(reader, size)?;
+    Ok(data)
+}
+
+fn skip_data(reader: &mut impl Read, size: u64) -> std::io::Result<()> {
+    std::io::copy(&mut reader.take(size), &mut std::io::sink())?;
+    skip_padding(reader, size)
+}
+
+fn skip_padding(reader: &mut impl Read, size: u64) -> std::io::Result<()> {
+    let padding = (512 - size % 512) % 512;
+    std::io::copy(&mut reader.take(padding), &mut std::io::sink())?;
+    Ok(())
+}
+
+/// A NUL-terminated header field.
+fn tar_string(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// An octal header field, or a base-256 one for large sizes.
+fn tar_number(field: &[u8]) -> Option<u64> {
+    if field.first().is_some_and(|&b| b & 0x80 != 0) {
+        return field[1..]
+            .iter()
+            .try_fold(u64::from(field[0] & 0x7f), |n, &b| {
+                n.checked_mul(256).map(|n| n + u64::from(b))
+            });
+    }
+    let text = tar_string(field);
+    let text = text.trim_matches([' ', '\0']);
+    if text.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(text, 8).ok()
+}
+
+/// The `<length> <key>=<value>\n` records of a pax header.
+fn pax_records(data: &[u8]) -> Vec<(String, String)> {
+    let mut records = Vec::new();
+    let mut rest = data;
+    while let Some(space) = rest.iter().position(|&b| b == b' ') {
+        let Some(len) = std::str::from_utf8(&rest[..space])
+            .ok()
+            .and_then(|len| len.parse::<usize>().ok())
+            .filter(|&len| len > space && len <= rest.len())
+        else {
+            break;
+        };
+        let record = String::from_utf8_lossy(&rest[space + 1..len]);
+        if let Some((key, value)) = record.trim_end_matches('\n').split_once('=') {
+            records.push((key.to_string(), value.to_string()));
+        }
+        rest = &rest[len..];
+    }
+    records
+}
+
+/// `dest` joined with the relative path `name`, or `None` if `name` is
+/// absolute or has `..` in it.
+fn safe_join(dest: &Path, name: &str) -> Option<PathBuf> {
+    let mut path = dest.to_path_buf();
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    Some(path)
+}
+
+/// Whether the relative `path` still points below where it starts once its
+/// `..` are resolved.
+fn stays_inside(path: &Path) -> bool {
+    let mut depth = 0usize;
+    for component in path.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => depth -= 1,
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Whether a folder between `dest` and `path` is a symlink unpacked
+/// earlier, through which `path` could reach outside `dest`.
+fn through_symlink(dest: &Path, path: &Path) -> bool {
+    path.ancestors()
+        .skip(1)
+        .take_while(|ancestor| *ancestor != dest)
+        .any(|ancestor| {
+            std::fs::symlink_metadata(ancestor).is_ok_and(|meta| meta.file_type().is_symlink())
+        })
+}
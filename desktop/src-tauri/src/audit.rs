@@ -0,0 +1,368 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+
+use crate::diagnostics;
+use crate::profile;
+
+/// In the app data root, shared by every profile. Appended to only, never
+/// trimmed or rewritten; a factory reset leaves it in place.
+pub const AUDIT_FILE: &str = "audit.jsonl";
+
+/// `prev_hash` of the first entry.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Entries returned by `get_audit_log` when no limit is given.
+const DEFAULT_LIMIT: usize = 500;
+
+/// What an audit entry records.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEvent {
+    SecretRead,
+    SecretStore,
+    SecretDelete,
+    DataReset,
+    BackupRestore,
+    RemoteMode,
+    SettingChanged,
+    SettingsImported,
+    DatabaseEncryption,
+    OsAuth,
+    TamperedBinary,
+    AppUpdate,
+    BackendUpdate,
+    PackUpdate,
+    KillSwitch,
+}
+
+/// One line of `audit.jsonl`.
+///
+/// `hash` is the SHA-256 of the other fields, `prev_hash` included, so
+/// editing, reordering or removing an entry breaks the chain from there on.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Position in the log, from 0.
+    pub seq: u64,
+    /// RFC 3339, kept as written so the hash can be checked.
+    pub at: String,
+    pub event: AuditEvent,
+    /// The active profile, `None` for the default one.
+    pub profile: Option<String>,
+    /// What was done, e.g. `ANTHROPIC_API_KEY by backend`. Never holds a
+    /// secret value.
+    pub detail: String,
+    /// Whether the operation succeeded.
+    pub ok: bool,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditEntry {
+    fn digest(&self) -> String {
+        let fields = (
+            self.seq,
+            &self.at,
+            self.event,
+            &self.profile,
+            &self.detail,
+            self.ok,
+            &self.prev_hash,
+        );
+        let bytes = serde_json::to_vec(&fields).unwrap_or_default();
+        hex::encode(Sha256::digest(bytes))
+    }
+}
+
+/// Result of `get_audit_log`.
+#[derive(Clone, Debug, Serialize)]
+pub struct AuditLog {
+    pub entries: Vec<AuditEntry>,
+    /// Whether every entry in the file links to the one before it.
+    pub valid: bool,
+    /// Line (from 1) of the first entry that does not, when `valid` is false.
+    pub broken_at: Option<usize>,
+    /// Number of entries in the file.
+    pub total: usize,
+}
+
+/// The end of the chain: the file it was read from, the next `seq` and the
+/// hash the next entry links to. Loaded from the file on first append.
+struct Head {
+    path: PathBuf,
+    seq: u64,
+    hash: String,
+}
+
+static HEAD: Mutex<Option<Head>> = Mutex::new(None);
+
+fn path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::app_data_root(app)?.join(AUDIT_FILE))
+}
+
+/// The head after the last line of `path`.
+fn read_head(path: &Path) -> Result<Head, String> {
+    let last = match diagnostics::tail_lines(path, 1) {
+        Ok(lines) => lines.into_iter().next(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+    let (seq, hash) = match last {
+        None => (0, GENESIS_HASH.to_string()),
+        Some(line) => match serde_json::from_str::<AuditEntry>(&line) {
+            Ok(entry) => (entry.seq + 1, entry.hash),
+            Err(e) => {
+                // Chained on anyway; `get_audit_log` reports the break.
+                log::warn!(
+                    "The last line of {} is not an audit entry: {e}",
+                    path.display()
+                );
+                let lines = std::fs::read_to_string(path)
+                    .map(|text| text.lines().count() as u64)
+                    .unwrap_or(0);
+                (lines, hex::encode(Sha256::digest(line.as_bytes())))
+            }
+        },
+    };
+    Ok(Head {
+        path: path.to_path_buf(),
+        seq,
+        hash,
+    })
+}
+
+fn append(
+    path: &Path,
+    event: AuditEvent,
+    profile: Option<String>,
+    ok: bool,
+    detail: String,
+) -> Result<(), String> {
+    let mut head = HEAD.lock().unwrap();
+    if head.as_ref().is_none_or(|head| head.path != path) {
+        *head = Some(read_head(path)?);
+    }
+    let Some(head) = head.as_mut() else {
+        return Ok(());
+    };
+    let mut entry = AuditEntry {
+        seq: head.seq,
+        at: Utc::now().to_rfc3339(),
+        event,
+        profile,
+        detail,
+        ok,
+        prev_hash: head.hash.clone(),
+        hash: String::new(),
+    };
+    entry.hash = entry.digest();
+    crate::store::append_jsonl(path, &entry)?;
+    head.seq += 1;
+    head.hash = entry.hash;
+    Ok(())
+}
+
+/// Append an entry to the audit log. Failing to write it is logged, not
+/// fatal.
+pub fn record(app: &AppHandle, event: AuditEvent, ok: bool, detail: impl Into<String>) {
+    let result = crate::app_data_root(app).and_then(|root| {
+        append(
+            &root.join(AUDIT_FILE),
+            event,
+            profile::active(&root),
+            ok,
+            detail.into(),
+        )
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to write the audit log: {e}");
+    }
+}
+
+/// Record remote mode being turned on or off for the active profile, when
+/// `enabled` differs from what the log last recorded for it (off if
+/// nothing). Called at each spawn, so an edit to `backend.remote_mode` and
+/// `TELETRAAN_REMOTE_MODE` are both caught.
+pub fn record_remote_mode(app: &AppHandle, enabled: bool) {
+    let Ok(root) = crate::app_data_root(app) else {
+        return;
+    };
+    let profile = profile::active(&root);
+    let last = read(&root.join(AUDIT_FILE))
+        .map(|(entries, _)| {
+            entries
+                .into_iter()
+                .rev()
+                .find(|entry| entry.event == AuditEvent::RemoteMode && entry.profile == profile)
+                .is_some_and(|entry| entry.detail == "enabled")
+        })
+        .unwrap_or(false);
+    if last != enabled {
+        record(
+            app,
+            AuditEvent::RemoteMode,
+            true,
+            if enabled { "enabled" } else { "disabled" },
+        );
+    }
+}
+
+/// Every entry of the file at `path`, and the line of the first one that
+/// breaks the chain.
+fn read(path: &Path) -> Result<(Vec<AuditEntry>, Option<usize>), String> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((Vec::new(), None)),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+    let mut entries = Vec::new();
+    let mut broken_at = None;
+    let mut prev_hash = GENESIS_HASH.to_string();
+    for (i, line) in text.lines().enumerate() {
+        let intact = match serde_json::from_str::<AuditEntry>(line) {
+            Ok(entry) => {
+                let intact = entry.seq == i as u64
+                    && entry.prev_hash == prev_hash
+                    && entry.hash == entry.digest();
+                prev_hash = entry.hash.clone();
+                entries.push(entry);
+                intact
+            }
+            Err(_) => {
+                prev_hash = hex::encode(Sha256::digest(line.as_bytes()));
+                false
+            }
+        };
+        if !intact && broken_at.is_none() {
+            broken_at = Some(i + 1);
+        }
+    }
+    Ok((entries, broken_at))
+}
+
+/// Tauri command: the audit log of keychain access, resets, restores,
+/// remote mode and settings changes, oldest first, optionally only the
+/// entries since `since`. The whole chain is checked on each call.
+#[tauri::command]
+pub async fn get_audit_log(
+    app: AppHandle,
+    limit: Option<usize>,
+    since: Option<DateTime<Utc>>,
+) -> Result<AuditLog, String> {
+    let path = path(&app)?;
+    let (entries, broken_at) = tauri::async_runtime::spawn_blocking(move || read(&path))
+        .await
+        .map_err(|e| format!("Failed to read the audit log: {e}"))??;
+    if let Some(line) = broken_at {
+        log::warn!("The audit log chain is broken at line {line}");
+    }
+    let total = entries.len();
+    let mut entries: Vec<AuditEntry> = entries
+        .into_iter()
+        .filter(|entry| match since {
+            Some(since) => DateTime::parse_from_rfc3339(&entry.at).is_ok_and(|at| at >= since),
+            None => true,
+        })
+        .collect();
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    if entries.len() > limit {
+        entries.drain(..entries.len() - limit);
+    }
+    Ok(AuditLog {
+        entries,
+        valid: broken_at.is_none(),
+        broken_at,
+        total,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A log of four entries in a folder of its own.
+    fn write_log(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("teletraan-audit-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let _ = std::fs::remove_file(&path);
+        for (i, event) in [
+            AuditEvent::SecretStore,
+            AuditEvent::SecretRead,
+            AuditEvent::DataReset,
+            AuditEvent::SecretDelete,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            append(&path, event, None, true, format!("entry {i}")).unwrap();
+        }
+        path
+    }
+
+    fn lines(path: &Path) -> Vec<String> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn write_lines(path: &Path, lines: &[String]) {
+        std::fs::write(path, lines.join("\n") + "\n").unwrap();
+    }
+
+    #[test]
+    fn an_untouched_log_is_valid() {
+        let path = write_log("untouched.jsonl");
+        let (entries, broken_at) = read(&path).unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(broken_at, None);
+        assert_eq!(entries[1].prev_hash, entries[0].hash);
+    }
+
+    #[test]
+    fn detects_an_edited_entry() {
+        let path = write_log("edited.jsonl");
+        let mut lines = lines(&path);
+        lines[1] = lines[1].replace("entry 1", "entry one");
+        write_lines(&path, &lines);
+        assert_eq!(read(&path).unwrap().1, Some(2));
+    }
+
+    #[test]
+    fn detects_reordered_entries() {
+        let path = write_log("reordered.jsonl");
+        let mut lines = lines(&path);
+        lines.swap(1, 2);
+        write_lines(&path, &lines);
+        assert_eq!(read(&path).unwrap().1, Some(2));
+    }
+
+    #[test]
+    fn detects_a_deleted_entry() {
+        let path = write_log("deleted.jsonl");
+        let mut lines = lines(&path);
+        lines.remove(1);
+        write_lines(&path, &lines);
+        assert_eq!(read(&path).unwrap().1, Some(2));
+    }
+
+    #[test]
+    fn appending_after_a_broken_line_keeps_the_break_visible() {
+        let path = write_log("appended.jsonl");
+        let mut lines = lines(&path);
+        lines[3] = "not json".to_string();
+        write_lines(&path, &lines);
+        // Another path first, so the head is read from the file again.
+        write_log("other.jsonl");
+        append(&path, AuditEvent::OsAuth, None, true, "after".to_string()).unwrap();
+        let (entries, broken_at) = read(&path).unwrap();
+        assert_eq!(broken_at, Some(4));
+        assert_eq!(entries.last().unwrap().seq, 4);
+    }
+}
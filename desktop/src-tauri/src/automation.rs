@@ -0,0 +1,259 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::audit::{self, AuditEvent};
+use crate::settings;
+use crate::store;
+
+/// Global shortcut that halts all automation from anywhere on the desktop.
+#[cfg(desktop)]
+pub const HALT_SHORTCUT: &str = "CommandOrControl+Alt+Shift+K";
+
+/// Plugin handling [`HALT_SHORTCUT`] as a system-wide kill switch. The
+/// shortcut itself is registered by [`register_hotkey`] once the plugin is in.
+#[cfg(desktop)]
+pub fn hotkey_plugin() -> tauri::plugin::TauriPlugin<tauri::Wry> {
+    use tauri_plugin_global_shortcut::{Builder, ShortcutState};
+
+    Builder::new()
+        .with_handler(|app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                if let Err(e) = halt(app, "hotkey", "Halted via global shortcut") {
+                    log::error!("Failed to halt automation from hotkey: {e}");
+                }
+            }
+        })
+        .build()
+}
+
+/// Register [`HALT_SHORTCUT`]. It fails when another app owns the combo or
+/// the desktop has no global shortcuts (Wayland without X11); the tray menu
+/// and `halt_automation` still halt automation then.
+#[cfg(desktop)]
+pub fn register_hotkey(app: &AppHandle) {
+    use tauri_plugin_global_shortcut::GlobalShortcutExt;
+
+    if let Err(e) = app.global_shortcut().register(HALT_SHORTCUT) {
+        log::warn!("Failed to register the kill-switch shortcut {HALT_SHORTCUT}: {e}");
+    }
+}
+
+/// Persisted kill-switch / circuit-breaker state.
+///
+/// Persisted so a halt survives an app restart: once tripped, automation
+/// stays off until the user explicitly re-arms it.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutomationState {
+    pub halted: bool,
+    pub reason: Option<String>,
+    pub halted_at: Option<DateTime<Utc>>,
+    pub consecutive_risk_failures: u32,
+}
+
+/// The circuit breaker's limits, the `[automation]` section of
+/// `settings.toml`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutomationSettings {
+    /// Trip after this many risk-check failures in a row.
+    pub max_consecutive_risk_failures: u32,
+    /// Trip when the day's P&L falls to `-daily_loss_limit_pct` or below.
+    pub daily_loss_limit_pct: f64,
+}
+
+impl Default for AutomationSettings {
+    fn default() -> Self {
+        Self {
+            max_consecutive_risk_failures: 3,
+            daily_loss_limit_pct: 3.0,
+        }
+    }
+}
+
+/// Managed state wrapping the current automation state.
+pub struct Automation(pub Mutex<AutomationState>);
+
+impl Automation {
+    /// Load the persisted state, falling back to armed defaults.
+    pub fn load(app: &AppHandle) -> Self {
+        let state: AutomationState = state_path(app)
+            .and_then(|path| store::read_json(&path))
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to load automation state, using defaults: {e}");
+                None
+            })
+            .unwrap_or_default();
+        if state.halted {
+            log::warn!(
+                "Automation is halted from a previous session: {}",
+                state.reason.as_deref().unwrap_or("no reason recorded"),
+            );
+        }
+        Self(Mutex::new(state))
+    }
+}
+
+/// One line in `automation_audit.jsonl`.
+#[derive(Serialize)]
+struct AuditRecord<'a> {
+    at: DateTime<Utc>,
+    event: &'a str,
+    source: &'a str,
+    reason: Option<&'a str>,
+}
+
+fn state_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::resolve_data_dir(app)?.join("automation.json"))
+}
+
+fn audit(app: &AppHandle, event: &str, source: &str, reason: Option<&str>) {
+    let record = AuditRecord {
+        at: Utc::now(),
+        event,
+        source,
+        reason,
+    };
+    let result = crate::resolve_data_dir(app)
+        .and_then(|dir| store::append_jsonl(&dir.join("automation_audit.jsonl"), &record));
+    if let Err(e) = result {
+        log::error!("Failed to record automation {event} in audit log: {e}");
+    }
+    let detail = match reason {
+        Some(reason) => format!("{event} by {source}: {reason}"),
+        None => format!("{event} by {source}"),
+    };
+    audit::record(app, AuditEvent::KillSwitch, true, detail);
+}
+
+fn persist(app: &AppHandle, state: &AutomationState) -> Result<(), String> {
+    store::write_json(&state_path(app)?, state)
+}
+
+/// Halt all automation. `source` identifies what pulled the switch
+/// (`tray`, `hotkey`, `command`, `circuit_breaker`) for the audit log.
+///
+/// Halting an already-halted system is a no-op so the original reason is kept.
+pub fn halt(app: &AppHandle, source: &str, reason: &str) -> Result<AutomationState, String> {
+    let automation = app.state::<Automation>();
+    let mut state = automation.0.lock().unwrap();
+    if state.halted {
+        return Ok(state.clone());
+    }
+
+    state.halted = true;
+    state.reason = Some(reason.to_string());
+    state.halted_at = Some(Utc::now());
+    persist(app, &state)?;
+
+    log::warn!("Automation halted by {source}: {reason}");
+    audit(app, "halted", source, Some(reason));
+    let _ = app.emit("automation-halted", state.clone());
+    Ok(state.clone())
+}
+
+/// Tauri command: pull the kill switch.
+#[tauri::command]
+pub async fn halt_automation(
+    app: AppHandle,
+    reason: Option<String>,
+) -> Result<AutomationState, String> {
+    halt(
+        &app,
+        "command",
+        reason.as_deref().unwrap_or("Halted by user"),
+    )
+}
+
+/// Tauri command: explicitly re-arm automation after a halt or breaker trip.
+#[tauri::command]
+pub async fn rearm_automation(
+    app: AppHandle,
+    automation: State<'_, Automation>,
+) -> Result<AutomationState, String> {
+    let mut state = automation.0.lock().unwrap();
+    if !state.halted {
+        return Ok(state.clone());
+    }
+
+    let previous_reason = state.reason.take();
+    state.halted = false;
+    state.halted_at = None;
+    state.consecutive_risk_failures = 0;
+    persist(&app, &state)?;
+
+    log::info!("Automation re-armed");
+    audit(&app, "rearmed", "command", previous_reason.as_deref());
+    let _ = app.emit("automation-rearmed", state.clone());
+    Ok(state.clone())
+}
+
+/// Tauri command: the current kill-switch state. Anything that routes paper
+/// or live orders must check `halted` before acting.
+#[tauri::command]
+pub async fn get_automation_state(
+    automation: State<'_, Automation>,
+) -> Result<AutomationState, String> {
+    Ok(automation.0.lock().unwrap().clone())
+}
+
+/// Tauri command: report the outcome of a pre-trade risk check.
+///
+/// Trips the breaker after `max_consecutive_risk_failures` failures in a row;
+/// any pass resets the streak.
+#[tauri::command]
+pub async fn record_risk_check(
+    app: AppHandle,
+    automation: State<'_, Automation>,
+    passed: bool,
+    detail: Option<String>,
+) -> Result<AutomationState, String> {
+    let limit = settings::current(&app)
+        .automation
+        .max_consecutive_risk_failures;
+    let tripped = {
+        let mut state = automation.0.lock().unwrap();
+        state.consecutive_risk_failures = if passed {
+            0
+        } else {
+            state.consecutive_risk_failures + 1
+        };
+        persist(&app, &state)?;
+        (!state.halted && state.consecutive_risk_failures >= limit)
+            .then_some(state.consecutive_risk_failures)
+    };
+
+    match tripped {
+        Some(failures) => {
+            let mut reason = format!("{failures} consecutive risk-check failures");
+            if let Some(detail) = detail {
+                reason.push_str(&format!(" (last: {detail})"));
+            }
+            halt(&app, "circuit_breaker", &reason)
+        }
+        None => Ok(automation.0.lock().unwrap().clone()),
+    }
+}
+
+/// Tauri command: report the day's running P&L as a percentage of equity.
+/// Trips the breaker once the loss reaches `daily_loss_limit_pct`.
+#[tauri::command]
+pub async fn record_daily_pnl(
+    app: AppHandle,
+    automation: State<'_, Automation>,
+    pnl_pct: f64,
+) -> Result<AutomationState, String> {
+    let limit = settings::current(&app).automation.daily_loss_limit_pct;
+    if pnl_pct <= -limit {
+        return halt(
+            &app,
+            "circuit_breaker",
+            &format!("Daily loss {pnl_pct:.2}% breached the -{limit:.2}% limit"),
+        );
+    }
+    Ok(automation.0.lock().unwrap().clone())
+}
@@ -0,0 +1,1384 @@
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Child, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::announce;
+use crate::audit;
+use crate::backend_update;
+use crate::backups;
+use crate::cli;
+use crate::config_watch;
+use crate::corruption;
+use crate::db_encryption;
+use crate::diagnostics::{BackendExit, ExitCause, OutputRing, StartupError, StartupFailure};
+use crate::health;
+use crate::health_history;
+use crate::integrity;
+use crate::launch;
+use crate::logging::{self, LogConfig, LogRecord, PlainLogParser, RotatingLog};
+use crate::logs;
+use crate::packs;
+use crate::paths;
+use crate::pidfile;
+use crate::port;
+use crate::process_group::{self, ProcessGroup};
+use crate::redact;
+use crate::shell_log::{self, Phase};
+use crate::restart::{RestartDecision, RestartTracker};
+use crate::review;
+use crate::sandbox::Sandbox;
+use crate::secrets;
+use crate::settings;
+use crate::tls;
+use crate::transport::{self, Endpoint};
+
+/// How often the supervisor checks whether the child is still running.
+const SUPERVISOR_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Time given to the output readers to flush the child's last lines to
+/// the session log before the crash report reads its tail.
+const OUTPUT_DRAIN_DELAY: Duration = Duration::from_millis(200);
+
+/// Port a backend started by hand from a terminal listens on
+/// (`uvicorn main:app --port 8000`), checked for one to adopt before spawning.
+const EXTERNAL_BACKEND_PORT: u16 = 8000;
+
+/// How long to wait for a backend that acknowledged `/api/v1/shutdown` while
+/// analysis runs were still writing their results.
+const ANALYSIS_SHUTDOWN_GRACE: Duration = Duration::from_secs(60);
+
+/// Lifecycle of the backend as shown by the splash screen and status bar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendState {
+    /// Spawned and waiting for its first successful health check.
+    Starting,
+    Healthy,
+    /// Running but failing health checks.
+    Degraded,
+    /// Crashed; the supervisor is waiting out the restart backoff.
+    Restarting,
+    /// Stopped deliberately.
+    Stopped,
+    /// Could not be started or restarted and needs user attention.
+    Failed,
+}
+
+/// Payload of the `backend-state-changed` event and result of `get_backend_state`.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct BackendStatus {
+    pub state: BackendState,
+    pub since: DateTime<Utc>,
+    pub pid: Option<u32>,
+    /// Why the backend entered this state, e.g. the failing health check.
+    pub detail: Option<String>,
+    /// The backend was started outside the app (e.g. from a terminal) and is
+    /// only being used, not supervised; it keeps running when the app quits.
+    pub external: bool,
+}
+
+/// The backend child process and its lifecycle state.
+///
+/// `child` is `None` whenever no backend is running; `stop_backend` takes the
+/// child out, which is how the supervisor tells a deliberate stop from a crash.
+pub struct Backend {
+    pub child: Option<Child>,
+    /// Process group of `child`, used to take its subprocesses down with it.
+    pub group: Option<ProcessGroup>,
+    /// Where the backend serves its API, chosen when it is spawned.
+    pub endpoint: Option<Endpoint>,
+    /// `port` belongs to an adopted external backend rather than `child`.
+    pub external: bool,
+    /// Recent output of `child`.
+    pub output: OutputRing,
+    /// `output` of the last backend that crashed, for the diagnostics bundle.
+    pub last_crash_output: Vec<String>,
+    /// When `child` was spawned.
+    pub started_at: Option<DateTime<Utc>>,
+    /// Executable `child` was spawned from.
+    pub binary: Option<PathBuf>,
+    pub status: BackendStatus,
+}
+
+/// State container for the backend child process.
+/// Wrapped in Mutex so it can be safely accessed from multiple async contexts.
+pub struct BackendProcess(pub Mutex<Backend>);
+
+impl BackendProcess {
+    pub fn new() -> Self {
+        Self(Mutex::new(Backend {
+            child: None,
+            group: None,
+            endpoint: None,
+            external: false,
+            output: OutputRing::default(),
+            last_crash_output: Vec::new(),
+            started_at: None,
+            binary: None,
+            status: BackendStatus {
+                state: BackendState::Stopped,
+                since: Utc::now(),
+                pid: None,
+                detail: None,
+                external: false,
+            },
+        }))
+    }
+}
+
+/// Bumped by every `start_backend` and `stop_backend`. A supervisor only acts
+/// while the generation it was started with is current, so one sleeping out a
+/// restart backoff neither respawns a stopped backend nor races the
+/// supervisor of a later start.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Token every backend this app spawns requires on each request, so no other
+/// local process can drive it. One per launch of the app, so the frontend can
+/// keep it across backend restarts.
+fn launch_token() -> &'static str {
+    static TOKEN: OnceLock<String> = OnceLock::new();
+    TOKEN.get_or_init(new_token)
+}
+
+/// Token the privileged endpoints (shutdown, log level, maintenance, packs)
+/// of every backend this app spawns take, besides the launch token. Unlike
+/// that one it stays in the shell: `get_backend_token` does not return it and
+/// it never goes in a URL, so scripts in the webview cannot call them.
+fn shell_token() -> &'static str {
+    static TOKEN: OnceLock<String> = OnceLock::new();
+    TOKEN.get_or_init(new_token)
+}
+
+fn new_token() -> String {
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    redact::register_secret(&token);
+    token
+}
+
+/// Startup milestones recognisable in the backend's output: text to match,
+/// milestone id, and how far along startup it is in percent. uvicorn logs to
+/// stderr and the lifespan prints to stdout, so both streams are matched.
+const STARTUP_MILESTONES: &[(&str, &str, u8)] = &[
+    ("Migrations applied", "migrations_applied", 40),
+    ("Providers initialized", "providers_initialized", 60),
+    ("Scheduler started", "scheduler_started", 75),
+    ("Application startup complete", "application_started", 90),
+    ("Uvicorn running on", "uvicorn_running", 100),
+];
+
+/// Payload of the `backend-progress` event.
+#[derive(Clone, serde::Serialize)]
+struct BackendProgress {
+    milestone: &'static str,
+    percent: u8,
+}
+
+/// Payload of the `backend-crashed` event.
+#[derive(Clone, serde::Serialize)]
+struct BackendCrashed {
+    /// Process exit code; `None` if the process was killed by a signal.
+    exit_code: Option<i32>,
+    /// 1-based index of the restart about to happen (or that was refused).
+    restart_attempt: u32,
+    max_restarts: u32,
+    restarting: bool,
+    /// Backoff before the respawn, when `restarting`.
+    restart_delay_ms: Option<u64>,
+    /// The backend's last output lines (up to 500), oldest first.
+    recent_output: Vec<String>,
+}
+
+/// Spawn the Python backend as a child process from the bundled resources.
+///
+/// The window is visible immediately (configured in tauri.conf.json) so
+/// the frontend `BackendReadinessGate` can show a splash screen while the
+/// backend starts up.  A background health-check loop logs when the backend
+/// becomes healthy but does **not** block the window from appearing, and a
+/// supervisor task respawns the backend if it exits unexpectedly.
+pub async fn start_backend(app: &AppHandle) -> Result<(), String> {
+    log::info!("Starting Teletraan backend...");
+
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+
+    if let Some(url) = dev_backend_url(app) {
+        return use_dev_backend(app, url, generation).inspect_err(|e| {
+            set_state(app, BackendState::Failed, Some(e.clone()));
+        });
+    }
+
+    // Clear out a backend orphaned by a previous crash before it blocks ours,
+    // or reuse one that another running instance of the app owns.
+    let data_dir = crate::resolve_data_dir(app)?;
+    let phase = Phase::start("reconcile");
+    let shared = tauri::async_runtime::spawn_blocking(move || pidfile::reconcile(&data_dir))
+        .await
+        .map_err(|e| format!("Stale backend cleanup failed: {e}"))?;
+    drop(phase);
+    if let Some(record) = shared {
+        return attach_shared(app, record).await;
+    }
+    let phase = Phase::start("adopt_external");
+    let adopted = adopt_external(app).await;
+    drop(phase);
+    if adopted {
+        return Ok(());
+    }
+    if cli::args().no_spawn_backend {
+        let message = format!(
+            "No Teletraan backend is listening on port {} and --no-spawn-backend is set",
+            external_port()
+        );
+        set_state(app, BackendState::Failed, Some(message.clone()));
+        return Err(message);
+    }
+
+    // Refuse a database SQLite would reject, before it is snapshotted over
+    // the healthy snapshots or crash-loops the backend.
+    let phase = Phase::start("check_database");
+    let handle = app.clone();
+    let data_dir = crate::resolve_data_dir(app)?;
+    let checked = tauri::async_runtime::spawn_blocking(move || {
+        corruption::check_before_start(&handle, &data_dir)
+    })
+    .await
+    .unwrap_or_else(|e| {
+        log::warn!("Database check task failed: {e}");
+        Ok(())
+    });
+    drop(phase);
+    if let Err(message) = checked {
+        set_state(app, BackendState::Failed, Some(message.clone()));
+        return Err(message);
+    }
+
+    // Copy the database aside before this backend gets to migrate or write
+    // it. A failed snapshot is logged and does not hold up the start.
+    let phase = Phase::start("snapshot");
+    let handle = app.clone();
+    let data_dir = crate::resolve_data_dir(app)?;
+    let snapshot = tauri::async_runtime::spawn_blocking(move || {
+        backups::snapshot_before_start(&handle, &data_dir)
+    })
+    .await
+    .unwrap_or_else(|e| Err(format!("Snapshot task failed: {e}")));
+    drop(phase);
+    if let Err(e) = snapshot {
+        log::warn!("Failed to snapshot the database before start: {e}");
+    }
+
+    let phase = Phase::start("spawn");
+    let spawned = spawn_backend(app);
+    drop(phase);
+    let pid = match spawned {
+        Ok(pid) => pid,
+        Err(e) => {
+            report_startup_failure(app, e.clone());
+            set_state(app, BackendState::Failed, Some(e.to_string()));
+            backend_update::note_failure(app, e.to_string(), Vec::new());
+            return Err(e.to_string());
+        }
+    };
+    health::spawn_monitor(app.clone(), pid);
+    tauri::async_runtime::spawn(supervise(app.clone(), generation));
+
+    Ok(())
+}
+
+/// URL of a development backend to use instead of spawning one:
+/// `--backend-url`, `TELETRAAN_BACKEND_URL` or the `backend.external_url`
+/// setting.
+pub fn dev_backend_url(app: &AppHandle) -> Option<String> {
+    settings::current(app)
+        .backend
+        .external_url
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+}
+
+/// Point the health checker and the proxy at a backend the developer runs
+/// themselves, typically `uvicorn main:app --reload` from a source checkout.
+/// Nothing is spawned, and the backend is never restarted or stopped.
+fn use_dev_backend(app: &AppHandle, url: String, generation: u64) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(&url)
+        .map_err(|e| format!("Invalid external backend URL {url:?}: {e}"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!(
+            "Invalid external backend URL {url:?}: expected http or https"
+        ));
+    }
+    log::info!("Using external development backend at {url}");
+    {
+        let state = app.state::<BackendProcess>();
+        let mut backend = state.0.lock().unwrap();
+        backend.endpoint = Some(Endpoint::Tcp(url.clone()));
+        backend.external = true;
+    }
+    transport::set_auth_token(None);
+    transport::set_shell_token(None);
+    transport::set_trusted_cert(None);
+    config_watch::clear(app);
+    set_state(
+        app,
+        BackendState::Starting,
+        Some(format!("Waiting for the external backend at {url}")),
+    );
+    health::spawn_dev_monitor(app.clone(), url, generation);
+    Ok(())
+}
+
+/// Whether `generation` is still the current `start_backend`, i.e. the
+/// backend it started has not been stopped or replaced since.
+pub fn is_current(generation: u64) -> bool {
+    GENERATION.load(Ordering::SeqCst) == generation
+}
+
+/// Use a backend spawned (and supervised) by another instance of the app
+/// instead of starting a second one on the same port and database.
+async fn attach_shared(app: &AppHandle, record: pidfile::PidRecord) -> Result<(), String> {
+    {
+        let state = app.state::<BackendProcess>();
+        let mut backend = state.0.lock().unwrap();
+        backend.endpoint = Some(match &record.socket {
+            Some(socket) => Endpoint::Unix(socket.clone()),
+            None => Endpoint::Tcp(port::base_url("127.0.0.1", record.port, record.tls)),
+        });
+        backend.external = false;
+    }
+    transport::set_auth_token(record.auth_token.clone());
+    transport::set_shell_token(record.shell_token.clone());
+    let cert = if record.tls { Some(shared_cert(app)?) } else { None };
+    transport::set_trusted_cert(cert);
+    if let Err(e) = health::check(app).await {
+        let message = format!(
+            "Backend owned by another Teletraan instance (pid: {}) is not responding: {e}",
+            record.pid
+        );
+        set_state(app, BackendState::Failed, Some(message.clone()));
+        return Err(message);
+    }
+    set_state(
+        app,
+        BackendState::Healthy,
+        Some(format!(
+            "Shared with another Teletraan instance (backend pid: {})",
+            record.pid
+        )),
+    );
+    let _ = app.emit("backend-ready", ());
+    Ok(())
+}
+
+/// The certificate of a shared backend serving HTTPS: the one of this
+/// install, which the owning instance started it with.
+fn shared_cert(app: &AppHandle) -> Result<String, String> {
+    let dir = tls::dir(&crate::app_data_root(app)?);
+    let info = tls::load(&dir)?.ok_or_else(|| {
+        format!(
+            "The shared backend serves HTTPS but there is no certificate in {}",
+            dir.display()
+        )
+    })?;
+    tls::read_pem(&info)
+}
+
+/// Port to look for a backend started by hand on: `--port` with
+/// `--no-spawn-backend`, else `EXTERNAL_BACKEND_PORT`.
+fn external_port() -> u16 {
+    let args = cli::args();
+    match args.port {
+        Some(port) if args.no_spawn_backend => port,
+        _ => EXTERNAL_BACKEND_PORT,
+    }
+}
+
+/// Use a healthy backend somebody started by hand (typically from a terminal
+/// during development) instead of spawning a second one, provided it is ours
+/// and the same version as the app. It is never supervised or stopped by us.
+/// With `--no-spawn-backend` a version mismatch is only logged, since there
+/// is no bundled backend to fall back to.
+///
+/// Returns whether the backend was adopted.
+async fn adopt_external(app: &AppHandle) -> bool {
+    if port::BindConfig::load(app).tls {
+        log::debug!("Not looking for an external backend: TLS is required");
+        return false;
+    }
+    let external_port = external_port();
+    let endpoint = Endpoint::Tcp(port::base_url("127.0.0.1", external_port, false));
+    let Ok(health) = health::check_at(&endpoint).await else {
+        return false;
+    };
+    if health.service.as_deref() != Some("teletraan") {
+        log::debug!("Port {external_port} is not served by a Teletraan backend");
+        return false;
+    }
+    let expected = backend_update::active(app)
+        .map(|active| active.version)
+        .unwrap_or_else(|_| app.package_info().version.to_string());
+    let version = health.version.unwrap_or_default();
+    if version != expected {
+        if !cli::args().no_spawn_backend {
+            log::warn!(
+                "Not adopting external backend on port {external_port}: \
+                 version {version} does not match backend version {expected}"
+            );
+            return false;
+        }
+        log::warn!(
+            "External backend on port {external_port} is version {version}, \
+             expected {expected}"
+        );
+    }
+
+    log::info!("Adopting external backend on port {external_port} (version {version})");
+    {
+        let state = app.state::<BackendProcess>();
+        let mut backend = state.0.lock().unwrap();
+        backend.endpoint = Some(endpoint);
+        backend.external = true;
+    }
+    transport::set_auth_token(None);
+    transport::set_shell_token(None);
+    transport::set_trusted_cert(None);
+    set_state(
+        app,
+        BackendState::Healthy,
+        Some(format!("External backend on port {external_port}")),
+    );
+    let _ = app.emit("backend-ready", ());
+    true
+}
+
+/// Emit `backend-startup-failed` so the frontend can show an actionable message.
+pub fn report_startup_failure(app: &AppHandle, error: StartupError) {
+    log::error!("Backend startup failed: {error}");
+    let _ = app.emit("backend-startup-failed", StartupFailure::from(error));
+}
+
+/// Spawn the child process, wire up its output, and stash it in `BackendProcess`.
+/// Returns the new process id.
+fn spawn_backend(app: &AppHandle) -> Result<u32, StartupError> {
+    let spawn_failed = |reason: String| StartupError::SpawnFailed { reason };
+
+    // Resolve persistent data directory for the bundled app.
+    let data_dir = crate::resolve_data_dir(app).map_err(spawn_failed)?;
+
+    // Build the DATABASE_URL pointing into the app data directory.
+    let db_path = paths::database_path(&data_dir);
+    let database_url = format!(
+        "sqlite+aiosqlite:///{}",
+        db_path.display()
+    );
+
+    // Locate the bundled backend binary inside the app's Resources directory,
+    // unless `--backend-path` points elsewhere.
+    // Tauri bundles files listed in `bundle.resources` into Contents/Resources/ on macOS.
+    let active = backend_update::active(app).map_err(spawn_failed)?;
+    let backend_bin = match &cli::args().backend_path {
+        Some(path) => path.clone(),
+        None => {
+            if !active.bundled {
+                log::info!("Using updated backend {}", active.version);
+            }
+            active.dir.join("teletraan-backend")
+        }
+    };
+
+    tracing::info!(binary = %backend_bin.display(), "Spawning backend");
+    // `Command` appends `.exe` on Windows, so accept either form.
+    if !backend_bin.exists() && !backend_bin.with_extension("exe").exists() {
+        return Err(StartupError::BinaryMissing {
+            path: backend_bin.display().to_string(),
+        });
+    }
+    if let Err(files) = integrity::verify_before_spawn(app, &active.dir, &active.manifest) {
+        return Err(StartupError::BinaryTampered { files });
+    }
+    log::info!("Backend DATABASE_URL: {database_url}");
+
+    // Spawn the backend as a regular child process, with only an allow-list
+    // of the environment. That also leaves out CLAUDECODE /
+    // CLAUDE_CODE_ENTRYPOINT, so the backend's claude-agent-sdk doesn't think
+    // it's running inside Claude Code (which would cause "cannot be launched
+    // inside another session" errors).
+    let bind = port::BindConfig::load(app);
+    audit::record_remote_mode(app, bind.remote_mode);
+    let sandbox = Sandbox::load(app);
+    let mut command = sandbox.command(&backend_bin, &data_dir);
+    let (endpoint, port, socket, cert) = match bind.transport {
+        port::Transport::Socket => {
+            if bind.tls {
+                log::warn!("TLS applies to the TCP transport only; the socket is not encrypted");
+            }
+            let socket = prepare_socket(&data_dir).map_err(spawn_failed)?;
+            log::info!("Backend socket: {}", socket.display());
+            command.arg("--uds").arg(&socket);
+            (Endpoint::Unix(socket.clone()), 0, Some(socket), None)
+        }
+        port::Transport::Tcp => {
+            let port = bind.port().map_err(spawn_failed)?;
+            if let Err(conflict) = port::check(&bind.host, port) {
+                log::error!("{}", conflict.message);
+                let error = StartupError::PortConflict {
+                    port,
+                    process_name: conflict.process_name.clone(),
+                };
+                let _ = app.emit("port-conflict", conflict);
+                return Err(error);
+            }
+            let cert = if bind.tls {
+                let root = crate::app_data_root(app).map_err(spawn_failed)?;
+                let info = tls::ensure(&tls::dir(&root), &bind.host).map_err(spawn_failed)?;
+                command
+                    .arg("--ssl-certfile")
+                    .arg(&info.cert)
+                    .arg("--ssl-keyfile")
+                    .arg(&info.key);
+                Some(tls::read_pem(&info).map_err(spawn_failed)?)
+            } else {
+                None
+            };
+            let base_url = port::base_url(&bind.host, port, bind.tls);
+            log::info!("Backend address: {base_url}");
+            command.args(["--host", &bind.host, "--port", &port.to_string()]);
+            (Endpoint::Tcp(base_url), port, None, cert)
+        }
+    };
+    let tls = cert.is_some();
+    command
+        .current_dir(&data_dir)
+        .env("DATABASE_URL", &database_url)
+        .env("TELETRAAN_PACKS_FILE", packs::active_file(&data_dir))
+        .envs(review::required(app).then(|| {
+            ("TELETRAAN_REVIEW_QUEUE_FILE", review::queue_file(&data_dir))
+        }))
+        .env("TELETRAAN_AUTH_TOKEN", launch_token())
+        .env("TELETRAAN_SHELL_TOKEN", shell_token())
+        .envs(BACKEND_LOG_LEVEL.lock().unwrap().as_ref().map(|l| ("TELETRAAN_LOG_LEVEL", l)))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    secrets::apply(app, &mut command);
+    db_encryption::apply(app, &data_dir, &mut command).map_err(spawn_failed)?;
+    launch::apply_passthrough(app, &mut command);
+    launch::LaunchOverrides::load(app).0.apply(&mut command);
+    // Own process group so the backend's subprocesses can be killed with it.
+    process_group::configure(&mut command);
+    let mut child = command
+        .spawn()
+        .map_err(|e| spawn_failed(e.to_string()))?;
+    let group = ProcessGroup::attach(&child);
+    sandbox.restrict(&child);
+
+    let pid = child.id();
+    tracing::info!(pid, "Backend process spawned");
+    pidfile::write(&data_dir, pid, port, socket, launch_token(), shell_token(), tls);
+
+    // ---- Capture stdout/stderr to the session log and Tauri console ----
+    let log_file = match logging::session_log_path(&data_dir)
+        .and_then(|path| {
+            log::info!("Backend log file: {}", path.display());
+            RotatingLog::open(&path, LogConfig::load(app))
+        }) {
+        Ok(log_file) => Some(Arc::new(Mutex::new(log_file))),
+        Err(e) => {
+            log::error!("Failed to open backend log file: {e}");
+            None
+        }
+    };
+
+    // Take the stdout/stderr handles before stashing the child.
+    let output = OutputRing::default();
+    let child_stdout = child.stdout.take();
+    let child_stderr = child.stderr.take();
+
+    if let Some(stdout) = child_stdout {
+        spawn_output_reader(app.clone(), stdout, log_file.clone(), output.clone(), "stdout");
+    }
+    if let Some(stderr) = child_stderr {
+        spawn_output_reader(app.clone(), stderr, log_file, output.clone(), "stderr");
+    }
+
+    // Stash the child handle so we can kill it later.
+    {
+        let state = app.state::<BackendProcess>();
+        let mut backend = state.0.lock().unwrap();
+        backend.child = Some(child);
+        backend.group = Some(group);
+        backend.endpoint = Some(endpoint);
+        backend.external = false;
+        backend.output = output;
+        backend.started_at = Some(Utc::now());
+        backend.binary = Some(backend_bin);
+    }
+    transport::set_auth_token(Some(launch_token().to_string()));
+    transport::set_shell_token(Some(shell_token().to_string()));
+    transport::set_trusted_cert(cert);
+    config_watch::clear(app);
+    set_state(app, BackendState::Starting, None);
+    let _ = app.emit(
+        "backend-progress",
+        BackendProgress {
+            milestone: "spawned",
+            percent: 10,
+        },
+    );
+
+    Ok(pid)
+}
+
+/// Put the backend's socket in a directory only we can enter, so other local
+/// users cannot reach the API, and clear out one left by an earlier run.
+#[cfg(unix)]
+fn prepare_socket(data_dir: &std::path::Path) -> Result<std::path::PathBuf, String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let run_dir = data_dir.join("run");
+    std::fs::create_dir_all(&run_dir)
+        .map_err(|e| format!("Failed to create {}: {e}", run_dir.display()))?;
+    std::fs::set_permissions(&run_dir, std::fs::Permissions::from_mode(0o700))
+        .map_err(|e| format!("Failed to restrict {}: {e}", run_dir.display()))?;
+    let socket = run_dir.join("backend.sock");
+    match std::fs::remove_file(&socket) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to remove stale {}: {e}", socket.display())),
+    }
+    Ok(socket)
+}
+
+#[cfg(not(unix))]
+fn prepare_socket(_data_dir: &std::path::Path) -> Result<std::path::PathBuf, String> {
+    Err("The socket transport is not supported on this platform".to_string())
+}
+
+/// Check that a freshly started backend cannot be reached from other machines.
+///
+/// Returns whether it may be marked ready. If it can be reached, a
+/// `security-warning` is emitted either way; unless remote mode is enabled the
+/// backend is also stopped and marked failed.
+pub async fn audit_exposure(app: &AppHandle, pid: u32) -> bool {
+    let Some(Endpoint::Tcp(base_url)) = endpoint(app) else {
+        return true;
+    };
+    let Some(port) = reqwest::Url::parse(&base_url).ok().and_then(|url| url.port()) else {
+        return true;
+    };
+    let exposed = tauri::async_runtime::spawn_blocking(move || port::external_exposure(port))
+        .await
+        .ok()
+        .flatten();
+    let Some(address) = exposed else {
+        return true;
+    };
+
+    let remote_mode = port::BindConfig::load(app).remote_mode;
+    let message = if remote_mode {
+        format!(
+            "The backend API is reachable from other machines at {address} (remote mode is on)."
+        )
+    } else {
+        format!(
+            "The backend API was reachable from other machines at {address}, so it was stopped. \
+             Bind it to 127.0.0.1, or enable remote mode to allow this."
+        )
+    };
+    log::error!("{message}");
+    let _ = app.emit(
+        "security-warning",
+        port::SecurityWarning {
+            address: address.to_string(),
+            remote_mode,
+            message: message.clone(),
+        },
+    );
+    announce::announce(
+        app,
+        announce::Severity::Critical,
+        "Teletraan backend is exposed",
+        &message,
+    );
+    if remote_mode {
+        return true;
+    }
+
+    if current_pid(app) == Some(pid) {
+        let handle = app.clone();
+        let _ = tauri::async_runtime::spawn_blocking(move || stop_backend(&handle)).await;
+    }
+    set_state(app, BackendState::Failed, Some(message));
+    false
+}
+
+/// Where the backend API can be reached, or `None` if no backend has been
+/// started yet.
+pub fn endpoint(app: &AppHandle) -> Option<Endpoint> {
+    let state = app.state::<BackendProcess>();
+    let endpoint = state.0.lock().unwrap().endpoint.clone();
+    endpoint
+}
+
+/// Base URL of the backend API for the frontend: `http://<host>:<port>`, or the
+/// `teletraan://` proxy for a socket-bound backend and one serving HTTPS with
+/// a certificate only the shell trusts.
+pub fn base_url(app: &AppHandle) -> Option<String> {
+    endpoint(app).map(|endpoint| match endpoint {
+        // The webview would refuse a certificate only the shell trusts.
+        Endpoint::Tcp(_) if transport::trusted_cert().is_some() => transport::PROXY_URL.to_string(),
+        Endpoint::Tcp(base_url) => base_url,
+        Endpoint::Unix(_) => transport::PROXY_URL.to_string(),
+    })
+}
+
+/// Spawn a thread that reads lines and writes to the shared log file + Tauri log.
+///
+/// The log file (which rotates itself) is shared by both output streams.
+///
+/// Lines announcing a startup milestone also emit `backend-progress`.
+fn spawn_output_reader(
+    app: AppHandle,
+    stream: impl std::io::Read + Send + 'static,
+    log_file: Option<Arc<Mutex<RotatingLog>>>,
+    output: OutputRing,
+    label: &'static str,
+) {
+    std::thread::spawn(move || {
+        let reader = BufReader::new(stream);
+        let mut plain = PlainLogParser::default();
+
+        for line in reader.lines() {
+            match line {
+                Ok(raw) => {
+                    // Drop colours and progress-bar redraws, then mask secrets
+                    // before the line reaches any log or event.
+                    let normalized = logging::normalize(&raw);
+                    if normalized.is_empty() && !raw.is_empty() {
+                        continue;
+                    }
+                    let text = redact::redact(&normalized);
+                    let now = chrono::Local::now().to_rfc3339();
+                    output.push(format!("[{label}] {text}"));
+                    if let Some(record) = LogRecord::from_json_line(&text, label) {
+                        // Structured line: keep the backend's level and logger.
+                        log_record(&record);
+                        logs::publish(&app, &record);
+                        if let Some(log_file) = &log_file {
+                            match serde_json::to_string(&record) {
+                                Ok(json) => log_file.lock().unwrap().write_line(&json),
+                                Err(e) => log::warn!("Failed to serialize log record: {e}"),
+                            }
+                        }
+                    } else {
+                        // Write to Tauri console via log crate.
+                        for record in plain.push(&text, label, &now) {
+                            log_record(&record);
+                            logs::publish(&app, &record);
+                        }
+                        // Append to log file.
+                        if let Some(log_file) = &log_file {
+                            log_file
+                                .lock()
+                                .unwrap()
+                                .write_line(&format!("{now} [{label}] {text}"));
+                        }
+                    }
+
+                    if let Some(&(_, milestone, percent)) = STARTUP_MILESTONES
+                        .iter()
+                        .find(|(needle, _, _)| text.contains(needle))
+                    {
+                        let _ = app.emit(
+                            "backend-progress",
+                            BackendProgress { milestone, percent },
+                        );
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Error reading backend {label}: {e}");
+                    break;
+                }
+            }
+        }
+        if let Some(record) = plain.finish(label) {
+            log_record(&record);
+            logs::publish(&app, &record);
+        }
+    });
+}
+
+/// Re-emit a backend record in the app's own log at its level.
+fn log_record(record: &LogRecord) {
+    log::log!(
+        target: shell_log::BACKEND_TARGET,
+        record.log_level(),
+        "[backend {}] {}",
+        record.logger.as_deref().unwrap_or(&record.stream),
+        record.message
+    );
+}
+
+/// Watch the child for unexpected exits and respawn it according to the
+/// configured `RestartPolicy`.
+///
+/// Returns once the backend is stopped deliberately (the child slot is empty
+/// or `generation` is stale) or the restart budget is exhausted.
+async fn supervise(app: AppHandle, generation: u64) {
+    loop {
+        tokio::time::sleep(SUPERVISOR_POLL_INTERVAL).await;
+        if GENERATION.load(Ordering::SeqCst) != generation {
+            log::debug!("Backend supervisor exiting: superseded");
+            return;
+        }
+
+        let (status, during_startup) = {
+            let state = app.state::<BackendProcess>();
+            let mut backend = state.0.lock().unwrap();
+            let Some(child) = backend.child.as_mut() else {
+                log::debug!("Backend supervisor exiting: backend was stopped");
+                return;
+            };
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    backend.child = None;
+                    // Don't leave the crashed backend's subprocesses behind.
+                    if let Some(group) = backend.group.take() {
+                        group.kill();
+                    }
+                    (status, backend.status.state == BackendState::Starting)
+                }
+                Ok(None) => continue,
+                Err(e) => {
+                    log::warn!("Failed to poll backend process status: {e}");
+                    continue;
+                }
+            }
+        };
+
+        log::error!("Backend process exited unexpectedly ({status})");
+
+        tokio::time::sleep(OUTPUT_DRAIN_DELAY).await;
+        let recent_output = {
+            let state = app.state::<BackendProcess>();
+            let mut backend = state.0.lock().unwrap();
+            backend.last_crash_output = backend.output.snapshot();
+            backend.last_crash_output.clone()
+        };
+        let corrupt = match crate::resolve_data_dir(&app) {
+            Ok(data_dir) => {
+                let log_path = logging::session_log_path(&data_dir)
+                    .unwrap_or_else(|_| data_dir.join("logs").join("latest.log"));
+                let exit = BackendExit::collect(status, &log_path);
+                let mut corrupt = None;
+                if let Some(diagnosis) = &exit.diagnosis {
+                    log::error!("Backend exit diagnosis: {}", diagnosis.message);
+                    if matches!(diagnosis.cause, ExitCause::DatabaseCorrupt) {
+                        corrupt = Some(data_dir);
+                    }
+                }
+                let _ = app.emit("backend-exit", exit);
+                corrupt
+            }
+            Err(e) => {
+                log::warn!("Failed to collect backend exit diagnostics: {e}");
+                None
+            }
+        };
+        if during_startup {
+            report_startup_failure(
+                &app,
+                StartupError::ExitedDuringStartup {
+                    exit_code: status.code(),
+                },
+            );
+        }
+
+        // Restarting on a damaged database only crashes again; offer a backup.
+        if let Some(data_dir) = corrupt {
+            let message = corruption::report(
+                &app,
+                &data_dir,
+                corruption::CorruptionSource::BackendLog,
+                "SQLite reported it as malformed".to_string(),
+            );
+            set_state(&app, BackendState::Failed, Some(message.clone()));
+            announce::announce(
+                &app,
+                announce::Severity::Critical,
+                "Teletraan database damaged",
+                &message,
+            );
+            let _ = app.emit("backend-error", message);
+            return;
+        }
+
+        let tracker = app.state::<RestartTracker>();
+        let max_restarts = tracker.0.lock().unwrap().policy.max_attempts;
+        let decision = tracker.record_crash();
+        // An updated backend that crash-loops goes back to the previous one
+        // instead of being restarted.
+        let exhausted = matches!(decision, RestartDecision::GiveUp { .. });
+        if backend_update::note_crash(&app, exhausted, &recent_output) {
+            return;
+        }
+        let (attempt, delay) = match decision {
+            RestartDecision::Restart { attempt, delay } => (attempt, delay),
+            RestartDecision::GiveUp { attempts } => {
+                let _ = app.emit(
+                    "backend-crashed",
+                    BackendCrashed {
+                        exit_code: status.code(),
+                        restart_attempt: attempts + 1,
+                        max_restarts,
+                        restarting: false,
+                        restart_delay_ms: None,
+                        recent_output: recent_output.clone(),
+                    },
+                );
+                let message = format!("Backend crashed after {attempts} restarts; not restarting again");
+                log::error!("{message}");
+                set_state(&app, BackendState::Failed, Some(message.clone()));
+                announce::announce(
+                    &app,
+                    announce::Severity::Critical,
+                    "Teletraan backend stopped",
+                    &message,
+                );
+                let _ = app.emit("backend-error", message);
+                return;
+            }
+        };
+
+        let _ = app.emit(
+            "backend-crashed",
+            BackendCrashed {
+                exit_code: status.code(),
+                restart_attempt: attempt,
+                max_restarts,
+                restarting: true,
+                restart_delay_ms: Some(delay.as_millis() as u64),
+                recent_output: recent_output.clone(),
+            },
+        );
+
+        set_state(
+            &app,
+            BackendState::Restarting,
+            Some(format!("Backend exited ({status}); restart {attempt}/{max_restarts}")),
+        );
+        log::info!(
+            "Restarting backend in {:.1}s (attempt {attempt}/{max_restarts})...",
+            delay.as_secs_f64(),
+        );
+        tokio::time::sleep(delay).await;
+        if GENERATION.load(Ordering::SeqCst) != generation {
+            log::info!("Backend restart cancelled: backend was stopped");
+            return;
+        }
+        let pid = match spawn_backend(&app) {
+            Ok(pid) => pid,
+            Err(e) => {
+                log::error!("Backend restart failed: {e}");
+                report_startup_failure(&app, e.clone());
+                set_state(&app, BackendState::Failed, Some(e.to_string()));
+                let _ = app.emit("backend-error", e.to_string());
+                backend_update::note_failure(&app, e.to_string(), recent_output);
+                return;
+            }
+        };
+        tracker.mark_restarted();
+        health::spawn_monitor(app.clone(), pid);
+    }
+}
+
+/// Whether the app uses a backend it did not start (adopted or from
+/// `dev_backend_url`).
+pub fn is_external(app: &AppHandle) -> bool {
+    app.state::<BackendProcess>().0.lock().unwrap().external
+}
+
+/// The running backend's last output lines, oldest first.
+pub fn recent_output(app: &AppHandle) -> Vec<String> {
+    app.state::<BackendProcess>().0.lock().unwrap().output.snapshot()
+}
+
+/// Pid of the running backend, or `None` if it is stopped or has just exited.
+pub fn current_pid(app: &AppHandle) -> Option<u32> {
+    let state = app.state::<BackendProcess>();
+    let backend = state.0.lock().unwrap();
+    backend.child.as_ref().map(Child::id)
+}
+
+/// Move the backend to `state`, emitting `backend-state-changed` if anything changed.
+pub fn set_state(app: &AppHandle, state: BackendState, detail: Option<String>) {
+    update_state(app, None, state, detail);
+}
+
+/// Like `set_state`, but only while `pid` is still the running backend, so a
+/// health monitor for a replaced process cannot overwrite its successor's state.
+pub fn set_state_if_current(
+    app: &AppHandle,
+    pid: u32,
+    state: BackendState,
+    detail: Option<String>,
+) {
+    update_state(app, Some(pid), state, detail);
+}
+
+fn update_state(
+    app: &AppHandle,
+    expected_pid: Option<u32>,
+    state: BackendState,
+    detail: Option<String>,
+) {
+    let status = {
+        let process = app.state::<BackendProcess>();
+        let mut backend = process.0.lock().unwrap();
+        let pid = backend.child.as_ref().map(Child::id);
+        if expected_pid.is_some() && expected_pid != pid {
+            return;
+        }
+        let current = &backend.status;
+        if current.state == state && current.detail == detail && current.pid == pid {
+            return;
+        }
+        tracing::info!(from = ?current.state, to = ?state, "Backend state changed");
+        backend.status = BackendStatus {
+            state,
+            since: Utc::now(),
+            pid,
+            detail,
+            external: backend.external,
+        };
+        backend.status.clone()
+    };
+    health_history::record(app, &status);
+    #[cfg(desktop)]
+    crate::tray::set_external_backend(app, status.external);
+    let _ = app.emit("backend-state-changed", status);
+}
+
+/// Tauri command: base URL of the backend API, so the frontend never has to
+/// hard-code its port.
+#[tauri::command]
+pub async fn get_backend_url(app: AppHandle) -> Result<String, String> {
+    base_url(&app).ok_or_else(|| "Backend has not been started".to_string())
+}
+
+/// Tauri command: the token the backend requires in the `X-Teletraan-Token`
+/// header (or a `token` query parameter, for WebSockets and links), or `null`
+/// when it requires none. Requests through `backend_request` and the proxy
+/// carry it already. It does not open the privileged endpoints, which take
+/// the shell token only the shell holds.
+#[tauri::command]
+pub async fn get_backend_token() -> Result<Option<String>, String> {
+    Ok(transport::auth_token())
+}
+
+/// Payload of `get_backend_info`.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct BackendInfo {
+    pub pid: Option<u32>,
+    /// Spawn time of a backend this app started.
+    pub started_at: Option<DateTime<Utc>>,
+    pub uptime_secs: Option<i64>,
+    /// TCP port, when not on a socket.
+    pub port: Option<u16>,
+    pub socket: Option<PathBuf>,
+    pub binary_path: Option<PathBuf>,
+    /// From the backend's `/api/v1/version`; `None` if it did not answer.
+    pub version: Option<String>,
+    pub external: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct VersionResponse {
+    version: String,
+}
+
+/// Tauri command: process, address and version details of the backend for
+/// the About/Diagnostics screen.
+#[tauri::command]
+pub async fn get_backend_info(app: AppHandle) -> Result<BackendInfo, String> {
+    let (mut info, endpoint) = {
+        let state = app.state::<BackendProcess>();
+        let backend = state.0.lock().unwrap();
+        let running = backend.child.is_some();
+        let info = BackendInfo {
+            pid: backend.status.pid,
+            started_at: backend.started_at.filter(|_| running),
+            uptime_secs: None,
+            port: None,
+            socket: None,
+            binary_path: backend.binary.clone(),
+            version: None,
+            external: backend.external,
+        };
+        (info, backend.endpoint.clone())
+    };
+    info.uptime_secs = info
+        .started_at
+        .map(|started| (Utc::now() - started).num_seconds());
+
+    let Some(endpoint) = endpoint else {
+        return Ok(info);
+    };
+    match &endpoint {
+        Endpoint::Tcp(base_url) => {
+            info.port = reqwest::Url::parse(base_url).ok().and_then(|url| url.port());
+        }
+        Endpoint::Unix(socket) => info.socket = Some(socket.clone()),
+    }
+    let version = async {
+        let resp = transport::Client::new(Duration::from_secs(3))?
+            .get(&endpoint, "/api/v1/version")
+            .await?;
+        if !resp.status().is_success() {
+            return Err(format!("HTTP {}", resp.status()));
+        }
+        transport::json::<VersionResponse>(&resp)
+    }
+    .await;
+    match version {
+        Ok(version) => info.version = Some(version.version),
+        Err(e) => log::warn!("Failed to query backend version: {e}"),
+    }
+    Ok(info)
+}
+
+/// Tauri command: the backend's current lifecycle state.
+#[tauri::command]
+pub async fn get_backend_state(process: State<'_, BackendProcess>) -> Result<BackendStatus, String> {
+    Ok(process.0.lock().unwrap().status.clone())
+}
+
+/// Kill a backend that stopped answering health checks.
+///
+/// The child is left in `BackendProcess` so the supervisor sees the exit as a
+/// crash and respawns it under the restart policy. Does nothing if `pid` has
+/// already been replaced.
+pub fn kill_unresponsive(app: &AppHandle, pid: u32) {
+    let state = app.state::<BackendProcess>();
+    let mut backend = state.0.lock().unwrap();
+    let backend = &mut *backend;
+    if let Some(child) = backend.child.as_mut().filter(|child| child.id() == pid) {
+        log::warn!("Killing unresponsive backend (pid: {pid})");
+        if let Some(group) = &backend.group {
+            group.kill();
+        }
+        if let Err(e) = child.kill() {
+            log::error!("Failed to kill unresponsive backend: {e}");
+        }
+    }
+}
+
+/// Stop the backend child process (called on app exit and by `stop_backend_cmd`).
+///
+/// The backend is asked to exit first — through `/api/v1/shutdown`, so running
+/// analyses can write their results, then with SIGTERM — so SQLite writes in
+/// flight can finish; it is only killed if it is still running after that.
+pub fn stop_backend(app: &AppHandle) {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    let state = app.state::<BackendProcess>();
+    let (child, group, endpoint) = {
+        let mut backend = state.0.lock().unwrap();
+        // An adopted external backend is left running; just stop using it.
+        backend.external = false;
+        (
+            backend.child.take(),
+            backend.group.take(),
+            backend.endpoint.clone(),
+        )
+    };
+    if let Some(mut child) = child {
+        log::info!("Shutting down backend process (pid: {})...", child.id());
+        let grace = shutdown_grace_period(app);
+        let exited = endpoint
+            .and_then(|endpoint| tauri::async_runtime::block_on(request_shutdown(&endpoint)))
+            .is_some_and(|active_tasks| {
+                let wait = if active_tasks > 0 {
+                    log::info!("Waiting for {active_tasks} analysis task(s) to checkpoint...");
+                    ANALYSIS_SHUTDOWN_GRACE
+                } else {
+                    grace
+                };
+                wait_for_exit(&mut child, wait)
+            });
+        if exited {
+            // Reap subprocesses that outlived the backend.
+            if let Some(group) = &group {
+                group.kill();
+            }
+        } else {
+            terminate(&mut child, group.as_ref(), grace);
+        }
+        if let Ok(data_dir) = crate::resolve_data_dir(app) {
+            pidfile::remove(&data_dir);
+        }
+    }
+    set_state(app, BackendState::Stopped, None);
+}
+
+fn shutdown_grace_period(app: &AppHandle) -> Duration {
+    Duration::from_secs(settings::current(app).backend.shutdown_grace_secs)
+}
+
+/// Payload of an acknowledged `/api/v1/shutdown` request.
+#[derive(serde::Deserialize)]
+struct ShutdownAck {
+    active_analysis_tasks: u32,
+}
+
+/// Ask the backend to shut down gracefully. Returns the number of analysis
+/// tasks it is letting finish, or `None` if the request was not acknowledged.
+async fn request_shutdown(endpoint: &Endpoint) -> Option<u32> {
+    let token = transport::shell_token();
+    let headers: Vec<(&str, &str)> = token
+        .as_deref()
+        .map(|token| (transport::SHELL_HEADER, token))
+        .into_iter()
+        .collect();
+    let result = async {
+        let resp = transport::Client::new(Duration::from_secs(2))?
+            .post(endpoint, "/api/v1/shutdown", &headers)
+            .await?;
+        if !resp.status().is_success() {
+            return Err(format!("HTTP {}", resp.status()));
+        }
+        transport::json::<ShutdownAck>(&resp)
+    }
+    .await;
+    match result {
+        Ok(ack) => Some(ack.active_analysis_tasks),
+        Err(e) => {
+            log::warn!("Backend did not acknowledge shutdown request: {e}");
+            None
+        }
+    }
+}
+
+/// Level requested with `set_log_level` for the backend, passed to every
+/// backend spawned from now on as `TELETRAAN_LOG_LEVEL`.
+static BACKEND_LOG_LEVEL: Mutex<Option<String>> = Mutex::new(None);
+
+/// Set the running backend's log level (a Python level name) through
+/// `/api/v1/log-level`, and remember it for respawns.
+pub async fn set_log_level(app: &AppHandle, level: &str) -> Result<(), String> {
+    *BACKEND_LOG_LEVEL.lock().unwrap() = Some(level.to_string());
+    let endpoint = {
+        let state = app.state::<BackendProcess>();
+        let backend = state.0.lock().unwrap();
+        backend.endpoint.clone()
+    };
+    let Some(endpoint) = endpoint else {
+        // Applied when the backend is next spawned.
+        return Ok(());
+    };
+    let Some(token) = transport::shell_token() else {
+        return Err(
+            "The backend was not started by this app; restart it to change its log level"
+                .to_string(),
+        );
+    };
+
+    #[derive(serde::Serialize)]
+    struct LogLevelRequest<'a> {
+        level: &'a str,
+    }
+    let resp = transport::Client::new(Duration::from_secs(5))?
+        .post_json(
+            &endpoint,
+            "/api/v1/log-level",
+            &[(transport::SHELL_HEADER, &token)],
+            &LogLevelRequest { level },
+        )
+        .await
+        .map_err(|e| format!("Failed to set backend log level: {e}"))?;
+    if !resp.status().is_success() {
+        return Err(format!(
+            "Failed to set backend log level: HTTP {}: {}",
+            resp.status(),
+            String::from_utf8_lossy(resp.body())
+        ));
+    }
+    Ok(())
+}
+
+/// Wait up to `timeout` for the child to exit. Returns whether it did.
+fn wait_for_exit(child: &mut Child, timeout: Duration) -> bool {
+    let deadline = std::time::Instant::now() + timeout;
+    while std::time::Instant::now() < deadline {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                log::info!("Backend process exited cleanly ({status}).");
+                return true;
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(100)),
+            Err(e) => {
+                log::warn!("Failed to poll backend process status: {e}");
+                return false;
+            }
+        }
+    }
+    false
+}
+
+/// Send SIGTERM to the backend's process group and wait up to `grace` for a
+/// clean exit, then SIGKILL whatever is left of the group. Windows has no
+/// SIGTERM equivalent for console processes, so the job is terminated
+/// directly there.
+fn terminate(child: &mut Child, group: Option<&ProcessGroup>, grace: Duration) {
+    let asked = group.is_some_and(ProcessGroup::terminate);
+    if asked {
+        wait_for_exit(child, grace);
+    }
+
+    // Reap subprocesses that outlived the backend, or all of it on timeout.
+    if let Some(group) = group {
+        group.kill();
+    }
+    if let Ok(Some(_)) = child.try_wait() {
+        return;
+    }
+    if asked {
+        log::warn!(
+            "Backend did not exit within {:.1}s of SIGTERM; killing it",
+            grace.as_secs_f64(),
+        );
+    }
+    match child.kill() {
+        Ok(()) => {
+            // Wait briefly for the process to fully exit
+            let _ = child.wait();
+            log::info!("Backend process terminated.");
+        }
+        Err(e) => log::error!("Failed to kill backend process: {e}"),
+    }
+}
+
+/// Tauri command: start the backend after the user stopped it, or retry after
+/// it failed. The restart budget starts over.
+#[tauri::command]
+pub async fn start_backend_cmd(
+    app: AppHandle,
+    tracker: State<'_, RestartTracker>,
+) -> Result<(), String> {
+    if current_pid(&app).is_some() {
+        return Err("Backend is already running".to_string());
+    }
+    tracker.reset();
+    start_backend(&app).await
+}
+
+/// Tauri command: stop the backend (and with it API spend and data polling)
+/// while leaving the desktop window open.
+#[tauri::command]
+pub async fn stop_backend_cmd(app: AppHandle) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || stop_backend(&app))
+        .await
+        .map_err(|e| format!("Failed to stop backend: {e}"))
+}
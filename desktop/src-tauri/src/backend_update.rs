@@ -0,0 +1,746 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::announce;
+use crate::archive;
+use crate::audit::{self, AuditEvent};
+use crate::backend;
+use crate::backups;
+use crate::delta;
+use crate::integrity::{self, Manifest};
+use crate::restart::RestartTracker;
+use crate::settings::{self, UpdateChannel};
+use crate::signature;
+use crate::store;
+use crate::updater::{self, Asset, UpdateComponent};
+
+/// Feed of the latest backend bundle on each channel. Backend releases are
+/// published under their own tags, so they do not become the app's latest
+/// release.
+const STABLE_FEED: &str =
+    "https://github.com/barkain/teletraan/releases/download/backend/backend.json";
+const BETA_FEED: &str =
+    "https://github.com/barkain/teletraan/releases/download/backend-beta/backend.json";
+
+/// In the app data root: one folder per installed backend version, its
+/// manifest as `<version>.json` beside it, and `state.json`.
+pub const VERSIONS_DIR: &str = "backend-versions";
+const STATE_FILE: &str = "state.json";
+
+/// Which backend to run, in `state.json`: `bundled` for the one that came
+/// with the app.
+const BUNDLED: &str = "bundled";
+
+/// How long an updated backend must stay up after its first healthy check
+/// before it is kept. Until then a failure rolls it back.
+const TRIAL_PERIOD: Duration = Duration::from_secs(10 * 60);
+
+/// Crashes of an updated backend on trial that count as a crash loop.
+const TRIAL_MAX_CRASHES: u32 = 2;
+
+/// Crashes of the backend on trial since it was installed or the app started.
+static TRIAL_CRASHES: AtomicU32 = AtomicU32::new(0);
+
+/// Set while a rollback runs, so a crash and a health timeout of the same
+/// backend start only one.
+static ROLLING_BACK: AtomicBool = AtomicBool::new(false);
+
+/// `backend.json` on a backend feed.
+#[derive(Clone, Debug, Deserialize)]
+struct BackendFeed {
+    version: String,
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    pub_date: Option<String>,
+    /// Oldest app that can run this backend; older apps are not offered it.
+    #[serde(default)]
+    min_app_version: Option<String>,
+    /// Downloads by `updater::target`.
+    platforms: BTreeMap<String, BackendAsset>,
+}
+
+/// The downloads of a backend for one platform.
+#[derive(Clone, Debug, Deserialize)]
+pub struct BackendAsset {
+    /// `.tar.gz` of the `teletraan-backend` folder.
+    #[serde(flatten)]
+    pub full: Asset,
+    /// `.tar.gz` of a delta (see `delta`) from each earlier version that
+    /// has one, by that version.
+    #[serde(default)]
+    pub deltas: BTreeMap<String, Asset>,
+}
+
+/// `state.json`: the version in use and the one it replaced, kept for
+/// `rollback_backend_update`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct Selection {
+    current: String,
+    previous: Option<String>,
+    updated_at: Option<String>,
+    /// `current` was just installed and has not stayed up `TRIAL_PERIOD`
+    /// yet.
+    on_trial: bool,
+}
+
+impl Default for Selection {
+    fn default() -> Self {
+        Self {
+            current: BUNDLED.to_string(),
+            previous: None,
+            updated_at: None,
+            on_trial: false,
+        }
+    }
+}
+
+/// The backend the next spawn runs.
+pub struct ActiveBackend {
+    /// Folder holding the `teletraan-backend` executable.
+    pub dir: PathBuf,
+    /// Its version; the app's for the bundled one.
+    pub version: String,
+    pub bundled: bool,
+    /// What `integrity` checks the folder against.
+    pub manifest: Manifest,
+}
+
+/// Payload of `backend-update-available`.
+#[derive(Clone, Debug, Serialize)]
+pub struct BackendUpdateInfo {
+    pub version: String,
+    pub current_version: String,
+    pub channel: UpdateChannel,
+    pub notes: Option<String>,
+    pub pub_date: Option<String>,
+    /// Size of the download, if the server reports it.
+    pub size_bytes: Option<u64>,
+    /// The download is a delta from `current_version` rather than the
+    /// whole backend.
+    pub delta: bool,
+}
+
+/// Payload of `update-rolled-back`.
+#[derive(Clone, Debug, Serialize)]
+pub struct UpdateRolledBack {
+    /// The backend version that failed and was removed.
+    pub failed_version: String,
+    /// The version running again.
+    pub version: String,
+    pub reason: String,
+    /// The failed backend's last output lines, oldest first.
+    pub logs: Vec<String>,
+    pub rolled_back_at: String,
+}
+
+/// Result of `get_backend_versions`, and payload of `backend-updated`.
+#[derive(Clone, Debug, Serialize)]
+pub struct BackendVersions {
+    /// The version in use, or `bundled`.
+    pub current: String,
+    /// The version `rollback_backend_update` goes back to.
+    pub previous: Option<String>,
+    /// The version of the backend that came with the app.
+    pub bundled: String,
+    pub updated_at: Option<String>,
+}
+
+fn versions_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::app_data_root(app)?.join(VERSIONS_DIR))
+}
+
+fn feed_url(channel: UpdateChannel) -> &'static str {
+    match channel {
+        UpdateChannel::Stable => STABLE_FEED,
+        UpdateChannel::Beta => BETA_FEED,
+    }
+}
+
+fn load_selection(dir: &Path) -> Selection {
+    store::read_json(&dir.join(STATE_FILE))
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to read the backend version state: {e}");
+            None
+        })
+        .unwrap_or_default()
+}
+
+fn executable(dir: &Path) -> PathBuf {
+    dir.join(if cfg!(windows) {
+        "teletraan-backend.exe"
+    } else {
+        "teletraan-backend"
+    })
+}
+
+fn bundled(app: &AppHandle) -> Result<ActiveBackend, String> {
+    Ok(ActiveBackend {
+        dir: integrity::backend_dir(app)?,
+        version: app.package_info().version.to_string(),
+        bundled: true,
+        manifest: integrity::bundled_manifest(),
+    })
+}
+
+/// The backend selected in `state.json`, or the bundled one when none is,
+/// or its folder is gone.
+pub fn active(app: &AppHandle) -> Result<ActiveBackend, String> {
+    let root = versions_dir(app)?;
+    let selection = load_selection(&root);
+    if selection.current == BUNDLED {
+        return bundled(app);
+    }
+    if updater::parse_version(&selection.current).is_err() {
+        log::warn!(
+            "Ignoring invalid backend version {:?} in {}",
+            selection.current,
+            root.join(STATE_FILE).display()
+        );
+        return bundled(app);
+    }
+    let dir = root.join(&selection.current);
+    if !executable(&dir).exists() {
+        log::warn!(
+            "Backend {} is missing from {}; using the bundled one",
+            selection.current,
+            dir.display()
+        );
+        return bundled(app);
+    }
+    // A missing manifest fails the integrity check rather than skipping it.
+    let manifest = store::read_json::<Manifest>(&root.join(format!("{}.json", selection.current)))
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| vec![(format!("{}.json", selection.current), String::new())]);
+    Ok(ActiveBackend {
+        dir,
+        version: selection.current,
+        bundled: false,
+        manifest,
+    })
+}
+
+/// The newest backend on the configured channel for this platform, if it
+/// is newer than the active one and runs with this app.
+pub async fn available(
+    app: &AppHandle,
+) -> Result<Option<(BackendUpdateInfo, BackendAsset)>, String> {
+    let channel = settings::current(app).updates.channel;
+    let feed: BackendFeed = updater::fetch_feed(feed_url(channel)).await?;
+    let current = active(app)?.version;
+    if updater::parse_version(&feed.version)? <= updater::parse_version(&current)? {
+        log::info!(
+            "Backend {current} is up to date on the {channel:?} channel (latest {})",
+            feed.version
+        );
+        return Ok(None);
+    }
+    if let Some(min) = &feed.min_app_version {
+        if updater::parse_version(min)? > app.package_info().version {
+            log::info!(
+                "Backend {} needs Teletraan {min} or later; update the app first",
+                feed.version
+            );
+            return Ok(None);
+        }
+    }
+    let Some(asset) = feed.platforms.get(&updater::target()).cloned() else {
+        log::info!(
+            "Backend {} is out, but not for {}",
+            feed.version,
+            updater::target()
+        );
+        return Ok(None);
+    };
+    let delta = delta_from(&asset, &current);
+    let size_bytes = updater::download_size(&delta.unwrap_or(&asset.full).url).await;
+    let info = BackendUpdateInfo {
+        version: feed.version,
+        delta: delta.is_some(),
+        current_version: current,
+        channel,
+        notes: feed.notes,
+        pub_date: feed.pub_date,
+        size_bytes,
+    };
+    Ok(Some((info, asset)))
+}
+
+/// The delta of `asset` from the backend version `current`, if the feed has
+/// one.
+fn delta_from<'a>(asset: &'a BackendAsset, current: &str) -> Option<&'a Asset> {
+    let current = updater::parse_version(current).ok()?;
+    asset
+        .deltas
+        .iter()
+        .find(|(from, _)| updater::parse_version(from).ok() == Some(current.clone()))
+        .map(|(_, delta)| delta)
+}
+
+/// Check the backend feed in the background at startup, alongside the app's
+/// (see `updater::spawn_check`), and emit `backend-update-available`.
+pub fn spawn_check(app: AppHandle) {
+    if !settings::current(&app).updates.check_on_startup || updater::public_key().is_err() {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        match available(&app).await {
+            Ok(Some((info, _))) => {
+                log::info!("Backend {} is available", info.version);
+                if let Err(e) = app.emit("backend-update-available", &info) {
+                    log::warn!("Failed to emit backend-update-available: {e}");
+                }
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to check for backend updates: {e}"),
+        }
+    });
+}
+
+/// Move the backend built in `bundle` to `root/<version>` and record its
+/// `manifest` beside it.
+fn install_staged(
+    bundle: &Path,
+    root: &Path,
+    version: &str,
+    manifest: &Manifest,
+) -> Result<(), String> {
+    if !executable(bundle).is_file() {
+        return Err("The backend update holds no teletraan-backend executable".to_string());
+    }
+    let target = root.join(version);
+    if target.exists() {
+        std::fs::remove_dir_all(&target)
+            .map_err(|e| format!("Failed to replace {}: {e}", target.display()))?;
+    }
+    std::fs::rename(bundle, &target)
+        .map_err(|e| format!("Failed to move the backend into {}: {e}", target.display()))?;
+    store::write_json(&root.join(format!("{version}.json")), manifest)?;
+    log::info!(
+        "Staged backend {version} ({} files) in {}",
+        manifest.len(),
+        target.display()
+    );
+    Ok(())
+}
+
+/// Remove `dir` if it is left over, so it can be used from scratch.
+fn clear(dir: &Path) -> Result<(), String> {
+    if dir.exists() {
+        std::fs::remove_dir_all(dir)
+            .map_err(|e| format!("Failed to clear {}: {e}", dir.display()))?;
+    }
+    Ok(())
+}
+
+/// Unpack the verified `archive` as `root/<version>` and record its
+/// manifest. The archive holds the files of the `teletraan-backend` folder,
+/// at the top or inside such a folder.
+fn stage(archive: &Path, root: &Path, version: &str) -> Result<(), String> {
+    let staging = root.join(format!("{version}.staging"));
+    clear(&staging)?;
+    let result = (|| {
+        archive::unpack_tar_gz(archive, &staging)?;
+        let nested = staging.join("teletraan-backend");
+        let bundle = if nested.is_dir() {
+            nested
+        } else {
+            staging.clone()
+        };
+        let manifest = integrity::hash_dir(&bundle)?;
+        install_staged(&bundle, root, version, &manifest)
+    })();
+    let _ = std::fs::remove_dir_all(&staging);
+    result
+}
+
+/// Build `root/<version>` from the backend `from` in `base` and the
+/// verified delta `archive`, and record its manifest.
+fn stage_delta(
+    archive: &Path,
+    base: &Path,
+    from: &str,
+    root: &Path,
+    version: &str,
+) -> Result<(), String> {
+    let unpacked = root.join(format!("{version}.delta"));
+    let staging = root.join(format!("{version}.staging"));
+    clear(&unpacked)?;
+    clear(&staging)?;
+    let result = (|| {
+        archive::unpack_tar_gz(archive, &unpacked)?;
+        let delta = delta::read(&unpacked)?;
+        if updater::parse_version(&delta.from_version)? != updater::parse_version(from)?
+            || updater::parse_version(&delta.to_version)? != updater::parse_version(version)?
+        {
+            return Err(format!(
+                "The delta goes from {} to {}, not from {from} to {version}",
+                delta.from_version, delta.to_version
+            ));
+        }
+        let manifest = delta::apply(base, &unpacked, &delta, &staging)?;
+        install_staged(&staging, root, version, &manifest)
+    })();
+    let _ = std::fs::remove_dir_all(&unpacked);
+    let _ = std::fs::remove_dir_all(&staging);
+    result
+}
+
+/// Download `asset` for backend `version`, check its signature and stage
+/// it, as a delta from the backend in use when `base` is set.
+async fn fetch_and_stage(
+    app: &AppHandle,
+    asset: &Asset,
+    version: &str,
+    root: &Path,
+    base: Option<ActiveBackend>,
+) -> Result<(), String> {
+    let key = updater::public_key()?;
+    let download_dir = root.join(format!("{version}.download"));
+    log::info!("Downloading backend {version} from {}", asset.url);
+    let archive = updater::download(
+        app,
+        UpdateComponent::Backend,
+        version,
+        &asset.url,
+        &download_dir,
+    )
+    .await?;
+    let staged = {
+        let (root, version, signature) = (
+            root.to_path_buf(),
+            version.to_string(),
+            asset.signature.clone(),
+        );
+        tauri::async_runtime::spawn_blocking(move || {
+            signature::verify_file(&archive, &signature, &key)
+                .map_err(|e| format!("The backend update failed its signature check: {e}"))?;
+            match base {
+                Some(base) => stage_delta(&archive, &base.dir, &base.version, &root, &version),
+                None => stage(&archive, &root, &version),
+            }
+        })
+        .await
+        .map_err(|e| format!("Failed to stage the backend update: {e}"))
+        .and_then(|result| result)
+    };
+    let _ = std::fs::remove_dir_all(&download_dir);
+    staged
+}
+
+/// Remove every staged version but `keep`.
+fn prune(root: &Path, keep: &[&str]) {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name == STATE_FILE {
+            continue;
+        }
+        let version = name.strip_suffix(".json").unwrap_or(&name);
+        if keep.contains(&version) {
+            continue;
+        }
+        let removed = if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        match removed {
+            Ok(()) => log::info!("Removed old backend {}", path.display()),
+            Err(e) => log::warn!("Failed to remove {}: {e}", path.display()),
+        }
+    }
+}
+
+/// Stop the backend, make `selection` current and start the backend again.
+async fn switch(app: &AppHandle, root: &Path, selection: &Selection) -> Result<(), String> {
+    if backend::is_external(app) {
+        return Err(
+            "The backend was not started by this app, so it cannot be replaced".to_string(),
+        );
+    }
+    let busy = backups::Busy::acquire()?;
+    backups::stop_backend(app).await?;
+    store::write_json(&root.join(STATE_FILE), selection)?;
+    // Released before the start, so a rollback of a backend that fails to
+    // spawn can take it.
+    drop(busy);
+    TRIAL_CRASHES.store(0, Ordering::SeqCst);
+    app.state::<RestartTracker>().reset();
+    backend::start_backend(app).await
+}
+
+/// Keep the backend on trial once `pid` has stayed up `TRIAL_PERIOD`.
+/// Called when the backend `pid` first reports healthy.
+pub fn confirm_when_stable(app: &AppHandle, pid: u32) {
+    let Ok(root) = versions_dir(app) else {
+        return;
+    };
+    let selection = load_selection(&root);
+    if !selection.on_trial {
+        return;
+    }
+    let version = selection.current;
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(TRIAL_PERIOD).await;
+        let mut selection = load_selection(&root);
+        if backend::current_pid(&app) != Some(pid)
+            || !selection.on_trial
+            || selection.current != version
+        {
+            return;
+        }
+        selection.on_trial = false;
+        match store::write_json(&root.join(STATE_FILE), &selection) {
+            Ok(()) => log::info!(
+                "Backend {version} stayed up for {}s; keeping it",
+                TRIAL_PERIOD.as_secs()
+            ),
+            Err(e) => log::warn!("Failed to save the backend version state: {e}"),
+        }
+    });
+}
+
+/// Count a crash of the backend. An updated backend on trial that crashes
+/// `TRIAL_MAX_CRASHES` times, or once the restart policy gives up
+/// (`exhausted`), is rolled back. Returns whether a rollback started, in
+/// which case the supervisor must not restart it.
+pub fn note_crash(app: &AppHandle, exhausted: bool, output: &[String]) -> bool {
+    let Ok(root) = versions_dir(app) else {
+        return false;
+    };
+    if !load_selection(&root).on_trial {
+        return false;
+    }
+    let crashes = TRIAL_CRASHES.fetch_add(1, Ordering::SeqCst) + 1;
+    if crashes < TRIAL_MAX_CRASHES && !exhausted {
+        return false;
+    }
+    note_failure(
+        app,
+        format!("it crashed {crashes} time(s)"),
+        output.to_vec(),
+    )
+}
+
+/// Roll back an updated backend on trial that failed for `reason`, e.g. it
+/// did not become healthy in time, in the background. Returns whether a
+/// rollback started.
+pub fn note_failure(app: &AppHandle, reason: String, logs: Vec<String>) -> bool {
+    let Ok(root) = versions_dir(app) else {
+        return false;
+    };
+    let selection = load_selection(&root);
+    if !selection.on_trial || selection.current == BUNDLED {
+        return false;
+    }
+    if ROLLING_BACK.swap(true, Ordering::SeqCst) {
+        return true;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        roll_back(&app, &root, selection, reason, logs).await;
+        ROLLING_BACK.store(false, Ordering::SeqCst);
+    });
+    true
+}
+
+/// Go back to `failed.previous`, remove the failed version and emit
+/// `update-rolled-back`.
+async fn roll_back(
+    app: &AppHandle,
+    root: &Path,
+    failed: Selection,
+    reason: String,
+    logs: Vec<String>,
+) {
+    let previous = failed
+        .previous
+        .clone()
+        .filter(|previous| previous == BUNDLED || executable(&root.join(previous)).exists())
+        .unwrap_or_else(|| BUNDLED.to_string());
+    let detail = format!(
+        "backend {} failed ({reason}); rolled back to {previous}",
+        failed.current
+    );
+    log::error!("{detail}");
+    let selection = Selection {
+        current: previous,
+        previous: None,
+        updated_at: Some(chrono::Utc::now().to_rfc3339()),
+        on_trial: false,
+    };
+    if let Err(e) = switch(app, root, &selection).await {
+        log::error!("Failed to roll back the backend update: {e}");
+        audit::record(
+            app,
+            AuditEvent::BackendUpdate,
+            false,
+            format!("{detail}: {e}"),
+        );
+        return;
+    }
+    audit::record(app, AuditEvent::BackendUpdate, false, detail);
+    prune(root, &[&selection.current]);
+    let payload = UpdateRolledBack {
+        failed_version: failed.current,
+        version: selection.current.clone(),
+        reason,
+        logs,
+        rolled_back_at: selection.updated_at.clone().unwrap_or_default(),
+    };
+    announce::announce(
+        app,
+        announce::Severity::Warning,
+        "Backend update rolled back",
+        &format!(
+            "Backend {} failed: {}. Teletraan went back to {}.",
+            payload.failed_version, payload.reason, payload.version
+        ),
+    );
+    if let Err(e) = app.emit("update-rolled-back", &payload) {
+        log::warn!("Failed to emit update-rolled-back: {e}");
+    }
+    emit_updated(app, &versions(app, selection));
+}
+
+fn versions(app: &AppHandle, selection: Selection) -> BackendVersions {
+    BackendVersions {
+        current: selection.current,
+        previous: selection.previous,
+        bundled: app.package_info().version.to_string(),
+        updated_at: selection.updated_at,
+    }
+}
+
+fn emit_updated(app: &AppHandle, versions: &BackendVersions) {
+    if let Err(e) = app.emit("backend-updated", versions) {
+        log::warn!("Failed to emit backend-updated: {e}");
+    }
+}
+
+/// Tauri command: the backend version in use, the one a rollback returns
+/// to and the bundled one.
+#[tauri::command]
+pub async fn get_backend_versions(app: AppHandle) -> Result<BackendVersions, String> {
+    let selection = load_selection(&versions_dir(&app)?);
+    Ok(versions(&app, selection))
+}
+
+/// Tauri command: download the newest backend on the configured channel,
+/// check its signature, stage it in the data dir and restart the backend on
+/// it. The version it replaces is kept for `rollback_backend_update`; older
+/// ones are removed. The app itself is not touched.
+#[tauri::command]
+pub async fn install_backend_update(app: AppHandle) -> Result<BackendVersions, String> {
+    updater::public_key()?;
+    let (info, asset) = available(&app)
+        .await?
+        .ok_or("The backend is already up to date")?;
+    let root = versions_dir(&app)?;
+    let detail = format!(
+        "backend {} -> {} ({:?})",
+        info.current_version, info.version, info.channel
+    );
+    let from_delta = match delta_from(&asset, &info.current_version) {
+        Some(delta) => {
+            let base = active(&app)?;
+            match fetch_and_stage(&app, delta, &info.version, &root, Some(base)).await {
+                Ok(()) => true,
+                Err(e) => {
+                    log::warn!("Failed to apply the backend delta; downloading it whole: {e}");
+                    false
+                }
+            }
+        }
+        None => false,
+    };
+    let staged = if from_delta {
+        Ok(())
+    } else {
+        fetch_and_stage(&app, &asset.full, &info.version, &root, None).await
+    };
+    if let Err(e) = staged {
+        audit::record(
+            &app,
+            AuditEvent::BackendUpdate,
+            false,
+            format!("{detail}: {e}"),
+        );
+        return Err(e);
+    }
+
+    let before = load_selection(&root);
+    let selection = Selection {
+        current: info.version.clone(),
+        previous: Some(before.current.clone()),
+        updated_at: Some(chrono::Utc::now().to_rfc3339()),
+        on_trial: true,
+    };
+    log::info!("Restarting the backend on version {}", info.version);
+    if let Err(e) = switch(&app, &root, &selection).await {
+        audit::record(
+            &app,
+            AuditEvent::BackendUpdate,
+            false,
+            format!("{detail}: {e}"),
+        );
+        return Err(e);
+    }
+    audit::record(&app, AuditEvent::BackendUpdate, true, detail);
+    prune(&root, &[&selection.current, &before.current]);
+    let versions = versions(&app, selection);
+    emit_updated(&app, &versions);
+    Ok(versions)
+}
+
+/// Tauri command: go back to the backend version the last update replaced,
+/// and restart the backend on it. Running it again returns to the newer
+/// version.
+#[tauri::command]
+pub async fn rollback_backend_update(app: AppHandle) -> Result<BackendVersions, String> {
+    let root = versions_dir(&app)?;
+    let before = load_selection(&root);
+    let previous = before
+        .previous
+        .clone()
+        .ok_or("There is no earlier backend version to go back to")?;
+    if previous != BUNDLED && !executable(&root.join(&previous)).exists() {
+        return Err(format!("Backend {previous} is no longer installed"));
+    }
+    let selection = Selection {
+        current: previous,
+        previous: Some(before.current.clone()),
+        updated_at: Some(chrono::Utc::now().to_rfc3339()),
+        on_trial: false,
+    };
+    let detail = format!(
+        "backend {} rolled back to {}",
+        before.current, selection.current
+    );
+    log::info!("{detail}");
+    if let Err(e) = switch(&app, &root, &selection).await {
+        audit::record(
+            &app,
+            AuditEvent::BackendUpdate,
+            false,
+            format!("{detail}: {e}"),
+        );
+        return Err(e);
+    }
+    audit::record(&app, AuditEvent::BackendUpdate, true, detail);
+    let versions = versions(&app, selection);
+    emit_updated(&app, &versions);
+    Ok(versions)
+}
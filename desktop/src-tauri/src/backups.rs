@@ -0,0 +1,812 @@
+use std::ffi::OsString;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, State};
+
+use crate::audit::{self, AuditEvent};
+use crate::backend;
+use crate::config_watch;
+use crate::crypto::{self, Key, KeyParams};
+use crate::logging;
+use crate::os_auth;
+use crate::paths;
+use crate::profile;
+use crate::reset;
+use crate::restart::RestartTracker;
+use crate::settings;
+use crate::storage;
+use crate::store;
+use crate::versions;
+
+/// Folder of the data dir holding the backups.
+pub const BACKUPS_DIR: &str = "backups";
+
+/// The database files SQLite may be using: the main file, the write-ahead
+/// log and its shared-memory index.
+const DB_SUFFIXES: &[&str] = &["", "-wal", "-shm"];
+
+/// Written last into each backup folder; a folder without it is an
+/// interrupted copy.
+const MANIFEST_FILE: &str = "snapshot.json";
+
+/// Items of the data dir a backup leaves out besides the logs: the backups
+/// themselves, the other profiles, the reset archives, and files that only
+/// describe the running shell and backend.
+const EXCLUDED: &[&str] = &[
+    BACKUPS_DIR,
+    profile::PROFILES_DIR,
+    reset::RESET_BACKUPS_DIR,
+    profile::SELECTION_FILE,
+    "browse",
+    "run",
+    "backend.pid",
+];
+
+/// Set while a `Busy` is held.
+static BUSY: AtomicBool = AtomicBool::new(false);
+
+/// Why a backup was taken; also the prefix of its folder name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupKind {
+    /// The database, copied before a backend start.
+    PreStart,
+    /// The data directory, from `create_backup`.
+    Manual,
+    /// What `restore_backup` replaced, to undo the restore.
+    BeforeRestore,
+}
+
+impl BackupKind {
+    fn prefix(self) -> &'static str {
+        match self {
+            BackupKind::PreStart => "pre-start-",
+            BackupKind::Manual => "backup-",
+            BackupKind::BeforeRestore => "before-restore-",
+        }
+    }
+}
+
+/// `snapshot.json`. The backed-up files sit next to it, laid out as in the
+/// data dir.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Manifest {
+    kind: BackupKind,
+    #[serde(default)]
+    label: Option<String>,
+    created_at: String,
+    app_version: String,
+    /// Fingerprint of the database schema, when the backend reported one.
+    #[serde(default)]
+    schema_version: Option<String>,
+    /// Over the database files, to skip a snapshot identical to the last one.
+    #[serde(default)]
+    sha256: Option<String>,
+    /// Relative paths, with `/` separators.
+    files: Vec<String>,
+    /// How the key for the files was derived, when `create_backup` was given
+    /// a passphrase.
+    #[serde(default)]
+    encryption: Option<KeyParams>,
+}
+
+/// One entry of `list_backups`.
+#[derive(Clone, Debug, Serialize)]
+pub struct BackupInfo {
+    /// Folder name in `backups/`, passed to `restore_backup`.
+    pub id: String,
+    pub kind: BackupKind,
+    pub label: Option<String>,
+    pub created_at: String,
+    pub size_bytes: u64,
+    pub schema_version: Option<String>,
+    pub app_version: String,
+    /// Whether restoring needs the passphrase the backup was made with.
+    pub encrypted: bool,
+}
+
+/// Result of `restore_backup`.
+#[derive(Clone, Debug, Serialize)]
+pub struct RestoreReport {
+    pub restored: String,
+    /// Items of the data dir that were replaced.
+    pub replaced: Vec<String>,
+    /// Id of the backup holding the replaced items, to undo the restore.
+    pub before_restore: String,
+}
+
+/// Held while the data dir is being backed up or replaced, so only one
+/// such command runs at a time. Clears `BUSY` when dropped.
+pub struct Busy;
+
+impl Busy {
+    pub fn acquire() -> Result<Busy, String> {
+        if BUSY.swap(true, Ordering::SeqCst) {
+            return Err("A backup or restore is already running".to_string());
+        }
+        Ok(Busy)
+    }
+}
+
+impl Drop for Busy {
+    fn drop(&mut self) {
+        BUSY.store(false, Ordering::SeqCst);
+    }
+}
+
+/// `path` with `suffix` appended to its file name (`db` + `-wal`).
+pub fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// The files of the database at `db_path` that exist.
+pub fn database_files(db_path: &Path) -> Vec<PathBuf> {
+    DB_SUFFIXES
+        .iter()
+        .map(|suffix| with_suffix(db_path, suffix))
+        .filter(|path| path.exists())
+        .collect()
+}
+
+/// `database_files` relative to `data_dir`.
+fn database_items(data_dir: &Path) -> Vec<PathBuf> {
+    database_files(&paths::database_path(data_dir))
+        .into_iter()
+        .filter_map(|path| path.strip_prefix(data_dir).ok().map(Path::to_path_buf))
+        .collect()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// `path` below `base`, with `/` separators.
+fn relative(path: &Path, base: &Path) -> String {
+    path.strip_prefix(base)
+        .unwrap_or(path)
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// SHA-256 over the database files that exist, in `DB_SUFFIXES` order, or
+/// `None` without a database.
+pub fn hash_database(db_path: &Path) -> Result<Option<String>, String> {
+    if !db_path.is_file() {
+        return Ok(None);
+    }
+    let mut hasher = Sha256::new();
+    for suffix in DB_SUFFIXES {
+        let path = with_suffix(db_path, suffix);
+        let mut file = match std::fs::File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Failed to open {}: {e}", path.display())),
+        };
+        hasher.update(suffix.as_bytes());
+        let mut buffer = vec![0; 1 << 16];
+        loop {
+            let read = file
+                .read(&mut buffer)
+                .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+    }
+    Ok(Some(hex::encode(hasher.finalize())))
+}
+
+/// Complete backups in `backups_dir`, oldest first.
+fn backups(backups_dir: &Path) -> Vec<(PathBuf, Manifest)> {
+    let mut backups: Vec<(PathBuf, Manifest)> = match std::fs::read_dir(backups_dir) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter_map(|dir| {
+                let manifest = store::read_json::<Manifest>(&dir.join(MANIFEST_FILE))
+                    .inspect_err(|e| log::warn!("Ignoring backup {}: {e}", dir.display()))
+                    .ok()
+                    .flatten()?;
+                Some((dir, manifest))
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    // RFC 3339 in UTC sorts by time.
+    backups.sort_by(|a, b| a.1.created_at.cmp(&b.1.created_at));
+    backups
+}
+
+fn info(dir: &Path, manifest: Manifest) -> BackupInfo {
+    BackupInfo {
+        id: file_name(dir),
+        kind: manifest.kind,
+        label: manifest.label,
+        created_at: manifest.created_at,
+        size_bytes: storage::size(dir),
+        schema_version: manifest.schema_version,
+        app_version: manifest.app_version,
+        encrypted: manifest.encryption.is_some(),
+    }
+}
+
+/// The newest unencrypted backup with a database that `usable` accepts,
+/// skipping copies of the database whose hash is `exclude`.
+pub fn newest_database_backup(
+    data_dir: &Path,
+    exclude: Option<&str>,
+    usable: impl Fn(&Path) -> bool,
+) -> Option<BackupInfo> {
+    backups(&data_dir.join(BACKUPS_DIR))
+        .into_iter()
+        .rev()
+        .find(|(dir, manifest)| {
+            let database = paths::database_path(dir);
+            database.is_file()
+                && manifest.encryption.is_none()
+                && (exclude.is_none() || manifest.sha256.as_deref() != exclude)
+                && usable(&database)
+        })
+        .map(|(dir, manifest)| info(&dir, manifest))
+}
+
+/// Names of the entries of `dir`.
+fn entries(dir: &Path) -> Result<Vec<OsString>, String> {
+    std::fs::read_dir(dir)
+        .and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.file_name()))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| format!("Failed to read {}: {e}", dir.display()))
+}
+
+/// Copy the file or folder `from` to `to`, adding the copied files to
+/// `files` relative to `base`.
+pub fn copy_item(
+    from: &Path,
+    to: &Path,
+    base: &Path,
+    files: &mut Vec<String>,
+) -> Result<(), String> {
+    if from.is_dir() {
+        std::fs::create_dir_all(to)
+            .map_err(|e| format!("Failed to create {}: {e}", to.display()))?;
+        for name in entries(from)? {
+            copy_item(&from.join(&name), &to.join(&name), base, files)?;
+        }
+        return Ok(());
+    }
+    if let Some(dir) = to.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    std::fs::copy(from, to).map_err(|e| format!("Failed to copy {}: {e}", from.display()))?;
+    files.push(relative(from, base));
+    Ok(())
+}
+
+/// Add the files below `path` to `files`, relative to `base`.
+fn list_files(path: &Path, base: &Path, files: &mut Vec<String>) {
+    if path.is_dir() {
+        for name in entries(path).unwrap_or_default() {
+            list_files(&path.join(name), base, files);
+        }
+    } else {
+        files.push(relative(path, base));
+    }
+}
+
+fn backup_name(kind: BackupKind) -> String {
+    format!(
+        "{}{}",
+        kind.prefix(),
+        chrono::Local::now().format("%Y-%m-%dT%H-%M-%S%.3f")
+    )
+}
+
+fn manifest(app: &AppHandle, kind: BackupKind, label: Option<String>) -> Manifest {
+    Manifest {
+        kind,
+        label,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        app_version: app.package_info().version.to_string(),
+        schema_version: None,
+        sha256: None,
+        files: Vec::new(),
+        encryption: None,
+    }
+}
+
+/// Copy `items` (relative to `data_dir`) into a new backup folder and return
+/// it, encrypting the copies with `key` when given. The copy is made under a
+/// hidden name and only renamed once complete.
+fn write_backup(
+    data_dir: &Path,
+    items: &[PathBuf],
+    mut manifest: Manifest,
+    key: Option<&Key>,
+) -> Result<PathBuf, String> {
+    let backups_dir = data_dir.join(BACKUPS_DIR);
+    let name = backup_name(manifest.kind);
+    let backup = backups_dir.join(&name);
+    let partial = backups_dir.join(format!(".{name}.partial"));
+    let written = (|| {
+        std::fs::create_dir_all(&partial)
+            .map_err(|e| format!("Failed to create {}: {e}", partial.display()))?;
+        for item in items {
+            copy_item(
+                &data_dir.join(item),
+                &partial.join(item),
+                data_dir,
+                &mut manifest.files,
+            )?;
+        }
+        manifest.sha256 = hash_database(&paths::database_path(&partial))?;
+        if let Some(key) = key {
+            for file in &manifest.files {
+                crypto::encrypt_file(key, &partial.join(file))?;
+            }
+        }
+        store::write_json(&partial.join(MANIFEST_FILE), &manifest)?;
+        std::fs::rename(&partial, &backup)
+            .map_err(|e| format!("Failed to move {}: {e}", partial.display()))
+    })();
+    if let Err(e) = written {
+        let _ = std::fs::remove_dir_all(&partial);
+        return Err(e);
+    }
+    Ok(backup)
+}
+
+/// Copy the database (with its WAL and SHM files) into
+/// `backups/pre-start-<time>/` before a backend opens it, so a bad migration
+/// or a crash mid-write can be rolled back. Nothing is copied when the
+/// database does not exist yet or is identical to the newest snapshot.
+/// Only the newest `backup.keep_snapshots` snapshots are kept.
+///
+/// Returns the new snapshot, if one was taken.
+pub fn snapshot_before_start(app: &AppHandle, data_dir: &Path) -> Result<Option<PathBuf>, String> {
+    let config = settings::current(app).backup;
+    if !config.snapshot_on_start {
+        return Ok(None);
+    }
+    let db_path = paths::database_path(data_dir);
+    let Some(sha256) = hash_database(&db_path)? else {
+        return Ok(None);
+    };
+    let backups_dir = data_dir.join(BACKUPS_DIR);
+    let snapshots = || {
+        backups(&backups_dir)
+            .into_iter()
+            .filter(|(_, manifest)| manifest.kind == BackupKind::PreStart)
+            .collect::<Vec<_>>()
+    };
+    if snapshots()
+        .last()
+        .is_some_and(|(_, newest)| newest.sha256.as_ref() == Some(&sha256))
+    {
+        log::info!("Database unchanged since the last pre-start snapshot");
+        return Ok(None);
+    }
+
+    let snapshot = write_backup(
+        data_dir,
+        &database_items(data_dir),
+        manifest(app, BackupKind::PreStart, None),
+        None,
+    )?;
+    log::info!("Database snapshot before start: {}", snapshot.display());
+
+    let snapshots = snapshots();
+    let excess = snapshots.len().saturating_sub(config.keep_snapshots.max(1));
+    for (old, _) in &snapshots[..excess] {
+        match std::fs::remove_dir_all(old) {
+            Ok(()) => log::info!("Removed old database snapshot {}", old.display()),
+            Err(e) => log::warn!("Failed to remove old snapshot {}: {e}", old.display()),
+        }
+    }
+    Ok(Some(snapshot))
+}
+
+/// Back up just the database, as a manual backup with `label`, before the
+/// shell replaces it. Returns `None` without a database.
+pub fn backup_database(
+    app: &AppHandle,
+    data_dir: &Path,
+    label: &str,
+) -> Result<Option<PathBuf>, String> {
+    let items = database_items(data_dir);
+    if items.is_empty() {
+        return Ok(None);
+    }
+    let manifest = manifest(app, BackupKind::Manual, Some(label.to_string()));
+    write_backup(data_dir, &items, manifest, None).map(Some)
+}
+
+/// Replace the database of `data_dir` with a copy of the one at `source`,
+/// with its WAL and SHM files. The copy is made first, then the current
+/// database is kept as a manual backup with `label`. Returns that backup, if
+/// there was a database, and the names of the files put in place.
+pub fn replace_database(
+    app: &AppHandle,
+    data_dir: &Path,
+    source: &Path,
+    label: &str,
+) -> Result<(Option<PathBuf>, Vec<String>), String> {
+    let target = paths::database_path(data_dir);
+    let target_dir = target.parent().unwrap_or(data_dir);
+    let staged = data_dir.join(".replace-database.partial");
+    let _ = std::fs::remove_dir_all(&staged);
+    let copied = database_files(source).iter().try_for_each(|from| {
+        let name = from.file_name().unwrap_or_default();
+        copy_item(from, &staged.join(name), target_dir, &mut Vec::new())
+    });
+    if let Err(e) = copied {
+        let _ = std::fs::remove_dir_all(&staged);
+        return Err(e);
+    }
+
+    let backup = backup_database(app, data_dir, label).inspect_err(|_| {
+        let _ = std::fs::remove_dir_all(&staged);
+    })?;
+    for file in database_files(&target) {
+        std::fs::remove_file(&file)
+            .map_err(|e| format!("Failed to remove {}: {e}", file.display()))?;
+    }
+    let mut files = Vec::new();
+    for name in entries(&staged)? {
+        let to = target_dir.join(&name);
+        std::fs::rename(staged.join(&name), &to)
+            .map_err(|e| format!("Failed to move {}: {e}", to.display()))?;
+        files.push(name.to_string_lossy().into_owned());
+    }
+    let _ = std::fs::remove_dir_all(&staged);
+    Ok((backup, files))
+}
+
+/// Remove the oldest backups from `create_backup` and `restore_backup` beyond
+/// the newest `keep`; 0 keeps them all. Pre-start snapshots are rotated on
+/// their own. Returns the removed ids and their size.
+pub fn prune_backups(data_dir: &Path, keep: usize) -> (Vec<String>, u64) {
+    let mut removed = (Vec::new(), 0);
+    if keep == 0 {
+        return removed;
+    }
+    let backups: Vec<PathBuf> = backups(&data_dir.join(BACKUPS_DIR))
+        .into_iter()
+        .filter(|(_, manifest)| manifest.kind != BackupKind::PreStart)
+        .map(|(dir, _)| dir)
+        .collect();
+    let excess = backups.len().saturating_sub(keep);
+    for old in &backups[..excess] {
+        let size = storage::size(old);
+        match std::fs::remove_dir_all(old) {
+            Ok(()) => {
+                log::info!("Removed old backup {}", old.display());
+                removed.0.push(file_name(old));
+                removed.1 += size;
+            }
+            Err(e) => log::warn!("Failed to remove old backup {}: {e}", old.display()),
+        }
+    }
+    removed
+}
+
+/// Top-level items of the data dir that a backup holds.
+fn data_items(data_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let logs_dir = logging::logs_dir(data_dir);
+    Ok(entries(data_dir)?
+        .into_iter()
+        .filter(|name| {
+            data_dir.join(name) != logs_dir && !EXCLUDED.iter().any(|excluded| name == *excluded)
+        })
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// The folder and manifest of the complete backup `id`.
+fn find(backups_dir: &Path, id: &str) -> Result<(PathBuf, Manifest), String> {
+    if id.starts_with('.') || Path::new(id).file_name() != Some(std::ffi::OsStr::new(id)) {
+        return Err(format!("Invalid backup id: {id:?}"));
+    }
+    let dir = backups_dir.join(id);
+    let manifest = store::read_json::<Manifest>(&dir.join(MANIFEST_FILE))?
+        .ok_or_else(|| format!("Backup not found: {id}"))?;
+    Ok((dir, manifest))
+}
+
+/// Move `names` from `from` back into `data_dir`.
+fn put_back(names: &[&OsString], from: &Path, data_dir: &Path) {
+    for name in names {
+        if let Err(e) = std::fs::rename(from.join(name), data_dir.join(name)) {
+            log::error!(
+                "Failed to put back {} from {}: {e}",
+                name.to_string_lossy(),
+                from.display()
+            );
+        }
+    }
+}
+
+/// Replace the items of `data_dir` that the backup `source` holds with its
+/// copies, decrypted with `key` when given. The replaced items are first
+/// moved into a `before-restore-` backup described by `manifest`; if
+/// anything fails, they are put back. Returns the replaced items and that
+/// backup.
+fn restore(
+    data_dir: &Path,
+    source: &Path,
+    mut manifest: Manifest,
+    key: Option<&Key>,
+) -> Result<(Vec<String>, PathBuf), String> {
+    let backups_dir = data_dir.join(BACKUPS_DIR);
+
+    // Copy the backup first, so it stays intact and a failed copy leaves the
+    // data dir untouched.
+    let staged = backups_dir.join(format!(".restore-{}.partial", file_name(source)));
+    let _ = std::fs::remove_dir_all(&staged);
+    let names: Vec<OsString> = entries(source)?
+        .into_iter()
+        .filter(|name| name != MANIFEST_FILE)
+        .collect();
+    let mut files = Vec::new();
+    let copied = names
+        .iter()
+        .try_for_each(|name| copy_item(&source.join(name), &staged.join(name), source, &mut files))
+        .and_then(|()| match key {
+            Some(key) => files
+                .iter()
+                .try_for_each(|file| crypto::decrypt_file(key, &staged.join(file))),
+            None => Ok(()),
+        });
+    if let Err(e) = copied {
+        let _ = std::fs::remove_dir_all(&staged);
+        return Err(e);
+    }
+
+    // Move what is there now aside.
+    let name = backup_name(manifest.kind);
+    let before = backups_dir.join(&name);
+    let partial = backups_dir.join(format!(".{name}.partial"));
+    let mut moved: Vec<&OsString> = Vec::new();
+    let aside = (|| {
+        std::fs::create_dir_all(&partial)
+            .map_err(|e| format!("Failed to create {}: {e}", partial.display()))?;
+        for name in &names {
+            let from = data_dir.join(name);
+            if !from.exists() {
+                continue;
+            }
+            std::fs::rename(&from, partial.join(name))
+                .map_err(|e| format!("Failed to move {}: {e}", from.display()))?;
+            moved.push(name);
+        }
+        for name in &moved {
+            list_files(&partial.join(name), &partial, &mut manifest.files);
+        }
+        manifest.sha256 = hash_database(&paths::database_path(&partial))?;
+        store::write_json(&partial.join(MANIFEST_FILE), &manifest)?;
+        std::fs::rename(&partial, &before)
+            .map_err(|e| format!("Failed to move {}: {e}", partial.display()))
+    })();
+    if let Err(e) = aside {
+        log::error!("{e}; putting back the items already moved");
+        put_back(&moved, &partial, data_dir);
+        let _ = std::fs::remove_dir_all(&partial);
+        let _ = std::fs::remove_dir_all(&staged);
+        return Err(e);
+    }
+
+    // Move the copies in.
+    let mut restored: Vec<&OsString> = Vec::new();
+    for name in &names {
+        if let Err(e) = std::fs::rename(staged.join(name), data_dir.join(name)) {
+            let error = format!("Failed to restore {}: {e}", name.to_string_lossy());
+            log::error!("{error}; putting back the replaced items");
+            for name in &restored {
+                let path = data_dir.join(name);
+                let removed = if path.is_dir() {
+                    std::fs::remove_dir_all(&path)
+                } else {
+                    std::fs::remove_file(&path)
+                };
+                if let Err(e) = removed {
+                    log::error!("Failed to remove {}: {e}", path.display());
+                }
+            }
+            put_back(&moved, &before, data_dir);
+            let _ = std::fs::remove_dir_all(&before);
+            let _ = std::fs::remove_dir_all(&staged);
+            return Err(error);
+        }
+        restored.push(name);
+    }
+    let _ = std::fs::remove_dir_all(&staged);
+    let replaced = moved
+        .iter()
+        .map(|name| name.to_string_lossy().into_owned())
+        .collect();
+    Ok((replaced, before))
+}
+
+/// Only a backend this app spawned can be stopped before its files are
+/// copied or replaced.
+pub fn check_managed(app: &AppHandle) -> Result<(), String> {
+    if backend::is_external(app) {
+        return Err(
+            "The backend was not started by this app, so it cannot be stopped for a backup"
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+pub async fn stop_backend(app: &AppHandle) -> Result<(), String> {
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || backend::stop_backend(&handle))
+        .await
+        .map_err(|e| format!("Failed to stop backend: {e}"))
+}
+
+/// Tauri command: every backup in `backups/`, newest first, for the restore
+/// picker. Pre-start snapshots hold only the database and have no schema
+/// version, since the backend was not running when they were taken.
+#[tauri::command]
+pub async fn list_backups(app: AppHandle) -> Result<Vec<BackupInfo>, String> {
+    let backups_dir = crate::resolve_data_dir(&app)?.join(BACKUPS_DIR);
+    tauri::async_runtime::spawn_blocking(move || {
+        backups(&backups_dir)
+            .into_iter()
+            .rev()
+            .map(|(dir, manifest)| info(&dir, manifest))
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Failed to list backups: {e}"))
+}
+
+/// Derive the key for `passphrase` off the async runtime; PBKDF2 is slow by
+/// design.
+async fn derive_key<T: Send + 'static>(
+    derive: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(derive)
+        .await
+        .map_err(|e| format!("Failed to derive the backup key: {e}"))
+        .and_then(|result| result)
+}
+
+/// Tauri command: back up the data directory (database, settings and app
+/// state, but not the logs or other profiles) to `backups/backup-<time>/`
+/// and return its id.
+///
+/// With a `passphrase`, every file of the backup is encrypted with
+/// AES-256-GCM under a key derived from it, so the folder can be copied off
+/// the device. The passphrase is not stored; the backup cannot be restored
+/// without it.
+///
+/// The backend is stopped for the copy, giving running analyses time to
+/// checkpoint, and started again afterwards whether or not the copy worked.
+#[tauri::command]
+pub async fn create_backup(
+    app: AppHandle,
+    label: Option<String>,
+    passphrase: Option<String>,
+) -> Result<String, String> {
+    let _busy = Busy::acquire()?;
+    check_managed(&app)?;
+    let data_dir = crate::resolve_data_dir(&app)?;
+    let label = label
+        .map(|label| label.trim().to_string())
+        .filter(|label| !label.is_empty());
+    let mut manifest = manifest(&app, BackupKind::Manual, label);
+    manifest.schema_version = versions::schema_version(&app).await;
+    let key = match passphrase {
+        Some(passphrase) => {
+            let (key, params) = derive_key(move || Key::new(&passphrase)).await?;
+            manifest.encryption = Some(params);
+            Some(key)
+        }
+        None => None,
+    };
+
+    log::info!("Stopping the backend for a backup");
+    stop_backend(&app).await?;
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        data_items(&data_dir)
+            .and_then(|items| write_backup(&data_dir, &items, manifest, key.as_ref()))
+    })
+    .await
+    .map_err(|e| format!("Failed to back up app data: {e}"))
+    .and_then(|result| result);
+    let started = backend::start_backend(&app).await;
+
+    let backup = result?;
+    started?;
+    log::info!("Backed up app data to {}", backup.display());
+    Ok(file_name(&backup))
+}
+
+/// Tauri command: put the data directory back to the backup `id` from
+/// `list_backups`, then restart the backend. An encrypted backup needs the
+/// `passphrase` it was made with, which is checked before anything is
+/// stopped, and the user to authenticate with the OS (see `os_auth`).
+///
+/// Only the items the backup holds are replaced, so a pre-start snapshot
+/// restores just the database. The replaced items are kept as a
+/// `before-restore-` backup, which can be restored to undo this. If an item
+/// cannot be moved, everything is put back and the old data starts again.
+#[tauri::command]
+pub async fn restore_backup(
+    app: AppHandle,
+    tracker: State<'_, RestartTracker>,
+    id: String,
+    passphrase: Option<String>,
+) -> Result<RestoreReport, String> {
+    let _busy = Busy::acquire()?;
+    check_managed(&app)?;
+    let data_dir = crate::resolve_data_dir(&app)?;
+    let (source, backup) = find(&data_dir.join(BACKUPS_DIR), &id)?;
+    let key = match (backup.encryption, passphrase) {
+        (Some(params), Some(passphrase)) => {
+            os_auth::require(&app, &format!("decrypt backup {id}")).await?;
+            Some(derive_key(move || Key::unlock(&passphrase, &params)).await?)
+        }
+        (Some(_), None) => return Err(format!("Backup {id} is encrypted; enter its passphrase")),
+        (None, _) => None,
+    };
+    let mut before = manifest(
+        &app,
+        BackupKind::BeforeRestore,
+        Some(format!("Before restoring {id}")),
+    );
+    before.schema_version = versions::schema_version(&app).await;
+
+    log::warn!("Restoring backup {id}");
+    stop_backend(&app).await?;
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        restore(&data_dir, &source, before, key.as_ref())
+    })
+    .await
+    .map_err(|e| format!("Failed to restore backup: {e}"))
+    .and_then(|result| result);
+
+    // Start again whether or not the restore worked: on failure everything
+    // was put back, so this is the old data.
+    if result.is_ok() {
+        settings::reload_changed(&app);
+        *tracker.0.lock().unwrap() = RestartTracker::load(&app).0.into_inner().unwrap();
+        config_watch::rescan();
+    }
+    audit::record(
+        &app,
+        AuditEvent::BackupRestore,
+        result.is_ok(),
+        match &result {
+            Ok(_) => format!("Restored {id}"),
+            Err(e) => format!("Restoring {id} failed: {e}"),
+        },
+    );
+    let started = backend::start_backend(&app).await;
+    let (replaced, before) = result?;
+    started?;
+    log::info!("Restored backup {id}, replaced {replaced:?}");
+    Ok(RestoreReport {
+        restored: id,
+        replaced,
+        before_restore: file_name(&before),
+    })
+}
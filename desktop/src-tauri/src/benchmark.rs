@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::settings;
+use crate::transport::{self, Endpoint};
+
+/// Benchmarks every comparison includes unless the user removes them.
+const DEFAULT_BENCHMARKS: [&str; 2] = ["SPY", "QQQ"];
+
+/// Trading days used when annualizing alpha.
+const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+
+/// Default rolling window (in trading days) for alpha/beta.
+const DEFAULT_WINDOW: usize = 60;
+
+/// A dated value, used for both the portfolio equity curve passed in by the
+/// frontend and benchmark closes fetched from the backend.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SeriesPoint {
+    pub date: NaiveDate,
+    pub value: f64,
+}
+
+/// Most rows `/stocks/{symbol}/history` returns at once, newest first.
+const HISTORY_PAGE_ROWS: usize = 1000;
+
+/// Subset of the backend's `/stocks/{symbol}/history` rows.
+#[derive(Deserialize)]
+struct PriceRow {
+    date: NaiveDate,
+    close: f64,
+    adjusted_close: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ComparisonPoint {
+    pub date: NaiveDate,
+    /// Cumulative return since the first aligned date.
+    pub portfolio_return: f64,
+    pub benchmark_return: f64,
+    /// `portfolio_return - benchmark_return`.
+    pub relative_return: f64,
+    pub rolling_beta: Option<f64>,
+    /// Annualized Jensen's alpha over the rolling window.
+    pub rolling_alpha: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BenchmarkComparison {
+    pub symbol: String,
+    pub points: Vec<ComparisonPoint>,
+    /// Full-period beta and annualized alpha.
+    pub beta: Option<f64>,
+    pub alpha: Option<f64>,
+}
+
+/// The `[benchmarks]` section of `settings.toml`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BenchmarkSettings {
+    /// The symbols performance views compare against, e.g. `["SPY", "QQQ"]`.
+    pub symbols: Vec<String>,
+}
+
+impl Default for BenchmarkSettings {
+    fn default() -> Self {
+        Self {
+            symbols: DEFAULT_BENCHMARKS.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+/// Tauri command: the benchmark symbols used by performance views.
+#[tauri::command]
+pub async fn get_benchmarks(app: AppHandle) -> Result<Vec<String>, String> {
+    Ok(settings::current(&app).benchmarks.symbols)
+}
+
+/// Tauri command: replace the benchmark list (symbols are upper-cased and
+/// de-duplicated), saved as `benchmarks.symbols`.
+#[tauri::command]
+pub async fn set_benchmarks(app: AppHandle, symbols: Vec<String>) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for symbol in symbols {
+        let symbol = symbol.trim().to_ascii_uppercase();
+        if !symbol.is_empty() && !normalized.contains(&symbol) {
+            normalized.push(symbol);
+        }
+    }
+    let symbols = normalized.clone();
+    settings::update(&app, |settings| settings.benchmarks.symbols = symbols)?;
+    Ok(normalized)
+}
+
+/// Tauri command: compare a portfolio equity curve against each configured
+/// benchmark (or `symbols`, if given).
+///
+/// Benchmark closes come from the backend's price history and are aligned to
+/// the portfolio's dates; days missing from either series are dropped.
+#[tauri::command]
+pub async fn compare_to_benchmarks(
+    app: AppHandle,
+    portfolio: Vec<SeriesPoint>,
+    symbols: Option<Vec<String>>,
+    window: Option<usize>,
+) -> Result<Vec<BenchmarkComparison>, String> {
+    let endpoint = crate::backend::endpoint(&app).ok_or("Backend has not been started")?;
+    let symbols = match symbols {
+        Some(symbols) => symbols,
+        None => get_benchmarks(app).await?,
+    };
+    let window = window.unwrap_or(DEFAULT_WINDOW).max(2);
+
+    let mut portfolio = portfolio;
+    portfolio.sort_by_key(|p| p.date);
+    let Some(start) = portfolio.first().map(|p| p.date) else {
+        return Ok(Vec::new());
+    };
+
+    let client = transport::Client::new(Duration::from_secs(10))?;
+
+    let mut comparisons = Vec::with_capacity(symbols.len());
+    for symbol in symbols {
+        let closes = fetch_closes(&client, &endpoint, &symbol, start).await?;
+        comparisons.push(compare(&symbol, &portfolio, &closes, window));
+    }
+    Ok(comparisons)
+}
+
+/// Closes of `symbol` since `start`. The backend returns at most
+/// `HISTORY_PAGE_ROWS` rows, newest first, so a longer range is fetched in
+/// pages, each ending the day before the oldest row of the last.
+async fn fetch_closes(
+    client: &transport::Client,
+    endpoint: &Endpoint,
+    symbol: &str,
+    start: NaiveDate,
+) -> Result<HashMap<NaiveDate, f64>, String> {
+    let segment = transport::path_segment(symbol);
+    let mut closes = HashMap::new();
+    let mut end: Option<NaiveDate> = None;
+    loop {
+        let mut path = format!(
+            "/api/v1/stocks/{segment}/history?start_date={start}&limit={HISTORY_PAGE_ROWS}"
+        );
+        if let Some(end) = end {
+            path.push_str(&format!("&end_date={end}"));
+        }
+        let resp = client
+            .get(endpoint, &path)
+            .await
+            .map_err(|e| format!("Failed to fetch {symbol} history: {e}"))?;
+        if !resp.status().is_success() {
+            return Err(format!(
+                "Failed to fetch {symbol} history: HTTP {}",
+                resp.status()
+            ));
+        }
+        let rows: Vec<PriceRow> =
+            transport::json(&resp).map_err(|e| format!("Failed to parse {symbol} history: {e}"))?;
+
+        let full = rows.len() == HISTORY_PAGE_ROWS;
+        let oldest = rows.iter().map(|row| row.date).min();
+        closes.extend(
+            rows.into_iter()
+                .map(|row| (row.date, row.adjusted_close.unwrap_or(row.close))),
+        );
+        end = match oldest.and_then(|date| date.pred_opt()) {
+            Some(before) if full && before >= start => Some(before),
+            _ => return Ok(closes),
+        };
+    }
+}
+
+fn compare(
+    symbol: &str,
+    portfolio: &[SeriesPoint],
+    closes: &HashMap<NaiveDate, f64>,
+    window: usize,
+) -> BenchmarkComparison {
+    // (date, portfolio value, benchmark close) for days present in both series.
+    let aligned: Vec<(NaiveDate, f64, f64)> = portfolio
+        .iter()
+        .filter_map(|p| closes.get(&p.date).map(|&b| (p.date, p.value, b)))
+        .filter(|&(_, p, b)| p > 0.0 && b > 0.0)
+        .collect();
+
+    let Some(&(_, p0, b0)) = aligned.first() else {
+        return BenchmarkComparison {
+            symbol: symbol.to_string(),
+            points: Vec::new(),
+            beta: None,
+            alpha: None,
+        };
+    };
+
+    // Daily returns; index i is the return from day i-1 to day i.
+    let returns: Vec<(f64, f64)> = aligned
+        .windows(2)
+        .map(|w| (w[1].1 / w[0].1 - 1.0, w[1].2 / w[0].2 - 1.0))
+        .collect();
+
+    let points = aligned
+        .iter()
+        .enumerate()
+        .map(|(i, &(date, p, b))| {
+            let portfolio_return = p / p0 - 1.0;
+            let benchmark_return = b / b0 - 1.0;
+            let (rolling_beta, rolling_alpha) = if i >= window {
+                regress(&returns[i - window..i])
+            } else {
+                (None, None)
+            };
+            ComparisonPoint {
+                date,
+                portfolio_return,
+                benchmark_return,
+                relative_return: portfolio_return - benchmark_return,
+                rolling_beta,
+                rolling_alpha,
+            }
+        })
+        .collect();
+
+    let (beta, alpha) = regress(&returns);
+    BenchmarkComparison {
+        symbol: symbol.to_string(),
+        points,
+        beta,
+        alpha,
+    }
+}
+
+/// Least-squares beta and annualized alpha of portfolio returns on benchmark
+/// returns. Returns `None`s when there are too few points or the benchmark
+/// didn't move.
+fn regress(returns: &[(f64, f64)]) -> (Option<f64>, Option<f64>) {
+    if returns.len() < 2 {
+        return (None, None);
+    }
+    let n = returns.len() as f64;
+    let mean_p = returns.iter().map(|r| r.0).sum::<f64>() / n;
+    let mean_b = returns.iter().map(|r| r.1).sum::<f64>() / n;
+    let cov = returns
+        .iter()
+        .map(|r| (r.0 - mean_p) * (r.1 - mean_b))
+        .sum::<f64>();
+    let var = returns.iter().map(|r| (r.1 - mean_b).powi(2)).sum::<f64>();
+    if var == 0.0 {
+        return (None, None);
+    }
+    let beta = cov / var;
+    let alpha = (mean_p - beta * mean_b) * TRADING_DAYS_PER_YEAR;
+    (Some(beta), Some(alpha))
+}
@@ -0,0 +1,105 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Flags of the desktop binary, e.g. for QA or for running two isolated
+/// installs side by side:
+///
+/// ```text
+/// teletraan --data-dir /tmp/qa --port 8123
+/// teletraan --no-spawn-backend --port 8000
+/// ```
+///
+/// Each value flag also accepts `--flag=value`. Unknown arguments are left
+/// alone, since the OS and Tauri pass some of their own.
+#[derive(Clone, Debug, Default)]
+pub struct Args {
+    /// Use this directory instead of the platform app data dir. Profiles
+    /// live below it as usual. See `config` for how flags, environment
+    /// variables and config files combine.
+    pub data_dir: Option<PathBuf>,
+    /// Spawn this backend binary instead of the bundled one.
+    pub backend_path: Option<PathBuf>,
+    /// Port for the spawned backend, over `backend.port` and
+    /// `TELETRAAN_BACKEND_PORT`. With `no_spawn_backend`, the port of the
+    /// backend to use.
+    pub port: Option<u16>,
+    /// Never spawn a backend; use one already listening on `port` (8000 by
+    /// default) instead.
+    pub no_spawn_backend: bool,
+    /// Use the development backend at this URL, see
+    /// `backend::dev_backend_url`.
+    pub backend_url: Option<String>,
+    /// Profile for this launch only, see `profile`.
+    pub profile: Option<String>,
+    /// Keep all data beside the app, as with a `portable.flag` file there
+    /// (see `config::portable_dir`).
+    pub portable: bool,
+}
+
+static ARGS: OnceLock<Args> = OnceLock::new();
+
+/// Parse the command line once, before the Tauri builder runs. Invalid
+/// values are logged and ignored.
+pub fn init() {
+    let args = parse(std::env::args().skip(1));
+    log::info!("Command-line flags: {args:?}");
+    let _ = ARGS.set(args);
+}
+
+/// The parsed flags; all defaults before `init`.
+pub fn args() -> &'static Args {
+    ARGS.get_or_init(Args::default)
+}
+
+fn parse(raw: impl Iterator<Item = String>) -> Args {
+    let mut args = Args::default();
+    let mut raw = raw.peekable();
+    while let Some(arg) = raw.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg, None),
+        };
+        let mut value = |flag: &str| {
+            let value = inline
+                .clone()
+                .or_else(|| raw.next_if(|next| !next.starts_with("--")));
+            if value.is_none() {
+                log::error!("Ignoring {flag}: it needs a value");
+            }
+            value
+        };
+        match flag.as_str() {
+            "--data-dir" => args.data_dir = value(&flag).map(absolute),
+            "--backend-path" => args.backend_path = value(&flag).map(absolute),
+            "--port" => {
+                args.port = value(&flag).and_then(|port| match port.parse::<u16>() {
+                    Ok(0) | Err(_) => {
+                        log::error!("Ignoring --port {port:?}: expected a port from 1 to 65535");
+                        None
+                    }
+                    Ok(port) => Some(port),
+                })
+            }
+            "--no-spawn-backend" => args.no_spawn_backend = true,
+            "--backend-url" => args.backend_url = value(&flag),
+            "--profile" => args.profile = value(&flag),
+            "--portable" => args.portable = true,
+            _ => {}
+        }
+    }
+    args
+}
+
+/// Relative paths are taken from the working directory the app was launched
+/// in, which the shell does not keep.
+pub fn absolute(path: String) -> PathBuf {
+    let path = PathBuf::from(path);
+    if path.is_absolute() {
+        return path;
+    }
+    std::env::current_dir()
+        .map(|dir| dir.join(&path))
+        .unwrap_or(path)
+}
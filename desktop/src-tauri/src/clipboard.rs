@@ -0,0 +1,222 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use serde::Deserialize;
+
+/// Candidate clipboard programs for this platform, tried in order.
+#[cfg(target_os = "macos")]
+const CLIPBOARD_COMMANDS: &[(&str, &[&str])] = &[("pbcopy", &[])];
+
+/// `clip.exe` mangles non-ASCII text, so PowerShell goes first.
+#[cfg(windows)]
+const CLIPBOARD_COMMANDS: &[(&str, &[&str])] = &[
+    (
+        "powershell",
+        &[
+            "-NoProfile",
+            "-Command",
+            "[Console]::InputEncoding = [Text.Encoding]::UTF8; Set-Clipboard -Value ([Console]::In.ReadToEnd())",
+        ],
+    ),
+    ("clip", &[]),
+];
+
+#[cfg(not(any(target_os = "macos", windows)))]
+const CLIPBOARD_COMMANDS: &[(&str, &[&str])] = &[
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+];
+
+/// Put `text` on the OS clipboard using the platform's clipboard utility.
+pub fn copy_text(text: &str) -> Result<(), String> {
+    let mut errors = Vec::new();
+    for (program, args) in CLIPBOARD_COMMANDS {
+        match pipe_to(program, args, text) {
+            Ok(()) => return Ok(()),
+            Err(e) => errors.push(format!("{program}: {e}")),
+        }
+    }
+    Err(format!(
+        "Failed to copy to clipboard ({})",
+        errors.join("; ")
+    ))
+}
+
+fn pipe_to(program: &str, args: &[&str], text: &str) -> Result<(), String> {
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+
+        // Don't flash a console window.
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let mut child = command.spawn().map_err(|e| e.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| e.to_string())?;
+    }
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(status.to_string())
+    }
+}
+
+/// How `copy_to_clipboard` renders a table.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardFormat {
+    /// Tab-separated, which spreadsheets paste into cells.
+    Tsv,
+    /// A GitHub-flavoured Markdown table, for notes.
+    Markdown,
+    /// Space-aligned columns.
+    Text,
+}
+
+/// What to copy: a table, or text that is copied unchanged.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ClipboardPayload {
+    Table {
+        columns: Vec<String>,
+        rows: Vec<Vec<serde_json::Value>>,
+    },
+    Text(String),
+}
+
+/// Tauri command: render `payload` as `format` and put it on the OS
+/// clipboard, e.g. to paste an insight table into a spreadsheet.
+#[tauri::command]
+pub async fn copy_to_clipboard(
+    format: ClipboardFormat,
+    payload: ClipboardPayload,
+) -> Result<(), String> {
+    let text = match payload {
+        ClipboardPayload::Text(text) => text,
+        ClipboardPayload::Table { columns, rows } => {
+            let rows: Vec<Vec<String>> = rows
+                .iter()
+                .map(|row| row.iter().map(cell_text).collect())
+                .collect();
+            match format {
+                ClipboardFormat::Tsv => render_tsv(&columns, &rows),
+                ClipboardFormat::Markdown => render_markdown(&columns, &rows),
+                ClipboardFormat::Text => render_text(&columns, &rows),
+            }
+        }
+    };
+    tauri::async_runtime::spawn_blocking(move || copy_text(&text))
+        .await
+        .map_err(|e| format!("Failed to copy to clipboard: {e}"))?
+}
+
+fn cell_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// A cell on one line, since every format here is line-based.
+fn single_line(cell: &str) -> String {
+    cell.split(['\r', '\n'])
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn render_tsv(columns: &[String], rows: &[Vec<String>]) -> String {
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .map(|cell| single_line(cell).replace('\t', " "))
+            .collect::<Vec<_>>()
+            .join("\t")
+    };
+    std::iter::once(line(columns))
+        .chain(rows.iter().map(|row| line(row)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_markdown(columns: &[String], rows: &[Vec<String>]) -> String {
+    let line = |cells: &[String]| {
+        let cells: Vec<String> = (0..columns.len())
+            .map(|i| single_line(cells.get(i).map_or("", String::as_str)).replace('|', "\\|"))
+            .collect();
+        format!("| {} |", cells.join(" | "))
+    };
+    // Right-align columns whose values are all numbers, like the UI does.
+    let separator: Vec<&str> = (0..columns.len())
+        .map(|i| {
+            let numeric = rows
+                .iter()
+                .all(|row| row.get(i).is_none_or(|cell| is_numeric(cell)));
+            if numeric && !rows.is_empty() {
+                "---:"
+            } else {
+                "---"
+            }
+        })
+        .collect();
+    std::iter::once(line(columns))
+        .chain(std::iter::once(format!("| {} |", separator.join(" | "))))
+        .chain(rows.iter().map(|row| line(row)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_text(columns: &[String], rows: &[Vec<String>]) -> String {
+    let cells = |row: &[String]| -> Vec<String> {
+        (0..columns.len())
+            .map(|i| single_line(row.get(i).map_or("", String::as_str)))
+            .collect()
+    };
+    let table: Vec<Vec<String>> = std::iter::once(cells(columns))
+        .chain(rows.iter().map(|row| cells(row)))
+        .collect();
+    let widths: Vec<usize> = (0..columns.len())
+        .map(|i| {
+            table
+                .iter()
+                .map(|row| row[i].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    table
+        .iter()
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, &width)| format!("{cell:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A number as the UI shows it, e.g. `12.5`, `+1.20%` or `-3`.
+fn is_numeric(cell: &str) -> bool {
+    let cell = cell
+        .trim()
+        .trim_end_matches('%')
+        .trim_start_matches(['+', '$']);
+    !cell.is_empty() && cell.replace(',', "").parse::<f64>().is_ok()
+}
@@ -0,0 +1,498 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use serde::Serialize;
+
+use crate::cli;
+use crate::health::HealthCheckConfig;
+use crate::logging::LogConfig;
+use crate::port::{BindConfig, Transport};
+use crate::restart::{RestartPolicy, RestartStrategy};
+use crate::settings::{Settings, UpdateChannel};
+
+/// A `TELETRAAN_*` environment variable the shell reads.
+pub struct EnvOverride {
+    pub name: &'static str,
+    /// The setting it overrides.
+    pub overrides: &'static str,
+}
+
+const fn var(name: &'static str, overrides: &'static str) -> EnvOverride {
+    EnvOverride { name, overrides }
+}
+
+/// Every shell setting that can be overridden from the environment.
+///
+/// A setting is resolved from, highest precedence first:
+/// 1. its command-line flag, where it has one (see `cli`),
+/// 2. its `TELETRAAN_*` variable,
+/// 3. `settings.toml` in the data dir,
+/// 4. the built-in default.
+///
+/// An invalid value is logged and ignored, so the next source applies.
+pub const ENV_OVERRIDES: &[EnvOverride] = &[
+    var("TELETRAAN_DATA_DIR", "app data directory (--data-dir)"),
+    var("TELETRAAN_PROFILE", "active profile (--profile)"),
+    var(
+        "TELETRAAN_BACKEND_URL",
+        "settings.toml backend.external_url (--backend-url)",
+    ),
+    var("TELETRAAN_BACKEND_HOST", "settings.toml backend.host"),
+    var(
+        "TELETRAAN_BACKEND_PORT",
+        "settings.toml backend.port (--port)",
+    ),
+    var(
+        "TELETRAAN_BACKEND_TRANSPORT",
+        "settings.toml backend.transport",
+    ),
+    var("TELETRAAN_REMOTE_MODE", "settings.toml backend.remote_mode"),
+    var("TELETRAAN_BACKEND_TLS", "settings.toml backend.tls"),
+    var(
+        "TELETRAAN_SHUTDOWN_GRACE_SECS",
+        "settings.toml backend.shutdown_grace_secs",
+    ),
+    var("TELETRAAN_BACKEND_SANDBOX", "settings.toml backend.sandbox"),
+    var(
+        "TELETRAAN_CHECK_DEPENDENCIES",
+        "settings.toml general.check_dependencies",
+    ),
+    var("TELETRAAN_EXPORT_DIR", "settings.toml export.dir"),
+    var(
+        "TELETRAAN_SHELL_LOG_LEVEL",
+        "settings.toml logging.shell_level",
+    ),
+    var(
+        "TELETRAAN_SNAPSHOT_ON_START",
+        "settings.toml backup.snapshot_on_start",
+    ),
+    var(
+        "TELETRAAN_KEEP_SNAPSHOTS",
+        "settings.toml backup.keep_snapshots",
+    ),
+    var(
+        "TELETRAAN_KEEP_BACKUPS",
+        "settings.toml backup.keep_backups",
+    ),
+    var(
+        "TELETRAAN_CLEANUP_ON_STARTUP",
+        "settings.toml retention.on_startup",
+    ),
+    var(
+        "TELETRAAN_PRUNE_MARKET_DATA",
+        "settings.toml retention.prune_market_data",
+    ),
+    var(
+        "TELETRAAN_CANDLE_RETENTION_DAYS",
+        "settings.toml retention.candle_days",
+    ),
+    var(
+        "TELETRAAN_OUTCOME_RETENTION_DAYS",
+        "settings.toml retention.outcome_days",
+    ),
+    var("TELETRAAN_UPDATE_CHANNEL", "settings.toml updates.channel"),
+    var(
+        "TELETRAAN_CHECK_FOR_UPDATES",
+        "settings.toml updates.check_on_startup",
+    ),
+    var(
+        "TELETRAAN_LOG_MAX_FILE_MB",
+        "settings.toml logging.max_file_mb",
+    ),
+    var(
+        "TELETRAAN_LOG_KEEP_FILES",
+        "settings.toml logging.keep_files",
+    ),
+    var(
+        "TELETRAAN_LOG_KEEP_SESSIONS",
+        "settings.toml logging.keep_sessions",
+    ),
+    var(
+        "TELETRAAN_LOG_MAX_AGE_DAYS",
+        "settings.toml logging.max_age_days",
+    ),
+    var("TELETRAAN_LOG_COMPRESS", "settings.toml logging.compress"),
+    var(
+        "TELETRAAN_STARTUP_POLL_INTERVAL_MS",
+        "settings.toml health_check.startup_initial_interval_ms and startup_max_interval_ms",
+    ),
+    var(
+        "TELETRAAN_HEALTH_INITIAL_INTERVAL_MS",
+        "settings.toml health_check.startup_initial_interval_ms",
+    ),
+    var(
+        "TELETRAAN_HEALTH_MAX_INTERVAL_MS",
+        "settings.toml health_check.startup_max_interval_ms",
+    ),
+    var(
+        "TELETRAAN_STARTUP_TIMEOUT_SECS",
+        "settings.toml health_check.startup_timeout_secs",
+    ),
+    var(
+        "TELETRAAN_STARTUP_MAX_ATTEMPTS",
+        "settings.toml health_check.startup_max_attempts",
+    ),
+    var(
+        "TELETRAAN_HEALTH_TIMEOUT_MS",
+        "settings.toml health_check.request_timeout_ms",
+    ),
+    var(
+        "TELETRAAN_HEALTH_WATCHDOG_INTERVAL_SECS",
+        "settings.toml health_check.watchdog_interval_secs",
+    ),
+    var(
+        "TELETRAAN_HEALTH_UNHEALTHY_THRESHOLD",
+        "settings.toml health_check.unhealthy_threshold",
+    ),
+    var(
+        "TELETRAAN_RESTART_STRATEGY",
+        "settings.toml restart.strategy",
+    ),
+    var(
+        "TELETRAAN_RESTART_MAX_ATTEMPTS",
+        "settings.toml restart.max_attempts",
+    ),
+    var(
+        "TELETRAAN_RESTART_INITIAL_DELAY_MS",
+        "settings.toml restart.initial_delay_ms",
+    ),
+    var(
+        "TELETRAAN_RESTART_MAX_DELAY_MS",
+        "settings.toml restart.max_delay_ms",
+    ),
+    var(
+        "TELETRAAN_RESTART_COOLDOWN_SECS",
+        "settings.toml restart.cooldown_secs",
+    ),
+    var(
+        "TELETRAAN_RESTART_ON_UNHEALTHY",
+        "settings.toml restart.restart_on_unhealthy",
+    ),
+];
+
+/// `TELETRAAN_*` variables meant for the backend, which it inherits.
+const BACKEND_VARS: &[&str] = &["TELETRAAN_LOG_LEVEL"];
+
+/// The trimmed value of `name`, unless it is unset or blank.
+fn env(name: &str) -> Option<String> {
+    debug_assert!(ENV_OVERRIDES.iter().any(|known| known.name == name));
+    std::env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn parse<T: FromStr>(name: &str) -> Option<T>
+where
+    T::Err: std::fmt::Display,
+{
+    let value = env(name)?;
+    value
+        .parse()
+        .inspect_err(|e| log::warn!("Ignoring invalid {name} {value:?}: {e}"))
+        .ok()
+}
+
+/// A positive number; 0 is ignored like an invalid value.
+fn positive<T: FromStr + PartialOrd + Default>(name: &str) -> Option<T>
+where
+    T::Err: std::fmt::Display,
+{
+    parse(name).filter(|value| {
+        let positive = *value > T::default();
+        if !positive {
+            log::warn!("Ignoring {name}: it must be greater than 0");
+        }
+        positive
+    })
+}
+
+fn flag(name: &str) -> Option<bool> {
+    let value = env(name)?;
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => {
+            log::warn!("Ignoring invalid {name} {value:?}: expected true or false");
+            None
+        }
+    }
+}
+
+/// Log the overrides in effect, and any `TELETRAAN_*` variable the shell does
+/// not know, which is most likely a typo.
+pub fn report() {
+    if let Some(dir) = portable_dir() {
+        log::info!("Portable mode: keeping all data in {}", dir.display());
+    }
+    for active in active() {
+        log::info!(
+            "{} = {:?} overrides {}",
+            active.name,
+            active.value,
+            active.overrides
+        );
+    }
+    for (name, _) in std::env::vars() {
+        let known = ENV_OVERRIDES.iter().any(|known| known.name == name)
+            || BACKEND_VARS.contains(&name.as_str());
+        if name.starts_with("TELETRAAN_") && !known {
+            log::warn!("Ignoring unknown environment variable {name}");
+        }
+    }
+}
+
+/// Entry of `get_config_overrides`.
+#[derive(Clone, Debug, Serialize)]
+pub struct ActiveOverride {
+    pub name: &'static str,
+    pub value: String,
+    pub overrides: &'static str,
+}
+
+fn active() -> Vec<ActiveOverride> {
+    ENV_OVERRIDES
+        .iter()
+        .filter_map(|known| {
+            Some(ActiveOverride {
+                name: known.name,
+                value: env(known.name)?,
+                overrides: known.overrides,
+            })
+        })
+        .collect()
+}
+
+/// Tauri command: the `TELETRAAN_*` variables set for this launch and the
+/// settings they override, for the settings page.
+#[tauri::command]
+pub async fn get_config_overrides() -> Result<Vec<ActiveOverride>, String> {
+    Ok(active())
+}
+
+/// Beside the executable, or on macOS beside the app bundle: turns on
+/// portable mode.
+pub const PORTABLE_FLAG: &str = "portable.flag";
+
+/// Folder beside the app that holds all data in portable mode.
+const PORTABLE_DATA_DIR: &str = "teletraan-data";
+
+/// The folder of the executable, and on macOS the one holding
+/// `Teletraan.app`, which is where a user sees the app. Nothing is written
+/// inside the signed bundle.
+fn install_dirs() -> Vec<PathBuf> {
+    let Some(exe_dir) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    else {
+        return Vec::new();
+    };
+    // Teletraan.app/Contents/MacOS/<binary>
+    let bundle_parent = exe_dir
+        .ancestors()
+        .nth(2)
+        .filter(|bundle| bundle.extension().is_some_and(|ext| ext == "app"))
+        .and_then(Path::parent)
+        .map(Path::to_path_buf);
+    match bundle_parent {
+        Some(dir) => vec![dir, exe_dir],
+        None => vec![exe_dir],
+    }
+}
+
+/// In portable mode (`--portable`, or a `portable.flag` beside the app), the
+/// `teletraan-data` folder beside the app, for running from a USB stick or
+/// keeping the data with the install.
+pub fn portable_dir() -> Option<PathBuf> {
+    static PORTABLE: OnceLock<Option<PathBuf>> = OnceLock::new();
+    PORTABLE
+        .get_or_init(|| {
+            let dirs = install_dirs();
+            let enabled =
+                cli::args().portable || dirs.iter().any(|dir| dir.join(PORTABLE_FLAG).is_file());
+            enabled
+                .then(|| dirs.first().map(|dir| dir.join(PORTABLE_DATA_DIR)))
+                .flatten()
+        })
+        .clone()
+}
+
+/// The app data root: `--data-dir`, else `TELETRAAN_DATA_DIR`, else the
+/// portable folder.
+pub fn data_dir() -> Option<PathBuf> {
+    cli::args()
+        .data_dir
+        .clone()
+        .or_else(|| env("TELETRAAN_DATA_DIR").map(cli::absolute))
+        .or_else(portable_dir)
+}
+
+/// The profile for this launch: `--profile`, else `TELETRAAN_PROFILE`.
+pub fn profile() -> Option<String> {
+    cli::args()
+        .profile
+        .clone()
+        .or_else(|| env("TELETRAAN_PROFILE"))
+}
+
+/// Apply the overrides to the `settings.toml` settings.
+pub fn settings(settings: &mut Settings) {
+    bind(&mut settings.backend.bind);
+    health(&mut settings.health_check);
+    restart_policy(&mut settings.restart);
+    logging(&mut settings.logging.files);
+    if let Some(url) = cli::args()
+        .backend_url
+        .clone()
+        .or_else(|| env("TELETRAAN_BACKEND_URL"))
+    {
+        settings.backend.external_url = Some(url);
+    }
+    if let Some(secs) = parse("TELETRAAN_SHUTDOWN_GRACE_SECS") {
+        settings.backend.shutdown_grace_secs = secs;
+    }
+    if let Some(sandbox) = flag("TELETRAAN_BACKEND_SANDBOX") {
+        settings.backend.sandbox = sandbox;
+    }
+    if let Some(check) = flag("TELETRAAN_CHECK_DEPENDENCIES") {
+        settings.general.check_dependencies = check;
+    }
+    if let Some(snapshot) = flag("TELETRAAN_SNAPSHOT_ON_START") {
+        settings.backup.snapshot_on_start = snapshot;
+    }
+    if let Some(keep) = positive("TELETRAAN_KEEP_SNAPSHOTS") {
+        settings.backup.keep_snapshots = keep;
+    }
+    if let Some(keep) = parse("TELETRAAN_KEEP_BACKUPS") {
+        settings.backup.keep_backups = keep;
+    }
+    if let Some(on_startup) = flag("TELETRAAN_CLEANUP_ON_STARTUP") {
+        settings.retention.on_startup = on_startup;
+    }
+    if let Some(prune) = flag("TELETRAAN_PRUNE_MARKET_DATA") {
+        settings.retention.prune_market_data = prune;
+    }
+    if let Some(days) = parse("TELETRAAN_CANDLE_RETENTION_DAYS") {
+        settings.retention.candle_days = days;
+    }
+    if let Some(days) = parse("TELETRAAN_OUTCOME_RETENTION_DAYS") {
+        settings.retention.outcome_days = days;
+    }
+    match env("TELETRAAN_UPDATE_CHANNEL").as_deref() {
+        Some("stable") => settings.updates.channel = UpdateChannel::Stable,
+        Some("beta") => settings.updates.channel = UpdateChannel::Beta,
+        Some(other) => log::warn!("Ignoring unknown TELETRAAN_UPDATE_CHANNEL {other:?}"),
+        None => {}
+    }
+    if let Some(check) = flag("TELETRAAN_CHECK_FOR_UPDATES") {
+        settings.updates.check_on_startup = check;
+    }
+    if let Some(dir) = env("TELETRAAN_EXPORT_DIR") {
+        settings.export.dir = Some(cli::absolute(dir));
+    }
+    if let Some(level) = env("TELETRAAN_SHELL_LOG_LEVEL") {
+        if level.parse::<log::LevelFilter>().is_ok() {
+            settings.logging.shell_level = Some(level);
+        } else {
+            log::warn!("Ignoring invalid TELETRAAN_SHELL_LOG_LEVEL {level:?}");
+        }
+    }
+}
+
+/// Apply the overrides to where the backend listens.
+fn bind(config: &mut BindConfig) {
+    if let Some(host) = env("TELETRAAN_BACKEND_HOST") {
+        config.host = host;
+    }
+    if let Some(port) = cli::args().port.or_else(|| parse("TELETRAAN_BACKEND_PORT")) {
+        config.port = Some(port);
+    }
+    match env("TELETRAAN_BACKEND_TRANSPORT").as_deref() {
+        Some("tcp") => config.transport = Transport::Tcp,
+        Some("socket") => config.transport = Transport::Socket,
+        Some(other) => log::warn!("Ignoring unknown TELETRAAN_BACKEND_TRANSPORT {other:?}"),
+        None => {}
+    }
+    if let Some(remote) = flag("TELETRAAN_REMOTE_MODE") {
+        config.remote_mode = remote;
+    }
+    if let Some(tls) = flag("TELETRAAN_BACKEND_TLS") {
+        config.tls = tls;
+    }
+}
+
+/// Apply the overrides to the health check schedule.
+fn health(config: &mut HealthCheckConfig) {
+    // A fixed interval (the pre-backoff setting) pins both ends of the schedule.
+    if let Some(ms) = positive("TELETRAAN_STARTUP_POLL_INTERVAL_MS") {
+        config.startup_initial_interval_ms = ms;
+        config.startup_max_interval_ms = ms;
+    }
+    if let Some(ms) = positive("TELETRAAN_HEALTH_INITIAL_INTERVAL_MS") {
+        config.startup_initial_interval_ms = ms;
+    }
+    if let Some(ms) = positive("TELETRAAN_HEALTH_MAX_INTERVAL_MS") {
+        config.startup_max_interval_ms = ms;
+    }
+    if let Some(secs) = positive("TELETRAAN_STARTUP_TIMEOUT_SECS") {
+        config.startup_timeout_secs = secs;
+    }
+    if let Some(n) = positive("TELETRAAN_STARTUP_MAX_ATTEMPTS") {
+        config.startup_max_attempts = Some(n);
+    }
+    if let Some(ms) = positive("TELETRAAN_HEALTH_TIMEOUT_MS") {
+        config.request_timeout_ms = ms;
+    }
+    if let Some(secs) = positive("TELETRAAN_HEALTH_WATCHDOG_INTERVAL_SECS") {
+        config.watchdog_interval_secs = secs;
+    }
+    if let Some(n) = positive("TELETRAAN_HEALTH_UNHEALTHY_THRESHOLD") {
+        config.unhealthy_threshold = n;
+    }
+}
+
+/// Apply the overrides to the restart policy.
+fn restart_policy(policy: &mut RestartPolicy) {
+    match env("TELETRAAN_RESTART_STRATEGY").as_deref() {
+        Some("immediate") => policy.strategy = RestartStrategy::Immediate,
+        Some("exponential_backoff") => policy.strategy = RestartStrategy::ExponentialBackoff,
+        Some(other) => log::warn!("Ignoring unknown TELETRAAN_RESTART_STRATEGY {other:?}"),
+        None => {}
+    }
+    // 0 is meaningful here: it disables automatic restarts.
+    if let Some(n) = parse("TELETRAAN_RESTART_MAX_ATTEMPTS") {
+        policy.max_attempts = n;
+    }
+    if let Some(ms) = parse("TELETRAAN_RESTART_INITIAL_DELAY_MS") {
+        policy.initial_delay_ms = ms;
+    }
+    if let Some(ms) = parse("TELETRAAN_RESTART_MAX_DELAY_MS") {
+        policy.max_delay_ms = ms;
+    }
+    if let Some(secs) = parse("TELETRAAN_RESTART_COOLDOWN_SECS") {
+        policy.cooldown_secs = secs;
+    }
+    if let Some(restart) = flag("TELETRAAN_RESTART_ON_UNHEALTHY") {
+        policy.restart_on_unhealthy = restart;
+    }
+}
+
+/// Apply the overrides to the log file limits.
+fn logging(config: &mut LogConfig) {
+    if let Some(mb) = positive("TELETRAAN_LOG_MAX_FILE_MB") {
+        config.max_file_mb = mb;
+    }
+    if let Some(n) = parse("TELETRAAN_LOG_KEEP_FILES") {
+        config.keep_files = n;
+    }
+    if let Some(n) = parse("TELETRAAN_LOG_KEEP_SESSIONS") {
+        config.keep_sessions = n;
+    }
+    if let Some(days) = parse("TELETRAAN_LOG_MAX_AGE_DAYS") {
+        config.max_age_days = days;
+    }
+    if let Some(compress) = flag("TELETRAAN_LOG_COMPRESS") {
+        config.compress = compress;
+    }
+}
@@ -0,0 +1,149 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::backend;
+use crate::profile;
+use crate::restart::RestartTracker;
+use crate::settings;
+
+/// How often the config files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Set by `rescan` to take the file on disk as the new baseline.
+static RESCAN: AtomicBool = AtomicBool::new(false);
+
+/// Managed state: changes waiting for a backend restart the user has not
+/// confirmed yet.
+#[derive(Default)]
+pub struct PendingRestart(Mutex<Vec<String>>);
+
+/// Payload of the `backend-restart-required` event.
+#[derive(Clone, Debug, Serialize)]
+struct RestartRequired {
+    /// Settings keys (e.g. `env.HTTPS_PROXY`) waiting for the restart,
+    /// including earlier unconfirmed ones.
+    changes: Vec<String>,
+}
+
+/// Whether a change to the settings `key` only applies to a new backend
+/// process (or, for `backend.external_url`, a new connection). Everything
+/// else is read by the shell on use.
+pub fn needs_backend_restart(key: &str) -> bool {
+    key.starts_with("env.")
+        || (key.starts_with("backend.") && key != "backend.shutdown_grace_secs")
+        || key == "review.required"
+}
+
+/// Record changes that need a backend restart and ask the user to confirm it
+/// with `backend-restart-required`. Nothing happens while no backend is
+/// running or in use, since the next start picks the changes up anyway.
+pub fn require_restart(app: &AppHandle, changes: Vec<String>) {
+    if changes.is_empty() || (backend::current_pid(app).is_none() && !backend::is_external(app)) {
+        return;
+    }
+    let Some(pending) = app.try_state::<PendingRestart>() else {
+        return;
+    };
+    let changes = {
+        let mut pending = pending.0.lock().unwrap();
+        for change in changes {
+            if !pending.contains(&change) {
+                pending.push(change);
+            }
+        }
+        pending.clone()
+    };
+    log::info!("Backend restart required to apply {changes:?}");
+    if let Err(e) = app.emit("backend-restart-required", RestartRequired { changes }) {
+        log::warn!("Failed to emit backend-restart-required: {e}");
+    }
+}
+
+/// Forget the pending changes once a new backend has picked them up.
+pub fn clear(app: &AppHandle) {
+    if let Some(pending) = app.try_state::<PendingRestart>() {
+        pending.0.lock().unwrap().clear();
+    }
+}
+
+/// Take `settings.toml` as it is now as seen, after the shell replaced it
+/// itself and reloads and restarts what it affects on its own.
+pub fn rescan() {
+    RESCAN.store(true, Ordering::SeqCst);
+}
+
+/// Watch `settings.toml` in the active data dir. Edits are applied right
+/// away; changes that need a new backend are passed to `require_restart`.
+pub fn spawn(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last = stamps(&app);
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let now = stamps(&app);
+            if RESCAN.swap(false, Ordering::SeqCst) {
+                last = now;
+                continue;
+            }
+            // A profile switch changes the data dir and reloads everything
+            // itself; only start comparing against the new file.
+            if now.data_dir == last.data_dir && now.settings != last.settings {
+                log::info!("settings.toml changed on disk, reloading");
+                let restart = settings::reload_changed(&app)
+                    .into_iter()
+                    .filter(|key| needs_backend_restart(key))
+                    .collect();
+                require_restart(&app, restart);
+            }
+            last = now;
+        }
+    });
+}
+
+/// Modification time of the watched file.
+struct Stamps {
+    data_dir: Option<PathBuf>,
+    settings: Option<SystemTime>,
+}
+
+fn stamps(app: &AppHandle) -> Stamps {
+    // Not `resolve_data_dir`, which creates and logs the directory.
+    let data_dir = crate::app_data_root(app)
+        .ok()
+        .map(|root| profile::data_dir(&root));
+    let settings = data_dir
+        .as_ref()
+        .and_then(|dir| std::fs::metadata(dir.join("settings.toml")).ok())
+        .and_then(|m| m.modified().ok());
+    Stamps { data_dir, settings }
+}
+
+/// Tauri command: the changes waiting for a backend restart.
+#[tauri::command]
+pub async fn get_pending_restart(
+    pending: State<'_, PendingRestart>,
+) -> Result<Vec<String>, String> {
+    Ok(pending.0.lock().unwrap().clone())
+}
+
+/// Tauri command: restart the backend to apply the pending changes, after
+/// the user confirmed `backend-restart-required`.
+#[tauri::command]
+pub async fn apply_pending_restart(
+    app: AppHandle,
+    pending: State<'_, PendingRestart>,
+    tracker: State<'_, RestartTracker>,
+) -> Result<(), String> {
+    let changes = pending.0.lock().unwrap().clone();
+    log::info!("Restarting backend to apply {changes:?}");
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || backend::stop_backend(&handle))
+        .await
+        .map_err(|e| format!("Failed to stop backend: {e}"))?;
+    tracker.reset();
+    backend::start_backend(&app).await
+}
@@ -0,0 +1,202 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::backups::{self, BackupInfo, RestoreReport};
+use crate::db_encryption;
+use crate::paths;
+use crate::restart::RestartTracker;
+
+/// First 16 bytes of every SQLite database file.
+pub const SQLITE_MAGIC: &[u8; 16] = b"SQLite format 3\0";
+
+/// First 4 bytes of a write-ahead log, by checksum byte order.
+const WAL_MAGIC: [[u8; 4]; 2] = [[0x37, 0x7f, 0x06, 0x82], [0x37, 0x7f, 0x06, 0x83]];
+
+/// What noticed the damage.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CorruptionSource {
+    /// The file check before a backend start.
+    FileCheck,
+    /// The backend exited with SQLite reporting a malformed database.
+    BackendLog,
+}
+
+/// Payload of `database-corrupt` and result of `get_database_corruption`.
+#[derive(Clone, Debug, Serialize)]
+pub struct DatabaseCorruption {
+    pub source: CorruptionSource,
+    pub detail: String,
+    pub database: PathBuf,
+    pub detected_at: String,
+    /// The newest backup with a database that passes the file check and is
+    /// not a copy of the damaged one; `None` if there is none.
+    pub snapshot: Option<BackupInfo>,
+    /// Human-readable explanation suitable for showing to the user as-is.
+    pub message: String,
+}
+
+/// Managed state: the damage found since the last good start.
+#[derive(Default)]
+pub struct Corruption(Mutex<Option<DatabaseCorruption>>);
+
+/// Look for damage SQLite itself would reject the file for: a missing
+/// header, an invalid page size, a file cut off partway through a page or a
+/// write-ahead log that is not one. Returns what is wrong, or `None` when the
+/// file looks sound or does not exist yet.
+///
+/// Without SQLite in the shell this only reads the file structure; damage
+/// inside pages is caught when the backend fails on it.
+pub fn check_file(database: &Path) -> Option<String> {
+    let mut file = match std::fs::File::open(database) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(e) => return Some(format!("the database cannot be read: {e}")),
+    };
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    // SQLite creates the file empty and writes the header with the first table.
+    if len == 0 {
+        return None;
+    }
+    let mut header = [0u8; 100];
+    if file.read_exact(&mut header).is_err() {
+        return Some(format!("the database file is truncated ({len} bytes)"));
+    }
+    if &header[..16] != SQLITE_MAGIC {
+        return Some("the database file has no SQLite header".to_string());
+    }
+    let page_size = match u16::from_be_bytes([header[16], header[17]]) {
+        1 => 65536,
+        size => u64::from(size),
+    };
+    if !(512..=65536).contains(&page_size) || !page_size.is_power_of_two() {
+        return Some(format!(
+            "the database header has an invalid page size ({page_size})"
+        ));
+    }
+    if len % page_size != 0 {
+        return Some(format!(
+            "the database file ends partway through a page ({len} bytes, {page_size}-byte pages)"
+        ));
+    }
+
+    let wal = backups::with_suffix(database, "-wal");
+    let wal_len = std::fs::metadata(&wal).map(|m| m.len()).unwrap_or(0);
+    if wal_len > 0 {
+        let mut magic = [0u8; 4];
+        let read = std::fs::File::open(&wal).and_then(|mut file| file.read_exact(&mut magic));
+        if wal_len < 32 || read.is_err() {
+            return Some(format!(
+                "the write-ahead log is truncated ({wal_len} bytes)"
+            ));
+        }
+        if !WAL_MAGIC.contains(&magic) {
+            return Some("the write-ahead log is not a SQLite WAL file".to_string());
+        }
+    }
+    None
+}
+
+/// `check_file` for a database that may be encrypted with SQLCipher, whose
+/// header and write-ahead log cannot be read without the key: an encrypted
+/// one is only checked for being made of whole pages.
+fn check(database: &Path, may_be_encrypted: bool) -> Option<String> {
+    if !may_be_encrypted || db_encryption::is_plaintext(database) {
+        return check_file(database);
+    }
+    let len = std::fs::metadata(database).map(|m| m.len()).unwrap_or(0);
+    let page_size = db_encryption::SQLCIPHER_PAGE_SIZE;
+    (!len.is_multiple_of(page_size)).then(|| {
+        format!(
+            "the encrypted database ends partway through a page ({len} bytes, {page_size}-byte pages)"
+        )
+    })
+}
+
+/// Record damage to the database in `data_dir`, offer the newest healthy
+/// backup with a `database-corrupt` event and return the message for the
+/// user.
+pub fn report(
+    app: &AppHandle,
+    data_dir: &Path,
+    source: CorruptionSource,
+    detail: String,
+) -> String {
+    let database = paths::database_path(data_dir);
+    // A snapshot taken after the damage is byte-for-byte the same database.
+    let damaged = backups::hash_database(&database).ok().flatten();
+    let encrypted = db_encryption::may_be_encrypted(data_dir);
+    let snapshot = backups::newest_database_backup(data_dir, damaged.as_deref(), |db| {
+        check(db, encrypted).is_none()
+    });
+    let message = match &snapshot {
+        Some(snapshot) => format!(
+            "The database is damaged: {detail}. Restore the backup from {} to continue.",
+            snapshot.created_at
+        ),
+        None => format!(
+            "The database is damaged: {detail}. There is no backup to restore; \
+             a factory reset starts over with an empty database."
+        ),
+    };
+    log::error!("{message}");
+    let corruption = DatabaseCorruption {
+        source,
+        detail,
+        database,
+        detected_at: chrono::Utc::now().to_rfc3339(),
+        snapshot,
+        message: message.clone(),
+    };
+    if let Err(e) = app.emit("database-corrupt", &corruption) {
+        log::warn!("Failed to emit database-corrupt: {e}");
+    }
+    *app.state::<Corruption>().0.lock().unwrap() = Some(corruption);
+    message
+}
+
+/// Check the database before the backend starts on it. A damaged one is
+/// reported and the start refused, rather than letting the backend
+/// crash-loop on it; a sound one clears an earlier report.
+pub fn check_before_start(app: &AppHandle, data_dir: &Path) -> Result<(), String> {
+    let encrypted = db_encryption::may_be_encrypted(data_dir);
+    match check(&paths::database_path(data_dir), encrypted) {
+        Some(detail) => Err(report(app, data_dir, CorruptionSource::FileCheck, detail)),
+        None => {
+            *app.state::<Corruption>().0.lock().unwrap() = None;
+            Ok(())
+        }
+    }
+}
+
+/// Tauri command: the damage found since the last good start, or `null`.
+#[tauri::command]
+pub async fn get_database_corruption(
+    state: State<'_, Corruption>,
+) -> Result<Option<DatabaseCorruption>, String> {
+    Ok(state.0.lock().unwrap().clone())
+}
+
+/// Tauri command: restore the backup offered in `database-corrupt` and start
+/// the backend on it. The damaged database is kept as a `before-restore`
+/// backup, as with `restore_backup`.
+#[tauri::command]
+pub async fn restore_healthy_snapshot(
+    app: AppHandle,
+    state: State<'_, Corruption>,
+    tracker: State<'_, RestartTracker>,
+) -> Result<RestoreReport, String> {
+    let id = state
+        .0
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|corruption| corruption.snapshot.as_ref())
+        .map(|snapshot| snapshot.id.clone())
+        .ok_or("No healthy backup to restore")?;
+    backups::restore_backup(app, tracker, id, None).await
+}
@@ -0,0 +1,352 @@
+use std::io::{Read, Write};
+use std::num::NonZeroU32;
+use std::path::Path;
+
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+
+/// First bytes of a file encrypted by `encrypt_file`; the last is the format
+/// version.
+const MAGIC: &[u8; 8] = b"TTENC\0\0\x01";
+
+/// Plaintext bytes sealed per record, so large databases are never held in
+/// memory whole.
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// OWASP's recommendation for PBKDF2-HMAC-SHA256.
+const ITERATIONS: u32 = 600_000;
+
+const SALT_LEN: usize = 16;
+
+/// Sealed into `KeyParams::check`, so a wrong passphrase is told apart from
+/// a damaged file before anything is decrypted.
+const CHECK_TEXT: &[u8] = b"teletraan";
+
+/// How a key was derived from a passphrase. Stored next to what it encrypts;
+/// none of it is secret.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeyParams {
+    pub algorithm: String,
+    pub kdf: String,
+    pub iterations: u32,
+    /// Hex.
+    pub salt: String,
+    /// Hex nonce and sealed `CHECK_TEXT`.
+    pub check: String,
+}
+
+/// An AES-256-GCM key derived from a passphrase.
+pub struct Key(LessSafeKey);
+
+fn random(bytes: &mut [u8]) -> Result<(), String> {
+    SystemRandom::new()
+        .fill(bytes)
+        .map_err(|_| "Failed to generate random bytes".to_string())
+}
+
+fn derive(passphrase: &str, salt: &[u8], iterations: u32) -> Result<Key, String> {
+    let iterations = NonZeroU32::new(iterations).ok_or("Invalid key derivation iterations")?;
+    let mut bytes = [0u8; 32];
+    ring::pbkdf2::derive(
+        ring::pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut bytes,
+    );
+    let key = UnboundKey::new(&AES_256_GCM, &bytes).map_err(|_| "Invalid key")?;
+    Ok(Key(LessSafeKey::new(key)))
+}
+
+impl Key {
+    /// A key from `passphrase` with a new random salt, and the parameters to
+    /// derive it again.
+    pub fn new(passphrase: &str) -> Result<(Key, KeyParams), String> {
+        Self::with_iterations(passphrase, ITERATIONS)
+    }
+
+    fn with_iterations(passphrase: &str, iterations: u32) -> Result<(Key, KeyParams), String> {
+        if passphrase.is_empty() {
+            return Err("The passphrase is empty".to_string());
+        }
+        let mut salt = [0u8; SALT_LEN];
+        random(&mut salt)?;
+        let key = derive(passphrase, &salt, iterations)?;
+        let mut nonce = [0u8; NONCE_LEN];
+        random(&mut nonce)?;
+        let mut check = CHECK_TEXT.to_vec();
+        key.0
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::empty(),
+                &mut check,
+            )
+            .map_err(|_| "Failed to encrypt")?;
+        let params = KeyParams {
+            algorithm: "AES-256-GCM".to_string(),
+            kdf: "PBKDF2-HMAC-SHA256".to_string(),
+            iterations,
+            salt: hex::encode(salt),
+            check: hex::encode([&nonce[..], &check].concat()),
+        };
+        Ok((key, params))
+    }
+
+    /// The key `params` describe, if `passphrase` is the one it came from.
+    pub fn unlock(passphrase: &str, params: &KeyParams) -> Result<Key, String> {
+        if params.algorithm != "AES-256-GCM" || params.kdf != "PBKDF2-HMAC-SHA256" {
+            return Err(format!(
+                "Unsupported encryption: {} with {}",
+                params.algorithm, params.kdf
+            ));
+        }
+        let salt = hex::decode(&params.salt).map_err(|e| format!("Invalid salt: {e}"))?;
+        let check = hex::decode(&params.check).map_err(|e| format!("Invalid check: {e}"))?;
+        if check.len() < NONCE_LEN {
+            return Err("Invalid check".to_string());
+        }
+        let key = derive(passphrase, &salt, params.iterations)?;
+        let (nonce, sealed) = check.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| "Invalid check")?;
+        let mut sealed = sealed.to_vec();
+        match key.0.open_in_place(nonce, Aad::empty(), &mut sealed) {
+            Ok(text) if text == CHECK_TEXT => Ok(key),
+            _ => Err("Wrong passphrase".to_string()),
+        }
+    }
+}
+
+/// Nonce of record `index`: the file's random prefix, then the index.
+fn nonce(prefix: &[u8; 4], index: u64) -> Nonce {
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce[..4].copy_from_slice(prefix);
+    nonce[4..].copy_from_slice(&index.to_be_bytes());
+    Nonce::assume_unique_for_key(nonce)
+}
+
+/// Binds each record to its place, so records cannot be reordered, and
+/// marks the last, so a cut-off file is noticed.
+fn aad(index: u64, last: bool) -> [u8; 9] {
+    let mut aad = [0u8; 9];
+    aad[..8].copy_from_slice(&index.to_be_bytes());
+    aad[8] = u8::from(last);
+    aad
+}
+
+/// Fill `buffer` from `reader` as far as it goes; returns the bytes read.
+fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// Replace `path` with what `transform` writes, through a temporary file
+/// next to it.
+fn rewrite(
+    path: &Path,
+    transform: impl FnOnce(&mut std::fs::File, &mut std::fs::File) -> Result<(), String>,
+) -> Result<(), String> {
+    let temp = crate::backups::with_suffix(path, ".crypt");
+    let result = (|| {
+        // Both closed before the rename, which Windows refuses on open files.
+        {
+            let mut reader = std::fs::File::open(path)
+                .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+            let mut writer = std::fs::File::create(&temp)
+                .map_err(|e| format!("Failed to write {}: {e}", temp.display()))?;
+            transform(&mut reader, &mut writer)?;
+            writer
+                .sync_all()
+                .map_err(|e| format!("Failed to write {}: {e}", temp.display()))?;
+        }
+        std::fs::rename(&temp, path)
+            .map_err(|e| format!("Failed to replace {}: {e}", path.display()))
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Encrypt the file at `path` in place, in records of `CHUNK_SIZE` bytes.
+pub fn encrypt_file(key: &Key, path: &Path) -> Result<(), String> {
+    rewrite(path, |reader, writer| {
+        let read_error = |e: std::io::Error| format!("Failed to read {}: {e}", path.display());
+        let write_error = |e: std::io::Error| format!("Failed to write {}: {e}", path.display());
+        let mut prefix = [0u8; 4];
+        random(&mut prefix)?;
+        writer.write_all(MAGIC).map_err(write_error)?;
+        writer.write_all(&prefix).map_err(write_error)?;
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        for index in 0u64.. {
+            let n = read_full(reader, &mut buffer).map_err(read_error)?;
+            // A short read is the end; a file of whole chunks ends with an
+            // empty record.
+            let last = n < CHUNK_SIZE;
+            let mut record = buffer[..n].to_vec();
+            key.0
+                .seal_in_place_append_tag(
+                    nonce(&prefix, index),
+                    Aad::from(aad(index, last)),
+                    &mut record,
+                )
+                .map_err(|_| format!("Failed to encrypt {}", path.display()))?;
+            writer
+                .write_all(&(record.len() as u32).to_be_bytes())
+                .and_then(|()| writer.write_all(&record))
+                .map_err(write_error)?;
+            if last {
+                break;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Decrypt in place a file written by `encrypt_file`. Fails on the wrong key
+/// and on a file that was altered or cut off, leaving it as it was.
+pub fn decrypt_file(key: &Key, path: &Path) -> Result<(), String> {
+    rewrite(path, |reader, writer| {
+        let damaged = || {
+            format!(
+                "{} is damaged or was not encrypted with this key",
+                path.display()
+            )
+        };
+        let read_error = |e: std::io::Error| format!("Failed to read {}: {e}", path.display());
+        let write_error = |e: std::io::Error| format!("Failed to write {}: {e}", path.display());
+        let mut header = [0u8; 12];
+        if read_full(reader, &mut header).map_err(read_error)? < header.len()
+            || &header[..8] != MAGIC
+        {
+            return Err(format!("{} is not an encrypted file", path.display()));
+        }
+        let prefix: [u8; 4] = header[8..].try_into().unwrap();
+        for index in 0u64.. {
+            let mut len = [0u8; 4];
+            if read_full(reader, &mut len).map_err(read_error)? < len.len() {
+                return Err(damaged());
+            }
+            let len = u32::from_be_bytes(len) as usize;
+            if len > CHUNK_SIZE + AES_256_GCM.tag_len() {
+                return Err(damaged());
+            }
+            let mut record = vec![0u8; len];
+            if read_full(reader, &mut record).map_err(read_error)? < len {
+                return Err(damaged());
+            }
+            // Only the last record may be short.
+            let last = len < CHUNK_SIZE + AES_256_GCM.tag_len();
+            let text = key
+                .0
+                .open_in_place(
+                    nonce(&prefix, index),
+                    Aad::from(aad(index, last)),
+                    &mut record,
+                )
+                .map_err(|_| damaged())?;
+            writer.write_all(text).map_err(write_error)?;
+            if last {
+                break;
+            }
+        }
+        if read_full(reader, &mut [0u8; 1]).map_err(read_error)? != 0 {
+            return Err(damaged());
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Few iterations, so the tests do not spend seconds deriving keys.
+    const TEST_ITERATIONS: u32 = 1000;
+
+    const RECORD_LEN: usize = 4 + CHUNK_SIZE + 16;
+
+    fn temp_file(name: &str, contents: &[u8]) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("teletraan-crypto-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn key() -> Key {
+        Key::with_iterations("correct horse", TEST_ITERATIONS)
+            .unwrap()
+            .0
+    }
+
+    fn round_trip(name: &str, len: usize) {
+        let data: Vec<u8> = (0..len).map(|i| (i % 253) as u8).collect();
+        let path = temp_file(name, &data);
+        let key = key();
+        encrypt_file(&key, &path).unwrap();
+        let encrypted = std::fs::read(&path).unwrap();
+        assert_eq!(&encrypted[..8], MAGIC);
+        assert_ne!(encrypted[12..], data[..]);
+        decrypt_file(&key, &path).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn round_trips_at_record_boundaries() {
+        round_trip("empty", 0);
+        round_trip("one-chunk", CHUNK_SIZE);
+        round_trip("chunk-and-a-byte", CHUNK_SIZE + 1);
+    }
+
+    #[test]
+    fn unlock_tells_a_wrong_passphrase() {
+        let (_, params) = Key::with_iterations("correct horse", TEST_ITERATIONS).unwrap();
+        assert!(Key::unlock("correct horse", &params).is_ok());
+        assert_eq!(
+            Key::unlock("battery staple", &params).err().unwrap(),
+            "Wrong passphrase"
+        );
+    }
+
+    #[test]
+    fn rejects_a_file_missing_its_last_record() {
+        let path = temp_file("truncated", &vec![7u8; CHUNK_SIZE + 1]);
+        let key = key();
+        encrypt_file(&key, &path).unwrap();
+        let encrypted = std::fs::read(&path).unwrap();
+        // The header and the first, full record, without the short last one.
+        let cut = &encrypted[..12 + RECORD_LEN];
+        std::fs::write(&path, cut).unwrap();
+        let error = decrypt_file(&key, &path).unwrap_err();
+        assert!(error.contains("damaged"), "{error}");
+        // Left as it was.
+        assert_eq!(std::fs::read(&path).unwrap(), cut);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn rejects_reordered_records() {
+        let data: Vec<u8> = (0..2 * CHUNK_SIZE + 1)
+            .map(|i| (i / CHUNK_SIZE) as u8)
+            .collect();
+        let path = temp_file("reordered", &data);
+        let key = key();
+        encrypt_file(&key, &path).unwrap();
+        let encrypted = std::fs::read(&path).unwrap();
+        let (header, records) = encrypted.split_at(12);
+        let (first, rest) = records.split_at(RECORD_LEN);
+        let (second, last) = rest.split_at(RECORD_LEN);
+        std::fs::write(&path, [header, second, first, last].concat()).unwrap();
+        let error = decrypt_file(&key, &path).unwrap_err();
+        assert!(error.contains("damaged"), "{error}");
+        let _ = std::fs::remove_file(&path);
+    }
+}
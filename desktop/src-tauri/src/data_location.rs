@@ -0,0 +1,337 @@
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::backend;
+use crate::backups;
+use crate::config;
+use crate::config_watch;
+use crate::logging::{self, LogConfig};
+use crate::shell_log;
+use crate::storage;
+use crate::store;
+
+/// In Tauri's `app_data_dir()`, which never moves: where the data went.
+const LOCATION_FILE: &str = "data_location.json";
+
+/// The folder the data was moved to, once read; `Some(None)` when it was
+/// never moved.
+static MOVED: RwLock<Option<Option<PathBuf>>> = RwLock::new(None);
+
+/// `data_location.json`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct DataLocation {
+    path: PathBuf,
+    moved_from: PathBuf,
+    moved_at: String,
+}
+
+/// Result of `move_data_dir` and payload of `data-dir-moved`.
+#[derive(Clone, Debug, Serialize)]
+pub struct DataMove {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub files: usize,
+    pub size_bytes: u64,
+    /// Items of the old location that could not be removed after the move.
+    pub left_behind: Vec<PathBuf>,
+}
+
+/// The app data root: `default_root`, or the folder `move_data_dir` moved
+/// the data to. Fails when that folder is gone, e.g. on an unplugged drive,
+/// rather than starting over with an empty install.
+pub fn root(default_root: &Path) -> Result<PathBuf, String> {
+    if let Some(moved) = &*MOVED.read().unwrap() {
+        return Ok(moved.clone().unwrap_or_else(|| default_root.to_path_buf()));
+    }
+    let location_file = default_root.join(LOCATION_FILE);
+    let moved = store::read_json::<DataLocation>(&location_file)?.map(|location| location.path);
+    if let Some(path) = &moved {
+        if !path.is_dir() {
+            return Err(format!(
+                "The data directory {} is not available. Connect the drive it is on, or delete {} to start over in {}",
+                path.display(),
+                location_file.display(),
+                default_root.display()
+            ));
+        }
+    }
+    *MOVED.write().unwrap() = Some(moved.clone());
+    Ok(moved.unwrap_or_else(|| default_root.to_path_buf()))
+}
+
+/// `path` with symlinks and `..` resolved, for a folder that may not exist
+/// yet.
+fn absolute(path: &Path) -> Result<PathBuf, String> {
+    if let Ok(path) = path.canonicalize() {
+        return Ok(path);
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => Ok(absolute(parent)?.join(name)),
+        _ => Err(format!("Invalid path {}", path.display())),
+    }
+}
+
+/// Free space on the disk holding `path`, if it can be told.
+fn available_space(path: &Path) -> Option<u64> {
+    let existing = path
+        .ancestors()
+        .find(|dir| dir.exists())?
+        .canonicalize()
+        .ok()?;
+    sysinfo::Disks::new_with_refreshed_list()
+        .list()
+        .iter()
+        .filter(|disk| existing.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+/// Check that the data can be moved from `from` to `to`. `to` must be empty
+/// apart from the location file when it is the default folder.
+fn check_target(from: &Path, to: &Path) -> Result<(), String> {
+    if to == from {
+        return Err(format!("The data is already in {}", to.display()));
+    }
+    if to.starts_with(from) || from.starts_with(to) {
+        return Err(format!(
+            "{} and {} are inside one another",
+            from.display(),
+            to.display()
+        ));
+    }
+    if to.exists() {
+        let occupied = std::fs::read_dir(to)
+            .map_err(|e| format!("Failed to read {}: {e}", to.display()))?
+            .filter_map(Result::ok)
+            .any(|entry| entry.file_name() != LOCATION_FILE);
+        if occupied {
+            return Err(format!("{} is not empty", to.display()));
+        }
+    }
+    let needed = storage::size(from);
+    if let Some(available) = available_space(to) {
+        if needed > available {
+            return Err(format!(
+                "{} needs {needed} bytes but only {available} are free there",
+                to.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn sha256(path: &Path) -> Result<String, String> {
+    let mut file =
+        std::fs::File::open(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Copy the file or folder `from` to `to`, checking every file copied
+/// against a hash of the bytes read. Adds to the file count and size.
+fn copy_verified(from: &Path, to: &Path, files: &mut usize, bytes: &mut u64) -> Result<(), String> {
+    if from.is_dir() {
+        std::fs::create_dir_all(to)
+            .map_err(|e| format!("Failed to create {}: {e}", to.display()))?;
+        for entry in std::fs::read_dir(from)
+            .map_err(|e| format!("Failed to read {}: {e}", from.display()))?
+        {
+            let entry = entry.map_err(|e| format!("Failed to read {}: {e}", from.display()))?;
+            copy_verified(&entry.path(), &to.join(entry.file_name()), files, bytes)?;
+        }
+        return Ok(());
+    }
+    let read_error = |e: std::io::Error| format!("Failed to read {}: {e}", from.display());
+    let write_error = |e: std::io::Error| format!("Failed to write {}: {e}", to.display());
+    let mut reader = std::fs::File::open(from).map_err(read_error)?;
+    let mut writer = std::fs::File::create(to).map_err(write_error)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 1 << 16];
+    loop {
+        let n = reader.read(&mut buffer).map_err(read_error)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+        writer.write_all(&buffer[..n]).map_err(write_error)?;
+        *bytes += n as u64;
+    }
+    writer.sync_all().map_err(write_error)?;
+    // Compared with what was read, so a file still being appended to (the
+    // shell log) verifies too.
+    if sha256(to)? != hex::encode(hasher.finalize()) {
+        return Err(format!(
+            "The copy of {} does not match the original",
+            from.display()
+        ));
+    }
+    *files += 1;
+    Ok(())
+}
+
+fn remove_item(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+/// The top-level items of the data in `root`.
+fn items(root: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut items = Vec::new();
+    for entry in
+        std::fs::read_dir(root).map_err(|e| format!("Failed to read {}: {e}", root.display()))?
+    {
+        let entry = entry.map_err(|e| format!("Failed to read {}: {e}", root.display()))?;
+        if entry.file_name() != LOCATION_FILE {
+            items.push(entry.path());
+        }
+    }
+    Ok(items)
+}
+
+/// Copy everything in `from` to `to`; on failure, remove what was copied.
+fn copy_all(from: &Path, to: &Path) -> Result<(Vec<PathBuf>, usize, u64), String> {
+    let items = items(from)?;
+    std::fs::create_dir_all(to).map_err(|e| format!("Failed to create {}: {e}", to.display()))?;
+    let (mut files, mut bytes) = (0, 0);
+    for item in &items {
+        let target = to.join(item.file_name().unwrap_or_default());
+        if let Err(e) = copy_verified(item, &target, &mut files, &mut bytes) {
+            for item in &items {
+                let _ = remove_item(&to.join(item.file_name().unwrap_or_default()));
+            }
+            return Err(e);
+        }
+    }
+    Ok((items, files, bytes))
+}
+
+/// Point `app_data_root` at `to`, or back at `default_root`.
+fn point_to(default_root: &Path, from: &Path, to: &Path) -> Result<(), String> {
+    let location_file = default_root.join(LOCATION_FILE);
+    if to == default_root {
+        std::fs::remove_file(&location_file)
+            .or_else(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => Ok(()),
+                _ => Err(e),
+            })
+            .map_err(|e| format!("Failed to remove {}: {e}", location_file.display()))?;
+    } else {
+        store::write_json(
+            &location_file,
+            &DataLocation {
+                path: to.to_path_buf(),
+                moved_from: from.to_path_buf(),
+                moved_at: chrono::Utc::now().to_rfc3339(),
+            },
+        )?;
+    }
+    *MOVED.write().unwrap() = Some((to != default_root).then(|| to.to_path_buf()));
+    Ok(())
+}
+
+/// Tauri command: move all of the app's data (every profile's database,
+/// logs, backups and settings) to `new_path`, e.g. on an external drive.
+///
+/// The backend is stopped, the data is copied and every file is checked
+/// against the original, and only then is the new location recorded in
+/// `data_location.json` and the old copy deleted. The backend starts again
+/// from the new location, and `data-dir-moved` reports the move. If the copy
+/// fails, the data stays where it was. Passing the default folder moves the
+/// data back there.
+#[tauri::command]
+pub async fn move_data_dir(app: AppHandle, new_path: String) -> Result<DataMove, String> {
+    if config::data_dir().is_some() {
+        return Err(
+            "The data directory is set with --data-dir, TELETRAAN_DATA_DIR or portable mode; change that instead"
+                .to_string(),
+        );
+    }
+    let new_path = PathBuf::from(new_path.trim());
+    if !new_path.is_absolute() {
+        return Err(format!("{} is not an absolute path", new_path.display()));
+    }
+    let _busy = backups::Busy::acquire()?;
+    backups::check_managed(&app)?;
+    let default_root = absolute(
+        &app.path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to resolve app data directory: {e}"))?,
+    )?;
+    let from = absolute(&crate::app_data_root(&app)?)?;
+    let to = absolute(&new_path)?;
+    check_target(&from, &to)?;
+
+    log::warn!(
+        "Moving the data directory from {} to {}",
+        from.display(),
+        to.display()
+    );
+    backups::stop_backend(&app).await?;
+    let (source, target) = (from.clone(), to.clone());
+    let copied = tauri::async_runtime::spawn_blocking(move || copy_all(&source, &target))
+        .await
+        .map_err(|e| format!("Failed to copy the data directory: {e}"))
+        .and_then(|result| result)
+        .and_then(|copied| match point_to(&default_root, &from, &to) {
+            Ok(()) => Ok(copied),
+            Err(e) => {
+                for item in &copied.0 {
+                    let _ = remove_item(&to.join(item.file_name().unwrap_or_default()));
+                }
+                Err(e)
+            }
+        });
+    let (items, files, size_bytes) = match copied {
+        Ok(copied) => copied,
+        Err(e) => {
+            log::error!("Failed to move the data directory: {e}");
+            backend::start_backend(&app).await?;
+            return Err(e);
+        }
+    };
+
+    let data_dir = crate::resolve_data_dir(&app)?;
+    // The session log was in the old `logs/`, which is removed below.
+    logging::reset_session_log();
+    if let Err(e) = shell_log::attach(&data_dir, LogConfig::load(&app)) {
+        log::warn!("Shell log file unavailable: {e}");
+    }
+    config_watch::rescan();
+    let left_behind = tauri::async_runtime::spawn_blocking(move || {
+        items
+            .into_iter()
+            .filter(|item| {
+                remove_item(item)
+                    .inspect_err(|e| log::warn!("Failed to remove {}: {e}", item.display()))
+                    .is_err()
+            })
+            .collect()
+    })
+    .await
+    .unwrap_or_default();
+
+    let moved = DataMove {
+        from,
+        to,
+        files,
+        size_bytes,
+        left_behind,
+    };
+    log::info!(
+        "Moved {files} files ({size_bytes} bytes) to {}",
+        moved.to.display()
+    );
+    let _ = app.emit("data-dir-moved", &moved);
+    backend::start_backend(&app).await?;
+    Ok(moved)
+}
@@ -0,0 +1,325 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_opener::OpenerExt;
+
+use crate::backend;
+use crate::backups;
+use crate::clipboard;
+use crate::corruption;
+use crate::paths;
+use crate::retention;
+use crate::transport;
+
+/// VACUUM rewrites the whole file, which takes a while on a large database.
+const MAINTENANCE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Merging compares every insight and outcome, which takes a while on years
+/// of history.
+const IMPORT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Name of the read-only copy handed to external DB browsers, in `browse/`.
+const BROWSE_SNAPSHOT: &str = "market-analyzer-snapshot.db";
+
+/// Copy the database (with its write-ahead log, so recent writes are included)
+/// to `dest` and mark the copy read-only.
+fn snapshot_database(db_path: &Path, dest: &Path) -> Result<(), String> {
+    if !db_path.exists() {
+        return Err(format!("Database not found at {}", db_path.display()));
+    }
+    if let Some(dir) = dest.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    for suffix in ["", "-wal"] {
+        let from = with_suffix(db_path, suffix);
+        let to = with_suffix(dest, suffix);
+        remove_readonly(&to)?;
+        if !from.exists() {
+            continue;
+        }
+        std::fs::copy(&from, &to).map_err(|e| format!("Failed to copy {}: {e}", from.display()))?;
+        set_readonly(&to, true)?;
+    }
+    Ok(())
+}
+
+/// `path` with `suffix` appended to its file name (`db` + `-wal`).
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn set_readonly(path: &Path, readonly: bool) -> Result<(), String> {
+    let mut permissions = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?
+        .permissions();
+    permissions.set_readonly(readonly);
+    std::fs::set_permissions(path, permissions)
+        .map_err(|e| format!("Failed to change permissions of {}: {e}", path.display()))
+}
+
+/// Delete an earlier snapshot; Windows refuses to delete read-only files.
+fn remove_readonly(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Ok(());
+    }
+    set_readonly(path, false)?;
+    std::fs::remove_file(path).map_err(|e| format!("Failed to remove {}: {e}", path.display()))
+}
+
+/// Tauri command: make a read-only snapshot of the database for poking at in
+/// an external SQLite browser, copy its path to the clipboard, and with `open`
+/// hand it to the system's default handler for `.db` files. Returns the
+/// snapshot's path.
+///
+/// The live database is never exposed, so a browser that writes (or locks)
+/// its file cannot corrupt it or stall the backend.
+#[tauri::command]
+pub async fn open_database_snapshot(app: AppHandle, open: Option<bool>) -> Result<String, String> {
+    let data_dir = crate::resolve_data_dir(&app)?;
+    let snapshot = data_dir.join("browse").join(BROWSE_SNAPSHOT);
+    let dest = snapshot.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        snapshot_database(&paths::database_path(&data_dir), &dest)
+    })
+    .await
+    .map_err(|e| format!("Failed to snapshot database: {e}"))??;
+
+    let path = snapshot.display().to_string();
+    log::info!("Database snapshot for browsing: {path}");
+    if let Err(e) = clipboard::copy_text(&path) {
+        log::warn!("{e}");
+    }
+    if open.unwrap_or(false) {
+        app.opener()
+            .open_path(path.clone(), None::<&str>)
+            .map_err(|e| format!("Failed to open {path}: {e}"))?;
+    }
+    Ok(path)
+}
+
+/// Result of `run_db_maintenance`, as reported by the backend.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    pub integrity_ok: bool,
+    /// Problems found by `PRAGMA integrity_check`; empty when it passed.
+    pub integrity_errors: Vec<String>,
+    pub vacuumed: bool,
+    pub analyzed: bool,
+    pub size_before_bytes: Option<u64>,
+    pub size_after_bytes: Option<u64>,
+    pub reclaimed_bytes: Option<u64>,
+    pub duration_ms: u64,
+}
+
+/// Tauri command: run `PRAGMA integrity_check`, then `VACUUM` and `ANALYZE`
+/// (each on by default) on the database, and report the space reclaimed.
+///
+/// The backend runs it on its own connection, so the database is never opened
+/// by two processes at once. It skips VACUUM and ANALYZE when the check finds
+/// corruption, and refuses VACUUM while an analysis is running.
+#[tauri::command]
+pub async fn run_db_maintenance(
+    app: AppHandle,
+    vacuum: Option<bool>,
+    analyze: Option<bool>,
+) -> Result<MaintenanceReport, String> {
+    let privileged = transport::privileged_endpoint(&app)?;
+
+    #[derive(Serialize)]
+    struct MaintenanceRequest {
+        vacuum: bool,
+        analyze: bool,
+    }
+    let request = MaintenanceRequest {
+        vacuum: vacuum.unwrap_or(true),
+        analyze: analyze.unwrap_or(true),
+    };
+    log::info!(
+        "Running database maintenance (vacuum: {}, analyze: {})",
+        request.vacuum,
+        request.analyze
+    );
+    let resp = transport::Client::new(MAINTENANCE_TIMEOUT)?
+        .post_json(
+            &privileged.endpoint,
+            "/api/v1/maintenance/database",
+            &privileged.headers(),
+            &request,
+        )
+        .await
+        .map_err(|e| format!("Failed to run database maintenance: {e}"))?;
+    if !resp.status().is_success() {
+        return Err(format!(
+            "Failed to run database maintenance: HTTP {}: {}",
+            resp.status(),
+            String::from_utf8_lossy(resp.body())
+        ));
+    }
+    let report: MaintenanceReport = transport::json(&resp)?;
+    if report.integrity_ok {
+        log::info!(
+            "Database maintenance finished, reclaimed {} bytes",
+            report.reclaimed_bytes.unwrap_or(0)
+        );
+    } else {
+        log::error!(
+            "Database integrity check failed: {:?}",
+            report.integrity_errors
+        );
+    }
+    Ok(report)
+}
+
+/// How `import_database` brings in the other database.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Use the other database instead of this one.
+    Replace,
+    /// Add the insights and outcomes this one lacks.
+    Merge,
+}
+
+/// What the backend added to the database in a merge.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MergeReport {
+    pub insights_imported: u64,
+    /// Already present, by ID or title and creation time.
+    pub insights_skipped: u64,
+    pub outcomes_imported: u64,
+    /// Already present, or for an insight that was not imported.
+    pub outcomes_skipped: u64,
+}
+
+/// Result of `import_database`.
+#[derive(Clone, Debug, Serialize)]
+pub struct DatabaseImport {
+    pub mode: ImportMode,
+    pub source: PathBuf,
+    /// Id of the backup holding the database as it was before the import,
+    /// for `restore_backup`; `None` when there was none yet.
+    pub backup: Option<String>,
+    /// Set for a merge.
+    pub merged: Option<MergeReport>,
+}
+
+/// Check that `source` is a database the import can use.
+fn check_source(source: &Path, current: &Path) -> Result<(), String> {
+    if !source.is_absolute() {
+        return Err(format!("{} is not an absolute path", source.display()));
+    }
+    let len = std::fs::metadata(source)
+        .map_err(|e| format!("Failed to read {}: {e}", source.display()))?
+        .len();
+    if !source.is_file() || len == 0 {
+        return Err(format!("{} is not a database file", source.display()));
+    }
+    if source.canonicalize().ok() == current.canonicalize().ok() {
+        return Err(format!("{} is the database in use", source.display()));
+    }
+    match corruption::check_file(source) {
+        Some(problem) => Err(format!(
+            "{} cannot be imported: {problem}",
+            source.display()
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Ask the backend to merge the insights and outcomes of `source`.
+async fn merge(app: &AppHandle, source: &Path) -> Result<MergeReport, String> {
+    let privileged = transport::privileged_endpoint(app)?;
+
+    #[derive(Serialize)]
+    struct ImportRequest<'a> {
+        path: &'a Path,
+    }
+    let resp = transport::Client::new(IMPORT_TIMEOUT)?
+        .post_json(
+            &privileged.endpoint,
+            "/api/v1/maintenance/import",
+            &privileged.headers(),
+            &ImportRequest { path: source },
+        )
+        .await
+        .map_err(|e| format!("Failed to merge the database: {e}"))?;
+    if !resp.status().is_success() {
+        return Err(format!(
+            "Failed to merge the database: HTTP {}: {}",
+            resp.status(),
+            String::from_utf8_lossy(resp.body())
+        ));
+    }
+    transport::json(&resp)
+}
+
+/// Tauri command: bring in the database of another install, e.g. from the
+/// old laptop, at `path`.
+///
+/// With `replace`, that database is used instead of this one. With `merge`,
+/// the backend adds the insights and outcomes this one lacks, skipping those
+/// already present by ID and creation time, so the learning history of both
+/// machines is kept; prices, conversations and settings stay as they are.
+/// Either way the backend is stopped and the current database is kept as a
+/// backup first, which `restore_backup` can put back.
+#[tauri::command]
+pub async fn import_database(
+    app: AppHandle,
+    path: String,
+    mode: ImportMode,
+) -> Result<DatabaseImport, String> {
+    let source = PathBuf::from(path.trim());
+    let data_dir = crate::resolve_data_dir(&app)?;
+    check_source(&source, &paths::database_path(&data_dir))?;
+    let _busy = backups::Busy::acquire()?;
+    backups::check_managed(&app)?;
+
+    log::warn!("Importing {} ({mode:?})", source.display());
+    backups::stop_backend(&app).await?;
+    let (handle, from) = (app.clone(), source.clone());
+    let label = format!(
+        "Before importing {}",
+        source.file_name().unwrap_or_default().to_string_lossy()
+    );
+    let result = tauri::async_runtime::spawn_blocking(move || match mode {
+        ImportMode::Replace => {
+            backups::replace_database(&handle, &data_dir, &from, &label).map(|(backup, _)| backup)
+        }
+        ImportMode::Merge => backups::backup_database(&handle, &data_dir, &label),
+    })
+    .await
+    .map_err(|e| format!("Failed to import the database: {e}"))
+    .and_then(|result| result);
+    let started = backend::start_backend(&app).await;
+    let backup = result?;
+    started?;
+
+    let merged = match mode {
+        ImportMode::Replace => None,
+        ImportMode::Merge => {
+            if !retention::wait_until_healthy(&app).await {
+                return Err("The backend did not become healthy; nothing was merged".to_string());
+            }
+            Some(merge(&app, &source).await?)
+        }
+    };
+    let import = DatabaseImport {
+        mode,
+        source,
+        backup: backup.map(|backup| {
+            backup
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        }),
+        merged,
+    };
+    log::info!("Imported {}: {:?}", import.source.display(), import.merged);
+    Ok(import)
+}
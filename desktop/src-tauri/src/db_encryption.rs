@@ -0,0 +1,223 @@
+use std::io::Read;
+use std::path::Path;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::audit::{self, AuditEvent};
+use crate::backend;
+use crate::backups;
+use crate::corruption;
+use crate::paths;
+use crate::retention;
+use crate::secrets;
+use crate::store;
+
+/// In the data dir: whether the database of this profile is to be encrypted.
+const STATE_FILE: &str = "database_encryption.json";
+
+/// SQLCipher's page size, which an encrypted database is made of whole pages
+/// of.
+pub const SQLCIPHER_PAGE_SIZE: u64 = 4096;
+
+/// `database_encryption.json`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct EncryptionState {
+    enabled: bool,
+    /// Set once the database has been encrypted, so its backups may be too.
+    ever_enabled: bool,
+    changed_at: Option<String>,
+}
+
+/// Result of `get_database_encryption` and `set_database_encryption`.
+#[derive(Clone, Debug, Serialize)]
+pub struct DatabaseEncryption {
+    /// Whether the database is to be encrypted.
+    pub enabled: bool,
+    /// Whether the file on disk is encrypted now.
+    pub encrypted: bool,
+    /// Id of the backup taken before the migration, for `restore_backup`.
+    pub backup: Option<String>,
+}
+
+fn load(data_dir: &Path) -> EncryptionState {
+    store::read_json(&data_dir.join(STATE_FILE))
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to read {STATE_FILE}: {e}");
+            None
+        })
+        .unwrap_or_default()
+}
+
+/// Whether files of the database in `data_dir`, or its backups, may be
+/// encrypted, so a header that is not SQLite's is not damage.
+pub fn may_be_encrypted(data_dir: &Path) -> bool {
+    let state = load(data_dir);
+    state.enabled || state.ever_enabled
+}
+
+/// Whether the file at `database` is plain SQLite, or empty or missing.
+pub fn is_plaintext(database: &Path) -> bool {
+    let mut header = [0u8; 16];
+    match std::fs::File::open(database) {
+        Ok(mut file) => match file.read(&mut header) {
+            Ok(0) => true,
+            Ok(_) => &header == corruption::SQLITE_MAGIC,
+            Err(_) => true,
+        },
+        Err(_) => true,
+    }
+}
+
+/// Pass the database key to the backend about to be spawned from `data_dir`,
+/// with `TELETRAAN_DB_DECRYPT` when encryption was turned off, so the backend
+/// encrypts or decrypts the file first if it is not as it should be. Nothing
+/// is read from the keychain for a plain database that is to stay so.
+pub fn apply(app: &AppHandle, data_dir: &Path, command: &mut Command) -> Result<(), String> {
+    let state = load(data_dir);
+    let plaintext = is_plaintext(&paths::database_path(data_dir));
+    if !state.enabled && plaintext {
+        return Ok(());
+    }
+    let key = secrets::database_key(app, state.enabled, "backend")?.ok_or(
+        "The database is encrypted but its key is not in the keychain; restore a backup from before it was encrypted",
+    )?;
+    command.env("TELETRAAN_DB_KEY", key);
+    if !state.enabled {
+        log::info!("Database encryption is off; the backend decrypts the database");
+        command.env("TELETRAAN_DB_DECRYPT", "1");
+    } else if plaintext {
+        log::info!("Database encryption is on; the backend encrypts the database");
+    }
+    Ok(())
+}
+
+fn status(data_dir: &Path, backup: Option<String>) -> DatabaseEncryption {
+    let database = paths::database_path(data_dir);
+    DatabaseEncryption {
+        enabled: load(data_dir).enabled,
+        encrypted: database.exists() && !is_plaintext(&database),
+        backup,
+    }
+}
+
+/// Tauri command: whether the database is to be and is encrypted.
+#[tauri::command]
+pub async fn get_database_encryption(app: AppHandle) -> Result<DatabaseEncryption, String> {
+    Ok(status(&crate::resolve_data_dir(&app)?, None))
+}
+
+/// Tauri command: turn SQLCipher encryption of the database on or off.
+///
+/// The key is generated on first use and kept in the platform keychain, and
+/// only ever passed to the backend in its environment. The backend is
+/// stopped, the database kept as a backup, and the backend started again,
+/// which migrates the file before opening it. If it does not come up, the
+/// setting is put back and the backend started as before.
+#[tauri::command]
+pub async fn set_database_encryption(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<DatabaseEncryption, String> {
+    let data_dir = crate::resolve_data_dir(&app)?;
+    let previous = load(&data_dir);
+    if previous.enabled == enabled {
+        return Ok(status(&data_dir, None));
+    }
+    let _busy = backups::Busy::acquire()?;
+    backups::check_managed(&app)?;
+    if enabled {
+        // Created now, so a keychain that cannot be written fails before
+        // anything is stopped.
+        let handle = app.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            secrets::database_key(&handle, true, "set_database_encryption")
+        })
+        .await
+        .map_err(|e| format!("Failed to access the keychain: {e}"))??;
+    }
+
+    log::warn!(
+        "Turning database encryption {}",
+        if enabled { "on" } else { "off" }
+    );
+    backups::stop_backend(&app).await?;
+    let (handle, dir) = (app.clone(), data_dir.clone());
+    let label = if enabled {
+        "Before encryption"
+    } else {
+        "Before decryption"
+    };
+    let backup = tauri::async_runtime::spawn_blocking(move || {
+        backups::backup_database(&handle, &dir, label)
+    })
+    .await
+    .map_err(|e| format!("Failed to back up the database: {e}"))
+    .and_then(|result| result);
+    let backup = match backup {
+        Ok(backup) => backup,
+        Err(e) => {
+            backend::start_backend(&app).await?;
+            return Err(e);
+        }
+    };
+    let state = EncryptionState {
+        enabled,
+        ever_enabled: previous.ever_enabled || enabled,
+        changed_at: Some(chrono::Utc::now().to_rfc3339()),
+    };
+    store::write_json(&data_dir.join(STATE_FILE), &state)?;
+
+    backend::start_backend(&app).await?;
+    if !retention::wait_until_healthy(&app).await {
+        log::error!("The backend did not start with database encryption changed; reverting");
+        store::write_json(
+            &data_dir.join(STATE_FILE),
+            &EncryptionState {
+                ever_enabled: state.ever_enabled,
+                ..previous
+            },
+        )?;
+        audit::record(
+            &app,
+            AuditEvent::DatabaseEncryption,
+            false,
+            format!(
+                "Turning encryption {} failed",
+                if enabled { "on" } else { "off" }
+            ),
+        );
+        backups::stop_backend(&app).await?;
+        backend::start_backend(&app).await?;
+        return Err(
+            "The backend could not migrate the database (see the backend log); encryption was left as it was"
+                .to_string(),
+        );
+    }
+    let backup = backup.map(|backup| {
+        backup
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    });
+    let result = status(&data_dir, backup);
+    audit::record(
+        &app,
+        AuditEvent::DatabaseEncryption,
+        true,
+        if enabled { "on" } else { "off" },
+    );
+    log::info!(
+        "Database encryption is {}; the file is {}",
+        if result.enabled { "on" } else { "off" },
+        if result.encrypted {
+            "encrypted"
+        } else {
+            "not encrypted"
+        }
+    );
+    Ok(result)
+}
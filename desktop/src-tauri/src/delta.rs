@@ -0,0 +1,361 @@
+use std::io::{Read, Write};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+
+use crate::integrity::{self, Manifest};
+
+/// `delta.json` at the top of an unpacked delta.
+pub const DELTA_FILE: &str = "delta.json";
+
+/// Patches are bsdiff's control, diff and extra blocks with gzip in place
+/// of bzip2, behind this magic.
+const PATCH_MAGIC: &[u8; 8] = b"BSDIFFGZ";
+const PATCH_HEADER_LEN: usize = 32;
+
+/// What a delta from one backend version to the next holds.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Delta {
+    pub from_version: String,
+    pub to_version: String,
+    /// Every file of the new version.
+    pub files: Vec<DeltaFile>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct DeltaFile {
+    /// Relative to the backend folder, with `/` separators.
+    pub path: String,
+    pub sha256: String,
+    #[serde(flatten)]
+    pub source: Source,
+}
+
+/// Where a file of the new version comes from.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum Source {
+    /// Unchanged from the old version's `from`, by default the same path.
+    Copy {
+        #[serde(default)]
+        from: Option<String>,
+    },
+    /// Shipped whole as `files/<path>` in the delta.
+    Add,
+    /// The old version's `from` (the same path by default) patched with
+    /// `patches/<path>.bsdiff` in the delta.
+    Patch {
+        #[serde(default)]
+        from: Option<String>,
+    },
+}
+
+pub fn read(dir: &Path) -> Result<Delta, String> {
+    let path = dir.join(DELTA_FILE);
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    serde_json::from_str(&text).map_err(|e| format!("Invalid {}: {e}", path.display()))
+}
+
+/// `relative` (with `/` separators) under `root`, refusing anything that
+/// would leave it.
+fn join(root: &Path, relative: &str) -> Result<std::path::PathBuf, String> {
+    let mut path = root.to_path_buf();
+    for part in relative.split('/') {
+        if part.is_empty() || part == "." || part == ".." || part.contains('\\') {
+            return Err(format!("Invalid path in the delta: {relative:?}"));
+        }
+        path.push(part);
+    }
+    Ok(path)
+}
+
+/// Build the new version in `dest` from the old one in `base` and the
+/// unpacked delta in `delta_dir`. Returns the manifest of `dest`, which is
+/// checked against every hash the delta lists.
+pub fn apply(
+    base: &Path,
+    delta_dir: &Path,
+    delta: &Delta,
+    dest: &Path,
+) -> Result<Manifest, String> {
+    std::fs::create_dir_all(dest)
+        .map_err(|e| format!("Failed to create {}: {e}", dest.display()))?;
+    for file in &delta.files {
+        let target = join(dest, &file.path)?;
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+        }
+        match &file.source {
+            Source::Copy { from } => {
+                let source = join(base, from.as_deref().unwrap_or(&file.path))?;
+                std::fs::copy(&source, &target)
+                    .map_err(|e| format!("Failed to copy {}: {e}", source.display()))?;
+            }
+            Source::Add => {
+                let source = join(&delta_dir.join("files"), &file.path)?;
+                std::fs::rename(&source, &target)
+                    .map_err(|e| format!("Failed to move {}: {e}", source.display()))?;
+            }
+            Source::Patch { from } => {
+                let source = join(base, from.as_deref().unwrap_or(&file.path))?;
+                let patch = join(&delta_dir.join("patches"), &format!("{}.bsdiff", file.path))?;
+                patch_file(&source, &patch, &target)?;
+            }
+        }
+    }
+
+    let manifest = integrity::hash_dir(dest)?;
+    let mut expected: Manifest = delta
+        .files
+        .iter()
+        .map(|file| (file.path.clone(), file.sha256.to_ascii_lowercase()))
+        .collect();
+    expected.sort();
+    if manifest != expected {
+        let wrong: Vec<&str> = expected
+            .iter()
+            .filter(|entry| !manifest.contains(entry))
+            .map(|(path, _)| path.as_str())
+            .collect();
+        return Err(format!(
+            "The patched backend does not match the delta: {wrong:?}"
+        ));
+    }
+    Ok(manifest)
+}
+
+/// Write `old` patched with the patch file at `patch` to `new`, with the
+/// permissions of `old`.
+fn patch_file(old: &Path, patch: &Path, new: &Path) -> Result<(), String> {
+    let old_data =
+        std::fs::read(old).map_err(|e| format!("Failed to read {}: {e}", old.display()))?;
+    let patch_data =
+        std::fs::read(patch).map_err(|e| format!("Failed to read {}: {e}", patch.display()))?;
+    let file = std::fs::File::create(new)
+        .map_err(|e| format!("Failed to create {}: {e}", new.display()))?;
+    let mut writer = std::io::BufWriter::new(file);
+    bspatch(&old_data, &patch_data, &mut writer)
+        .map_err(|e| format!("Failed to patch {}: {e}", old.display()))?;
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to write {}: {e}", new.display()))?;
+    if let Ok(metadata) = std::fs::metadata(old) {
+        let _ = std::fs::set_permissions(new, metadata.permissions());
+    }
+    Ok(())
+}
+
+/// bsdiff's sign-and-magnitude little-endian integer.
+fn offtin(bytes: &[u8]) -> i64 {
+    let mut value = [0u8; 8];
+    value.copy_from_slice(&bytes[..8]);
+    let sign = value[7] & 0x80 != 0;
+    value[7] &= 0x7f;
+    let magnitude = i64::from_le_bytes(value);
+    if sign {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+fn read_offset(reader: &mut impl Read) -> Result<i64, String> {
+    let mut bytes = [0u8; 8];
+    reader
+        .read_exact(&mut bytes)
+        .map_err(|e| format!("truncated control block: {e}"))?;
+    Ok(offtin(&bytes))
+}
+
+/// Apply a `BSDIFFGZ` patch to `old`, writing the result to `out`. Returns
+/// the length written.
+fn bspatch(old: &[u8], patch: &[u8], out: &mut impl Write) -> Result<u64, String> {
+    if patch.len() < PATCH_HEADER_LEN || &patch[..8] != PATCH_MAGIC {
+        return Err("not a BSDIFFGZ patch".to_string());
+    }
+    let ctrl_len = offtin(&patch[8..16]);
+    let diff_len = offtin(&patch[16..24]);
+    let new_size = offtin(&patch[24..32]);
+    let body = patch.len() - PATCH_HEADER_LEN;
+    if ctrl_len < 0
+        || diff_len < 0
+        || new_size < 0
+        || (ctrl_len as u64).saturating_add(diff_len as u64) > body as u64
+    {
+        return Err("corrupt patch header".to_string());
+    }
+    let (ctrl_len, diff_len) = (ctrl_len as usize, diff_len as usize);
+    let blocks = &patch[PATCH_HEADER_LEN..];
+    let mut ctrl = GzDecoder::new(&blocks[..ctrl_len]);
+    let mut diff = GzDecoder::new(&blocks[ctrl_len..ctrl_len + diff_len]);
+    let mut extra = GzDecoder::new(&blocks[ctrl_len + diff_len..]);
+
+    let new_size = new_size as u64;
+    let mut buffer = vec![0u8; 64 * 1024];
+    let (mut new_pos, mut old_pos) = (0u64, 0i64);
+    while new_pos < new_size {
+        let add = read_offset(&mut ctrl)?;
+        let copy = read_offset(&mut ctrl)?;
+        let seek = read_offset(&mut ctrl)?;
+        let end = (add >= 0 && copy >= 0)
+            .then(|| new_pos.checked_add(add as u64)?.checked_add(copy as u64))
+            .flatten();
+        if end.is_none_or(|end| end > new_size) {
+            return Err("corrupt control block".to_string());
+        }
+
+        // `add` bytes of the diff block, each added to the old byte at the
+        // same offset.
+        let mut left = add as usize;
+        while left > 0 {
+            let take = left.min(buffer.len());
+            let chunk = &mut buffer[..take];
+            diff.read_exact(chunk)
+                .map_err(|e| format!("truncated diff block: {e}"))?;
+            for (i, byte) in chunk.iter_mut().enumerate() {
+                match old_pos.checked_add(i as i64) {
+                    Some(at) if at >= 0 && (at as usize) < old.len() => {
+                        *byte = byte.wrapping_add(old[at as usize]);
+                    }
+                    _ => {}
+                }
+            }
+            out.write_all(chunk).map_err(|e| e.to_string())?;
+            old_pos = old_pos
+                .checked_add(take as i64)
+                .ok_or("corrupt control block")?;
+            left -= take;
+        }
+        new_pos += add as u64;
+
+        // `copy` bytes of the extra block as they are.
+        let mut left = copy as usize;
+        while left > 0 {
+            let take = left.min(buffer.len());
+            extra
+                .read_exact(&mut buffer[..take])
+                .map_err(|e| format!("truncated extra block: {e}"))?;
+            out.write_all(&buffer[..take]).map_err(|e| e.to_string())?;
+            left -= take;
+        }
+        new_pos += copy as u64;
+        old_pos = old_pos.checked_add(seek).ok_or("corrupt control block")?;
+    }
+    Ok(new_pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    use super::*;
+
+    fn offtout(value: i64) -> [u8; 8] {
+        let mut bytes = value.unsigned_abs().to_le_bytes();
+        if value < 0 {
+            bytes[7] |= 0x80;
+        }
+        bytes
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// A `BSDIFFGZ` patch of the given control triples and blocks.
+    fn make_patch(ctrl: &[(i64, i64, i64)], diff: &[u8], extra: &[u8], new_size: i64) -> Vec<u8> {
+        let ctrl: Vec<u8> = ctrl
+            .iter()
+            .flat_map(|&(add, copy, seek)| [offtout(add), offtout(copy), offtout(seek)])
+            .flatten()
+            .collect();
+        let (ctrl, diff, extra) = (gzip(&ctrl), gzip(diff), gzip(extra));
+        let mut patch = PATCH_MAGIC.to_vec();
+        patch.extend(offtout(ctrl.len() as i64));
+        patch.extend(offtout(diff.len() as i64));
+        patch.extend(offtout(new_size));
+        patch.extend([ctrl, diff, extra].concat());
+        patch
+    }
+
+    const OLD: &[u8] = b"hello world";
+    const NEW: &[u8] = b"jello there world!";
+
+    /// `NEW` from `OLD`: "hello " with its first byte changed, "there " from
+    /// the extra block, "world" unchanged and "!" from the extra block.
+    fn known_patch() -> Vec<u8> {
+        let mut diff = vec![0u8; 11];
+        diff[0] = b'j'.wrapping_sub(b'h');
+        make_patch(&[(6, 6, 0), (5, 1, 0)], &diff, b"there !", NEW.len() as i64)
+    }
+
+    fn patch(old: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        bspatch(old, patch, &mut out)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn applies_a_known_patch() {
+        assert_eq!(patch(OLD, &known_patch()).unwrap(), NEW);
+    }
+
+    #[test]
+    fn rejects_truncated_patches() {
+        let full = known_patch();
+        // Into the extra block's compressed data, past its 8-byte trailer.
+        assert!(patch(OLD, &full[..full.len() - 12]).is_err());
+        assert!(patch(OLD, &full[..20]).is_err());
+    }
+
+    #[test]
+    fn rejects_patches_whose_header_lies() {
+        let mut longer = known_patch();
+        longer[24..32].copy_from_slice(&offtout(NEW.len() as i64 + 10));
+        assert!(patch(OLD, &longer).is_err());
+
+        let mut blocks_too_long = known_patch();
+        blocks_too_long[8..16].copy_from_slice(&offtout(1 << 40));
+        assert_eq!(
+            patch(OLD, &blocks_too_long).unwrap_err(),
+            "corrupt patch header"
+        );
+    }
+
+    #[test]
+    fn rejects_overflowing_control_values() {
+        // Two bytes in, the next triple would run past `u64::MAX`.
+        let huge = make_patch(&[(0, 2, 0), (i64::MAX, i64::MAX, 0)], b"", b"ab", i64::MAX);
+        assert_eq!(patch(OLD, &huge).unwrap_err(), "corrupt control block");
+
+        let seek = make_patch(&[(0, 1, i64::MAX), (0, 1, i64::MAX)], b"", b"ab", 2);
+        assert_eq!(patch(OLD, &seek).unwrap_err(), "corrupt control block");
+    }
+
+    #[test]
+    fn join_stays_inside_the_root() {
+        let root = Path::new("/tmp/teletraan-delta");
+        assert_eq!(
+            join(root, "lib/a.so").unwrap(),
+            root.join("lib").join("a.so")
+        );
+        for path in [
+            "../etc/passwd",
+            "lib/../../x",
+            "",
+            "a//b",
+            "./a",
+            "a\\..\\b",
+        ] {
+            assert!(join(root, path).is_err(), "{path:?}");
+        }
+    }
+}
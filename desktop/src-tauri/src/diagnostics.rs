@@ -0,0 +1,259 @@
+use std::collections::VecDeque;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::process::ExitStatus;
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+/// How many trailing lines of the backend log accompany a `backend-exit` event.
+pub const EXIT_LOG_TAIL_LINES: usize = 200;
+
+/// How many recent backend output lines are kept in memory for crash reports.
+pub const CRASH_CONTEXT_LINES: usize = 500;
+
+/// The backend's most recent output, shared by its stdout and stderr readers.
+///
+/// Unlike the log tail this never depends on the log file being writable.
+#[derive(Clone, Default)]
+pub struct OutputRing(Arc<Mutex<VecDeque<String>>>);
+
+impl OutputRing {
+    pub fn push(&self, line: String) {
+        let mut lines = self.0.lock().unwrap();
+        if lines.len() >= CRASH_CONTEXT_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    pub fn snapshot(&self) -> Vec<String> {
+        self.0.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+/// Known failure modes recognisable from the backend's last output.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitCause {
+    PortInUse,
+    MissingModule,
+    DatabaseLocked,
+    DatabaseCorrupt,
+    PermissionDenied,
+    KilledBySystem,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ExitDiagnosis {
+    pub cause: ExitCause,
+    /// Human-readable explanation suitable for showing to the user as-is.
+    pub message: String,
+}
+
+/// Payload of the `backend-exit` event.
+#[derive(Clone, Debug, Serialize)]
+pub struct BackendExit {
+    pub exit_code: Option<i32>,
+    /// Terminating signal on Unix; always `None` on Windows.
+    pub signal: Option<i32>,
+    pub diagnosis: Option<ExitDiagnosis>,
+    pub log_tail: Vec<String>,
+}
+
+impl BackendExit {
+    /// Build the exit report from the process status and the tail of the log.
+    pub fn collect(status: ExitStatus, log_path: &Path) -> Self {
+        #[cfg(unix)]
+        let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+        #[cfg(not(unix))]
+        let signal = None;
+
+        let log_tail = tail_lines(log_path, EXIT_LOG_TAIL_LINES).unwrap_or_else(|e| {
+            log::warn!(
+                "Failed to read backend log tail {}: {e}",
+                log_path.display()
+            );
+            Vec::new()
+        });
+
+        Self {
+            exit_code: status.code(),
+            signal,
+            diagnosis: diagnose(signal, &log_tail),
+            log_tail,
+        }
+    }
+}
+
+/// Why the backend failed to come up, carried by `backend-startup-failed`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StartupError {
+    /// The bundled backend executable is not where the installer put it.
+    BinaryMissing { path: String },
+    /// The bundled backend's files differ from the manifest this build was
+    /// made with.
+    BinaryTampered { files: Vec<String> },
+    /// The executable exists but could not be launched.
+    SpawnFailed { reason: String },
+    /// The process started but exited before its first healthy health check.
+    ExitedDuringStartup { exit_code: Option<i32> },
+    /// The process is running but never reported healthy.
+    HealthTimeout { waited_secs: u64 },
+    /// Another process is already listening on the backend's port.
+    PortConflict {
+        port: u16,
+        process_name: Option<String>,
+    },
+}
+
+impl std::fmt::Display for StartupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BinaryMissing { path } => write!(
+                f,
+                "The backend executable is missing ({path}). Reinstalling Teletraan should restore it."
+            ),
+            Self::BinaryTampered { files } => write!(
+                f,
+                "The backend files have been modified ({}), so Teletraan will not run them. Reinstalling Teletraan will repair them.",
+                files.join(", ")
+            ),
+            Self::SpawnFailed { reason } => write!(f, "Failed to spawn backend process: {reason}"),
+            Self::ExitedDuringStartup {
+                exit_code: Some(code),
+            } => write!(f, "Backend exited during startup (exit code {code})"),
+            Self::ExitedDuringStartup { exit_code: None } => {
+                write!(f, "Backend was killed during startup")
+            }
+            Self::HealthTimeout { waited_secs } => {
+                write!(f, "Backend did not become healthy within {waited_secs}s")
+            }
+            Self::PortConflict {
+                port,
+                process_name: Some(name),
+            } => write!(f, "Backend port {port} is already in use by {name}"),
+            Self::PortConflict {
+                port,
+                process_name: None,
+            } => write!(f, "Backend port {port} is already in use by another program"),
+        }
+    }
+}
+
+/// Payload of the `backend-startup-failed` event.
+#[derive(Clone, Debug, Serialize)]
+pub struct StartupFailure {
+    #[serde(flatten)]
+    pub error: StartupError,
+    /// Human-readable explanation suitable for showing to the user as-is.
+    pub message: String,
+}
+
+impl From<StartupError> for StartupFailure {
+    fn from(error: StartupError) -> Self {
+        Self {
+            message: error.to_string(),
+            error,
+        }
+    }
+}
+
+/// Return the last `n` lines of a text file without reading all of it.
+pub fn tail_lines(path: &Path, n: usize) -> std::io::Result<Vec<String>> {
+    const CHUNK: u64 = 8 * 1024;
+
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut start = len;
+    let mut buf: Vec<u8> = Vec::new();
+
+    // Read backwards in chunks until we've seen more than `n` newlines.
+    while start > 0 && buf.iter().filter(|&&b| b == b'\n').count() <= n {
+        let read = CHUNK.min(start);
+        start -= read;
+        file.seek(SeekFrom::Start(start))?;
+        let mut chunk = vec![0; read as usize];
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&buf);
+        buf = chunk;
+    }
+
+    let text = String::from_utf8_lossy(&buf);
+    let lines: Vec<&str> = text.lines().collect();
+    let skip = lines.len().saturating_sub(n);
+    Ok(lines[skip..].iter().map(|l| l.to_string()).collect())
+}
+
+/// Match the exit against failure modes we know how to explain.
+///
+/// The most recent lines are checked first so the error that actually ended
+/// the process wins over earlier, recovered ones.
+fn diagnose(signal: Option<i32>, log_tail: &[String]) -> Option<ExitDiagnosis> {
+    for line in log_tail.iter().rev() {
+        let lower = line.to_ascii_lowercase();
+
+        if lower.contains("address already in use")
+            || lower.contains("only one usage of each socket address")
+        {
+            return Some(ExitDiagnosis {
+                cause: ExitCause::PortInUse,
+                message: "The backend port is already in use by another process. \
+                          Close the other application (or another Teletraan instance) and restart."
+                    .into(),
+            });
+        }
+
+        if let Some(idx) = line.find("No module named ") {
+            let module =
+                line[idx + "No module named ".len()..].trim_matches(|c| c == '\'' || c == '"');
+            return Some(ExitDiagnosis {
+                cause: ExitCause::MissingModule,
+                message: format!(
+                    "The bundled backend is missing the Python module {module}. \
+                     Reinstalling Teletraan should restore it."
+                ),
+            });
+        }
+
+        if lower.contains("database is locked") {
+            return Some(ExitDiagnosis {
+                cause: ExitCause::DatabaseLocked,
+                message: "The database is locked by another process, possibly a \
+                          leftover backend from a previous session."
+                    .into(),
+            });
+        }
+
+        if lower.contains("database disk image is malformed")
+            || lower.contains("file is not a database")
+        {
+            return Some(ExitDiagnosis {
+                cause: ExitCause::DatabaseCorrupt,
+                message: "The database file appears to be corrupted.".into(),
+            });
+        }
+
+        if lower.contains("permissionerror") || lower.contains("permission denied") {
+            return Some(ExitDiagnosis {
+                cause: ExitCause::PermissionDenied,
+                message: "The backend was denied access to a file it needs. \
+                          Check permissions on the Teletraan data folder."
+                    .into(),
+            });
+        }
+    }
+
+    // SIGKILL with nothing in the log usually means the OOM killer or a force quit.
+    if signal == Some(9) {
+        return Some(ExitDiagnosis {
+            cause: ExitCause::KilledBySystem,
+            message: "The backend was killed by the operating system, possibly \
+                      because the machine ran low on memory."
+                .into(),
+        });
+    }
+
+    None
+}
@@ -0,0 +1,436 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::http::Request;
+use tauri::{AppHandle, Emitter};
+use tokio::io::AsyncWriteExt;
+
+use crate::backend;
+use crate::settings;
+use crate::transport;
+
+/// Timeout of each page fetched from the backend.
+const PAGE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Timeout for connecting to the backend's table export. The body then
+/// streams for as long as the table takes.
+const TABLE_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Shortest interval between two `export-table-progress` events.
+const TABLE_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// What `export_file` and `export_table` save.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportKind {
+    /// Deep insights, newest first.
+    Insights,
+    /// Daily candles of one `symbol`, newest first; the backend serves up to
+    /// the last 1000.
+    Candles,
+    /// Tracked insight outcomes, newest first.
+    Outcomes,
+}
+
+impl ExportKind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Insights => "insights",
+            Self::Candles => "candles",
+            Self::Outcomes => "outcomes",
+        }
+    }
+
+    /// Largest page the backend serves for this kind.
+    fn page_size(self) -> usize {
+        match self {
+            Self::Insights => 100,
+            Self::Candles => 1000,
+            Self::Outcomes => 200,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// Formats of `export_table`.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableFormat {
+    Csv,
+}
+
+/// Payload of the `export-progress` event, sent after every page.
+#[derive(Clone, Debug, Serialize)]
+struct ExportProgress {
+    kind: ExportKind,
+    rows: usize,
+    /// Rows the backend reported in total; `None` for unpaginated kinds.
+    total: Option<usize>,
+}
+
+/// Result of `export_file`.
+#[derive(Clone, Debug, Serialize)]
+pub struct ExportResult {
+    pub path: PathBuf,
+    pub rows: usize,
+}
+
+/// Payload of the `export-table-progress` event.
+#[derive(Clone, Debug, Serialize)]
+struct TableExportProgress {
+    table: ExportKind,
+    bytes: u64,
+    /// Rows in the table when the export started.
+    total_rows: Option<u64>,
+}
+
+/// Result of `export_table`.
+#[derive(Clone, Debug, Serialize)]
+pub struct TableExportResult {
+    pub path: PathBuf,
+    /// Rows in the table when the export started.
+    pub rows: Option<u64>,
+    pub size_bytes: u64,
+}
+
+/// A paginated backend list.
+#[derive(Deserialize)]
+struct Page {
+    items: Vec<serde_json::Value>,
+    total: usize,
+}
+
+/// Tauri command: fetch every row of `kind` from the backend and save it as
+/// `format` to `destination`, or to the Downloads folder when no path is
+/// given. `symbol` is required for candles.
+///
+/// An existing file is only replaced with `overwrite: true`. The file is
+/// written next to the destination and renamed into place, so a failed export
+/// never leaves a partial file. `export-progress` reports each page fetched.
+#[tauri::command]
+pub async fn export_file(
+    app: AppHandle,
+    kind: ExportKind,
+    format: ExportFormat,
+    destination: Option<String>,
+    overwrite: Option<bool>,
+    symbol: Option<String>,
+) -> Result<ExportResult, String> {
+    let symbol = symbol
+        .map(|s| s.trim().to_uppercase())
+        .filter(|s| !s.is_empty());
+    if matches!(kind, ExportKind::Candles) && symbol.is_none() {
+        return Err("Exporting candles requires a symbol".to_string());
+    }
+    let name = match (&symbol, kind) {
+        (Some(symbol), ExportKind::Candles) => format!("candles-{symbol}"),
+        _ => kind.name().to_string(),
+    };
+    let destination = destination_path(&app, destination, &name, format.extension(), overwrite)?;
+
+    let rows = fetch_rows(&app, kind, symbol.as_deref()).await?;
+    let count = rows.len();
+    let path = destination.clone();
+    tauri::async_runtime::spawn_blocking(move || write_rows(&path, format, &rows))
+        .await
+        .map_err(|e| format!("Failed to write export: {e}"))??;
+    log::info!(
+        "Exported {count} {} rows to {}",
+        kind.name(),
+        destination.display()
+    );
+    Ok(ExportResult {
+        path: destination,
+        rows: count,
+    })
+}
+
+/// `destination`, or a timestamped file named after `name` in the export
+/// folder. An existing file is only accepted with `overwrite`.
+fn destination_path(
+    app: &AppHandle,
+    destination: Option<String>,
+    name: &str,
+    extension: &str,
+    overwrite: Option<bool>,
+) -> Result<PathBuf, String> {
+    let destination = match destination {
+        Some(path) => PathBuf::from(path),
+        None => settings::export_dir(app)?.join(format!(
+            "teletraan-{name}-{}.{extension}",
+            chrono::Local::now().format("%Y-%m-%dT%H-%M-%S"),
+        )),
+    };
+    if destination.exists() && !overwrite.unwrap_or(false) {
+        return Err(format!(
+            "{} already exists; export again with overwrite to replace it",
+            destination.display()
+        ));
+    }
+    Ok(destination)
+}
+
+/// The `.part` file an export is written to before it is renamed to `path`.
+fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+async fn fetch_rows(
+    app: &AppHandle,
+    kind: ExportKind,
+    symbol: Option<&str>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let endpoint = backend::endpoint(app).ok_or("Backend has not been started")?;
+    let client = transport::Client::new(PAGE_TIMEOUT)?;
+    let limit = kind.page_size();
+    let mut rows = Vec::new();
+    loop {
+        let path = match kind {
+            ExportKind::Insights => {
+                format!("/api/v1/deep-insights?limit={limit}&offset={}", rows.len())
+            }
+            ExportKind::Outcomes => format!("/api/v1/outcomes?limit={limit}&offset={}", rows.len()),
+            ExportKind::Candles => {
+                format!(
+                    "/api/v1/stocks/{}/history?limit={limit}",
+                    transport::path_segment(symbol.unwrap_or_default())
+                )
+            }
+        };
+        let resp = client.get(&endpoint, &path).await?;
+        if !resp.status().is_success() {
+            return Err(format!(
+                "Failed to fetch {path}: HTTP {}: {}",
+                resp.status(),
+                String::from_utf8_lossy(resp.body())
+            ));
+        }
+        let (items, total) = match kind {
+            ExportKind::Candles => (transport::json::<Vec<serde_json::Value>>(&resp)?, None),
+            _ => {
+                let page = transport::json::<Page>(&resp)?;
+                (page.items, Some(page.total))
+            }
+        };
+        let last_page = items.len() < limit || total.is_none();
+        rows.extend(items);
+        let _ = app.emit(
+            "export-progress",
+            ExportProgress {
+                kind,
+                rows: rows.len(),
+                total,
+            },
+        );
+        if last_page || total.is_some_and(|total| rows.len() >= total) {
+            return Ok(rows);
+        }
+    }
+}
+
+fn write_rows(path: &Path, format: ExportFormat, rows: &[serde_json::Value]) -> Result<(), String> {
+    let contents = match format {
+        ExportFormat::Json => serde_json::to_vec_pretty(rows)
+            .map_err(|e| format!("Failed to serialize export: {e}"))?,
+        ExportFormat::Csv => to_csv(rows).into_bytes(),
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {}: {e}", parent.display()))?;
+    }
+    let tmp_path = part_path(path);
+    std::fs::File::create(&tmp_path)
+        .and_then(|mut file| file.write_all(&contents).and_then(|()| file.sync_all()))
+        .map_err(|e| format!("Failed to write {}: {e}", tmp_path.display()))?;
+    std::fs::rename(&tmp_path, path).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp_path);
+        format!("Failed to replace {}: {e}", path.display())
+    })
+}
+
+/// Tauri command: save a whole table (`insights`, `outcomes` or the
+/// `candles` of every symbol) straight from the database to `path`, or to
+/// the export folder when no path is given.
+///
+/// The backend reads the table on its own read-only connection and streams
+/// it as CSV, which is written to disk as it arrives, so tables of millions
+/// of rows never pass through the webview or sit in memory.
+/// `export-table-progress` reports the bytes written. As with `export_file`,
+/// an existing file is only replaced with `overwrite: true` and a failed
+/// export leaves no partial file.
+#[tauri::command]
+pub async fn export_table(
+    app: AppHandle,
+    table: ExportKind,
+    format: TableFormat,
+    path: Option<String>,
+    overwrite: Option<bool>,
+) -> Result<TableExportResult, String> {
+    let extension = match format {
+        TableFormat::Csv => "csv",
+    };
+    let destination = destination_path(
+        &app,
+        path,
+        &format!("{}-all", table.name()),
+        extension,
+        overwrite,
+    )?;
+    let privileged = transport::privileged_endpoint(&app)?;
+
+    let mut request = Request::get(format!("/api/v1/maintenance/export/{}", table.name()));
+    for (name, value) in privileged.headers() {
+        request = request.header(name, value);
+    }
+    let request = request
+        .body(Vec::new())
+        .map_err(|e| format!("Invalid export request: {e}"))?;
+    let resp = transport::open_stream(&privileged.endpoint, request, TABLE_CONNECT_TIMEOUT)
+        .await
+        .map_err(|e| format!("Failed to export {}: {e}", table.name()))?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let mut body = resp.into_body();
+        let detail = body.chunk().await.ok().flatten().unwrap_or_default();
+        return Err(format!(
+            "Failed to export {}: HTTP {status}: {}",
+            table.name(),
+            String::from_utf8_lossy(&detail)
+        ));
+    }
+    let total_rows = resp
+        .headers()
+        .get("X-Total-Rows")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok());
+
+    if let Some(parent) = destination.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {}: {e}", parent.display()))?;
+    }
+    let tmp_path = part_path(&destination);
+    let written = stream_to_file(&app, table, total_rows, resp.into_body(), &tmp_path).await;
+    let size_bytes = match written {
+        Ok(size_bytes) => size_bytes,
+        Err(e) => {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Err(e);
+        }
+    };
+    std::fs::rename(&tmp_path, &destination).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp_path);
+        format!("Failed to replace {}: {e}", destination.display())
+    })?;
+    log::info!(
+        "Exported the {} table ({size_bytes} bytes) to {}",
+        table.name(),
+        destination.display()
+    );
+    Ok(TableExportResult {
+        path: destination,
+        rows: total_rows,
+        size_bytes,
+    })
+}
+
+/// Write `body` to `path` as it arrives; returns the bytes written.
+async fn stream_to_file(
+    app: &AppHandle,
+    table: ExportKind,
+    total_rows: Option<u64>,
+    mut body: transport::BodyStream,
+    path: &Path,
+) -> Result<u64, String> {
+    let write_error = |e: std::io::Error| format!("Failed to write {}: {e}", path.display());
+    let mut file = tokio::fs::File::create(path).await.map_err(write_error)?;
+    let mut bytes = 0u64;
+    let mut reported = std::time::Instant::now();
+    while let Some(chunk) = body
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to export {}: {e}", table.name()))?
+    {
+        file.write_all(&chunk).await.map_err(write_error)?;
+        bytes += chunk.len() as u64;
+        if reported.elapsed() >= TABLE_PROGRESS_INTERVAL {
+            reported = std::time::Instant::now();
+            let _ = app.emit(
+                "export-table-progress",
+                TableExportProgress {
+                    table,
+                    bytes,
+                    total_rows,
+                },
+            );
+        }
+    }
+    file.sync_all().await.map_err(write_error)?;
+    let _ = app.emit(
+        "export-table-progress",
+        TableExportProgress {
+            table,
+            bytes,
+            total_rows,
+        },
+    );
+    Ok(bytes)
+}
+
+/// CSV with a column for every field of any row. Nested objects and lists
+/// are written as JSON.
+fn to_csv(rows: &[serde_json::Value]) -> String {
+    let mut columns: Vec<&str> = Vec::new();
+    for row in rows {
+        for key in row.as_object().into_iter().flat_map(|o| o.keys()) {
+            if !columns.contains(&key.as_str()) {
+                columns.push(key);
+            }
+        }
+    }
+
+    let mut out = String::new();
+    let header: Vec<String> = columns.iter().map(|c| csv_field(c)).collect();
+    out.push_str(&header.join(","));
+    out.push_str("\r\n");
+    for row in rows {
+        let fields: Vec<String> = columns
+            .iter()
+            .map(|column| match row.get(column) {
+                None | Some(serde_json::Value::Null) => String::new(),
+                Some(serde_json::Value::String(text)) => csv_field(text),
+                Some(other) => csv_field(&other.to_string()),
+            })
+            .collect();
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Quote a field when it contains a delimiter, quote or line break (RFC 4180).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
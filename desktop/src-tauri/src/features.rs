@@ -0,0 +1,249 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::settings;
+use crate::store;
+
+/// A capability that can ship dark and be turned on per user.
+struct Flag {
+    name: &'static str,
+    description: &'static str,
+    default: bool,
+}
+
+/// Every flag this build knows. Flags in the remote manifest that are not
+/// listed here are ignored, and `features.overrides` cannot set them.
+const FLAGS: &[Flag] = &[
+    Flag {
+        name: "streaming_quotes",
+        description: "Live quotes pushed over the WebSocket bridge instead of polled",
+        default: false,
+    },
+    Flag {
+        name: "backtester",
+        description: "Backtest insights against historical prices",
+        default: false,
+    },
+];
+
+/// Last manifest fetched from `manifest_url`, used while offline.
+const MANIFEST_CACHE: &str = "features_manifest.json";
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The `[features]` section of `settings.toml`: per-user flag overrides and
+/// where to fetch the remote manifest from, e.g.
+/// `manifest_url = "https://example.com/flags.json"` and
+/// `overrides = { backtester = true }`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeatureConfig {
+    /// JSON manifest of the form `{"flags": {"<name>": true}}`, fetched at
+    /// startup and by `refresh_feature_flags`.
+    pub manifest_url: Option<String>,
+    pub overrides: BTreeMap<String, bool>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+struct Manifest {
+    flags: BTreeMap<String, bool>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct CachedManifest {
+    url: String,
+    fetched_at: String,
+    flags: BTreeMap<String, bool>,
+}
+
+/// Where a flag's value comes from, lowest precedence first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagSource {
+    Default,
+    Remote,
+    User,
+}
+
+/// One entry of `get_feature_flags`, also the payload of the
+/// `feature-flags-changed` event.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FeatureFlag {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub enabled: bool,
+    pub source: FlagSource,
+}
+
+fn path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    Ok(crate::resolve_data_dir(app)?.join(name))
+}
+
+fn load_config(app: &AppHandle) -> FeatureConfig {
+    settings::current(app).features
+}
+
+/// Whether this build knows the flag `name`.
+pub fn is_known(name: &str) -> bool {
+    FLAGS.iter().any(|flag| flag.name == name)
+}
+
+/// `manifest_url` if it is an http or https URL.
+pub fn parse_manifest_url(url: &str) -> Result<reqwest::Url, String> {
+    let parsed =
+        reqwest::Url::parse(url).map_err(|e| format!("Invalid manifest_url {url:?}: {e}"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!(
+            "Invalid manifest_url {url:?}: expected http or https"
+        ));
+    }
+    Ok(parsed)
+}
+
+/// The cached manifest, if it came from the configured `manifest_url`.
+fn load_manifest(app: &AppHandle, config: &FeatureConfig) -> BTreeMap<String, bool> {
+    let cached = path(app, MANIFEST_CACHE)
+        .and_then(|path| store::read_json::<CachedManifest>(&path))
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to read cached feature manifest: {e}");
+            None
+        });
+    match (cached, &config.manifest_url) {
+        (Some(cached), Some(url)) if cached.url == *url => cached.flags,
+        _ => BTreeMap::new(),
+    }
+}
+
+/// Every known flag with its value: the user's override, else the remote
+/// manifest, else the built-in default.
+pub fn flags(app: &AppHandle) -> Vec<FeatureFlag> {
+    let config = load_config(app);
+    let remote = load_manifest(app, &config);
+    FLAGS
+        .iter()
+        .map(|flag| {
+            let (enabled, source) = match (config.overrides.get(flag.name), remote.get(flag.name)) {
+                (Some(&enabled), _) => (enabled, FlagSource::User),
+                (None, Some(&enabled)) => (enabled, FlagSource::Remote),
+                (None, None) => (flag.default, FlagSource::Default),
+            };
+            FeatureFlag {
+                name: flag.name,
+                description: flag.description,
+                enabled,
+                source,
+            }
+        })
+        .collect()
+}
+
+/// Emit `feature-flags-changed` after `features` changed in the settings.
+pub fn settings_changed(app: &AppHandle) {
+    emit_changed(app, &flags(app));
+}
+
+fn emit_changed(app: &AppHandle, flags: &[FeatureFlag]) {
+    if let Err(e) = app.emit("feature-flags-changed", flags) {
+        log::warn!("Failed to emit feature-flags-changed: {e}");
+    }
+}
+
+/// Fetch the remote manifest into the cache. Returns whether it was fetched;
+/// on failure the last cached manifest stays in use.
+async fn fetch_manifest(app: &AppHandle) -> Result<bool, String> {
+    let Some(url) = load_config(app).manifest_url else {
+        return Ok(false);
+    };
+    let parsed = parse_manifest_url(&url)?;
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {e}"))?;
+    let manifest: Manifest = client
+        .get(parsed)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to fetch feature manifest: {e}"))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid feature manifest: {e}"))?;
+    for name in manifest.flags.keys() {
+        if !is_known(name) {
+            log::debug!("Ignoring feature flag {name} unknown to this version");
+        }
+    }
+    store::write_json(
+        &path(app, MANIFEST_CACHE)?,
+        &CachedManifest {
+            url,
+            fetched_at: chrono::Utc::now().to_rfc3339(),
+            flags: manifest.flags,
+        },
+    )?;
+    Ok(true)
+}
+
+/// Fetch the manifest and emit `feature-flags-changed` if a flag changed.
+async fn refresh(app: &AppHandle) -> Result<Vec<FeatureFlag>, String> {
+    let before = flags(app);
+    if fetch_manifest(app).await? {
+        log::info!("Feature manifest refreshed");
+    }
+    let after = flags(app);
+    if after != before {
+        emit_changed(app, &after);
+    }
+    Ok(after)
+}
+
+/// Refresh the remote manifest in the background at startup.
+pub fn spawn_refresh(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = refresh(&app).await {
+            log::warn!("{e}; using the cached feature flags");
+        }
+    });
+}
+
+/// Tauri command: every feature flag this build knows, with whether it is
+/// `enabled` and whether that comes from the `default`, the `remote`
+/// manifest or the `user`.
+#[tauri::command]
+pub async fn get_feature_flags(app: AppHandle) -> Result<Vec<FeatureFlag>, String> {
+    Ok(flags(&app))
+}
+
+/// Tauri command: turn the flag `name` on or off for this user, or with
+/// `null` go back to the manifest or default value. Saved as
+/// `features.overrides`, and emits `feature-flags-changed`.
+#[tauri::command]
+pub async fn set_feature_flag(
+    app: AppHandle,
+    name: String,
+    enabled: Option<bool>,
+) -> Result<Vec<FeatureFlag>, String> {
+    if !is_known(&name) {
+        return Err(format!("Unknown feature flag: {name}"));
+    }
+    settings::update(&app, |settings| {
+        match enabled {
+            Some(enabled) => settings.features.overrides.insert(name.clone(), enabled),
+            None => settings.features.overrides.remove(&name),
+        };
+    })?;
+    log::info!("Feature flag {name} set to {enabled:?}");
+    Ok(flags(&app))
+}
+
+/// Tauri command: fetch the remote manifest now, e.g. after `manifest_url`
+/// changed.
+#[tauri::command]
+pub async fn refresh_feature_flags(app: AppHandle) -> Result<Vec<FeatureFlag>, String> {
+    refresh(&app).await
+}
@@ -0,0 +1,531 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::announce;
+use crate::backend::{self, BackendState};
+use crate::backend_update;
+use crate::diagnostics::StartupError;
+use crate::health_history;
+use crate::restart::RestartTracker;
+use crate::settings;
+use crate::shell_log::Phase;
+use crate::transport::{self, Endpoint};
+
+const HEALTH_PATH: &str = "/api/v1/health";
+const DEEP_HEALTH_PATH: &str = "/api/v1/health/deep";
+
+/// Health polling schedule, the `[health_check]` section of `settings.toml`;
+/// `TELETRAAN_*` environment variables override individual fields (see
+/// `config::health`).
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct HealthCheckConfig {
+    /// First startup poll interval; it doubles after each failed poll...
+    pub startup_initial_interval_ms: u64,
+    /// ...up to this ceiling.
+    pub startup_max_interval_ms: u64,
+    /// Give up on startup after this long...
+    pub startup_timeout_secs: u64,
+    /// ...or after this many polls, whichever comes first.
+    pub startup_max_attempts: Option<u32>,
+    /// Per-request HTTP timeout.
+    pub request_timeout_ms: u64,
+    /// Time between watchdog probes once the backend is ready.
+    pub watchdog_interval_secs: u64,
+    /// Consecutive failed probes before the backend is declared unhealthy.
+    pub unhealthy_threshold: u32,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            startup_initial_interval_ms: 100,
+            startup_max_interval_ms: 2_000,
+            startup_timeout_secs: 150,
+            startup_max_attempts: None,
+            request_timeout_ms: 2_000,
+            watchdog_interval_secs: 10,
+            unhealthy_threshold: 3,
+        }
+    }
+}
+
+impl HealthCheckConfig {
+    fn load(app: &AppHandle) -> Self {
+        let mut config = settings::current(app).health_check;
+        config.startup_initial_interval_ms = config.startup_initial_interval_ms.max(1);
+        config.startup_max_interval_ms = config
+            .startup_max_interval_ms
+            .max(config.startup_initial_interval_ms);
+        config.watchdog_interval_secs = config.watchdog_interval_secs.max(1);
+        config.unhealthy_threshold = config.unhealthy_threshold.max(1);
+        config
+    }
+
+    /// Delay before startup poll `attempt + 1`, given `attempt` polls so far.
+    fn startup_interval(&self, attempt: u32) -> Duration {
+        let factor = 1u64 << attempt.saturating_sub(1).min(20);
+        Duration::from_millis(
+            self.startup_initial_interval_ms
+                .saturating_mul(factor)
+                .min(self.startup_max_interval_ms),
+        )
+    }
+}
+
+/// Backend health check JSON response, forwarded as-is in `backend-health`.
+///
+/// Everything but `status` is optional so an older backend still parses.
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct HealthResponse {
+    pub status: String,
+    /// `"teletraan"`; tells our backend apart from another server on the port.
+    pub service: Option<String>,
+    pub version: Option<String>,
+    /// `"connected"` or `"disconnected"`.
+    pub database: Option<String>,
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Model tables and columns the database does not have yet.
+    pub pending_migrations: u32,
+    pub llm_provider: Option<String>,
+    pub llm_clients_available: Option<u32>,
+    pub llm_pool_size: Option<u32>,
+    /// Analysis tasks queued or running.
+    pub queue_depth: u32,
+    pub uptime_seconds: Option<f64>,
+}
+
+/// Result of `check_backend_health`, shown by the frontend diagnostics panel.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct HealthCheckResult {
+    /// The backend answered the request at all.
+    pub reachable: bool,
+    pub http_status: Option<u16>,
+    pub latency_ms: Option<u64>,
+    pub version: Option<String>,
+    pub uptime_seconds: Option<f64>,
+    /// Why the request failed or the response could not be parsed.
+    pub error: Option<String>,
+}
+
+/// Subset of the backend deep health check JSON response.
+#[derive(serde::Deserialize)]
+struct DeepHealthResponse {
+    database: String,
+    missing_tables: Vec<String>,
+    missing_columns: Vec<String>,
+}
+
+/// How far up the stack the backend is working, as returned by
+/// `get_backend_health`.
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(tag = "level", rename_all = "snake_case")]
+pub enum HealthLevel {
+    /// No backend process, and nothing answering on its port.
+    Down,
+    /// Liveness only: the process is running but the API is not ready.
+    Alive { reason: String },
+    /// Readiness: the API answers healthy, but the deep check found problems.
+    Ready { issues: Vec<String> },
+    /// Ready, with the database reachable and its schema up to date.
+    Healthy,
+}
+
+/// Payload of the `backend-degraded` event.
+#[derive(Clone, serde::Serialize)]
+struct BackendDegraded {
+    reason: String,
+}
+
+/// Payload of the `backend-recovered` event.
+#[derive(Clone, serde::Serialize)]
+struct BackendRecovered {
+    failed_probes: u32,
+    degraded_for_ms: u64,
+}
+
+/// Payload of the `backend-unhealthy` event.
+#[derive(Clone, serde::Serialize)]
+struct BackendUnhealthy {
+    consecutive_failures: u32,
+    last_error: String,
+    /// The watchdog is killing the backend so the supervisor restarts it.
+    restarting: bool,
+}
+
+/// Endpoint of the running backend.
+fn backend_endpoint(app: &AppHandle) -> Result<Endpoint, String> {
+    backend::endpoint(app).ok_or_else(|| "Backend address is not known yet".to_string())
+}
+
+/// Probe the health endpoint of the running backend once.
+async fn probe(app: &AppHandle, client: &transport::Client) -> Result<HealthResponse, String> {
+    probe_endpoint(client, &backend_endpoint(app)?).await
+}
+
+async fn probe_endpoint(
+    client: &transport::Client,
+    endpoint: &Endpoint,
+) -> Result<HealthResponse, String> {
+    let started = std::time::Instant::now();
+    let resp = client.get(endpoint, HEALTH_PATH).await?;
+    if !resp.status().is_success() {
+        return Err(format!("HTTP {}", resp.status()));
+    }
+    let body = transport::json::<HealthResponse>(&resp)
+        .map_err(|e| format!("Invalid health response: {e}"))?;
+    if body.status != "healthy" {
+        return Err(format!("Backend reported status {:?}", body.status));
+    }
+    health_history::note_latency(started.elapsed().as_millis() as u64);
+    Ok(body)
+}
+
+/// Probe the health endpoint once with a fresh client.
+pub async fn check(app: &AppHandle) -> Result<(), String> {
+    check_at(&backend_endpoint(app)?).await.map(|_| ())
+}
+
+/// Probe the health endpoint of a backend at `endpoint`, which need not be the
+/// one we spawned.
+pub async fn check_at(endpoint: &Endpoint) -> Result<HealthResponse, String> {
+    let client = transport::Client::new(Duration::from_secs(2))?;
+    probe_endpoint(&client, endpoint).await
+}
+
+/// Problems reported by the deep health check; empty when all is well.
+async fn deep_issues(app: &AppHandle, client: &transport::Client) -> Vec<String> {
+    let result = async {
+        let resp = client
+            .get(&backend_endpoint(app)?, DEEP_HEALTH_PATH)
+            .await?;
+        if !resp.status().is_success() {
+            return Err(format!("HTTP {}", resp.status()));
+        }
+        transport::json::<DeepHealthResponse>(&resp)
+    }
+    .await;
+    let deep = match result {
+        Ok(deep) => deep,
+        Err(e) => return vec![format!("Deep health check failed: {e}")],
+    };
+
+    let mut issues = Vec::new();
+    if deep.database != "connected" {
+        issues.push(format!("Database is {}", deep.database));
+    }
+    if !deep.missing_tables.is_empty() {
+        issues.push(format!(
+            "Missing tables: {}",
+            deep.missing_tables.join(", ")
+        ));
+    }
+    if !deep.missing_columns.is_empty() {
+        issues.push(format!(
+            "Missing columns: {}",
+            deep.missing_columns.join(", ")
+        ));
+    }
+    issues
+}
+
+/// Check liveness, readiness, and deep health in turn.
+///
+/// A backend shared with another app instance has no child process here, so
+/// an answering API also counts as alive.
+pub async fn assess(app: &AppHandle) -> HealthLevel {
+    let running = backend::current_pid(app).is_some();
+    let client = match transport::Client::new(Duration::from_secs(2)) {
+        Ok(c) => c,
+        Err(e) => {
+            return HealthLevel::Alive {
+                reason: format!("Failed to build HTTP client for health check: {e}"),
+            }
+        }
+    };
+
+    if let Err(reason) = probe(app, &client).await {
+        return if running {
+            HealthLevel::Alive { reason }
+        } else {
+            HealthLevel::Down
+        };
+    }
+
+    let issues = deep_issues(app, &client).await;
+    if issues.is_empty() {
+        HealthLevel::Healthy
+    } else {
+        HealthLevel::Ready { issues }
+    }
+}
+
+/// Tauri command exposed to the frontend: reachability, HTTP status, latency,
+/// and the version and uptime the backend reports.
+#[tauri::command]
+pub async fn check_backend_health(app: AppHandle) -> Result<HealthCheckResult, String> {
+    let client = transport::Client::new(Duration::from_secs(2))?;
+
+    let started = std::time::Instant::now();
+    let result = match backend_endpoint(&app) {
+        Ok(endpoint) => client.get(&endpoint, HEALTH_PATH).await,
+        Err(e) => Err(e),
+    };
+    let resp = match result {
+        Ok(resp) => resp,
+        Err(e) => {
+            return Ok(HealthCheckResult {
+                reachable: false,
+                http_status: None,
+                latency_ms: None,
+                version: None,
+                uptime_seconds: None,
+                error: Some(e),
+            })
+        }
+    };
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let http_status = resp.status().as_u16();
+
+    let (body, error) = match transport::json::<HealthResponse>(&resp) {
+        Ok(body) => (Some(body), None),
+        Err(e) => (None, Some(format!("Invalid health response: {e}"))),
+    };
+    Ok(HealthCheckResult {
+        reachable: true,
+        http_status: Some(http_status),
+        latency_ms: Some(latency_ms),
+        version: body.as_ref().and_then(|b| b.version.clone()),
+        uptime_seconds: body.and_then(|b| b.uptime_seconds),
+        error,
+    })
+}
+
+/// Tauri command: structured liveness / readiness / deep health of the backend.
+#[tauri::command]
+pub async fn get_backend_health(app: AppHandle) -> Result<HealthLevel, String> {
+    Ok(assess(&app).await)
+}
+
+/// Monitor the backend process `pid`: wait for it to become ready, then keep
+/// probing it for the rest of its life.
+///
+/// The monitor exits quietly as soon as `pid` is no longer the current
+/// backend, so a respawned backend never ends up with two monitors.
+pub fn spawn_monitor(app: AppHandle, pid: u32) {
+    tauri::async_runtime::spawn(async move {
+        let config = HealthCheckConfig::load(&app);
+        let client = match transport::Client::new(Duration::from_millis(config.request_timeout_ms))
+        {
+            Ok(c) => c,
+            Err(e) => {
+                log::error!("Failed to build HTTP client for health check: {e}");
+                return;
+            }
+        };
+
+        let phase = Phase::start("wait_until_ready");
+        let ready = wait_until_ready(&app, &client, &config, pid).await;
+        drop(phase);
+        if ready {
+            watchdog(&app, &client, &config, pid).await;
+        }
+    });
+}
+
+/// Probe the development backend at `url` (see `backend::dev_backend_url`)
+/// until `generation` is stopped or replaced. It is never killed: a backend
+/// reloading its code is shown as degraded until it answers again.
+pub fn spawn_dev_monitor(app: AppHandle, url: String, generation: u64) {
+    tauri::async_runtime::spawn(async move {
+        let config = HealthCheckConfig::load(&app);
+        let client = match transport::Client::new(Duration::from_millis(config.request_timeout_ms))
+        {
+            Ok(c) => c,
+            Err(e) => {
+                log::error!("Failed to build HTTP client for health check: {e}");
+                return;
+            }
+        };
+
+        let mut ready = false;
+        let mut attempt: u32 = 0;
+        loop {
+            let result = probe(&app, &client).await;
+            if !backend::is_current(generation) {
+                return;
+            }
+            match result {
+                Ok(health) => {
+                    let _ = app.emit("backend-health", health);
+                    backend::set_state(
+                        &app,
+                        BackendState::Healthy,
+                        Some(format!("External backend at {url}")),
+                    );
+                    if !ready {
+                        log::info!("External backend at {url} is healthy");
+                        ready = true;
+                        let _ = app.emit("backend-ready", ());
+                    }
+                }
+                Err(e) if ready => {
+                    log::warn!("External backend health probe failed: {e}");
+                    backend::set_state(&app, BackendState::Degraded, Some(e));
+                }
+                Err(e) => log::debug!("External backend at {url} not answering yet: {e}"),
+            }
+            attempt = attempt.saturating_add(1);
+            let interval = if ready {
+                Duration::from_secs(config.watchdog_interval_secs)
+            } else {
+                config.startup_interval(attempt)
+            };
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+/// Poll until the backend reports healthy. Returns `false` on timeout or if
+/// the backend was replaced or stopped meanwhile.
+async fn wait_until_ready(
+    app: &AppHandle,
+    client: &transport::Client,
+    config: &HealthCheckConfig,
+    pid: u32,
+) -> bool {
+    let started = std::time::Instant::now();
+    let deadline = started + Duration::from_secs(config.startup_timeout_secs);
+    let max_attempts = config.startup_max_attempts.unwrap_or(u32::MAX);
+
+    for attempt in 1..=max_attempts {
+        if backend::current_pid(app) != Some(pid) {
+            return false;
+        }
+        match probe(app, client).await {
+            Ok(health) => {
+                let _ = app.emit("backend-health", health);
+                if !backend::audit_exposure(app, pid).await {
+                    return false;
+                }
+                log::info!(
+                    "Backend healthy after {attempt} attempts ({:.1}s)",
+                    started.elapsed().as_secs_f64(),
+                );
+                backend::set_state_if_current(app, pid, BackendState::Healthy, None);
+                let _ = app.emit("backend-ready", ());
+                backend_update::confirm_when_stable(app, pid);
+                return true;
+            }
+            Err(e) => log::debug!("Health attempt {attempt}: {e}"),
+        }
+        let now = std::time::Instant::now();
+        if now >= deadline {
+            break;
+        }
+        tokio::time::sleep(config.startup_interval(attempt).min(deadline - now)).await;
+    }
+
+    let error = StartupError::HealthTimeout {
+        waited_secs: started.elapsed().as_secs(),
+    };
+    let message = error.to_string();
+    backend::set_state_if_current(app, pid, BackendState::Failed, Some(message.clone()));
+    backend::report_startup_failure(app, error);
+    let _ = app.emit("backend-error", message.clone());
+    if backend_update::note_failure(app, message.clone(), backend::recent_output(app)) {
+        return false;
+    }
+    announce::announce(
+        app,
+        announce::Severity::Critical,
+        "Teletraan backend unavailable",
+        &message,
+    );
+    false
+}
+
+/// Keep probing a ready backend. After `unhealthy_threshold` consecutive
+/// failures, emit `backend-unhealthy` and — if the restart policy allows —
+/// kill the hung process so the supervisor respawns it.
+///
+/// The first failed probe emits `backend-degraded`, and the first success after
+/// one or more failures emits `backend-recovered`, to drive a status indicator.
+async fn watchdog(
+    app: &AppHandle,
+    client: &transport::Client,
+    config: &HealthCheckConfig,
+    pid: u32,
+) {
+    let threshold = config.unhealthy_threshold;
+    let mut failures: u32 = 0;
+    let mut degraded_since = std::time::Instant::now();
+
+    loop {
+        tokio::time::sleep(Duration::from_secs(config.watchdog_interval_secs)).await;
+        if backend::current_pid(app) != Some(pid) {
+            return;
+        }
+
+        let error = match probe(app, client).await {
+            Ok(health) => {
+                let _ = app.emit("backend-health", health);
+                if failures > 0 {
+                    log::info!("Backend health probe recovered after {failures} failures");
+                    let _ = app.emit(
+                        "backend-recovered",
+                        BackendRecovered {
+                            failed_probes: failures,
+                            degraded_for_ms: degraded_since.elapsed().as_millis() as u64,
+                        },
+                    );
+                }
+                failures = 0;
+                backend::set_state_if_current(app, pid, BackendState::Healthy, None);
+                continue;
+            }
+            Err(e) => e,
+        };
+
+        failures += 1;
+        log::warn!("Backend health probe failed ({failures}/{threshold}): {error}");
+        if failures == 1 {
+            degraded_since = std::time::Instant::now();
+            let _ = app.emit(
+                "backend-degraded",
+                BackendDegraded {
+                    reason: error.clone(),
+                },
+            );
+        }
+        backend::set_state_if_current(app, pid, BackendState::Degraded, Some(error.clone()));
+        if failures != threshold {
+            continue;
+        }
+
+        let restarting = app
+            .state::<RestartTracker>()
+            .0
+            .lock()
+            .unwrap()
+            .policy
+            .restart_on_unhealthy;
+        log::error!("Backend unhealthy after {failures} consecutive failed probes");
+        let _ = app.emit(
+            "backend-unhealthy",
+            BackendUnhealthy {
+                consecutive_failures: failures,
+                last_error: error,
+                restarting,
+            },
+        );
+
+        if restarting {
+            backend::kill_unresponsive(app, pid);
+            return;
+        }
+    }
+}
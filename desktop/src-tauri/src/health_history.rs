@@ -0,0 +1,87 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::backend::{BackendState, BackendStatus};
+use crate::diagnostics;
+use crate::store;
+
+/// Entries returned by `get_health_history` when no limit is given.
+const DEFAULT_HISTORY_LIMIT: usize = 500;
+
+/// Once the history file grows past this, it is trimmed to the most recent
+/// `KEEP_ON_TRIM` entries.
+const MAX_HISTORY_BYTES: u64 = 1024 * 1024;
+const KEEP_ON_TRIM: usize = 2_000;
+
+/// Latency of the most recent successful health probe; `u64::MAX` if none yet.
+static LAST_LATENCY_MS: AtomicU64 = AtomicU64::new(u64::MAX);
+
+/// One lifecycle transition, as stored in `health_history.jsonl`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HealthRecord {
+    pub at: DateTime<Utc>,
+    pub state: BackendState,
+    pub pid: Option<u32>,
+    /// Latency of the probe that found the backend healthy.
+    pub latency_ms: Option<u64>,
+    /// Why the backend entered this state.
+    pub reason: Option<String>,
+}
+
+fn history_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::resolve_data_dir(app)?.join("health_history.jsonl"))
+}
+
+/// Remember the latency of a successful probe for the next transition record.
+pub fn note_latency(latency_ms: u64) {
+    LAST_LATENCY_MS.store(latency_ms, Ordering::Relaxed);
+}
+
+/// Append a state transition to the history.
+pub fn record(app: &AppHandle, status: &BackendStatus) {
+    let latency_ms = match (status.state, LAST_LATENCY_MS.load(Ordering::Relaxed)) {
+        (BackendState::Healthy, ms) if ms != u64::MAX => Some(ms),
+        _ => None,
+    };
+    let record = HealthRecord {
+        at: status.since,
+        state: status.state,
+        pid: status.pid,
+        latency_ms,
+        reason: status.detail.clone(),
+    };
+
+    let result = history_path(app).and_then(|path| {
+        store::append_jsonl(&path, &record)?;
+        store::trim_jsonl(&path, MAX_HISTORY_BYTES, KEEP_ON_TRIM)
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to record health history: {e}");
+    }
+}
+
+/// Tauri command: recorded health transitions, oldest first, optionally only
+/// those since `since`.
+#[tauri::command]
+pub async fn get_health_history(
+    app: AppHandle,
+    limit: Option<usize>,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<HealthRecord>, String> {
+    let path = history_path(&app)?;
+    let lines = match diagnostics::tail_lines(&path, limit.unwrap_or(DEFAULT_HISTORY_LIMIT)) {
+        Ok(lines) => lines,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+
+    Ok(lines
+        .iter()
+        .filter_map(|line| serde_json::from_str::<HealthRecord>(line).ok())
+        .filter(|record| since.is_none_or(|since| record.at >= since))
+        .collect())
+}
@@ -0,0 +1,257 @@
+use std::collections::{BTreeSet, HashMap};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::audit::{self, AuditEvent};
+use crate::backend_update;
+use crate::cli;
+
+/// `(path, sha256)` of every file of the bundled backend, relative to its
+/// folder with `/` separators, computed by `build.rs` when this binary was
+/// built. Empty in builds without a bundled backend.
+const MANIFEST: &[(&str, &str)] = include!(concat!(env!("OUT_DIR"), "/backend_manifest.rs"));
+
+/// `(path, sha256)` of every file of a backend, as in `MANIFEST`.
+pub type Manifest = Vec<(String, String)>;
+
+/// Where `repair_backend` sends the user for a fresh installer.
+const INSTALLER_URL: &str = "https://github.com/barkain/teletraan/releases/latest";
+
+/// Files and folders that may appear next to the backend without being part
+/// of it, skipped by `build.rs` too.
+const IGNORED_NAMES: &[&str] = &["__pycache__", ".DS_Store"];
+
+/// Files found unchanged, with the length and modification time they had,
+/// so a restart only hashes what changed since.
+static VERIFIED: Mutex<Option<HashMap<PathBuf, (u64, SystemTime)>>> = Mutex::new(None);
+
+/// Result of `check_backend_integrity` and payload of `tampered-binary`.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct IntegrityReport {
+    /// The folder checked.
+    pub dir: PathBuf,
+    /// Whether this build has a manifest to check against.
+    pub has_manifest: bool,
+    /// Files checked against the manifest.
+    pub checked: usize,
+    /// Files whose content differs from the manifest.
+    pub modified: Vec<String>,
+    pub missing: Vec<String>,
+    /// Files the manifest does not list, which the backend could load.
+    pub unexpected: Vec<String>,
+    /// Where to download the installer to repair the install.
+    pub installer_url: String,
+}
+
+impl IntegrityReport {
+    pub fn is_intact(&self) -> bool {
+        self.modified.is_empty() && self.missing.is_empty() && self.unexpected.is_empty()
+    }
+
+    /// The files that failed the check, modified first.
+    pub fn failed(&self) -> Vec<String> {
+        self.modified
+            .iter()
+            .chain(&self.missing)
+            .chain(&self.unexpected)
+            .cloned()
+            .collect()
+    }
+}
+
+/// The manifest of the bundled backend this build was made with.
+pub fn bundled_manifest() -> Manifest {
+    MANIFEST
+        .iter()
+        .map(|(name, hash)| (name.to_string(), hash.to_string()))
+        .collect()
+}
+
+/// The folder of the bundled backend, whose files the manifest lists.
+pub fn backend_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .resource_dir()
+        .map_err(|e| format!("Failed to resolve resource directory: {e}"))?
+        .join("resources")
+        .join("teletraan-backend"))
+}
+
+fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Files under `dir`, relative to `root` with `/` separators.
+fn list_files(root: &Path, dir: &Path, files: &mut BTreeSet<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if IGNORED_NAMES.contains(&entry.file_name().to_string_lossy().as_ref()) {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            list_files(root, &path, files);
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let name = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.insert(name);
+        }
+    }
+}
+
+/// The manifest of the files in `dir`, for a backend this build did not
+/// ship with.
+pub fn hash_dir(dir: &Path) -> Result<Manifest, String> {
+    let mut files = BTreeSet::new();
+    list_files(dir, dir, &mut files);
+    files
+        .into_iter()
+        .map(|name| {
+            let path = dir.join(&name);
+            let hash = sha256_file(&path)
+                .map_err(|e| format!("Failed to hash {}: {e}", path.display()))?;
+            Ok((name, hash))
+        })
+        .collect()
+}
+
+/// Compare the files in `dir` with `manifest`. Files unchanged since they
+/// last passed are not hashed again.
+pub fn check(dir: &Path, manifest: &[(String, String)]) -> IntegrityReport {
+    let mut report = IntegrityReport {
+        dir: dir.to_path_buf(),
+        has_manifest: !manifest.is_empty(),
+        installer_url: INSTALLER_URL.to_string(),
+        ..Default::default()
+    };
+    if manifest.is_empty() {
+        return report;
+    }
+    let mut present = BTreeSet::new();
+    list_files(dir, dir, &mut present);
+
+    let mut verified = VERIFIED.lock().unwrap();
+    let verified = verified.get_or_insert_with(HashMap::new);
+    for (name, expected) in manifest {
+        let path = dir.join(name);
+        present.remove(name);
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            report.missing.push(name.to_string());
+            continue;
+        };
+        report.checked += 1;
+        let stamp = (
+            metadata.len(),
+            metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+        );
+        if verified.get(&path) == Some(&stamp) {
+            continue;
+        }
+        match sha256_file(&path) {
+            Ok(actual) if actual == *expected => {
+                verified.insert(path, stamp);
+            }
+            Ok(_) => {
+                verified.remove(&path);
+                report.modified.push(name.to_string());
+            }
+            Err(e) => {
+                log::warn!("Failed to hash {}: {e}", path.display());
+                verified.remove(&path);
+                report.modified.push(name.to_string());
+            }
+        }
+    }
+    report.unexpected = present.into_iter().collect();
+    report
+}
+
+/// Check the backend in `dir` against its `manifest` before it is spawned.
+/// Returns the files that do not match, after emitting the report as
+/// `tampered-binary` and recording it in the audit log.
+///
+/// A backend given with `--backend-path`, or a build without a manifest,
+/// is not checked.
+pub fn verify_before_spawn(
+    app: &AppHandle,
+    dir: &Path,
+    manifest: &[(String, String)],
+) -> Result<(), Vec<String>> {
+    if cli::args().backend_path.is_some() {
+        log::info!("Backend set with --backend-path; not checking it against the manifest");
+        return Ok(());
+    }
+    if manifest.is_empty() {
+        log::info!("This build has no backend manifest; not checking the backend files");
+        return Ok(());
+    }
+    let report = check(dir, manifest);
+    if report.is_intact() {
+        log::info!(
+            "Backend files match the manifest ({} files)",
+            report.checked
+        );
+        return Ok(());
+    }
+    log::error!(
+        "Backend files do not match the manifest: modified {:?}, missing {:?}, unexpected {:?}",
+        report.modified,
+        report.missing,
+        report.unexpected
+    );
+    let failed = report.failed();
+    audit::record(
+        app,
+        AuditEvent::TamperedBinary,
+        false,
+        format!("{} file(s) failed: {failed:?}", failed.len()),
+    );
+    if let Err(e) = app.emit("tampered-binary", &report) {
+        log::warn!("Failed to emit tampered-binary: {e}");
+    }
+    Err(failed)
+}
+
+/// Tauri command: compare the backend in use with its manifest, the one
+/// this build was made with or the one recorded when it was updated,
+/// without starting anything.
+#[tauri::command]
+pub async fn check_backend_integrity(app: AppHandle) -> Result<IntegrityReport, String> {
+    let active = backend_update::active(&app)?;
+    tauri::async_runtime::spawn_blocking(move || check(&active.dir, &active.manifest))
+        .await
+        .map_err(|e| format!("Failed to check the backend files: {e}"))
+}
+
+/// Tauri command: open the download page of the installer, which puts back
+/// the backend files as shipped. Returns the URL.
+#[tauri::command]
+pub async fn repair_backend(app: AppHandle) -> Result<String, String> {
+    log::info!("Opening {INSTALLER_URL} to repair the backend");
+    app.opener()
+        .open_url(INSTALLER_URL, None::<&str>)
+        .map_err(|e| format!("Failed to open {INSTALLER_URL}: {e}"))?;
+    Ok(INSTALLER_URL.to_string())
+}
@@ -0,0 +1,255 @@
+use std::collections::BTreeMap;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::settings::{self, EnvSettings};
+use crate::{redact, secrets};
+
+/// Arguments the shell passes itself; overriding them would break the
+/// connection to the backend.
+const RESERVED_ARGS: &[&str] = &[
+    "--host",
+    "--port",
+    "--uds",
+    "--ssl-certfile",
+    "--ssl-keyfile",
+];
+
+/// Variables the shell sets itself.
+const RESERVED_ENV: &[&str] = &[
+    "DATABASE_URL",
+    "TELETRAAN_AUTH_TOKEN",
+    "TELETRAAN_DB_KEY",
+    "TELETRAAN_DB_DECRYPT",
+    "TELETRAAN_PACKS_FILE",
+    "TELETRAAN_REVIEW_QUEUE_FILE",
+    "TELETRAAN_SHELL_TOKEN",
+];
+
+/// Extra command-line arguments and environment variables for the backend,
+/// from `backend.extra_args` and `backend.extra_env` in `settings.toml`, e.g.
+/// `extra_args = ["--workers", "2"]` and `extra_env = { LOG_LEVEL = "debug" }`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaunchOverrides {
+    pub extra_args: Vec<String>,
+    pub extra_env: BTreeMap<String, String>,
+}
+
+/// Where a pass-through variable listed by `get_backend_env` comes from.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvSource {
+    /// The `[env]` section of `settings.toml`.
+    Settings,
+    /// Stored with `set_secret`.
+    Keychain,
+    /// Inherited from the environment the app was started in.
+    Environment,
+}
+
+/// One entry of `get_backend_env`.
+#[derive(Clone, Debug, Serialize)]
+pub struct PassthroughVar {
+    pub name: String,
+    /// The value, or `[REDACTED]` for keys and tokens.
+    pub value: String,
+    pub source: EnvSource,
+}
+
+/// Payload of `get_backend_overrides`.
+#[derive(Clone, Debug, Serialize)]
+pub struct ActiveOverrides {
+    /// True when any override is applied, so the UI should show its banner.
+    pub active: bool,
+    pub args: Vec<String>,
+    /// `KEY=value` pairs, with secrets redacted.
+    pub env: Vec<String>,
+    /// Entries that were ignored, and why.
+    pub rejected: Vec<String>,
+}
+
+impl LaunchOverrides {
+    /// The valid overrides from the settings; invalid entries are logged and
+    /// returned in the second element.
+    pub fn load(app: &AppHandle) -> (Self, Vec<String>) {
+        let (config, rejected) = settings::current(app).backend.launch.validate();
+        for reason in &rejected {
+            log::warn!("Ignoring backend override: {reason}");
+        }
+        (config, rejected)
+    }
+
+    fn validate(self) -> (Self, Vec<String>) {
+        let mut rejected = Vec::new();
+        let mut extra_args = Vec::new();
+        let mut args = self.extra_args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg_problem(&arg) {
+                Some(problem) => {
+                    rejected.push(format!("argument {arg:?} {problem}"));
+                    // Drop the value of a reserved `--port 8001` as well.
+                    if RESERVED_ARGS.contains(&arg.as_str()) {
+                        args.next();
+                    }
+                }
+                None => extra_args.push(arg),
+            }
+        }
+        let extra_env = self
+            .extra_env
+            .into_iter()
+            .filter(|(key, value)| match env_problem(key, value) {
+                Some(problem) => {
+                    rejected.push(format!("variable {key:?} {problem}"));
+                    false
+                }
+                None => true,
+            })
+            .collect();
+        let config = Self {
+            extra_args,
+            extra_env,
+        };
+        (config, rejected)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.extra_args.is_empty() && self.extra_env.is_empty()
+    }
+
+    /// Append the overrides to the backend command.
+    pub fn apply(&self, command: &mut Command) {
+        if self.is_empty() {
+            return;
+        }
+        log::warn!(
+            "Starting backend with non-default settings: args {:?}, env {:?}",
+            self.extra_args,
+            self.redacted_env()
+        );
+        command.args(&self.extra_args).envs(&self.extra_env);
+    }
+
+    fn redacted_env(&self) -> Vec<String> {
+        self.extra_env
+            .iter()
+            .map(|(key, value)| redact::redact(&format!("{key}={value}")).into_owned())
+            .collect()
+    }
+}
+
+/// Set the `[env]` variables from the settings on the backend command. Their
+/// values are masked in every log from now on.
+pub fn apply_passthrough(app: &AppHandle, command: &mut Command) {
+    let vars = settings::current(app).env.vars();
+    if vars.is_empty() {
+        return;
+    }
+    let mut logged = Vec::new();
+    for (name, value) in vars {
+        if is_secret(&name) {
+            redact::register_secret(&value);
+        }
+        logged.push(masked(&name, &value));
+        command.env(name, value);
+    }
+    log::info!("Passing environment to backend: {logged:?}");
+}
+
+/// Tauri command: the pass-through variables the next backend will get,
+/// whether set in the settings, stored in the keychain or inherited, with
+/// keys and tokens masked. Values from the settings take precedence, then
+/// the keychain.
+#[tauri::command]
+pub async fn get_backend_env(app: AppHandle) -> Result<Vec<PassthroughVar>, String> {
+    let configured = settings::current(&app).env.vars();
+    let handle = app.clone();
+    let stored =
+        tauri::async_runtime::spawn_blocking(move || secrets::stored(&handle, "get_backend_env"))
+            .await
+            .unwrap_or_default();
+    Ok(EnvSettings::names()
+        .into_iter()
+        .filter_map(|name| {
+            let find = |vars: &[(String, String)]| {
+                vars.iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, value)| value.clone())
+            };
+            let (value, source) = match (find(&configured), find(&stored)) {
+                (Some(value), _) => (value, EnvSource::Settings),
+                (None, Some(value)) => (value, EnvSource::Keychain),
+                (None, None) => (
+                    std::env::var(&name).ok().filter(|v| !v.is_empty())?,
+                    EnvSource::Environment,
+                ),
+            };
+            Some(PassthroughVar {
+                value: masked(&name, &value),
+                name,
+                source,
+            })
+        })
+        .collect())
+}
+
+/// Keys and tokens are never shown, whatever their format.
+pub fn is_secret(name: &str) -> bool {
+    name.contains("KEY") || name.contains("TOKEN")
+}
+
+fn masked(name: &str, value: &str) -> String {
+    if is_secret(name) {
+        "[REDACTED]".to_string()
+    } else {
+        // Proxy URLs may carry credentials.
+        redact::redact(value).into_owned()
+    }
+}
+
+fn arg_problem(arg: &str) -> Option<&'static str> {
+    if arg.trim().is_empty() {
+        return Some("is empty");
+    }
+    if arg.contains('\0') {
+        return Some("contains a NUL byte");
+    }
+    let name = arg.split('=').next().unwrap_or(arg);
+    RESERVED_ARGS
+        .contains(&name)
+        .then_some("is set by the app and cannot be overridden")
+}
+
+fn env_problem(key: &str, value: &str) -> Option<&'static str> {
+    let mut chars = key.chars();
+    let valid_name = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_name {
+        return Some("is not a valid variable name");
+    }
+    if value.contains('\0') {
+        return Some("contains a NUL byte");
+    }
+    RESERVED_ENV
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(key))
+        .then_some("is set by the app and cannot be overridden")
+}
+
+/// Tauri command: the extra backend arguments and variables in effect, for
+/// the "non-default settings" banner.
+#[tauri::command]
+pub async fn get_backend_overrides(app: AppHandle) -> Result<ActiveOverrides, String> {
+    let (config, rejected) = LaunchOverrides::load(&app);
+    Ok(ActiveOverrides {
+        active: !config.is_empty(),
+        env: config.redacted_env(),
+        args: config.extra_args,
+        rejected,
+    })
+}
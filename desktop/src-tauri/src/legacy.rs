@@ -0,0 +1,270 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::backend;
+use crate::backups;
+use crate::logging;
+use crate::paths;
+use crate::profile;
+use crate::store;
+
+/// Identifiers earlier builds kept their app data under. Builds made before
+/// `identifier` was set in `tauri.conf.json` used Tauri's placeholder.
+const LEGACY_IDENTIFIERS: &[&str] = &["com.tauri.dev"];
+
+/// File name of the database in every layout so far.
+const DATABASE_NAME: &str = "market-analyzer.db";
+
+/// In the app data root: what was done about the data found, so it is only
+/// offered once.
+const RECORD_FILE: &str = "legacy_migration.json";
+
+/// Where migrated logs go, inside the logs folder, so they never mix with
+/// the current sessions' logs and rotation.
+const LEGACY_LOGS_DIR: &str = "legacy";
+
+/// Payload of `legacy-data-found` and result of `get_legacy_data`.
+#[derive(Clone, Debug, Serialize)]
+pub struct LegacyInstall {
+    pub database: PathBuf,
+    /// The logs folder next to it, if there is one.
+    pub logs: Option<PathBuf>,
+    pub size_bytes: u64,
+    pub modified_at: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationAction {
+    Migrated,
+    Declined,
+}
+
+/// `legacy_migration.json`, also the result of `migrate_legacy_data` and
+/// `decline_legacy_data`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LegacyMigration {
+    pub action: MigrationAction,
+    pub source: PathBuf,
+    pub at: String,
+    /// Files copied into the data dir, relative to it.
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Backup of the database the migrated one replaced, if there was one.
+    #[serde(default)]
+    pub backup: Option<String>,
+}
+
+/// Managed state: the data of an earlier build found at startup and not yet
+/// migrated or declined.
+pub struct LegacyData(Mutex<Option<LegacyInstall>>);
+
+impl LegacyData {
+    /// On the first run in the current layout, look for a database left by an
+    /// earlier build and send it as a `legacy-data-found` event. Nothing is
+    /// moved until the user confirms with `migrate_legacy_data`.
+    pub fn scan(app: &AppHandle) -> Self {
+        let found = find(app).unwrap_or_else(|e| {
+            log::warn!("Failed to look for data from earlier versions: {e}");
+            None
+        });
+        if let Some(found) = &found {
+            log::info!(
+                "Found a database from an earlier version at {}",
+                found.database.display()
+            );
+            if let Err(e) = app.emit("legacy-data-found", found) {
+                log::warn!("Failed to emit legacy-data-found: {e}");
+            }
+        }
+        Self(Mutex::new(found))
+    }
+}
+
+/// Databases earlier builds may have left, with the logs folder next to
+/// each. The first builds kept the database directly in the app data dir,
+/// and before that the backend wrote `data/` into its working directory:
+/// the folder of the executable or the home directory.
+fn candidates(app: &AppHandle, root: &Path) -> Vec<(PathBuf, Option<PathBuf>)> {
+    let mut candidates = vec![(root.join(DATABASE_NAME), None)];
+    if let Ok(platform_dir) = app.path().data_dir() {
+        for identifier in LEGACY_IDENTIFIERS {
+            let old_root = platform_dir.join(identifier);
+            let logs = Some(logging::logs_dir(&old_root));
+            candidates.push((old_root.join(DATABASE_NAME), logs.clone()));
+            candidates.push((paths::database_path(&old_root), logs));
+        }
+    }
+    let working_dirs = [
+        std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf)),
+        app.path().home_dir().ok(),
+    ];
+    for dir in working_dirs.into_iter().flatten() {
+        candidates.push((paths::database_path(&dir), None));
+    }
+    candidates
+}
+
+fn find(app: &AppHandle) -> Result<Option<LegacyInstall>, String> {
+    let root = crate::app_data_root(app)?;
+    if store::read_json::<LegacyMigration>(&root.join(RECORD_FILE))?.is_some() {
+        return Ok(None);
+    }
+    let current = paths::database_path(&profile::data_dir(&root));
+    if current.exists() {
+        return Ok(None);
+    }
+    let found = candidates(app, &root)
+        .into_iter()
+        .filter(|(database, _)| *database != current)
+        .filter_map(|(database, logs)| {
+            let metadata = std::fs::metadata(&database).ok()?;
+            if !metadata.is_file() {
+                return None;
+            }
+            let modified = metadata.modified().ok();
+            Some((
+                modified,
+                LegacyInstall {
+                    database,
+                    logs: logs.filter(|logs| logs.is_dir()),
+                    size_bytes: metadata.len(),
+                    modified_at: modified
+                        .map(|at| chrono::DateTime::<chrono::Utc>::from(at).to_rfc3339()),
+                },
+            ))
+        })
+        // The most recently used one, if several builds left data.
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, found)| found);
+    Ok(found)
+}
+
+fn record(app: &AppHandle, migration: &LegacyMigration) -> Result<(), String> {
+    store::write_json(&crate::app_data_root(app)?.join(RECORD_FILE), migration)
+}
+
+/// Copy the legacy database (with its WAL and SHM files) and logs into
+/// `data_dir`. The database the backend created meanwhile is backed up
+/// first and then replaced. The legacy files are left where they are.
+fn migrate(
+    app: &AppHandle,
+    data_dir: &Path,
+    found: &LegacyInstall,
+) -> Result<LegacyMigration, String> {
+    let (backup, names) = backups::replace_database(
+        app,
+        data_dir,
+        &found.database,
+        "Before migrating the data of an earlier version",
+    )?;
+    let mut files: Vec<String> = names
+        .into_iter()
+        .map(|name| format!("data/{name}"))
+        .collect();
+
+    // The logs are nice to have; a failed copy does not undo the migration.
+    if let Some(logs) = &found.logs {
+        let to = logging::logs_dir(data_dir).join(LEGACY_LOGS_DIR);
+        match backups::copy_item(logs, &to, data_dir, &mut Vec::new()) {
+            Ok(()) => files.push(format!("logs/{LEGACY_LOGS_DIR}/")),
+            Err(e) => log::warn!("Failed to copy the logs of the earlier version: {e}"),
+        }
+    }
+
+    let migration = LegacyMigration {
+        action: MigrationAction::Migrated,
+        source: found.database.clone(),
+        at: chrono::Utc::now().to_rfc3339(),
+        files,
+        backup: backup.map(|backup| {
+            backup
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        }),
+    };
+    record(app, &migration)?;
+    Ok(migration)
+}
+
+/// Tauri command: the data of an earlier build waiting for the user's
+/// decision, or `null`.
+#[tauri::command]
+pub async fn get_legacy_data(
+    state: State<'_, LegacyData>,
+) -> Result<Option<LegacyInstall>, String> {
+    Ok(state.0.lock().unwrap().clone())
+}
+
+/// Tauri command: bring the database and logs of an earlier build into the
+/// current data directory, then restart the backend on it.
+///
+/// The database created by this version so far is kept as a backup (see
+/// `list_backups`), and the old files stay where they were. The migration is
+/// recorded in `legacy_migration.json` so it is not offered again.
+#[tauri::command]
+pub async fn migrate_legacy_data(
+    app: AppHandle,
+    state: State<'_, LegacyData>,
+) -> Result<LegacyMigration, String> {
+    let _busy = backups::Busy::acquire()?;
+    let found = state
+        .0
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("No data from an earlier version to migrate")?;
+    backups::check_managed(&app)?;
+    let data_dir = crate::resolve_data_dir(&app)?;
+
+    log::warn!(
+        "Migrating the database of an earlier version from {}",
+        found.database.display()
+    );
+    backups::stop_backend(&app).await?;
+    let handle = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || migrate(&handle, &data_dir, &found))
+        .await
+        .map_err(|e| format!("Failed to migrate legacy data: {e}"))
+        .and_then(|result| result);
+    if result.is_ok() {
+        *state.0.lock().unwrap() = None;
+    }
+    let started = backend::start_backend(&app).await;
+    let migration = result?;
+    started?;
+    log::info!("Migrated legacy data: {:?}", migration.files);
+    Ok(migration)
+}
+
+/// Tauri command: keep the fresh data directory and stop offering the data
+/// of the earlier build.
+#[tauri::command]
+pub async fn decline_legacy_data(
+    app: AppHandle,
+    state: State<'_, LegacyData>,
+) -> Result<LegacyMigration, String> {
+    let found = state
+        .0
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or("No data from an earlier version to decline")?;
+    let migration = LegacyMigration {
+        action: MigrationAction::Declined,
+        source: found.database,
+        at: chrono::Utc::now().to_rfc3339(),
+        files: Vec::new(),
+        backup: None,
+    };
+    record(&app, &migration)?;
+    log::info!("Declined migrating the data of an earlier version");
+    Ok(migration)
+}
@@ -1,33 +1,97 @@
-use std::io::{BufRead, BufReader, Write};
-use std::process::{Child, Command as StdCommand, Stdio};
 use std::sync::Mutex;
-use std::time::Duration;
 
 use tauri::{AppHandle, Emitter, Manager, RunEvent};
 
-/// State container for the backend child process.
-/// Wrapped in Mutex so it can be safely accessed from multiple async contexts.
-struct BackendProcess(Mutex<Option<Child>>);
-
-/// Subset of the backend health check JSON response.
-#[derive(serde::Deserialize)]
-struct HealthResponse {
-    status: String,
-}
-
-/// Resolve the persistent data directory for the backend.
-///
-/// Uses Tauri's `app_data_dir()` which resolves to platform-appropriate paths:
+use backend::BackendProcess;
+
+mod announce;
+mod archive;
+mod audit;
+mod automation;
+mod backend;
+mod backend_update;
+mod backups;
+mod benchmark;
+mod cli;
+mod clipboard;
+mod config;
+mod config_watch;
+mod corruption;
+mod crypto;
+mod data_location;
+mod database;
+mod db_encryption;
+mod delta;
+mod diagnostics;
+mod export;
+mod features;
+mod health;
+mod health_history;
+mod integrity;
+mod launch;
+mod legacy;
+mod logging;
+mod logs;
+mod os_auth;
+mod packs;
+mod paths;
+mod pidfile;
+mod port;
+mod preflight;
+mod profile;
+mod process_group;
+mod plugins;
+mod redact;
+mod restart;
+mod secrets;
+mod reset;
+mod retention;
+mod settings;
+mod review;
+mod sandbox;
+mod shell_log;
+mod signature;
+mod sse;
+mod storage;
+mod store;
+mod support;
+mod tls;
+#[cfg(desktop)]
+mod tray;
+mod transfer;
+mod transport;
+mod updater;
+mod versions;
+mod workspace;
+mod ws;
+
+/// The directory holding the app's data: `--data-dir`, `TELETRAAN_DATA_DIR`
+/// or the portable folder when given (see `config`), else Tauri's
+/// `app_data_dir()`, which resolves to platform-appropriate paths:
 /// - macOS: ~/Library/Application Support/com.teletraan.app/
 /// - Linux: ~/.local/share/com.teletraan.app/
 /// - Windows: C:\Users\<User>\AppData\Roaming\com.teletraan.app\
 ///
-/// Creates the directory (and `data/` subdirectory) if they don't exist.
-fn resolve_data_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
-    let data_dir = app
+/// or the folder the data was moved to from there (see `data_location`).
+fn app_data_root(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    if let Some(dir) = config::data_dir() {
+        return Ok(dir);
+    }
+    let default_root = app
         .path()
         .app_data_dir()
         .map_err(|e| format!("Failed to resolve app data directory: {e}"))?;
+    data_location::root(&default_root)
+}
+
+/// Resolve the persistent data directory for the backend below
+/// `app_data_root`. A named profile (see `profile`) uses `profiles/<name>/`
+/// below it.
+///
+/// Creates the directory (and `data/` subdirectory) if they don't exist.
+fn resolve_data_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    let root = app_data_root(app)?;
+    let data_dir = profile::data_dir(&root);
 
     // Ensure the directory tree exists
     std::fs::create_dir_all(&data_dir)
@@ -42,220 +106,186 @@ fn resolve_data_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
     Ok(data_dir)
 }
 
-/// Spawn the Python backend as a child process from the bundled resources.
-///
-/// The window is visible immediately (configured in tauri.conf.json) so
-/// the frontend `BackendReadinessGate` can show a splash screen while the
-/// backend starts up.  A background health-check loop logs when the backend
-/// becomes healthy but does **not** block the window from appearing.
-async fn start_backend(app: &AppHandle) -> Result<(), String> {
-    log::info!("Starting Teletraan backend...");
-
-    // Resolve persistent data directory for the bundled app.
-    let data_dir = resolve_data_dir(app)?;
-
-    // Build the DATABASE_URL pointing into the app data directory.
-    let db_path = data_dir.join("data").join("market-analyzer.db");
-    let database_url = format!(
-        "sqlite+aiosqlite:///{}",
-        db_path.display()
-    );
-
-    // Locate the bundled backend binary inside the app's Resources directory.
-    // Tauri bundles files listed in `bundle.resources` into Contents/Resources/ on macOS.
-    let resource_dir = app
-        .path()
-        .resource_dir()
-        .map_err(|e| format!("Failed to resolve resource directory: {e}"))?;
-    let backend_bin = resource_dir
-        .join("resources")
-        .join("teletraan-backend")
-        .join("teletraan-backend");
-
-    log::info!("Backend binary: {}", backend_bin.display());
-    log::info!("Backend DATABASE_URL: {database_url}");
-
-    // Spawn the backend as a regular child process.
-    // Remove CLAUDECODE / CLAUDE_CODE_ENTRYPOINT so the backend's
-    // claude-agent-sdk doesn't think it's running inside Claude Code
-    // (which would cause "cannot be launched inside another session" errors).
-    let mut child = StdCommand::new(&backend_bin)
-        .args(["--host", "127.0.0.1", "--port", "8000"])
-        .current_dir(&data_dir)
-        .env("DATABASE_URL", &database_url)
-        .env_remove("CLAUDECODE")
-        .env_remove("CLAUDE_CODE_ENTRYPOINT")
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("Failed to spawn backend process: {e}"))?;
-
-    log::info!("Backend process spawned (pid: {})", child.id());
-
-    // ---- Capture stdout/stderr to backend.log and Tauri console ----
-    let log_path = data_dir.join("backend.log");
-    log::info!("Backend log file: {}", log_path.display());
-
-    // Take the stdout/stderr handles before stashing the child.
-    let child_stdout = child.stdout.take();
-    let child_stderr = child.stderr.take();
-
-    // Helper: spawn a thread that reads lines and writes to the shared log file + Tauri log.
-    fn spawn_output_reader(
-        stream: impl std::io::Read + Send + 'static,
-        log_path: std::path::PathBuf,
-        label: &'static str,
-    ) {
-        std::thread::spawn(move || {
-            let reader = BufReader::new(stream);
-            // Open log file in append mode (create if missing).
-            let mut log_file = match std::fs::OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(&log_path)
-            {
-                Ok(f) => f,
-                Err(e) => {
-                    log::error!("Failed to open backend log file {}: {e}", log_path.display());
-                    return;
-                }
-            };
-
-            for line in reader.lines() {
-                match line {
-                    Ok(text) => {
-                        // Write to Tauri console via log crate.
-                        if label == "stderr" {
-                            log::error!("[backend {label}] {text}");
-                        } else {
-                            log::info!("[backend {label}] {text}");
-                        }
-                        // Append to log file.
-                        let _ = writeln!(log_file, "[{label}] {text}");
-                    }
-                    Err(e) => {
-                        log::warn!("Error reading backend {label}: {e}");
-                        break;
-                    }
-                }
-            }
-        });
-    }
-
-    if let Some(stdout) = child_stdout {
-        spawn_output_reader(stdout, log_path.clone(), "stdout");
-    }
-    if let Some(stderr) = child_stderr {
-        spawn_output_reader(stderr, log_path, "stderr");
-    }
-
-    // Stash the child handle so we can kill it later.
-    let state = app.state::<BackendProcess>();
-    *state.0.lock().unwrap() = Some(child);
-
-    // ---- background health-check (non-blocking, for logging only) ----
-    let app_for_health = app.clone();
-    tauri::async_runtime::spawn(async move {
-        let client = match reqwest::Client::builder()
-            .timeout(Duration::from_secs(2))
-            .build()
-        {
-            Ok(c) => c,
-            Err(e) => {
-                log::error!("Failed to build HTTP client for health check: {e}");
-                return;
-            }
-        };
-
-        let health_url = "http://127.0.0.1:8000/api/v1/health";
-        let max_attempts: u32 = 300; // 300 x 500 ms = 150 s
-        let interval = Duration::from_millis(500);
-
-        for attempt in 1..=max_attempts {
-            match client.get(health_url).send().await {
-                Ok(resp) if resp.status().is_success() => {
-                    if let Ok(body) = resp.json::<HealthResponse>().await {
-                        if body.status == "healthy" {
-                            log::info!(
-                                "Backend healthy after {attempt} attempts ({:.1}s)",
-                                attempt as f64 * 0.5,
-                            );
-                            let _ = app_for_health.emit("backend-ready", ());
-                            return;
-                        }
-                    }
-                }
-                Ok(resp) => {
-                    log::debug!("Health attempt {attempt}/{max_attempts}: HTTP {}", resp.status());
-                }
-                Err(e) => {
-                    log::debug!("Health attempt {attempt}/{max_attempts}: {e}");
-                }
-            }
-            tokio::time::sleep(interval).await;
-        }
-
-        log::error!("Backend did not become healthy within 150s");
-        let _ = app_for_health.emit("backend-error", "Backend did not become healthy within 150s".to_string());
-    });
-
-    Ok(())
-}
-
-/// Kill the backend child process (called on app exit).
-fn stop_backend(app: &AppHandle) {
-    let state = app.state::<BackendProcess>();
-    let mut guard = state.0.lock().unwrap();
-    if let Some(mut child) = guard.take() {
-        log::info!("Shutting down backend process (pid: {})...", child.id());
-        match child.kill() {
-            Ok(()) => {
-                // Wait briefly for the process to fully exit
-                let _ = child.wait();
-                log::info!("Backend process terminated.");
-            }
-            Err(e) => log::error!("Failed to kill backend process: {e}"),
-        }
-    }
-}
-
-/// Tauri command exposed to the frontend: returns whether the backend is reachable.
-#[tauri::command]
-async fn check_backend_health() -> Result<bool, String> {
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(2))
-        .build()
-        .map_err(|e| format!("{e}"))?;
-
-    match client.get("http://127.0.0.1:8000/api/v1/health").send().await {
-        Ok(resp) => Ok(resp.status().is_success()),
-        Err(_) => Ok(false),
-    }
-}
-
 /// Application entry point.
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    env_logger::Builder::from_env(
-        env_logger::Env::default().default_filter_or("info"),
-    )
-    .init();
+    shell_log::init();
+    cli::init();
+    config::report();
+    profile::init(config::profile().as_deref());
 
     let app = tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .manage(BackendProcess(Mutex::new(None)))
-        .invoke_handler(tauri::generate_handler![check_backend_health])
+        .plugin(tauri_plugin_notification::init())
+        .manage(BackendProcess::new())
+        .manage(review::ReviewQueueLock(Mutex::new(())))
+        .manage(logs::LogStream::default())
+        .manage(sse::SseRelays::default())
+        .manage(ws::WsBridges::default())
+        .manage(config_watch::PendingRestart::default())
+        .manage(corruption::Corruption::default())
+        // Lets the webview reach a backend bound to a Unix domain socket.
+        .register_asynchronous_uri_scheme_protocol(
+            transport::PROXY_SCHEME,
+            |ctx, request, responder| {
+                let app = ctx.app_handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    responder.respond(transport::proxy(&app, request).await);
+                });
+            },
+        )
+        .invoke_handler(tauri::generate_handler![
+            health::check_backend_health,
+            workspace::get_workspace,
+            workspace::save_workspace,
+            workspace::delete_workspace,
+            workspace::list_workspaces,
+            review::enqueue_insights,
+            review::list_review_queue,
+            review::review_insight,
+            review::get_approved_insights,
+            automation::halt_automation,
+            automation::rearm_automation,
+            automation::get_automation_state,
+            automation::record_risk_check,
+            automation::record_daily_pnl,
+            benchmark::get_benchmarks,
+            benchmark::set_benchmarks,
+            benchmark::compare_to_benchmarks,
+            announce::announce_event,
+            announce::notify,
+            announce::get_announcement_settings,
+            announce::set_announcement_settings,
+            plugins::list_plugins,
+            restart::get_restart_state,
+            preflight::check_dependencies,
+            settings::get_all_settings,
+            settings::get_setting,
+            settings::set_setting,
+            settings::get_settings_migration,
+            config::get_config_overrides,
+            transfer::export_settings,
+            transfer::import_settings,
+            features::get_feature_flags,
+            features::set_feature_flag,
+            features::refresh_feature_flags,
+            config_watch::get_pending_restart,
+            config_watch::apply_pending_restart,
+            backend::get_backend_state,
+            backend::get_backend_url,
+            backend::get_backend_token,
+            backend::get_backend_info,
+            launch::get_backend_overrides,
+            launch::get_backend_env,
+            secrets::set_secret,
+            secrets::delete_secret,
+            secrets::list_secret_names,
+            secrets::get_secret_masked,
+            secrets::get_secret_access_log,
+            audit::get_audit_log,
+            integrity::check_backend_integrity,
+            integrity::repair_backend,
+            updater::check_for_updates,
+            updater::install_update,
+            backend_update::get_backend_versions,
+            backend_update::install_backend_update,
+            backend_update::rollback_backend_update,
+            packs::get_packs,
+            packs::update_packs,
+            packs::pin_pack,
+            packs::rollback_pack,
+            db_encryption::get_database_encryption,
+            db_encryption::set_database_encryption,
+            transport::backend_request,
+            sse::subscribe_sse,
+            sse::unsubscribe_sse,
+            ws::connect_ws,
+            ws::send_ws_message,
+            ws::disconnect_ws,
+            logs::get_backend_logs,
+            logs::subscribe_backend_logs,
+            logs::unsubscribe_backend_logs,
+            logs::search_logs,
+            logs::export_logs,
+            support::create_diagnostics_bundle,
+            logs::open_log_folder,
+            logs::set_log_level,
+            logs::log_frontend_event,
+            paths::open_data_dir,
+            paths::get_app_paths,
+            storage::get_storage_usage,
+            data_location::move_data_dir,
+            clipboard::copy_to_clipboard,
+            export::export_file,
+            export::export_table,
+            database::open_database_snapshot,
+            database::run_db_maintenance,
+            database::import_database,
+            versions::get_versions,
+            backend::start_backend_cmd,
+            backend::stop_backend_cmd,
+            reset::reset_app_data,
+            backups::list_backups,
+            backups::create_backup,
+            backups::restore_backup,
+            corruption::get_database_corruption,
+            corruption::restore_healthy_snapshot,
+            legacy::get_legacy_data,
+            legacy::migrate_legacy_data,
+            legacy::decline_legacy_data,
+            retention::run_cleanup,
+            profile::list_profiles,
+            profile::switch_profile,
+            health::get_backend_health,
+            health_history::get_health_history,
+        ])
         .setup(|app| {
             let handle = app.handle().clone();
 
+            // The log limits are settings; lines logged until then are kept.
+            app.manage(settings::SettingsStore::load(&handle));
+            if let Err(e) = resolve_data_dir(&handle).and_then(|dir| {
+                shell_log::attach(&dir, logging::LogConfig::load(&handle))
+            }) {
+                log::warn!("Shell log file unavailable: {e}");
+            }
+
+            app.manage(legacy::LegacyData::scan(&handle));
+            config_watch::spawn(handle.clone());
+            features::spawn_refresh(handle.clone());
+            updater::spawn_check(handle.clone());
+            backend_update::spawn_check(handle.clone());
+            packs::spawn_check(handle.clone());
+            app.manage(automation::Automation::load(&handle));
+            app.manage(restart::RestartTracker::load(&handle));
+
+            #[cfg(desktop)]
+            {
+                tray::init(&handle)?;
+
+                // Kill-switch hotkey works even when the window isn't focused.
+                handle.plugin(automation::hotkey_plugin())?;
+                automation::register_hotkey(&handle);
+            }
+
             // Window is visible immediately (configured in tauri.conf.json).
             // The frontend BackendReadinessGate shows a splash screen while
             // the backend starts up.
             tauri::async_runtime::spawn(async move {
-                if let Err(e) = start_backend(&handle).await {
+                if settings::current(&handle).general.check_dependencies {
+                    preflight::report(&handle).await;
+                }
+                if let Err(e) = backend::start_backend(&handle).await {
                     log::error!("Backend startup failed: {e}");
+                    announce::announce(
+                        &handle,
+                        announce::Severity::Critical,
+                        "Teletraan backend failed to start",
+                        &e,
+                    );
                     let _ = handle.emit("backend-error", e);
                 }
+                retention::startup(&handle).await;
             });
 
             Ok(())
@@ -265,7 +295,7 @@ pub fn run() {
 
     app.run(|app_handle, event| {
         if let RunEvent::Exit = event {
-            stop_backend(app_handle);
+            backend::stop_backend(app_handle);
         }
     });
 }
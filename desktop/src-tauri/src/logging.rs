@@ -0,0 +1,640 @@
+use std::borrow::Cow;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::settings;
+
+/// Prefix of the per-launch log files in `logs/`.
+const SESSION_PREFIX: &str = "backend-";
+
+/// Name of the link to the current session's log.
+const LATEST_LOG: &str = "latest.log";
+
+/// Log file of this app launch, shared by every backend it spawns for the
+/// same data dir, with the `logs/` folder it is in.
+static SESSION_LOG: Mutex<Option<(PathBuf, PathBuf)>> = Mutex::new(None);
+
+/// One backend log entry. Lines the backend prints as JSON are stored in the
+/// session log in this shape so the log viewer can query them by field.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LogRecord {
+    /// RFC 3339; the backend's own timestamp when it sent one.
+    pub timestamp: String,
+    /// Python level name: `DEBUG`, `INFO`, `WARNING`, `ERROR` or `CRITICAL`.
+    pub level: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logger: Option<String>,
+    pub message: String,
+    /// `stdout` or `stderr`.
+    pub stream: String,
+}
+
+impl LogRecord {
+    /// Parse a JSON log line such as `{"level": "INFO", "name": "uvicorn",
+    /// "message": "..."}`. Returns `None` for anything that is not a JSON
+    /// object with a message, which is then treated as plain text.
+    pub fn from_json_line(line: &str, stream: &str) -> Option<Self> {
+        let trimmed = line.trim();
+        if !trimmed.starts_with('{') {
+            return None;
+        }
+        let value: serde_json::Value = serde_json::from_str(trimmed).ok()?;
+        let object = value.as_object()?;
+        let field = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| object.get(*key))
+                .map(|v| v.as_str().map_or_else(|| v.to_string(), str::to_string))
+        };
+
+        Some(Self {
+            message: field(&["message", "msg", "event"])?,
+            level: field(&["level", "levelname", "severity"])
+                .map_or_else(|| "INFO".to_string(), |level| level.to_uppercase()),
+            logger: field(&["logger", "name"]),
+            timestamp: field(&["timestamp", "time", "asctime"])
+                .unwrap_or_else(|| chrono::Local::now().to_rfc3339()),
+            stream: stream.to_string(),
+        })
+    }
+
+    /// `log` crate level for re-emitting this record in the app's own log.
+    pub fn log_level(&self) -> log::Level {
+        level_from_name(&self.level)
+    }
+}
+
+/// `log` crate level for a Python (or `log`) level name, case-insensitively.
+pub fn level_from_name(name: &str) -> log::Level {
+    match name.to_uppercase().as_str() {
+        "TRACE" => log::Level::Trace,
+        "DEBUG" => log::Level::Debug,
+        "WARNING" | "WARN" => log::Level::Warn,
+        "ERROR" | "CRITICAL" | "FATAL" => log::Level::Error,
+        _ => log::Level::Info,
+    }
+}
+
+/// Terminal escape sequences: CSI (colours, cursor movement) and OSC
+/// (window titles, hyperlinks).
+fn escape_sequences() -> &'static regex::Regex {
+    static ESCAPES: OnceLock<regex::Regex> = OnceLock::new();
+    ESCAPES.get_or_init(|| {
+        regex::Regex::new(r"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-_]")
+            .expect("escape pattern is valid")
+    })
+}
+
+/// A raw output line as it should be stored: escape sequences removed and,
+/// for a progress bar redrawn with `\r`, only its final state kept.
+pub fn normalize(line: &str) -> Cow<'_, str> {
+    let line = match line.trim_end_matches('\r').rsplit_once('\r') {
+        Some((_, last)) => last,
+        None => line.trim_end_matches('\r'),
+    };
+    escape_sequences().replace_all(line, "")
+}
+
+/// Python level names a plain-text line may be tagged with.
+const LEVEL_NAMES: &[&str] = &["DEBUG", "INFO", "WARNING", "ERROR", "CRITICAL"];
+
+/// First line of a Python traceback.
+const TRACEBACK_START: &str = "Traceback (most recent call last):";
+
+/// Lines that join one traceback to the next in a chained exception.
+const TRACEBACK_CHAIN: &[&str] = &[
+    "During handling of the above exception",
+    "The above exception was the direct cause",
+    TRACEBACK_START,
+];
+
+/// Longest traceback kept as one record; anything past this starts a new one.
+const MAX_TRACEBACK_LINES: usize = 500;
+
+/// Turns plain-text backend output into records. Each line's level comes
+/// from its uvicorn (`INFO:     ...`) or `logging` (`... - name - INFO - ...`)
+/// prefix, and a Python traceback becomes a single `ERROR` record.
+///
+/// A traceback is only complete once the line after its exception message
+/// arrives, since a chained exception may follow.
+#[derive(Default)]
+pub struct PlainLogParser {
+    traceback: Vec<String>,
+    /// When the buffered traceback started.
+    traceback_timestamp: String,
+    /// The exception message ending the buffered traceback has been seen.
+    traceback_ended: bool,
+}
+
+impl PlainLogParser {
+    /// Feed one line, printed at `timestamp` unless it carries its own;
+    /// returns the records it completes (zero, one or two).
+    pub fn push(&mut self, line: &str, stream: &str, timestamp: &str) -> Vec<LogRecord> {
+        let mut records = Vec::new();
+        if !self.traceback.is_empty() {
+            let chained = TRACEBACK_CHAIN.iter().any(|chain| line.starts_with(chain));
+            let continues = !self.traceback_ended || chained || line.trim().is_empty();
+            if continues && self.traceback.len() < MAX_TRACEBACK_LINES {
+                if chained {
+                    self.traceback_ended = false;
+                } else if !line.is_empty() && !line.starts_with(char::is_whitespace) {
+                    // `ValueError: ...` after the indented frames.
+                    self.traceback_ended = true;
+                }
+                self.traceback.push(line.to_string());
+                return records;
+            }
+            records.extend(self.finish(stream));
+        }
+
+        if line == TRACEBACK_START {
+            self.traceback.push(line.to_string());
+            self.traceback_timestamp = timestamp.to_string();
+            self.traceback_ended = false;
+        } else {
+            records.push(parse_plain_line(line, stream, timestamp));
+        }
+        records
+    }
+
+    /// Flush a traceback still being buffered, e.g. at end of output.
+    pub fn finish(&mut self, stream: &str) -> Option<LogRecord> {
+        if self.traceback.is_empty() {
+            return None;
+        }
+        let lines = std::mem::take(&mut self.traceback);
+        self.traceback_ended = false;
+        Some(LogRecord {
+            timestamp: std::mem::take(&mut self.traceback_timestamp),
+            level: "ERROR".to_string(),
+            logger: None,
+            message: lines.join("\n").trim_end().to_string(),
+            stream: stream.to_string(),
+        })
+    }
+}
+
+/// Level, logger and timestamp of a single plain-text line. Untagged stderr
+/// output (Python warnings, library chatter) is a `WARNING`, stdout `INFO`.
+fn parse_plain_line(line: &str, stream: &str, default_timestamp: &str) -> LogRecord {
+    let now = || default_timestamp.to_string();
+    let record = |timestamp: String, level: &str, logger: Option<&str>, message: &str| LogRecord {
+        timestamp,
+        level: level.to_string(),
+        logger: logger.map(str::to_string),
+        message: message.to_string(),
+        stream: stream.to_string(),
+    };
+
+    // uvicorn: `INFO:     Started server process [1234]`
+    if let Some((level, message)) = line.split_once(':') {
+        if LEVEL_NAMES.contains(&level) {
+            return record(now(), level, Some("uvicorn"), message.trim_start());
+        }
+    }
+
+    // logging.basicConfig in main.py: `2024-06-01 09:12:33,123 - name - INFO - message`
+    let parts: Vec<&str> = line.splitn(4, " - ").collect();
+    if let [asctime, logger, level, message] = parts[..] {
+        if LEVEL_NAMES.contains(&level) {
+            let timestamp = chrono::NaiveDateTime::parse_from_str(asctime, "%Y-%m-%d %H:%M:%S,%3f")
+                .ok()
+                .and_then(|t| t.and_local_timezone(chrono::Local).single())
+                .map_or_else(now, |t| t.to_rfc3339());
+            return record(timestamp, level, Some(logger), message);
+        }
+    }
+
+    let level = if stream == "stderr" {
+        "WARNING"
+    } else {
+        "INFO"
+    };
+    record(now(), level, None, line)
+}
+
+/// Size and age limits for the backend logs, the `[logging]` section of
+/// `settings.toml`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    /// Rotate once the active file reaches this size.
+    pub max_file_mb: u64,
+    /// Rotated files kept next to the active one (`.log.1` is newest).
+    pub keep_files: usize,
+    /// Log files of earlier launches kept, newest first.
+    pub keep_sessions: usize,
+    /// Log files older than this are deleted; 0 keeps them regardless of age.
+    pub max_age_days: u64,
+    /// Gzip rotated files (`.log.1.gz`).
+    pub compress: bool,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            max_file_mb: 10,
+            keep_files: 5,
+            keep_sessions: 20,
+            max_age_days: 30,
+            compress: false,
+        }
+    }
+}
+
+impl LogConfig {
+    pub fn load(app: &AppHandle) -> Self {
+        settings::current(app).logging.files
+    }
+
+    fn max_bytes(&self) -> u64 {
+        self.max_file_mb.max(1) * 1024 * 1024
+    }
+}
+
+/// An append-only log file that rotates itself when it grows too large.
+///
+/// Shared by the stdout and stderr readers of one backend process.
+pub struct RotatingLog {
+    path: PathBuf,
+    /// `None` only while rotating: Windows cannot rename a file that is open.
+    file: Option<File>,
+    written: u64,
+    config: LogConfig,
+}
+
+impl RotatingLog {
+    pub fn open(path: &Path, config: LogConfig) -> Result<Self, String> {
+        let file = open_append(path)?;
+        let written = file.metadata().map(|m| m.len()).unwrap_or(0);
+        let mut log = Self {
+            path: path.to_path_buf(),
+            file: Some(file),
+            written,
+            config,
+        };
+        if log.written >= log.config.max_bytes() {
+            log.rotate()?;
+        }
+        prune(&log.path, &log.config);
+        Ok(log)
+    }
+
+    /// Append one line, rotating first if it would overflow the file.
+    pub fn write_line(&mut self, line: &str) {
+        let len = line.len() as u64 + 1;
+        if self.written > 0 && self.written + len > self.config.max_bytes() {
+            if let Err(e) = self.rotate() {
+                log::warn!("Failed to rotate {}: {e}", self.path.display());
+            }
+        }
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+        match writeln!(file, "{line}") {
+            Ok(()) => self.written += len,
+            Err(e) => log::warn!("Failed to write {}: {e}", self.path.display()),
+        }
+    }
+
+    /// Shift `<log>.N` to `.N+1`, dropping the oldest, and start a new file.
+    fn rotate(&mut self) -> Result<(), String> {
+        let keep = self.config.keep_files;
+        if keep == 0 {
+            self.file = Some(truncate(&self.path)?);
+            self.written = 0;
+            return Ok(());
+        }
+
+        for n in (1..=keep).rev() {
+            for from in [
+                rotated_path(&self.path, n, false),
+                rotated_path(&self.path, n, true),
+            ] {
+                if !from.exists() {
+                    continue;
+                }
+                let result = if n == keep {
+                    std::fs::remove_file(&from)
+                } else {
+                    let compressed = from.extension().is_some_and(|ext| ext == "gz");
+                    std::fs::rename(&from, rotated_path(&self.path, n + 1, compressed))
+                };
+                if let Err(e) = result {
+                    log::warn!("Failed to rotate {}: {e}", from.display());
+                }
+            }
+        }
+
+        let first = rotated_path(&self.path, 1, false);
+        self.file = None;
+        let renamed = std::fs::rename(&self.path, &first)
+            .map_err(|e| format!("Failed to rename {}: {e}", self.path.display()));
+        self.file = Some(open_append(&self.path)?);
+        renamed?;
+        self.written = 0;
+
+        if self.config.compress {
+            // Off the reader thread so the backend's pipe keeps draining.
+            std::thread::spawn(move || {
+                if let Err(e) = compress(&first) {
+                    log::warn!("Failed to compress {}: {e}", first.display());
+                }
+            });
+        }
+        prune(&self.path, &self.config);
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> Result<File, String> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {e}", path.display()))
+}
+
+fn truncate(path: &Path) -> Result<File, String> {
+    OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(path)
+        .map_err(|e| format!("Failed to truncate {}: {e}", path.display()))
+}
+
+/// `<log>.<n>`, or `<log>.<n>.gz` when `compressed`.
+fn rotated_path(path: &Path, n: usize, compressed: bool) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{n}"));
+    if compressed {
+        name.push(".gz");
+    }
+    PathBuf::from(name)
+}
+
+/// Replace `path` with a gzipped copy at `path.gz`.
+fn compress(path: &Path) -> std::io::Result<()> {
+    let gz_path = {
+        let mut name = path.as_os_str().to_owned();
+        name.push(".gz");
+        PathBuf::from(name)
+    };
+    let mut input = File::open(path)?;
+    let mut encoder =
+        flate2::write::GzEncoder::new(File::create(&gz_path)?, flate2::Compression::default());
+    std::io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+    std::fs::remove_file(path)
+}
+
+/// Folder holding the backend's session logs.
+pub fn logs_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("logs")
+}
+
+/// The session log being written, or `latest.log` if no backend was spawned
+/// by this launch.
+pub fn current_log_path(data_dir: &Path) -> PathBuf {
+    cached_session_log(data_dir).unwrap_or_else(|| logs_dir(data_dir).join(LATEST_LOG))
+}
+
+/// The session log already opened for `data_dir`, unless its folder is gone.
+fn cached_session_log(data_dir: &Path) -> Option<PathBuf> {
+    let logs_dir = logs_dir(data_dir);
+    match &*SESSION_LOG.lock().unwrap() {
+        Some((dir, path)) if *dir == logs_dir && dir.is_dir() => Some(path.clone()),
+        _ => None,
+    }
+}
+
+/// Forget the session log, so the next backend starts a new one. Called when
+/// the data dir moves or another profile takes over.
+pub fn reset_session_log() {
+    *SESSION_LOG.lock().unwrap() = None;
+}
+
+/// Rotations of `path` that exist on disk followed by `path` itself, oldest
+/// first.
+pub fn rotated_files(path: &Path, config: &LogConfig) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = (1..=config.keep_files.max(1))
+        .rev()
+        .flat_map(|n| [rotated_path(path, n, true), rotated_path(path, n, false)])
+        .filter(|p| p.exists())
+        .collect();
+    if path.exists() {
+        files.push(path.to_path_buf());
+    }
+    files
+}
+
+/// Every retained log file in `logs_dir`, oldest session first and each
+/// session's rotations before its active file.
+pub fn all_log_files(logs_dir: &Path, config: &LogConfig) -> Result<Vec<PathBuf>, String> {
+    let entries = std::fs::read_dir(logs_dir)
+        .map_err(|e| format!("Failed to list {}: {e}", logs_dir.display()))?;
+    let mut sessions: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with(SESSION_PREFIX))
+        .filter_map(|name| Some(format!("{}.log", name.split(".log").next()?)))
+        .collect();
+    sessions.sort();
+    sessions.dedup();
+    Ok(sessions
+        .iter()
+        .flat_map(|session| rotated_files(&logs_dir.join(session), config))
+        .collect())
+}
+
+/// Contents of a log file, decompressing `.gz` rotations.
+pub fn read_log_file(path: &Path) -> Result<String, String> {
+    let read = || -> std::io::Result<String> {
+        let mut text = String::new();
+        let file = File::open(path)?;
+        if path.extension().is_some_and(|ext| ext == "gz") {
+            std::io::Read::read_to_string(&mut flate2::read::GzDecoder::new(file), &mut text)?;
+        } else {
+            std::io::Read::read_to_string(&mut std::io::BufReader::new(file), &mut text)?;
+        }
+        Ok(text)
+    };
+    read().map_err(|e| format!("Failed to read {}: {e}", path.display()))
+}
+
+/// Records stored in a session log file, in file order.
+///
+/// Plain lines are stored as `<RFC 3339 time> [stream] text`; lines from
+/// before timestamps were added take the time of the record before them, or
+/// the session start for the first lines of the file.
+pub fn parse_log_file(path: &Path, text: &str) -> Vec<LogRecord> {
+    let mut timestamp = session_start(path);
+    let mut stdout = PlainLogParser::default();
+    let mut stderr = PlainLogParser::default();
+    let mut records = Vec::new();
+
+    for line in text.lines() {
+        if let Ok(record) = serde_json::from_str::<LogRecord>(line) {
+            timestamp.clone_from(&record.timestamp);
+            records.push(record);
+            continue;
+        }
+        let line = match line.split_once(' ') {
+            Some((time, rest)) if chrono::DateTime::parse_from_rfc3339(time).is_ok() => {
+                timestamp = time.to_string();
+                rest
+            }
+            _ => line,
+        };
+        let (stream, text) = if let Some(text) = line.strip_prefix("[stderr] ") {
+            ("stderr", text)
+        } else {
+            ("stdout", line.strip_prefix("[stdout] ").unwrap_or(line))
+        };
+        let parser = if stream == "stderr" {
+            &mut stderr
+        } else {
+            &mut stdout
+        };
+        for record in parser.push(text, stream, &timestamp) {
+            timestamp.clone_from(&record.timestamp);
+            records.push(record);
+        }
+    }
+    records.extend(stdout.finish("stdout"));
+    records.extend(stderr.finish("stderr"));
+    records
+}
+
+/// Launch time encoded in a session log's name, else its modification time.
+fn session_start(path: &Path) -> String {
+    let from_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_prefix(SESSION_PREFIX))
+        .and_then(|name| name.get(.."2024-06-01T09-12".len()))
+        .and_then(|stamp| chrono::NaiveDateTime::parse_from_str(stamp, "%Y-%m-%dT%H-%M").ok())
+        .and_then(|t| t.and_local_timezone(chrono::Local).single());
+    let from_mtime = || {
+        std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .map(chrono::DateTime::<chrono::Local>::from)
+            .unwrap_or_else(|_| chrono::Local::now())
+    };
+    from_name.unwrap_or_else(from_mtime).to_rfc3339()
+}
+
+/// Log file for this launch, `logs/backend-<local time>.log`, created (with a
+/// `logs/latest.log` link to it) on first use.
+pub fn session_log_path(data_dir: &Path) -> Result<PathBuf, String> {
+    if let Some(path) = cached_session_log(data_dir) {
+        return Ok(path);
+    }
+    let logs_dir = logs_dir(data_dir);
+    std::fs::create_dir_all(&logs_dir)
+        .map_err(|e| format!("Failed to create {}: {e}", logs_dir.display()))?;
+    let name = format!(
+        "{SESSION_PREFIX}{}.log",
+        chrono::Local::now().format("%Y-%m-%dT%H-%M")
+    );
+    let path = logs_dir.join(name);
+    open_append(&path)?;
+    *SESSION_LOG.lock().unwrap() = Some((logs_dir.clone(), path.clone()));
+    link_latest(&logs_dir, &path);
+    Ok(path)
+}
+
+/// Point `latest.log` at `session`. Windows needs a privilege for symlinks,
+/// so a hard link (which goes stale after the first rotation) is the fallback.
+fn link_latest(logs_dir: &Path, session: &Path) {
+    let latest = logs_dir.join(LATEST_LOG);
+    match std::fs::remove_file(&latest) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => log::warn!("Failed to remove {}: {e}", latest.display()),
+    }
+    #[cfg(unix)]
+    let linked = session
+        .file_name()
+        .map_or(Ok(()), |name| std::os::unix::fs::symlink(name, &latest));
+    #[cfg(windows)]
+    let linked = std::os::windows::fs::symlink_file(session, &latest)
+        .or_else(|_| std::fs::hard_link(session, &latest));
+    #[cfg(not(any(unix, windows)))]
+    let linked = std::fs::hard_link(session, &latest);
+    if let Err(e) = linked {
+        log::warn!("Failed to link {}: {e}", latest.display());
+    }
+}
+
+/// Delete session logs beyond `keep_sessions` launches or older than
+/// `max_age_days`, never touching the active file `current`, this launch's
+/// session log, or their rotations. Returns the number of files removed and
+/// their size.
+fn prune(current: &Path, config: &LogConfig) -> (usize, u64) {
+    let mut removed = (0, 0);
+    let Some(logs_dir) = current.parent() else {
+        return removed;
+    };
+    let session = SESSION_LOG.lock().unwrap().clone().map(|(_, path)| path);
+    let active: Vec<&str> = [Some(current), session.as_deref()]
+        .into_iter()
+        .flatten()
+        .filter_map(|path| path.file_name()?.to_str())
+        .collect();
+    let entries = match std::fs::read_dir(logs_dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::warn!("Failed to list {}: {e}", logs_dir.display());
+            return removed;
+        }
+    };
+
+    let mut files: Vec<(String, PathBuf)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| Some((entry.file_name().into_string().ok()?, entry.path())))
+        .filter(|(name, _)| {
+            name.starts_with(SESSION_PREFIX) && !active.iter().any(|a| name.starts_with(a))
+        })
+        .collect();
+    // Timestamped names sort chronologically; newest first.
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let max_age = Duration::from_secs(config.max_age_days * 24 * 60 * 60);
+    let now = SystemTime::now();
+    let mut sessions: Vec<&str> = Vec::new();
+    for (name, path) in &files {
+        let session = name.split(".log").next().unwrap_or(name);
+        if !sessions.contains(&session) {
+            sessions.push(session);
+        }
+        let expired = config.max_age_days > 0
+            && std::fs::metadata(path)
+                .and_then(|m| m.modified())
+                .is_ok_and(|modified| now.duration_since(modified).unwrap_or_default() > max_age);
+        if sessions.len() > config.keep_sessions || expired {
+            let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+            match std::fs::remove_file(path) {
+                Ok(()) => {
+                    removed.0 += 1;
+                    removed.1 += size;
+                }
+                Err(e) => log::warn!("Failed to remove old log {}: {e}", path.display()),
+            }
+        }
+    }
+    removed
+}
+
+/// Apply `keep_sessions` and `max_age_days` to the logs in `data_dir` now,
+/// rather than at the next backend start. Returns the number of files
+/// removed and their size.
+pub fn prune_logs(data_dir: &Path, config: &LogConfig) -> (usize, u64) {
+    prune(&current_log_path(data_dir), config)
+}
@@ -0,0 +1,447 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::archive::ZipWriter;
+use crate::backend;
+use crate::logging::{self, LogConfig, LogRecord};
+use crate::paths;
+use crate::redact;
+use crate::settings;
+use crate::shell_log;
+
+/// Lines returned by `get_backend_logs` when the caller gives no limit.
+const DEFAULT_TAIL: usize = 500;
+
+/// Upper bound on `get_backend_logs`, to keep the IPC payload manageable.
+const MAX_TAIL: usize = 10_000;
+
+/// Records buffered for `backend-log` subscribers; older ones are dropped
+/// (and counted) when the webview falls behind.
+const STREAM_QUEUE_CAPACITY: usize = 2_000;
+
+/// Most records sent in one `backend-log` event.
+const STREAM_BATCH_SIZE: usize = 200;
+
+/// How often buffered records are flushed to the webview.
+const STREAM_FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Managed state: live backend log subscriptions and their pending records.
+#[derive(Default)]
+pub struct LogStream(Mutex<StreamState>);
+
+#[derive(Default)]
+struct StreamState {
+    /// Minimum level per subscription id.
+    subscribers: HashMap<u64, log::Level>,
+    next_id: u64,
+    queue: VecDeque<LogRecord>,
+    /// Records discarded since the last event because the queue was full.
+    dropped: u64,
+    flushing: bool,
+}
+
+impl StreamState {
+    /// Most verbose level any subscriber asked for.
+    fn min_level(&self) -> Option<log::Level> {
+        self.subscribers.values().copied().max()
+    }
+}
+
+/// Payload of the `backend-log` event.
+#[derive(Clone, Serialize)]
+struct LogBatch {
+    records: Vec<LogRecord>,
+    /// Records skipped before this batch because the subscriber fell behind.
+    dropped: u64,
+}
+
+/// Queue a record for live subscribers. Cheap no-op when nobody listens, so
+/// the output readers can call it for every line.
+pub fn publish(app: &AppHandle, record: &LogRecord) {
+    let Some(stream) = app.try_state::<LogStream>() else {
+        return;
+    };
+    let mut state = stream.0.lock().unwrap();
+    if state.min_level().is_none_or(|min| record.log_level() > min) {
+        return;
+    }
+    if state.queue.len() >= STREAM_QUEUE_CAPACITY {
+        state.queue.pop_front();
+        state.dropped += 1;
+    }
+    state.queue.push_back(record.clone());
+}
+
+/// Tauri command: start receiving `backend-log` events with records at or
+/// above `level` (default `INFO`). Returns the id to unsubscribe with.
+///
+/// Records are sent in batches every 100 ms; when the webview cannot keep up
+/// the oldest pending records are dropped and the next batch reports how many.
+#[tauri::command]
+pub async fn subscribe_backend_logs(
+    app: AppHandle,
+    stream: State<'_, LogStream>,
+    level: Option<String>,
+) -> Result<u64, String> {
+    let level = level
+        .as_deref()
+        .map_or(log::Level::Info, logging::level_from_name);
+    let mut state = stream.0.lock().unwrap();
+    state.next_id += 1;
+    let id = state.next_id;
+    state.subscribers.insert(id, level);
+    if !state.flushing {
+        state.flushing = true;
+        tauri::async_runtime::spawn(flush_loop(app));
+    }
+    Ok(id)
+}
+
+/// Tauri command: stop a subscription made by `subscribe_backend_logs`.
+#[tauri::command]
+pub async fn unsubscribe_backend_logs(stream: State<'_, LogStream>, id: u64) -> Result<(), String> {
+    let mut state = stream.0.lock().unwrap();
+    if state.subscribers.remove(&id).is_none() {
+        return Err(format!("No log subscription {id}"));
+    }
+    if state.subscribers.is_empty() {
+        state.queue.clear();
+        state.dropped = 0;
+    }
+    Ok(())
+}
+
+/// Emit queued records until the last subscriber leaves.
+async fn flush_loop(app: AppHandle) {
+    loop {
+        tokio::time::sleep(STREAM_FLUSH_INTERVAL).await;
+        let batch = {
+            let stream = app.state::<LogStream>();
+            let mut state = stream.0.lock().unwrap();
+            if state.subscribers.is_empty() {
+                state.flushing = false;
+                return;
+            }
+            if state.queue.is_empty() {
+                continue;
+            }
+            let take = state.queue.len().min(STREAM_BATCH_SIZE);
+            LogBatch {
+                records: state.queue.drain(..take).collect(),
+                dropped: std::mem::take(&mut state.dropped),
+            }
+        };
+        if let Err(e) = app.emit("backend-log", batch) {
+            log::warn!("Failed to emit backend-log: {e}");
+        }
+    }
+}
+
+/// Tauri command: the last `lines` records of the current session's backend
+/// log (including its rotations), oldest first.
+///
+/// `level` is a minimum severity (`WARNING` returns warnings and errors),
+/// `since`/`until` are RFC 3339 bounds, and `contains` is a case-insensitive
+/// substring of the message or logger.
+#[tauri::command]
+pub async fn get_backend_logs(
+    app: AppHandle,
+    lines: Option<usize>,
+    level: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    contains: Option<String>,
+) -> Result<Vec<LogRecord>, String> {
+    let filter = LogFilter::new(level, since, until, contains)?;
+    let data_dir = crate::resolve_data_dir(&app)?;
+    let config = LogConfig::load(&app);
+    let limit = lines.unwrap_or(DEFAULT_TAIL).min(MAX_TAIL);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let current = logging::current_log_path(&data_dir);
+        let mut records = Vec::new();
+        for path in logging::rotated_files(&current, &config) {
+            let text = logging::read_log_file(&path)?;
+            records.extend(
+                logging::parse_log_file(&path, &text)
+                    .into_iter()
+                    .filter(|record| filter.matches(record)),
+            );
+        }
+        let skip = records.len().saturating_sub(limit);
+        records.drain(..skip);
+        Ok(records)
+    })
+    .await
+    .map_err(|e| format!("Failed to read backend logs: {e}"))?
+}
+
+/// Matches returned by `search_logs` when the caller gives no limit.
+const DEFAULT_SEARCH_LIMIT: usize = 1_000;
+
+/// A `search_logs` hit: the record and the file it came from.
+#[derive(Clone, Serialize)]
+pub struct LogMatch {
+    /// File name within the logs folder, e.g. `backend-2024-06-01T09-12.log.1`.
+    pub file: String,
+    #[serde(flatten)]
+    pub record: LogRecord,
+}
+
+/// Tauri command: search every retained log file (all sessions, rotations
+/// included) for records whose message or logger matches `query`, oldest
+/// first, stopping after `limit` matches.
+///
+/// `query` is a substring unless `regex` is set; matching ignores case unless
+/// `case_sensitive` is set.
+#[tauri::command]
+pub async fn search_logs(
+    app: AppHandle,
+    query: String,
+    regex: Option<bool>,
+    case_sensitive: Option<bool>,
+    limit: Option<usize>,
+) -> Result<Vec<LogMatch>, String> {
+    let pattern = if regex.unwrap_or(false) {
+        query
+    } else {
+        regex::escape(&query)
+    };
+    let matcher = regex::RegexBuilder::new(&pattern)
+        .case_insensitive(!case_sensitive.unwrap_or(false))
+        .build()
+        .map_err(|e| format!("Invalid search pattern: {e}"))?;
+    let logs_dir = logging::logs_dir(&crate::resolve_data_dir(&app)?);
+    let config = LogConfig::load(&app);
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut matches = Vec::new();
+        for path in logging::all_log_files(&logs_dir, &config)? {
+            let file = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let text = match logging::read_log_file(&path) {
+                Ok(text) => text,
+                Err(e) => {
+                    // Pruned or compressed between listing and reading.
+                    log::warn!("{e}");
+                    continue;
+                }
+            };
+            for record in logging::parse_log_file(&path, &text) {
+                let hit = matcher.is_match(&record.message)
+                    || record
+                        .logger
+                        .as_deref()
+                        .is_some_and(|l| matcher.is_match(l));
+                if hit {
+                    matches.push(LogMatch {
+                        file: file.clone(),
+                        record,
+                    });
+                    if matches.len() >= limit {
+                        return Ok(matches);
+                    }
+                }
+            }
+        }
+        Ok(matches)
+    })
+    .await
+    .map_err(|e| format!("Failed to search logs: {e}"))?
+}
+
+/// Criteria shared by the log commands.
+#[derive(Clone, Debug, Default)]
+pub struct LogFilter {
+    level: Option<log::Level>,
+    since: Option<chrono::DateTime<chrono::FixedOffset>>,
+    until: Option<chrono::DateTime<chrono::FixedOffset>>,
+    contains: Option<String>,
+}
+
+impl LogFilter {
+    pub fn new(
+        level: Option<String>,
+        since: Option<String>,
+        until: Option<String>,
+        contains: Option<String>,
+    ) -> Result<Self, String> {
+        let parse_time = |value: Option<String>| {
+            value
+                .map(|v| {
+                    chrono::DateTime::parse_from_rfc3339(&v)
+                        .map_err(|e| format!("Invalid time {v:?}: {e}"))
+                })
+                .transpose()
+        };
+        Ok(Self {
+            level: level.as_deref().map(logging::level_from_name),
+            since: parse_time(since)?,
+            until: parse_time(until)?,
+            contains: contains.filter(|c| !c.is_empty()).map(|c| c.to_lowercase()),
+        })
+    }
+
+    pub fn matches(&self, record: &LogRecord) -> bool {
+        if self.level.is_some_and(|min| record.log_level() > min) {
+            return false;
+        }
+        if self.since.is_some() || self.until.is_some() {
+            // Records with an unreadable timestamp are kept rather than hidden.
+            if let Ok(time) = chrono::DateTime::parse_from_rfc3339(&record.timestamp) {
+                if self.since.is_some_and(|since| time < since)
+                    || self.until.is_some_and(|until| time > until)
+                {
+                    return false;
+                }
+            }
+        }
+        if let Some(needle) = &self.contains {
+            let in_logger = record
+                .logger
+                .as_ref()
+                .is_some_and(|logger| logger.to_lowercase().contains(needle));
+            if !in_logger && !record.message.to_lowercase().contains(needle) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Tauri command: zip every retained backend log (all sessions, rotations
+/// included) into `destination`, or into the Downloads folder when no path is
+/// given, and return the archive's path.
+///
+/// The frontend picks `destination` with its own save dialog; the shell has no
+/// dialog plugin.
+#[tauri::command]
+pub async fn export_logs(app: AppHandle, destination: Option<String>) -> Result<String, String> {
+    let logs_dir = logging::logs_dir(&crate::resolve_data_dir(&app)?);
+    let destination = match destination {
+        Some(path) => PathBuf::from(path),
+        None => settings::export_dir(&app)?.join(format!(
+            "teletraan-logs-{}.zip",
+            chrono::Local::now().format("%Y-%m-%dT%H-%M-%S")
+        )),
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let entries = std::fs::read_dir(&logs_dir)
+            .map_err(|e| format!("Failed to list {}: {e}", logs_dir.display()))?;
+        let mut files: Vec<(String, PathBuf)> = entries
+            .filter_map(Result::ok)
+            // `latest.log` is a link to one of the session files.
+            .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+            .filter_map(|entry| Some((entry.file_name().into_string().ok()?, entry.path())))
+            .filter(|(name, _)| name != "latest.log")
+            .collect();
+        files.sort();
+
+        let mut zip = ZipWriter::create(&destination)?;
+        for (name, path) in &files {
+            zip.add_file(&format!("logs/{name}"), path)?;
+        }
+        zip.finish()?;
+        log::info!(
+            "Exported {} log files to {}",
+            files.len(),
+            destination.display()
+        );
+        Ok(destination.display().to_string())
+    })
+    .await
+    .map_err(|e| format!("Failed to export logs: {e}"))?
+}
+
+/// Tauri command: show the logs folder in Finder/Explorer, with the current
+/// session's log selected.
+#[tauri::command]
+pub async fn open_log_folder(app: AppHandle) -> Result<(), String> {
+    let data_dir = crate::resolve_data_dir(&app)?;
+    let logs_dir = logging::logs_dir(&data_dir);
+    std::fs::create_dir_all(&logs_dir)
+        .map_err(|e| format!("Failed to create {}: {e}", logs_dir.display()))?;
+    paths::reveal(&app, &logging::current_log_path(&data_dir), &logs_dir)
+}
+
+/// Tauri command: change log verbosity at runtime, e.g. to `debug` while
+/// reproducing an issue. Takes effect immediately and lasts until the app quits.
+///
+/// `target` is `shell` (the default), `backend`, `all`, or a shell module path
+/// such as `teletraan_desktop_lib::health`. `level` is `trace`, `debug`,
+/// `info`, `warn`, `error` or `off`.
+#[tauri::command]
+pub async fn set_log_level(
+    app: AppHandle,
+    level: String,
+    target: Option<String>,
+) -> Result<(), String> {
+    let filter: log::LevelFilter = match level.to_lowercase().as_str() {
+        "warning" => log::LevelFilter::Warn,
+        "critical" => log::LevelFilter::Error,
+        other => other
+            .parse()
+            .map_err(|_| format!("Unknown log level: {level}"))?,
+    };
+    let target = target.unwrap_or_else(|| "shell".to_string());
+    log::info!("Log level of {target} set to {filter}");
+
+    if matches!(target.as_str(), "shell" | "all") {
+        shell_log::set_level(None, filter);
+    } else if target != "backend" {
+        shell_log::set_level(Some(&target), filter);
+    }
+    if matches!(target.as_str(), "backend" | "all") {
+        // Python has no TRACE or OFF; use its nearest levels.
+        let python_level = match filter {
+            log::LevelFilter::Trace | log::LevelFilter::Debug => "DEBUG",
+            log::LevelFilter::Info => "INFO",
+            log::LevelFilter::Warn => "WARNING",
+            log::LevelFilter::Error => "ERROR",
+            log::LevelFilter::Off => "CRITICAL",
+        };
+        backend::set_log_level(&app, python_level).await?;
+    }
+    Ok(())
+}
+
+/// Longest frontend message written to the log.
+const MAX_FRONTEND_MESSAGE: usize = 8_000;
+
+/// Tauri command: write a webview console error or warning to the shell's
+/// log (console and `shell.log`) under the `frontend` target, so the UI's
+/// problems sit in time order next to the shell events around them.
+///
+/// `source` says where it was caught, e.g. `console`, `window` or `promise`.
+#[tauri::command]
+pub async fn log_frontend_event(
+    level: String,
+    message: String,
+    source: Option<String>,
+) -> Result<(), String> {
+    let mut message = redact::redact(&message).into_owned();
+    if message.len() > MAX_FRONTEND_MESSAGE {
+        let mut end = MAX_FRONTEND_MESSAGE;
+        while !message.is_char_boundary(end) {
+            end -= 1;
+        }
+        message.truncate(end);
+        message.push_str(" [truncated]");
+    }
+    log::log!(
+        target: shell_log::FRONTEND_TARGET,
+        logging::level_from_name(&level),
+        "[{}] {message}",
+        source.as_deref().unwrap_or("frontend")
+    );
+    Ok(())
+}
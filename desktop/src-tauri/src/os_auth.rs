@@ -0,0 +1,371 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::AppHandle;
+
+use crate::audit::{self, AuditEvent};
+use crate::settings;
+
+/// How long a successful prompt covers further gated commands, so revealing
+/// several keys in a row asks once.
+const GRACE_PERIOD: Duration = Duration::from_secs(5 * 60);
+
+static LAST_VERIFIED: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// What asking the OS to authenticate the user came to.
+enum Verification {
+    Verified,
+    /// The user cancelled or failed the prompt.
+    Denied(String),
+    /// The platform has no way to ask, e.g. no password is set.
+    Unavailable(String),
+}
+
+/// Ask the user to confirm it is them, with Touch ID or the account password
+/// on macOS, Windows Hello or the account password on Windows, and polkit on
+/// Linux, before `action` (e.g. `show ANTHROPIC_API_KEY`) goes ahead.
+///
+/// Passes without a prompt within `GRACE_PERIOD` of the last success, or
+/// when `general.require_os_auth` is off. Fails when the platform cannot
+/// ask, since the setting promises a prompt. Every prompt is recorded in the
+/// audit log.
+pub async fn require(app: &AppHandle, action: &str) -> Result<(), String> {
+    if !settings::current(app).general.require_os_auth {
+        return Ok(());
+    }
+    if LAST_VERIFIED
+        .lock()
+        .unwrap()
+        .is_some_and(|at| at.elapsed() < GRACE_PERIOD)
+    {
+        return Ok(());
+    }
+    let reason = format!("Teletraan needs to confirm it's you to {action}.");
+    let verification = tauri::async_runtime::spawn_blocking(move || verify(&reason))
+        .await
+        .map_err(|e| format!("Failed to ask for authentication: {e}"))?;
+    match verification {
+        Verification::Verified => {
+            *LAST_VERIFIED.lock().unwrap() = Some(Instant::now());
+            audit::record(app, AuditEvent::OsAuth, true, action);
+            Ok(())
+        }
+        Verification::Denied(why) => {
+            log::warn!("Authentication to {action} failed: {why}");
+            audit::record(app, AuditEvent::OsAuth, false, format!("{action}: {why}"));
+            Err(format!("Authentication failed: {why}"))
+        }
+        Verification::Unavailable(why) => {
+            log::warn!("Cannot ask for authentication to {action}: {why}");
+            audit::record(
+                app,
+                AuditEvent::OsAuth,
+                false,
+                format!("{action}: not available ({why})"),
+            );
+            Err(format!(
+                "Authentication is not available: {why}. Set general.require_os_auth to false to go ahead without it."
+            ))
+        }
+    }
+}
+
+/// LocalAuthentication with `LAPolicyDeviceOwnerAuthentication`: Touch ID
+/// or a paired Apple Watch, falling back to the account password.
+#[cfg(target_os = "macos")]
+fn verify(reason: &str) -> Verification {
+    use block2::RcBlock;
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyObject, Bool};
+    use objc2::{class, msg_send};
+    use objc2_foundation::NSString;
+
+    #[link(name = "LocalAuthentication", kind = "framework")]
+    extern "C" {}
+
+    const POLICY_DEVICE_OWNER_AUTHENTICATION: isize = 2;
+    const ERROR_USER_CANCEL: isize = -2;
+    const ERROR_SYSTEM_CANCEL: isize = -4;
+    const ERROR_APP_CANCEL: isize = -9;
+
+    /// The NSError's code and description.
+    unsafe fn describe(error: *mut AnyObject) -> (isize, String) {
+        if error.is_null() {
+            return (0, "unknown error".to_string());
+        }
+        let code: isize = msg_send![error, code];
+        let description: Option<Retained<NSString>> = msg_send![error, localizedDescription];
+        let description = description.map(|d| d.to_string()).unwrap_or_default();
+        (code, description)
+    }
+
+    // SAFETY: messages to LAContext and NSError as documented; the reply
+    // block owns its sender and is kept alive by LocalAuthentication until
+    // it has run.
+    unsafe {
+        let context: Retained<AnyObject> = msg_send![class!(LAContext), new];
+        let available: Bool = msg_send![
+            &*context,
+            canEvaluatePolicy: POLICY_DEVICE_OWNER_AUTHENTICATION,
+            error: std::ptr::null_mut::<*mut AnyObject>()
+        ];
+        if !available.as_bool() {
+            return Verification::Unavailable(
+                "no Touch ID or account password is set up".to_string(),
+            );
+        }
+        let (tx, rx) = std::sync::mpsc::channel();
+        let reply = RcBlock::new(move |success: Bool, error: *mut AnyObject| {
+            let outcome = if success.as_bool() {
+                Ok(())
+            } else {
+                Err(describe(error))
+            };
+            let _ = tx.send(outcome);
+        });
+        let reason = NSString::from_str(reason);
+        let _: () = msg_send![
+            &*context,
+            evaluatePolicy: POLICY_DEVICE_OWNER_AUTHENTICATION,
+            localizedReason: &*reason,
+            reply: &*reply
+        ];
+        match rx.recv() {
+            Ok(Ok(())) => Verification::Verified,
+            Ok(Err((ERROR_USER_CANCEL | ERROR_SYSTEM_CANCEL | ERROR_APP_CANCEL, _))) => {
+                Verification::Denied("cancelled".to_string())
+            }
+            Ok(Err((_, description))) => Verification::Denied(description),
+            Err(_) => Verification::Denied("no reply from LocalAuthentication".to_string()),
+        }
+    }
+}
+
+/// Windows Hello (face, fingerprint or PIN) where it is set up, otherwise
+/// the Windows sign-in prompt for the current account's password.
+#[cfg(windows)]
+fn verify(reason: &str) -> Verification {
+    use windows::core::HSTRING;
+    use windows::Security::Credentials::UI::{
+        UserConsentVerificationResult, UserConsentVerifier, UserConsentVerifierAvailability,
+    };
+
+    let available = UserConsentVerifier::CheckAvailabilityAsync().and_then(|op| op.get());
+    if available != Ok(UserConsentVerifierAvailability::Available) {
+        log::info!("Windows Hello is not available ({available:?}); asking for the password");
+        return verify_password(reason);
+    }
+    match UserConsentVerifier::RequestVerificationAsync(&HSTRING::from(reason))
+        .and_then(|op| op.get())
+    {
+        Ok(UserConsentVerificationResult::Verified) => Verification::Verified,
+        Ok(UserConsentVerificationResult::Canceled) => {
+            Verification::Denied("cancelled".to_string())
+        }
+        Ok(result) => Verification::Denied(format!("Windows Hello returned {result:?}")),
+        Err(e) => Verification::Denied(format!("Windows Hello failed: {e}")),
+    }
+}
+
+/// The credential prompt for the signed-in user, checked with `LogonUserW`.
+#[cfg(windows)]
+fn verify_password(reason: &str) -> Verification {
+    use windows_sys::Win32::Foundation::{CloseHandle, ERROR_CANCELLED, HANDLE};
+    use windows_sys::Win32::Security::Credentials::{
+        CredUIParseUserNameW, CredUIPromptForWindowsCredentialsW, CredUnPackAuthenticationBufferW,
+        CREDUIWIN_ENUMERATE_CURRENT_USER, CREDUI_INFOW, CRED_PACK_PROTECTED_CREDENTIALS,
+    };
+    use windows_sys::Win32::Security::{
+        LogonUserW, LOGON32_LOGON_INTERACTIVE, LOGON32_PROVIDER_DEFAULT,
+    };
+    use windows_sys::Win32::System::Com::CoTaskMemFree;
+
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+    fn wipe(buffer: &mut [u16]) {
+        for c in buffer.iter_mut() {
+            // SAFETY: `c` is a valid, aligned element of `buffer`.
+            unsafe { std::ptr::write_volatile(c, 0) };
+        }
+    }
+
+    let message = wide(reason);
+    let caption = wide("Teletraan");
+    let info = CREDUI_INFOW {
+        cbSize: std::mem::size_of::<CREDUI_INFOW>() as u32,
+        hwndParent: std::ptr::null_mut(),
+        pszMessageText: message.as_ptr(),
+        pszCaptionText: caption.as_ptr(),
+        hbmBanner: std::ptr::null_mut(),
+    };
+    let mut package = 0u32;
+    let mut buffer: *mut std::ffi::c_void = std::ptr::null_mut();
+    let mut size = 0u32;
+    // SAFETY: every pointer refers to a live local; the returned buffer is
+    // wiped and freed with CoTaskMemFree, and the password copy is wiped.
+    unsafe {
+        let status = CredUIPromptForWindowsCredentialsW(
+            &info,
+            0,
+            &mut package,
+            std::ptr::null(),
+            0,
+            &mut buffer,
+            &mut size,
+            std::ptr::null_mut(),
+            CREDUIWIN_ENUMERATE_CURRENT_USER,
+        );
+        if status == ERROR_CANCELLED {
+            return Verification::Denied("cancelled".to_string());
+        }
+        if status != 0 {
+            return Verification::Unavailable(format!(
+                "the credential prompt failed: {}",
+                std::io::Error::from_raw_os_error(status as i32)
+            ));
+        }
+
+        let mut user = [0u16; 514];
+        let mut domain = [0u16; 338];
+        let mut password = [0u16; 257];
+        let (mut user_len, mut domain_len, mut password_len) = (
+            user.len() as u32,
+            domain.len() as u32,
+            password.len() as u32,
+        );
+        let unpacked = CredUnPackAuthenticationBufferW(
+            CRED_PACK_PROTECTED_CREDENTIALS,
+            buffer,
+            size,
+            user.as_mut_ptr(),
+            &mut user_len,
+            domain.as_mut_ptr(),
+            &mut domain_len,
+            password.as_mut_ptr(),
+            &mut password_len,
+        ) != 0;
+        std::ptr::write_bytes(buffer.cast::<u8>(), 0, size as usize);
+        CoTaskMemFree(buffer);
+        if !unpacked {
+            wipe(&mut password);
+            return Verification::Denied(format!(
+                "Failed to read the credentials: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+
+        // `DOMAIN\user` or `user@domain` into its parts.
+        let mut name = [0u16; 514];
+        let mut name_domain = [0u16; 338];
+        let parsed = CredUIParseUserNameW(
+            user.as_ptr(),
+            name.as_mut_ptr(),
+            name.len() as u32,
+            name_domain.as_mut_ptr(),
+            name_domain.len() as u32,
+        ) == 0;
+        let (name, name_domain) = if parsed {
+            (name.as_ptr(), name_domain.as_ptr())
+        } else {
+            (user.as_ptr(), std::ptr::null())
+        };
+        let mut token: HANDLE = std::ptr::null_mut();
+        let verified = LogonUserW(
+            name,
+            name_domain,
+            password.as_ptr(),
+            LOGON32_LOGON_INTERACTIVE,
+            LOGON32_PROVIDER_DEFAULT,
+            &mut token,
+        ) != 0;
+        let error = std::io::Error::last_os_error();
+        wipe(&mut password);
+        if verified {
+            CloseHandle(token);
+            Verification::Verified
+        } else {
+            Verification::Denied(format!("the password was not accepted: {error}"))
+        }
+    }
+}
+
+/// The polkit action the deb and rpm packages install, which asks the user
+/// for their own password (`auth_self`).
+#[cfg(all(unix, not(target_os = "macos")))]
+const POLKIT_ACTION: &str = "com.teletraan.app.authenticate";
+
+/// Where polkit looks for `POLKIT_ACTION`, from `polkit/com.teletraan.app.policy`.
+#[cfg(all(unix, not(target_os = "macos")))]
+const POLKIT_POLICY: &str = "/usr/share/polkit-1/actions/com.teletraan.app.policy";
+
+/// A process's start time, in clock ticks since boot, from the contents of
+/// its `/proc/<pid>/stat`. It is the 22nd field; the command name before it
+/// is in parentheses and may itself hold spaces or parentheses.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn start_time(stat: &str) -> Option<u64> {
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(19)?.parse().ok()
+}
+
+/// polkit, whose agent asks for the user's password for `POLKIT_ACTION`.
+/// The process is named by pid and start time, so a process that reuses the
+/// pid cannot take over the authorization.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn verify(_reason: &str) -> Verification {
+    if !std::path::Path::new(POLKIT_POLICY).exists() {
+        return Verification::Unavailable(format!(
+            "the polkit action {POLKIT_ACTION} is not installed"
+        ));
+    }
+    let pid = std::process::id();
+    let started = match std::fs::read_to_string("/proc/self/stat") {
+        Ok(stat) => start_time(&stat),
+        Err(e) => return Verification::Unavailable(format!("Failed to read /proc/self/stat: {e}")),
+    };
+    let Some(started) = started else {
+        return Verification::Unavailable("unexpected /proc/self/stat".to_string());
+    };
+    let status = std::process::Command::new("pkcheck")
+        .args([
+            "--action-id",
+            POLKIT_ACTION,
+            "--process",
+            &format!("{pid},{started}"),
+            "--allow-user-interaction",
+        ])
+        .status();
+    match status {
+        Ok(status) if status.success() => Verification::Verified,
+        // pkcheck exits with 2 when the prompt was dismissed.
+        Ok(status) if status.code() == Some(2) => Verification::Denied("cancelled".to_string()),
+        Ok(status) => Verification::Denied(format!("polkit did not authorize it ({status})")),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            Verification::Unavailable("pkcheck is not installed".to_string())
+        }
+        Err(e) => Verification::Unavailable(format!("Failed to run pkcheck: {e}")),
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn verify(_reason: &str) -> Verification {
+    Verification::Unavailable("not supported on this platform".to_string())
+}
+
+#[cfg(all(test, unix, not(target_os = "macos")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_start_time_after_the_command_name() {
+        let stat = "4242 (tele traan) (x)) S 1 4242 4242 0 -1 4194560 1 0 0 0 3 1 0 0 20 0 9 0 123456 1000 50";
+        assert_eq!(start_time(stat), Some(123456));
+        assert_eq!(start_time("4242 (teletraan) S 1"), None);
+    }
+
+    #[test]
+    fn reads_its_own_start_time() {
+        let stat = std::fs::read_to_string("/proc/self/stat").unwrap();
+        assert!(start_time(&stat).is_some());
+    }
+}
@@ -0,0 +1,541 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter};
+
+use crate::audit::{self, AuditEvent};
+use crate::settings::{self, UpdateChannel};
+use crate::signature;
+use crate::store;
+use crate::transport;
+use crate::updater;
+
+/// Manifest of the newest analysis prompt and strategy packs on each
+/// channel, signed as `packs.json.sig` beside it. Packs are released under
+/// their own tags, apart from the app and the backend.
+const STABLE_FEED: &str = "https://github.com/barkain/teletraan/releases/download/packs/packs.json";
+const BETA_FEED: &str =
+    "https://github.com/barkain/teletraan/releases/download/packs-beta/packs.json";
+
+/// In the data dir: each pack's versions as `<name>/<version>.json`,
+/// `state.json` and `active.json`.
+const PACKS_DIR: &str = "packs";
+const STATE_FILE: &str = "state.json";
+/// The packs in use, which the backend reads through `TELETRAAN_PACKS_FILE`.
+const ACTIVE_FILE: &str = "active.json";
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+const RELOAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest manifest or pack accepted; packs are text.
+const MAX_DOWNLOAD_BYTES: usize = 4 * 1024 * 1024;
+
+/// Held while packs are installed, pinned or rolled back.
+static BUSY: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// What a pack holds, which decides what the backend does with it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackKind {
+    /// System prompts of the analysts, by name.
+    Prompts,
+    /// Settings of the analysis, such as the confidence weights.
+    Strategy,
+}
+
+/// `packs.json` on a packs feed.
+#[derive(Clone, Debug, Deserialize)]
+struct PackFeed {
+    packs: Vec<FeedPack>,
+}
+
+/// The newest version of one pack.
+#[derive(Clone, Debug, Deserialize)]
+struct FeedPack {
+    name: String,
+    kind: PackKind,
+    version: String,
+    url: String,
+    /// Of the pack file; the manifest is signed, so this vouches for it.
+    sha256: String,
+    #[serde(default)]
+    notes: Option<String>,
+    /// Oldest app whose backend understands this pack.
+    #[serde(default)]
+    min_app_version: Option<String>,
+}
+
+/// One pack in `state.json`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PackState {
+    kind: PackKind,
+    /// The version in use; `None` for the prompts or settings built into
+    /// the backend.
+    #[serde(default)]
+    current: Option<String>,
+    /// The version `rollback_pack` goes back to.
+    #[serde(default)]
+    previous: Option<String>,
+    /// Updates leave a pinned pack as it is.
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    updated_at: Option<String>,
+}
+
+/// `active.json`.
+#[derive(Clone, Debug, Serialize)]
+struct ActivePacks {
+    packs: Vec<ActivePack>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct ActivePack {
+    name: String,
+    kind: PackKind,
+    version: String,
+    path: PathBuf,
+}
+
+/// A pack as `get_packs` reports it, and in `packs-updated`.
+#[derive(Clone, Debug, Serialize)]
+pub struct PackStatus {
+    pub name: String,
+    pub kind: PackKind,
+    /// `None` when the built-in prompts or settings are used.
+    pub version: Option<String>,
+    pub previous: Option<String>,
+    pub pinned: bool,
+    pub updated_at: Option<String>,
+}
+
+/// Result of `update_packs`.
+#[derive(Clone, Debug, Serialize)]
+pub struct PacksUpdate {
+    pub channel: UpdateChannel,
+    /// `name version` of each pack installed.
+    pub installed: Vec<String>,
+    /// Why a pack could not be installed, by name.
+    pub errors: BTreeMap<String, String>,
+    pub packs: Vec<PackStatus>,
+    /// Why the backend could not be told to reload; it reads the packs again
+    /// when it next starts.
+    pub reload_error: Option<String>,
+}
+
+/// What the backend reports from `/api/v1/maintenance/reload-packs`.
+#[derive(Clone, Debug, Deserialize)]
+struct ReloadReport {
+    errors: Vec<String>,
+}
+
+/// The file the backend spawned for `data_dir` reads the packs in use from.
+pub fn active_file(data_dir: &Path) -> PathBuf {
+    data_dir.join(PACKS_DIR).join(ACTIVE_FILE)
+}
+
+fn packs_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::resolve_data_dir(app)?.join(PACKS_DIR))
+}
+
+fn feed_url(channel: UpdateChannel) -> &'static str {
+    match channel {
+        UpdateChannel::Stable => STABLE_FEED,
+        UpdateChannel::Beta => BETA_FEED,
+    }
+}
+
+/// Pack names become folder names, so only plain ones are accepted.
+fn check_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid pack name {name:?}"))
+    }
+}
+
+fn pack_path(root: &Path, name: &str, version: &str) -> PathBuf {
+    root.join(name).join(format!("{version}.json"))
+}
+
+fn load_state(root: &Path) -> BTreeMap<String, PackState> {
+    store::read_json(&root.join(STATE_FILE))
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to read the pack state: {e}");
+            None
+        })
+        .unwrap_or_default()
+}
+
+/// Save `state` and write `active.json` from it.
+fn save_state(root: &Path, state: &BTreeMap<String, PackState>) -> Result<(), String> {
+    store::write_json(&root.join(STATE_FILE), state)?;
+    let packs = state
+        .iter()
+        .filter_map(|(name, pack)| {
+            let version = pack.current.clone()?;
+            let path = pack_path(root, name, &version);
+            if !path.is_file() {
+                log::warn!("Pack {name} {version} is missing from {}", path.display());
+                return None;
+            }
+            Some(ActivePack {
+                name: name.clone(),
+                kind: pack.kind,
+                version,
+                path,
+            })
+        })
+        .collect();
+    store::write_json(&root.join(ACTIVE_FILE), &ActivePacks { packs })
+}
+
+fn statuses(state: &BTreeMap<String, PackState>) -> Vec<PackStatus> {
+    state
+        .iter()
+        .map(|(name, pack)| PackStatus {
+            name: name.clone(),
+            kind: pack.kind,
+            version: pack.current.clone(),
+            previous: pack.previous.clone(),
+            pinned: pack.pinned,
+            updated_at: pack.updated_at.clone(),
+        })
+        .collect()
+}
+
+/// Fetch `url` whole, refusing anything but https and anything larger than
+/// `MAX_DOWNLOAD_BYTES`.
+async fn fetch(url: &str) -> Result<Vec<u8>, String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid download {url:?}: {e}"))?;
+    if parsed.scheme() != "https" {
+        return Err(format!("Invalid download {url:?}: expected https"));
+    }
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {e}"))?;
+    let mut response = client
+        .get(parsed)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to download {url}: {e}"))?;
+    let mut data = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to download {url}: {e}"))?
+    {
+        data.extend_from_slice(&chunk);
+        if data.len() > MAX_DOWNLOAD_BYTES {
+            return Err(format!("{url} is larger than {MAX_DOWNLOAD_BYTES} bytes"));
+        }
+    }
+    Ok(data)
+}
+
+/// The manifest on the configured channel, once its signature checks out.
+async fn fetch_feed(app: &AppHandle) -> Result<(UpdateChannel, PackFeed), String> {
+    let key = updater::public_key()?;
+    let channel = settings::current(app).updates.channel;
+    let url = feed_url(channel);
+    let manifest = fetch(url).await?;
+    let signature = fetch(&format!("{url}.sig")).await?;
+    let signature = String::from_utf8(signature)
+        .map_err(|_| "The pack manifest's signature is not text".to_string())?;
+    signature::verify(&manifest, &signature, &key)
+        .map_err(|e| format!("The pack manifest failed its signature check: {e}"))?;
+    let feed =
+        serde_json::from_slice(&manifest).map_err(|e| format!("Invalid pack manifest: {e}"))?;
+    Ok((channel, feed))
+}
+
+/// Whether `pack` should replace what `state` has installed of it.
+fn wanted(app: &AppHandle, pack: &FeedPack, state: Option<&PackState>) -> Result<bool, String> {
+    check_name(&pack.name)?;
+    let version = updater::parse_version(&pack.version)?;
+    if let Some(min) = &pack.min_app_version {
+        if updater::parse_version(min)? > app.package_info().version {
+            log::info!(
+                "Pack {} {} needs Teletraan {min} or later",
+                pack.name,
+                pack.version
+            );
+            return Ok(false);
+        }
+    }
+    let Some(state) = state else {
+        return Ok(true);
+    };
+    if state.pinned {
+        log::info!(
+            "Pack {} is pinned; not updating it to {}",
+            pack.name,
+            pack.version
+        );
+        return Ok(false);
+    }
+    match &state.current {
+        Some(current) => {
+            Ok(updater::parse_version(current).map_or(true, |current| version > current))
+        }
+        None => Ok(true),
+    }
+}
+
+/// Download `pack`, check it against the manifest and stage it in `root`.
+async fn stage(root: &Path, pack: &FeedPack) -> Result<(), String> {
+    let data = fetch(&pack.url).await?;
+    let digest = hex::encode(Sha256::digest(&data));
+    if !digest.eq_ignore_ascii_case(pack.sha256.trim()) {
+        return Err(format!(
+            "Pack {} {} does not match the manifest's hash",
+            pack.name, pack.version
+        ));
+    }
+    let content: serde_json::Value = serde_json::from_slice(&data)
+        .map_err(|e| format!("Invalid pack {} {}: {e}", pack.name, pack.version))?;
+    let kind = serde_json::to_value(pack.kind).map_err(|e| e.to_string())?;
+    for (field, expected) in [
+        ("name", serde_json::Value::from(pack.name.as_str())),
+        ("version", serde_json::Value::from(pack.version.as_str())),
+        ("kind", kind),
+    ] {
+        if content.get(field) != Some(&expected) {
+            return Err(format!(
+                "Pack {} {} has {field} {}, not {expected}",
+                pack.name,
+                pack.version,
+                content.get(field).unwrap_or(&serde_json::Value::Null)
+            ));
+        }
+    }
+    let path = pack_path(root, &pack.name, &pack.version);
+    store::write_json(&path, &content)?;
+    log::info!(
+        "Staged pack {} {} in {}",
+        pack.name,
+        pack.version,
+        path.display()
+    );
+    Ok(())
+}
+
+/// Remove every version of the pack `name` but its current and previous one.
+fn prune(root: &Path, name: &str, pack: &PackState) {
+    let Ok(entries) = std::fs::read_dir(root.join(name)) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let file = entry.file_name().to_string_lossy().into_owned();
+        let version = file.strip_suffix(".json").unwrap_or(&file);
+        if pack.current.as_deref() == Some(version) || pack.previous.as_deref() == Some(version) {
+            continue;
+        }
+        match std::fs::remove_file(entry.path()) {
+            Ok(()) => log::info!("Removed old pack {}", entry.path().display()),
+            Err(e) => log::warn!("Failed to remove {}: {e}", entry.path().display()),
+        }
+    }
+}
+
+/// Have the backend read `active.json` again. A backend that was not
+/// started by this app is left alone.
+async fn reload_backend(app: &AppHandle) -> Result<(), String> {
+    let privileged = transport::privileged_endpoint(app)?;
+    let resp = transport::Client::new(RELOAD_TIMEOUT)?
+        .post_json(
+            &privileged.endpoint,
+            "/api/v1/maintenance/reload-packs",
+            &privileged.headers(),
+            &(),
+        )
+        .await
+        .map_err(|e| format!("Failed to reload the packs: {e}"))?;
+    if !resp.status().is_success() {
+        return Err(format!(
+            "Failed to reload the packs: HTTP {}: {}",
+            resp.status(),
+            String::from_utf8_lossy(resp.body())
+        ));
+    }
+    let report: ReloadReport = transport::json(&resp)?;
+    for error in &report.errors {
+        log::warn!("The backend skipped part of a pack: {error}");
+    }
+    Ok(())
+}
+
+/// Tell the backend about a change and emit `packs-updated`. Returns why the
+/// backend could not be told, if it could not.
+async fn publish(app: &AppHandle, packs: &[PackStatus]) -> Option<String> {
+    let reload_error = reload_backend(app).await.err();
+    if let Some(e) = &reload_error {
+        log::warn!("{e}");
+    }
+    if let Err(e) = app.emit("packs-updated", packs) {
+        log::warn!("Failed to emit packs-updated: {e}");
+    }
+    reload_error
+}
+
+/// Install every pack on the channel's manifest that is newer than the one
+/// in use and not pinned.
+async fn update(app: &AppHandle) -> Result<PacksUpdate, String> {
+    let _busy = BUSY.lock().await;
+    let (channel, feed) = fetch_feed(app).await?;
+    let root = packs_dir(app)?;
+    let mut state = load_state(&root);
+    let mut installed = Vec::new();
+    let mut errors = BTreeMap::new();
+    for pack in &feed.packs {
+        let staged = match wanted(app, pack, state.get(&pack.name)) {
+            Ok(true) => stage(&root, pack).await,
+            Ok(false) => continue,
+            Err(e) => Err(e),
+        };
+        let detail = format!("pack {} {} ({channel:?})", pack.name, pack.version);
+        if let Err(e) = staged {
+            log::warn!("Failed to install {detail}: {e}");
+            audit::record(app, AuditEvent::PackUpdate, false, format!("{detail}: {e}"));
+            errors.insert(pack.name.clone(), e);
+            continue;
+        }
+        let entry = state.entry(pack.name.clone()).or_insert(PackState {
+            kind: pack.kind,
+            current: None,
+            previous: None,
+            pinned: false,
+            updated_at: None,
+        });
+        entry.kind = pack.kind;
+        entry.previous = entry.current.replace(pack.version.clone());
+        entry.updated_at = Some(chrono::Utc::now().to_rfc3339());
+        prune(&root, &pack.name, entry);
+        match &pack.notes {
+            Some(notes) => log::info!("Installed {detail}: {notes}"),
+            None => log::info!("Installed {detail}"),
+        }
+        audit::record(app, AuditEvent::PackUpdate, true, detail);
+        installed.push(format!("{} {}", pack.name, pack.version));
+    }
+    let packs = statuses(&state);
+    let mut reload_error = None;
+    if !installed.is_empty() {
+        save_state(&root, &state)?;
+        reload_error = publish(app, &packs).await;
+    }
+    Ok(PacksUpdate {
+        channel,
+        installed,
+        errors,
+        packs,
+        reload_error,
+    })
+}
+
+/// Update the packs in the background at startup, with
+/// `updates.check_on_startup`. Unlike the app and the backend, packs are
+/// installed without asking: they only change prompts and settings, and
+/// each can be rolled back or pinned.
+pub fn spawn_check(app: AppHandle) {
+    if !settings::current(&app).updates.check_on_startup || updater::public_key().is_err() {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        match update(&app).await {
+            Ok(update) if update.installed.is_empty() => log::info!("The packs are up to date"),
+            Ok(update) => log::info!("Updated packs: {}", update.installed.join(", ")),
+            Err(e) => log::warn!("Failed to check for pack updates: {e}"),
+        }
+    });
+}
+
+/// Tauri command: the installed packs, their versions and whether each is
+/// pinned.
+#[tauri::command]
+pub async fn get_packs(app: AppHandle) -> Result<Vec<PackStatus>, String> {
+    Ok(statuses(&load_state(&packs_dir(&app)?)))
+}
+
+/// Tauri command: fetch the signed pack manifest of the configured channel,
+/// install the newer versions of the packs that are not pinned and have the
+/// backend reload them. The versions they replace are kept for
+/// `rollback_pack`.
+#[tauri::command]
+pub async fn update_packs(app: AppHandle) -> Result<PacksUpdate, String> {
+    update(&app).await
+}
+
+/// Tauri command: pin the pack `name` to its current version, or unpin it
+/// so updates apply again.
+#[tauri::command]
+pub async fn pin_pack(
+    app: AppHandle,
+    name: String,
+    pinned: bool,
+) -> Result<Vec<PackStatus>, String> {
+    let _busy = BUSY.lock().await;
+    let root = packs_dir(&app)?;
+    let mut state = load_state(&root);
+    let pack = state
+        .get_mut(&name)
+        .ok_or_else(|| format!("Pack {name:?} is not installed"))?;
+    pack.pinned = pinned;
+    store::write_json(&root.join(STATE_FILE), &state)?;
+    log::info!("Pack {name} {}", if pinned { "pinned" } else { "unpinned" });
+    Ok(statuses(&state))
+}
+
+/// Tauri command: go back to the version of the pack `name` its last update
+/// replaced, or to the built-in prompts or settings for a first install, and
+/// have the backend reload it. The pack is pinned, so the next update does
+/// not bring the newer version back; running it again returns to it.
+#[tauri::command]
+pub async fn rollback_pack(app: AppHandle, name: String) -> Result<Vec<PackStatus>, String> {
+    let _busy = BUSY.lock().await;
+    let root = packs_dir(&app)?;
+    let mut state = load_state(&root);
+    let pack = state
+        .get_mut(&name)
+        .ok_or_else(|| format!("Pack {name:?} is not installed"))?;
+    if pack.current.is_none() && pack.previous.is_none() {
+        return Err(format!("Pack {name:?} has no other version to go back to"));
+    }
+    if let Some(previous) = &pack.previous {
+        if !pack_path(&root, &name, previous).is_file() {
+            return Err(format!("Pack {name} {previous} is no longer installed"));
+        }
+    }
+    let detail = format!(
+        "pack {name} {} rolled back to {}",
+        pack.current.as_deref().unwrap_or("built-in"),
+        pack.previous.as_deref().unwrap_or("built-in")
+    );
+    std::mem::swap(&mut pack.current, &mut pack.previous);
+    pack.pinned = true;
+    pack.updated_at = Some(chrono::Utc::now().to_rfc3339());
+    if let Err(e) = save_state(&root, &state) {
+        audit::record(
+            &app,
+            AuditEvent::PackUpdate,
+            false,
+            format!("{detail}: {e}"),
+        );
+        return Err(e);
+    }
+    log::info!("{detail}");
+    audit::record(&app, AuditEvent::PackUpdate, true, detail);
+    let packs = statuses(&state);
+    publish(&app, &packs).await;
+    Ok(packs)
+}
@@ -0,0 +1,73 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::logging;
+
+/// Hand-edited config files the shell reads from the data dir.
+pub const CONFIG_FILES: &[&str] = &["settings.toml"];
+
+/// The backend's SQLite database inside the data dir.
+pub fn database_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("data").join("market-analyzer.db")
+}
+
+/// Payload of `get_app_paths`.
+#[derive(Clone, Debug, Serialize)]
+pub struct AppPaths {
+    pub data_dir: PathBuf,
+    pub database: PathBuf,
+    pub log_dir: PathBuf,
+    /// Log of the current backend session (or `latest.log`).
+    pub current_log: PathBuf,
+    /// Where the bundled backend and other resources are installed.
+    pub resource_dir: Option<PathBuf>,
+    /// Config files the shell reads, whether or not they exist yet.
+    pub config_files: Vec<PathBuf>,
+}
+
+/// Tauri command: where the app keeps its data, logs and configuration, for
+/// the settings page.
+#[tauri::command]
+pub async fn get_app_paths(app: AppHandle) -> Result<AppPaths, String> {
+    let data_dir = crate::resolve_data_dir(&app)?;
+    let resource_dir = app
+        .path()
+        .resource_dir()
+        .inspect_err(|e| log::warn!("Failed to resolve resource directory: {e}"))
+        .ok();
+    Ok(AppPaths {
+        database: database_path(&data_dir),
+        log_dir: logging::logs_dir(&data_dir),
+        current_log: logging::current_log_path(&data_dir),
+        resource_dir,
+        config_files: CONFIG_FILES
+            .iter()
+            .map(|name| data_dir.join(name))
+            .collect(),
+        data_dir,
+    })
+}
+
+/// Tauri command: show the app data directory in Finder/Explorer, with the
+/// database selected when it exists.
+#[tauri::command]
+pub async fn open_data_dir(app: AppHandle) -> Result<(), String> {
+    let data_dir = crate::resolve_data_dir(&app)?;
+    reveal(&app, &database_path(&data_dir), &data_dir)
+}
+
+/// Select `item` in the file manager, or open `dir` if `item` does not exist.
+pub fn reveal(app: &AppHandle, item: &Path, dir: &Path) -> Result<(), String> {
+    if item.exists() {
+        app.opener()
+            .reveal_item_in_dir(item)
+            .map_err(|e| format!("Failed to reveal {}: {e}", item.display()))
+    } else {
+        app.opener()
+            .open_path(dir.display().to_string(), None::<&str>)
+            .map_err(|e| format!("Failed to open {}: {e}", dir.display()))
+    }
+}
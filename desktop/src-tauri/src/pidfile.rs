@@ -0,0 +1,195 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessesToUpdate, Signal, System, UpdateKind};
+
+use crate::store;
+
+/// Name carried by the bundled backend executable (plus `.exe` on Windows).
+pub const BACKEND_PROCESS_NAME: &str = "teletraan-backend";
+
+/// How long a stale backend gets to exit after SIGTERM before it is killed.
+const STALE_GRACE: Duration = Duration::from_secs(5);
+
+/// Contents of `backend.pid`: enough to recognise the backend again on a later
+/// launch even after its pid has been reused by an unrelated process.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PidRecord {
+    pub pid: u32,
+    /// Process start time in seconds since the Unix epoch.
+    pub started_at: u64,
+    /// TCP port, or 0 when the backend serves on `socket`.
+    pub port: u16,
+    #[serde(default)]
+    pub socket: Option<PathBuf>,
+    /// The desktop app process that spawned the backend.
+    pub owner_pid: u32,
+    /// Token the backend requires on every request, for another instance
+    /// sharing it.
+    #[serde(default)]
+    pub auth_token: Option<String>,
+    /// Token its privileged endpoints take, for another instance sharing it.
+    #[serde(default)]
+    pub shell_token: Option<String>,
+    /// Whether it serves HTTPS with the certificate of this install.
+    #[serde(default)]
+    pub tls: bool,
+}
+
+fn pid_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("backend.pid")
+}
+
+/// Record the backend we just spawned.
+pub fn write(
+    data_dir: &Path,
+    pid: u32,
+    port: u16,
+    socket: Option<PathBuf>,
+    auth_token: &str,
+    shell_token: &str,
+    tls: bool,
+) {
+    let mut system = System::new();
+    let Some(started_at) = refresh(&mut system, Pid::from_u32(pid)).map(Process::start_time) else {
+        log::warn!("Backend (pid: {pid}) exited before its pid file could be written");
+        return;
+    };
+    let record = PidRecord {
+        pid,
+        started_at,
+        port,
+        socket,
+        owner_pid: std::process::id(),
+        auth_token: Some(auth_token.to_string()),
+        shell_token: Some(shell_token.to_string()),
+        tls,
+    };
+    if let Err(e) = store::write_json(&pid_path(data_dir), &record) {
+        log::warn!("Failed to write backend pid file: {e}");
+    }
+}
+
+/// Remove the pid file after a clean shutdown.
+pub fn remove(data_dir: &Path) {
+    let path = pid_path(data_dir);
+    match std::fs::remove_file(&path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => log::warn!("Failed to remove backend pid file {}: {e}", path.display()),
+    }
+}
+
+fn read(data_dir: &Path) -> Option<PidRecord> {
+    store::read_json(&pid_path(data_dir)).unwrap_or_else(|e| {
+        log::warn!("Ignoring unreadable backend pid file: {e}");
+        None
+    })
+}
+
+/// Refresh a single process (including its command line and executable) and
+/// return it if it is still running.
+fn refresh(system: &mut System, pid: Pid) -> Option<&Process> {
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing()
+            .with_cmd(UpdateKind::OnlyIfNotSet)
+            .with_exe(UpdateKind::OnlyIfNotSet),
+    );
+    system.process(pid)
+}
+
+/// Whether `process` is the backend described by `record`: same start time,
+/// the backend executable, and serving the recorded port or socket.
+fn is_recorded_backend(process: &Process, record: &PidRecord) -> bool {
+    let (flag, value) = match &record.socket {
+        Some(socket) => ("--uds", socket.display().to_string()),
+        None => ("--port", record.port.to_string()),
+    };
+    let cmd = process.cmd();
+    process.start_time() == record.started_at
+        && process
+            .name()
+            .to_string_lossy()
+            .contains(BACKEND_PROCESS_NAME)
+        && cmd
+            .windows(2)
+            .any(|pair| pair[0] == flag && pair[1] == value.as_str())
+}
+
+/// Whether the app instance that spawned the backend is still running.
+fn owner_alive(system: &mut System, record: &PidRecord) -> bool {
+    if record.owner_pid == std::process::id() {
+        return false;
+    }
+    let Ok(current_exe) = std::env::current_exe() else {
+        return false;
+    };
+    refresh(system, Pid::from_u32(record.owner_pid))
+        .and_then(Process::exe)
+        .is_some_and(|exe| exe == current_exe)
+}
+
+/// Reconcile the pid file left by a previous launch before spawning a backend.
+///
+/// Returns the record if it describes a backend still owned by another running
+/// instance of the app; that backend should be reused rather than spawning a
+/// second one on the same port and database. A backend orphaned by a session
+/// that crashed before it could shut its child down is terminated, since it
+/// keeps the port and the SQLite lock. A pid that no longer exists, or now
+/// belongs to an unrelated process (pids are reused), is ignored and only the
+/// stale file is removed.
+pub fn reconcile(data_dir: &Path) -> Option<PidRecord> {
+    let record = read(data_dir)?;
+    let pid = Pid::from_u32(record.pid);
+    let mut system = System::new();
+
+    let is_backend = refresh(&mut system, pid).is_some_and(|p| is_recorded_backend(p, &record));
+    if !is_backend {
+        log::debug!(
+            "Stale backend pid file ({}) does not belong to a running backend",
+            record.pid
+        );
+        remove(data_dir);
+        return None;
+    }
+
+    if owner_alive(&mut system, &record) {
+        log::info!(
+            "Backend (pid: {}) is owned by another running instance (pid: {}); reusing it",
+            record.pid,
+            record.owner_pid
+        );
+        return Some(record);
+    }
+
+    log::warn!(
+        "Found orphaned backend from a previous session (pid: {}); terminating it",
+        record.pid
+    );
+    let process = system.process(pid).expect("process was just refreshed");
+    if process.kill_with(Signal::Term) != Some(true) {
+        // No SIGTERM on Windows: go straight to TerminateProcess.
+        process.kill();
+    }
+
+    let deadline = Instant::now() + STALE_GRACE;
+    while refresh(&mut system, pid).is_some() {
+        if Instant::now() >= deadline {
+            log::warn!(
+                "Orphaned backend (pid: {}) ignored SIGTERM; killing it",
+                record.pid
+            );
+            if let Some(process) = system.process(pid) {
+                process.kill();
+            }
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    remove(data_dir);
+    None
+}
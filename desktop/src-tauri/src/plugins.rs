@@ -0,0 +1,193 @@
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::store;
+
+/// Host API version offered to plugins. A plugin declares the version it was
+/// built against; any version in `MIN_SUPPORTED_API..=HOST_API_VERSION` is
+/// accepted.
+const HOST_API_VERSION: u32 = 1;
+const MIN_SUPPORTED_API: u32 = 1;
+
+/// Capabilities a plugin may request from the host. Anything else is refused
+/// at discovery time so a plugin can never silently gain access.
+const KNOWN_CAPABILITIES: [&str; 3] = ["http", "storage", "notify"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginKind {
+    DataProvider,
+    Indicator,
+    AlertAction,
+}
+
+/// `plugin.json` at the root of each plugin directory.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub name: String,
+    pub version: String,
+    pub api_version: u32,
+    pub kind: PluginKind,
+    /// Path to the WASM module, relative to the plugin directory.
+    pub entry: String,
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "state", content = "detail", rename_all = "snake_case")]
+pub enum PluginStatus {
+    /// The manifest checks out, but this build has no WASM runtime to load
+    /// the plugin with, so it never runs.
+    NotLoaded(String),
+    Incompatible(String),
+    Invalid(String),
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PluginInfo {
+    /// Directory name under `plugins/`.
+    pub id: String,
+    pub path: PathBuf,
+    pub manifest: Option<PluginManifest>,
+    pub status: PluginStatus,
+}
+
+/// Directory scanned for plugins, one subdirectory per plugin.
+fn plugins_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::resolve_data_dir(app)?.join("plugins");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create plugins directory {}: {e}", dir.display()))?;
+    Ok(dir)
+}
+
+/// Check a manifest against what this host can offer.
+fn negotiate(dir: &Path, manifest: &PluginManifest) -> PluginStatus {
+    if manifest.api_version < MIN_SUPPORTED_API || manifest.api_version > HOST_API_VERSION {
+        return PluginStatus::Incompatible(format!(
+            "Plugin targets host API v{}, this build supports v{MIN_SUPPORTED_API}-v{HOST_API_VERSION}",
+            manifest.api_version,
+        ));
+    }
+
+    if let Some(unknown) = manifest
+        .capabilities
+        .iter()
+        .find(|c| !KNOWN_CAPABILITIES.contains(&c.as_str()))
+    {
+        return PluginStatus::Incompatible(format!("Unknown capability requested: {unknown}"));
+    }
+
+    let relative = Path::new(&manifest.entry);
+    let contained = relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)));
+    if !contained || relative.extension().is_none_or(|ext| ext != "wasm") {
+        return PluginStatus::Invalid(format!(
+            "Entry must be a .wasm file inside the plugin directory: {}",
+            manifest.entry
+        ));
+    }
+    if !dir.join(relative).is_file() {
+        return PluginStatus::Invalid(format!("Entry module not found: {}", manifest.entry));
+    }
+
+    PluginStatus::NotLoaded("not loaded (no runtime)".into())
+}
+
+fn inspect(dir: PathBuf) -> PluginInfo {
+    let id = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let (manifest, status) = match store::read_json::<PluginManifest>(&dir.join("plugin.json")) {
+        Ok(Some(manifest)) => {
+            let status = negotiate(&dir, &manifest);
+            (Some(manifest), status)
+        }
+        Ok(None) => (None, PluginStatus::Invalid("Missing plugin.json".into())),
+        Err(e) => (None, PluginStatus::Invalid(e)),
+    };
+
+    PluginInfo {
+        id,
+        path: dir,
+        manifest,
+        status,
+    }
+}
+
+/// Tauri command: discover plugins in the plugins directory and report
+/// whether each is compatible with this host. None is loaded yet: a
+/// compatible plugin is reported as `not_loaded`, since this build has no
+/// plugin runtime.
+#[tauri::command]
+pub async fn list_plugins(app: AppHandle) -> Result<Vec<PluginInfo>, String> {
+    let dir = plugins_dir(&app)?;
+    let entries = std::fs::read_dir(&dir)
+        .map_err(|e| format!("Failed to read plugins directory {}: {e}", dir.display()))?;
+
+    let mut plugins: Vec<PluginInfo> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .map(inspect)
+        .collect();
+    plugins.sort_by(|a, b| a.id.cmp(&b.id));
+
+    for plugin in &plugins {
+        if let PluginStatus::Incompatible(reason) | PluginStatus::Invalid(reason) = &plugin.status {
+            log::warn!("Plugin {} is not loadable: {reason}", plugin.id);
+        }
+    }
+    Ok(plugins)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(api_version: u32, entry: &str, capabilities: &[&str]) -> PluginManifest {
+        PluginManifest {
+            name: "demo".to_string(),
+            version: "1.0.0".to_string(),
+            api_version,
+            kind: PluginKind::Indicator,
+            entry: entry.to_string(),
+            capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            description: None,
+        }
+    }
+
+    #[test]
+    fn never_reports_a_plugin_as_loaded() {
+        let dir = std::env::temp_dir().join(format!("teletraan-plugins-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("demo.wasm"), b"\0asm").unwrap();
+
+        let status = negotiate(&dir, &manifest(1, "demo.wasm", &["http"]));
+        assert!(matches!(status, PluginStatus::NotLoaded(_)), "{status:?}");
+        assert_eq!(
+            serde_json::to_value(&status).unwrap(),
+            serde_json::json!({"state": "not_loaded", "detail": "not loaded (no runtime)"})
+        );
+        assert!(matches!(
+            negotiate(&dir, &manifest(2, "demo.wasm", &[])),
+            PluginStatus::Incompatible(_)
+        ));
+        assert!(matches!(
+            negotiate(&dir, &manifest(1, "demo.wasm", &["shell"])),
+            PluginStatus::Incompatible(_)
+        ));
+        assert!(matches!(
+            negotiate(&dir, &manifest(1, "../demo.wasm", &[])),
+            PluginStatus::Invalid(_)
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,228 @@
+use std::net::{SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::process::Command;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::AppHandle;
+
+use crate::pidfile::BACKEND_PROCESS_NAME;
+use crate::settings;
+
+/// How the backend serves its API.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transport {
+    /// TCP on `host` and `port`.
+    #[default]
+    Tcp,
+    /// A Unix domain socket in the data dir, which the webview reaches through
+    /// the `teletraan://` proxy. Not available on Windows.
+    Socket,
+}
+
+/// Where the backend listens, from the `[backend]` section of
+/// `settings.toml`; `--port` and `TELETRAAN_BACKEND_*` variables override it
+/// (see `config`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BindConfig {
+    pub transport: Transport,
+    pub host: String,
+    /// Fixed port; a free one is picked at every spawn when unset.
+    pub port: Option<u16>,
+    /// Allow the backend to be reachable from other machines. Without it, a
+    /// backend found listening beyond loopback is stopped.
+    pub remote_mode: bool,
+    /// Serve HTTPS with the certificate of this install (see `tls`), which
+    /// the shell then requires. TCP only.
+    pub tls: bool,
+}
+
+impl Default for BindConfig {
+    fn default() -> Self {
+        Self {
+            transport: Transport::Tcp,
+            host: "127.0.0.1".to_string(),
+            port: None,
+            remote_mode: false,
+            tls: false,
+        }
+    }
+}
+
+impl BindConfig {
+    pub fn load(app: &AppHandle) -> Self {
+        let mut config = settings::current(app).backend.bind;
+        if cfg!(windows) && config.transport == Transport::Socket {
+            log::warn!("The socket transport is not supported on Windows; using TCP");
+            config.transport = Transport::Tcp;
+        }
+        // Port 0 means "pick one", same as leaving it unset.
+        config.port = config.port.filter(|&port| port != 0);
+        config
+    }
+
+    /// The configured port, or a free one on `host`.
+    pub fn port(&self) -> Result<u16, String> {
+        match self.port {
+            Some(port) => Ok(port),
+            None => pick_free(&self.host),
+        }
+    }
+}
+
+/// `http://host:port`, or `https://` with `tls`, with IPv6 hosts bracketed.
+pub fn base_url(host: &str, port: u16, tls: bool) -> String {
+    let scheme = if tls { "https" } else { "http" };
+    if host.contains(':') {
+        format!("{scheme}://[{host}]:{port}")
+    } else {
+        format!("{scheme}://{host}:{port}")
+    }
+}
+
+/// Payload of the `security-warning` event.
+#[derive(Clone, Debug, Serialize)]
+pub struct SecurityWarning {
+    /// Address on which another machine could reach the backend.
+    pub address: String,
+    /// Remote mode is on, so the backend was left running.
+    pub remote_mode: bool,
+    /// Human-readable explanation suitable for showing to the user as-is.
+    pub message: String,
+}
+
+/// A non-loopback address of this machine on which `port` accepts
+/// connections, e.g. because the backend bound `0.0.0.0`.
+///
+/// The outward-facing address is found by "connecting" a UDP socket, which
+/// sends nothing. Returns `None` on a machine without a network route.
+pub fn external_exposure(port: u16) -> Option<SocketAddr> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0)).ok()?;
+    socket.connect(("192.0.2.1", 9)).ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    if ip.is_loopback() || ip.is_unspecified() {
+        return None;
+    }
+    let addr = SocketAddr::new(ip, port);
+    TcpStream::connect_timeout(&addr, Duration::from_millis(300))
+        .is_ok()
+        .then_some(addr)
+}
+
+/// Payload of the `port-conflict` event: who is holding the backend's port.
+#[derive(Clone, Debug, Serialize)]
+pub struct PortConflict {
+    pub port: u16,
+    /// Listening process, if the platform lets us find out.
+    pub pid: Option<u32>,
+    pub process_name: Option<String>,
+    /// The owner is a Teletraan backend left over from an earlier session.
+    pub stale_backend: bool,
+    /// Human-readable explanation suitable for showing to the user as-is.
+    pub message: String,
+}
+
+/// Ask the OS for a free port on `host` for the backend to listen on.
+///
+/// The port is released again before the backend binds it, so another process
+/// could in principle take it in between; `check` catches that before spawning.
+pub fn pick_free(host: &str) -> Result<u16, String> {
+    TcpListener::bind((host, 0))
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .map_err(|e| format!("Failed to find a free port for the backend: {e}"))
+}
+
+/// Make sure nothing is listening on `port`, neither on `host` nor on the
+/// loopback interface.
+///
+/// Returns a description of the process holding it otherwise, so the user gets
+/// an actionable message instead of a backend that never becomes healthy.
+pub fn check(host: &str, port: u16) -> Result<(), PortConflict> {
+    // Windows lets us bind 127.0.0.1 over a listener on 0.0.0.0, so also try
+    // connecting.
+    let loopback = SocketAddr::from(([127, 0, 0, 1], port));
+    let answering = TcpStream::connect_timeout(&loopback, Duration::from_millis(200)).is_ok();
+    if !answering && TcpListener::bind((host, port)).is_ok() {
+        return Ok(());
+    }
+
+    let pid = listening_pid(port);
+    let process_name = pid.and_then(process_name);
+    let stale_backend = process_name
+        .as_deref()
+        .is_some_and(|name| name.contains(BACKEND_PROCESS_NAME));
+    let message = match (&process_name, pid) {
+        (Some(_), Some(pid)) if stale_backend => format!(
+            "Port {port} is held by a Teletraan backend from an earlier session (pid {pid}). \
+             Quit it or end the process, then start the backend again."
+        ),
+        (Some(name), Some(pid)) => format!(
+            "Port {port} is already in use by {name} (pid {pid}). \
+             Stop that program or configure a different backend port."
+        ),
+        _ => format!(
+            "Port {port} is already in use by another program. \
+             Stop it or configure a different backend port."
+        ),
+    };
+    Err(PortConflict {
+        port,
+        pid,
+        process_name,
+        stale_backend,
+        message,
+    })
+}
+
+fn process_name(pid: u32) -> Option<String> {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing(),
+    );
+    system
+        .process(pid)
+        .map(|p| p.name().to_string_lossy().into_owned())
+}
+
+/// Pid of the process listening on TCP `port`, via `lsof`.
+#[cfg(unix)]
+fn listening_pid(port: u16) -> Option<u32> {
+    let output = Command::new("lsof")
+        .args(["-nP", &format!("-iTCP:{port}"), "-sTCP:LISTEN", "-t"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().parse().ok())
+}
+
+/// Pid of the process listening on TCP `port`, via `netstat -ano`.
+#[cfg(windows)]
+fn listening_pid(port: u16) -> Option<u32> {
+    use std::os::windows::process::CommandExt;
+
+    // Don't flash a console window.
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let output = Command::new("netstat")
+        .args(["-ano", "-p", "TCP"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+    let suffix = format!(":{port}");
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| {
+            // Proto  Local Address  Foreign Address  State  PID
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                [_, local, _, "LISTENING", pid] if local.ends_with(&suffix) => pid.parse().ok(),
+                _ => None,
+            }
+        })
+}
@@ -0,0 +1,330 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+/// Oldest claude CLI the bundled claude-agent-sdk works with.
+const MIN_CLAUDE_VERSION: (u64, u64, u64) = (2, 0, 0);
+
+/// How long `claude --version` may take before the CLI counts as broken.
+const VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where the claude-agent-sdk looks for the CLI when it is not on `PATH`,
+/// relative to the home directory. GUI apps on macOS get a minimal `PATH`, so
+/// these matter more here than in a terminal.
+const CLAUDE_FALLBACK_DIRS: &[&str] = &[
+    ".npm-global/bin",
+    ".local/bin",
+    "node_modules/.bin",
+    ".yarn/bin",
+    ".claude/local",
+];
+
+/// System CA bundles, by the distributions that ship them.
+#[cfg(target_os = "linux")]
+const CA_BUNDLES: &[&str] = &[
+    "/etc/ssl/certs/ca-certificates.crt",
+    "/etc/pki/tls/certs/ca-bundle.crt",
+    "/etc/ssl/ca-bundle.pem",
+    "/etc/pki/ca-trust/extracted/pem/tls-ca-bundle.pem",
+    "/etc/ssl/cert.pem",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Missing,
+    /// Present but older than the app supports.
+    Outdated,
+    /// Present but not working, e.g. the CLI crashed on `--version`.
+    Broken,
+}
+
+/// One entry of `check_dependencies`.
+#[derive(Clone, Debug, Serialize)]
+pub struct DependencyCheck {
+    /// `claude_cli`, `certificates` or `webview`.
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub version: Option<String>,
+    /// Where it was found, or what went wrong.
+    pub detail: Option<String>,
+    /// What the user can do about a failed check; `None` when it passed.
+    pub hint: Option<String>,
+}
+
+impl DependencyCheck {
+    fn ok(name: &'static str, version: Option<String>, detail: Option<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Ok,
+            version,
+            detail,
+            hint: None,
+        }
+    }
+
+    fn failed(name: &'static str, status: CheckStatus, detail: String, hint: &str) -> Self {
+        Self {
+            name,
+            status,
+            version: None,
+            detail: Some(detail),
+            hint: Some(hint.to_string()),
+        }
+    }
+}
+
+/// Run every check, off the async runtime since the CLI check spawns a process.
+pub async fn run() -> Vec<DependencyCheck> {
+    tauri::async_runtime::spawn_blocking(|| vec![claude_cli(), certificates(), webview()])
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!("Dependency checks failed to run: {e}");
+            Vec::new()
+        })
+}
+
+/// Check the dependencies before the first backend spawn, log what is
+/// missing and emit `dependencies-missing` with the failed checks.
+///
+/// A missing dependency does not stop the backend: market data and the
+/// dashboards work without the claude CLI, only the analysis does not.
+pub async fn report(app: &AppHandle) {
+    let failed: Vec<DependencyCheck> = run()
+        .await
+        .into_iter()
+        .filter(|check| check.status != CheckStatus::Ok)
+        .collect();
+    if failed.is_empty() {
+        log::info!("Dependency checks passed");
+        return;
+    }
+    for check in &failed {
+        log::warn!(
+            "Dependency check {} failed ({:?}): {}",
+            check.name,
+            check.status,
+            check.detail.as_deref().unwrap_or_default()
+        );
+    }
+    if let Err(e) = app.emit("dependencies-missing", &failed) {
+        log::warn!("Failed to emit dependencies-missing: {e}");
+    }
+}
+
+/// Tauri command: verify the external tools the app relies on (the claude
+/// CLI, the system certificates and the webview runtime), with a hint on how
+/// to fix each one that is missing or too old.
+#[tauri::command]
+pub async fn check_dependencies() -> Result<Vec<DependencyCheck>, String> {
+    Ok(run().await)
+}
+
+fn claude_cli() -> DependencyCheck {
+    const NAME: &str = "claude_cli";
+    const INSTALL: &str = "Install Claude Code with `npm install -g @anthropic-ai/claude-code`, \
+                           then restart Teletraan.";
+    let Some(path) = find_claude() else {
+        return DependencyCheck::failed(
+            NAME,
+            CheckStatus::Missing,
+            "claude was not found on PATH or in the usual install locations".to_string(),
+            INSTALL,
+        );
+    };
+    let output = match run_with_timeout(&path, "--version") {
+        Ok(output) => output,
+        Err(e) => {
+            return DependencyCheck::failed(
+                NAME,
+                CheckStatus::Broken,
+                format!("{} --version failed: {e}", path.display()),
+                "Reinstall Claude Code with `npm install -g @anthropic-ai/claude-code`.",
+            )
+        }
+    };
+    // e.g. `2.0.14 (Claude Code)`
+    let version = output.split_whitespace().next().unwrap_or_default();
+    match parse_version(version) {
+        Some(parsed) if parsed < MIN_CLAUDE_VERSION => {
+            let (major, minor, patch) = MIN_CLAUDE_VERSION;
+            DependencyCheck {
+                version: Some(version.to_string()),
+                ..DependencyCheck::failed(
+                    NAME,
+                    CheckStatus::Outdated,
+                    format!("claude {version} is older than {major}.{minor}.{patch}"),
+                    "Update Claude Code with `npm install -g @anthropic-ai/claude-code@latest` \
+                     or `claude update`.",
+                )
+            }
+        }
+        _ => DependencyCheck::ok(
+            NAME,
+            Some(version.to_string()),
+            Some(path.display().to_string()),
+        ),
+    }
+}
+
+/// The claude CLI on `PATH`, else in one of the SDK's fallback locations.
+fn find_claude() -> Option<PathBuf> {
+    let names: &[&str] = if cfg!(windows) {
+        &["claude.exe", "claude.cmd"]
+    } else {
+        &["claude"]
+    };
+    let path_dirs = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let fallback_dirs = home_dir()
+        .map(|home| {
+            CLAUDE_FALLBACK_DIRS
+                .iter()
+                .map(|dir| home.join(dir))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let system_dirs = [
+        PathBuf::from("/usr/local/bin"),
+        PathBuf::from("/opt/homebrew/bin"),
+    ];
+    path_dirs
+        .into_iter()
+        .chain(fallback_dirs)
+        .chain(system_dirs)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).map(PathBuf::from)
+}
+
+/// Run `program arg` and return its trimmed stdout, killing it after
+/// `VERSION_TIMEOUT`.
+fn run_with_timeout(program: &Path, arg: &str) -> Result<String, String> {
+    let mut child = Command::new(program)
+        .arg(arg)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    let deadline = Instant::now() + VERSION_TIMEOUT;
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!("no answer within {}s", VERSION_TIMEOUT.as_secs()));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    let mut stdout = String::new();
+    if let Some(mut pipe) = child.stdout.take() {
+        pipe.read_to_string(&mut stdout)
+            .map_err(|e| e.to_string())?;
+    }
+    if !status.success() {
+        return Err(format!("exited with {status}"));
+    }
+    Ok(stdout.trim().to_string())
+}
+
+/// `major.minor.patch`, ignoring any pre-release suffix.
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = version
+        .split(['-', '+'])
+        .next()?
+        .split('.')
+        .map(|part| part.parse::<u64>().ok());
+    Some((
+        parts.next()??,
+        parts.next()??,
+        parts.next().flatten().unwrap_or(0),
+    ))
+}
+
+fn certificates() -> DependencyCheck {
+    const NAME: &str = "certificates";
+    // A broken override breaks every HTTPS request, bundled certificates or not.
+    for var in ["SSL_CERT_FILE", "REQUESTS_CA_BUNDLE", "CURL_CA_BUNDLE"] {
+        if let Some(value) = std::env::var_os(var).filter(|value| !value.is_empty()) {
+            if !Path::new(&value).is_file() {
+                return DependencyCheck::failed(
+                    NAME,
+                    CheckStatus::Missing,
+                    format!(
+                        "{var} points to {}, which does not exist",
+                        value.to_string_lossy()
+                    ),
+                    &format!("Unset {var} or point it to a valid CA bundle."),
+                );
+            }
+        }
+    }
+    system_certificates()
+}
+
+#[cfg(target_os = "linux")]
+fn system_certificates() -> DependencyCheck {
+    match CA_BUNDLES.iter().find(|path| Path::new(path).is_file()) {
+        Some(path) => DependencyCheck::ok("certificates", None, Some(path.to_string())),
+        None => DependencyCheck::failed(
+            "certificates",
+            CheckStatus::Missing,
+            "No system CA bundle found".to_string(),
+            "Install your distribution's `ca-certificates` package.",
+        ),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn system_certificates() -> DependencyCheck {
+    if Path::new("/etc/ssl/cert.pem").is_file() {
+        DependencyCheck::ok("certificates", None, Some("/etc/ssl/cert.pem".to_string()))
+    } else {
+        DependencyCheck::failed(
+            "certificates",
+            CheckStatus::Missing,
+            "/etc/ssl/cert.pem is missing".to_string(),
+            "Install the latest macOS updates, which restore the system certificates.",
+        )
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn system_certificates() -> DependencyCheck {
+    DependencyCheck::ok(
+        "certificates",
+        None,
+        Some("Windows certificate store".to_string()),
+    )
+}
+
+fn webview() -> DependencyCheck {
+    match tauri::webview_version() {
+        Ok(version) => DependencyCheck::ok("webview", Some(version), None),
+        Err(e) => DependencyCheck::failed(
+            "webview",
+            CheckStatus::Missing,
+            format!("Failed to read webview version: {e}"),
+            if cfg!(windows) {
+                "Install the Microsoft Edge WebView2 Evergreen Runtime from \
+                 https://developer.microsoft.com/microsoft-edge/webview2/."
+            } else if cfg!(target_os = "linux") {
+                "Install WebKitGTK 4.1 (`libwebkit2gtk-4.1-0` on Debian and Ubuntu)."
+            } else {
+                "Install the latest system updates."
+            },
+        ),
+    }
+}
@@ -0,0 +1,157 @@
+use std::process::{Child, Command};
+
+/// Put the process about to be spawned by `cmd` into a fresh process group.
+pub fn configure(cmd: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    #[cfg(not(unix))]
+    let _ = cmd;
+}
+
+/// The backend and everything it spawns (the claude CLI, helper Python
+/// processes), so they can be torn down together.
+///
+/// On Unix the backend leads its own process group and signals go to the whole
+/// group. On Windows it is placed in a Job Object that kills its members when
+/// the job is terminated or its last handle closes, even if the app crashes.
+pub struct ProcessGroup {
+    #[cfg(unix)]
+    pgid: libc::pid_t,
+    #[cfg(windows)]
+    job: Option<windows_sys::Win32::Foundation::HANDLE>,
+}
+
+// SAFETY: the job handle is owned exclusively by this value; job object
+// handles may be used and closed from any thread.
+#[cfg(windows)]
+unsafe impl Send for ProcessGroup {}
+
+impl ProcessGroup {
+    /// Track the group of a child spawned with `configure`.
+    pub fn attach(child: &Child) -> Self {
+        #[cfg(unix)]
+        {
+            // `process_group(0)` makes the child's pid its group id.
+            Self {
+                pgid: child.id() as libc::pid_t,
+            }
+        }
+        #[cfg(windows)]
+        {
+            Self {
+                job: create_job(child),
+            }
+        }
+        #[cfg(not(any(unix, windows)))]
+        {
+            let _ = child;
+            Self {}
+        }
+    }
+
+    /// Ask every process in the group to exit. Returns whether the request
+    /// was delivered; Windows has no equivalent, so it always returns `false`.
+    pub fn terminate(&self) -> bool {
+        #[cfg(unix)]
+        {
+            self.signal(libc::SIGTERM)
+        }
+        #[cfg(not(unix))]
+        {
+            false
+        }
+    }
+
+    /// Kill every process still in the group.
+    pub fn kill(&self) {
+        #[cfg(unix)]
+        {
+            self.signal(libc::SIGKILL);
+        }
+        #[cfg(windows)]
+        if let Some(job) = self.job {
+            // SAFETY: `job` is a live job object handle owned by `self`.
+            if unsafe { windows_sys::Win32::System::JobObjects::TerminateJobObject(job, 1) } == 0 {
+                log::warn!(
+                    "Failed to terminate backend job object: {}",
+                    std::io::Error::last_os_error()
+                );
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    fn signal(&self, signal: libc::c_int) -> bool {
+        // SAFETY: `kill` has no memory-safety preconditions. A negative pid
+        // addresses the group, which cannot be reused while any member lives.
+        if unsafe { libc::kill(-self.pgid, signal) } == 0 {
+            return true;
+        }
+        let err = std::io::Error::last_os_error();
+        // ESRCH: the whole group has already exited.
+        if err.raw_os_error() != Some(libc::ESRCH) {
+            log::warn!("Failed to signal backend process group: {err}");
+        }
+        false
+    }
+}
+
+#[cfg(windows)]
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        if let Some(job) = self.job.take() {
+            // SAFETY: `job` was returned by `CreateJobObjectW` and is closed once.
+            unsafe { windows_sys::Win32::Foundation::CloseHandle(job) };
+        }
+    }
+}
+
+/// Create a kill-on-close job object and assign `child` to it.
+///
+/// The child is assigned right after spawning; a process it spawned in the
+/// first instants of its life would escape, but the backend only launches
+/// subprocesses once it is serving requests.
+#[cfg(windows)]
+fn create_job(child: &Child) -> Option<windows_sys::Win32::Foundation::HANDLE> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+        SetInformationJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+
+    // SAFETY: plain Win32 calls; every pointer passed refers to a live local
+    // and the job handle is closed on each failure path.
+    unsafe {
+        let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+        if job.is_null() {
+            log::warn!(
+                "Failed to create backend job object: {}",
+                std::io::Error::last_os_error()
+            );
+            return None;
+        }
+
+        let mut info = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+        info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+        let configured = SetInformationJobObject(
+            job,
+            JobObjectExtendedLimitInformation,
+            &info as *const _ as *const std::ffi::c_void,
+            std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+        ) != 0;
+        if !configured || AssignProcessToJobObject(job, child.as_raw_handle()) == 0 {
+            log::warn!(
+                "Failed to place backend in a job object: {}",
+                std::io::Error::last_os_error()
+            );
+            CloseHandle(job);
+            return None;
+        }
+        Some(job)
+    }
+}
@@ -0,0 +1,185 @@
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::automation::Automation;
+use crate::backend;
+use crate::logging::{self, LogConfig};
+use crate::restart::RestartTracker;
+use crate::settings;
+use crate::shell_log;
+use crate::store;
+
+/// Folder of the app data dir holding the named profiles.
+pub const PROFILES_DIR: &str = "profiles";
+
+/// File in the app data dir remembering the last profile switched to.
+pub const SELECTION_FILE: &str = "profile.json";
+
+/// Name of the profile that lives directly in the app data dir.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// The active profile: `None` until it is first resolved, then the profile
+/// name (`None` for the default profile).
+static ACTIVE: RwLock<Option<Option<String>>> = RwLock::new(None);
+
+#[derive(Default, Serialize, Deserialize)]
+struct Selection {
+    active: Option<String>,
+}
+
+/// Use `name` for this launch only, e.g. from `--profile paper`.
+fn select(name: &str) -> Result<(), String> {
+    *ACTIVE.write().unwrap() = Some(normalize(name)?);
+    Ok(())
+}
+
+/// Pick up the profile from `--profile <name>` or `TELETRAAN_PROFILE` (see
+/// `config::profile`) before the first data dir is resolved. An invalid name
+/// is logged and the remembered profile used.
+pub fn init(name: Option<&str>) {
+    if let Some(name) = name {
+        if let Err(e) = select(name) {
+            log::error!("Ignoring profile {name:?}: {e}");
+        }
+    }
+}
+
+/// `None` for the default profile, else the validated name.
+fn normalize(name: &str) -> Result<Option<String>, String> {
+    let name = name.trim();
+    if name.is_empty() || name == DEFAULT_PROFILE {
+        return Ok(None);
+    }
+    let valid = name.len() <= 32
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!(
+            "Profile names are up to 32 letters, digits, '-' or '_', got {name:?}"
+        ));
+    }
+    Ok(Some(name.to_string()))
+}
+
+/// The active profile's name, `None` for the default one. Resolved on first
+/// use from the selection remembered in `root`.
+pub fn active(root: &Path) -> Option<String> {
+    if let Some(active) = ACTIVE.read().unwrap().clone() {
+        return active;
+    }
+    let remembered = store::read_json::<Selection>(&root.join(SELECTION_FILE))
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to read the last profile, using the default: {e}");
+            None
+        })
+        .and_then(|selection| selection.active)
+        .and_then(|name| normalize(&name).ok().flatten());
+    ACTIVE.write().unwrap().get_or_insert(remembered).clone()
+}
+
+/// Data dir of the active profile: `root` itself for the default profile,
+/// `root/profiles/<name>` for the others.
+pub fn data_dir(root: &Path) -> PathBuf {
+    match active(root) {
+        Some(name) => root.join(PROFILES_DIR).join(name),
+        None => root.to_path_buf(),
+    }
+}
+
+/// Payload of `list_profiles`.
+#[derive(Clone, Debug, Serialize)]
+pub struct Profiles {
+    pub active: String,
+    /// `default` first, then the other profiles alphabetically.
+    pub profiles: Vec<String>,
+}
+
+/// Payload of the `profile-changed` event.
+#[derive(Clone, Debug, Serialize)]
+struct ProfileChanged {
+    profile: String,
+    data_dir: PathBuf,
+}
+
+/// Tauri command: the active profile and every profile that has a data dir.
+#[tauri::command]
+pub async fn list_profiles(app: AppHandle) -> Result<Profiles, String> {
+    let root = crate::app_data_root(&app)?;
+    let mut profiles: Vec<String> = match std::fs::read_dir(root.join(PROFILES_DIR)) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| normalize(name).is_ok_and(|name| name.is_some()))
+            .collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(format!("Failed to list profiles: {e}")),
+    };
+    profiles.sort();
+    profiles.insert(0, DEFAULT_PROFILE.to_string());
+    Ok(Profiles {
+        active: active(&root).unwrap_or_else(|| DEFAULT_PROFILE.to_string()),
+        profiles,
+    })
+}
+
+/// Tauri command: stop the backend, switch to the profile `name` (created on
+/// first use) and start the backend again on that profile's database, logs
+/// and configuration. The choice is remembered for the next launch.
+#[tauri::command]
+pub async fn switch_profile(
+    app: AppHandle,
+    tracker: State<'_, RestartTracker>,
+    name: String,
+) -> Result<Profiles, String> {
+    let target = normalize(&name)?;
+    let root = crate::app_data_root(&app)?;
+    if active(&root) == target {
+        return list_profiles(app).await;
+    }
+    let label = target
+        .clone()
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    log::info!("Switching to profile {label}");
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || backend::stop_backend(&handle))
+        .await
+        .map_err(|e| format!("Failed to stop backend: {e}"))?;
+
+    *ACTIVE.write().unwrap() = Some(target.clone());
+    if let Err(e) = store::write_json(
+        &root.join(SELECTION_FILE),
+        &Selection {
+            active: target.clone(),
+        },
+    ) {
+        log::warn!("Failed to remember profile {label}: {e}");
+    }
+
+    let data_dir = crate::resolve_data_dir(&app)?;
+    // The new profile's backend writes to a session log in its own `logs/`.
+    logging::reset_session_log();
+    if let Err(e) = shell_log::attach(&data_dir, LogConfig::load(&app)) {
+        log::warn!("Shell log file unavailable: {e}");
+    }
+    settings::reload(&app);
+    *tracker.0.lock().unwrap() = RestartTracker::load(&app).0.into_inner().unwrap();
+    if let Some(automation) = app.try_state::<Automation>() {
+        *automation.0.lock().unwrap() = Automation::load(&app).0.into_inner().unwrap();
+    }
+
+    let _ = app.emit(
+        "profile-changed",
+        ProfileChanged {
+            profile: label,
+            data_dir,
+        },
+    );
+    backend::start_backend(&app).await?;
+    list_profiles(app).await
+}
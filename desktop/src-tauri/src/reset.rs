@@ -0,0 +1,197 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::audit::{self, AuditEvent};
+use crate::backend;
+use crate::backend_update;
+use crate::logging;
+use crate::profile;
+use crate::restart::{self, RestartTracker};
+use crate::settings;
+
+/// What the caller must pass to `reset_app_data`, typed by the user.
+const RESET_CONFIRMATION: &str = "RESET";
+
+/// Folder of the app data root holding the archives of every profile's
+/// resets, outside `profiles/` so they are never taken for a profile.
+pub const RESET_BACKUPS_DIR: &str = "reset-backups";
+
+/// Result of `reset_app_data`.
+#[derive(Clone, Debug, Serialize)]
+pub struct ResetReport {
+    /// Where the previous data dir contents were moved.
+    pub backup_dir: PathBuf,
+    /// Names of the files and folders archived.
+    pub archived: Vec<String>,
+}
+
+/// Tauri command: factory-reset the app. Stops the backend, moves everything
+/// in the data dir except the logs into a timestamped
+/// `reset-backups/<profile>-<time>` folder of the app data root, recreates an
+/// empty `data/` tree and starts a fresh backend with the default settings.
+///
+/// `confirmation` must be `RESET`, so a stray call cannot wipe the data. If
+/// moving anything fails, what was already moved is put back and the old
+/// backend is started again.
+#[tauri::command]
+pub async fn reset_app_data(
+    app: AppHandle,
+    tracker: State<'_, RestartTracker>,
+    confirmation: String,
+) -> Result<ResetReport, String> {
+    if confirmation != RESET_CONFIRMATION {
+        return Err(format!(
+            "Reset not confirmed; pass {RESET_CONFIRMATION:?} to erase the app data"
+        ));
+    }
+    let root = crate::app_data_root(&app)?;
+    let data_dir = crate::resolve_data_dir(&app)?;
+    let profile = profile::active(&root).unwrap_or_else(|| profile::DEFAULT_PROFILE.to_string());
+    log::warn!("Resetting app data in {}", data_dir.display());
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || backend::stop_backend(&handle))
+        .await
+        .map_err(|e| format!("Failed to stop backend: {e}"))?;
+
+    let archive_dir = data_dir.clone();
+    let result =
+        tauri::async_runtime::spawn_blocking(move || archive(&root, &archive_dir, &profile))
+            .await
+            .map_err(|e| format!("Failed to archive app data: {e}"))?;
+
+    // Start again whether or not the archive worked: on failure everything
+    // was moved back, so this is the old install.
+    *tracker.0.lock().unwrap() = RestartTracker::load(&app).0.into_inner().unwrap();
+    let report = match result {
+        Ok(report) => report,
+        Err(e) => {
+            audit::record(&app, AuditEvent::DataReset, false, e.clone());
+            if let Err(start) = backend::start_backend(&app).await {
+                log::error!("Failed to restart backend after aborted reset: {start}");
+            }
+            return Err(e);
+        }
+    };
+    audit::record(
+        &app,
+        AuditEvent::DataReset,
+        true,
+        format!("Archived to {}", report.backup_dir.display()),
+    );
+    crate::resolve_data_dir(&app)?;
+    settings::reload(&app);
+    restart::reload_policy(&app);
+    log::info!(
+        "Archived {} item(s) to {}",
+        report.archived.len(),
+        report.backup_dir.display()
+    );
+    backend::start_backend(&app).await?;
+    Ok(report)
+}
+
+/// Move the contents of `data_dir` (except the logs, which the shell is
+/// writing to) into a new backup folder for `profile` in the
+/// `reset-backups/` folder of `root`.
+fn archive(root: &Path, data_dir: &Path, profile: &str) -> Result<ResetReport, String> {
+    let backups_dir = root.join(RESET_BACKUPS_DIR);
+    std::fs::create_dir_all(&backups_dir)
+        .map_err(|e| format!("Failed to create {}: {e}", backups_dir.display()))?;
+    let backup_dir = backups_dir.join(format!(
+        "{profile}-{}",
+        chrono::Local::now().format("%Y-%m-%dT%H-%M-%S")
+    ));
+    std::fs::create_dir(&backup_dir)
+        .map_err(|e| format!("Failed to create {}: {e}", backup_dir.display()))?;
+
+    let logs_dir = logging::logs_dir(data_dir);
+    let names = std::fs::read_dir(data_dir)
+        .and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.file_name()))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| format!("Failed to read {}: {e}", data_dir.display()))?;
+    let mut moved: Vec<OsString> = Vec::new();
+    for name in names {
+        let from = data_dir.join(&name);
+        // The other profiles live inside the default profile's data dir, and
+        // so do the reset archives, the audit log and the backend updates of
+        // the install.
+        if from == logs_dir
+            || name == profile::PROFILES_DIR
+            || name == RESET_BACKUPS_DIR
+            || name == profile::SELECTION_FILE
+            || name == audit::AUDIT_FILE
+            || name == backend_update::VERSIONS_DIR
+        {
+            continue;
+        }
+        if let Err(e) = std::fs::rename(&from, backup_dir.join(&name)) {
+            let error = format!("Failed to move {}: {e}", from.display());
+            log::error!("{error}; restoring the items already moved");
+            for name in &moved {
+                if let Err(e) = std::fs::rename(backup_dir.join(name), data_dir.join(name)) {
+                    log::error!(
+                        "Failed to restore {} from {}: {e}",
+                        name.to_string_lossy(),
+                        backup_dir.display()
+                    );
+                }
+            }
+            let _ = std::fs::remove_dir(&backup_dir);
+            return Err(error);
+        }
+        moved.push(name);
+    }
+    let mut archived: Vec<String> = moved
+        .iter()
+        .map(|name| name.to_string_lossy().into_owned())
+        .collect();
+    archived.sort();
+    Ok(ResetReport {
+        backup_dir,
+        archived,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archives_a_profile_outside_profiles() {
+        let root = std::env::temp_dir().join(format!("teletraan-reset-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let data_dir = root.join(profile::PROFILES_DIR).join("paper");
+        std::fs::create_dir_all(data_dir.join("data")).unwrap();
+        std::fs::create_dir_all(logging::logs_dir(&data_dir)).unwrap();
+        std::fs::write(data_dir.join("settings.toml"), "version = 3\n").unwrap();
+
+        let report = archive(&root, &data_dir, "paper").unwrap();
+        assert_eq!(
+            report.backup_dir.parent(),
+            Some(root.join(RESET_BACKUPS_DIR).as_path())
+        );
+        assert!(report
+            .backup_dir
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("paper-"));
+        assert_eq!(report.archived, ["data", "settings.toml"]);
+        assert!(report.backup_dir.join("settings.toml").exists());
+        assert!(logging::logs_dir(&data_dir).exists());
+        // Only the profile itself is left in profiles/.
+        let profiles: Vec<_> = std::fs::read_dir(root.join(profile::PROFILES_DIR))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(profiles, ["paper"]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,162 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::settings;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartStrategy {
+    /// Respawn as soon as the crash is detected.
+    Immediate,
+    /// Wait `initial_delay_ms`, doubling per consecutive crash up to `max_delay_ms`.
+    ExponentialBackoff,
+}
+
+/// How the supervisor responds to a crashed backend, the `[restart]` section
+/// of `settings.toml`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RestartPolicy {
+    pub strategy: RestartStrategy,
+    /// Restarts allowed before giving up; 0 disables automatic restart.
+    pub max_attempts: u32,
+    pub initial_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// A backend that stays up this long is considered recovered: the next
+    /// crash starts counting attempts (and backoff) from scratch.
+    pub cooldown_secs: u64,
+    /// Kill and respawn a backend that stops answering health checks, rather
+    /// than only emitting `backend-unhealthy`.
+    pub restart_on_unhealthy: bool,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            strategy: RestartStrategy::ExponentialBackoff,
+            max_attempts: 3,
+            initial_delay_ms: 1_000,
+            max_delay_ms: 30_000,
+            cooldown_secs: 300,
+            restart_on_unhealthy: true,
+        }
+    }
+}
+
+impl RestartPolicy {
+    fn delay_for(&self, attempt: u32) -> Duration {
+        match self.strategy {
+            RestartStrategy::Immediate => Duration::ZERO,
+            RestartStrategy::ExponentialBackoff => {
+                let factor = 1u64 << attempt.saturating_sub(1).min(20);
+                Duration::from_millis(
+                    self.initial_delay_ms
+                        .saturating_mul(factor)
+                        .min(self.max_delay_ms),
+                )
+            }
+        }
+    }
+}
+
+/// Restart bookkeeping, returned as-is by `get_restart_state`.
+#[derive(Clone, Debug, Serialize)]
+pub struct RestartState {
+    pub policy: RestartPolicy,
+    /// Restarts performed since the last cool-down reset.
+    pub attempts: u32,
+    pub last_crash_at: Option<DateTime<Utc>>,
+    /// Set while the supervisor is waiting out a backoff delay.
+    pub next_restart_at: Option<DateTime<Utc>>,
+    /// The policy's attempt budget ran out and the backend was left stopped.
+    pub exhausted: bool,
+}
+
+pub enum RestartDecision {
+    Restart { attempt: u32, delay: Duration },
+    GiveUp { attempts: u32 },
+}
+
+/// Managed state tracking crashes against the restart policy.
+pub struct RestartTracker(pub Mutex<RestartState>);
+
+impl RestartTracker {
+    pub fn load(app: &AppHandle) -> Self {
+        let policy = settings::current(app).restart;
+        log::info!("Backend restart policy: {policy:?}");
+
+        Self(Mutex::new(RestartState {
+            policy,
+            attempts: 0,
+            last_crash_at: None,
+            next_restart_at: None,
+            exhausted: false,
+        }))
+    }
+
+    /// Record a crash and decide whether (and when) to respawn.
+    pub fn record_crash(&self) -> RestartDecision {
+        let mut state = self.0.lock().unwrap();
+        let now = Utc::now();
+
+        let cooled_down = state
+            .last_crash_at
+            .is_some_and(|last| (now - last).num_seconds() >= state.policy.cooldown_secs as i64);
+        if cooled_down {
+            state.attempts = 0;
+        }
+        state.last_crash_at = Some(now);
+
+        if state.attempts >= state.policy.max_attempts {
+            state.exhausted = true;
+            state.next_restart_at = None;
+            return RestartDecision::GiveUp {
+                attempts: state.attempts,
+            };
+        }
+
+        state.attempts += 1;
+        let delay = state.policy.delay_for(state.attempts);
+        state.next_restart_at = chrono::Duration::from_std(delay)
+            .ok()
+            .map(|delay| now + delay);
+        RestartDecision::Restart {
+            attempt: state.attempts,
+            delay,
+        }
+    }
+
+    /// Forget past crashes, e.g. when the user starts the backend by hand.
+    pub fn reset(&self) {
+        let mut state = self.0.lock().unwrap();
+        state.attempts = 0;
+        state.last_crash_at = None;
+        state.next_restart_at = None;
+        state.exhausted = false;
+    }
+
+    /// Clear the pending-restart marker once the respawn has happened.
+    pub fn mark_restarted(&self) {
+        self.0.lock().unwrap().next_restart_at = None;
+    }
+}
+
+/// Take up the policy from the settings after it changed, keeping the
+/// supervisor's progress against the old one.
+pub fn reload_policy(app: &AppHandle) {
+    if let Some(tracker) = app.try_state::<RestartTracker>() {
+        let policy = settings::current(app).restart;
+        log::info!("Backend restart policy: {policy:?}");
+        tracker.0.lock().unwrap().policy = policy;
+    }
+}
+
+/// Tauri command: the restart policy in effect and the supervisor's progress against it.
+#[tauri::command]
+pub async fn get_restart_state(tracker: State<'_, RestartTracker>) -> Result<RestartState, String> {
+    Ok(tracker.0.lock().unwrap().clone())
+}
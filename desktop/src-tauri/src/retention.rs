@@ -0,0 +1,164 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::backend::{BackendProcess, BackendState};
+use crate::backups;
+use crate::logging::{self, LogConfig};
+use crate::settings::{self, RetentionSettings};
+use crate::transport;
+
+/// Deleting years of price history can take a while on a large database.
+const PRUNE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How long the startup cleanup waits for the backend before giving up on
+/// pruning market data.
+const STARTUP_WAIT: Duration = Duration::from_secs(300);
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Result of `run_cleanup`.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CleanupReport {
+    /// Session logs and rotations removed under `logging.keep_sessions`
+    /// and `logging.max_age_days`.
+    pub logs_removed: usize,
+    /// Ids of the backups removed under `backup.keep_backups`.
+    pub backups_removed: Vec<String>,
+    /// Size of the removed logs and backups. Rows deleted from the database
+    /// only free space inside it until the next VACUUM.
+    pub reclaimed_bytes: u64,
+    /// What the backend deleted, when `retention.prune_market_data` is on.
+    pub market_data: Option<PruneReport>,
+    /// Why pruning market data failed; the file cleanup still ran.
+    pub market_data_error: Option<String>,
+}
+
+/// Reported by the backend's `/api/v1/maintenance/prune`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PruneReport {
+    pub candles_deleted: u64,
+    pub outcomes_deleted: u64,
+}
+
+/// Ask the backend to delete price history and finished outcomes past the
+/// configured ages.
+async fn prune_market_data(
+    app: &AppHandle,
+    retention: &RetentionSettings,
+) -> Result<PruneReport, String> {
+    let privileged = transport::privileged_endpoint(app)?;
+
+    #[derive(Serialize)]
+    struct PruneRequest {
+        candle_days: u64,
+        outcome_days: u64,
+    }
+    let resp = transport::Client::new(PRUNE_TIMEOUT)?
+        .post_json(
+            &privileged.endpoint,
+            "/api/v1/maintenance/prune",
+            &privileged.headers(),
+            &PruneRequest {
+                candle_days: retention.candle_days,
+                outcome_days: retention.outcome_days,
+            },
+        )
+        .await
+        .map_err(|e| format!("Failed to prune market data: {e}"))?;
+    if !resp.status().is_success() {
+        return Err(format!(
+            "Failed to prune market data: HTTP {}: {}",
+            resp.status(),
+            String::from_utf8_lossy(resp.body())
+        ));
+    }
+    transport::json(&resp)
+}
+
+/// Apply every retention limit: old logs, excess backups and, when enabled,
+/// old market data in the database.
+async fn cleanup(app: &AppHandle) -> Result<CleanupReport, String> {
+    let settings = settings::current(app);
+    let data_dir = crate::resolve_data_dir(app)?;
+    let log_config = LogConfig::load(app);
+    let keep_backups = settings.backup.keep_backups;
+    let mut report = tauri::async_runtime::spawn_blocking(move || {
+        let (logs_removed, log_bytes) = logging::prune_logs(&data_dir, &log_config);
+        let (backups_removed, backup_bytes) = match backups::Busy::acquire() {
+            Ok(_busy) => backups::prune_backups(&data_dir, keep_backups),
+            Err(_) => {
+                log::info!("Leaving the backups alone while a backup or restore runs");
+                (Vec::new(), 0)
+            }
+        };
+        CleanupReport {
+            logs_removed,
+            backups_removed,
+            reclaimed_bytes: log_bytes + backup_bytes,
+            ..CleanupReport::default()
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to clean up: {e}"))?;
+
+    if settings.retention.prune_market_data {
+        match prune_market_data(app, &settings.retention).await {
+            Ok(pruned) => report.market_data = Some(pruned),
+            Err(e) => {
+                log::warn!("{e}");
+                report.market_data_error = Some(e);
+            }
+        }
+    }
+    log::info!(
+        "Cleanup removed {} log file(s) and {} backup(s), {} bytes; market data: {:?}",
+        report.logs_removed,
+        report.backups_removed.len(),
+        report.reclaimed_bytes,
+        report.market_data
+    );
+    Ok(report)
+}
+
+/// Wait for the backend's first successful health check; false if it is
+/// not healthy within `STARTUP_WAIT`.
+pub async fn wait_until_healthy(app: &AppHandle) -> bool {
+    let started = Instant::now();
+    loop {
+        let state = app.state::<BackendProcess>().0.lock().unwrap().status.state;
+        match state {
+            BackendState::Healthy => return true,
+            BackendState::Failed | BackendState::Stopped => return false,
+            _ if started.elapsed() >= STARTUP_WAIT => return false,
+            _ => tokio::time::sleep(POLL_INTERVAL).await,
+        }
+    }
+}
+
+/// The cleanup run after the backend was started at launch, unless
+/// `retention.on_startup` is off. Pruning market data waits for the backend
+/// to become healthy.
+pub async fn startup(app: &AppHandle) {
+    let settings = settings::current(app);
+    if !settings.retention.on_startup {
+        return;
+    }
+    if settings.retention.prune_market_data && !wait_until_healthy(app).await {
+        log::warn!("Backend not healthy; the startup cleanup may not prune market data");
+    }
+    if let Err(e) = cleanup(app).await {
+        log::warn!("Startup cleanup failed: {e}");
+    }
+}
+
+/// Tauri command: apply the retention limits now. Removes session logs past
+/// `logging.keep_sessions` or `logging.max_age_days` and the oldest backups
+/// past `backup.keep_backups`. With `retention.prune_market_data`, the
+/// backend also deletes price history older than `retention.candle_days`
+/// and finished outcomes older than `retention.outcome_days`.
+#[tauri::command]
+pub async fn run_cleanup(app: AppHandle) -> Result<CleanupReport, String> {
+    cleanup(&app).await
+}
@@ -0,0 +1,455 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::backend::BackendProcess;
+use crate::settings;
+use crate::store;
+use crate::transport;
+
+/// File in the data dir holding the queue, which the backend reads through
+/// `TELETRAAN_REVIEW_QUEUE_FILE` while review is required.
+const QUEUE_FILE: &str = "review_queue.json";
+
+/// How long the backend gets to start tracking an approved insight.
+const TRACK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Serializes read-modify-write cycles on `review_queue.json`.
+pub struct ReviewQueueLock(pub Mutex<()>);
+
+/// The `[review]` section of `settings.toml`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReviewSettings {
+    /// Hold new insights until they are approved: until then they are not
+    /// notified and the backend leaves them out of the track record.
+    pub required: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+/// An insight awaiting (or having received) a human decision.
+///
+/// `insight` is the payload exactly as the backend produced it; `edits` holds
+/// the fields the reviewer changed, overlaid on top when the item is read
+/// back through `effective`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReviewItem {
+    pub insight_id: i64,
+    pub status: ReviewStatus,
+    pub insight: serde_json::Value,
+    #[serde(default)]
+    pub edits: Option<serde_json::Value>,
+    #[serde(default)]
+    pub reason: Option<String>,
+    pub submitted_at: DateTime<Utc>,
+    #[serde(default)]
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+impl ReviewItem {
+    /// The insight with any reviewer edits applied.
+    fn effective(&self) -> serde_json::Value {
+        let mut merged = self.insight.clone();
+        if let (Some(base), Some(serde_json::Value::Object(edits))) =
+            (merged.as_object_mut(), self.edits.as_ref())
+        {
+            for (key, value) in edits {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+        merged
+    }
+}
+
+/// Summary emitted as `review-queue-updated` whenever the queue changes.
+#[derive(Clone, Serialize)]
+struct QueueUpdated {
+    pending: usize,
+}
+
+type Queue = BTreeMap<i64, ReviewItem>;
+
+pub fn queue_file(data_dir: &Path) -> PathBuf {
+    data_dir.join(QUEUE_FILE)
+}
+
+fn queue_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(queue_file(&crate::resolve_data_dir(app)?))
+}
+
+/// Whether new insights wait for approval (`review.required`).
+pub fn required(app: &AppHandle) -> bool {
+    settings::current(app).review.required
+}
+
+fn load(app: &AppHandle) -> Result<Queue, String> {
+    Ok(store::read_json(&queue_path(app)?)?.unwrap_or_default())
+}
+
+fn save_and_notify(app: &AppHandle, queue: &Queue) -> Result<(), String> {
+    store::write_json(&queue_path(app)?, queue)?;
+    let pending = queue
+        .values()
+        .filter(|item| item.status == ReviewStatus::Pending)
+        .count();
+    let _ = app.emit("review-queue-updated", QueueUpdated { pending });
+    Ok(())
+}
+
+/// Tauri command: add newly generated insights to the queue as pending.
+///
+/// Each insight must carry a numeric `id`. Insights already in the queue are
+/// left untouched so a decision is never reset by a re-delivery. Returns how
+/// many items were added.
+#[tauri::command]
+pub async fn enqueue_insights(
+    app: AppHandle,
+    lock: State<'_, ReviewQueueLock>,
+    insights: Vec<serde_json::Value>,
+) -> Result<usize, String> {
+    let _guard = lock.0.lock().unwrap();
+    let mut queue = load(&app)?;
+
+    let mut added = 0;
+    for insight in insights {
+        if add(&mut queue, insight)? {
+            added += 1;
+        }
+    }
+
+    if added > 0 {
+        save_and_notify(&app, &queue)?;
+    }
+    Ok(added)
+}
+
+/// Add `insight` to `queue` as pending unless it is already there. Returns
+/// whether it was added.
+fn add(queue: &mut Queue, insight: serde_json::Value) -> Result<bool, String> {
+    let insight_id = insight
+        .get("id")
+        .and_then(|id| id.as_i64())
+        .ok_or_else(|| "Insight is missing a numeric `id`".to_string())?;
+    if queue.contains_key(&insight_id) {
+        return Ok(false);
+    }
+    queue.insert(
+        insight_id,
+        ReviewItem {
+            insight_id,
+            status: ReviewStatus::Pending,
+            insight,
+            edits: None,
+            reason: None,
+            submitted_at: Utc::now(),
+            reviewed_at: None,
+        },
+    );
+    Ok(true)
+}
+
+/// Whether `insight` must be held back from a consumer such as a
+/// notification: review is required and it is not approved. An insight the
+/// queue does not have yet is added as pending.
+pub fn held(app: &AppHandle, insight: serde_json::Value) -> Result<bool, String> {
+    if !required(app) {
+        return Ok(false);
+    }
+    let lock = app.state::<ReviewQueueLock>();
+    let _guard = lock.0.lock().unwrap();
+    let mut queue = load(app)?;
+    let insight_id = insight.get("id").and_then(|id| id.as_i64());
+    if add(&mut queue, insight)? {
+        save_and_notify(app, &queue)?;
+    }
+    Ok(insight_id
+        .and_then(|id| queue.get(&id))
+        .is_none_or(|item| item.status != ReviewStatus::Approved))
+}
+
+/// Tauri command: list queued items, optionally filtered by status, oldest first.
+#[tauri::command]
+pub async fn list_review_queue(
+    app: AppHandle,
+    lock: State<'_, ReviewQueueLock>,
+    status: Option<ReviewStatus>,
+) -> Result<Vec<ReviewItem>, String> {
+    let _guard = lock.0.lock().unwrap();
+    let mut items: Vec<ReviewItem> = load(&app)?
+        .into_values()
+        .filter(|item| status.is_none_or(|s| item.status == s))
+        .collect();
+    items.sort_by_key(|item| item.submitted_at);
+    Ok(items)
+}
+
+/// Tauri command: record a reviewer decision.
+///
+/// `status` must be `approved` or `rejected`. `edits` (an object of changed
+/// fields) may accompany either decision and replaces any earlier edits;
+/// `reason` is stored verbatim for the audit trail. While review is
+/// required, approving an insight has the backend start tracking its
+/// outcome, with the reviewer's edits applied.
+#[tauri::command]
+pub async fn review_insight(
+    app: AppHandle,
+    lock: State<'_, ReviewQueueLock>,
+    insight_id: i64,
+    status: ReviewStatus,
+    reason: Option<String>,
+    edits: Option<serde_json::Value>,
+) -> Result<ReviewItem, String> {
+    if status == ReviewStatus::Pending {
+        return Err("A review decision must be `approved` or `rejected`".into());
+    }
+    if edits.as_ref().is_some_and(|e| !e.is_object()) {
+        return Err("`edits` must be an object of changed fields".into());
+    }
+
+    let decided = {
+        let _guard = lock.0.lock().unwrap();
+        decide(&app, insight_id, status, reason, edits)?
+    };
+    log::info!("Insight {insight_id} reviewed: {status:?}");
+    if decided.newly_approved && required(&app) {
+        if let Err(e) = track(&app, &decided.item.effective()).await {
+            log::warn!("Failed to start tracking insight {insight_id}: {e}");
+        }
+    }
+    Ok(decided.item)
+}
+
+/// A decision recorded by `decide`.
+struct Decided {
+    item: ReviewItem,
+    /// It was not approved before.
+    newly_approved: bool,
+}
+
+fn decide(
+    app: &AppHandle,
+    insight_id: i64,
+    status: ReviewStatus,
+    reason: Option<String>,
+    edits: Option<serde_json::Value>,
+) -> Result<Decided, String> {
+    let mut queue = load(app)?;
+    let item = queue
+        .get_mut(&insight_id)
+        .ok_or_else(|| format!("Insight {insight_id} is not in the review queue"))?;
+
+    let newly_approved = status == ReviewStatus::Approved && item.status != status;
+    item.status = status;
+    item.reason = reason;
+    if edits.is_some() {
+        item.edits = edits;
+    }
+    item.reviewed_at = Some(Utc::now());
+    let item = item.clone();
+
+    save_and_notify(app, &queue)?;
+    Ok(Decided {
+        item,
+        newly_approved,
+    })
+}
+
+/// Have the backend start tracking the outcome of the approved `insight`,
+/// which it held back from the track record while it was pending. Insights
+/// without a symbol or a buy or sell action are not tracked, as when the
+/// backend tracks them itself.
+async fn track(app: &AppHandle, insight: &serde_json::Value) -> Result<(), String> {
+    let endpoint = app
+        .state::<BackendProcess>()
+        .0
+        .lock()
+        .unwrap()
+        .endpoint
+        .clone();
+    let endpoint = endpoint.ok_or("Backend has not been started")?;
+    start_tracking(&endpoint, insight).await
+}
+
+/// POST `insight` to the backend at `endpoint` as `/api/v1/outcomes/start`
+/// takes it, unless it is not tracked.
+async fn start_tracking(
+    endpoint: &transport::Endpoint,
+    insight: &serde_json::Value,
+) -> Result<(), String> {
+    let direction = match insight.get("action").and_then(|action| action.as_str()) {
+        Some("STRONG_BUY" | "BUY") => "bullish",
+        Some("STRONG_SELL" | "SELL") => "bearish",
+        _ => return Ok(()),
+    };
+    let Some(symbol) = insight.get("primary_symbol").and_then(|s| s.as_str()) else {
+        return Ok(());
+    };
+
+    #[derive(Serialize)]
+    struct StartTracking<'a> {
+        insight_id: &'a serde_json::Value,
+        symbol: &'a str,
+        predicted_direction: &'a str,
+    }
+    let resp = transport::Client::new(TRACK_TIMEOUT)?
+        .post_json(
+            endpoint,
+            "/api/v1/outcomes/start",
+            &[],
+            &StartTracking {
+                insight_id: &insight["id"],
+                symbol,
+                predicted_direction: direction,
+            },
+        )
+        .await?;
+    if !resp.status().is_success() {
+        return Err(format!(
+            "HTTP {}: {}",
+            resp.status(),
+            String::from_utf8_lossy(resp.body())
+        ));
+    }
+    log::info!(
+        "Started tracking approved insight {} ({symbol})",
+        insight["id"]
+    );
+    Ok(())
+}
+
+/// Tauri command: approved insights with reviewer edits applied.
+///
+/// This is the only view downstream consumers (notifications, paper trading,
+/// the track record) should read from, so unreviewed or rejected output
+/// never reaches them.
+#[tauri::command]
+pub async fn get_approved_insights(
+    app: AppHandle,
+    lock: State<'_, ReviewQueueLock>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let _guard = lock.0.lock().unwrap();
+    Ok(load(&app)?
+        .values()
+        .filter(|item| item.status == ReviewStatus::Approved)
+        .map(ReviewItem::effective)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_decision_of_a_redelivered_insight() {
+        let mut queue = Queue::new();
+        let insight = serde_json::json!({"id": 7, "action": "BUY"});
+        assert!(add(&mut queue, insight.clone()).unwrap());
+        queue.get_mut(&7).unwrap().status = ReviewStatus::Approved;
+
+        assert!(!add(&mut queue, insight).unwrap());
+        assert_eq!(queue[&7].status, ReviewStatus::Approved);
+        assert!(add(&mut queue, serde_json::json!({"action": "BUY"})).is_err());
+    }
+
+    #[test]
+    fn applies_the_reviewer_edits() {
+        let mut queue = Queue::new();
+        add(
+            &mut queue,
+            serde_json::json!({"id": 7, "action": "BUY", "title": "NVDA"}),
+        )
+        .unwrap();
+        let item = queue.get_mut(&7).unwrap();
+        item.edits = Some(serde_json::json!({"action": "HOLD"}));
+
+        assert_eq!(
+            item.effective(),
+            serde_json::json!({"id": 7, "action": "HOLD", "title": "NVDA"})
+        );
+        // The backend looks decisions up by the id as a string.
+        let written = serde_json::to_value(&queue).unwrap();
+        assert_eq!(written["7"]["status"], "pending");
+    }
+
+    /// Serve one request on a local port and hand back its request line and
+    /// body.
+    fn fake_backend() -> (
+        transport::Endpoint,
+        std::thread::JoinHandle<(String, serde_json::Value)>,
+    ) {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint =
+            transport::Endpoint::Tcp(format!("http://{}", listener.local_addr().unwrap()));
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\n{}")
+                .unwrap();
+            (
+                request_line.trim().to_string(),
+                serde_json::from_slice(&body).unwrap(),
+            )
+        });
+        (endpoint, server)
+    }
+
+    #[test]
+    fn an_approved_insight_starts_tracking_with_the_edits() {
+        let mut queue = Queue::new();
+        add(
+            &mut queue,
+            serde_json::json!({"id": 7, "action": "BUY", "primary_symbol": "NVDA"}),
+        )
+        .unwrap();
+        let item = queue.get_mut(&7).unwrap();
+        item.status = ReviewStatus::Approved;
+        item.edits = Some(serde_json::json!({"action": "SELL"}));
+        let (endpoint, server) = fake_backend();
+
+        tauri::async_runtime::block_on(start_tracking(&endpoint, &item.effective())).unwrap();
+
+        let (request_line, body) = server.join().unwrap();
+        assert_eq!(request_line, "POST /api/v1/outcomes/start HTTP/1.1");
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "insight_id": 7,
+                "symbol": "NVDA",
+                "predicted_direction": "bearish",
+            })
+        );
+    }
+}
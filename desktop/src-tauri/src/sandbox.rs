@@ -0,0 +1,324 @@
+use std::path::Path;
+use std::process::{Child, Command};
+
+use tauri::AppHandle;
+
+use crate::settings::{self, EnvSettings};
+
+/// Variables passed on from the shell's own environment: what Python, the
+/// claude CLI and the cloud SDKs need to find the user's files, certificates
+/// and credentials. Compared case-insensitively.
+const INHERITED_ENV: &[&str] = &[
+    // Every platform.
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+    "LANG",
+    "LANGUAGE",
+    "TZ",
+    "SSL_CERT_FILE",
+    "SSL_CERT_DIR",
+    "REQUESTS_CA_BUNDLE",
+    "CURL_CA_BUNDLE",
+    "NODE_EXTRA_CA_CERTS",
+    "CLAUDE_CONFIG_DIR",
+    "GOOGLE_APPLICATION_CREDENTIALS",
+    "CLOUDSDK_CONFIG",
+    "TELETRAAN_LOG_LEVEL",
+    // Windows.
+    "SYSTEMROOT",
+    "SYSTEMDRIVE",
+    "WINDIR",
+    "COMSPEC",
+    "PATHEXT",
+    "USERNAME",
+    "USERPROFILE",
+    "HOMEDRIVE",
+    "HOMEPATH",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "PROGRAMDATA",
+    "PROGRAMFILES",
+    "PROGRAMFILES(X86)",
+    "COMPUTERNAME",
+    "NUMBER_OF_PROCESSORS",
+    "PROCESSOR_ARCHITECTURE",
+    "OS",
+];
+
+/// Prefixes of further inherited variables: the locale, the XDG folders
+/// and the AWS and Azure credentials Bedrock and Foundry use.
+const INHERITED_PREFIXES: &[&str] = &["LC_", "XDG_", "AWS_", "AZURE_"];
+
+#[cfg(target_os = "macos")]
+const SANDBOX_EXEC: &str = "/usr/bin/sandbox-exec";
+
+/// The macOS sandbox profile: everything is allowed except writing outside
+/// the data dir, the temporary folders and the claude CLI's configuration.
+#[cfg(target_os = "macos")]
+const MACOS_PROFILE: &str = r#"(version 1)
+(allow default)
+(deny file-write*)
+(allow file-write*
+    (subpath (param "DATA_DIR"))
+    (subpath (param "TMPDIR"))
+    (subpath "/private/tmp")
+    (subpath "/private/var/folders")
+    (prefix (param "CLAUDE_HOME"))
+    (subpath "/dev"))
+"#;
+
+/// How the backend process is confined, from the `backend.inherit_env` and
+/// `backend.sandbox` settings.
+pub struct Sandbox {
+    /// Names inherited on top of `INHERITED_ENV`.
+    inherit_env: Vec<String>,
+    /// Whether to apply the platform's sandbox as well.
+    platform: bool,
+}
+
+impl Sandbox {
+    pub fn load(app: &AppHandle) -> Self {
+        let backend = settings::current(app).backend;
+        Self {
+            inherit_env: backend.inherit_env,
+            platform: backend.sandbox,
+        }
+    }
+
+    /// A command running `program` with a minimal environment: only the
+    /// variables listed above, the pass-through variables of `[env]` and
+    /// those in `backend.inherit_env` are inherited, and everything the
+    /// shell sets is added by the caller. On Unix no file descriptor but
+    /// stdin, stdout and stderr survives into the backend. With
+    /// `backend.sandbox`, on macOS it runs under `sandbox-exec` and may only
+    /// write to `data_dir`, the temporary folders and the claude CLI's
+    /// configuration; on Linux it cannot gain privileges through setuid
+    /// programs.
+    pub fn command(&self, program: &Path, data_dir: &Path) -> Command {
+        let mut command = self.platform_command(program, data_dir);
+        command.env_clear();
+        let passthrough = EnvSettings::names();
+        let mut inherited = Vec::new();
+        for (name, value) in std::env::vars_os() {
+            let Some(key) = name.to_str() else {
+                continue;
+            };
+            let upper = key.to_ascii_uppercase();
+            let allowed = INHERITED_ENV.contains(&upper.as_str())
+                || INHERITED_PREFIXES
+                    .iter()
+                    .any(|prefix| upper.starts_with(prefix))
+                || passthrough.contains(&upper)
+                || self.inherit_env.iter().any(|extra| extra == key);
+            if allowed {
+                inherited.push(key.to_string());
+                command.env(&name, value);
+            }
+        }
+        inherited.sort();
+        log::info!("Backend inherits {inherited:?}");
+        #[cfg(unix)]
+        self.restrict_exec(&mut command);
+        command
+    }
+
+    #[cfg(target_os = "macos")]
+    fn platform_command(&self, program: &Path, data_dir: &Path) -> Command {
+        use std::path::PathBuf;
+
+        if !self.platform {
+            return Command::new(program);
+        }
+        if !Path::new(SANDBOX_EXEC).exists() {
+            log::warn!("{SANDBOX_EXEC} not found; starting the backend without a sandbox");
+            return Command::new(program);
+        }
+        // The sandbox matches resolved paths, so `/var` must be `/private/var`.
+        let resolve = |path: PathBuf| std::fs::canonicalize(&path).unwrap_or(path);
+        let home = std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        let tmp = std::env::var_os("TMPDIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(std::env::temp_dir);
+        let mut claude_home = resolve(home).into_os_string();
+        claude_home.push("/.claude");
+        let define = |name: &str, path: &std::ffi::OsStr| {
+            let mut define = std::ffi::OsString::from(format!("{name}="));
+            define.push(path);
+            define
+        };
+        log::info!("Starting the backend in the macOS sandbox");
+        let mut command = Command::new(SANDBOX_EXEC);
+        command
+            .arg("-D")
+            .arg(define(
+                "DATA_DIR",
+                resolve(data_dir.to_path_buf()).as_os_str(),
+            ))
+            .arg("-D")
+            .arg(define("TMPDIR", resolve(tmp).as_os_str()))
+            .arg("-D")
+            .arg(define("CLAUDE_HOME", &claude_home))
+            .arg("-p")
+            .arg(MACOS_PROFILE)
+            .arg(program);
+        command
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn platform_command(&self, program: &Path, _data_dir: &Path) -> Command {
+        Command::new(program)
+    }
+
+    /// Mark every descriptor above stderr close-on-exec in the forked child,
+    /// so one a library left inheritable (a socket, the database, a log)
+    /// does not reach the backend, and with `backend.sandbox` on Linux set
+    /// `no_new_privs`.
+    #[cfg(unix)]
+    fn restrict_exec(&self, command: &mut Command) {
+        use std::os::unix::process::CommandExt;
+
+        // Listed before forking, since the child may not allocate. The
+        // floor covers descriptors opened on other threads meanwhile.
+        let highest = std::fs::read_dir("/dev/fd")
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<i32>().ok())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .unwrap_or(0)
+            .max(255);
+        let no_new_privs = self.platform && cfg!(target_os = "linux");
+        // SAFETY: the closure only makes async-signal-safe calls (`fcntl`,
+        // `prctl`) and does not allocate.
+        unsafe {
+            command.pre_exec(move || {
+                for fd in 3..=highest {
+                    libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+                }
+                #[cfg(target_os = "linux")]
+                if no_new_privs && libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                #[cfg(not(target_os = "linux"))]
+                let _ = no_new_privs;
+                Ok(())
+            });
+        }
+    }
+
+    /// Confine the spawned backend further where that can only be done once
+    /// it runs: with `backend.sandbox` on Windows, every privilege but
+    /// `SeChangeNotifyPrivilege` is removed from its token.
+    ///
+    /// The standard library cannot spawn a process with a restricted token,
+    /// so this happens right after spawning, before the backend has started
+    /// any subprocess; those inherit the reduced token. Handles are left
+    /// alone on Windows, where the standard library and Tauri open them
+    /// non-inheritable.
+    pub fn restrict(&self, child: &Child) {
+        #[cfg(windows)]
+        if self.platform {
+            match remove_privileges(child) {
+                Ok(removed) => log::info!("Removed {removed} privileges from the backend token"),
+                Err(e) => log::warn!("Failed to reduce the backend token: {e}"),
+            }
+        }
+        #[cfg(not(windows))]
+        let _ = child;
+    }
+}
+
+/// Remove every privilege but `SeChangeNotifyPrivilege` (needed to traverse
+/// folders) from the primary token of `child`. Returns how many were removed.
+#[cfg(windows)]
+fn remove_privileges(child: &Child) -> Result<usize, String> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::{CloseHandle, ERROR_NOT_ALL_ASSIGNED, HANDLE, LUID};
+    use windows_sys::Win32::Security::{
+        AdjustTokenPrivileges, GetTokenInformation, LookupPrivilegeValueW, TokenPrivileges,
+        LUID_AND_ATTRIBUTES, SE_CHANGE_NOTIFY_NAME, SE_PRIVILEGE_REMOVED, TOKEN_ADJUST_PRIVILEGES,
+        TOKEN_PRIVILEGES, TOKEN_QUERY,
+    };
+    use windows_sys::Win32::System::Threading::OpenProcessToken;
+
+    let last_error = || std::io::Error::last_os_error();
+    // SAFETY: plain Win32 calls on the live child handle; every pointer
+    // refers to a live local, the privilege array is read within the length
+    // the token reported, and the token handle is closed on every path.
+    unsafe {
+        let mut token: HANDLE = std::ptr::null_mut();
+        if OpenProcessToken(
+            child.as_raw_handle(),
+            TOKEN_QUERY | TOKEN_ADJUST_PRIVILEGES,
+            &mut token,
+        ) == 0
+        {
+            return Err(format!("Failed to open the token: {}", last_error()));
+        }
+        let result = (|| {
+            let mut len = 0u32;
+            GetTokenInformation(token, TokenPrivileges, std::ptr::null_mut(), 0, &mut len);
+            // u64s, so the buffer is aligned for TOKEN_PRIVILEGES.
+            let mut buffer = vec![0u64; (len as usize).div_ceil(8)];
+            if GetTokenInformation(
+                token,
+                TokenPrivileges,
+                buffer.as_mut_ptr().cast(),
+                len,
+                &mut len,
+            ) == 0
+            {
+                return Err(format!("Failed to read the privileges: {}", last_error()));
+            }
+            let privileges = buffer.as_mut_ptr() as *mut TOKEN_PRIVILEGES;
+            let entries: *mut LUID_AND_ATTRIBUTES = (*privileges).Privileges.as_mut_ptr();
+            let count = (*privileges).PrivilegeCount as usize;
+
+            let mut keep = LUID::default();
+            if LookupPrivilegeValueW(std::ptr::null(), SE_CHANGE_NOTIFY_NAME, &mut keep) == 0 {
+                return Err(format!("Failed to look up a privilege: {}", last_error()));
+            }
+            // Move the privileges to remove to the front, and pass only those.
+            let mut removed = 0;
+            for i in 0..count {
+                let entry = *entries.add(i);
+                if entry.Luid.LowPart == keep.LowPart && entry.Luid.HighPart == keep.HighPart {
+                    continue;
+                }
+                *entries.add(removed) = LUID_AND_ATTRIBUTES {
+                    Luid: entry.Luid,
+                    Attributes: SE_PRIVILEGE_REMOVED,
+                };
+                removed += 1;
+            }
+            if removed == 0 {
+                return Ok(0);
+            }
+            (*privileges).PrivilegeCount = removed as u32;
+            if AdjustTokenPrivileges(
+                token,
+                0,
+                privileges,
+                0,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            ) == 0
+                || last_error().raw_os_error() == Some(ERROR_NOT_ALL_ASSIGNED as i32)
+            {
+                return Err(format!("Failed to remove privileges: {}", last_error()));
+            }
+            Ok(removed)
+        })();
+        CloseHandle(token);
+        result
+    }
+}
@@ -0,0 +1,492 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::audit::{self, AuditEvent};
+use crate::config_watch;
+use crate::diagnostics;
+use crate::launch;
+use crate::os_auth;
+use crate::redact;
+use crate::settings::EnvSettings;
+use crate::store;
+
+/// Entries returned by `get_secret_access_log` when no limit is given.
+const DEFAULT_ACCESS_LIMIT: usize = 500;
+
+/// Once the access log grows past this, it is trimmed to the most recent
+/// `KEEP_ON_TRIM` entries.
+const MAX_ACCESS_LOG_BYTES: u64 = 1024 * 1024;
+const KEEP_ON_TRIM: usize = 2_000;
+
+/// What was done with a secret.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretAction {
+    Read,
+    Store,
+    Delete,
+}
+
+/// One access to a stored secret, as kept in `secret_access.jsonl`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SecretAccess {
+    pub at: DateTime<Utc>,
+    pub name: String,
+    pub action: SecretAction,
+    /// What asked for it: `backend` when it is passed to a spawned backend,
+    /// otherwise the command.
+    pub component: String,
+}
+
+/// The variables a secret can be stored for: the keys and tokens `[env]`
+/// accepts.
+pub fn names() -> Vec<String> {
+    EnvSettings::names()
+        .into_iter()
+        .filter(|name| launch::is_secret(name))
+        .collect()
+}
+
+fn check_name(name: &str) -> Result<(), String> {
+    if names().iter().any(|known| known == name) {
+        return Ok(());
+    }
+    Err(format!(
+        "{name:?} cannot be stored; expected one of {}",
+        names().join(", ")
+    ))
+}
+
+/// In the app data root, like the keychain entries shared by every profile.
+fn access_log_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::app_data_root(app)?.join("secret_access.jsonl"))
+}
+
+/// Append an access to the log and the audit log. Failing to write it is
+/// logged, not fatal.
+fn record_access(app: &AppHandle, name: &str, action: SecretAction, component: &str) {
+    let event = match action {
+        SecretAction::Read => AuditEvent::SecretRead,
+        SecretAction::Store => AuditEvent::SecretStore,
+        SecretAction::Delete => AuditEvent::SecretDelete,
+    };
+    audit::record(app, event, true, format!("{name} by {component}"));
+    let access = SecretAccess {
+        at: Utc::now(),
+        name: name.to_string(),
+        action,
+        component: component.to_string(),
+    };
+    let result = access_log_path(app).and_then(|path| {
+        store::append_jsonl(&path, &access)?;
+        store::trim_jsonl(&path, MAX_ACCESS_LOG_BYTES, KEEP_ON_TRIM)
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to record secret access: {e}");
+    }
+}
+
+/// `value` with all but its first 3 and last 4 characters hidden, e.g.
+/// `sk-…abcd`; fully hidden when that would show too much of it.
+fn mask(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() < 12 {
+        return "…".to_string();
+    }
+    let head: String = chars[..3].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{head}…{tail}")
+}
+
+/// Keychain service the secrets are stored under: the app identifier, so
+/// development builds keep their own.
+fn service(app: &AppHandle) -> String {
+    app.config().identifier.clone()
+}
+
+#[cfg(target_os = "macos")]
+mod keychain {
+    use security_framework::passwords;
+    use security_framework_sys::base::errSecItemNotFound;
+
+    pub fn get(service: &str, name: &str) -> Result<Option<String>, String> {
+        match passwords::get_generic_password(service, name) {
+            Ok(value) => String::from_utf8(value)
+                .map(Some)
+                .map_err(|e| format!("The keychain item {name} is not text: {e}")),
+            Err(e) if e.code() == errSecItemNotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read {name} from the keychain: {e}")),
+        }
+    }
+
+    pub fn set(service: &str, name: &str, value: &str) -> Result<(), String> {
+        passwords::set_generic_password(service, name, value.as_bytes())
+            .map_err(|e| format!("Failed to store {name} in the keychain: {e}"))
+    }
+
+    pub fn delete(service: &str, name: &str) -> Result<bool, String> {
+        match passwords::delete_generic_password(service, name) {
+            Ok(()) => Ok(true),
+            Err(e) if e.code() == errSecItemNotFound => Ok(false),
+            Err(e) => Err(format!("Failed to delete {name} from the keychain: {e}")),
+        }
+    }
+}
+
+#[cfg(windows)]
+mod keychain {
+    use windows_sys::Win32::Foundation::{GetLastError, ERROR_NOT_FOUND};
+    use windows_sys::Win32::Security::Credentials::{
+        CredDeleteW, CredFree, CredReadW, CredWriteW, CREDENTIALW, CRED_PERSIST_LOCAL_MACHINE,
+        CRED_TYPE_GENERIC,
+    };
+
+    /// `service/name`, as a NUL-terminated UTF-16 Credential Manager target.
+    fn target(service: &str, name: &str) -> Vec<u16> {
+        format!("{service}/{name}")
+            .encode_utf16()
+            .chain(Some(0))
+            .collect()
+    }
+
+    pub fn get(service: &str, name: &str) -> Result<Option<String>, String> {
+        let target = target(service, name);
+        let mut credential: *mut CREDENTIALW = std::ptr::null_mut();
+        // SAFETY: `target` is NUL-terminated, and a credential returned by
+        // CredReadW is valid until it is passed to CredFree.
+        unsafe {
+            if CredReadW(target.as_ptr(), CRED_TYPE_GENERIC, 0, &mut credential) == 0 {
+                return match GetLastError() {
+                    ERROR_NOT_FOUND => Ok(None),
+                    code => Err(format!(
+                        "Failed to read {name} from the Credential Manager (error {code})"
+                    )),
+                };
+            }
+            let blob = std::slice::from_raw_parts(
+                (*credential).CredentialBlob,
+                (*credential).CredentialBlobSize as usize,
+            )
+            .to_vec();
+            CredFree(credential as *const _);
+            String::from_utf8(blob)
+                .map(Some)
+                .map_err(|e| format!("The credential {name} is not text: {e}"))
+        }
+    }
+
+    pub fn set(service: &str, name: &str, value: &str) -> Result<(), String> {
+        let mut target = target(service, name);
+        let mut user: Vec<u16> = name.encode_utf16().chain(Some(0)).collect();
+        let mut blob = value.as_bytes().to_vec();
+        let credential = CREDENTIALW {
+            Type: CRED_TYPE_GENERIC,
+            TargetName: target.as_mut_ptr(),
+            UserName: user.as_mut_ptr(),
+            CredentialBlobSize: blob.len() as u32,
+            CredentialBlob: blob.as_mut_ptr(),
+            Persist: CRED_PERSIST_LOCAL_MACHINE,
+            ..Default::default()
+        };
+        // SAFETY: every pointer in `credential` outlives the call.
+        if unsafe { CredWriteW(&credential, 0) } == 0 {
+            let code = unsafe { GetLastError() };
+            return Err(format!(
+                "Failed to store {name} in the Credential Manager (error {code})"
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn delete(service: &str, name: &str) -> Result<bool, String> {
+        let target = target(service, name);
+        // SAFETY: `target` is NUL-terminated.
+        unsafe {
+            if CredDeleteW(target.as_ptr(), CRED_TYPE_GENERIC, 0) != 0 {
+                return Ok(true);
+            }
+            match GetLastError() {
+                ERROR_NOT_FOUND => Ok(false),
+                code => Err(format!(
+                    "Failed to delete {name} from the Credential Manager (error {code})"
+                )),
+            }
+        }
+    }
+}
+
+/// The Secret Service (GNOME Keyring, KWallet) through libsecret's
+/// `secret-tool`, which takes the secret on stdin rather than the command
+/// line.
+#[cfg(all(unix, not(target_os = "macos")))]
+mod keychain {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    use super::Command;
+
+    fn secret_tool(args: &[&str], input: Option<&str>) -> Result<std::process::Output, String> {
+        let mut child = Command::new("secret-tool")
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => {
+                    "secret-tool was not found; install libsecret-tools to store secrets"
+                        .to_string()
+                }
+                _ => format!("Failed to run secret-tool: {e}"),
+            })?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(input.unwrap_or_default().as_bytes())
+                .map_err(|e| format!("Failed to run secret-tool: {e}"))?;
+        }
+        child
+            .wait_with_output()
+            .map_err(|e| format!("Failed to run secret-tool: {e}"))
+    }
+
+    fn stderr(output: &std::process::Output) -> String {
+        String::from_utf8_lossy(&output.stderr).trim().to_string()
+    }
+
+    pub fn get(service: &str, name: &str) -> Result<Option<String>, String> {
+        let output = secret_tool(&["lookup", "service", service, "account", name], None)?;
+        // `lookup` exits with 1 and prints nothing when there is no such item.
+        if !output.status.success() {
+            let error = stderr(&output);
+            if error.is_empty() {
+                return Ok(None);
+            }
+            return Err(format!("Failed to read {name} from the keyring: {error}"));
+        }
+        String::from_utf8(output.stdout)
+            .map(Some)
+            .map_err(|e| format!("The keyring item {name} is not text: {e}"))
+    }
+
+    pub fn set(service: &str, name: &str, value: &str) -> Result<(), String> {
+        let label = format!("Teletraan {name}");
+        let output = secret_tool(
+            &[
+                "store", "--label", &label, "service", service, "account", name,
+            ],
+            Some(value),
+        )?;
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to store {name} in the keyring: {}",
+                stderr(&output)
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn delete(service: &str, name: &str) -> Result<bool, String> {
+        let existed = get(service, name)?.is_some();
+        let output = secret_tool(&["clear", "service", service, "account", name], None)?;
+        if !output.status.success() && existed {
+            return Err(format!(
+                "Failed to delete {name} from the keyring: {}",
+                stderr(&output)
+            ));
+        }
+        Ok(existed)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod keychain {
+    const UNSUPPORTED: &str = "No keychain is available on this platform";
+
+    pub fn get(_service: &str, _name: &str) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+
+    pub fn set(_service: &str, _name: &str, _value: &str) -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub fn delete(_service: &str, _name: &str) -> Result<bool, String> {
+        Err(UNSUPPORTED.to_string())
+    }
+}
+
+/// The stored secrets, as `(name, value)`, read on behalf of `component`
+/// and recorded in the access log. Their values are masked in every log from
+/// now on. A keychain that cannot be read is logged and skipped.
+pub fn stored(app: &AppHandle, component: &str) -> Vec<(String, String)> {
+    let service = service(app);
+    names()
+        .into_iter()
+        .filter_map(|name| match keychain::get(&service, &name) {
+            Ok(Some(value)) if !value.is_empty() => {
+                redact::register_secret(&value);
+                record_access(app, &name, SecretAction::Read, component);
+                Some((name, value))
+            }
+            Ok(_) => None,
+            Err(e) => {
+                log::warn!("{e}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Keychain account of the SQLCipher key of the database, one per install.
+const DATABASE_KEY: &str = "TELETRAAN_DB_KEY";
+
+/// The key the database is encrypted with, read on behalf of `component`. With
+/// `create`, a new random one is stored when there is none yet.
+pub fn database_key(
+    app: &AppHandle,
+    create: bool,
+    component: &str,
+) -> Result<Option<String>, String> {
+    let service = service(app);
+    let key = match keychain::get(&service, DATABASE_KEY)? {
+        Some(key) if !key.is_empty() => {
+            record_access(app, DATABASE_KEY, SecretAction::Read, component);
+            key
+        }
+        _ if create => {
+            let mut bytes = [0u8; 32];
+            ring::rand::SecureRandom::fill(&ring::rand::SystemRandom::new(), &mut bytes)
+                .map_err(|_| "Failed to generate the database key".to_string())?;
+            let key = hex::encode(bytes);
+            keychain::set(&service, DATABASE_KEY, &key)?;
+            record_access(app, DATABASE_KEY, SecretAction::Store, component);
+            log::info!("Stored a new database key in the keychain");
+            key
+        }
+        _ => return Ok(None),
+    };
+    redact::register_secret(&key);
+    Ok(Some(key))
+}
+
+/// Store `value` for the variable `name`, found in the `[env]` of
+/// `settings.toml`, in the keychain, which holds the keys and tokens instead
+/// of the file. A running backend asks for a restart, as with `set_secret`.
+pub fn move_from_settings(app: &AppHandle, name: &str, value: &str) -> Result<(), String> {
+    check_name(name)?;
+    redact::register_secret(value);
+    keychain::set(&service(app), name, value)?;
+    record_access(app, name, SecretAction::Store, "settings.toml");
+    config_watch::require_restart(app, vec![format!("secret.{name}")]);
+    Ok(())
+}
+
+/// Set the stored secrets on the backend command.
+pub fn apply(app: &AppHandle, command: &mut Command) {
+    let secrets = stored(app, "backend");
+    if secrets.is_empty() {
+        return;
+    }
+    let names: Vec<&str> = secrets.iter().map(|(name, _)| name.as_str()).collect();
+    log::info!("Passing secrets from the keychain to backend: {names:?}");
+    command.envs(secrets.iter().map(|(name, value)| (name, value)));
+}
+
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|e| format!("Failed to access the keychain: {e}"))
+        .and_then(|result| result)
+}
+
+/// Tauri command: store `value` for the variable `name` (e.g.
+/// `ANTHROPIC_API_KEY`) in the platform keychain: Keychain Services on macOS,
+/// the Credential Manager on Windows, the Secret Service on Linux. The next
+/// backend gets it in its environment; a running one asks for a restart.
+#[tauri::command]
+pub async fn set_secret(app: AppHandle, name: String, value: String) -> Result<(), String> {
+    check_name(&name)?;
+    let value = value.trim().to_string();
+    if value.is_empty() || value.contains('\0') {
+        return Err(format!("Invalid value for {name}"));
+    }
+    redact::register_secret(&value);
+    let (service, key) = (service(&app), name.clone());
+    blocking(move || keychain::set(&service, &key, &value)).await?;
+    record_access(&app, &name, SecretAction::Store, "set_secret");
+    log::info!("Stored {name} in the keychain");
+    config_watch::require_restart(&app, vec![format!("secret.{name}")]);
+    Ok(())
+}
+
+/// Tauri command: remove the stored secret `name`. Returns whether there was
+/// one.
+#[tauri::command]
+pub async fn delete_secret(app: AppHandle, name: String) -> Result<bool, String> {
+    check_name(&name)?;
+    let (service, key) = (service(&app), name.clone());
+    let deleted = blocking(move || keychain::delete(&service, &key)).await?;
+    if deleted {
+        record_access(&app, &name, SecretAction::Delete, "delete_secret");
+        log::info!("Deleted {name} from the keychain");
+        config_watch::require_restart(&app, vec![format!("secret.{name}")]);
+    }
+    Ok(deleted)
+}
+
+/// Tauri command: the names of the stored secrets. Their values never leave
+/// the shell.
+#[tauri::command]
+pub async fn list_secret_names(app: AppHandle) -> Result<Vec<String>, String> {
+    blocking(move || {
+        Ok(stored(&app, "list_secret_names")
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect())
+    })
+    .await
+}
+
+/// Tauri command: the stored secret `name`, masked (e.g. `sk-…abcd`), so the
+/// user can tell which key is set without it leaving the shell; `null` when
+/// none is stored. Asks the user to authenticate first (see `os_auth`).
+#[tauri::command]
+pub async fn get_secret_masked(app: AppHandle, name: String) -> Result<Option<String>, String> {
+    check_name(&name)?;
+    os_auth::require(&app, &format!("show {name}")).await?;
+    let (service, key) = (service(&app), name.clone());
+    let value = blocking(move || keychain::get(&service, &key)).await?;
+    let Some(value) = value.filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+    redact::register_secret(&value);
+    record_access(&app, &name, SecretAction::Read, "get_secret_masked");
+    Ok(Some(mask(&value)))
+}
+
+/// Tauri command: the recorded accesses to stored secrets, oldest first,
+/// optionally only those since `since`.
+#[tauri::command]
+pub async fn get_secret_access_log(
+    app: AppHandle,
+    limit: Option<usize>,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<SecretAccess>, String> {
+    let path = access_log_path(&app)?;
+    let lines = match diagnostics::tail_lines(&path, limit.unwrap_or(DEFAULT_ACCESS_LIMIT)) {
+        Ok(lines) => lines,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+    Ok(lines
+        .iter()
+        .filter_map(|line| serde_json::from_str::<SecretAccess>(line).ok())
+        .filter(|access| since.is_none_or(|since| access.at >= since))
+        .collect())
+}
@@ -0,0 +1,1381 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::announce::AnnouncementSettings;
+use crate::audit::{self, AuditEvent};
+use crate::automation::AutomationSettings;
+use crate::benchmark::BenchmarkSettings;
+use crate::config;
+use crate::config_watch;
+use crate::features::{self, FeatureConfig};
+use crate::health::HealthCheckConfig;
+use crate::launch::{self, LaunchOverrides};
+use crate::logging::LogConfig;
+use crate::port::BindConfig;
+use crate::redact;
+use crate::restart::{self, RestartPolicy};
+use crate::review::ReviewSettings;
+use crate::secrets;
+use crate::shell_log;
+use crate::store;
+
+/// The settings file in the data dir.
+const SETTINGS_FILE: &str = "settings.toml";
+
+/// Version of the settings layout written by this build. Files without a
+/// `version` key predate versioning and are version 1.
+const CURRENT_VERSION: u32 = 3;
+
+/// A step from the previous layout to `to`.
+struct Migration {
+    to: u32,
+    /// Keys moved, as `(old, new)` `<section>.<name>` pairs.
+    renamed: &'static [(&'static str, &'static str)],
+    /// JSON config files of the data dir whose keys move into a section.
+    merged: &'static [MergedFile],
+}
+
+/// A JSON config file that became a section of `settings.toml`.
+struct MergedFile {
+    file: &'static str,
+    section: &'static str,
+    /// The key the whole file moves to, for a file that is not an object.
+    as_key: Option<&'static str>,
+    /// The only keys that move, for a file that also holds state and stays;
+    /// empty moves every key and retires the file.
+    only: &'static [&'static str],
+}
+
+const fn merged(file: &'static str, section: &'static str) -> MergedFile {
+    MergedFile {
+        file,
+        section,
+        as_key: None,
+        only: &[],
+    }
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        to: 2,
+        renamed: &[("general.export_dir", "export.dir")],
+        merged: &[],
+    },
+    Migration {
+        to: 3,
+        renamed: &[],
+        merged: &[
+            merged("backend.json", "backend"),
+            merged("logging.json", "logging"),
+            merged("health_check.json", "health_check"),
+            merged("restart_policy.json", "restart"),
+            merged("announcements.json", "announcements"),
+            merged("features.json", "features"),
+            MergedFile {
+                as_key: Some("symbols"),
+                ..merged("benchmarks.json", "benchmarks")
+            },
+            MergedFile {
+                only: &["max_consecutive_risk_failures", "daily_loss_limit_pct"],
+                ..merged("automation.json", "automation")
+            },
+        ],
+    },
+];
+
+/// User settings of the shell, stored as `settings.toml` in the data dir.
+///
+/// Keys are addressed as `<section>.<name>`, e.g. `export.dir`. Optional
+/// settings are left out of the file while unset.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Layout version, see `CURRENT_VERSION`.
+    pub version: u32,
+    pub general: GeneralSettings,
+    pub export: ExportSettings,
+    pub logging: LoggingSettings,
+    pub backend: BackendSettings,
+    pub backup: BackupSettings,
+    pub retention: RetentionSettings,
+    pub updates: UpdateSettings,
+    pub env: EnvSettings,
+    pub health_check: HealthCheckConfig,
+    pub restart: RestartPolicy,
+    pub announcements: AnnouncementSettings,
+    pub features: FeatureConfig,
+    pub automation: AutomationSettings,
+    pub benchmarks: BenchmarkSettings,
+    pub review: ReviewSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: CURRENT_VERSION,
+            general: GeneralSettings::default(),
+            export: ExportSettings::default(),
+            logging: LoggingSettings::default(),
+            backend: BackendSettings::default(),
+            backup: BackupSettings::default(),
+            retention: RetentionSettings::default(),
+            updates: UpdateSettings::default(),
+            env: EnvSettings::default(),
+            health_check: HealthCheckConfig::default(),
+            restart: RestartPolicy::default(),
+            announcements: AnnouncementSettings::default(),
+            features: FeatureConfig::default(),
+            automation: AutomationSettings::default(),
+            benchmarks: BenchmarkSettings::default(),
+            review: ReviewSettings::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeneralSettings {
+    /// Check the claude CLI, certificates and webview before the backend
+    /// starts (see `check_dependencies`).
+    pub check_dependencies: bool,
+    /// Ask for Touch ID, Windows Hello or the account password before a
+    /// key is shown or a backup decrypted (see `os_auth`).
+    pub require_os_auth: bool,
+}
+
+impl Default for GeneralSettings {
+    fn default() -> Self {
+        Self {
+            check_dependencies: true,
+            require_os_auth: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportSettings {
+    /// Where exports, log archives and diagnostics bundles go when the caller
+    /// gives no path; the Downloads folder when unset.
+    pub dir: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingSettings {
+    /// The shell's log level (`trace` to `error`, or `off`) at startup; `RUST_LOG`
+    /// or `info` when unset.
+    pub shell_level: Option<String>,
+    /// Rotation and retention of the log files, e.g. `logging.max_file_mb`.
+    #[serde(flatten)]
+    pub files: LogConfig,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackendSettings {
+    /// How long a stopping backend gets to exit before it is killed, when no
+    /// analysis is running. `TELETRAAN_SHUTDOWN_GRACE_SECS` takes precedence.
+    pub shutdown_grace_secs: u64,
+    /// Use the backend at this URL (e.g. `uvicorn` running from source with
+    /// `--reload`) instead of spawning the bundled one. `--backend-url` and
+    /// `TELETRAAN_BACKEND_URL` take precedence.
+    pub external_url: Option<String>,
+    /// Variables of the shell's environment passed on to the backend besides
+    /// the ones it needs to run (see `sandbox`), e.g. `["DEBUG"]`.
+    pub inherit_env: Vec<String>,
+    /// Also run the backend in the platform's sandbox: writes confined to the
+    /// data dir on macOS, no privileges in its token on Windows, no setuid
+    /// on Linux. `TELETRAAN_BACKEND_SANDBOX` takes precedence.
+    pub sandbox: bool,
+    /// Where the backend listens, e.g. `backend.port`.
+    #[serde(flatten)]
+    pub bind: BindConfig,
+    /// `backend.extra_args` and `backend.extra_env`.
+    #[serde(flatten)]
+    pub launch: LaunchOverrides,
+}
+
+impl Default for BackendSettings {
+    fn default() -> Self {
+        Self {
+            shutdown_grace_secs: 5,
+            external_url: None,
+            inherit_env: Vec::new(),
+            sandbox: false,
+            bind: BindConfig::default(),
+            launch: LaunchOverrides::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    /// Copy the database into `backups/` before each backend start.
+    pub snapshot_on_start: bool,
+    /// Pre-start snapshots kept, newest first.
+    pub keep_snapshots: usize,
+    /// Backups from `create_backup` and `restore_backup` kept by
+    /// `run_cleanup`, newest first; 0 keeps them all.
+    pub keep_backups: usize,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            snapshot_on_start: true,
+            keep_snapshots: 5,
+            keep_backups: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionSettings {
+    /// Run `run_cleanup` once at startup.
+    pub on_startup: bool,
+    /// Have the backend delete old price history and finished outcomes.
+    pub prune_market_data: bool,
+    /// Price history older than this is deleted; 0 keeps it all.
+    pub candle_days: u64,
+    /// Finished outcomes whose window closed longer ago than this are
+    /// deleted; 0 keeps them all.
+    pub outcome_days: u64,
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        Self {
+            on_startup: true,
+            prune_market_data: false,
+            candle_days: 730,
+            outcome_days: 0,
+        }
+    }
+}
+
+/// Release channel the updater follows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    /// Pre-releases, ahead of stable.
+    Beta,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdateSettings {
+    /// Which release feed to check (see `updater`).
+    pub channel: UpdateChannel,
+    /// Check the feed at startup and emit `update-available`.
+    pub check_on_startup: bool,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self {
+            channel: UpdateChannel::Stable,
+            check_on_startup: true,
+        }
+    }
+}
+
+/// Environment variables passed to the backend, under their own names
+/// (`env.HTTP_PROXY`). Only these can be set, so a typo or a variable the
+/// shell manages itself is rejected instead of silently passed along. The
+/// keys and tokens among them are held by the keychain (see `secrets`) and
+/// rejected here, so the file cannot hold or override them.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct EnvSettings {
+    pub http_proxy: Option<String>,
+    pub https_proxy: Option<String>,
+    pub no_proxy: Option<String>,
+    pub llm_provider: Option<String>,
+    pub anthropic_api_key: Option<String>,
+    pub anthropic_auth_token: Option<String>,
+    pub anthropic_base_url: Option<String>,
+    pub anthropic_model: Option<String>,
+    pub api_timeout_ms: Option<String>,
+    pub claude_code_use_bedrock: Option<String>,
+    pub aws_region: Option<String>,
+    pub claude_code_use_vertex: Option<String>,
+    pub vertex_project: Option<String>,
+    pub vertex_region: Option<String>,
+    pub claude_code_use_foundry: Option<String>,
+    pub fred_api_key: Option<String>,
+    pub finnhub_api_key: Option<String>,
+}
+
+impl EnvSettings {
+    /// Every variable the section can hold, set or not.
+    pub fn names() -> Vec<String> {
+        match serde_json::to_value(Self::default()) {
+            Ok(serde_json::Value::Object(vars)) => vars.into_iter().map(|(name, _)| name).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The variables that are set, as `(name, value)`.
+    pub fn vars(&self) -> Vec<(String, String)> {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(vars)) => vars
+                .into_iter()
+                .filter_map(|(name, value)| match value {
+                    serde_json::Value::String(value) if !value.is_empty() => Some((name, value)),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Check the values the types alone cannot.
+    fn validate(&self) -> Result<(), String> {
+        if let Some(level) = &self.logging.shell_level {
+            parse_level(level)?;
+        }
+        for (name, value) in self.env.vars() {
+            if launch::is_secret(&name) {
+                return Err(format!(
+                    "env.{name} is a secret; store it in the keychain with set_secret instead"
+                ));
+            }
+            if value.contains('\0') {
+                return Err(format!("env.{name} contains a NUL byte"));
+            }
+        }
+        for name in &self.backend.inherit_env {
+            if name.is_empty() || name.contains(['=', '\0']) {
+                return Err(format!("backend.inherit_env has an invalid name {name:?}"));
+            }
+        }
+        if let Some(dir) = &self.export.dir {
+            if !dir.is_absolute() {
+                return Err(format!(
+                    "export.dir must be an absolute path, got {}",
+                    dir.display()
+                ));
+            }
+        }
+        if self.backup.keep_snapshots == 0 {
+            return Err(
+                "backup.keep_snapshots must be at least 1; set backup.snapshot_on_start to false instead"
+                    .to_string(),
+            );
+        }
+        if self.backend.bind.host.trim().is_empty() {
+            return Err("backend.host must not be empty".to_string());
+        }
+        if let Some(url) = &self.features.manifest_url {
+            features::parse_manifest_url(url)?;
+        }
+        if let Some(name) = self
+            .features
+            .overrides
+            .keys()
+            .find(|name| !features::is_known(name))
+        {
+            return Err(format!("features.overrides has an unknown flag {name:?}"));
+        }
+        if self.automation.max_consecutive_risk_failures == 0 {
+            return Err("automation.max_consecutive_risk_failures must be at least 1".to_string());
+        }
+        let limit = self.automation.daily_loss_limit_pct;
+        if !(limit.is_finite() && limit > 0.0) {
+            return Err(format!(
+                "automation.daily_loss_limit_pct must be a positive number, got {limit}"
+            ));
+        }
+        if self
+            .benchmarks
+            .symbols
+            .iter()
+            .any(|symbol| symbol.trim().is_empty())
+        {
+            return Err("benchmarks.symbols has an empty symbol".to_string());
+        }
+        Ok(())
+    }
+
+    /// Apply the settings that are read once at startup rather than on use.
+    fn apply(&self) {
+        if let Some(level) = shell_level(self) {
+            if let Ok(level) = parse_level(&level) {
+                shell_log::set_level(None, level);
+            }
+        }
+    }
+}
+
+fn parse_level(level: &str) -> Result<log::LevelFilter, String> {
+    level
+        .parse()
+        .map_err(|_| format!("Unknown log level: {level}"))
+}
+
+/// Managed state: the current settings, as last loaded or saved.
+pub struct SettingsStore {
+    settings: Mutex<Settings>,
+    /// What loading the file found, if it needed any attention.
+    migration: Mutex<Option<MigrationReport>>,
+}
+
+/// Payload of the `settings-migration` event and result of
+/// `get_settings_migration`.
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    /// Copy of the file as it was before it was migrated.
+    pub backup: Option<PathBuf>,
+    /// JSON config files folded into the settings, as they were renamed to
+    /// `<name>.v<version>.bak`.
+    pub merged_files: Vec<PathBuf>,
+    /// Keys and tokens found in `[env]` and moved to the keychain.
+    pub moved_to_keychain: Vec<String>,
+    /// Keys the current layout does not have; they are not used.
+    pub unknown_keys: Vec<String>,
+    /// Keys whose value was rejected; they keep their default.
+    pub invalid_keys: Vec<InvalidKey>,
+    /// Why the file could not be read at all, in which case every setting
+    /// has its default.
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct InvalidKey {
+    pub key: String,
+    pub error: String,
+}
+
+impl MigrationReport {
+    fn new(from_version: u32) -> Self {
+        Self {
+            from_version,
+            to_version: CURRENT_VERSION,
+            backup: None,
+            merged_files: Vec::new(),
+            moved_to_keychain: Vec::new(),
+            unknown_keys: Vec::new(),
+            invalid_keys: Vec::new(),
+            error: None,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.from_version == self.to_version
+            && self.moved_to_keychain.is_empty()
+            && self.unknown_keys.is_empty()
+            && self.invalid_keys.is_empty()
+            && self.error.is_none()
+    }
+}
+
+impl SettingsStore {
+    /// Read `settings.toml`, migrating it to the current layout, and apply
+    /// it. Anything that needed attention is logged and sent as a
+    /// `settings-migration` event.
+    pub fn load(app: &AppHandle) -> Self {
+        let (settings, migration) = read(app);
+        settings.apply();
+        Self {
+            settings: Mutex::new(settings),
+            migration: Mutex::new(migration),
+        }
+    }
+}
+
+/// The settings on disk, and the report if loading them needed attention.
+fn read(app: &AppHandle) -> (Settings, Option<MigrationReport>) {
+    let (settings, report) = match settings_path(app) {
+        Ok(path) => load_file(&path, |name, value| {
+            secrets::move_from_settings(app, name, value)
+        }),
+        Err(e) => {
+            let mut report = MigrationReport::new(CURRENT_VERSION);
+            report.error = Some(e);
+            (Settings::default(), report)
+        }
+    };
+    let migration = (!report.is_empty()).then(|| {
+        if let Some(error) = &report.error {
+            log::warn!("Failed to load settings, using defaults: {error}");
+        }
+        for key in &report.unknown_keys {
+            log::warn!("Ignoring unknown setting {key}");
+        }
+        for InvalidKey { key, error } in &report.invalid_keys {
+            log::warn!("Ignoring invalid setting {key}: {error}");
+        }
+        if let Err(e) = app.emit("settings-migration", &report) {
+            log::warn!("Failed to emit settings-migration: {e}");
+        }
+        report
+    });
+    (settings, migration)
+}
+
+/// Load and migrate the settings file, keeping every valid key.
+///
+/// A migrated file is backed up as `settings.toml.v<version>.bak` and
+/// rewritten; otherwise the file is left as the user wrote it, so a typo can
+/// be fixed by hand. The JSON config files of older layouts beside it are
+/// folded in and renamed to `<name>.v<version>.bak`. Keys and tokens in
+/// `[env]` are handed to `keep_secret` for the keychain and taken out of the
+/// file.
+fn load_file(
+    path: &Path,
+    keep_secret: impl Fn(&str, &str) -> Result<(), String>,
+) -> (Settings, MigrationReport) {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let (mut table, exists) = match store::read_toml::<toml::Table>(path) {
+        Ok(Some(table)) => (table, true),
+        // Without the file, the JSON config files are all there is to migrate.
+        Ok(None) => {
+            let mut table = toml::Table::new();
+            table.insert("version".to_string(), toml::Value::Integer(2));
+            (table, false)
+        }
+        Err(e) => {
+            let mut report = MigrationReport::new(CURRENT_VERSION);
+            report.error = Some(e);
+            return (Settings::default(), report);
+        }
+    };
+    let upgrade = upgrade(&mut table, |file| store::read_json(&dir.join(file)));
+    if !exists && upgrade.merged.is_empty() && upgrade.unreadable.is_empty() {
+        return (Settings::default(), MigrationReport::new(CURRENT_VERSION));
+    }
+    let from_version = upgrade.from_version;
+    let mut report = MigrationReport::new(from_version);
+    report.invalid_keys.extend(upgrade.unreadable);
+    take_secrets(&mut table, keep_secret, &mut report);
+    if from_version > CURRENT_VERSION {
+        log::warn!(
+            "{} was written by a newer version of the app (settings version {from_version})",
+            path.display()
+        );
+    }
+
+    let settings = merge(table, &mut report);
+    if from_version < CURRENT_VERSION {
+        let backup = path.with_extension(format!("toml.v{from_version}.bak"));
+        let saved = if exists {
+            std::fs::copy(path, &backup)
+                .map(|_| ())
+                .map_err(|e| format!("Failed to back up {}: {e}", path.display()))
+        } else {
+            Ok(())
+        }
+        .and_then(|()| store::write_toml(path, &settings));
+        match saved {
+            Ok(()) => {
+                log::info!("Migrated settings from version {from_version} to {CURRENT_VERSION}");
+                if exists {
+                    report.backup = Some(backup);
+                }
+                for merged in upgrade.merged.iter().filter(|m| m.only.is_empty()) {
+                    let file = dir.join(merged.file);
+                    let retired = dir.join(format!("{}.v{from_version}.bak", merged.file));
+                    match std::fs::rename(&file, &retired) {
+                        Ok(()) => {
+                            log::info!(
+                                "Moved {} into [{}]; the old file is {}",
+                                merged.file,
+                                merged.section,
+                                retired.display()
+                            );
+                            report.merged_files.push(retired);
+                        }
+                        Err(e) => log::warn!("Failed to rename {}: {e}", file.display()),
+                    }
+                }
+            }
+            Err(e) => log::warn!("Failed to save migrated settings: {e}"),
+        }
+    } else if from_version == CURRENT_VERSION && !report.moved_to_keychain.is_empty() {
+        if let Err(e) = store::write_toml(path, &settings) {
+            log::warn!("Failed to remove the secrets from {}: {e}", path.display());
+        }
+    }
+    (settings, report)
+}
+
+/// Take the keys and tokens, which the keychain holds (see `secrets`), out of
+/// the `[env]` of `table` and store them with `keep_secret`. One that cannot
+/// be stored is reported and left out too, so the file never overrides the
+/// keychain.
+fn take_secrets(
+    table: &mut toml::Table,
+    keep_secret: impl Fn(&str, &str) -> Result<(), String>,
+    report: &mut MigrationReport,
+) {
+    let Some(env) = table.get_mut("env").and_then(|env| env.as_table_mut()) else {
+        return;
+    };
+    for name in secrets::names() {
+        let Some(value) = env.remove(&name) else {
+            continue;
+        };
+        let stored = match value.as_str() {
+            Some("") => continue,
+            Some(value) => keep_secret(&name, value),
+            None => Err("expected a string".to_string()),
+        };
+        match stored {
+            Ok(()) => {
+                log::info!("Moved env.{name} from settings.toml to the keychain");
+                report.moved_to_keychain.push(name);
+            }
+            Err(e) => report.invalid_keys.push(InvalidKey {
+                key: format!("env.{name}"),
+                error: format!("{e}; store it with set_secret instead"),
+            }),
+        }
+    }
+}
+
+/// What `upgrade` did to a settings table.
+struct Upgrade {
+    /// The version the table was written with.
+    from_version: u32,
+    /// JSON config files whose keys were moved into the table.
+    merged: Vec<&'static MergedFile>,
+    /// JSON config files that could not be read, and why.
+    unreadable: Vec<InvalidKey>,
+}
+
+/// Take the layout version out of `table` and move the keys of older layouts
+/// to their current place, including those of the JSON config files `read`
+/// returns by name. Keys already in `table` win over those of a file.
+fn upgrade(
+    table: &mut toml::Table,
+    read: impl Fn(&str) -> Result<Option<serde_json::Value>, String>,
+) -> Upgrade {
+    let from_version = match table.remove("version") {
+        None => 1,
+        Some(toml::Value::Integer(version)) => u32::try_from(version).unwrap_or(0),
+        Some(_) => 0,
+    };
+    let mut upgrade = Upgrade {
+        from_version,
+        merged: Vec::new(),
+        unreadable: Vec::new(),
+    };
+    for migration in MIGRATIONS.iter().filter(|m| m.to > from_version) {
+        for (old, new) in migration.renamed {
+            rename_key(table, old, new);
+        }
+        for file in migration.merged {
+            let merged = read(file.file).and_then(|value| match value {
+                Some(value) => merge_file(table, file, value).map(|()| true),
+                None => Ok(false),
+            });
+            match merged {
+                Ok(true) => upgrade.merged.push(file),
+                Ok(false) => {}
+                Err(error) => upgrade.unreadable.push(InvalidKey {
+                    key: file.file.to_string(),
+                    error,
+                }),
+            }
+        }
+    }
+    upgrade
+}
+
+/// Move the keys of the JSON config `value` into its section of `table`.
+fn merge_file(
+    table: &mut toml::Table,
+    file: &MergedFile,
+    value: serde_json::Value,
+) -> Result<(), String> {
+    let value = toml::Value::try_from(without_nulls(value)).map_err(|e| e.to_string())?;
+    let section = table
+        .entry(file.section)
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        .as_table_mut()
+        .ok_or_else(|| format!("[{}] in settings.toml is not a table", file.section))?;
+    match (file.as_key, value) {
+        (Some(key), value) => {
+            section.entry(key).or_insert(value);
+        }
+        (None, toml::Value::Table(values)) => {
+            for (name, value) in values {
+                if file.only.is_empty() || file.only.contains(&name.as_str()) {
+                    section.entry(name).or_insert(value);
+                }
+            }
+        }
+        (None, _) => return Err("not a JSON object".to_string()),
+    }
+    Ok(())
+}
+
+/// Move `old` to `new` (both `<section>.<name>`), unless `new` is already set.
+fn rename_key(table: &mut toml::Table, old: &str, new: &str) {
+    let (Some((old_section, old_name)), Some((new_section, new_name))) =
+        (old.split_once('.'), new.split_once('.'))
+    else {
+        return;
+    };
+    let Some(value) = table
+        .get_mut(old_section)
+        .and_then(toml::Value::as_table_mut)
+        .and_then(|section| section.remove(old_name))
+    else {
+        return;
+    };
+    if table
+        .get(old_section)
+        .and_then(toml::Value::as_table)
+        .is_some_and(|s| s.is_empty())
+    {
+        table.remove(old_section);
+    }
+    let section = table
+        .entry(new_section)
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    if let Some(section) = section.as_table_mut() {
+        section.entry(new_name).or_insert(value);
+    }
+}
+
+/// Settings from the keys of `table` that exist and hold a valid value, the
+/// defaults for the rest; the others are recorded in `report`.
+fn merge(table: toml::Table, report: &mut MigrationReport) -> Settings {
+    let mut accepted = to_value(&Settings::default()).unwrap_or_default();
+    for (section, values) in table {
+        let Some(values) = values.as_table() else {
+            report.unknown_keys.push(section);
+            continue;
+        };
+        for (name, value) in values {
+            let key = format!("{section}.{name}");
+            let known = accepted
+                .get(&section)
+                .is_some_and(|defaults| defaults.is_object() && defaults.get(name).is_some());
+            if !known {
+                report.unknown_keys.push(key);
+                continue;
+            }
+            let mut candidate = accepted.clone();
+            candidate[&section][name] = serde_json::to_value(value).unwrap_or_default();
+            let checked = serde_json::from_value::<Settings>(candidate.clone())
+                .map_err(|e| e.to_string())
+                .and_then(|settings| settings.validate());
+            match checked {
+                Ok(()) => accepted = candidate,
+                Err(error) => report.invalid_keys.push(InvalidKey { key, error }),
+            }
+        }
+    }
+    serde_json::from_value(accepted).unwrap_or_default()
+}
+
+/// Re-read `settings.toml`, e.g. after the data dir was reset.
+pub fn reload(app: &AppHandle) {
+    if let Some(store) = app.try_state::<SettingsStore>() {
+        let fresh = SettingsStore::load(app);
+        *store.settings.lock().unwrap() = fresh.settings.into_inner().unwrap();
+        *store.migration.lock().unwrap() = fresh.migration.into_inner().unwrap();
+    }
+}
+
+/// Re-read `settings.toml` after it was edited outside the app, apply what
+/// changed and emit `settings-changed` for each key. Returns the changed keys.
+pub fn reload_changed(app: &AppHandle) -> Vec<String> {
+    let Some(store) = app.try_state::<SettingsStore>() else {
+        return Vec::new();
+    };
+    let (fresh, migration) = read(app);
+    let mut current = store.settings.lock().unwrap();
+    let changed = changes(&current, &fresh);
+    *current = fresh.clone();
+    drop(current);
+    *store.migration.lock().unwrap() = migration;
+
+    applied(app, &fresh, &changed);
+    changed.into_iter().map(|(key, _)| key).collect()
+}
+
+/// The saved settings for `export_settings`, without the `[env]` variables
+/// and `backend.extra_env` entries that hold secrets (keys, tokens, proxy
+/// URLs with credentials). Their keys, e.g. `env.<name>`, are returned second.
+pub fn export(app: &AppHandle) -> Result<(serde_json::Value, Vec<String>), String> {
+    let settings = current_saved(app);
+    let mut value = to_value(&settings)?;
+    let mut excluded = Vec::new();
+    for (name, secret) in settings.env.vars() {
+        if is_secret(&name, &secret) {
+            if let Some(env) = value.get_mut("env").and_then(|env| env.as_object_mut()) {
+                env.remove(&name);
+            }
+            excluded.push(format!("env.{name}"));
+        }
+    }
+    for (name, secret) in &settings.backend.launch.extra_env {
+        if is_secret(name, secret) {
+            if let Some(env) = value["backend"]
+                .get_mut("extra_env")
+                .and_then(|env| env.as_object_mut())
+            {
+                env.remove(name);
+            }
+            excluded.push(format!("backend.extra_env.{name}"));
+        }
+    }
+    Ok((value, excluded))
+}
+
+fn is_secret(name: &str, value: &str) -> bool {
+    launch::is_secret(name) || redact::redact(value) != value
+}
+
+/// Replace the saved settings with `value` from `import_settings`, migrated
+/// from the layout it was exported with; an export from before version 3
+/// brings its JSON config `files` by name. Secrets `export` leaves out keep
+/// their local value. The result is applied like an edit to the file, and
+/// the changed keys are returned with the keys that were skipped and why.
+pub fn import(
+    app: &AppHandle,
+    value: serde_json::Value,
+    files: BTreeMap<String, serde_json::Value>,
+) -> Result<(Vec<String>, Vec<String>), String> {
+    let mut table = toml::Table::try_from(without_nulls(value))
+        .map_err(|e| format!("Invalid settings: {e}"))?;
+    let upgrade = upgrade(&mut table, |file| Ok(files.get(file).cloned()));
+    let from_version = upgrade.from_version;
+    if from_version > CURRENT_VERSION {
+        return Err(format!(
+            "The settings were exported by a newer version of the app (settings version {from_version})"
+        ));
+    }
+    let mut report = MigrationReport::new(from_version);
+    report.invalid_keys.extend(upgrade.unreadable);
+    for name in files.keys() {
+        if !upgrade.merged.iter().any(|merged| merged.file == name) {
+            report.invalid_keys.push(InvalidKey {
+                key: name.clone(),
+                error: "not a config file".to_string(),
+            });
+        }
+    }
+    let mut imported = merge(table, &mut report);
+
+    let (_, excluded) = export(app)?;
+    let local = current_saved(app);
+    let mut env =
+        serde_json::to_value(&imported.env).map_err(|e| format!("Invalid settings: {e}"))?;
+    for (name, secret) in local.env.vars() {
+        if excluded.contains(&format!("env.{name}")) && env[&name].is_null() {
+            env[&name] = serde_json::Value::String(secret);
+        }
+    }
+    imported.env = serde_json::from_value(env).map_err(|e| format!("Invalid settings: {e}"))?;
+    for (name, secret) in local.backend.launch.extra_env {
+        if excluded.contains(&format!("backend.extra_env.{name}")) {
+            imported
+                .backend
+                .launch
+                .extra_env
+                .entry(name)
+                .or_insert(secret);
+        }
+    }
+
+    store::write_toml(&settings_path(app)?, &imported)?;
+    let skipped = report
+        .unknown_keys
+        .into_iter()
+        .map(|key| format!("{key}: unknown setting"))
+        .chain(
+            report
+                .invalid_keys
+                .into_iter()
+                .map(|InvalidKey { key, error }| format!("{key}: {error}")),
+        )
+        .collect();
+    Ok((reload_changed(app), skipped))
+}
+
+/// `value` without its `null` members, which TOML cannot hold; every
+/// nullable setting is optional.
+fn without_nulls(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(members) => members
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(name, value)| (name, without_nulls(value)))
+            .collect(),
+        other => other,
+    }
+}
+
+/// Every setting by its `<section>.<name>` key.
+fn flatten(settings: &Settings) -> BTreeMap<String, serde_json::Value> {
+    let mut keys = BTreeMap::new();
+    if let Ok(serde_json::Value::Object(sections)) = serde_json::to_value(settings) {
+        for (section, values) in sections {
+            if let serde_json::Value::Object(values) = values {
+                for (name, value) in values {
+                    keys.insert(format!("{section}.{name}"), value);
+                }
+            }
+        }
+    }
+    keys
+}
+
+/// `logging.shell_level`, unless `TELETRAAN_SHELL_LOG_LEVEL` overrides it.
+fn shell_level(settings: &Settings) -> Option<String> {
+    let mut settings = settings.clone();
+    config::settings(&mut settings);
+    settings.logging.shell_level
+}
+
+/// The keys whose value differs in `after`, with their new value.
+fn changes(before: &Settings, after: &Settings) -> Vec<(String, serde_json::Value)> {
+    let before = flatten(before);
+    flatten(after)
+        .into_iter()
+        .filter(|(key, value)| before.get(key) != Some(value))
+        .collect()
+}
+
+/// Apply the `changed` keys of `settings` that the shell does not read on
+/// use, then log each change and emit `settings-changed`. Called once the
+/// new settings are current.
+fn applied(app: &AppHandle, settings: &Settings, changed: &[(String, serde_json::Value)]) {
+    let changed_in = |prefix: &str| changed.iter().any(|(key, _)| key.starts_with(prefix));
+    if changed_in("logging.shell_level") {
+        apply_shell_level(settings);
+    }
+    if changed_in("restart.") {
+        restart::reload_policy(app);
+    }
+    if changed_in("features.") {
+        features::settings_changed(app);
+    }
+    for (key, value) in changed {
+        log_change(app, key, value);
+        emit_changed(app, key, value);
+    }
+}
+
+fn apply_shell_level(settings: &Settings) {
+    match shell_level(settings).as_deref().map(parse_level) {
+        Some(Ok(level)) => shell_log::set_level(None, level),
+        Some(Err(_)) => {}
+        None => shell_log::reset_levels(),
+    }
+}
+
+/// Log a changed setting and record it in the audit log.
+fn log_change(app: &AppHandle, key: &str, value: &serde_json::Value) {
+    let detail = if key.starts_with("env.") {
+        // May be an API key.
+        format!("{key} changed")
+    } else {
+        format!("{key} changed to {value}")
+    };
+    log::info!("Setting {detail}");
+    audit::record(app, AuditEvent::SettingChanged, true, detail);
+}
+
+fn emit_changed(app: &AppHandle, key: &str, value: &serde_json::Value) {
+    if let Err(e) = app.emit("settings-changed", SettingsChanged { key, value }) {
+        log::warn!("Failed to emit settings-changed: {e}");
+    }
+}
+
+/// Payload of the `settings-changed` event.
+#[derive(Clone, Debug, Serialize)]
+struct SettingsChanged<'a> {
+    key: &'a str,
+    value: &'a serde_json::Value,
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::resolve_data_dir(app)?.join(SETTINGS_FILE))
+}
+
+/// The current settings with the command-line and environment overrides
+/// applied (see `config`); the defaults before they are loaded.
+pub fn current(app: &AppHandle) -> Settings {
+    let mut settings = current_saved(app);
+    config::settings(&mut settings);
+    settings
+}
+
+/// The settings as saved, without overrides.
+fn current_saved(app: &AppHandle) -> Settings {
+    app.try_state::<SettingsStore>()
+        .map(|store| store.settings.lock().unwrap().clone())
+        .unwrap_or_default()
+}
+
+/// The folder exports are saved to by default.
+pub fn export_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    match current(app).export.dir {
+        Some(dir) => Ok(dir),
+        None => app
+            .path()
+            .download_dir()
+            .map_err(|e| format!("Failed to resolve Downloads folder: {e}")),
+    }
+}
+
+/// Tauri command: all settings, grouped by section.
+#[tauri::command]
+pub async fn get_all_settings(store: State<'_, SettingsStore>) -> Result<Settings, String> {
+    Ok(store.settings.lock().unwrap().clone())
+}
+
+/// Tauri command: one setting by its `<section>.<name>` key; `null` while an
+/// optional setting is unset.
+#[tauri::command]
+pub async fn get_setting(
+    store: State<'_, SettingsStore>,
+    key: String,
+) -> Result<serde_json::Value, String> {
+    let settings = to_value(&store.settings.lock().unwrap())?;
+    lookup(&settings, &key)
+        .cloned()
+        .ok_or_else(|| format!("Unknown setting: {key}"))
+}
+
+/// Tauri command: change one setting, save `settings.toml` and emit
+/// `settings-changed`. `null` resets an optional setting.
+///
+/// The value must have the setting's type; an unknown key or invalid value is
+/// rejected and nothing is saved.
+#[tauri::command]
+pub async fn set_setting(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    key: String,
+    value: serde_json::Value,
+) -> Result<(), String> {
+    let current = store.settings.lock().unwrap();
+    let mut settings = to_value(&current)?;
+    let (section, name) = key
+        .split_once('.')
+        .ok_or_else(|| format!("Unknown setting: {key}"))?;
+    let slot = settings
+        .get_mut(section)
+        .and_then(|section| section.get_mut(name))
+        .ok_or_else(|| format!("Unknown setting: {key}"))?;
+    *slot = value;
+    let updated: Settings =
+        serde_json::from_value(settings).map_err(|e| format!("Invalid value for {key}: {e}"))?;
+    save(&app, current, updated).map(|_| ())
+}
+
+/// Change the saved settings with `change`, then validate, save and apply
+/// them like `set_setting`. Returns the changed keys.
+pub fn update(app: &AppHandle, change: impl FnOnce(&mut Settings)) -> Result<Vec<String>, String> {
+    let store = app
+        .try_state::<SettingsStore>()
+        .ok_or("Settings are not loaded yet")?;
+    let current = store.settings.lock().unwrap();
+    let mut updated = current.clone();
+    change(&mut updated);
+    save(app, current, updated)
+}
+
+/// Save `updated` to `settings.toml` in place of `current` if it is valid,
+/// apply each changed key and emit `settings-changed` for it. Changes that
+/// need a new backend are passed to `config_watch::require_restart`.
+fn save(
+    app: &AppHandle,
+    mut current: MutexGuard<'_, Settings>,
+    updated: Settings,
+) -> Result<Vec<String>, String> {
+    updated.validate()?;
+    let changed = changes(&current, &updated);
+    if changed.is_empty() {
+        return Ok(Vec::new());
+    }
+    store::write_toml(&settings_path(app)?, &updated)?;
+    *current = updated.clone();
+    drop(current);
+
+    applied(app, &updated, &changed);
+    let keys: Vec<String> = changed.into_iter().map(|(key, _)| key).collect();
+    config_watch::require_restart(
+        app,
+        keys.iter()
+            .filter(|key| config_watch::needs_backend_restart(key))
+            .cloned()
+            .collect(),
+    );
+    Ok(keys)
+}
+
+/// Tauri command: what loading `settings.toml` found (a migration from an
+/// older layout, unknown or invalid keys), or `null` if it loaded cleanly.
+/// The same report is sent as `settings-migration` at startup.
+#[tauri::command]
+pub async fn get_settings_migration(
+    store: State<'_, SettingsStore>,
+) -> Result<Option<MigrationReport>, String> {
+    Ok(store.migration.lock().unwrap().clone())
+}
+
+fn to_value(settings: &Settings) -> Result<serde_json::Value, String> {
+    serde_json::to_value(settings).map_err(|e| format!("Failed to serialize settings: {e}"))
+}
+
+fn lookup<'a>(settings: &'a serde_json::Value, key: &str) -> Option<&'a serde_json::Value> {
+    let (section, name) = key.split_once('.')?;
+    settings.get(section)?.get(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty data dir of its own for `name`.
+    fn data_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("teletraan-settings-{}", std::process::id()))
+            .join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(dir: &Path, name: &str, contents: &str) {
+        std::fs::write(dir.join(name), contents).unwrap();
+    }
+
+    /// `load_file` without a keychain.
+    fn load(path: &Path) -> (Settings, MigrationReport) {
+        load_file(path, |_, _| Err("No keychain".to_string()))
+    }
+
+    #[test]
+    fn round_trips_through_the_file() {
+        let dir = data_dir("round-trip");
+        let path = dir.join(SETTINGS_FILE);
+        let mut settings = Settings::default();
+        settings.backend.bind.port = Some(8123);
+        settings.backend.launch.extra_args = vec!["--workers".to_string(), "2".to_string()];
+        settings
+            .backend
+            .launch
+            .extra_env
+            .insert("LOG_LEVEL".to_string(), "debug".to_string());
+        settings.logging.files.keep_files = 2;
+        settings.health_check.startup_max_attempts = Some(7);
+        settings
+            .features
+            .overrides
+            .insert("backtester".to_string(), true);
+        settings.benchmarks.symbols = vec!["DIA".to_string()];
+        store::write_toml(&path, &settings).unwrap();
+
+        let (loaded, report) = load(&path);
+        assert!(report.is_empty(), "{report:?}");
+        assert_eq!(flatten(&loaded), flatten(&settings));
+    }
+
+    #[test]
+    fn merges_the_json_config_files() {
+        let dir = data_dir("merge");
+        write(
+            &dir,
+            SETTINGS_FILE,
+            "version = 2\n[general]\ncheck_dependencies = false\n[backend]\nport = 9000\n",
+        );
+        write(
+            &dir,
+            "backend.json",
+            r#"{"port": 8123, "tls": true, "extra_env": {"LOG_LEVEL": "debug"}}"#,
+        );
+        write(&dir, "health_check.json", r#"{"startup_timeout_secs": 60}"#);
+        write(&dir, "benchmarks.json", r#"["DIA"]"#);
+        write(
+            &dir,
+            "automation.json",
+            r#"{"halted": true, "daily_loss_limit_pct": 5.0}"#,
+        );
+
+        let (settings, report) = load(&dir.join(SETTINGS_FILE));
+        assert_eq!(report.from_version, 2);
+        assert!(report.unknown_keys.is_empty(), "{report:?}");
+        assert!(!settings.general.check_dependencies);
+        // A key already in settings.toml wins over the file's.
+        assert_eq!(settings.backend.bind.port, Some(9000));
+        assert!(settings.backend.bind.tls);
+        assert_eq!(settings.backend.launch.extra_env["LOG_LEVEL"], "debug");
+        assert_eq!(settings.health_check.startup_timeout_secs, 60);
+        assert_eq!(settings.benchmarks.symbols, ["DIA"]);
+        assert_eq!(settings.automation.daily_loss_limit_pct, 5.0);
+
+        // The merged files are retired, except the automation state.
+        assert!(!dir.join("backend.json").exists());
+        assert!(dir.join("backend.json.v2.bak").exists());
+        assert!(!dir.join("benchmarks.json").exists());
+        assert!(dir.join("automation.json").exists());
+        assert_eq!(report.merged_files.len(), 3);
+
+        let (again, report) = load(&dir.join(SETTINGS_FILE));
+        assert!(report.is_empty(), "{report:?}");
+        assert_eq!(flatten(&again), flatten(&settings));
+    }
+
+    #[test]
+    fn merges_json_config_files_without_a_settings_file() {
+        let dir = data_dir("json-only");
+        write(&dir, "restart_policy.json", r#"{"max_attempts": 0}"#);
+
+        let (settings, report) = load(&dir.join(SETTINGS_FILE));
+        assert_eq!(settings.restart.max_attempts, 0);
+        assert_eq!(report.backup, None);
+        assert!(dir.join(SETTINGS_FILE).exists());
+        assert!(dir.join("restart_policy.json.v2.bak").exists());
+    }
+
+    #[test]
+    fn migrates_version_1() {
+        let dir = data_dir("v1");
+        let original = "[general]\nexport_dir = \"/tmp/reports\"\ncheck_dependencies = false\n";
+        write(&dir, SETTINGS_FILE, original);
+
+        let (settings, report) = load(&dir.join(SETTINGS_FILE));
+        assert_eq!(report.from_version, 1);
+        assert_eq!(report.to_version, CURRENT_VERSION);
+        assert!(report.unknown_keys.is_empty(), "{report:?}");
+        assert_eq!(settings.export.dir, Some(PathBuf::from("/tmp/reports")));
+        assert!(!settings.general.check_dependencies);
+
+        // The file as it was is kept next to the rewritten one.
+        let backup = dir.join("settings.toml.v1.bak");
+        assert_eq!(report.backup.as_deref(), Some(backup.as_path()));
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), original);
+        let rewritten = std::fs::read_to_string(dir.join(SETTINGS_FILE)).unwrap();
+        assert!(rewritten.contains(&format!("version = {CURRENT_VERSION}")));
+        assert!(!rewritten.contains("export_dir"));
+    }
+
+    #[test]
+    fn reports_unknown_and_invalid_keys_of_merged_files() {
+        let dir = data_dir("bad-keys");
+        write(&dir, SETTINGS_FILE, "version = 2\n");
+        write(&dir, "backend.json", r#"{"port": 8123, "bogus": 1}"#);
+        write(
+            &dir,
+            "health_check.json",
+            r#"{"startup_timeout_secs": "soon"}"#,
+        );
+        write(&dir, "logging.json", "{not json");
+
+        let (settings, report) = load(&dir.join(SETTINGS_FILE));
+        assert_eq!(report.unknown_keys, ["backend.bogus"]);
+        assert_eq!(settings.backend.bind.port, Some(8123));
+        let invalid: Vec<_> = report.invalid_keys.iter().map(|k| k.key.as_str()).collect();
+        assert!(
+            invalid.contains(&"health_check.startup_timeout_secs"),
+            "{invalid:?}"
+        );
+        assert!(invalid.contains(&"logging.json"), "{invalid:?}");
+        assert_eq!(
+            settings.health_check.startup_timeout_secs,
+            HealthCheckConfig::default().startup_timeout_secs
+        );
+        // A file that could not be read is left for the user to fix.
+        assert!(dir.join("logging.json").exists());
+    }
+
+    #[test]
+    fn leaves_a_newer_file_as_it_is() {
+        let dir = data_dir("newer");
+        let original = "version = 99\n[general]\ncheck_dependencies = false\nfuture = 1\n";
+        write(&dir, SETTINGS_FILE, original);
+
+        let (settings, report) = load(&dir.join(SETTINGS_FILE));
+        assert_eq!(report.from_version, 99);
+        assert!(!settings.general.check_dependencies);
+        assert_eq!(report.unknown_keys, ["general.future"]);
+        assert_eq!(
+            std::fs::read_to_string(dir.join(SETTINGS_FILE)).unwrap(),
+            original
+        );
+    }
+
+    #[test]
+    fn moves_env_secrets_to_the_keychain() {
+        let dir = data_dir("secrets");
+        write(
+            &dir,
+            SETTINGS_FILE,
+            &format!(
+                "version = {CURRENT_VERSION}\n[env]\nHTTPS_PROXY = \"http://proxy:3128\"\nANTHROPIC_API_KEY = \"sk-test\"\n"
+            ),
+        );
+        let stored = std::cell::RefCell::new(Vec::new());
+        let (settings, report) = load_file(&dir.join(SETTINGS_FILE), |name, value| {
+            stored
+                .borrow_mut()
+                .push((name.to_string(), value.to_string()));
+            Ok(())
+        });
+        assert_eq!(
+            stored.into_inner(),
+            [("ANTHROPIC_API_KEY".to_string(), "sk-test".to_string())]
+        );
+        assert_eq!(report.moved_to_keychain, ["ANTHROPIC_API_KEY"]);
+        assert_eq!(settings.env.anthropic_api_key, None);
+        assert_eq!(
+            settings.env.https_proxy.as_deref(),
+            Some("http://proxy:3128")
+        );
+        let rewritten = std::fs::read_to_string(dir.join(SETTINGS_FILE)).unwrap();
+        assert!(!rewritten.contains("sk-test"), "{rewritten}");
+        assert!(rewritten.contains("HTTPS_PROXY"), "{rewritten}");
+    }
+
+    #[test]
+    fn rejects_env_secrets_the_keychain_cannot_take() {
+        let dir = data_dir("secrets-rejected");
+        let original = format!("version = {CURRENT_VERSION}\n[env]\nFRED_API_KEY = \"abc\"\n");
+        write(&dir, SETTINGS_FILE, &original);
+
+        let (settings, report) = load(&dir.join(SETTINGS_FILE));
+        assert_eq!(settings.env.fred_api_key, None);
+        assert!(report.moved_to_keychain.is_empty());
+        assert_eq!(report.invalid_keys.len(), 1);
+        assert_eq!(report.invalid_keys[0].key, "env.FRED_API_KEY");
+        assert!(report.invalid_keys[0].error.contains("set_secret"));
+        // Left for the user to move by hand.
+        assert_eq!(
+            std::fs::read_to_string(dir.join(SETTINGS_FILE)).unwrap(),
+            original
+        );
+
+        let mut settings = Settings::default();
+        settings.env.finnhub_api_key = Some("abc".to_string());
+        assert!(settings.validate().unwrap_err().contains("set_secret"));
+    }
+
+    #[test]
+    fn leaves_a_new_data_dir_alone() {
+        let dir = data_dir("empty");
+        let (_, report) = load(&dir.join(SETTINGS_FILE));
+        assert!(report.is_empty(), "{report:?}");
+        assert!(!dir.join(SETTINGS_FILE).exists());
+    }
+}
@@ -0,0 +1,185 @@
+use std::cell::Cell;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::Instant;
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+use crate::logging::{self, LogConfig, RotatingLog};
+
+/// Target of backend output re-logged by the output readers. Those lines
+/// already have their own session log, so they only go to the console.
+pub const BACKEND_TARGET: &str = "backend";
+
+/// Target of webview events forwarded by `log_frontend_event`.
+pub const FRONTEND_TARGET: &str = "frontend";
+
+/// Lines kept from before the data dir is known, written once it is.
+const EARLY_LINES: usize = 1_000;
+
+static LOGGER: OnceLock<ShellLogger> = OnceLock::new();
+
+thread_local! {
+    /// Set while a line is being written, so warnings from the file itself
+    /// (a failed rotation) cannot re-enter the sink and deadlock.
+    static WRITING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// The app's own logger: `env_logger` on the console (honouring `RUST_LOG`)
+/// plus `logs/shell.log` in the data dir, rotated like the backend logs.
+struct ShellLogger {
+    console: RwLock<env_logger::Logger>,
+    /// Levels set at runtime by `set_log_level`, applied over `RUST_LOG`.
+    overrides: Mutex<Vec<(Option<String>, LevelFilter)>>,
+    sink: Mutex<Sink>,
+}
+
+enum Sink {
+    /// Before `attach`: lines buffered in memory.
+    Pending(Vec<String>),
+    File(RotatingLog),
+}
+
+/// Install the logger. Call once, first thing in `run`.
+pub fn init() {
+    let console = build_console(&[]);
+    let max_level = console.filter();
+    let logger = LOGGER.get_or_init(|| ShellLogger {
+        console: RwLock::new(console),
+        overrides: Mutex::new(Vec::new()),
+        sink: Mutex::new(Sink::Pending(Vec::new())),
+    });
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// `RUST_LOG` (default `info`) with `overrides` on top; a `None` target sets
+/// the level for everything.
+fn build_console(overrides: &[(Option<String>, LevelFilter)]) -> env_logger::Logger {
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"));
+    for (target, level) in overrides {
+        match target {
+            Some(target) => builder.filter_module(target, *level),
+            None => builder.filter_level(*level),
+        };
+    }
+    builder.build()
+}
+
+/// Change the shell's log level for `target` (a module path such as
+/// `teletraan_desktop_lib::health`), or for everything when `None`.
+pub fn set_level(target: Option<&str>, level: LevelFilter) {
+    let Some(logger) = LOGGER.get() else {
+        return;
+    };
+    let mut overrides = logger.overrides.lock().unwrap();
+    if target.is_none() {
+        // A global level replaces earlier per-module ones.
+        overrides.clear();
+    }
+    overrides.retain(|(t, _)| t.as_deref() != target);
+    overrides.push((target.map(str::to_string), level));
+
+    let console = build_console(&overrides);
+    log::set_max_level(console.filter());
+    *logger.console.write().unwrap() = console;
+}
+
+/// Drop every level set at runtime and go back to `RUST_LOG` (default `info`).
+pub fn reset_levels() {
+    let Some(logger) = LOGGER.get() else {
+        return;
+    };
+    let mut overrides = logger.overrides.lock().unwrap();
+    overrides.clear();
+    let console = build_console(&overrides);
+    log::set_max_level(console.filter());
+    *logger.console.write().unwrap() = console;
+}
+
+/// Start writing `logs/shell.log`, including the lines logged so far.
+pub fn attach(data_dir: &Path, config: LogConfig) -> Result<(), String> {
+    let Some(logger) = LOGGER.get() else {
+        return Ok(());
+    };
+    let logs_dir = logging::logs_dir(data_dir);
+    std::fs::create_dir_all(&logs_dir)
+        .map_err(|e| format!("Failed to create {}: {e}", logs_dir.display()))?;
+    let mut file = RotatingLog::open(&logs_dir.join("shell.log"), config)?;
+
+    let mut sink = logger.sink.lock().unwrap();
+    if let Sink::Pending(lines) = &*sink {
+        for line in lines {
+            file.write_line(line);
+        }
+    }
+    *sink = Sink::File(file);
+    Ok(())
+}
+
+impl Log for ShellLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.console.read().unwrap().enabled(metadata)
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        {
+            let console = self.console.read().unwrap();
+            if !console.matches(record) {
+                return;
+            }
+            console.log(record);
+        }
+        if record.target() == BACKEND_TARGET || WRITING.get() {
+            return;
+        }
+
+        let line = format!(
+            "{} {:<5} {}: {}",
+            chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        WRITING.set(true);
+        match &mut *self.sink.lock().unwrap() {
+            Sink::Pending(lines) if lines.len() < EARLY_LINES => lines.push(line),
+            Sink::Pending(_) => {}
+            Sink::File(file) => file.write_line(&line),
+        }
+        WRITING.set(false);
+    }
+
+    fn flush(&self) {
+        self.console.read().unwrap().flush();
+    }
+}
+
+/// A timed startup phase: a `tracing` span named `phase`, which the `log`
+/// bridge writes as `++ phase; name="<name>"` when it opens and `-- phase;`
+/// when it is dropped, with a `finished name="<name>" duration_ms=<n>` event
+/// in between, so slow launches can be broken down from `shell.log`.
+pub struct Phase {
+    name: &'static str,
+    span: tracing::Span,
+    started: Instant,
+}
+
+impl Phase {
+    pub fn start(name: &'static str) -> Self {
+        Self {
+            name,
+            span: tracing::info_span!("phase", name),
+            started: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Phase {
+    fn drop(&mut self) {
+        let duration_ms = self.started.elapsed().as_millis() as u64;
+        tracing::info!(parent: &self.span, name = self.name, duration_ms, "finished");
+    }
+}
@@ -0,0 +1,373 @@
+use std::io::Read;
+use std::path::Path;
+
+use base64::Engine;
+use ring::signature::{UnparsedPublicKey, ED25519};
+
+/// minisign signature of the data itself.
+const ALG_PURE: [u8; 2] = *b"Ed";
+/// minisign signature of the BLAKE2b-512 hash of the data, what `minisign`
+/// and `tauri signer` write by default.
+const ALG_PREHASHED: [u8; 2] = *b"ED";
+
+/// A minisign public key, as `tauri signer generate` writes it.
+#[derive(Clone, Debug)]
+pub struct PublicKey {
+    key_id: [u8; 8],
+    key: [u8; 32],
+}
+
+impl PublicKey {
+    /// Parse the `.pub` file, either as written or base64-encoded whole, as
+    /// Tauri configurations hold it.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = decode_file(text)?;
+        let line = last_line(&text).ok_or("the public key is empty")?;
+        let bytes = decode(line).map_err(|e| format!("Invalid public key: {e}"))?;
+        if bytes.len() != 42 || bytes[..2] != ALG_PURE {
+            return Err("Invalid public key: not a minisign Ed25519 key".to_string());
+        }
+        let mut key = Self {
+            key_id: [0; 8],
+            key: [0; 32],
+        };
+        key.key_id.copy_from_slice(&bytes[2..10]);
+        key.key.copy_from_slice(&bytes[10..]);
+        Ok(key)
+    }
+}
+
+/// A parsed `.sig` file.
+struct Signature {
+    prehashed: bool,
+    key_id: [u8; 8],
+    signature: Vec<u8>,
+    trusted_comment: String,
+    global_signature: Vec<u8>,
+}
+
+impl Signature {
+    fn parse(text: &str) -> Result<Self, String> {
+        let text = decode_file(text)?;
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+        let (Some(_untrusted), Some(signature), Some(trusted), Some(global)) =
+            (lines.next(), lines.next(), lines.next(), lines.next())
+        else {
+            return Err("Invalid signature: expected four lines".to_string());
+        };
+        let bytes = decode(signature).map_err(|e| format!("Invalid signature: {e}"))?;
+        if bytes.len() != 74 {
+            return Err("Invalid signature: wrong length".to_string());
+        }
+        let prehashed = match [bytes[0], bytes[1]] {
+            ALG_PREHASHED => true,
+            ALG_PURE => false,
+            _ => return Err("Invalid signature: unknown algorithm".to_string()),
+        };
+        let trusted_comment = trusted
+            .strip_prefix("trusted comment: ")
+            .ok_or("Invalid signature: no trusted comment")?
+            .to_string();
+        let mut key_id = [0; 8];
+        key_id.copy_from_slice(&bytes[2..10]);
+        Ok(Self {
+            prehashed,
+            key_id,
+            signature: bytes[10..].to_vec(),
+            trusted_comment,
+            global_signature: decode(global).map_err(|e| format!("Invalid signature: {e}"))?,
+        })
+    }
+}
+
+fn decode(text: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    base64::engine::general_purpose::STANDARD.decode(text.trim())
+}
+
+/// `text` as a minisign file, decoding it first if it is base64.
+fn decode_file(text: &str) -> Result<String, String> {
+    let text = text.trim();
+    if text.contains("comment:") {
+        return Ok(text.to_string());
+    }
+    let bytes = decode(text).map_err(|e| format!("Invalid minisign file: {e}"))?;
+    String::from_utf8(bytes).map_err(|_| "Invalid minisign file: not text".to_string())
+}
+
+fn last_line(text: &str) -> Option<&str> {
+    text.lines().map(str::trim).rfind(|line| !line.is_empty())
+}
+
+/// Check the minisign `signature` (the `.sig` file, as written or
+/// base64-encoded) of the file at `path` against `key`. Returns the trusted
+/// comment. Prehashed signatures are checked without reading the file into
+/// memory.
+pub fn verify_file(path: &Path, signature: &str, key: &PublicKey) -> Result<String, String> {
+    let signature = Signature::parse(signature)?;
+    let mut file =
+        std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    let message = if signature.prehashed {
+        let mut hasher = Blake2b::new();
+        let mut buffer = vec![0u8; 1024 * 1024];
+        loop {
+            let read = file
+                .read(&mut buffer)
+                .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+        hasher.finalize().to_vec()
+    } else {
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        data
+    };
+    check(&signature, &message, key)?;
+    Ok(signature.trusted_comment)
+}
+
+/// Check the minisign `signature` of `data`, held in memory, against `key`.
+/// Returns the trusted comment.
+pub fn verify(data: &[u8], signature: &str, key: &PublicKey) -> Result<String, String> {
+    let signature = Signature::parse(signature)?;
+    let message = if signature.prehashed {
+        let mut hasher = Blake2b::new();
+        hasher.update(data);
+        hasher.finalize().to_vec()
+    } else {
+        data.to_vec()
+    };
+    check(&signature, &message, key)?;
+    Ok(signature.trusted_comment)
+}
+
+fn check(signature: &Signature, message: &[u8], key: &PublicKey) -> Result<(), String> {
+    if signature.key_id != key.key_id {
+        return Err("The signature was made with a different key".to_string());
+    }
+    let key = UnparsedPublicKey::new(&ED25519, key.key);
+    key.verify(message, &signature.signature)
+        .map_err(|_| "The signature does not match".to_string())?;
+    // The trusted comment is signed too, so it cannot be swapped.
+    let mut signed = signature.signature.clone();
+    signed.extend_from_slice(signature.trusted_comment.as_bytes());
+    key.verify(&signed, &signature.global_signature)
+        .map_err(|_| "The signature's trusted comment does not match".to_string())
+}
+
+/// BLAKE2b-512 (RFC 7693), unkeyed, for prehashed minisign signatures.
+struct Blake2b {
+    h: [u64; 8],
+    /// Bytes compressed so far.
+    counter: u128,
+    buffer: [u8; 128],
+    buffered: usize,
+}
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const SIGMA: [[usize; 16]; 12] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+];
+
+impl Blake2b {
+    fn new() -> Self {
+        let mut h = IV;
+        // Parameter block: 64-byte digest, no key, fanout and depth 1.
+        h[0] ^= 0x0101_0040;
+        Self {
+            h,
+            counter: 0,
+            buffer: [0; 128],
+            buffered: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            // The last block is compressed by `finalize`, so only compress a
+            // full buffer once more data follows.
+            if self.buffered == 128 {
+                self.counter += 128;
+                let block = self.buffer;
+                self.compress(&block, false);
+                self.buffered = 0;
+            }
+            let take = (128 - self.buffered).min(data.len());
+            self.buffer[self.buffered..self.buffered + take].copy_from_slice(&data[..take]);
+            self.buffered += take;
+            data = &data[take..];
+        }
+    }
+
+    fn finalize(mut self) -> [u8; 64] {
+        self.counter += self.buffered as u128;
+        self.buffer[self.buffered..].fill(0);
+        let block = self.buffer;
+        self.compress(&block, true);
+        let mut out = [0u8; 64];
+        for (chunk, word) in out.chunks_exact_mut(8).zip(self.h) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        out
+    }
+
+    fn compress(&mut self, block: &[u8; 128], last: bool) {
+        let mut m = [0u64; 16];
+        for (word, chunk) in m.iter_mut().zip(block.chunks_exact(8)) {
+            *word = u64::from_le_bytes(chunk.try_into().unwrap_or_default());
+        }
+        let mut v = [0u64; 16];
+        v[..8].copy_from_slice(&self.h);
+        v[8..].copy_from_slice(&IV);
+        v[12] ^= self.counter as u64;
+        v[13] ^= (self.counter >> 64) as u64;
+        if last {
+            v[14] = !v[14];
+        }
+
+        fn g(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+            v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+            v[d] = (v[d] ^ v[a]).rotate_right(32);
+            v[c] = v[c].wrapping_add(v[d]);
+            v[b] = (v[b] ^ v[c]).rotate_right(24);
+            v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+            v[d] = (v[d] ^ v[a]).rotate_right(16);
+            v[c] = v[c].wrapping_add(v[d]);
+            v[b] = (v[b] ^ v[c]).rotate_right(63);
+        }
+
+        for s in &SIGMA {
+            g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+            g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+            g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+            g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+            g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+            g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+            g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+            g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+        }
+        for i in 0..8 {
+            self.h[i] ^= v[i] ^ v[i + 8];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A key and a signature in the format `tauri signer` writes: minisign
+    /// files, base64-encoded whole, prehashed, with its trusted comment.
+    const PUBLIC_KEY: &str = "dW50cnVzdGVkIGNvbW1lbnQ6IG1pbmlzaWduIHB1YmxpYyBrZXk6IDg4Nzc2NjU1NDQzMzIyMTEKUldRUklqTkVWV1ozaUFPaEI3L3p6aEMrSFhEZEdPZEx3SmxuNU5Zd202VU5YeDNjaG1RU1ZURzQK";
+    const SIGNATURE: &str = "dW50cnVzdGVkIGNvbW1lbnQ6IHNpZ25hdHVyZSBmcm9tIHRhdXJpIHNlY3JldCBrZXkKUlVRUklqTkVWV1ozaUczL1FtZXgwSGwxRTRLMHdmemRBUU8vMzFCRURBWUFDNFhiUHkzazZWQncvckRrcW1IT3ZqNHU1MUdyVmVtTllaMHUzKy8vcWVMRWxjNThFU2Zka0FFPQp0cnVzdGVkIGNvbW1lbnQ6IHRpbWVzdGFtcDoxNzYwNDAwMDAwCWZpbGU6dGVsZXRyYWFuLmFwcC50YXIuZ3oKWW1yL2h2MnpmWnNieGxNTWdJME5uRzRBeDVnZ0xTQmNZeWNISUFhYTI5R0tJcmhOaG0zeGpkVDZRaldqOWJwV3BsVlBrL2VGT0VpdS9CL1FabGpxQXc9PQo=";
+    const SIGNED_DATA: &[u8] = b"Teletraan update payload\n";
+
+    fn blake2b(data: &[u8]) -> String {
+        let mut hasher = Blake2b::new();
+        hasher.update(data);
+        hex::encode(hasher.finalize())
+    }
+
+    #[test]
+    fn blake2b_matches_rfc_7693() {
+        assert_eq!(
+            blake2b(b""),
+            "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419\
+             d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce"
+        );
+        assert_eq!(
+            blake2b(b"abc"),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+    }
+
+    #[test]
+    fn blake2b_handles_block_boundaries() {
+        let block: Vec<u8> = (0..128u8).collect();
+        assert_eq!(
+            blake2b(&block),
+            "2319e3789c47e2daa5fe807f61bec2a1a6537fa03f19ff32e87eecbfd64b7e0e\
+             8ccff439ac333b040f19b0c4ddd11a61e24ac1fe0f10a039806c5dcc0da3d115"
+        );
+        let data: Vec<u8> = (0..256u32).map(|i| (i % 251) as u8).collect();
+        let expected = "93463ac058b6163eb43be3f5bb32b28541498f4e3366f1effe253ad44e1e076e\
+                        41c3616046027c82a7124f8f4746668ad10b12e8e25a95ac8f3151df01cd5a93";
+        assert_eq!(blake2b(&data), expected);
+        // Fed in uneven pieces, the result is the same.
+        let mut hasher = Blake2b::new();
+        for piece in data.chunks(100) {
+            hasher.update(piece);
+        }
+        assert_eq!(hex::encode(hasher.finalize()), expected);
+        assert_eq!(
+            blake2b(&data[..129]),
+            "f59711d44a031d5f97a9413c065d1e614c417ede998590325f49bad2fd444d3e\
+             4418be19aec4e11449ac1a57207898bc57d76a1bcf3566292c20c683a5c4648f"
+        );
+    }
+
+    #[test]
+    fn verifies_a_signer_signature() {
+        let key = PublicKey::parse(PUBLIC_KEY).unwrap();
+        let comment = verify(SIGNED_DATA, SIGNATURE, &key).unwrap();
+        assert_eq!(comment, "timestamp:1760400000\tfile:teletraan.app.tar.gz");
+
+        let path = std::env::temp_dir().join(format!("teletraan-sig-{}", std::process::id()));
+        std::fs::write(&path, SIGNED_DATA).unwrap();
+        let result = verify_file(&path, SIGNATURE, &key);
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(result.unwrap(), comment);
+    }
+
+    #[test]
+    fn rejects_a_different_key() {
+        let mut key = PublicKey::parse(PUBLIC_KEY).unwrap();
+        key.key_id[0] ^= 1;
+        let error = verify(SIGNED_DATA, SIGNATURE, &key).unwrap_err();
+        assert!(error.contains("different key"), "{error}");
+    }
+
+    #[test]
+    fn rejects_changed_data() {
+        let key = PublicKey::parse(PUBLIC_KEY).unwrap();
+        let mut data = SIGNED_DATA.to_vec();
+        data[0] ^= 1;
+        let error = verify(&data, SIGNATURE, &key).unwrap_err();
+        assert_eq!(error, "The signature does not match");
+    }
+
+    #[test]
+    fn rejects_a_changed_trusted_comment() {
+        let key = PublicKey::parse(PUBLIC_KEY).unwrap();
+        let signature = decode_file(SIGNATURE)
+            .unwrap()
+            .replace("file:teletraan.app.tar.gz", "file:other.tar.gz");
+        let error = verify(SIGNED_DATA, &signature, &key).unwrap_err();
+        assert_eq!(error, "The signature's trusted comment does not match");
+    }
+}
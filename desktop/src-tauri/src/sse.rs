@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::async_runtime::JoinHandle;
+use tauri::http::{header, Request, StatusCode};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::backend;
+use crate::transport;
+
+/// Reconnect delay until the server sends a `retry:` field.
+const DEFAULT_RETRY: Duration = Duration::from_secs(1);
+
+/// Longest wait between reconnects after repeated failures.
+const MAX_RETRY: Duration = Duration::from_secs(30);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Managed state: running SSE relays by subscription id.
+#[derive(Default)]
+pub struct SseRelays(Mutex<Relays>);
+
+#[derive(Default)]
+struct Relays {
+    tasks: HashMap<u64, JoinHandle<()>>,
+    next_id: u64,
+}
+
+/// Payload of the `sse-event` event: one server-sent event.
+#[derive(Clone, Debug, Serialize)]
+struct SseEvent {
+    subscription: u64,
+    /// The `event:` field, `message` when the server sent none.
+    event: String,
+    data: String,
+    id: Option<String>,
+}
+
+/// Connection state of a relay, reported by `sse-status` and `ws-status`.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayState {
+    Connected,
+    /// The stream dropped; reconnecting after `retry_ms`.
+    Reconnecting,
+    /// Given up, e.g. because the endpoint answered 404.
+    Closed,
+}
+
+/// Payload of the `sse-status` event.
+#[derive(Clone, Debug, Serialize)]
+struct SseStatus {
+    subscription: u64,
+    state: RelayState,
+    retry_ms: Option<u64>,
+    error: Option<String>,
+}
+
+/// Incremental parser for the `text/event-stream` format.
+#[derive(Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+    /// Last event id seen, sent back as `Last-Event-ID` on reconnect.
+    last_id: Option<String>,
+    retry: Option<Duration>,
+}
+
+/// A dispatched event, before it is tagged with its subscription.
+struct Parsed {
+    event: String,
+    data: String,
+    id: Option<String>,
+}
+
+impl SseParser {
+    /// Feed a chunk of the body and return the events it completes.
+    fn push(&mut self, chunk: &[u8]) -> Vec<Parsed> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if line.is_empty() {
+                events.extend(self.dispatch());
+                continue;
+            }
+            if line.starts_with(':') {
+                // Comment, typically a keep-alive.
+                continue;
+            }
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => self.event = Some(value.to_string()),
+                "data" => self.data.push(value.to_string()),
+                "id" if !value.contains('\0') => self.last_id = Some(value.to_string()),
+                "retry" => {
+                    if let Ok(ms) = value.parse() {
+                        self.retry = Some(Duration::from_millis(ms));
+                    }
+                }
+                _ => {}
+            }
+        }
+        events
+    }
+
+    fn dispatch(&mut self) -> Option<Parsed> {
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(Parsed {
+            event: event.unwrap_or_else(|| "message".to_string()),
+            data: std::mem::take(&mut self.data).join("\n"),
+            id: self.last_id.clone(),
+        })
+    }
+
+    /// Drop a half-received event when the connection breaks.
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.event = None;
+        self.data.clear();
+    }
+}
+
+/// Tauri command: relay the server-sent events of the backend endpoint at
+/// `path` as `sse-event` events, so a long stream does not depend on the
+/// webview keeping its connection open. Returns the id to unsubscribe with.
+///
+/// The relay reconnects whenever the stream drops, waiting the server's
+/// `retry:` delay (doubled after each failed attempt, up to 30 s) and sending
+/// `Last-Event-ID`; `sse-status` reports each change. It stops on
+/// `unsubscribe_sse` or when the endpoint answers with a client error.
+#[tauri::command]
+pub async fn subscribe_sse(
+    app: AppHandle,
+    relays: State<'_, SseRelays>,
+    path: String,
+) -> Result<u64, String> {
+    if !path.starts_with('/') || path.starts_with("//") {
+        return Err(format!("Expected an absolute API path, got {path:?}"));
+    }
+    let mut relays = relays.0.lock().unwrap();
+    relays.next_id += 1;
+    let id = relays.next_id;
+    let task = tauri::async_runtime::spawn(relay(app, id, path));
+    relays.tasks.insert(id, task);
+    Ok(id)
+}
+
+/// Tauri command: stop a relay started by `subscribe_sse`.
+#[tauri::command]
+pub async fn unsubscribe_sse(relays: State<'_, SseRelays>, id: u64) -> Result<(), String> {
+    let task = relays.0.lock().unwrap().tasks.remove(&id);
+    match task {
+        Some(task) => {
+            task.abort();
+            Ok(())
+        }
+        None => Err(format!("No SSE subscription {id}")),
+    }
+}
+
+async fn relay(app: AppHandle, id: u64, path: String) {
+    let mut parser = SseParser::default();
+    let mut failures = 0u32;
+    loop {
+        let error = match stream_once(&app, id, &path, &mut parser, &mut failures).await {
+            Ok(()) => "Stream ended".to_string(),
+            Err(StreamError::Fatal(e)) => {
+                log::warn!("SSE relay for {path} stopped: {e}");
+                emit_status(&app, id, RelayState::Closed, None, Some(e));
+                if let Some(relays) = app.try_state::<SseRelays>() {
+                    relays.0.lock().unwrap().tasks.remove(&id);
+                }
+                return;
+            }
+            Err(StreamError::Retry(e)) => e,
+        };
+        parser.reset();
+        let base = parser.retry.unwrap_or(DEFAULT_RETRY);
+        let delay = base
+            .saturating_mul(2u32.saturating_pow(failures))
+            .min(MAX_RETRY);
+        failures = failures.saturating_add(1);
+        log::debug!("SSE relay for {path}: {error}; reconnecting in {delay:?}");
+        emit_status(
+            &app,
+            id,
+            RelayState::Reconnecting,
+            Some(delay.as_millis() as u64),
+            Some(error),
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Why a connection ended with an error.
+enum StreamError {
+    /// Worth reconnecting, e.g. the backend restarted.
+    Retry(String),
+    /// Reconnecting would fail the same way.
+    Fatal(String),
+}
+
+/// Hold one connection to the stream until it ends.
+async fn stream_once(
+    app: &AppHandle,
+    id: u64,
+    path: &str,
+    parser: &mut SseParser,
+    failures: &mut u32,
+) -> Result<(), StreamError> {
+    let endpoint = backend::endpoint(app)
+        .ok_or_else(|| StreamError::Retry("Backend has not been started".to_string()))?;
+    let mut builder = Request::get(path)
+        .header(header::ACCEPT, "text/event-stream")
+        .header(header::CACHE_CONTROL, "no-cache");
+    if let Some(last_id) = &parser.last_id {
+        builder = builder.header("Last-Event-ID", last_id);
+    }
+    let request = builder
+        .body(Vec::new())
+        .map_err(|e| StreamError::Fatal(format!("Invalid request for {path}: {e}")))?;
+    let resp = transport::open_stream(&endpoint, request, CONNECT_TIMEOUT)
+        .await
+        .map_err(StreamError::Retry)?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        let error = format!("HTTP {status}");
+        let transient = status.is_server_error()
+            || matches!(
+                status,
+                StatusCode::REQUEST_TIMEOUT | StatusCode::TOO_MANY_REQUESTS
+            );
+        return Err(if transient {
+            StreamError::Retry(error)
+        } else {
+            StreamError::Fatal(error)
+        });
+    }
+    *failures = 0;
+    emit_status(app, id, RelayState::Connected, None, None);
+
+    let mut body = resp.into_body();
+    while let Some(chunk) = body.chunk().await.map_err(StreamError::Retry)? {
+        for parsed in parser.push(&chunk) {
+            let event = SseEvent {
+                subscription: id,
+                event: parsed.event,
+                data: parsed.data,
+                id: parsed.id,
+            };
+            if let Err(e) = app.emit("sse-event", event) {
+                log::warn!("Failed to emit sse-event: {e}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn emit_status(
+    app: &AppHandle,
+    id: u64,
+    state: RelayState,
+    retry_ms: Option<u64>,
+    error: Option<String>,
+) {
+    let status = SseStatus {
+        subscription: id,
+        state,
+        retry_ms,
+        error,
+    };
+    if let Err(e) = app.emit("sse-status", status) {
+        log::warn!("Failed to emit sse-status: {e}");
+    }
+}
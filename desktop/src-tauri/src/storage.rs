@@ -0,0 +1,115 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::backups;
+use crate::logging;
+use crate::paths;
+
+/// Folder yfinance keeps its time-zone and cookie cache in, inside the
+/// platform cache dir. It is shared with any other program using yfinance.
+const MARKET_DATA_CACHE: &str = "py-yfinance";
+
+/// One entry of `get_storage_usage`.
+#[derive(Clone, Debug, Serialize)]
+pub struct StorageComponent {
+    /// `database`, `wal`, `backups`, `logs`, `market_data_cache` or `other`.
+    pub name: &'static str,
+    /// The file or folder; for `wal`, the database the files belong to, and
+    /// for `other`, the data dir.
+    pub path: PathBuf,
+    pub size_bytes: u64,
+}
+
+/// Result of `get_storage_usage`.
+#[derive(Clone, Debug, Serialize)]
+pub struct StorageUsage {
+    pub data_dir: PathBuf,
+    pub total_bytes: u64,
+    pub components: Vec<StorageComponent>,
+}
+
+/// Bytes used by the file `path`, or by everything below the folder `path`.
+pub fn size(path: &Path) -> u64 {
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => std::fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .map(|entry| size(&entry.path()))
+                    .sum()
+            })
+            .unwrap_or(0),
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    }
+}
+
+fn usage(data_dir: &Path, cache_dir: Option<PathBuf>) -> StorageUsage {
+    let database = paths::database_path(data_dir);
+    let database_files = backups::database_files(&database);
+    let database_size = size(&database);
+    let wal_size = database_files.iter().map(|path| size(path)).sum::<u64>() - database_size;
+    let mut components = vec![
+        StorageComponent {
+            name: "database",
+            path: database.clone(),
+            size_bytes: database_size,
+        },
+        StorageComponent {
+            name: "wal",
+            path: database,
+            size_bytes: wal_size,
+        },
+        StorageComponent {
+            name: "backups",
+            path: data_dir.join(backups::BACKUPS_DIR),
+            size_bytes: size(&data_dir.join(backups::BACKUPS_DIR)),
+        },
+        StorageComponent {
+            name: "logs",
+            path: logging::logs_dir(data_dir),
+            size_bytes: size(&logging::logs_dir(data_dir)),
+        },
+    ];
+    // Everything else in the data dir: settings, app state and, for the
+    // default profile, the other profiles.
+    let counted: u64 = components.iter().map(|c| c.size_bytes).sum();
+    components.push(StorageComponent {
+        name: "other",
+        path: data_dir.to_path_buf(),
+        size_bytes: size(data_dir).saturating_sub(counted),
+    });
+    if let Some(cache_dir) = cache_dir {
+        let path = cache_dir.join(MARKET_DATA_CACHE);
+        components.push(StorageComponent {
+            name: "market_data_cache",
+            size_bytes: size(&path),
+            path,
+        });
+    }
+    StorageUsage {
+        data_dir: data_dir.to_path_buf(),
+        total_bytes: components.iter().map(|c| c.size_bytes).sum(),
+        components,
+    }
+}
+
+/// Tauri command: how much disk space the app uses, by component, for the
+/// settings screen. The database is split from its write-ahead log (`wal`,
+/// with the shared-memory index), which can grow large between checkpoints.
+/// `market_data_cache` is the price data library's cache outside the data
+/// dir.
+#[tauri::command]
+pub async fn get_storage_usage(app: AppHandle) -> Result<StorageUsage, String> {
+    let data_dir = crate::resolve_data_dir(&app)?;
+    let cache_dir = app
+        .path()
+        .cache_dir()
+        .inspect_err(|e| log::warn!("Failed to resolve cache directory: {e}"))
+        .ok();
+    tauri::async_runtime::spawn_blocking(move || usage(&data_dir, cache_dir))
+        .await
+        .map_err(|e| format!("Failed to measure storage usage: {e}"))
+}
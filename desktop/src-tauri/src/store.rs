@@ -0,0 +1,107 @@
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Read a JSON document from disk, returning `None` if the file doesn't exist yet.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, String> {
+    let bytes = match std::fs::read(path) {
+        Ok(b) => b,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+    serde_json::from_slice(&bytes)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse {}: {e}", path.display()))
+}
+
+/// Write a JSON document atomically.
+///
+/// The document is written to a sibling `.tmp` file and renamed into place so
+/// a crash mid-write never leaves a truncated file behind.
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {}: {e}", parent.display()))?;
+    }
+
+    let json = serde_json::to_vec_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {e}", path.display()))?;
+
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, json)
+        .map_err(|e| format!("Failed to write {}: {e}", tmp_path.display()))?;
+    std::fs::rename(&tmp_path, path)
+        .map_err(|e| format!("Failed to replace {}: {e}", path.display()))
+}
+
+/// Append one JSON record as a line to a JSONL file, creating it if needed.
+pub fn append_jsonl<T: Serialize>(path: &Path, record: &T) -> Result<(), String> {
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {}: {e}", parent.display()))?;
+    }
+
+    let mut line = serde_json::to_vec(record)
+        .map_err(|e| format!("Failed to serialize record for {}: {e}", path.display()))?;
+    line.push(b'\n');
+
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut f| f.write_all(&line))
+        .map_err(|e| format!("Failed to append to {}: {e}", path.display()))
+}
+
+/// Keep a JSONL file under `max_bytes` by dropping all but its last `keep`
+/// lines once it grows past that.
+pub fn trim_jsonl(path: &Path, max_bytes: u64, keep: usize) -> Result<(), String> {
+    let len = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to stat {}: {e}", path.display()))?
+        .len();
+    if len <= max_bytes {
+        return Ok(());
+    }
+    let mut kept = crate::diagnostics::tail_lines(path, keep)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?
+        .join("\n");
+    kept.push('\n');
+
+    let tmp_path = path.with_extension("jsonl.tmp");
+    std::fs::write(&tmp_path, kept)
+        .map_err(|e| format!("Failed to write {}: {e}", tmp_path.display()))?;
+    std::fs::rename(&tmp_path, path)
+        .map_err(|e| format!("Failed to replace {}: {e}", path.display()))
+}
+
+/// Read a TOML document from disk, returning `None` if the file doesn't exist yet.
+pub fn read_toml<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, String> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+    toml::from_str(&text)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse {}: {e}", path.display()))
+}
+
+/// Write a TOML document atomically, like `write_json`.
+pub fn write_toml<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {}: {e}", parent.display()))?;
+    }
+
+    let text = toml::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {e}", path.display()))?;
+
+    let tmp_path = path.with_extension("toml.tmp");
+    std::fs::write(&tmp_path, text)
+        .map_err(|e| format!("Failed to write {}: {e}", tmp_path.display()))?;
+    std::fs::rename(&tmp_path, path)
+        .map_err(|e| format!("Failed to replace {}: {e}", path.display()))
+}
@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use sysinfo::{CpuRefreshKind, System};
+use tauri::{AppHandle, Manager};
+
+use crate::archive::ZipWriter;
+use crate::backend::{BackendProcess, BackendStatus};
+use crate::logging::{self, LogConfig};
+use crate::paths;
+use crate::redact;
+use crate::settings;
+use crate::versions::{self, Versions};
+
+/// `system.json` in the bundle.
+#[derive(Serialize)]
+struct SystemReport {
+    created_at: String,
+    versions: Option<Versions>,
+    os: Option<String>,
+    kernel: Option<String>,
+    arch: String,
+    cpu: Option<String>,
+    logical_cpus: usize,
+    physical_cores: Option<usize>,
+    total_memory_bytes: u64,
+    available_memory_bytes: u64,
+    backend: BackendStatus,
+}
+
+/// Tauri command: zip everything a bug report needs into `destination`, or
+/// into `teletraan-diagnostics-<time>.zip` in the Downloads folder, and return
+/// the archive's path.
+///
+/// The bundle holds `system.json` (versions, OS, CPU, memory and backend
+/// state), every retained log, the config files, the health history and the
+/// output of the last backend crash. Secrets are masked line by line. As with
+/// `export_logs`, the frontend picks `destination` with its own save dialog.
+#[tauri::command]
+pub async fn create_diagnostics_bundle(
+    app: AppHandle,
+    destination: Option<String>,
+) -> Result<String, String> {
+    let data_dir = crate::resolve_data_dir(&app)?;
+    let destination = match destination {
+        Some(path) => PathBuf::from(path),
+        None => settings::export_dir(&app)?.join(format!(
+            "teletraan-diagnostics-{}.zip",
+            chrono::Local::now().format("%Y-%m-%dT%H-%M-%S")
+        )),
+    };
+
+    let versions = versions::get_versions(app.clone())
+        .await
+        .inspect_err(|e| log::warn!("Failed to collect versions for diagnostics: {e}"))
+        .ok();
+    let (backend, crash_output) = {
+        let state = app.state::<BackendProcess>();
+        let backend = state.0.lock().unwrap();
+        (backend.status.clone(), backend.last_crash_output.clone())
+    };
+    let log_config = LogConfig::load(&app);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let system = system_report(versions, backend);
+        let system = serde_json::to_vec_pretty(&system)
+            .map_err(|e| format!("Failed to serialize system report: {e}"))?;
+
+        let mut zip = ZipWriter::create(&destination)?;
+        zip.add("system.json", &system)?;
+        let logs_dir = logging::logs_dir(&data_dir);
+        let mut logs = logging::all_log_files(&logs_dir, &log_config).unwrap_or_else(|e| {
+            log::warn!("{e}");
+            Vec::new()
+        });
+        logs.extend(logging::rotated_files(
+            &logs_dir.join("shell.log"),
+            &log_config,
+        ));
+        for path in logs {
+            // Rotations are stored decompressed.
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let name = name.strip_suffix(".gz").unwrap_or(&name);
+            add_redacted(&mut zip, &format!("logs/{name}"), &path)?;
+        }
+        for name in paths::CONFIG_FILES {
+            add_redacted(&mut zip, &format!("config/{name}"), &data_dir.join(name))?;
+        }
+        add_redacted(
+            &mut zip,
+            "health_history.jsonl",
+            &data_dir.join("health_history.jsonl"),
+        )?;
+        if !crash_output.is_empty() {
+            // Already masked when it was read from the backend.
+            zip.add("last_crash_output.log", crash_output.join("\n").as_bytes())?;
+        }
+        zip.finish()?;
+        log::info!("Wrote diagnostics bundle to {}", destination.display());
+        Ok(destination.display().to_string())
+    })
+    .await
+    .map_err(|e| format!("Failed to create diagnostics bundle: {e}"))?
+}
+
+fn system_report(versions: Option<Versions>, backend: BackendStatus) -> SystemReport {
+    let mut system = System::new();
+    system.refresh_memory();
+    system.refresh_cpu_list(CpuRefreshKind::nothing());
+    SystemReport {
+        created_at: chrono::Local::now().to_rfc3339(),
+        versions,
+        os: System::long_os_version(),
+        kernel: System::kernel_version(),
+        arch: System::cpu_arch(),
+        cpu: system
+            .cpus()
+            .first()
+            .map(|cpu| cpu.brand().trim().to_string()),
+        logical_cpus: system.cpus().len(),
+        physical_cores: System::physical_core_count(),
+        total_memory_bytes: system.total_memory(),
+        available_memory_bytes: system.available_memory(),
+        backend,
+    }
+}
+
+/// Add the text file at `path` with secrets masked; skipped if it does not
+/// exist.
+fn add_redacted(zip: &mut ZipWriter, name: &str, path: &Path) -> Result<(), String> {
+    if !path.is_file() {
+        return Ok(());
+    }
+    let text = logging::read_log_file(path)?;
+    let mut redacted = String::with_capacity(text.len());
+    for line in text.lines() {
+        redacted.push_str(&redact::redact(line));
+        redacted.push('\n');
+    }
+    zip.add(name, redacted.as_bytes())
+}
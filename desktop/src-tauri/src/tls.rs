@@ -0,0 +1,252 @@
+use std::io::Write;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use chrono::{DateTime, Datelike, Duration, Utc};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::store;
+
+/// Folder in the app data root holding the certificate, shared by every
+/// profile.
+const TLS_DIR: &str = "tls";
+const CERT_FILE: &str = "backend-cert.pem";
+const KEY_FILE: &str = "backend-key.pem";
+const INFO_FILE: &str = "backend-cert.json";
+
+/// Below Apple's limit of 825 days for a TLS server certificate.
+const VALIDITY_DAYS: i64 = 800;
+
+/// A certificate this close to expiring is replaced at the next spawn.
+const RENEW_BEFORE_DAYS: i64 = 30;
+
+/// Names every certificate covers, besides the configured host.
+const LOOPBACK_NAMES: [&str; 3] = ["localhost", "127.0.0.1", "::1"];
+
+// DER-encoded object identifiers.
+const OID_ECDSA_WITH_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+const OID_EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+const OID_PRIME256V1: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+const OID_COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+const OID_SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1d, 0x11];
+const OID_EXT_KEY_USAGE: &[u8] = &[0x55, 0x1d, 0x25];
+const OID_SERVER_AUTH: &[u8] = &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x01];
+
+/// `backend-cert.json`: what the certificate next to it covers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CertInfo {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+    /// Hex SHA-256 of the DER certificate, to compare with what a client
+    /// is shown.
+    pub sha256: String,
+    pub hosts: Vec<String>,
+    pub created_at: String,
+    pub expires_at: String,
+}
+
+/// Where the certificate of this install is kept.
+pub fn dir(app_data_root: &Path) -> PathBuf {
+    app_data_root.join(TLS_DIR)
+}
+
+/// DER element `tag` holding `content`.
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes: Vec<u8> = len
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|&b| b == 0)
+            .collect();
+        out.push(0x80 | bytes.len() as u8);
+        out.extend(bytes);
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+fn sequence(items: &[Vec<u8>]) -> Vec<u8> {
+    der(0x30, &items.concat())
+}
+
+fn oid(encoded: &[u8]) -> Vec<u8> {
+    der(0x06, encoded)
+}
+
+/// UTCTime until 2049, GeneralizedTime after, as RFC 5280 requires.
+fn time(at: DateTime<Utc>) -> Vec<u8> {
+    if at.year() < 2050 {
+        der(0x17, at.format("%y%m%d%H%M%SZ").to_string().as_bytes())
+    } else {
+        der(0x18, at.format("%Y%m%d%H%M%SZ").to_string().as_bytes())
+    }
+}
+
+/// A subjectAltName entry: an IP address, or else a DNS name.
+fn general_name(host: &str) -> Vec<u8> {
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => der(0x87, &ip.octets()),
+        Ok(IpAddr::V6(ip)) => der(0x87, &ip.octets()),
+        Err(_) => der(0x82, host.as_bytes()),
+    }
+}
+
+fn pem(label: &str, der: &[u8]) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(der);
+    let mut out = format!("-----BEGIN {label}-----\n");
+    for line in encoded.as_bytes().chunks(64) {
+        out.push_str(std::str::from_utf8(line).unwrap_or_default());
+        out.push('\n');
+    }
+    out.push_str(&format!("-----END {label}-----\n"));
+    out
+}
+
+/// A self-signed ECDSA P-256 certificate for `hosts`: the PEM certificate,
+/// the PEM PKCS#8 key, and the certificate's DER.
+fn generate(
+    hosts: &[String],
+    not_before: DateTime<Utc>,
+    not_after: DateTime<Utc>,
+) -> Result<(String, String, Vec<u8>), String> {
+    let rng = SystemRandom::new();
+    let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng)
+        .map_err(|_| "Failed to generate the certificate key")?;
+    let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &rng)
+        .map_err(|_| "Failed to load the certificate key")?;
+
+    let mut serial = [0u8; 16];
+    rng.fill(&mut serial)
+        .map_err(|_| "Failed to generate random bytes")?;
+    // Positive, and without a leading zero byte DER would drop.
+    serial[0] = (serial[0] & 0x7f) | 0x40;
+
+    let algorithm = sequence(&[oid(OID_ECDSA_WITH_SHA256)]);
+    let name = sequence(&[der(
+        0x31,
+        &sequence(&[oid(OID_COMMON_NAME), der(0x0c, b"Teletraan local backend")]),
+    )]);
+    let mut public_key = vec![0u8];
+    public_key.extend_from_slice(key_pair.public_key().as_ref());
+    let names: Vec<Vec<u8>> = hosts.iter().map(|host| general_name(host)).collect();
+    let extensions = sequence(&[
+        sequence(&[oid(OID_SUBJECT_ALT_NAME), der(0x04, &sequence(&names))]),
+        sequence(&[
+            oid(OID_EXT_KEY_USAGE),
+            der(0x04, &sequence(&[oid(OID_SERVER_AUTH)])),
+        ]),
+    ]);
+    let tbs = sequence(&[
+        der(0xa0, &der(0x02, &[2])),
+        der(0x02, &serial),
+        algorithm.clone(),
+        name.clone(),
+        sequence(&[time(not_before), time(not_after)]),
+        name,
+        sequence(&[
+            sequence(&[oid(OID_EC_PUBLIC_KEY), oid(OID_PRIME256V1)]),
+            der(0x03, &public_key),
+        ]),
+        der(0xa3, &extensions),
+    ]);
+    let signature = key_pair
+        .sign(&rng, &tbs)
+        .map_err(|_| "Failed to sign the certificate")?;
+    let mut signature_bits = vec![0u8];
+    signature_bits.extend_from_slice(signature.as_ref());
+    let cert = sequence(&[tbs, algorithm, der(0x03, &signature_bits)]);
+
+    Ok((
+        pem("CERTIFICATE", &cert),
+        pem("PRIVATE KEY", pkcs8.as_ref()),
+        cert,
+    ))
+}
+
+/// Write the private key readable by the owner only.
+fn write_key(path: &Path, key: &str) -> Result<(), String> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(key.as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// The certificate in `dir`, if there is one that both files are there for.
+pub fn load(dir: &Path) -> Result<Option<CertInfo>, String> {
+    let Some(mut info) = store::read_json::<CertInfo>(&dir.join(INFO_FILE))? else {
+        return Ok(None);
+    };
+    // Still found after the data directory moved.
+    info.cert = dir.join(CERT_FILE);
+    info.key = dir.join(KEY_FILE);
+    Ok((info.cert.is_file() && info.key.is_file()).then_some(info))
+}
+
+/// The PEM certificate `info` describes.
+pub fn read_pem(info: &CertInfo) -> Result<String, String> {
+    std::fs::read_to_string(&info.cert)
+        .map_err(|e| format!("Failed to read {}: {e}", info.cert.display()))
+}
+
+/// The certificate in `dir` for a backend on `host`, generated on first use
+/// and again when it is about to expire or does not cover `host`.
+pub fn ensure(dir: &Path, host: &str) -> Result<CertInfo, String> {
+    let now = Utc::now();
+    if let Some(info) = load(dir)? {
+        let current = DateTime::parse_from_rfc3339(&info.expires_at)
+            .is_ok_and(|expires| expires > now + Duration::days(RENEW_BEFORE_DAYS));
+        if current && info.hosts.iter().any(|known| known == host) {
+            return Ok(info);
+        }
+        log::info!(
+            "Replacing the backend TLS certificate, which expires {} for {:?}",
+            info.expires_at,
+            info.hosts
+        );
+    }
+
+    let mut hosts: Vec<String> = LOOPBACK_NAMES.iter().map(|name| name.to_string()).collect();
+    if !hosts.iter().any(|known| known == host) {
+        hosts.push(host.to_string());
+    }
+    // A day early, so a clock slightly behind still accepts it.
+    let not_before = now - Duration::days(1);
+    let not_after = now + Duration::days(VALIDITY_DAYS);
+    let (cert, key, der) = generate(&hosts, not_before, not_after)?;
+
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    let info = CertInfo {
+        cert: dir.join(CERT_FILE),
+        key: dir.join(KEY_FILE),
+        sha256: hex::encode(Sha256::digest(&der)),
+        hosts,
+        created_at: now.to_rfc3339(),
+        expires_at: not_after.to_rfc3339(),
+    };
+    write_key(&info.key, &key)?;
+    std::fs::write(&info.cert, cert)
+        .map_err(|e| format!("Failed to write {}: {e}", info.cert.display()))?;
+    store::write_json(&dir.join(INFO_FILE), &info)?;
+    log::info!(
+        "Generated a backend TLS certificate for {:?} (SHA-256 {}), valid until {}",
+        info.hosts,
+        info.sha256,
+        info.expires_at
+    );
+    Ok(info)
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::audit::{self, AuditEvent};
+use crate::config_watch;
+use crate::settings;
+use crate::store;
+
+/// Marks a file written by `export_settings`.
+const FORMAT: &str = "teletraan-settings";
+
+/// Layout of the exported file; bumped when it changes incompatibly. Version
+/// 1 also held the JSON config files that are now part of the settings.
+const FORMAT_VERSION: u32 = 2;
+
+/// Copy of the configuration taken right before an import replaces it.
+const BACKUP_FILE: &str = "settings-before-import.json";
+
+/// The file `export_settings` writes and `import_settings` reads.
+#[derive(Serialize, Deserialize)]
+struct SettingsFile {
+    format: String,
+    version: u32,
+    app_version: String,
+    exported_at: String,
+    /// `settings.toml`, by section. Automation state, the review queue and
+    /// workspaces are data, not configuration, and stay behind.
+    settings: serde_json::Value,
+    /// The JSON config files of a version 1 export, by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    files: BTreeMap<String, serde_json::Value>,
+    /// Secrets left out, e.g. `env.ANTHROPIC_API_KEY`.
+    excluded: Vec<String>,
+}
+
+/// Result of `import_settings`.
+#[derive(Clone, Debug, Serialize)]
+pub struct ImportReport {
+    /// `settings.toml`, if it changed.
+    pub imported: Vec<String>,
+    /// Settings that were not imported, and why.
+    pub skipped: Vec<String>,
+    /// The configuration as it was before the import.
+    pub backup: PathBuf,
+    /// Some imported settings only apply once the backend restarts, which
+    /// `backend-restart-required` asks the user to confirm.
+    pub restart_required: bool,
+}
+
+fn collect(app: &AppHandle) -> Result<SettingsFile, String> {
+    let (settings, excluded) = settings::export(app)?;
+    Ok(SettingsFile {
+        format: FORMAT.to_string(),
+        version: FORMAT_VERSION,
+        app_version: app.package_info().version.to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        settings,
+        files: BTreeMap::new(),
+        excluded,
+    })
+}
+
+/// Tauri command: write the shell's configuration to one JSON file at
+/// `destination`, or to `teletraan-settings-<time>.json` in the export
+/// folder, and return its path.
+///
+/// The file holds `settings.toml`. API keys, tokens and proxy credentials are
+/// left out and listed under `excluded`, so the file can be shared or synced.
+#[tauri::command]
+pub async fn export_settings(
+    app: AppHandle,
+    destination: Option<String>,
+) -> Result<String, String> {
+    let destination = match destination {
+        Some(path) => PathBuf::from(path),
+        None => settings::export_dir(&app)?.join(format!(
+            "teletraan-settings-{}.json",
+            chrono::Local::now().format("%Y-%m-%dT%H-%M-%S")
+        )),
+    };
+    let file = collect(&app)?;
+    store::write_json(&destination, &file)?;
+    log::info!(
+        "Exported settings to {} ({} secrets left out)",
+        destination.display(),
+        file.excluded.len()
+    );
+    Ok(destination.display().to_string())
+}
+
+/// Tauri command: replace the configuration with the file `source` written by
+/// `export_settings`, e.g. on a second machine or after `reset_app_data`.
+///
+/// The current configuration is first saved to `settings-before-import.json`
+/// in the data dir. Secrets the file leaves out keep their local values, and
+/// invalid settings are skipped and reported rather than failing the import.
+/// Settings the shell reads on use apply right away; the rest wait for a
+/// backend restart the user confirms.
+#[tauri::command]
+pub async fn import_settings(app: AppHandle, source: String) -> Result<ImportReport, String> {
+    let source = PathBuf::from(source);
+    let file: SettingsFile =
+        store::read_json(&source)?.ok_or_else(|| format!("{} does not exist", source.display()))?;
+    if file.format != FORMAT {
+        return Err(format!(
+            "{} is not a Teletraan settings file",
+            source.display()
+        ));
+    }
+    if file.version > FORMAT_VERSION {
+        return Err(format!(
+            "{} was exported by a newer version of Teletraan ({})",
+            source.display(),
+            file.app_version
+        ));
+    }
+
+    let data_dir = crate::resolve_data_dir(&app)?;
+    let backup = data_dir.join(BACKUP_FILE);
+    store::write_json(&backup, &collect(&app)?)?;
+
+    let (changed, skipped) = settings::import(&app, file.settings, file.files)?;
+    let mut imported = Vec::new();
+    if !changed.is_empty() {
+        imported.push("settings.toml".to_string());
+    }
+    let restart: Vec<String> = changed
+        .into_iter()
+        .filter(|key| config_watch::needs_backend_restart(key))
+        .collect();
+
+    let restart_required = !restart.is_empty();
+    config_watch::require_restart(&app, restart);
+    log::info!(
+        "Imported settings from {}: {imported:?} (skipped: {skipped:?})",
+        source.display()
+    );
+    audit::record(
+        &app,
+        AuditEvent::SettingsImported,
+        true,
+        format!("{imported:?} from {}", source.display()),
+    );
+    Ok(ImportReport {
+        imported,
+        skipped,
+        backup,
+        restart_required,
+    })
+}
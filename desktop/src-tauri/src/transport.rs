@@ -0,0 +1,564 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::de::DeserializeOwned;
+use tauri::http::{header, Method, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager};
+
+use crate::backend::{self, BackendProcess};
+
+/// URI scheme the webview uses to reach a backend on a Unix domain socket.
+pub const PROXY_SCHEME: &str = "teletraan";
+
+/// Base URL the frontend uses for the proxy (see `get_backend_url`).
+pub const PROXY_URL: &str = "teletraan://localhost";
+
+/// Header carrying the token the backend was started with (see
+/// `set_auth_token`).
+pub const AUTH_HEADER: &str = "X-Teletraan-Token";
+
+/// Header carrying the token the privileged endpoints require (see
+/// `set_shell_token`).
+pub const SHELL_HEADER: &str = "X-Teletraan-Shell-Token";
+
+/// Token the current backend requires on every request; `None` for one the
+/// shell did not start.
+static AUTH_TOKEN: RwLock<Option<String>> = RwLock::new(None);
+
+/// Token the current backend's privileged endpoints require, known only to
+/// the shell: never given to the webview, put in a URL or added to proxied
+/// requests. `None` for a backend the shell did not start.
+static SHELL_TOKEN: RwLock<Option<String>> = RwLock::new(None);
+
+/// PEM certificate of a backend serving HTTPS, the only one trusted for it;
+/// `None` for plain HTTP.
+static TRUSTED_CERT: RwLock<Option<String>> = RwLock::new(None);
+
+/// Timeout for requests proxied from the frontend; analysis runs are slow.
+const PROXY_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Tries of an idempotent `backend_request` before its error is returned.
+const REQUEST_ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry, doubled for each one after.
+const REQUEST_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// Where the backend API can be reached.
+#[derive(Clone, Debug)]
+pub enum Endpoint {
+    /// Base URL of a TCP listener, e.g. `http://127.0.0.1:8000`.
+    Tcp(String),
+    /// Unix domain socket the backend serves HTTP on.
+    Unix(PathBuf),
+}
+
+/// Set the token sent with every request to the backend from now on.
+pub fn set_auth_token(token: Option<String>) {
+    *AUTH_TOKEN.write().unwrap() = token;
+}
+
+/// The token sent with every request to the backend, if it requires one.
+pub fn auth_token() -> Option<String> {
+    AUTH_TOKEN.read().unwrap().clone()
+}
+
+/// Set the token sent to the backend's privileged endpoints from now on.
+pub fn set_shell_token(token: Option<String>) {
+    *SHELL_TOKEN.write().unwrap() = token;
+}
+
+/// The token the backend's privileged endpoints take, if it has them.
+pub fn shell_token() -> Option<String> {
+    SHELL_TOKEN.read().unwrap().clone()
+}
+
+/// Trust only `cert` (PEM) for the backend from now on, or no certificate
+/// beyond the system's when `None`.
+pub fn set_trusted_cert(cert: Option<String>) {
+    *TRUSTED_CERT.write().unwrap() = cert;
+}
+
+pub fn trusted_cert() -> Option<String> {
+    TRUSTED_CERT.read().unwrap().clone()
+}
+
+/// A reqwest client builder that, for a backend on HTTPS, accepts its
+/// certificate and nothing else.
+fn http_builder() -> Result<reqwest::ClientBuilder, String> {
+    let builder = reqwest::Client::builder();
+    let Some(cert) = trusted_cert() else {
+        return Ok(builder);
+    };
+    let cert = reqwest::Certificate::from_pem(cert.as_bytes())
+        .map_err(|e| format!("Invalid backend certificate: {e}"))?;
+    Ok(builder
+        .tls_built_in_root_certs(false)
+        .add_root_certificate(cert)
+        .https_only(true))
+}
+
+/// Characters left as they are in a path segment; the rest of ASCII is
+/// percent-encoded, along with everything beyond it.
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// `value` encoded for use as one segment of a request path, so a symbol
+/// such as `^GSPC` is sent intact and none can reach into the query.
+pub fn path_segment(value: &str) -> String {
+    utf8_percent_encode(value, PATH_SEGMENT).to_string()
+}
+
+/// A backend's privileged endpoints (maintenance, log level, packs), and the
+/// shell token they take.
+pub struct Privileged {
+    pub endpoint: Endpoint,
+    token: String,
+}
+
+impl Privileged {
+    /// Headers authorizing a request to a privileged endpoint.
+    pub fn headers(&self) -> [(&'static str, &str); 1] {
+        [(SHELL_HEADER, &self.token)]
+    }
+}
+
+/// Where to reach the backend for a privileged endpoint, which only a backend
+/// started by this app or another of its windows has.
+pub fn privileged_endpoint(app: &AppHandle) -> Result<Privileged, String> {
+    let endpoint = {
+        let state = app.state::<BackendProcess>();
+        let backend = state.0.lock().unwrap();
+        backend.endpoint.clone()
+    };
+    let endpoint = endpoint.ok_or("Backend has not been started")?;
+    let token = shell_token().ok_or("The backend was not started by this app; restart it first")?;
+    Ok(Privileged { endpoint, token })
+}
+
+/// Add the backend token to `request` unless it carries one already.
+fn authenticate(request: &mut Request<Vec<u8>>) {
+    let Some(token) = auth_token() else {
+        return;
+    };
+    if let Ok(value) = header::HeaderValue::from_str(&token) {
+        request.headers_mut().entry(AUTH_HEADER).or_insert(value);
+    }
+}
+
+/// HTTP client that speaks to the backend over either transport.
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    timeout: Duration,
+}
+
+impl Client {
+    pub fn new(timeout: Duration) -> Result<Self, String> {
+        let http = http_builder()?
+            .timeout(timeout)
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {e}"))?;
+        Ok(Self { http, timeout })
+    }
+
+    /// Send `request`, whose URI holds only the path and query, to `endpoint`.
+    pub async fn send(
+        &self,
+        endpoint: &Endpoint,
+        mut request: Request<Vec<u8>>,
+    ) -> Result<Response<Vec<u8>>, String> {
+        authenticate(&mut request);
+        match endpoint {
+            Endpoint::Tcp(base_url) => self.send_tcp(base_url, request).await,
+            Endpoint::Unix(socket) => {
+                tokio::time::timeout(self.timeout, send_unix(socket, request))
+                    .await
+                    .map_err(|_| format!("Request timed out after {:?}", self.timeout))?
+            }
+        }
+    }
+
+    pub async fn get(&self, endpoint: &Endpoint, path: &str) -> Result<Response<Vec<u8>>, String> {
+        let request = Request::get(path)
+            .body(Vec::new())
+            .map_err(|e| format!("Invalid request for {path}: {e}"))?;
+        self.send(endpoint, request).await
+    }
+
+    pub async fn post(
+        &self,
+        endpoint: &Endpoint,
+        path: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<Response<Vec<u8>>, String> {
+        let mut builder = Request::post(path);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        let request = builder
+            .body(Vec::new())
+            .map_err(|e| format!("Invalid request for {path}: {e}"))?;
+        self.send(endpoint, request).await
+    }
+
+    /// POST `body` as JSON.
+    pub async fn post_json<T: serde::Serialize>(
+        &self,
+        endpoint: &Endpoint,
+        path: &str,
+        headers: &[(&str, &str)],
+        body: &T,
+    ) -> Result<Response<Vec<u8>>, String> {
+        let body = serde_json::to_vec(body).map_err(|e| e.to_string())?;
+        let mut builder = Request::post(path).header(header::CONTENT_TYPE, "application/json");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        let request = builder
+            .body(body)
+            .map_err(|e| format!("Invalid request for {path}: {e}"))?;
+        self.send(endpoint, request).await
+    }
+
+    async fn send_tcp(
+        &self,
+        base_url: &str,
+        request: Request<Vec<u8>>,
+    ) -> Result<Response<Vec<u8>>, String> {
+        let (parts, body) = request.into_parts();
+        let path = parts.uri.path_and_query().map_or("/", |pq| pq.as_str());
+        let resp = self
+            .http
+            .request(parts.method, format!("{base_url}{path}"))
+            .headers(parts.headers)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut builder = Response::builder().status(resp.status());
+        if let Some(headers) = builder.headers_mut() {
+            *headers = resp.headers().clone();
+        }
+        let body = resp.bytes().await.map_err(|e| e.to_string())?;
+        builder
+            .body(body.to_vec())
+            .map_err(|e| format!("Invalid response: {e}"))
+    }
+}
+
+/// Parse a JSON response body.
+pub fn json<T: DeserializeOwned>(response: &Response<Vec<u8>>) -> Result<T, String> {
+    serde_json::from_slice(response.body()).map_err(|e| e.to_string())
+}
+
+/// HTTP/1.1 over a Unix domain socket, one connection per request.
+#[cfg(unix)]
+async fn send_unix(
+    socket: &std::path::Path,
+    request: Request<Vec<u8>>,
+) -> Result<Response<Vec<u8>>, String> {
+    use http_body_util::BodyExt;
+
+    let (parts, body) = open_unix(socket, request).await?.into_parts();
+    let body = body.collect().await.map_err(|e| e.to_string())?.to_bytes();
+    Ok(Response::from_parts(parts, body.to_vec()))
+}
+
+/// Send `request` over `socket` and return once the response headers arrive.
+#[cfg(unix)]
+async fn open_unix(
+    socket: &std::path::Path,
+    request: Request<Vec<u8>>,
+) -> Result<Response<hyper::body::Incoming>, String> {
+    use http_body_util::Full;
+    use hyper::body::Bytes;
+    use hyper_util::rt::TokioIo;
+
+    let stream = tokio::net::UnixStream::connect(socket)
+        .await
+        .map_err(|e| format!("Failed to connect to {}: {e}", socket.display()))?;
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+        .await
+        .map_err(|e| format!("HTTP handshake over {} failed: {e}", socket.display()))?;
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = connection.await {
+            log::debug!("Backend socket connection closed with error: {e}");
+        }
+    });
+
+    let (mut parts, body) = request.into_parts();
+    parts
+        .headers
+        .insert(header::HOST, header::HeaderValue::from_static("localhost"));
+    sender
+        .send_request(Request::from_parts(parts, Full::new(Bytes::from(body))))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(unix))]
+async fn send_unix(
+    _socket: &std::path::Path,
+    _request: Request<Vec<u8>>,
+) -> Result<Response<Vec<u8>>, String> {
+    Err("Unix domain sockets are not supported on this platform".to_string())
+}
+
+/// Body of a response read as it arrives, for long-lived streams.
+pub enum BodyStream {
+    Tcp(reqwest::Response),
+    #[cfg(unix)]
+    Unix(hyper::body::Incoming),
+}
+
+impl BodyStream {
+    /// The next chunk of the body, or `None` once it has ended.
+    pub async fn chunk(&mut self) -> Result<Option<Vec<u8>>, String> {
+        match self {
+            Self::Tcp(resp) => resp
+                .chunk()
+                .await
+                .map(|chunk| chunk.map(|bytes| bytes.to_vec()))
+                .map_err(|e| e.to_string()),
+            #[cfg(unix)]
+            Self::Unix(body) => {
+                use http_body_util::BodyExt;
+                loop {
+                    let Some(frame) = body.frame().await else {
+                        return Ok(None);
+                    };
+                    // Skip trailers; only data frames carry the body.
+                    if let Ok(data) = frame.map_err(|e| e.to_string())?.into_data() {
+                        return Ok(Some(data.to_vec()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Send `request`, whose URI holds only the path and query, to `endpoint` and
+/// return as soon as the response headers arrive.
+///
+/// Unlike `Client`, there is no overall timeout: `connect_timeout` bounds only
+/// connecting, so the body can stream for as long as the backend keeps it open.
+pub async fn open_stream(
+    endpoint: &Endpoint,
+    mut request: Request<Vec<u8>>,
+    connect_timeout: Duration,
+) -> Result<Response<BodyStream>, String> {
+    authenticate(&mut request);
+    match endpoint {
+        Endpoint::Tcp(base_url) => {
+            let (parts, body) = request.into_parts();
+            let path = parts.uri.path_and_query().map_or("/", |pq| pq.as_str());
+            let resp = http_builder()?
+                .connect_timeout(connect_timeout)
+                .build()
+                .map_err(|e| format!("Failed to build HTTP client: {e}"))?
+                .request(parts.method, format!("{base_url}{path}"))
+                .headers(parts.headers)
+                .body(body)
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            let mut builder = Response::builder().status(resp.status());
+            if let Some(headers) = builder.headers_mut() {
+                *headers = resp.headers().clone();
+            }
+            builder
+                .body(BodyStream::Tcp(resp))
+                .map_err(|e| format!("Invalid response: {e}"))
+        }
+        #[cfg(unix)]
+        Endpoint::Unix(socket) => {
+            let resp = tokio::time::timeout(connect_timeout, open_unix(socket, request))
+                .await
+                .map_err(|_| format!("Request timed out after {connect_timeout:?}"))??;
+            Ok(resp.map(BodyStream::Unix))
+        }
+        #[cfg(not(unix))]
+        Endpoint::Unix(_) => {
+            Err("Unix domain sockets are not supported on this platform".to_string())
+        }
+    }
+}
+
+/// Handle a `teletraan://` request from the webview by forwarding it to the
+/// backend, so the frontend can use a socket-bound backend like a TCP one.
+pub async fn proxy(app: &AppHandle, request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    /// Rebuilt when the backend certificate changes.
+    static CLIENT: Mutex<Option<(Option<String>, Client)>> = Mutex::new(None);
+
+    let result: Result<_, String> = async {
+        let client = {
+            let cert = trusted_cert();
+            let mut cached = CLIENT.lock().unwrap();
+            match &*cached {
+                Some((built_for, client)) if *built_for == cert => client.clone(),
+                _ => {
+                    let client = Client::new(PROXY_TIMEOUT)?;
+                    *cached = Some((cert, client.clone()));
+                    client
+                }
+            }
+        };
+        let endpoint = backend::endpoint(app).ok_or("Backend has not been started")?;
+
+        let (mut parts, body) = request.into_parts();
+        // The webview's URI is `teletraan://localhost/<path>`; keep only the path.
+        let uri = parts
+            .uri
+            .path_and_query()
+            .map_or("/", |pq| pq.as_str())
+            .parse()
+            .map_err(|e| format!("Invalid request URI: {e}"))?;
+        parts.uri = uri;
+        parts.headers.remove(header::HOST);
+        client
+            .send(&endpoint, Request::from_parts(parts, body))
+            .await
+    }
+    .await;
+
+    result.unwrap_or_else(|e| {
+        log::warn!("Backend proxy request failed: {e}");
+        Response::builder()
+            .status(StatusCode::BAD_GATEWAY)
+            .header(header::CONTENT_TYPE, "text/plain")
+            .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+            .body(e.into_bytes())
+            .expect("static response parts are valid")
+    })
+}
+
+/// Result of `backend_request`.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct BackendResponse {
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    /// Parsed JSON for a JSON response, otherwise the body as text.
+    pub body: serde_json::Value,
+}
+
+/// Tauri command: send a request to the backend wherever it currently
+/// listens, so the frontend needs no base URL.
+///
+/// `path` is the API path with any query, e.g. `/api/v1/stocks?limit=5`, and
+/// `body` is sent as JSON. GET, HEAD, PUT, DELETE and OPTIONS are retried with
+/// backoff on connection errors and 502/503/504 (e.g. while the backend
+/// restarts); other methods are sent once. Non-2xx responses are returned, not
+/// treated as errors.
+#[tauri::command]
+pub async fn backend_request(
+    app: AppHandle,
+    method: String,
+    path: String,
+    body: Option<serde_json::Value>,
+    timeout_ms: Option<u64>,
+) -> Result<BackendResponse, String> {
+    let method = Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+        .map_err(|e| format!("Invalid method {method:?}: {e}"))?;
+    if !path.starts_with('/') || path.starts_with("//") {
+        return Err(format!("Expected an absolute API path, got {path:?}"));
+    }
+    let body = body
+        .map(|body| serde_json::to_vec(&body))
+        .transpose()
+        .map_err(|e| format!("Failed to serialize request body: {e}"))?;
+    let timeout = timeout_ms.map_or(PROXY_TIMEOUT, Duration::from_millis);
+    let client = Client::new(timeout)?;
+    let attempts = if method.is_idempotent() {
+        REQUEST_ATTEMPTS
+    } else {
+        1
+    };
+
+    let mut delay = REQUEST_RETRY_DELAY;
+    for attempt in 1..=attempts {
+        // Looked up on every try: a restarted backend may be on a new port.
+        let endpoint = {
+            let state = app.state::<BackendProcess>();
+            let backend = state.0.lock().unwrap();
+            backend.endpoint.clone()
+        };
+        let result = match endpoint {
+            Some(endpoint) => {
+                let mut builder = Request::builder().method(method.clone()).uri(&path);
+                if body.is_some() {
+                    builder = builder.header(header::CONTENT_TYPE, "application/json");
+                }
+                let request = builder
+                    .body(body.clone().unwrap_or_default())
+                    .map_err(|e| format!("Invalid request for {path}: {e}"))?;
+                client.send(&endpoint, request).await
+            }
+            None => Err("Backend has not been started".to_string()),
+        };
+        let retryable = match &result {
+            Ok(resp) => matches!(
+                resp.status(),
+                StatusCode::BAD_GATEWAY
+                    | StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::GATEWAY_TIMEOUT
+            ),
+            Err(_) => true,
+        };
+        if !retryable || attempt == attempts {
+            let resp = result.map_err(|e| format!("{method} {path} failed: {e}"))?;
+            return Ok(to_backend_response(resp));
+        }
+        log::debug!("{method} {path} failed (attempt {attempt}/{attempts}), retrying in {delay:?}");
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+    }
+    unreachable!("the last attempt always returns")
+}
+
+fn to_backend_response(resp: Response<Vec<u8>>) -> BackendResponse {
+    let headers = resp
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    let is_json = resp
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("json"));
+    let body = if resp.body().is_empty() {
+        serde_json::Value::Null
+    } else if is_json {
+        serde_json::from_slice(resp.body()).unwrap_or_else(|_| {
+            serde_json::Value::String(String::from_utf8_lossy(resp.body()).into_owned())
+        })
+    } else {
+        serde_json::Value::String(String::from_utf8_lossy(resp.body()).into_owned())
+    };
+    BackendResponse {
+        status: resp.status().as_u16(),
+        headers,
+        body,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_symbols_as_path_segments() {
+        assert_eq!(path_segment("AAPL"), "AAPL");
+        assert_eq!(path_segment("BRK.B"), "BRK.B");
+        assert_eq!(path_segment("^GSPC"), "%5EGSPC");
+        assert_eq!(path_segment("BRK/B"), "BRK%2FB");
+        assert_eq!(path_segment("A B?c=1"), "A%20B%3Fc%3D1");
+    }
+}
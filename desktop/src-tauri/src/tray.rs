@@ -0,0 +1,55 @@
+use tauri::menu::{Menu, MenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::AppHandle;
+
+use crate::automation;
+
+const HALT_ID: &str = "halt_automation";
+const QUIT_ID: &str = "quit";
+
+/// Build the system tray icon and its menu.
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let halt = MenuItem::with_id(
+        app,
+        HALT_ID,
+        "Halt All Automation",
+        true,
+        Some(automation::HALT_SHORTCUT),
+    )?;
+    let quit = MenuItem::with_id(app, QUIT_ID, "Quit Teletraan", true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[&halt, &quit])?;
+
+    let mut tray = TrayIconBuilder::with_id("main")
+        .tooltip("Teletraan")
+        .menu(&menu)
+        .on_menu_event(|app, event| match event.id.as_ref() {
+            HALT_ID => {
+                if let Err(e) = automation::halt(app, "tray", "Halted from tray menu") {
+                    log::error!("Failed to halt automation from tray: {e}");
+                }
+            }
+            QUIT_ID => app.exit(0),
+            _ => {}
+        });
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app)?;
+
+    Ok(())
+}
+
+/// Show in the tray tooltip that the app is using a backend it did not start.
+pub fn set_external_backend(app: &AppHandle, external: bool) {
+    let Some(tray) = app.tray_by_id("main") else {
+        return;
+    };
+    let tooltip = if external {
+        "Teletraan (external backend)"
+    } else {
+        "Teletraan"
+    };
+    if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+        log::warn!("Failed to update tray tooltip: {e}");
+    }
+}
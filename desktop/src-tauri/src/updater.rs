@@ -0,0 +1,544 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::audit::{self, AuditEvent};
+use crate::backend;
+use crate::backend_update::{self, BackendUpdateInfo};
+use crate::settings::{self, UpdateChannel};
+use crate::signature::{self, PublicKey};
+
+/// minisign public key the releases are signed with, base64-encoded as
+/// `tauri signer generate` prints it. Builds made without one cannot update
+/// themselves.
+const PUBLIC_KEY: Option<&str> = option_env!("TELETRAAN_UPDATER_PUBKEY");
+
+/// Feed of the latest release on each channel, in the `latest.json` format
+/// of Tauri's updater.
+const STABLE_FEED: &str =
+    "https://github.com/barkain/teletraan/releases/latest/download/latest.json";
+const BETA_FEED: &str = "https://github.com/barkain/teletraan/releases/download/beta/latest.json";
+
+/// Where to get a new version by hand when the app cannot update itself.
+const RELEASES_URL: &str = "https://github.com/barkain/teletraan/releases";
+
+/// Downloads go here, in the app data root, and are removed once installed.
+const UPDATES_DIR: &str = "updates";
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Shortest interval between two `update-download-progress` events.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A release feed: the newest version and its download for each platform.
+#[derive(Clone, Debug, Deserialize)]
+struct Feed {
+    version: String,
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    pub_date: Option<String>,
+    platforms: BTreeMap<String, Asset>,
+}
+
+/// A download listed in a feed.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Asset {
+    /// The minisign `.sig` file of the download, base64-encoded.
+    pub signature: String,
+    pub url: String,
+}
+
+/// Payload of `update-available` and `update-installing`.
+#[derive(Clone, Debug, Serialize)]
+pub struct UpdateInfo {
+    pub version: String,
+    pub current_version: String,
+    pub channel: UpdateChannel,
+    pub notes: Option<String>,
+    pub pub_date: Option<String>,
+    /// Size of the download, if the server reports it.
+    pub size_bytes: Option<u64>,
+}
+
+/// What a download is for, in `update-download-progress`.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateComponent {
+    App,
+    Backend,
+}
+
+/// Payload of the `update-download-progress` event.
+#[derive(Clone, Debug, Serialize)]
+struct DownloadProgress {
+    component: UpdateComponent,
+    version: String,
+    bytes: u64,
+    total_bytes: Option<u64>,
+}
+
+/// Result of `check_for_updates`.
+#[derive(Clone, Debug, Serialize)]
+pub struct UpdateCheck {
+    pub channel: UpdateChannel,
+    pub current_version: String,
+    /// The backend version in use, which differs from the app's once the
+    /// backend was updated on its own.
+    pub backend_version: String,
+    /// A newer app, if there is one.
+    pub app: Option<UpdateInfo>,
+    /// A newer backend, if there is one.
+    pub backend: Option<BackendUpdateInfo>,
+    /// Why the app's feed could not be read.
+    pub app_error: Option<String>,
+    /// Why the backend's feed could not be read.
+    pub backend_error: Option<String>,
+    pub checked_at: String,
+}
+
+fn feed_url(channel: UpdateChannel) -> &'static str {
+    match channel {
+        UpdateChannel::Stable => STABLE_FEED,
+        UpdateChannel::Beta => BETA_FEED,
+    }
+}
+
+/// The key releases are signed with, if this build has one.
+pub fn public_key() -> Result<PublicKey, String> {
+    let key = PUBLIC_KEY.ok_or_else(|| {
+        format!("This build cannot update itself; download new versions from {RELEASES_URL}")
+    })?;
+    PublicKey::parse(key).map_err(|e| format!("Invalid update key in this build: {e}"))
+}
+
+/// This platform as feeds name it, e.g. `darwin-aarch64`.
+pub fn target() -> String {
+    let os = match std::env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    };
+    format!("{os}-{}", std::env::consts::ARCH)
+}
+
+/// Keys of this platform's download in the feed, most specific first, e.g.
+/// `darwin-aarch64-app` then `darwin-aarch64`.
+fn platform_keys() -> [String; 2] {
+    let installer = match std::env::consts::OS {
+        "macos" => "app",
+        "windows" => "nsis",
+        _ => "appimage",
+    };
+    [format!("{}-{installer}", target()), target()]
+}
+
+pub fn parse_version(version: &str) -> Result<semver::Version, String> {
+    semver::Version::parse(version.trim().trim_start_matches('v'))
+        .map_err(|e| format!("Invalid version {version:?}: {e}"))
+}
+
+/// Fetch the feed at `url`.
+pub async fn fetch_feed<T: DeserializeOwned>(url: &str) -> Result<T, String> {
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {e}"))?;
+    client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to fetch the release feed: {e}"))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid release feed: {e}"))
+}
+
+/// Size of the download at `url` from a `HEAD` request, or `None` if the
+/// server does not say.
+pub async fn download_size(url: &str) -> Option<u64> {
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .ok()?;
+    let response = client
+        .head(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status());
+    match response {
+        Ok(response) => response
+            .headers()
+            .get(reqwest::header::CONTENT_LENGTH)
+            .and_then(|length| length.to_str().ok())
+            .and_then(|length| length.parse().ok()),
+        Err(e) => {
+            log::warn!("Failed to get the size of {url}: {e}");
+            None
+        }
+    }
+}
+
+/// The release on the configured channel and this platform's download, if
+/// it is newer than the running app.
+async fn available(app: &AppHandle) -> Result<Option<(UpdateInfo, Asset)>, String> {
+    let channel = settings::current(app).updates.channel;
+    let feed: Feed = fetch_feed(feed_url(channel)).await?;
+    let current = app.package_info().version.clone();
+    if parse_version(&feed.version)? <= current {
+        log::info!(
+            "Teletraan {current} is up to date on the {channel:?} channel (latest {})",
+            feed.version
+        );
+        return Ok(None);
+    }
+    let Some(asset) = platform_keys()
+        .iter()
+        .find_map(|key| feed.platforms.get(key))
+        .cloned()
+    else {
+        log::info!(
+            "Teletraan {} is out, but not for {}",
+            feed.version,
+            target()
+        );
+        return Ok(None);
+    };
+    let info = UpdateInfo {
+        version: feed.version,
+        current_version: current.to_string(),
+        channel,
+        notes: feed.notes,
+        pub_date: feed.pub_date,
+        size_bytes: download_size(&asset.url).await,
+    };
+    Ok(Some((info, asset)))
+}
+
+/// Check the release feed in the background at startup, with
+/// `updates.check_on_startup`, and emit `update-available` if there is a
+/// newer version.
+pub fn spawn_check(app: AppHandle) {
+    // Left by the Windows installer, which reads it after the app exits.
+    if let Ok(dir) = crate::app_data_root(&app).map(|root| root.join(UPDATES_DIR)) {
+        if dir.exists() {
+            if let Err(e) = std::fs::remove_dir_all(&dir) {
+                log::warn!("Failed to remove {}: {e}", dir.display());
+            }
+        }
+    }
+    if !settings::current(&app).updates.check_on_startup {
+        return;
+    }
+    if PUBLIC_KEY.is_none() {
+        log::info!("This build has no update key; not checking for updates");
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        match available(&app).await {
+            Ok(Some((info, _))) => {
+                log::info!("Teletraan {} is available", info.version);
+                if let Err(e) = app.emit("update-available", &info) {
+                    log::warn!("Failed to emit update-available: {e}");
+                }
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to check for updates: {e}"),
+        }
+    });
+}
+
+/// Download `url`, the `version` of `component`, into `dir`, which is
+/// emptied first. The file is named after the last segment of the URL,
+/// which the installers rely on. `update-download-progress` reports the
+/// bytes received.
+pub async fn download(
+    app: &AppHandle,
+    component: UpdateComponent,
+    version: &str,
+    url: &str,
+    dir: &Path,
+) -> Result<PathBuf, String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid download {url:?}: {e}"))?;
+    if parsed.scheme() != "https" {
+        return Err(format!("Invalid download {url:?}: expected https"));
+    }
+    let name = parsed
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|name| !name.is_empty() && !name.starts_with('.'))
+        .ok_or_else(|| format!("Invalid download {url:?}: no file name"))?
+        .to_string();
+    if dir.exists() {
+        std::fs::remove_dir_all(dir)
+            .map_err(|e| format!("Failed to clear {}: {e}", dir.display()))?;
+    }
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    let path = dir.join(name);
+
+    let client = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {e}"))?;
+    let mut response = client
+        .get(parsed)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to download {url}: {e}"))?;
+    let mut file = std::fs::File::create(&path)
+        .map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+    let mut progress = DownloadProgress {
+        component,
+        version: version.to_string(),
+        bytes: 0,
+        total_bytes: response.content_length(),
+    };
+    let mut reported = Instant::now();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to download {url}: {e}"))?
+    {
+        file.write_all(&chunk)
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+        progress.bytes += chunk.len() as u64;
+        if reported.elapsed() >= PROGRESS_INTERVAL {
+            reported = Instant::now();
+            let _ = app.emit("update-download-progress", &progress);
+        }
+    }
+    file.sync_all()
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    let _ = app.emit("update-download-progress", &progress);
+    Ok(path)
+}
+
+/// Put the verified download in place of the running app. Returns whether
+/// the app must relaunch itself; the Windows installer does it instead.
+#[cfg(target_os = "macos")]
+fn install(download: &Path) -> Result<bool, String> {
+    let exe =
+        std::env::current_exe().map_err(|e| format!("Failed to locate the running app: {e}"))?;
+    let bundle = exe
+        .ancestors()
+        .nth(3)
+        .filter(|bundle| bundle.extension().is_some_and(|ext| ext == "app"))
+        .ok_or("The app is not running from an app bundle, so it cannot replace itself")?
+        .to_path_buf();
+    let parent = bundle
+        .parent()
+        .ok_or("The app bundle has no parent folder")?;
+    // Next to the bundle, so the swap is a rename on the same volume.
+    let staging = parent.join(format!(".teletraan-update-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&staging);
+    std::fs::create_dir_all(&staging)
+        .map_err(|e| format!("Failed to create {}: {e}", staging.display()))?;
+    let result = (|| {
+        let status = std::process::Command::new("/usr/bin/tar")
+            .arg("-xzf")
+            .arg(download)
+            .arg("-C")
+            .arg(&staging)
+            .status()
+            .map_err(|e| format!("Failed to run tar: {e}"))?;
+        if !status.success() {
+            return Err(format!("Failed to unpack the update ({status})"));
+        }
+        let new_bundle = std::fs::read_dir(&staging)
+            .map_err(|e| format!("Failed to read {}: {e}", staging.display()))?
+            .flatten()
+            .map(|entry| entry.path())
+            .find(|path| path.extension().is_some_and(|ext| ext == "app"))
+            .ok_or("The update holds no app bundle")?;
+        let previous = staging.join("previous.app");
+        std::fs::rename(&bundle, &previous)
+            .map_err(|e| format!("Failed to move {} aside: {e}", bundle.display()))?;
+        if let Err(e) = std::fs::rename(&new_bundle, &bundle) {
+            let _ = std::fs::rename(&previous, &bundle);
+            return Err(format!("Failed to install {}: {e}", bundle.display()));
+        }
+        Ok(true)
+    })();
+    let _ = std::fs::remove_dir_all(&staging);
+    result
+}
+
+#[cfg(windows)]
+fn install(download: &Path) -> Result<bool, String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::UI::Shell::ShellExecuteW;
+    use windows_sys::Win32::UI::WindowsAndMessaging::SW_SHOW;
+
+    fn wide(text: &std::ffi::OsStr) -> Vec<u16> {
+        text.encode_wide().chain(std::iter::once(0)).collect()
+    }
+
+    if !download
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("exe"))
+    {
+        return Err(format!(
+            "Cannot install {}: expected the setup program",
+            download.display()
+        ));
+    }
+    let verb = wide("open".as_ref());
+    let file = wide(download.as_os_str());
+    // Passive, as an update, and start the app again when done.
+    let parameters = wide("/P /R /UPDATE".as_ref());
+    // ShellExecuteW rather than `Command`, since a per-machine install asks
+    // for elevation.
+    // SAFETY: every pointer refers to a live, NUL-terminated local.
+    let result = unsafe {
+        ShellExecuteW(
+            std::ptr::null_mut(),
+            verb.as_ptr(),
+            file.as_ptr(),
+            parameters.as_ptr(),
+            std::ptr::null(),
+            SW_SHOW,
+        )
+    };
+    // Values above 32 mean success.
+    if result as isize <= 32 {
+        return Err(format!(
+            "Failed to start the installer: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(false)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn install(download: &Path) -> Result<bool, String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let appimage = std::env::var_os("APPIMAGE")
+        .map(PathBuf::from)
+        .ok_or_else(|| {
+            format!(
+                "Only the AppImage can update itself; install the new package from {RELEASES_URL}"
+            )
+        })?;
+    // Copied next to the AppImage first, so the swap is a rename.
+    let staged = appimage.with_extension("AppImage.new");
+    std::fs::copy(download, &staged)
+        .map_err(|e| format!("Failed to copy the update to {}: {e}", staged.display()))?;
+    let installed = std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))
+        .and_then(|_| std::fs::rename(&staged, &appimage));
+    if let Err(e) = installed {
+        let _ = std::fs::remove_file(&staged);
+        return Err(format!("Failed to replace {}: {e}", appimage.display()));
+    }
+    Ok(true)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn install(_download: &Path) -> Result<bool, String> {
+    Err(format!(
+        "This platform cannot update itself; download new versions from {RELEASES_URL}"
+    ))
+}
+
+/// Tauri command: download the newest release on the configured channel,
+/// check its signature, stop the backend cleanly, install it and relaunch.
+/// The backend is started again if installing fails.
+#[tauri::command]
+pub async fn install_update(app: AppHandle) -> Result<(), String> {
+    let key = public_key()?;
+    let (info, asset) = available(&app)
+        .await?
+        .ok_or("Teletraan is already up to date")?;
+    let dir = crate::app_data_root(&app)?.join(UPDATES_DIR);
+    log::info!("Downloading Teletraan {} from {}", info.version, asset.url);
+    let download = download(&app, UpdateComponent::App, &info.version, &asset.url, &dir).await?;
+    let checked = {
+        let download = download.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            signature::verify_file(&download, &asset.signature, &key)
+        })
+        .await
+        .map_err(|e| format!("Failed to check the update: {e}"))?
+    };
+    let detail = format!(
+        "{} -> {} ({:?})",
+        info.current_version, info.version, info.channel
+    );
+    if let Err(e) = checked {
+        let _ = std::fs::remove_dir_all(&dir);
+        audit::record(&app, AuditEvent::AppUpdate, false, format!("{detail}: {e}"));
+        return Err(format!("The update failed its signature check: {e}"));
+    }
+
+    if let Err(e) = app.emit("update-installing", &info) {
+        log::warn!("Failed to emit update-installing: {e}");
+    }
+    log::info!("Stopping the backend to install Teletraan {}", info.version);
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || backend::stop_backend(&handle))
+        .await
+        .map_err(|e| format!("Failed to stop backend: {e}"))?;
+    let installed = tauri::async_runtime::spawn_blocking(move || install(&download))
+        .await
+        .map_err(|e| format!("Failed to install the update: {e}"))
+        .and_then(|result| result);
+    let relaunch = match installed {
+        Ok(relaunch) => relaunch,
+        Err(e) => {
+            log::error!("Failed to install Teletraan {}: {e}", info.version);
+            audit::record(&app, AuditEvent::AppUpdate, false, format!("{detail}: {e}"));
+            let _ = std::fs::remove_dir_all(&dir);
+            backend::start_backend(&app).await?;
+            return Err(e);
+        }
+    };
+    audit::record(&app, AuditEvent::AppUpdate, true, detail);
+    log::info!("Installed Teletraan {}; relaunching", info.version);
+    if relaunch {
+        let _ = std::fs::remove_dir_all(&dir);
+        app.restart();
+    }
+    // The installer still reads the download, and starts the new version.
+    app.exit(0);
+    Ok(())
+}
+
+/// Tauri command: check the release feeds of the configured channel for a
+/// newer app and a newer backend, with their notes and download sizes,
+/// without installing anything. A feed that cannot be read is reported in
+/// `app_error` or `backend_error` rather than failing the whole check.
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle) -> Result<UpdateCheck, String> {
+    public_key()?;
+    let (app_update, app_error) = match available(&app).await {
+        Ok(update) => (update.map(|(info, _)| info), None),
+        Err(e) => {
+            log::warn!("Failed to check for updates: {e}");
+            (None, Some(e))
+        }
+    };
+    let (backend_update, backend_error) = match backend_update::available(&app).await {
+        Ok(update) => (update.map(|(info, _)| info), None),
+        Err(e) => {
+            log::warn!("Failed to check for backend updates: {e}");
+            (None, Some(e))
+        }
+    };
+    Ok(UpdateCheck {
+        channel: settings::current(&app).updates.channel,
+        current_version: app.package_info().version.to_string(),
+        backend_version: backend_update::active(&app)?.version,
+        app: app_update,
+        backend: backend_update,
+        app_error,
+        backend_error,
+        checked_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
@@ -0,0 +1,132 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+use crate::backend::{self, BackendProcess};
+use crate::transport;
+
+/// Payload of `get_versions`.
+#[derive(Clone, Debug, Serialize)]
+pub struct Versions {
+    pub app: String,
+    /// Commit the desktop shell was built from.
+    pub build_hash: Option<&'static str>,
+    pub tauri: &'static str,
+    pub webview: Option<String>,
+    /// `<os>-<arch>`, e.g. `macos-aarch64`.
+    pub platform: String,
+    /// The rest come from the backend's `/api/v1/version` and are `None` if it
+    /// did not answer.
+    pub backend: Option<String>,
+    /// SHA-256 of the backend executable this app spawned.
+    pub backend_sha256: Option<String>,
+    pub python: Option<String>,
+    /// Fingerprint of the backend's database schema.
+    pub schema: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct BackendVersion {
+    version: String,
+    python_version: String,
+    schema_version: String,
+}
+
+/// Tauri command: versions of every component, for bug reports and the
+/// About screen.
+#[tauri::command]
+pub async fn get_versions(app: AppHandle) -> Result<Versions, String> {
+    let mut versions = Versions {
+        app: app.package_info().version.to_string(),
+        build_hash: option_env!("TELETRAAN_BUILD_HASH"),
+        tauri: tauri::VERSION,
+        webview: tauri::webview_version()
+            .inspect_err(|e| log::warn!("Failed to read webview version: {e}"))
+            .ok(),
+        platform: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+        backend: None,
+        backend_sha256: None,
+        python: None,
+        schema: None,
+    };
+
+    let binary = {
+        let state = app.state::<BackendProcess>();
+        let backend = state.0.lock().unwrap();
+        backend.binary.clone()
+    };
+    if let Some(binary) = binary {
+        versions.backend_sha256 =
+            tauri::async_runtime::spawn_blocking(move || binary_hash(&binary))
+                .await
+                .map_err(|e| format!("Failed to hash backend binary: {e}"))?;
+    }
+
+    match backend_version(&app).await {
+        Some(Ok(reported)) => {
+            versions.backend = Some(reported.version);
+            versions.python = Some(reported.python_version);
+            versions.schema = Some(reported.schema_version);
+        }
+        Some(Err(e)) => log::warn!("Failed to query backend version: {e}"),
+        None => {}
+    }
+    Ok(versions)
+}
+
+/// The running backend's `/api/v1/version`, or `None` without a backend.
+async fn backend_version(app: &AppHandle) -> Option<Result<BackendVersion, String>> {
+    let endpoint = backend::endpoint(app)?;
+    let reported = async {
+        let resp = transport::Client::new(Duration::from_secs(3))?
+            .get(&endpoint, "/api/v1/version")
+            .await?;
+        if !resp.status().is_success() {
+            return Err(format!("HTTP {}", resp.status()));
+        }
+        transport::json::<BackendVersion>(&resp)
+    }
+    .await;
+    Some(reported)
+}
+
+/// Fingerprint of the running backend's database schema, if it answers.
+pub async fn schema_version(app: &AppHandle) -> Option<String> {
+    match backend_version(app).await? {
+        Ok(reported) => Some(reported.schema_version),
+        Err(e) => {
+            log::warn!("Failed to query backend schema version: {e}");
+            None
+        }
+    }
+}
+
+/// Hex SHA-256 of the backend executable, computed once per path since the
+/// bundled binary is large and does not change while the app runs.
+fn binary_hash(path: &Path) -> Option<String> {
+    static CACHE: Mutex<Option<(PathBuf, String)>> = Mutex::new(None);
+    if let Some((cached_path, hash)) = CACHE.lock().unwrap().as_ref() {
+        if cached_path == path {
+            return Some(hash.clone());
+        }
+    }
+
+    // The binary may be `teletraan-backend` or `teletraan-backend.exe`.
+    let path = [path.to_path_buf(), path.with_extension("exe")]
+        .into_iter()
+        .find(|p| p.is_file())?;
+    let mut file = std::fs::File::open(&path)
+        .inspect_err(|e| log::warn!("Failed to open {}: {e}", path.display()))
+        .ok()?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)
+        .inspect_err(|e| log::warn!("Failed to read {}: {e}", path.display()))
+        .ok()?;
+    let hash = hex::encode(hasher.finalize());
+    *CACHE.lock().unwrap() = Some((path, hash.clone()));
+    Some(hash)
+}
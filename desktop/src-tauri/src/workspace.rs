@@ -0,0 +1,164 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::store;
+
+/// A horizontal price level the user drew on the chart.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DrawnLevel {
+    pub price: f64,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+/// A news article the user attached to the symbol.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LinkedNews {
+    pub url: String,
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+/// Everything the research view needs to restore a ticker exactly as the
+/// user left it.
+///
+/// `chart_settings` is opaque to the shell: the frontend owns its shape
+/// (timeframe, indicators, overlays) and the shell only persists it.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Workspace {
+    pub symbol: String,
+    pub chart_settings: serde_json::Value,
+    pub levels: Vec<DrawnLevel>,
+    pub notes: String,
+    pub pinned_insights: Vec<i64>,
+    pub linked_news: Vec<LinkedNews>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Normalize a ticker into the form used as the workspace key.
+///
+/// Symbols double as file names, so anything outside the characters that
+/// appear in real tickers (`BRK.B`, `^GSPC`, `EURUSD=X`, `BTC-USD`) is
+/// rejected rather than escaped.
+fn normalize_symbol(symbol: &str) -> Result<String, String> {
+    let symbol = symbol.trim().to_ascii_uppercase();
+    let valid = !symbol.is_empty()
+        && symbol.len() <= 32
+        && symbol
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '^' | '=' | '-'));
+    if !valid || symbol.starts_with('.') {
+        return Err(format!("Invalid symbol: {symbol:?}"));
+    }
+    Ok(symbol)
+}
+
+/// Names Windows reserves for devices, with or without an extension.
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// File name of the workspace of a normalized `symbol`. Tickers such as
+/// `CON` or `PRN.B` are names Windows reserves, so those get a `_` prefix,
+/// which no symbol contains; the same on every platform, so a data dir can
+/// be moved between them.
+fn file_name(symbol: &str) -> String {
+    let stem = symbol.split('.').next().unwrap_or(symbol);
+    if RESERVED_NAMES.contains(&stem) {
+        format!("_{symbol}.json")
+    } else {
+        format!("{symbol}.json")
+    }
+}
+
+/// Directory holding one `<SYMBOL>.json` file per workspace.
+fn workspaces_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::resolve_data_dir(app)?.join("workspaces"))
+}
+
+/// Tauri command: load the saved workspace for `symbol`, or an empty one if
+/// the user has never opened it.
+#[tauri::command]
+pub async fn get_workspace(app: AppHandle, symbol: String) -> Result<Workspace, String> {
+    let symbol = normalize_symbol(&symbol)?;
+    let path = workspaces_dir(&app)?.join(file_name(&symbol));
+    let workspace = store::read_json::<Workspace>(&path)?.unwrap_or_default();
+    Ok(Workspace {
+        symbol,
+        ..workspace
+    })
+}
+
+/// Tauri command: persist a workspace, stamping `updated_at`.
+#[tauri::command]
+pub async fn save_workspace(app: AppHandle, workspace: Workspace) -> Result<Workspace, String> {
+    let symbol = normalize_symbol(&workspace.symbol)?;
+    let workspace = Workspace {
+        symbol,
+        updated_at: Some(Utc::now()),
+        ..workspace
+    };
+    let path = workspaces_dir(&app)?.join(file_name(&workspace.symbol));
+    store::write_json(&path, &workspace)?;
+    Ok(workspace)
+}
+
+/// Tauri command: forget the saved workspace for `symbol`.
+#[tauri::command]
+pub async fn delete_workspace(app: AppHandle, symbol: String) -> Result<(), String> {
+    let symbol = normalize_symbol(&symbol)?;
+    let path = workspaces_dir(&app)?.join(file_name(&symbol));
+    match std::fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to delete {}: {e}", path.display())),
+    }
+}
+
+/// Tauri command: list symbols that have a saved workspace, sorted.
+#[tauri::command]
+pub async fn list_workspaces(app: AppHandle) -> Result<Vec<String>, String> {
+    let dir = workspaces_dir(&app)?;
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {e}", dir.display())),
+    };
+
+    let mut symbols: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension()? != "json" {
+                return None;
+            }
+            let stem = path.file_stem()?.to_string_lossy();
+            Some(stem.strip_prefix('_').unwrap_or(&stem).to_string())
+        })
+        .collect();
+    symbols.sort();
+    Ok(symbols)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefixes_names_windows_reserves() {
+        assert_eq!(file_name("AAPL"), "AAPL.json");
+        assert_eq!(file_name("BRK.B"), "BRK.B.json");
+        assert_eq!(file_name("CON"), "_CON.json");
+        assert_eq!(file_name("PRN.B"), "_PRN.B.json");
+        assert_eq!(file_name("COM1"), "_COM1.json");
+        assert_eq!(file_name("CONX"), "CONX.json");
+        assert_eq!(file_name("NUL-USD"), "NUL-USD.json");
+    }
+}
@@ -0,0 +1,654 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use base64::Engine;
+use serde::Serialize;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use crate::backend;
+use crate::sse::RelayState;
+use crate::transport::{self, Endpoint};
+
+/// First reconnect delay, doubled after each failed attempt.
+const INITIAL_RETRY: Duration = Duration::from_secs(1);
+
+/// Longest wait between reconnects.
+const MAX_RETRY: Duration = Duration::from_secs(30);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest message accepted from the backend.
+const MAX_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Largest handshake response accepted.
+const MAX_HANDSHAKE_BYTES: usize = 16 * 1024;
+
+/// Appended to the client key to compute `Sec-WebSocket-Accept` (RFC 6455).
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const OP_CONTINUATION: u8 = 0x0;
+const OP_TEXT: u8 = 0x1;
+const OP_BINARY: u8 = 0x2;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xA;
+
+/// Managed state: open WebSocket bridges by connection id.
+#[derive(Default)]
+pub struct WsBridges(Mutex<Bridges>);
+
+#[derive(Default)]
+struct Bridges {
+    connections: HashMap<u64, Bridge>,
+    next_id: u64,
+}
+
+struct Bridge {
+    outgoing: UnboundedSender<Outgoing>,
+    shared: Arc<Shared>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Default)]
+struct Shared {
+    connected: AtomicBool,
+    stopped: AtomicBool,
+}
+
+/// Work for the task that owns the write half of the socket.
+enum Outgoing {
+    Text(String),
+    /// Reply to a ping from the backend.
+    Pong(Vec<u8>),
+    /// The reader of connection `attempt` stopped.
+    ReaderEnded {
+        attempt: u64,
+        error: String,
+    },
+    /// `disconnect_ws` was called.
+    Close,
+}
+
+/// Payload of the `ws-message` event.
+#[derive(Clone, Debug, Serialize)]
+struct WsMessage {
+    connection: u64,
+    /// The `type` field of a JSON object message, e.g. `text` or `done`.
+    #[serde(rename = "type")]
+    message_type: Option<String>,
+    /// Parsed JSON when the message is JSON; otherwise the text, or base64
+    /// for a binary message.
+    data: serde_json::Value,
+    binary: bool,
+}
+
+/// Payload of the `ws-status` event.
+#[derive(Clone, Debug, Serialize)]
+struct WsStatus {
+    connection: u64,
+    state: RelayState,
+    retry_ms: Option<u64>,
+    error: Option<String>,
+}
+
+/// Tauri command: open a WebSocket to the backend at `path` (e.g.
+/// `/api/v1/chat`) and relay its messages as `ws-message` events. Returns the
+/// connection id for `send_ws_message` and `disconnect_ws`.
+///
+/// The host reconnects whenever the socket drops, backing off from 1 s to
+/// 30 s, and reports each change as `ws-status`. It gives up when the
+/// backend rejects the handshake with a client error. Going through the host
+/// also makes WebSockets work when the backend is on a Unix socket.
+#[tauri::command]
+pub async fn connect_ws(
+    app: AppHandle,
+    bridges: State<'_, WsBridges>,
+    path: String,
+) -> Result<u64, String> {
+    if !path.starts_with('/') || path.starts_with("//") {
+        return Err(format!("Expected an absolute API path, got {path:?}"));
+    }
+    let (outgoing, incoming) = mpsc::unbounded_channel();
+    let shared = Arc::new(Shared::default());
+    let mut bridges = bridges.0.lock().unwrap();
+    bridges.next_id += 1;
+    let id = bridges.next_id;
+    let task = tauri::async_runtime::spawn(supervise(
+        app,
+        id,
+        path,
+        outgoing.clone(),
+        incoming,
+        shared.clone(),
+    ));
+    bridges.connections.insert(
+        id,
+        Bridge {
+            outgoing,
+            shared,
+            task,
+        },
+    );
+    Ok(id)
+}
+
+/// Tauri command: send `message` over connection `id`, as-is when it is a
+/// string and serialized otherwise. Fails while the connection is down rather
+/// than queueing, so a chat message is never delivered late.
+#[tauri::command]
+pub async fn send_ws_message(
+    bridges: State<'_, WsBridges>,
+    id: u64,
+    message: serde_json::Value,
+) -> Result<(), String> {
+    let bridges = bridges.0.lock().unwrap();
+    let bridge = bridges
+        .connections
+        .get(&id)
+        .ok_or_else(|| format!("No WebSocket connection {id}"))?;
+    if !bridge.shared.connected.load(Ordering::SeqCst) {
+        return Err(format!("WebSocket connection {id} is reconnecting"));
+    }
+    let text = match message {
+        serde_json::Value::String(text) => text,
+        other => other.to_string(),
+    };
+    bridge
+        .outgoing
+        .send(Outgoing::Text(text))
+        .map_err(|_| format!("WebSocket connection {id} is closed"))
+}
+
+/// Tauri command: close connection `id`.
+#[tauri::command]
+pub async fn disconnect_ws(bridges: State<'_, WsBridges>, id: u64) -> Result<(), String> {
+    let bridge = bridges
+        .0
+        .lock()
+        .unwrap()
+        .connections
+        .remove(&id)
+        .ok_or_else(|| format!("No WebSocket connection {id}"))?;
+    bridge.shared.stopped.store(true, Ordering::SeqCst);
+    if bridge.outgoing.send(Outgoing::Close).is_err() {
+        bridge.task.abort();
+    }
+    Ok(())
+}
+
+/// Why a connection ended.
+enum ConnectionError {
+    /// Worth reconnecting, e.g. the backend restarted.
+    Retry(String),
+    /// Reconnecting would fail the same way.
+    Fatal(String),
+}
+
+/// Keep connection `id` up until it is closed or rejected.
+async fn supervise(
+    app: AppHandle,
+    id: u64,
+    path: String,
+    outgoing: UnboundedSender<Outgoing>,
+    mut incoming: UnboundedReceiver<Outgoing>,
+    shared: Arc<Shared>,
+) {
+    let mut delay = INITIAL_RETRY;
+    let mut attempt = 0u64;
+    while !shared.stopped.load(Ordering::SeqCst) {
+        attempt += 1;
+        let result = run_connection(&app, id, &path, attempt, &outgoing, &mut incoming, &shared);
+        let result = result.await;
+        if shared.connected.swap(false, Ordering::SeqCst) {
+            delay = INITIAL_RETRY;
+        }
+        let error = match result {
+            Ok(()) => break,
+            Err(ConnectionError::Fatal(e)) => {
+                log::warn!("WebSocket bridge for {path} stopped: {e}");
+                emit_status(&app, id, RelayState::Closed, None, Some(e));
+                if let Some(bridges) = app.try_state::<WsBridges>() {
+                    bridges.0.lock().unwrap().connections.remove(&id);
+                }
+                return;
+            }
+            Err(ConnectionError::Retry(e)) => e,
+        };
+        if shared.stopped.load(Ordering::SeqCst) {
+            break;
+        }
+        // Anything sent in the meantime targeted the dead connection.
+        while let Ok(message) = incoming.try_recv() {
+            if matches!(message, Outgoing::Close) {
+                shared.stopped.store(true, Ordering::SeqCst);
+            }
+        }
+        log::debug!("WebSocket bridge for {path}: {error}; reconnecting in {delay:?}");
+        emit_status(
+            &app,
+            id,
+            RelayState::Reconnecting,
+            Some(delay.as_millis() as u64),
+            Some(error),
+        );
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RETRY);
+    }
+    emit_status(&app, id, RelayState::Closed, None, None);
+}
+
+/// One connection: handshake, then write until the reader or the caller ends it.
+///
+/// Returns `Ok` only for a deliberate close.
+async fn run_connection(
+    app: &AppHandle,
+    id: u64,
+    path: &str,
+    attempt: u64,
+    outgoing: &UnboundedSender<Outgoing>,
+    incoming: &mut UnboundedReceiver<Outgoing>,
+    shared: &Shared,
+) -> Result<(), ConnectionError> {
+    let endpoint = backend::endpoint(app)
+        .ok_or_else(|| ConnectionError::Retry("Backend has not been started".to_string()))?;
+    let (reader, mut writer) = tokio::time::timeout(CONNECT_TIMEOUT, connect(&endpoint, path))
+        .await
+        .map_err(|_| ConnectionError::Retry(format!("Timed out after {CONNECT_TIMEOUT:?}")))??;
+    shared.connected.store(true, Ordering::SeqCst);
+    emit_status(app, id, RelayState::Connected, None, None);
+
+    let reader = AbortOnDrop(tauri::async_runtime::spawn(read_messages(
+        app.clone(),
+        id,
+        attempt,
+        reader,
+        outgoing.clone(),
+    )));
+    let result = loop {
+        let Some(message) = incoming.recv().await else {
+            break Ok(());
+        };
+        let written = match message {
+            Outgoing::Text(text) => {
+                writer
+                    .write_all(&encode_frame(OP_TEXT, text.as_bytes()))
+                    .await
+            }
+            Outgoing::Pong(payload) => writer.write_all(&encode_frame(OP_PONG, &payload)).await,
+            Outgoing::ReaderEnded {
+                attempt: ended,
+                error,
+            } if ended == attempt => {
+                break Err(ConnectionError::Retry(error));
+            }
+            // From the reader of an earlier connection.
+            Outgoing::ReaderEnded { .. } => Ok(()),
+            Outgoing::Close => {
+                // Normal closure (1000).
+                let _ = writer
+                    .write_all(&encode_frame(OP_CLOSE, &1000u16.to_be_bytes()))
+                    .await;
+                break Ok(());
+            }
+        };
+        if let Err(e) = written {
+            break Err(ConnectionError::Retry(format!(
+                "Failed to write to WebSocket: {e}"
+            )));
+        }
+    };
+    drop(reader);
+    result
+}
+
+/// Aborts the task when dropped, so a reader never outlives its connection.
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+type Reader = BufReader<Box<dyn AsyncRead + Send + Unpin>>;
+type Writer = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// Start TLS on `stream` to a backend serving HTTPS, accepting only the
+/// certificate the shell gave it (see `transport::set_trusted_cert`).
+async fn connect_tls(
+    authority: &str,
+    stream: tokio::net::TcpStream,
+) -> Result<tokio_native_tls::TlsStream<tokio::net::TcpStream>, ConnectionError> {
+    let fatal = |e: native_tls::Error| ConnectionError::Fatal(format!("Invalid TLS setup: {e}"));
+    let mut builder = native_tls::TlsConnector::builder();
+    if let Some(cert) = transport::trusted_cert() {
+        builder.disable_built_in_roots(true).add_root_certificate(
+            native_tls::Certificate::from_pem(cert.as_bytes()).map_err(fatal)?,
+        );
+    }
+    let connector = tokio_native_tls::TlsConnector::from(builder.build().map_err(fatal)?);
+    // The host without the port, and an IPv6 address without its brackets.
+    let host = authority
+        .rsplit_once(':')
+        .map_or(authority, |(host, _)| host)
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    connector
+        .connect(host, stream)
+        .await
+        .map_err(|e| ConnectionError::Retry(format!("TLS handshake failed: {e}")))
+}
+
+/// Open the socket and complete the WebSocket handshake.
+async fn connect(endpoint: &Endpoint, path: &str) -> Result<(Reader, Writer), ConnectionError> {
+    let retry = |e: std::io::Error| ConnectionError::Retry(format!("Failed to connect: {e}"));
+    let (host, reader, writer): (String, Box<dyn AsyncRead + Send + Unpin>, Writer) = match endpoint
+    {
+        Endpoint::Tcp(base_url) => {
+            let (scheme, authority) = base_url
+                .split_once("://")
+                .unwrap_or(("http", base_url.as_str()));
+            let authority = authority.trim_end_matches('/').to_string();
+            let stream = tokio::net::TcpStream::connect(&authority)
+                .await
+                .map_err(retry)?;
+            if scheme == "https" {
+                let stream = connect_tls(&authority, stream).await?;
+                let (reader, writer) = tokio::io::split(stream);
+                (authority, Box::new(reader), Box::new(writer))
+            } else {
+                let (reader, writer) = stream.into_split();
+                (authority, Box::new(reader), Box::new(writer))
+            }
+        }
+        #[cfg(unix)]
+        Endpoint::Unix(socket) => {
+            let stream = tokio::net::UnixStream::connect(socket)
+                .await
+                .map_err(retry)?;
+            let (reader, writer) = stream.into_split();
+            ("localhost".to_string(), Box::new(reader), Box::new(writer))
+        }
+        #[cfg(not(unix))]
+        Endpoint::Unix(_) => {
+            return Err(ConnectionError::Fatal(
+                "Unix domain sockets are not supported on this platform".to_string(),
+            ))
+        }
+    };
+    let mut reader = BufReader::new(reader);
+    let mut writer = writer;
+
+    let key = base64::engine::general_purpose::STANDARD.encode(random_bytes::<16>());
+    let auth = transport::auth_token()
+        .map(|token| format!("{}: {token}\r\n", transport::AUTH_HEADER))
+        .unwrap_or_default();
+    let request = format!(
+        "GET {path} HTTP/1.1\r\nHost: {host}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Key: {key}\r\nSec-WebSocket-Version: 13\r\n{auth}\r\n"
+    );
+    writer
+        .write_all(request.as_bytes())
+        .await
+        .map_err(|e| ConnectionError::Retry(format!("Failed to send handshake: {e}")))?;
+
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        let read = reader
+            .read_until(b'\n', &mut head)
+            .await
+            .map_err(|e| ConnectionError::Retry(format!("Failed to read handshake: {e}")))?;
+        if read == 0 {
+            return Err(ConnectionError::Retry(
+                "Connection closed during handshake".to_string(),
+            ));
+        }
+        if head.len() > MAX_HANDSHAKE_BYTES {
+            return Err(ConnectionError::Fatal(
+                "Handshake response too large".to_string(),
+            ));
+        }
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut lines = head.lines();
+    let status: u16 = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| ConnectionError::Fatal("Malformed handshake response".to_string()))?;
+    if status != 101 {
+        let error = format!("Handshake refused with HTTP {status}");
+        return Err(
+            if (400..500).contains(&status) && status != 408 && status != 429 {
+                ConnectionError::Fatal(error)
+            } else {
+                ConnectionError::Retry(error)
+            },
+        );
+    }
+    let accept = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("sec-websocket-accept"))
+        .map(|(_, value)| value.trim().to_string());
+    let expected = base64::engine::general_purpose::STANDARD
+        .encode(sha1(format!("{key}{ACCEPT_GUID}").as_bytes()));
+    if accept.as_deref() != Some(expected.as_str()) {
+        return Err(ConnectionError::Fatal(
+            "Invalid Sec-WebSocket-Accept".to_string(),
+        ));
+    }
+    Ok((reader, writer))
+}
+
+/// Relay messages from the backend until the connection ends, then tell the
+/// writer why.
+async fn read_messages(
+    app: AppHandle,
+    id: u64,
+    attempt: u64,
+    mut reader: Reader,
+    outgoing: UnboundedSender<Outgoing>,
+) {
+    let mut message: Option<(u8, Vec<u8>)> = None;
+    let error = loop {
+        let (fin, opcode, payload) = match read_frame(&mut reader).await {
+            Ok(frame) => frame,
+            Err(e) => break e,
+        };
+        match opcode {
+            OP_TEXT | OP_BINARY => message = Some((opcode, payload)),
+            OP_CONTINUATION => match &mut message {
+                Some((_, buffer)) if buffer.len() + payload.len() <= MAX_MESSAGE_BYTES => {
+                    buffer.extend_from_slice(&payload)
+                }
+                Some(_) => break "Message too large".to_string(),
+                None => break "Unexpected continuation frame".to_string(),
+            },
+            OP_PING => {
+                let _ = outgoing.send(Outgoing::Pong(payload));
+                continue;
+            }
+            OP_PONG => continue,
+            OP_CLOSE => {
+                let code = payload
+                    .get(..2)
+                    .map(|code| u16::from_be_bytes([code[0], code[1]]));
+                break match code {
+                    Some(code) => format!("Closed by the backend (code {code})"),
+                    None => "Closed by the backend".to_string(),
+                };
+            }
+            other => break format!("Unknown opcode {other:#x}"),
+        }
+        if !fin {
+            continue;
+        }
+        if let Some((opcode, payload)) = message.take() {
+            emit_message(&app, id, opcode, payload);
+        }
+    };
+    let _ = outgoing.send(Outgoing::ReaderEnded { attempt, error });
+}
+
+/// Read one frame: FIN flag, opcode and unmasked payload.
+async fn read_frame(reader: &mut Reader) -> Result<(bool, u8, Vec<u8>), String> {
+    let eof = |e: std::io::Error| format!("Connection lost: {e}");
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head).await.map_err(eof)?;
+    let fin = head[0] & 0x80 != 0;
+    let opcode = head[0] & 0x0f;
+    let masked = head[1] & 0x80 != 0;
+    let len = match head[1] & 0x7f {
+        126 => u64::from(reader.read_u16().await.map_err(eof)?),
+        127 => reader.read_u64().await.map_err(eof)?,
+        len => u64::from(len),
+    };
+    if len > MAX_MESSAGE_BYTES as u64 {
+        return Err("Message too large".to_string());
+    }
+    let mut mask = [0u8; 4];
+    if masked {
+        reader.read_exact(&mut mask).await.map_err(eof)?;
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload).await.map_err(eof)?;
+    if masked {
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+    }
+    Ok((fin, opcode, payload))
+}
+
+/// A single masked frame, as clients must send them.
+fn encode_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 14);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        len if len < 126 => frame.push(0x80 | len as u8),
+        len if len <= usize::from(u16::MAX) => {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(0x80 | 127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    let mask = random_bytes::<4>();
+    frame.extend_from_slice(&mask);
+    frame.extend(
+        payload
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| byte ^ mask[i % 4]),
+    );
+    frame
+}
+
+/// Random bytes for the handshake key and frame masks; a v4 UUID is 122 bits
+/// from the OS generator.
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    for chunk in bytes.chunks_mut(16) {
+        chunk.copy_from_slice(&uuid::Uuid::new_v4().as_bytes()[..chunk.len()]);
+    }
+    bytes
+}
+
+/// SHA-1, which the handshake uses to prove the server speaks WebSocket. Not
+/// used for anything security-sensitive.
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (chunk, word) in digest.chunks_mut(4).zip(h) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+fn emit_message(app: &AppHandle, id: u64, opcode: u8, payload: Vec<u8>) {
+    let binary = opcode == OP_BINARY;
+    let data = if binary {
+        serde_json::Value::String(base64::engine::general_purpose::STANDARD.encode(&payload))
+    } else {
+        let text = String::from_utf8_lossy(&payload);
+        serde_json::from_str(&text).unwrap_or_else(|_| serde_json::Value::String(text.into_owned()))
+    };
+    let message_type = data
+        .get("type")
+        .and_then(|value| value.as_str())
+        .map(str::to_string);
+    let message = WsMessage {
+        connection: id,
+        message_type,
+        data,
+        binary,
+    };
+    if let Err(e) = app.emit("ws-message", message) {
+        log::warn!("Failed to emit ws-message: {e}");
+    }
+}
+
+fn emit_status(
+    app: &AppHandle,
+    id: u64,
+    state: RelayState,
+    retry_ms: Option<u64>,
+    error: Option<String>,
+) {
+    let status = WsStatus {
+        connection: id,
+        state,
+        retry_ms,
+        error,
+    };
+    if let Err(e) = app.emit("ws-status", status) {
+        log::warn!("Failed to emit ws-status: {e}");
+    }
+}
@@ -11,6 +11,7 @@ import { Input } from '@/components/ui/input';
 import { DeepInsightCard } from '@/components/insights/deep-insight-card';
 import { StatisticalSignalsCard } from '@/components/insights/statistical-signals-card';
 import { useDeepInsights, DeepInsightParams } from '@/lib/hooks/use-deep-insights';
+import { useEnqueueInsights } from '@/lib/hooks/use-review-queue';
 import type { DeepInsight, DeepInsightType, InsightAction } from '@/types';
 import { Sparkles, ChevronLeft, ChevronRight, Search, Filter, Activity, PanelRightOpen, Calendar, LayoutGrid, List, TrendingUp, TrendingDown, Minus, Target } from 'lucide-react';
 import { ConnectionError } from '@/components/ui/empty-state';
@@ -260,6 +261,7 @@ export default function InsightsPage() {
   };
 
   const { data, isLoading, error } = useDeepInsights(params);
+  useEnqueueInsights(data?.items);
 
   const totalPages = data ? Math.ceil(data.total / ITEMS_PER_PAGE) : 1;
 
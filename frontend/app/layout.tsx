@@ -6,6 +6,12 @@ import { Providers } from '@/lib/providers';
 import { Header } from '@/components/layout/header';
 import { Sidebar } from '@/components/layout/sidebar';
 import { BackendReadinessGate } from '@/components/backend-readiness-gate';
+import { BackendOverridesBanner } from '@/components/backend-overrides-banner';
+import { RestartRequiredBanner } from '@/components/restart-required-banner';
+import { ExternalBackendBanner } from '@/components/external-backend-banner';
+import { LegacyDataBanner } from '@/components/legacy-data-banner';
+import { UpdateAvailableBanner } from '@/components/update-available-banner';
+import { InsightNotifier } from '@/components/insight-notifier';
 
 const geistSans = Geist({
   variable: '--font-geist-sans',
@@ -42,6 +48,12 @@ export default function RootLayout({
             <BackendReadinessGate>
               <div className="relative flex min-h-screen flex-col">
                 <Header />
+                <BackendOverridesBanner />
+                <RestartRequiredBanner />
+                <ExternalBackendBanner />
+                <LegacyDataBanner />
+                <UpdateAvailableBanner />
+                <InsightNotifier />
                 <div className="flex flex-1">
                   <Sidebar />
                   <main className="flex-1 p-6">{children}</main>
@@ -47,6 +47,7 @@ import { AnalysisSummaryBanner } from '@/components/insights/analysis-summary-ba
 import { GradientProgressBar } from '@/components/insights/gradient-progress-bar';
 import { useRecentDeepInsights } from '@/lib/hooks/use-deep-insights';
 import { useAnalysisTask } from '@/lib/hooks/use-analysis-task';
+import { useEnqueueInsights } from '@/lib/hooks/use-review-queue';
 import { knowledgeApi, outcomesApi } from '@/lib/api';
 import type { DeepInsight, InsightAction, AutonomousAnalysisResponse } from '@/types';
 import type { TrackRecordStats, MonthlyTrendResponse, OutcomeSummary } from '@/lib/types/track-record';
@@ -1177,6 +1178,7 @@ function EmptyChartState({ message, height = 300 }: { message: string; height?:
 export default function DashboardPage() {
   // Fetch insights for stats & confidence distribution
   const { data: allInsightsData, isLoading: isLoadingInsights } = useRecentDeepInsights(100);
+  useEnqueueInsights(allInsightsData?.items);
 
   // Fetch track record stats
   const { data: trackRecord, isLoading: isLoadingTrackRecord } = useQuery<TrackRecordStats>({
@@ -0,0 +1,17 @@
+'use client';
+
+import { ReviewQueue } from '@/components/insights/review-queue';
+
+export default function ReviewPage() {
+  return (
+    <div className="container py-6 max-w-4xl mx-auto space-y-6">
+      <div>
+        <h1 className="text-2xl font-bold">Review</h1>
+        <p className="text-muted-foreground">
+          Approve or reject new insights before they are notified and tracked.
+        </p>
+      </div>
+      <ReviewQueue />
+    </div>
+  );
+}
@@ -52,6 +52,7 @@ import {
   ChevronRight,
 } from 'lucide-react';
 import { Button } from '@/components/ui/button';
+import { CopyTableButton } from '@/components/export';
 
 // Sector rotation phase indicator component
 function RotationPhaseIndicator({
@@ -285,11 +286,25 @@ function SectorRankingsTable({
     return <Minus className="h-4 w-4 text-muted-foreground" />;
   };
 
+  const copyColumns = ['#', 'Sector', 'Symbol', 'Daily', 'Weekly', 'Monthly', 'Volume'];
+  const copyRows = sortedSectors.map((sector, index) => [
+    index + 1,
+    sector.name,
+    sector.symbol,
+    formatPerformance(sector.performance),
+    formatPerformance(sector.weeklyPerformance),
+    formatPerformance(sector.monthlyPerformance),
+    formatVolume(sector.volume),
+  ]);
+
   return (
     <Card>
-      <CardHeader>
-        <CardTitle className="text-lg">Sector Rankings</CardTitle>
-        <CardDescription>Ranked by daily performance</CardDescription>
+      <CardHeader className="flex flex-row items-start justify-between space-y-0">
+        <div className="space-y-1.5">
+          <CardTitle className="text-lg">Sector Rankings</CardTitle>
+          <CardDescription>Ranked by daily performance</CardDescription>
+        </div>
+        <CopyTableButton columns={copyColumns} rows={copyRows} />
       </CardHeader>
       <CardContent>
         <Table>
@@ -0,0 +1,56 @@
+'use client';
+
+import { useEffect, useState } from 'react';
+import { AlertTriangle } from 'lucide-react';
+
+interface ActiveOverrides {
+  active: boolean;
+  args: string[];
+  env: string[];
+  rejected: string[];
+}
+
+// eslint-disable-next-line @typescript-eslint/no-explicit-any
+function tauriInvoke(): ((cmd: string) => Promise<any>) | null {
+  if (typeof window === 'undefined') return null;
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  const w = window as any;
+  return w.__TAURI_INTERNALS__?.invoke ?? w.__TAURI__?.core?.invoke ?? null;
+}
+
+/**
+ * Warns when the desktop shell starts the backend with extra arguments or
+ * environment variables from the `[backend]` section of `settings.toml`, since
+ * those can change behaviour in ways the settings page does not show. Renders
+ * nothing outside Tauri.
+ */
+export function BackendOverridesBanner() {
+  const [overrides, setOverrides] = useState<ActiveOverrides | null>(null);
+
+  useEffect(() => {
+    const invoke = tauriInvoke();
+    if (!invoke) return;
+    invoke('get_backend_overrides')
+      .then((result: ActiveOverrides) => setOverrides(result))
+      .catch(() => {});
+  }, []);
+
+  if (!overrides || (!overrides.active && overrides.rejected.length === 0)) return null;
+
+  const applied = [...overrides.args, ...overrides.env];
+  return (
+    <div className="flex items-start gap-2 px-4 py-3 border-b bg-yellow-500/10 border-yellow-500/30 text-sm">
+      <AlertTriangle className="h-4 w-4 text-yellow-500 mt-0.5 shrink-0" />
+      <span className="text-muted-foreground">
+        {applied.length > 0 && (
+          <>
+            The backend is running with non-default settings from{' '}
+            <code className="text-xs bg-muted px-1 py-0.5 rounded">settings.toml</code>:{' '}
+            <code className="text-xs bg-muted px-1 py-0.5 rounded">{applied.join(' ')}</code>.{' '}
+          </>
+        )}
+        {overrides.rejected.length > 0 && <>Ignored: {overrides.rejected.join('; ')}.</>}
+      </span>
+    </div>
+  );
+}
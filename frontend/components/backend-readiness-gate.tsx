@@ -1,7 +1,8 @@
 'use client';
 
 import { useState, useEffect, useCallback, useRef, type ReactNode } from 'react';
-import { Loader2, AlertCircle, RefreshCw } from 'lucide-react';
+import { Loader2, AlertCircle, RefreshCw, DatabaseBackup } from 'lucide-react';
+import { backendAuthHeaders, resolveBackendUrl, tauriInvoke, withBackendToken } from '@/lib/backend-url';
 
 /**
  * Detect whether we are running inside a Tauri desktop shell.
@@ -25,7 +26,13 @@ interface BackendReadinessGateProps {
   pollInterval?: number;
 }
 
-type GateState = 'checking' | 'ready' | 'error';
+type GateState = 'checking' | 'ready' | 'error' | 'corrupt';
+
+/** What the desktop shell reports from `get_database_corruption`. */
+interface DatabaseCorruption {
+  message: string;
+  snapshot: { id: string; created_at: string } | null;
+}
 
 /**
  * When running inside Tauri, this component polls the backend health endpoint
@@ -49,13 +56,16 @@ export function BackendReadinessGate({
   const [state, setState] = useState<GateState>('ready');
   const [attempt, setAttempt] = useState(0);
   const [statusText, setStatusText] = useState('Starting backend...');
+  const [corruption, setCorruption] = useState<DatabaseCorruption | null>(null);
+  const [restoring, setRestoring] = useState(false);
   const mountedRef = useRef(true);
   const initialCheckDone = useRef(false);
 
   const checkHealth = useCallback(async (): Promise<boolean> => {
     try {
       // Try normal fetch first
-      const resp = await fetch('http://127.0.0.1:8000/api/v1/health', {
+      const resp = await fetch(`${await resolveBackendUrl()}/api/v1/health`, {
+        headers: backendAuthHeaders(),
         signal: AbortSignal.timeout(3000),
       });
       if (resp.ok) {
@@ -71,7 +81,7 @@ export function BackendReadinessGate({
     } catch {
       // Network error or CORS block — try no-cors as fallback
       try {
-        const resp = await fetch('http://127.0.0.1:8000/api/v1/health', {
+        const resp = await fetch(withBackendToken(`${await resolveBackendUrl()}/api/v1/health`), {
           mode: 'no-cors',
           signal: AbortSignal.timeout(3000),
         });
@@ -140,6 +150,16 @@ export function BackendReadinessGate({
         return;
       }
 
+      // The shell refuses to start the backend on a damaged database and
+      // offers a backup instead; no point waiting for it then.
+      const found: DatabaseCorruption | null = await tauriInvoke()?.('get_database_corruption').catch(() => null);
+      if (!mountedRef.current) return;
+      if (found) {
+        setCorruption(found);
+        setState('corrupt');
+        return;
+      }
+
       if (currentAttempt >= maxAttempts) {
         setState('error');
         return;
@@ -161,6 +181,38 @@ export function BackendReadinessGate({
     return <>{children}</>;
   }
 
+  if (state === 'corrupt' && corruption) {
+    const handleRestore = async () => {
+      setRestoring(true);
+      try {
+        await tauriInvoke()?.('restore_healthy_snapshot');
+        setCorruption(null);
+        startChecking();
+      } catch (error) {
+        setCorruption({ ...corruption, message: `Restore failed: ${error instanceof Error ? error.message : String(error)}` });
+      } finally {
+        setRestoring(false);
+      }
+    };
+    return (
+      <SplashScreen>
+        <DatabaseBackup className="h-8 w-8 text-destructive mb-4" />
+        <h2 className="text-lg font-semibold mb-2">Database Damaged</h2>
+        <p className="text-sm text-muted-foreground mb-6 max-w-sm text-center">{corruption.message}</p>
+        {corruption.snapshot && (
+          <button
+            onClick={handleRestore}
+            disabled={restoring}
+            className="inline-flex items-center gap-2 rounded-md bg-primary px-4 py-2 text-sm font-medium text-primary-foreground hover:bg-primary/90 transition-colors disabled:opacity-50"
+          >
+            <DatabaseBackup className="h-4 w-4" />
+            {restoring ? 'Restoring…' : `Restore backup from ${new Date(corruption.snapshot.created_at).toLocaleString()}`}
+          </button>
+        )}
+      </SplashScreen>
+    );
+  }
+
   // Error state -- backend did not start
   if (state === 'error') {
     return (
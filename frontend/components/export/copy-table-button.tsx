@@ -0,0 +1,77 @@
+'use client';
+
+import { Copy } from 'lucide-react';
+import { Button } from '@/components/ui/button';
+import {
+  DropdownMenu,
+  DropdownMenuContent,
+  DropdownMenuItem,
+  DropdownMenuLabel,
+  DropdownMenuSeparator,
+  DropdownMenuTrigger,
+} from '@/components/ui/dropdown-menu';
+import { toast } from 'sonner';
+import { tauriInvoke } from '@/lib/backend-url';
+
+export type CopyFormat = 'tsv' | 'markdown' | 'text';
+
+export type TableCell = string | number | boolean | null;
+
+const FORMATS: { format: CopyFormat; label: string; description: string }[] = [
+  { format: 'tsv', label: 'Spreadsheet', description: 'TSV' },
+  { format: 'markdown', label: 'Markdown', description: 'for notes' },
+  { format: 'text', label: 'Plain text', description: 'aligned columns' },
+];
+
+interface CopyTableButtonProps {
+  columns: string[];
+  rows: TableCell[][];
+  /** Button size */
+  size?: 'default' | 'sm' | 'icon';
+  className?: string;
+}
+
+// Browser fallback outside the desktop shell, where there is no Rust renderer:
+// tab-separated works for every paste target.
+function toTsv(columns: string[], rows: TableCell[][]): string {
+  const line = (cells: TableCell[]) =>
+    cells.map((cell) => String(cell ?? '').replace(/[\t\r\n]+/g, ' ')).join('\t');
+  return [line(columns), ...rows.map(line)].join('\n');
+}
+
+export function CopyTableButton({ columns, rows, size = 'sm', className }: CopyTableButtonProps) {
+  const handleCopy = async (format: CopyFormat) => {
+    try {
+      const invoke = tauriInvoke();
+      if (invoke) {
+        await invoke('copy_to_clipboard', { format, payload: { columns, rows } });
+      } else {
+        await navigator.clipboard.writeText(toTsv(columns, rows));
+      }
+      toast.success('Table copied to clipboard');
+    } catch (error) {
+      toast.error(`Copy failed: ${error instanceof Error ? error.message : String(error)}`);
+    }
+  };
+
+  return (
+    <DropdownMenu>
+      <DropdownMenuTrigger asChild>
+        <Button variant="outline" size={size} className={className}>
+          <Copy className="h-4 w-4" />
+          {size !== 'icon' && <span className="ml-2">Copy</span>}
+        </Button>
+      </DropdownMenuTrigger>
+      <DropdownMenuContent align="end">
+        <DropdownMenuLabel>Copy as</DropdownMenuLabel>
+        <DropdownMenuSeparator />
+        {FORMATS.map(({ format, label, description }) => (
+          <DropdownMenuItem key={format} onClick={() => handleCopy(format)}>
+            <span>{label}</span>
+            <span className="ml-2 text-xs text-muted-foreground">({description})</span>
+          </DropdownMenuItem>
+        ))}
+      </DropdownMenuContent>
+    </DropdownMenu>
+  );
+}
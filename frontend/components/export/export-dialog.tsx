@@ -13,6 +13,7 @@ import {
   DialogTrigger,
 } from '@/components/ui/dialog';
 import { Input } from '@/components/ui/input';
+import { getBackendUrl } from '@/lib/backend-url';
 import {
   Select,
   SelectContent,
@@ -53,7 +54,6 @@ interface ExportConfig {
   includeAnnotations: boolean;
 }
 
-const API_URL = process.env.NEXT_PUBLIC_API_URL || 'http://localhost:8000';
 
 export function ExportDialog({
   type,
@@ -89,7 +89,7 @@ export function ExportDialog({
           params.set('include_indicators', 'true');
         }
         const stockQuery = params.toString();
-        return `${API_URL}/api/export/stocks/${symbol}/${config.format}${stockQuery ? `?${stockQuery}` : ''}`;
+        return `${getBackendUrl()}/api/export/stocks/${symbol}/${config.format}${stockQuery ? `?${stockQuery}` : ''}`;
 
       case 'insights':
         if (insightFilters?.type && insightFilters.type !== 'all') {
@@ -102,7 +102,7 @@ export function ExportDialog({
           params.set('include_annotations', 'true');
         }
         const insightsQuery = params.toString();
-        return `${API_URL}/api/export/insights/${config.format}${insightsQuery ? `?${insightsQuery}` : ''}`;
+        return `${getBackendUrl()}/api/export/insights/${config.format}${insightsQuery ? `?${insightsQuery}` : ''}`;
 
       case 'analysis':
         params.set('format', config.format);
@@ -112,7 +112,7 @@ export function ExportDialog({
         if (config.includeInsights) {
           params.set('include_insights', 'true');
         }
-        return `${API_URL}/api/export/analysis/${symbol}?${params.toString()}`;
+        return `${getBackendUrl()}/api/export/analysis/${symbol}?${params.toString()}`;
 
       default:
         throw new Error(`Unknown export type: ${type}`);
@@ -1,5 +1,8 @@
 export { ExportButton } from './export-button';
 export type { ExportOption, ExportFormat } from './export-button';
 
+export { CopyTableButton } from './copy-table-button';
+export type { CopyFormat, TableCell } from './copy-table-button';
+
 export { ExportDialog } from './export-dialog';
 export type { ExportType } from './export-dialog';
@@ -0,0 +1,50 @@
+'use client';
+
+import { useEffect, useState } from 'react';
+import { Plug } from 'lucide-react';
+import { tauriInvoke } from '@/lib/backend-url';
+
+const POLL_INTERVAL_MS = 5000;
+
+interface BackendStatus {
+  state: string;
+  external: boolean;
+}
+
+/**
+ * Shows that the desktop shell is using a backend it did not start, such as
+ * `uvicorn --reload` from a source checkout set with `--backend-url` or
+ * `backend.external_url`. Renders nothing outside Tauri.
+ */
+export function ExternalBackendBanner() {
+  const [url, setUrl] = useState<string | null>(null);
+
+  useEffect(() => {
+    const invoke = tauriInvoke();
+    if (!invoke) return;
+    const poll = async () => {
+      try {
+        const status: BackendStatus = await invoke('get_backend_state');
+        setUrl(status.external ? await invoke('get_backend_url') : null);
+      } catch {
+        setUrl(null);
+      }
+    };
+    poll();
+    const timer = setInterval(poll, POLL_INTERVAL_MS);
+    return () => clearInterval(timer);
+  }, []);
+
+  if (!url) return null;
+
+  return (
+    <div className="flex items-center gap-2 px-4 py-2 border-b bg-purple-500/10 border-purple-500/30 text-sm">
+      <Plug className="h-4 w-4 text-purple-500 shrink-0" />
+      <span className="text-muted-foreground">
+        Using the external backend at{' '}
+        <code className="text-xs bg-muted px-1 py-0.5 rounded">{url}</code>. The app does not
+        start, restart or stop it.
+      </span>
+    </div>
+  );
+}
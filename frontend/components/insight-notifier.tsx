@@ -0,0 +1,57 @@
+'use client';
+
+import { useEffect, useRef } from 'react';
+import { useQuery } from '@tanstack/react-query';
+import { api } from '@/lib/api';
+import { tauriInvoke } from '@/lib/backend-url';
+import { deepInsightKeys } from '@/lib/hooks/use-deep-insights';
+import { useEnqueueInsights } from '@/lib/hooks/use-review-queue';
+import type { DeepInsightListResponse } from '@/types';
+
+const POLL_INTERVAL_MS = 60 * 1000;
+const RECENT_LIMIT = 20;
+// Insights at least this confident get a native notification
+const NOTIFY_CONFIDENCE = 0.8;
+
+/**
+ * Posts a native notification through the desktop shell's `notify` for each
+ * confident insight generated while the app is open, and hands new insights
+ * to the review queue while review is required. The shell holds back the
+ * notification of an insight that is not approved. Renders nothing.
+ */
+export function InsightNotifier() {
+  const isDesktop = tauriInvoke() !== null;
+  const { data } = useQuery<DeepInsightListResponse>({
+    queryKey: deepInsightKeys.recent(RECENT_LIMIT),
+    queryFn: () => api.deepInsights.list({ limit: RECENT_LIMIT }),
+    refetchInterval: POLL_INTERVAL_MS,
+    enabled: isDesktop,
+  });
+  // Insights already seen; those present on load are not notified
+  const seen = useRef<Set<number> | null>(null);
+
+  useEnqueueInsights(data?.items);
+
+  useEffect(() => {
+    const invoke = tauriInvoke();
+    if (!invoke || !data) return;
+    if (seen.current === null) {
+      seen.current = new Set(data.items.map((insight) => insight.id));
+      return;
+    }
+    for (const insight of data.items) {
+      if (seen.current.has(insight.id)) continue;
+      seen.current.add(insight.id);
+      if (insight.confidence < NOTIFY_CONFIDENCE) continue;
+      const symbol = insight.primary_symbol ? `${insight.primary_symbol}: ` : '';
+      invoke('notify', {
+        title: `${symbol}${insight.action.replace('_', ' ')}`,
+        body: insight.title,
+        tag: `insight-${insight.id}`,
+        insight,
+      }).catch(() => {});
+    }
+  }, [data]);
+
+  return null;
+}
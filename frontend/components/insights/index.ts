@@ -11,3 +11,4 @@ export { OutcomeBadge } from './outcome-badge';
 export { DiscoveryContextCard } from './discovery-context-card';
 export { AnalysisSummaryBanner } from './analysis-summary-banner';
 export { GradientProgressBar } from './gradient-progress-bar';
+export { ReviewQueue } from './review-queue';
@@ -0,0 +1,178 @@
+'use client';
+
+import { useState } from 'react';
+import { toast } from 'sonner';
+import { Check, ClipboardCheck, Loader2, X } from 'lucide-react';
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/components/ui/card';
+import { Badge } from '@/components/ui/badge';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@/components/ui/select';
+import { Skeleton } from '@/components/ui/skeleton';
+import { EmptyState } from '@/components/ui/empty-state';
+import {
+  useReviewInsight,
+  useReviewQueue,
+  useReviewRequired,
+  type ReviewItem,
+} from '@/lib/hooks/use-review-queue';
+import type { InsightAction } from '@/types';
+
+const actions: InsightAction[] = ['STRONG_BUY', 'BUY', 'HOLD', 'SELL', 'STRONG_SELL', 'WATCH'];
+
+// Decided items listed under the pending ones
+const RECENTLY_DECIDED = 10;
+
+function actionLabel(action: string): string {
+  return action.replace('_', ' ');
+}
+
+function PendingItem({ item }: { item: ReviewItem }) {
+  const review = useReviewInsight();
+  const [action, setAction] = useState<InsightAction>(item.edits?.action ?? item.insight.action);
+  const [reason, setReason] = useState('');
+  const { insight } = item;
+
+  const decide = (status: 'approved' | 'rejected') => {
+    const edits = action !== insight.action ? { action } : undefined;
+    review.mutate(
+      { insightId: item.insight_id, status, reason, edits },
+      {
+        onSuccess: () =>
+          toast.success(status === 'approved' ? 'Insight approved' : 'Insight rejected'),
+        onError: (error) => toast.error(`Review failed: ${error.message}`),
+      },
+    );
+  };
+
+  return (
+    <Card>
+      <CardHeader className="pb-3">
+        <div className="flex items-start justify-between gap-4">
+          <div className="flex-1">
+            <div className="flex items-center gap-2 mb-1">
+              <Badge variant="outline">{actionLabel(insight.action)}</Badge>
+              {insight.primary_symbol && <Badge variant="secondary">{insight.primary_symbol}</Badge>}
+            </div>
+            <CardTitle className="text-base">{insight.title}</CardTitle>
+          </div>
+          <div className="text-right">
+            <div className="text-2xl font-bold">{Math.round(insight.confidence * 100)}%</div>
+            <div className="text-xs text-muted-foreground">confidence</div>
+          </div>
+        </div>
+      </CardHeader>
+      <CardContent className="space-y-3">
+        <p className="text-sm text-muted-foreground">{insight.thesis}</p>
+        <div className="flex flex-wrap items-center gap-2">
+          <Select value={action} onValueChange={(value) => setAction(value as InsightAction)}>
+            <SelectTrigger className="w-40" aria-label="Action">
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              {actions.map((a) => (
+                <SelectItem key={a} value={a}>
+                  {actionLabel(a)}
+                </SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
+          <Input
+            className="flex-1 min-w-48"
+            placeholder="Reason (optional)"
+            value={reason}
+            onChange={(e) => setReason(e.target.value)}
+          />
+          <Button variant="outline" onClick={() => decide('rejected')} disabled={review.isPending}>
+            <X className="h-4 w-4 mr-1" />
+            Reject
+          </Button>
+          <Button onClick={() => decide('approved')} disabled={review.isPending}>
+            {review.isPending ? (
+              <Loader2 className="h-4 w-4 mr-1 animate-spin" />
+            ) : (
+              <Check className="h-4 w-4 mr-1" />
+            )}
+            Approve
+          </Button>
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
+
+/**
+ * The desktop shell's review queue: pending insights, with their action
+ * editable, to approve or reject, and the latest decisions. Only approved
+ * insights are notified and count toward the track record.
+ */
+export function ReviewQueue() {
+  const required = useReviewRequired();
+  const { data: items, isLoading } = useReviewQueue();
+
+  if (!required) {
+    return (
+      <EmptyState
+        icon={ClipboardCheck}
+        title="Review is off"
+        description="Turn on review.required in the desktop app's settings to hold new insights until they are approved."
+      />
+    );
+  }
+
+  if (isLoading) {
+    return (
+      <div className="space-y-4">
+        {Array.from({ length: 3 }).map((_, i) => (
+          <Skeleton key={i} className="h-40 w-full" />
+        ))}
+      </div>
+    );
+  }
+
+  const pending = items?.filter((item) => item.status === 'pending') ?? [];
+  const decided = (items?.filter((item) => item.status !== 'pending') ?? [])
+    .sort((a, b) => (b.reviewed_at ?? '').localeCompare(a.reviewed_at ?? ''))
+    .slice(0, RECENTLY_DECIDED);
+
+  return (
+    <div className="space-y-6">
+      {pending.length === 0 ? (
+        <EmptyState
+          icon={ClipboardCheck}
+          title="Nothing to review"
+          description="New insights appear here until you approve or reject them."
+        />
+      ) : (
+        <div className="space-y-4">
+          {pending.map((item) => (
+            <PendingItem key={item.insight_id} item={item} />
+          ))}
+        </div>
+      )}
+
+      {decided.length > 0 && (
+        <Card>
+          <CardHeader>
+            <CardTitle className="text-base">Recent decisions</CardTitle>
+            <CardDescription>The latest insights you approved or rejected</CardDescription>
+          </CardHeader>
+          <CardContent className="space-y-2">
+            {decided.map((item) => (
+              <div key={item.insight_id} className="flex items-center gap-2 text-sm">
+                <Badge variant={item.status === 'approved' ? 'default' : 'destructive'}>
+                  {item.status}
+                </Badge>
+                <span className="flex-1 truncate">{item.insight.title}</span>
+                {item.edits?.action && (
+                  <span className="text-muted-foreground">as {actionLabel(item.edits.action)}</span>
+                )}
+                {item.reason && <span className="text-muted-foreground truncate">{item.reason}</span>}
+              </div>
+            ))}
+          </CardContent>
+        </Card>
+      )}
+    </div>
+  );
+}
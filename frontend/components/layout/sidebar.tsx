@@ -26,8 +26,10 @@ import {
   Briefcase,
   FileText,
   Activity,
+  ClipboardCheck,
 } from 'lucide-react';
 import { useDeepInsights } from '@/lib/hooks/use-deep-insights';
+import { useReviewQueue, useReviewRequired } from '@/lib/hooks/use-review-queue';
 
 interface SidebarItem {
   name: string;
@@ -49,6 +51,13 @@ const primaryNav: SidebarItem[] = [
   { name: 'Research', href: '/research', icon: <FlaskConical className="h-4 w-4" /> },
 ];
 
+// Shown after Insights while new insights wait for approval
+const reviewNav: SidebarItem = {
+  name: 'Review',
+  href: '/review',
+  icon: <ClipboardCheck className="h-4 w-4" />,
+};
+
 // Secondary navigation - Supporting data views
 const secondaryNav: SidebarItem[] = [
   { name: 'Market Data', href: '/stocks', icon: <BarChart3 className="h-4 w-4" /> },
@@ -69,6 +78,8 @@ export function Sidebar() {
 
   // Fetch insights to show counts
   const { data: insightsData } = useDeepInsights({ limit: 100 });
+  const reviewRequired = useReviewRequired();
+  const { data: pendingReview } = useReviewQueue('pending');
 
   // Calculate badge counts
   const pendingModifications = insightsData?.items?.filter(
@@ -80,10 +91,16 @@ export function Sidebar() {
   ).length || 0;
 
   // Create primary nav with dynamic badges
-  const primaryNavWithBadges = primaryNav.map((item) => {
+  const navItems = reviewRequired
+    ? primaryNav.flatMap((item) => (item.name === 'Insights' ? [item, reviewNav] : [item]))
+    : primaryNav;
+  const primaryNavWithBadges = navItems.map((item) => {
     if (item.name === 'Insights' && pendingModifications > 0) {
       return { ...item, badge: pendingModifications, badgeVariant: 'destructive' as const };
     }
+    if (item.name === 'Review' && pendingReview?.length) {
+      return { ...item, badge: pendingReview.length, badgeVariant: 'default' as const };
+    }
     if (item.name === 'Research' && activeResearch > 0) {
       return { ...item, badge: activeResearch, badgeVariant: 'secondary' as const };
     }
@@ -0,0 +1,69 @@
+'use client';
+
+import { useEffect, useState } from 'react';
+import { DatabaseBackup } from 'lucide-react';
+import { toast } from 'sonner';
+import { Button } from '@/components/ui/button';
+import { tauriInvoke } from '@/lib/backend-url';
+
+interface LegacyInstall {
+  database: string;
+  size_bytes: number;
+  modified_at: string | null;
+}
+
+/**
+ * Offers to bring in the database and logs an earlier build of the desktop
+ * app left behind, on the first run in the current data layout. Nothing is
+ * moved until the user confirms. Renders nothing outside Tauri.
+ */
+export function LegacyDataBanner() {
+  const [found, setFound] = useState<LegacyInstall | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  useEffect(() => {
+    const invoke = tauriInvoke();
+    if (!invoke) return;
+    invoke('get_legacy_data')
+      .then((legacy: LegacyInstall | null) => setFound(legacy))
+      .catch(() => {});
+  }, []);
+
+  if (!found) return null;
+
+  const resolve = async (command: 'migrate_legacy_data' | 'decline_legacy_data') => {
+    const invoke = tauriInvoke();
+    if (!invoke) return;
+    setBusy(true);
+    try {
+      await invoke(command);
+      setFound(null);
+      if (command === 'migrate_legacy_data') {
+        toast.success('Data from the earlier version migrated');
+      }
+    } catch (error) {
+      toast.error(`Migration failed: ${error instanceof Error ? error.message : String(error)}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const modified = found.modified_at ? new Date(found.modified_at).toLocaleDateString() : null;
+
+  return (
+    <div className="flex items-center gap-2 px-4 py-2 border-b bg-blue-500/10 border-blue-500/30 text-sm">
+      <DatabaseBackup className="h-4 w-4 text-blue-500 shrink-0" />
+      <span className="flex-1 text-muted-foreground">
+        Found data from an earlier version at{' '}
+        <code className="text-xs bg-muted px-1 py-0.5 rounded">{found.database}</code>
+        {modified ? ` (last used ${modified})` : ''}. Migrate it to continue with your history.
+      </span>
+      <Button size="sm" variant="ghost" onClick={() => resolve('decline_legacy_data')} disabled={busy}>
+        Start fresh
+      </Button>
+      <Button size="sm" variant="outline" onClick={() => resolve('migrate_legacy_data')} disabled={busy}>
+        Migrate
+      </Button>
+    </div>
+  );
+}
@@ -0,0 +1,62 @@
+'use client';
+
+import { useEffect, useState } from 'react';
+import { RefreshCw } from 'lucide-react';
+import { toast } from 'sonner';
+import { Button } from '@/components/ui/button';
+import { tauriInvoke } from '@/lib/backend-url';
+
+const POLL_INTERVAL_MS = 5000;
+
+/**
+ * Asks to restart the backend when settings it only reads at startup (the
+ * `[env]` section, `backend.json`) changed while it was running. The desktop
+ * shell never restarts it for these on its own. Renders nothing outside Tauri.
+ */
+export function RestartRequiredBanner() {
+  const [changes, setChanges] = useState<string[]>([]);
+  const [restarting, setRestarting] = useState(false);
+
+  useEffect(() => {
+    const invoke = tauriInvoke();
+    if (!invoke) return;
+    const poll = () =>
+      invoke('get_pending_restart')
+        .then((pending: string[]) => setChanges(pending))
+        .catch(() => {});
+    poll();
+    const timer = setInterval(poll, POLL_INTERVAL_MS);
+    return () => clearInterval(timer);
+  }, []);
+
+  if (changes.length === 0) return null;
+
+  const handleRestart = async () => {
+    const invoke = tauriInvoke();
+    if (!invoke) return;
+    setRestarting(true);
+    try {
+      await invoke('apply_pending_restart');
+      setChanges([]);
+      toast.success('Backend restarted with the new settings');
+    } catch (error) {
+      toast.error(`Restart failed: ${error instanceof Error ? error.message : String(error)}`);
+    } finally {
+      setRestarting(false);
+    }
+  };
+
+  return (
+    <div className="flex items-center gap-2 px-4 py-2 border-b bg-blue-500/10 border-blue-500/30 text-sm">
+      <RefreshCw className="h-4 w-4 text-blue-500 shrink-0" />
+      <span className="flex-1 text-muted-foreground">
+        Restart the backend to apply{' '}
+        <code className="text-xs bg-muted px-1 py-0.5 rounded">{changes.join(', ')}</code>. Running
+        analyses will be allowed to finish first.
+      </span>
+      <Button size="sm" variant="outline" onClick={handleRestart} disabled={restarting}>
+        {restarting ? 'Restarting…' : 'Restart now'}
+      </Button>
+    </div>
+  );
+}
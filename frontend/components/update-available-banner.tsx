@@ -0,0 +1,128 @@
+'use client';
+
+import { useEffect, useState } from 'react';
+import { Download } from 'lucide-react';
+import { toast } from 'sonner';
+import { Button } from '@/components/ui/button';
+import { tauriInvoke, tauriListen } from '@/lib/backend-url';
+
+interface UpdateInfo {
+  version: string;
+  current_version: string;
+  notes: string | null;
+  size_bytes: number | null;
+}
+
+interface UpdateCheck {
+  app: UpdateInfo | null;
+  backend: UpdateInfo | null;
+}
+
+interface UpdateRolledBack {
+  failed_version: string;
+  version: string;
+  reason: string;
+}
+
+interface DownloadProgress {
+  component: 'app' | 'backend';
+  bytes: number;
+  total_bytes: number | null;
+}
+
+function formatSize(bytes: number): string {
+  return bytes >= 1024 * 1024
+    ? `${(bytes / (1024 * 1024)).toFixed(1)} MB`
+    : `${Math.ceil(bytes / 1024)} KB`;
+}
+
+/**
+ * Offers the newer app or backend the desktop shell's `check_for_updates`
+ * found on the configured channel, with its release notes and size, and
+ * shows the download progress while it installs. Checks once on load unless
+ * `updates.check_on_startup` is off, and tells when a backend update that
+ * failed was rolled back. Renders nothing outside Tauri.
+ */
+export function UpdateAvailableBanner() {
+  const [check, setCheck] = useState<UpdateCheck | null>(null);
+  const [progress, setProgress] = useState<DownloadProgress | null>(null);
+  const [installing, setInstalling] = useState(false);
+
+  useEffect(() => {
+    const invoke = tauriInvoke();
+    if (!invoke) return;
+    invoke('get_setting', { key: 'updates.check_on_startup' })
+      .then((enabled: boolean) => (enabled ? invoke('check_for_updates') : null))
+      .then((result: UpdateCheck | null) => setCheck(result))
+      .catch(() => {});
+
+    const unlisten: (() => void)[] = [];
+    let cancelled = false;
+    const subscribe = (subscription: Promise<(() => void) | null>) =>
+      subscription.then((stop) => {
+        if (!stop) return;
+        if (cancelled) stop();
+        else unlisten.push(stop);
+      });
+    subscribe(tauriListen<DownloadProgress>('update-download-progress', setProgress));
+    subscribe(
+      tauriListen<UpdateRolledBack>('update-rolled-back', (rollback) =>
+        toast.error(
+          `Backend ${rollback.failed_version} failed (${rollback.reason}), so ${rollback.version} is back in use`,
+        ),
+      ),
+    );
+    return () => {
+      cancelled = true;
+      unlisten.forEach((stop) => stop());
+    };
+  }, []);
+
+  const component = check?.app ? 'app' : check?.backend ? 'backend' : null;
+  if (!check || !component) return null;
+  const update = (check.app ?? check.backend) as UpdateInfo;
+
+  const handleInstall = async () => {
+    const invoke = tauriInvoke();
+    if (!invoke) return;
+    setInstalling(true);
+    try {
+      // The app relaunches itself once installed; the backend restarts.
+      await invoke(component === 'app' ? 'install_update' : 'install_backend_update');
+      setCheck({ ...check, [component]: null });
+      toast.success(`Backend updated to ${update.version}`);
+    } catch (error) {
+      toast.error(`Update failed: ${error instanceof Error ? error.message : String(error)}`);
+    } finally {
+      setInstalling(false);
+      setProgress(null);
+    }
+  };
+
+  const dismiss = () => setCheck({ ...check, [component]: null });
+
+  let status = installing ? 'Installing…' : 'Install';
+  if (installing && progress?.component === component) {
+    status = progress.total_bytes
+      ? `Downloading ${Math.floor((progress.bytes / progress.total_bytes) * 100)}%`
+      : `Downloading ${formatSize(progress.bytes)}`;
+  }
+  const size = update.size_bytes ? ` (${formatSize(update.size_bytes)})` : '';
+
+  return (
+    <div className="flex items-center gap-2 px-4 py-2 border-b bg-blue-500/10 border-blue-500/30 text-sm">
+      <Download className="h-4 w-4 text-blue-500 shrink-0" />
+      <span className="flex-1 text-muted-foreground" title={update.notes ?? undefined}>
+        {component === 'app' ? 'Teletraan' : 'The backend'} {update.version} is available
+        {size}, replacing {update.current_version}.
+        {update.notes ? ` ${update.notes.split('\n')[0]}` : ''}
+      </span>
+      <Button size="sm" variant="ghost" onClick={dismiss} disabled={installing}>
+        Later
+      </Button>
+      <Button size="sm" variant="outline" onClick={handleInstall} disabled={installing}>
+        {status}
+      </Button>
+    </div>
+  );
+}
@@ -1,6 +1,10 @@
-// Get the base URL, removing any trailing /api/v1 if present (to avoid duplication)
-const rawUrl = process.env.NEXT_PUBLIC_API_URL || 'http://localhost:8000';
-const API_URL = rawUrl.replace(/\/api\/v1\/?$/, '');
+import {
+  backendAuthHeaders,
+  getBackendUrl,
+  resolveBackendUrl,
+  tauriInvoke,
+  withBackendToken,
+} from '@/lib/backend-url';
 
 export class ApiError extends Error {
   constructor(public status: number, message: string) {
@@ -14,7 +18,7 @@ export async function fetchApi<T>(
   endpoint: string,
   options?: RequestInit & { params?: Record<string, string | number | boolean | undefined> }
 ): Promise<T> {
-  let url = `${API_URL}${endpoint}`;
+  let path = endpoint;
 
   // Add query params if provided
   if (options?.params) {
@@ -26,16 +30,35 @@ export async function fetchApi<T>(
     });
     const queryString = params.toString();
     if (queryString) {
-      url += `?${queryString}`;
+      path += `?${queryString}`;
     }
   }
 
   const { params: _params, ...fetchOptions } = options || {};
 
+  // In the desktop shell, let the host send it: it knows the backend's current
+  // address and retries while the backend restarts.
+  const invoke = tauriInvoke();
+  if (invoke && (fetchOptions.body === undefined || typeof fetchOptions.body === 'string')) {
+    const res: { status: number; body: unknown } = await invoke('backend_request', {
+      method: fetchOptions.method ?? 'GET',
+      path,
+      body: fetchOptions.body ? JSON.parse(fetchOptions.body) : null,
+    });
+    if (res.status < 200 || res.status >= 300) {
+      const message = typeof res.body === 'string' ? res.body : JSON.stringify(res.body);
+      throw new ApiError(res.status, message || `HTTP ${res.status}`);
+    }
+    return res.body as T;
+  }
+
+  const url = `${await resolveBackendUrl()}${path}`;
+
   const res = await fetch(url, {
     ...fetchOptions,
     headers: {
       'Content-Type': 'application/json',
+      ...backendAuthHeaders(),
       ...fetchOptions?.headers,
     },
   });
@@ -345,18 +368,28 @@ export const api = {
 
   // Portfolio
   portfolio: {
-    get: () =>
-      fetchApi<Portfolio>('/api/v1/portfolio'),
+    get: (accountId?: number) =>
+      fetchApi<Portfolio>('/api/v1/portfolio', { params: { account_id: accountId } }),
     create: (data?: { name?: string; description?: string }) =>
       postApi<Portfolio>('/api/v1/portfolio', data),
+    accounts: () =>
+      fetchApi<PortfolioAccount[]>('/api/v1/portfolio/accounts'),
+    createAccount: (data: AccountCreate) =>
+      postApi<PortfolioAccount>('/api/v1/portfolio/accounts', data),
+    updateAccount: (accountId: number, data: AccountUpdate) =>
+      putApi<PortfolioAccount>(`/api/v1/portfolio/accounts/${accountId}`, data),
+    deleteAccount: (accountId: number) =>
+      deleteApi<void>(`/api/v1/portfolio/accounts/${accountId}`),
+    consolidated: (includePaper?: boolean) =>
+      fetchApi<ConsolidatedPortfolio>('/api/v1/portfolio/consolidated', { params: { include_paper: includePaper } }),
     addHolding: (holding: HoldingCreate) =>
       postApi<PortfolioHolding>('/api/v1/portfolio/holdings', holding),
     updateHolding: (holdingId: number, data: HoldingUpdate) =>
       putApi<PortfolioHolding>(`/api/v1/portfolio/holdings/${holdingId}`, data),
     deleteHolding: (holdingId: number) =>
       deleteApi<void>(`/api/v1/portfolio/holdings/${holdingId}`),
-    impact: () =>
-      fetchApi<PortfolioImpact>('/api/v1/portfolio/impact'),
+    impact: (accountId?: number) =>
+      fetchApi<PortfolioImpact>('/api/v1/portfolio/impact', { params: { account_id: accountId } }),
   },
 
   // Research
@@ -381,7 +414,7 @@ export const api = {
       }),
     get: (id: string) =>
       fetchApi<ReportDetail>(`/api/v1/reports/${id}`),
-    htmlUrl: (id: string) => `${API_URL}/api/v1/reports/${id}/html`,
+    htmlUrl: (id: string) => withBackendToken(`${getBackendUrl()}/api/v1/reports/${id}/html`),
     publish: (id: string) =>
       postApi<PublishResponse>(`/api/v1/reports/${id}/publish`),
   },
@@ -441,7 +474,7 @@ export const chatApi = {
 };
 
 // Import types
-import type { Stock, PriceHistory, Insight, InsightAnnotation, InsightFilters, AnalysisResult, PaginatedResponse, RefreshDataResponse, WatchlistSettings, DeepInsight, DeepInsightListResponse, DeepInsightType, InsightAction, AutonomousAnalysisResponse, Portfolio, PortfolioHolding, HoldingCreate, HoldingUpdate, PortfolioImpact, LLMProviderStatus, LLMProviderConfig, LLMTestRequest, LLMTestResult, RunListResponse, RunsAggregateStats, RunSummary } from '@/types';
+import type { Stock, PriceHistory, Insight, InsightAnnotation, InsightFilters, AnalysisResult, PaginatedResponse, RefreshDataResponse, WatchlistSettings, DeepInsight, DeepInsightListResponse, DeepInsightType, InsightAction, AutonomousAnalysisResponse, Portfolio, PortfolioHolding, HoldingCreate, HoldingUpdate, PortfolioImpact, PortfolioAccount, AccountCreate, AccountUpdate, ConsolidatedPortfolio, LLMProviderStatus, LLMProviderConfig, LLMTestRequest, LLMTestResult, RunListResponse, RunsAggregateStats, RunSummary } from '@/types';
 import type { KnowledgePattern, KnowledgePatternsResponse, KnowledgePatternsParams, PatternsSummary, MatchingPatternsParams, ConversationTheme, ConversationThemesResponse, ConversationThemesParams } from '@/lib/types/knowledge';
 import type { FollowUpResearch, ResearchListResponse, ResearchListParams, ResearchCreateRequest } from '@/lib/types/research';
 import type { ReportListResponse, ReportDetail, PublishResponse } from '@/lib/types/report';
@@ -0,0 +1,97 @@
+// Resolve where the backend API lives.
+//
+// In the web build this is NEXT_PUBLIC_API_URL (default localhost:8000). In the
+// Tauri desktop shell the backend listens on a port picked at launch, which the
+// Rust host reports through the `get_backend_url` command, along with the
+// token the backend requires on every request (`get_backend_token`).
+
+const rawUrl = process.env.NEXT_PUBLIC_API_URL || 'http://localhost:8000';
+// Strip any trailing /api/v1 (to avoid duplication)
+const DEFAULT_API_URL = rawUrl.replace(/\/api\/v1\/?$/, '');
+
+let resolvedUrl: string | null = null;
+let resolvedToken: string | null = null;
+let pending: Promise<string> | null = null;
+
+/** The desktop host's `invoke`, or `null` outside Tauri. */
+// eslint-disable-next-line @typescript-eslint/no-explicit-any
+export function tauriInvoke(): ((cmd: string, args?: Record<string, unknown>) => Promise<any>) | null {
+  if (typeof window === 'undefined') return null;
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  const w = window as any;
+  return w.__TAURI_INTERNALS__?.invoke ?? w.__TAURI__?.core?.invoke ?? null;
+}
+
+/**
+ * Subscribe to an event the desktop host emits. Resolves to the function that
+ * unsubscribes, or `null` outside Tauri.
+ */
+export async function tauriListen<T>(
+  event: string,
+  handler: (payload: T) => void,
+): Promise<(() => void) | null> {
+  if (typeof window === 'undefined') return null;
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  const listen = (window as any).__TAURI__?.event?.listen;
+  if (!listen) return null;
+  return listen(event, (e: { payload: T }) => handler(e.payload));
+}
+
+/**
+ * Base URL of the backend (no trailing /api/v1), asking the desktop host for
+ * it on first use. Falls back to the configured URL outside Tauri or while the
+ * host has not started the backend yet.
+ */
+export async function resolveBackendUrl(): Promise<string> {
+  if (resolvedUrl) return resolvedUrl;
+  const invoke = tauriInvoke();
+  if (!invoke) return DEFAULT_API_URL;
+
+  pending ??= Promise.all([invoke('get_backend_url'), invoke('get_backend_token')])
+    .then(([url, token]: [string, string | null]) => {
+      resolvedToken = token;
+      resolvedUrl = url;
+      return url;
+    })
+    .catch(() => DEFAULT_API_URL)
+    .finally(() => {
+      pending = null;
+    });
+  return pending;
+}
+
+/**
+ * Last resolved backend URL, for code that has to build a URL synchronously
+ * (links, WebSockets). Call `resolveBackendUrl` first where possible.
+ */
+export function getBackendUrl(): string {
+  return resolvedUrl ?? DEFAULT_API_URL;
+}
+
+/** WebSocket base URL (ws:// or wss://) matching `getBackendUrl`. */
+export function getBackendWsUrl(): string {
+  if (!resolvedUrl && process.env.NEXT_PUBLIC_WS_URL) {
+    try {
+      const url = new URL(process.env.NEXT_PUBLIC_WS_URL);
+      return `${url.protocol}//${url.host}`;
+    } catch {
+      // fall through to the HTTP URL
+    }
+  }
+  return getBackendUrl().replace(/^http/, 'ws');
+}
+
+/** Header for a request that goes to the backend directly, not through the host. */
+export function backendAuthHeaders(): Record<string, string> {
+  return resolvedToken ? { 'X-Teletraan-Token': resolvedToken } : {};
+}
+
+/**
+ * `url` with the backend token as a query parameter, for requests that cannot
+ * carry headers (WebSockets, links, no-cors fetches).
+ */
+export function withBackendToken(url: string): string {
+  if (!resolvedToken) return url;
+  const separator = url.includes('?') ? '&' : '?';
+  return `${url}${separator}token=${encodeURIComponent(resolvedToken)}`;
+}
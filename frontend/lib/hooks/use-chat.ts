@@ -2,10 +2,7 @@
 
 import { useState, useCallback, useRef, useEffect } from 'react';
 import type { Message, ToolCall, ChatState, SendMessageOptions } from '@/types/chat';
-
-// WebSocket URL - can be configured via environment variable
-const WS_URL = process.env.NEXT_PUBLIC_WS_URL || 'ws://localhost:8000/api/v1/chat';
-const API_URL = process.env.NEXT_PUBLIC_API_URL || 'http://localhost:8000/api/v1';
+import { backendAuthHeaders, getBackendWsUrl, resolveBackendUrl, withBackendToken } from '@/lib/backend-url';
 
 // Reconnection settings
 const RECONNECT_INTERVAL = 3000;
@@ -159,7 +156,7 @@ export function useChat() {
     }
 
     try {
-      const ws = new WebSocket(WS_URL);
+      const ws = new WebSocket(withBackendToken(`${getBackendWsUrl()}/api/v1/chat`));
 
       ws.onopen = () => {
         console.log('WebSocket connected');
@@ -311,7 +308,10 @@ export function useChat() {
 
     // Also clear on server
     try {
-      await fetch(`${API_URL}/chat/clear`, { method: 'POST' });
+      await fetch(`${await resolveBackendUrl()}/api/v1/chat/clear`, {
+        method: 'POST',
+        headers: backendAuthHeaders(),
+      });
     } catch (error) {
       console.error('Failed to clear server chat history:', error);
     }
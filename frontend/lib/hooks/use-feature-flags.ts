@@ -0,0 +1,37 @@
+'use client';
+
+import { useQuery } from '@tanstack/react-query';
+import { tauriInvoke } from '@/lib/backend-url';
+
+export interface FeatureFlag {
+  name: string;
+  description: string;
+  enabled: boolean;
+  source: 'default' | 'remote' | 'user';
+}
+
+export const featureFlagKeys = {
+  all: ['feature-flags'] as const,
+};
+
+/**
+ * Feature flags from the desktop shell's `get_feature_flags`. Outside Tauri
+ * (e.g. `npm run dev` in a browser) there are none, so every flag is off.
+ */
+export function useFeatureFlags() {
+  return useQuery<FeatureFlag[]>({
+    queryKey: featureFlagKeys.all,
+    queryFn: async () => {
+      const invoke = tauriInvoke();
+      if (!invoke) return [];
+      return invoke('get_feature_flags');
+    },
+    staleTime: 60 * 1000,
+  });
+}
+
+/** Whether the flag `name` is on; `false` while the flags load. */
+export function useFeatureFlag(name: string): boolean {
+  const { data } = useFeatureFlags();
+  return data?.some((flag) => flag.name === name && flag.enabled) ?? false;
+}
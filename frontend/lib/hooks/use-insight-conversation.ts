@@ -3,6 +3,7 @@
 import { useState, useCallback, useRef, useEffect } from 'react';
 import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query';
 import { fetchApi, postApi, putApi, deleteApi } from '@/lib/api';
+import { getBackendWsUrl, withBackendToken } from '@/lib/backend-url';
 
 // ============================================
 // Types
@@ -240,17 +241,6 @@ export function useInsightConversation(conversationId: number | undefined) {
 // WebSocket Chat Hook
 // ============================================
 
-// WebSocket URL configuration
-const WS_BASE_URL = (() => {
-  const wsUrl = process.env.NEXT_PUBLIC_WS_URL || 'ws://localhost:8000/api/v1/chat';
-  try {
-    const url = new URL(wsUrl);
-    return `${url.protocol}//${url.host}`;
-  } catch {
-    return 'ws://localhost:8000';
-  }
-})();
-
 // Reconnection settings
 const RECONNECT_INTERVAL = 3000;
 const MAX_RECONNECT_ATTEMPTS = 5;
@@ -285,7 +275,7 @@ export function useInsightChat(conversationId: number | undefined) {
 
   // WebSocket URL for this conversation
   const wsUrl = conversationId
-    ? `${WS_BASE_URL}/api/v1/conversations/${conversationId}/chat`
+    ? withBackendToken(`${getBackendWsUrl()}/api/v1/conversations/${conversationId}/chat`)
     : null;
 
   // Handle incoming WebSocket messages
@@ -2,13 +2,15 @@
 
 import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query';
 import { api } from '@/lib/api';
-import type { Portfolio, PortfolioHolding, HoldingCreate, HoldingUpdate, PortfolioImpact } from '@/types';
+import type { Portfolio, PortfolioHolding, HoldingCreate, HoldingUpdate, PortfolioImpact, PortfolioAccount, ConsolidatedPortfolio } from '@/types';
 
 // Query keys for portfolio
 export const portfolioKeys = {
   all: ['portfolio'] as const,
   detail: () => [...portfolioKeys.all, 'detail'] as const,
   impact: () => [...portfolioKeys.all, 'impact'] as const,
+  accounts: () => [...portfolioKeys.all, 'accounts'] as const,
+  consolidated: (includePaper: boolean) => [...portfolioKeys.all, 'consolidated', includePaper] as const,
 };
 
 /**
@@ -36,6 +38,30 @@ export function usePortfolioImpact(enabled?: boolean) {
   });
 }
 
+/**
+ * Custom hook for listing the portfolio accounts with their totals
+ */
+export function usePortfolioAccounts() {
+  return useQuery<PortfolioAccount[]>({
+    queryKey: portfolioKeys.accounts(),
+    queryFn: () => api.portfolio.accounts(),
+    staleTime: 60_000, // 1 minute
+    gcTime: 600_000, // 10 minutes cache
+  });
+}
+
+/**
+ * Custom hook for fetching the holdings of all accounts combined
+ */
+export function useConsolidatedPortfolio(includePaper = false) {
+  return useQuery<ConsolidatedPortfolio>({
+    queryKey: portfolioKeys.consolidated(includePaper),
+    queryFn: () => api.portfolio.consolidated(includePaper),
+    staleTime: 60_000, // 1 minute
+    gcTime: 600_000, // 10 minutes cache
+  });
+}
+
 /**
  * Custom hook for adding a holding to the portfolio
  */
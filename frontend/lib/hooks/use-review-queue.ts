@@ -0,0 +1,128 @@
+'use client';
+
+import { useEffect, useRef } from 'react';
+import { useMutation, useQuery, useQueryClient } from '@tanstack/react-query';
+import { tauriInvoke, tauriListen } from '@/lib/backend-url';
+import type { DeepInsight } from '@/types';
+
+export type ReviewStatus = 'pending' | 'approved' | 'rejected';
+
+/** An insight in the desktop shell's review queue (`review_queue.json`). */
+export interface ReviewItem {
+  insight_id: number;
+  status: ReviewStatus;
+  insight: DeepInsight;
+  edits: Partial<DeepInsight> | null;
+  reason: string | null;
+  submitted_at: string;
+  reviewed_at: string | null;
+}
+
+export const reviewKeys = {
+  all: ['review'] as const,
+  required: () => [...reviewKeys.all, 'required'] as const,
+  queue: (status?: ReviewStatus) => [...reviewKeys.all, 'queue', status] as const,
+};
+
+/**
+ * Whether new insights wait for approval (`review.required` in the desktop
+ * shell's settings). Always `false` outside Tauri.
+ */
+export function useReviewRequired(): boolean {
+  const { data } = useQuery<boolean>({
+    queryKey: reviewKeys.required(),
+    queryFn: async () => {
+      const invoke = tauriInvoke();
+      if (!invoke) return false;
+      return invoke('get_setting', { key: 'review.required' });
+    },
+    staleTime: 60 * 1000,
+  });
+  return data ?? false;
+}
+
+/**
+ * The review queue from `list_review_queue`, oldest first, optionally only
+ * the items with `status`. Refetched whenever the shell reports the queue
+ * changed.
+ */
+export function useReviewQueue(status?: ReviewStatus) {
+  const queryClient = useQueryClient();
+  const required = useReviewRequired();
+
+  useEffect(() => {
+    let stop: (() => void) | null = null;
+    let cancelled = false;
+    tauriListen('review-queue-updated', () =>
+      queryClient.invalidateQueries({ queryKey: [...reviewKeys.all, 'queue'] }),
+    ).then((unlisten) => {
+      if (cancelled) unlisten?.();
+      else stop = unlisten;
+    });
+    return () => {
+      cancelled = true;
+      stop?.();
+    };
+  }, [queryClient]);
+
+  return useQuery<ReviewItem[]>({
+    queryKey: reviewKeys.queue(status),
+    queryFn: async () => {
+      const invoke = tauriInvoke();
+      if (!invoke) return [];
+      return invoke('list_review_queue', { status: status ?? null });
+    },
+    enabled: required,
+  });
+}
+
+export interface ReviewDecision {
+  insightId: number;
+  status: Exclude<ReviewStatus, 'pending'>;
+  reason?: string;
+  edits?: Partial<DeepInsight>;
+}
+
+/**
+ * Approve or reject a queued insight through `review_insight`. Approving one
+ * has the backend start tracking its outcome.
+ */
+export function useReviewInsight() {
+  const queryClient = useQueryClient();
+  return useMutation<ReviewItem, Error, ReviewDecision>({
+    mutationFn: async ({ insightId, status, reason, edits }) => {
+      const invoke = tauriInvoke();
+      if (!invoke) throw new Error('Insight review needs the desktop app');
+      return invoke('review_insight', {
+        insightId,
+        status,
+        reason: reason || null,
+        edits: edits ?? null,
+      });
+    },
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: [...reviewKeys.all, 'queue'] });
+    },
+  });
+}
+
+/**
+ * Hand `insights` to the review queue through `enqueue_insights` while review
+ * is required. Each insight is sent once; the queue keeps the decisions of
+ * insights it already has.
+ */
+export function useEnqueueInsights(insights: DeepInsight[] | undefined) {
+  const required = useReviewRequired();
+  const sent = useRef(new Set<number>());
+
+  useEffect(() => {
+    const invoke = tauriInvoke();
+    if (!invoke || !required || !insights) return;
+    const fresh = insights.filter((insight) => !sent.current.has(insight.id));
+    if (fresh.length === 0) return;
+    fresh.forEach((insight) => sent.current.add(insight.id));
+    invoke('enqueue_insights', { insights: fresh }).catch(() => {
+      fresh.forEach((insight) => sent.current.delete(insight.id));
+    });
+  }, [insights, required]);
+}
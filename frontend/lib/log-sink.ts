@@ -0,0 +1,57 @@
+// Forward the webview's console errors and warnings to the desktop host, so
+// they land in the same rotated log (`logs/shell.log`) as the shell's own
+// events. Does nothing outside the Tauri desktop build.
+
+type Level = 'warn' | 'error';
+
+// Longest message sent; the host truncates too, this just saves the IPC.
+const MAX_MESSAGE_LENGTH = 8000;
+
+let installed = false;
+
+// eslint-disable-next-line @typescript-eslint/no-explicit-any
+function tauriInvoke(): ((cmd: string, args?: Record<string, unknown>) => Promise<any>) | null {
+  if (typeof window === 'undefined') return null;
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  const w = window as any;
+  return w.__TAURI_INTERNALS__?.invoke ?? w.__TAURI__?.core?.invoke ?? null;
+}
+
+function describe(value: unknown): string {
+  if (value instanceof Error) return value.stack || `${value.name}: ${value.message}`;
+  if (typeof value === 'string') return value;
+  try {
+    return JSON.stringify(value);
+  } catch {
+    return String(value);
+  }
+}
+
+function send(level: Level, source: string, parts: unknown[]) {
+  const invoke = tauriInvoke();
+  if (!invoke) return;
+  const message = parts.map(describe).join(' ').slice(0, MAX_MESSAGE_LENGTH);
+  // Never report a failed report through console.error: that would loop.
+  invoke('log_frontend_event', { level, message, source }).catch(() => {});
+}
+
+/** Start forwarding; safe to call more than once. */
+export function installLogSink() {
+  if (installed || !tauriInvoke()) return;
+  installed = true;
+
+  for (const level of ['warn', 'error'] as const) {
+    const original = console[level].bind(console);
+    console[level] = (...args: unknown[]) => {
+      original(...args);
+      send(level, 'console', args);
+    };
+  }
+
+  window.addEventListener('error', (event) => {
+    send('error', 'window', [event.error ?? event.message]);
+  });
+  window.addEventListener('unhandledrejection', (event) => {
+    send('error', 'promise', [event.reason]);
+  });
+}
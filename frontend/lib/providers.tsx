@@ -1,8 +1,9 @@
 'use client';
 
 import { QueryClient, QueryClientProvider } from '@tanstack/react-query';
-import { useState, type ReactNode } from 'react';
+import { useEffect, useState, type ReactNode } from 'react';
 import { Toaster } from '@/components/ui/sonner';
+import { installLogSink } from '@/lib/log-sink';
 
 interface ProvidersProps {
   children: ReactNode;
@@ -24,6 +25,10 @@ export function Providers({ children }: ProvidersProps) {
       })
   );
 
+  useEffect(() => {
+    installLogSink();
+  }, []);
+
   return (
     <QueryClientProvider client={queryClient}>
       {children}
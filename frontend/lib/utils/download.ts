@@ -2,7 +2,7 @@
  * Utility functions for downloading files from the API.
  */
 
-const API_URL = process.env.NEXT_PUBLIC_API_URL || 'http://localhost:8000';
+import { backendAuthHeaders, getBackendUrl } from '@/lib/backend-url';
 
 export interface DownloadOptions {
   /** Custom filename override (if not using server-provided name) */
@@ -27,7 +27,9 @@ export async function downloadFile(
   try {
     onStart?.();
 
-    const response = await fetch(url);
+    // Only the backend gets its token.
+    const headers = url.startsWith(getBackendUrl()) ? backendAuthHeaders() : {};
+    const response = await fetch(url, { headers });
 
     if (!response.ok) {
       throw new Error(`Download failed: ${response.status} ${response.statusText}`);
@@ -96,7 +98,7 @@ export function buildStockExportUrl(
   }
 
   const queryString = params.toString();
-  return `${API_URL}/api/export/stocks/${symbol}/${format}${queryString ? `?${queryString}` : ''}`;
+  return `${getBackendUrl()}/api/export/stocks/${symbol}/${format}${queryString ? `?${queryString}` : ''}`;
 }
 
 /**
@@ -127,7 +129,7 @@ export function buildInsightsExportUrl(
   }
 
   const queryString = params.toString();
-  return `${API_URL}/api/export/insights/${format}${queryString ? `?${queryString}` : ''}`;
+  return `${getBackendUrl()}/api/export/insights/${format}${queryString ? `?${queryString}` : ''}`;
 }
 
 /**
@@ -159,5 +161,5 @@ export function buildAnalysisExportUrl(
     params.set('include_insights', String(options.includeInsights));
   }
 
-  return `${API_URL}/api/export/analysis/${symbol}?${params.toString()}`;
+  return `${getBackendUrl()}/api/export/analysis/${symbol}?${params.toString()}`;
 }
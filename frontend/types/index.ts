@@ -365,24 +365,94 @@ export interface PortfolioHolding {
   updated_at?: string;
 }
 
+export type AccountType = 'taxable' | 'ira' | 'paper';
+
+export interface RiskLimitBreach {
+  account_id: number;
+  symbol: string;
+  allocation_pct: number;
+  max_position_pct: number;
+}
+
 export interface Portfolio {
   id: number;
   name: string;
   description?: string;
+  account_type: AccountType;
+  max_position_pct?: number;
   holdings: PortfolioHolding[];
   total_value?: number;
   total_cost?: number;
   total_gain_loss?: number;
   total_gain_loss_pct?: number;
+  limit_breaches: RiskLimitBreach[];
   created_at: string;
   updated_at?: string;
 }
 
+export interface PortfolioAccount {
+  id: number;
+  name: string;
+  description?: string;
+  account_type: AccountType;
+  max_position_pct?: number;
+  holdings_count: number;
+  total_value?: number;
+  total_cost?: number;
+  total_gain_loss?: number;
+  total_gain_loss_pct?: number;
+  limit_breaches: RiskLimitBreach[];
+}
+
+export interface AccountCreate {
+  name: string;
+  description?: string;
+  account_type?: AccountType;
+  max_position_pct?: number;
+}
+
+export interface AccountUpdate {
+  name?: string;
+  description?: string | null;
+  account_type?: AccountType;
+  max_position_pct?: number | null;
+}
+
+export interface ConsolidatedHolding {
+  symbol: string;
+  shares: number;
+  cost_basis: number;
+  current_price?: number;
+  market_value?: number;
+  gain_loss?: number;
+  gain_loss_pct?: number;
+  allocation_pct?: number;
+  accounts: Array<{
+    account_id: number;
+    account_name: string;
+    account_type: AccountType;
+    shares: number;
+    market_value?: number;
+  }>;
+}
+
+export interface ConsolidatedPortfolio {
+  accounts: PortfolioAccount[];
+  holdings: ConsolidatedHolding[];
+  total_value?: number;
+  total_cost?: number;
+  total_gain_loss?: number;
+  total_gain_loss_pct?: number;
+  value_by_account_type: Partial<Record<AccountType, number>>;
+  limit_breaches: RiskLimitBreach[];
+}
+
 export interface HoldingCreate {
   symbol: string;
   shares: number;
   cost_basis: number;
   notes?: string;
+  account_id?: number;
 }
 
 export interface HoldingUpdate {
@@ -392,6 +462,7 @@ export interface HoldingUpdate {
 }
 
 export interface PortfolioImpact {
+  account_id: number;
   portfolio_value: number;
   affected_holdings: Array<{
     symbol: string;